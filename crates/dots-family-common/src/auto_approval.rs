@@ -0,0 +1,365 @@
+// Auto-Approval Rules Module
+//
+// Parent-defined rules that resolve approval requests without waiting for a
+// parent to respond. Rules are evaluated when a child submits a request; deny
+// rules are checked before approve rules so a restriction always wins.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveTime, Utc, Weekday};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::types::RequestType;
+
+/// What happens to a request matched by a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Approve the request and grant the matching exception
+    AutoApprove,
+    /// Deny the request without notifying a parent
+    AutoDeny,
+}
+
+/// A single condition; all conditions of a rule must hold for it to match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Request is of the given kind ("app", "website", "screen_time", ...)
+    RequestKind { kind: String },
+    /// Only on Saturday and Sunday
+    Weekend,
+    /// Only Monday to Friday
+    Weekday,
+    /// At or after the given local time (HH:MM)
+    After { time: String },
+    /// Before the given local time (HH:MM)
+    Before { time: String },
+    /// Screen time extensions or credit spends of at most this many minutes
+    MaxMinutes { minutes: u32 },
+    /// Application belongs to the given category (case-insensitive)
+    Category { category: String },
+    /// Request is for the given application
+    AppId { app_id: String },
+    /// Rule fires at most this many times per day
+    MaxPerDay { count: u32 },
+}
+
+/// A parent-defined rule for resolving approval requests automatically
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AutoApprovalRule {
+    pub id: Uuid,
+    pub profile_id: Option<Uuid>, // None applies to every profile
+    pub name: String,
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Facts about the request and its surroundings that rules are checked against
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+    pub now: DateTime<Local>,
+    /// Category of the requested application, when known
+    pub category: Option<String>,
+    /// How many times each rule has already fired today
+    pub fired_today: HashMap<Uuid, u32>,
+}
+
+/// Outcome of rule evaluation, including a human-readable explanation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleDecision {
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub action: RuleAction,
+    pub explanation: String,
+}
+
+impl AutoApprovalRule {
+    pub fn new(
+        profile_id: Option<Uuid>,
+        name: String,
+        conditions: Vec<RuleCondition>,
+        action: RuleAction,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            profile_id,
+            name,
+            conditions,
+            action,
+            enabled: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Check that the rule is well-formed before it is stored
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule name must not be empty".to_string());
+        }
+        if self.conditions.is_empty() {
+            return Err("Rule must have at least one condition".to_string());
+        }
+        for condition in &self.conditions {
+            match condition {
+                RuleCondition::After { time } | RuleCondition::Before { time } => {
+                    parse_time(time)?;
+                }
+                RuleCondition::MaxPerDay { count: 0 } => {
+                    return Err("max_per_day must be at least 1".to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether every condition of this rule holds for the request
+    pub fn matches(&self, request: &RequestType, context: &RuleContext) -> bool {
+        self.enabled && self.conditions.iter().all(|c| self.condition_holds(c, request, context))
+    }
+
+    fn condition_holds(
+        &self,
+        condition: &RuleCondition,
+        request: &RequestType,
+        context: &RuleContext,
+    ) -> bool {
+        match condition {
+            RuleCondition::RequestKind { kind } => request.kind() == kind,
+            RuleCondition::Weekend => is_weekend(context.now.weekday()),
+            RuleCondition::Weekday => !is_weekend(context.now.weekday()),
+            RuleCondition::After { time } => {
+                parse_time(time).map(|t| context.now.time() >= t).unwrap_or(false)
+            }
+            RuleCondition::Before { time } => {
+                parse_time(time).map(|t| context.now.time() < t).unwrap_or(false)
+            }
+            RuleCondition::MaxMinutes { minutes } => match request {
+                RequestType::ScreenTimeExtension { requested_minutes } => {
+                    requested_minutes <= minutes
+                }
+                RequestType::TimeCreditSpend { minutes: requested } => requested <= minutes,
+                _ => false,
+            },
+            RuleCondition::Category { category } => context
+                .category
+                .as_deref()
+                .map(|c| c.eq_ignore_ascii_case(category))
+                .unwrap_or(false),
+            RuleCondition::AppId { app_id } => match request {
+                RequestType::ApplicationAccess { app_id: requested } => requested == app_id,
+                _ => false,
+            },
+            RuleCondition::MaxPerDay { count } => {
+                context.fired_today.get(&self.id).copied().unwrap_or(0) < *count
+            }
+        }
+    }
+
+    /// Human-readable summary of the rule's conditions
+    pub fn describe(&self) -> String {
+        self.conditions.iter().map(describe_condition).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Explanation recorded when this rule resolves a request
+    pub fn explain(&self) -> String {
+        let verb = match self.action {
+            RuleAction::AutoApprove => "Auto-approved",
+            RuleAction::AutoDeny => "Auto-denied",
+        };
+        format!("{} by rule '{}' ({})", verb, self.name, self.describe())
+    }
+}
+
+/// Evaluate rules against a request, returning the first matching decision.
+/// Deny rules take precedence over approve rules; within each group the
+/// slice order is kept.
+pub fn evaluate_rules(
+    rules: &[AutoApprovalRule],
+    request: &RequestType,
+    context: &RuleContext,
+) -> Option<RuleDecision> {
+    let deny = rules.iter().filter(|r| r.action == RuleAction::AutoDeny);
+    let approve = rules.iter().filter(|r| r.action == RuleAction::AutoApprove);
+
+    deny.chain(approve).find(|rule| rule.matches(request, context)).map(|rule| RuleDecision {
+        rule_id: rule.id,
+        rule_name: rule.name.clone(),
+        action: rule.action,
+        explanation: rule.explain(),
+    })
+}
+
+fn describe_condition(condition: &RuleCondition) -> String {
+    match condition {
+        RuleCondition::RequestKind { kind } => format!("{} requests", kind.replace('_', " ")),
+        RuleCondition::Weekend => "on weekends".to_string(),
+        RuleCondition::Weekday => "on weekdays".to_string(),
+        RuleCondition::After { time } => format!("from {}", time),
+        RuleCondition::Before { time } => format!("before {}", time),
+        RuleCondition::MaxMinutes { minutes } => format!("up to {} minutes", minutes),
+        RuleCondition::Category { category } => format!("category {}", category),
+        RuleCondition::AppId { app_id } => format!("app {}", app_id),
+        RuleCondition::MaxPerDay { count: 1 } => "once per day".to_string(),
+        RuleCondition::MaxPerDay { count } => format!("at most {} times per day", count),
+    }
+}
+
+/// Helper function to check if a weekday is a weekend day
+fn is_weekend(day: Weekday) -> bool {
+    matches!(day, Weekday::Sat | Weekday::Sun)
+}
+
+/// Helper function to parse time string in HH:MM format
+fn parse_time(time_str: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time_str, "%H:%M").map_err(|e| format!("Invalid time format: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // 2026-01-31 is a Saturday, 2026-02-02 a Monday
+    fn saturday_at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 31, hour, 0, 0).unwrap()
+    }
+
+    fn monday_at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 2, 2, hour, 0, 0).unwrap()
+    }
+
+    fn context(now: DateTime<Local>, category: Option<&str>) -> RuleContext {
+        RuleContext { now, category: category.map(String::from), fired_today: HashMap::new() }
+    }
+
+    fn weekend_bonus_rule() -> AutoApprovalRule {
+        AutoApprovalRule::new(
+            None,
+            "Weekend bonus".to_string(),
+            vec![
+                RuleCondition::RequestKind { kind: "screen_time".to_string() },
+                RuleCondition::Weekend,
+                RuleCondition::MaxMinutes { minutes: 15 },
+                RuleCondition::MaxPerDay { count: 1 },
+            ],
+            RuleAction::AutoApprove,
+        )
+    }
+
+    #[test]
+    fn test_weekend_bonus_rule_matches_small_weekend_extension() {
+        let rule = weekend_bonus_rule();
+        let request = RequestType::ScreenTimeExtension { requested_minutes: 15 };
+
+        assert!(rule.matches(&request, &context(saturday_at(10), None)));
+        assert!(!rule.matches(&request, &context(monday_at(10), None)));
+
+        let too_long = RequestType::ScreenTimeExtension { requested_minutes: 30 };
+        assert!(!rule.matches(&too_long, &context(saturday_at(10), None)));
+    }
+
+    #[test]
+    fn test_max_per_day_limits_rule() {
+        let rule = weekend_bonus_rule();
+        let request = RequestType::ScreenTimeExtension { requested_minutes: 10 };
+
+        let mut ctx = context(saturday_at(10), None);
+        ctx.fired_today.insert(rule.id, 1);
+        assert!(!rule.matches(&request, &ctx));
+    }
+
+    #[test]
+    fn test_category_rule_is_case_insensitive() {
+        let rule = AutoApprovalRule::new(
+            None,
+            "Education apps".to_string(),
+            vec![
+                RuleCondition::RequestKind { kind: "app".to_string() },
+                RuleCondition::Category { category: "Education".to_string() },
+            ],
+            RuleAction::AutoApprove,
+        );
+        let request = RequestType::ApplicationAccess { app_id: "gcompris".to_string() };
+
+        assert!(rule.matches(&request, &context(monday_at(16), Some("education"))));
+        assert!(!rule.matches(&request, &context(monday_at(16), Some("Game"))));
+        assert!(!rule.matches(&request, &context(monday_at(16), None)));
+    }
+
+    #[test]
+    fn test_deny_rules_take_precedence() {
+        let approve_apps = AutoApprovalRule::new(
+            None,
+            "Any app".to_string(),
+            vec![RuleCondition::RequestKind { kind: "app".to_string() }],
+            RuleAction::AutoApprove,
+        );
+        let deny_late_games = AutoApprovalRule::new(
+            None,
+            "No games late".to_string(),
+            vec![
+                RuleCondition::Category { category: "Game".to_string() },
+                RuleCondition::After { time: "20:00".to_string() },
+            ],
+            RuleAction::AutoDeny,
+        );
+        let rules = vec![approve_apps, deny_late_games];
+        let request = RequestType::ApplicationAccess { app_id: "supertuxkart".to_string() };
+
+        let late = evaluate_rules(&rules, &request, &context(monday_at(21), Some("Game"))).unwrap();
+        assert_eq!(late.action, RuleAction::AutoDeny);
+        assert_eq!(late.rule_name, "No games late");
+        assert!(late.explanation.starts_with("Auto-denied by rule 'No games late'"));
+
+        let early =
+            evaluate_rules(&rules, &request, &context(monday_at(17), Some("Game"))).unwrap();
+        assert_eq!(early.action, RuleAction::AutoApprove);
+    }
+
+    #[test]
+    fn test_disabled_rule_never_matches() {
+        let mut rule = weekend_bonus_rule();
+        rule.enabled = false;
+        let request = RequestType::ScreenTimeExtension { requested_minutes: 5 };
+
+        assert!(evaluate_rules(&[rule], &request, &context(saturday_at(10), None)).is_none());
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        assert!(weekend_bonus_rule().validate().is_ok());
+
+        let mut bad_time = weekend_bonus_rule();
+        bad_time.conditions.push(RuleCondition::After { time: "25:00".to_string() });
+        assert!(bad_time.validate().is_err());
+
+        let mut no_conditions = weekend_bonus_rule();
+        no_conditions.conditions.clear();
+        assert!(no_conditions.validate().is_err());
+    }
+
+    #[test]
+    fn test_condition_serialization() {
+        let condition = RuleCondition::MaxMinutes { minutes: 15 };
+        let json = serde_json::to_string(&condition).unwrap();
+        assert_eq!(json, r#"{"type":"max_minutes","minutes":15}"#);
+
+        let action: RuleAction = serde_json::from_str(r#""auto_deny""#).unwrap();
+        assert_eq!(action, RuleAction::AutoDeny);
+    }
+
+    #[test]
+    fn test_explanation_describes_conditions() {
+        assert_eq!(
+            weekend_bonus_rule().explain(),
+            "Auto-approved by rule 'Weekend bonus' (screen time requests, on weekends, up to 15 minutes, once per day)"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Guardian Accounts Module
+//
+// Each adult who looks after the children signs in with their own account.
+// The account's role decides what it may do and its profile list limits
+// which children it may do it for, so a babysitter can approve requests
+// for one child without being able to change rules.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// What a guardian account is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardianRole {
+    /// Full control, including rules, time windows and other guardians
+    Admin,
+    /// Approve or deny requests and grant exceptions and time credits
+    Approver,
+    /// Read-only access to requests, reports and settings
+    Viewer,
+}
+
+impl GuardianRole {
+    /// Identifier stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuardianRole::Admin => "admin",
+            GuardianRole::Approver => "approver",
+            GuardianRole::Viewer => "viewer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "admin" => Some(GuardianRole::Admin),
+            "approver" => Some(GuardianRole::Approver),
+            "viewer" => Some(GuardianRole::Viewer),
+            _ => None,
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            GuardianRole::Admin => true,
+            GuardianRole::Approver => permission != Permission::Manage,
+            GuardianRole::Viewer => permission == Permission::View,
+        }
+    }
+}
+
+/// Kind of access a parent operation needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    View,
+    Approve,
+    Manage,
+}
+
+/// A signed-in guardian, as bound to a session token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Guardian {
+    pub id: String,
+    pub name: String,
+    pub role: GuardianRole,
+    /// Profiles this guardian may act on; empty means every profile
+    pub profile_ids: Vec<String>,
+}
+
+impl Guardian {
+    /// Account name used for the shared parent password
+    pub const PRIMARY_NAME: &'static str = "parent";
+
+    /// The account behind the shared parent password from the daemon config
+    pub fn primary() -> Self {
+        Self {
+            id: Self::PRIMARY_NAME.to_string(),
+            name: Self::PRIMARY_NAME.to_string(),
+            role: GuardianRole::Admin,
+            profile_ids: Vec::new(),
+        }
+    }
+
+    /// Prefix of IDs of parents authorized by their system account rather than a password
+    pub const LOCAL_PREFIX: &'static str = "local:";
+
+    /// A parent authorized through polkit or group membership, acting for one call
+    pub fn local(username: &str) -> Self {
+        Self {
+            id: format!("{}{}", Self::LOCAL_PREFIX, username),
+            name: username.to_string(),
+            role: GuardianRole::Admin,
+            profile_ids: Vec::new(),
+        }
+    }
+
+    /// Actor recorded in the audit log for this guardian's actions
+    pub fn actor(&self) -> String {
+        if self.id.starts_with(Self::LOCAL_PREFIX) {
+            format!("user:{}", self.name)
+        } else {
+            format!("guardian:{}", self.name)
+        }
+    }
+
+    /// Whether this guardian covers every profile
+    pub fn is_unscoped(&self) -> bool {
+        self.profile_ids.is_empty()
+    }
+
+    pub fn can_access_profile(&self, profile_id: &str) -> bool {
+        self.is_unscoped() || self.profile_ids.iter().any(|id| id == profile_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_grant_nested_permissions() {
+        assert!(GuardianRole::Admin.allows(Permission::Manage));
+        assert!(GuardianRole::Approver.allows(Permission::Approve));
+        assert!(!GuardianRole::Approver.allows(Permission::Manage));
+        assert!(GuardianRole::Viewer.allows(Permission::View));
+        assert!(!GuardianRole::Viewer.allows(Permission::Approve));
+    }
+
+    #[test]
+    fn test_profile_scope() {
+        let babysitter = Guardian {
+            id: "g1".to_string(),
+            name: "babysitter".to_string(),
+            role: GuardianRole::Approver,
+            profile_ids: vec!["child-1".to_string()],
+        };
+
+        assert!(babysitter.can_access_profile("child-1"));
+        assert!(!babysitter.can_access_profile("child-2"));
+        assert!(Guardian::primary().can_access_profile("child-2"));
+        assert_eq!(babysitter.actor(), "guardian:babysitter");
+        assert_eq!(Guardian::local("mum").actor(), "user:mum");
+    }
+}
@@ -1,9 +1,15 @@
+pub mod auto_approval;
 pub mod config;
 pub mod error;
+pub mod guardian;
 pub mod security;
+pub mod time_bank;
 pub mod time_window;
 pub mod types;
 
+pub use auto_approval::{AutoApprovalRule, RuleAction, RuleCondition, RuleContext, RuleDecision};
 pub use error::{Error, Result};
-pub use time_window::{AccessResult, TimeWindowConfig, TimeWindowEnforcer};
+pub use guardian::{Guardian, GuardianRole, Permission};
+pub use time_bank::{CreditEntryKind, TimeBankSummary, TimeCreditEntry};
+pub use time_window::{AccessResult, EscalationStage, TimeWindowConfig, TimeWindowEnforcer};
 pub use types::*;
@@ -41,18 +41,19 @@ impl PasswordManager {
 #[derive(Debug, Clone)]
 pub struct SessionToken {
     token: String,
+    guardian_id: String,
     created_at: chrono::DateTime<chrono::Utc>,
     expires_at: chrono::DateTime<chrono::Utc>,
 }
 
 impl SessionToken {
-    /// Generate a new secure session token with 15-minute expiry
-    pub fn generate() -> Self {
+    /// Generate a new secure session token with 15-minute expiry for a guardian account
+    pub fn generate(guardian_id: &str) -> Self {
         let token = Self::generate_secure_token();
         let created_at = chrono::Utc::now();
         let expires_at = created_at + chrono::Duration::minutes(15);
 
-        Self { token, created_at, expires_at }
+        Self { token, guardian_id: guardian_id.to_string(), created_at, expires_at }
     }
 
     /// Generate a cryptographically secure random token
@@ -70,6 +71,11 @@ impl SessionToken {
         &self.token
     }
 
+    /// Get the guardian account the token was issued to
+    pub fn guardian_id(&self) -> &str {
+        &self.guardian_id
+    }
+
     /// Get the expiration time
     pub fn expires_at(&self) -> chrono::DateTime<chrono::Utc> {
         self.expires_at
@@ -182,6 +188,13 @@ impl RateLimiter {
     pub fn record_attempt(&mut self, successful: bool, ip_address: Option<String>) {
         self.attempts.push(AuthAttempt { timestamp: chrono::Utc::now(), successful, ip_address });
     }
+
+    /// Whether no attempts remain within the window, so the limiter can be dropped
+    pub fn is_idle(&mut self) -> bool {
+        let cutoff = chrono::Utc::now() - chrono::Duration::minutes(self.window_minutes);
+        self.attempts.retain(|attempt| attempt.timestamp > cutoff);
+        self.attempts.is_empty()
+    }
 }
 
 impl Default for RateLimiter {
@@ -216,8 +229,8 @@ mod tests {
 
     #[test]
     fn test_session_token_generation() {
-        let token1 = SessionToken::generate();
-        let token2 = SessionToken::generate();
+        let token1 = SessionToken::generate("parent");
+        let token2 = SessionToken::generate("parent");
 
         // Tokens should be different
         assert_ne!(token1.token(), token2.token());
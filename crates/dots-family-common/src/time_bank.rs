@@ -0,0 +1,214 @@
+// Screen-Time Bank Module
+//
+// A per-profile ledger of time credits. Parents grant earned minutes as
+// rewards, unused daily minutes can roll over (up to a cap, with an expiry),
+// and children spend credits to extend today's allowance. Every change is a
+// ledger entry so the full history can be shown to parents.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of ledger entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditEntryKind {
+    /// Minutes granted by a parent as a reward
+    Earned,
+    /// Unused minutes carried over from a previous day
+    RolledOver,
+    /// Minutes the child moved into today's allowance
+    Spent,
+    /// Rolled-over minutes that lapsed before being spent
+    Expired,
+}
+
+impl CreditEntryKind {
+    /// Identifier stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreditEntryKind::Earned => "earned",
+            CreditEntryKind::RolledOver => "rolled_over",
+            CreditEntryKind::Spent => "spent",
+            CreditEntryKind::Expired => "expired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "earned" => Some(CreditEntryKind::Earned),
+            "rolled_over" => Some(CreditEntryKind::RolledOver),
+            "spent" => Some(CreditEntryKind::Spent),
+            "expired" => Some(CreditEntryKind::Expired),
+            _ => None,
+        }
+    }
+
+    /// Whether entries of this kind add credit to the bank
+    pub fn is_credit(&self) -> bool {
+        matches!(self, CreditEntryKind::Earned | CreditEntryKind::RolledOver)
+    }
+}
+
+/// A single ledger entry; credits are positive, debits negative
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeCreditEntry {
+    pub id: Uuid,
+    pub profile_id: Uuid,
+    pub kind: CreditEntryKind,
+    pub minutes: i32,
+    /// Unspent part of a credit entry (always 0 for debits)
+    pub remaining_minutes: u32,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: String, // 'parent', 'system', 'child'
+}
+
+impl TimeCreditEntry {
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// Totals of a profile's ledger over a period, used in reports
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeBankSummary {
+    /// Minutes available to spend right now
+    pub balance_minutes: u32,
+    pub earned_minutes: u32,
+    pub rolled_over_minutes: u32,
+    pub spent_minutes: u32,
+    pub expired_minutes: u32,
+}
+
+impl TimeBankSummary {
+    /// Build a summary from the entries of a period and the current balance
+    pub fn from_entries(entries: &[TimeCreditEntry], balance_minutes: u32) -> Self {
+        let mut summary = Self { balance_minutes, ..Default::default() };
+        for entry in entries {
+            let minutes = entry.minutes.unsigned_abs();
+            match entry.kind {
+                CreditEntryKind::Earned => summary.earned_minutes += minutes,
+                CreditEntryKind::RolledOver => summary.rolled_over_minutes += minutes,
+                CreditEntryKind::Spent => summary.spent_minutes += minutes,
+                CreditEntryKind::Expired => summary.expired_minutes += minutes,
+            }
+        }
+        summary
+    }
+}
+
+/// Minutes to roll over, keeping outstanding rolled-over credit within the cap
+pub fn rollover_amount(unused_minutes: u32, outstanding_rolled_over: u32, cap_minutes: u32) -> u32 {
+    unused_minutes.min(cap_minutes.saturating_sub(outstanding_rolled_over))
+}
+
+/// Decide how much to draw from each credit entry to spend `minutes`.
+///
+/// Credits expiring soonest are used first so rolled-over time is not wasted;
+/// earned credits without an expiry are used last. Returns `None` when the
+/// available credit is insufficient.
+pub fn plan_spend(
+    credits: &[TimeCreditEntry],
+    minutes: u32,
+    now: DateTime<Utc>,
+) -> Option<Vec<(Uuid, u32)>> {
+    let mut available: Vec<&TimeCreditEntry> = credits
+        .iter()
+        .filter(|c| c.kind.is_credit() && c.remaining_minutes > 0 && !c.is_expired_at(now))
+        .collect();
+    available.sort_by_key(|c| (c.expires_at.is_none(), c.expires_at, c.created_at));
+
+    let mut needed = minutes;
+    let mut plan = Vec::new();
+    for credit in available {
+        if needed == 0 {
+            break;
+        }
+        let take = credit.remaining_minutes.min(needed);
+        plan.push((credit.id, take));
+        needed -= take;
+    }
+
+    (needed == 0).then_some(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn credit(
+        kind: CreditEntryKind,
+        minutes: u32,
+        expires_in_days: Option<i64>,
+    ) -> TimeCreditEntry {
+        let now = Utc::now();
+        TimeCreditEntry {
+            id: Uuid::new_v4(),
+            profile_id: Uuid::nil(),
+            kind,
+            minutes: minutes as i32,
+            remaining_minutes: minutes,
+            reason: None,
+            created_at: now,
+            expires_at: expires_in_days.map(|d| now + Duration::days(d)),
+            created_by: "system".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rollover_amount_respects_cap() {
+        assert_eq!(rollover_amount(30, 0, 60), 30);
+        assert_eq!(rollover_amount(30, 45, 60), 15);
+        assert_eq!(rollover_amount(30, 90, 60), 0);
+    }
+
+    #[test]
+    fn test_plan_spend_uses_soonest_expiring_first() {
+        let earned = credit(CreditEntryKind::Earned, 30, None);
+        let late = credit(CreditEntryKind::RolledOver, 20, Some(5));
+        let soon = credit(CreditEntryKind::RolledOver, 10, Some(1));
+        let credits = vec![earned.clone(), late.clone(), soon.clone()];
+
+        let plan = plan_spend(&credits, 25, Utc::now()).unwrap();
+        assert_eq!(plan, vec![(soon.id, 10), (late.id, 15)]);
+
+        let plan = plan_spend(&credits, 60, Utc::now()).unwrap();
+        assert_eq!(plan, vec![(soon.id, 10), (late.id, 20), (earned.id, 30)]);
+    }
+
+    #[test]
+    fn test_plan_spend_rejects_insufficient_or_expired_credit() {
+        let expired = credit(CreditEntryKind::RolledOver, 30, Some(-1));
+        let earned = credit(CreditEntryKind::Earned, 10, None);
+
+        assert!(plan_spend(&[expired.clone(), earned.clone()], 20, Utc::now()).is_none());
+        assert_eq!(
+            plan_spend(&[expired, earned.clone()], 10, Utc::now()),
+            Some(vec![(earned.id, 10)])
+        );
+    }
+
+    #[test]
+    fn test_summary_from_entries() {
+        let mut spent = credit(CreditEntryKind::Spent, 0, None);
+        spent.minutes = -15;
+        spent.remaining_minutes = 0;
+        let entries = vec![
+            credit(CreditEntryKind::Earned, 30, None),
+            credit(CreditEntryKind::RolledOver, 20, Some(7)),
+            spent,
+        ];
+
+        let summary = TimeBankSummary::from_entries(&entries, 35);
+        assert_eq!(summary.balance_minutes, 35);
+        assert_eq!(summary.earned_minutes, 30);
+        assert_eq!(summary.rolled_over_minutes, 20);
+        assert_eq!(summary.spent_minutes, 15);
+        assert_eq!(summary.expired_minutes, 0);
+    }
+}
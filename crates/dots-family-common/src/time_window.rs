@@ -3,7 +3,7 @@
 // This module implements the logic for enforcing time-based access controls
 // based on weekday, weekend, and holiday schedules.
 
-use chrono::{DateTime, Datelike, Local, NaiveTime, Timelike, Weekday};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Timelike, Weekday};
 
 use crate::types::TimeWindow;
 
@@ -15,6 +15,10 @@ pub struct TimeWindowConfig {
     pub holiday_windows: Vec<TimeWindow>,
     pub grace_period_minutes: u32,
     pub warning_minutes: u32,
+    /// Minutes before the end at which the countdown overlay is shown
+    pub countdown_minutes: u32,
+    /// Minutes between closing applications and locking the session
+    pub close_apps_minutes: u32,
 }
 
 impl Default for TimeWindowConfig {
@@ -25,6 +29,85 @@ impl Default for TimeWindowConfig {
             holiday_windows: Vec::new(),
             grace_period_minutes: 2,
             warning_minutes: 5,
+            countdown_minutes: 1,
+            close_apps_minutes: 1,
+        }
+    }
+}
+
+impl TimeWindowConfig {
+    /// Work out the escalation stage from the time left until access ends.
+    ///
+    /// `remaining` goes negative once access has ended. `extra_grace_minutes`
+    /// lengthens the grace period, e.g. after a "finishing up" request.
+    pub fn escalation_stage(
+        &self,
+        remaining: Duration,
+        extra_grace_minutes: u32,
+    ) -> EscalationStage {
+        let seconds = remaining.num_seconds();
+
+        if seconds > 0 {
+            return if seconds <= minutes_to_seconds(self.countdown_minutes) {
+                EscalationStage::Countdown { seconds_remaining: seconds }
+            } else if seconds <= minutes_to_seconds(self.warning_minutes) {
+                EscalationStage::Warning { seconds_remaining: seconds }
+            } else {
+                EscalationStage::Normal
+            };
+        }
+
+        let overrun = -seconds;
+        let grace = minutes_to_seconds(self.grace_period_minutes + extra_grace_minutes);
+        let lock_at = grace + minutes_to_seconds(self.close_apps_minutes);
+
+        if overrun < grace {
+            EscalationStage::GracePeriod { seconds_remaining: grace - overrun }
+        } else if overrun < lock_at {
+            EscalationStage::CloseApps { seconds_remaining: lock_at - overrun }
+        } else {
+            EscalationStage::Lock
+        }
+    }
+}
+
+/// Stage of the escalation ladder as allowed time runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscalationStage {
+    /// Plenty of time left
+    Normal,
+    /// Time is nearly up; the child is notified
+    Warning { seconds_remaining: i64 },
+    /// Final countdown before time is up, shown as an overlay
+    Countdown { seconds_remaining: i64 },
+    /// Time is up, but the child can still save their work
+    GracePeriod { seconds_remaining: i64 },
+    /// Applications are closed; the session locks when this runs out
+    CloseApps { seconds_remaining: i64 },
+    /// The session is locked
+    Lock,
+}
+
+impl EscalationStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscalationStage::Normal => "normal",
+            EscalationStage::Warning { .. } => "warning",
+            EscalationStage::Countdown { .. } => "countdown",
+            EscalationStage::GracePeriod { .. } => "grace_period",
+            EscalationStage::CloseApps { .. } => "close_apps",
+            EscalationStage::Lock => "lock",
+        }
+    }
+
+    /// Seconds until the next stage begins (0 for `Normal` and `Lock`)
+    pub fn seconds_remaining(&self) -> i64 {
+        match self {
+            EscalationStage::Warning { seconds_remaining }
+            | EscalationStage::Countdown { seconds_remaining }
+            | EscalationStage::GracePeriod { seconds_remaining }
+            | EscalationStage::CloseApps { seconds_remaining } => *seconds_remaining,
+            EscalationStage::Normal | EscalationStage::Lock => 0,
         }
     }
 }
@@ -56,17 +139,8 @@ impl TimeWindowEnforcer {
 
     /// Check if access is allowed at the given time
     pub fn check_access(&self, current_time: DateTime<Local>) -> AccessResult {
-        let weekday = current_time.weekday();
         let time_str = current_time.format("%H:%M").to_string();
-
-        // Determine which set of windows to use
-        let windows = if self.is_holiday {
-            &self.config.holiday_windows
-        } else if is_weekend(weekday) {
-            &self.config.weekend_windows
-        } else {
-            &self.config.weekday_windows
-        };
+        let windows = self.windows_for(current_time);
 
         // Check if we're in any allowed window
         if self.is_in_window(&time_str, windows) {
@@ -80,6 +154,17 @@ impl TimeWindowEnforcer {
         AccessResult::Denied { reason, next_window }
     }
 
+    /// Windows that apply on the day of the given time
+    fn windows_for(&self, current_time: DateTime<Local>) -> &[TimeWindow] {
+        if self.is_holiday {
+            &self.config.holiday_windows
+        } else if is_weekend(current_time.weekday()) {
+            &self.config.weekend_windows
+        } else {
+            &self.config.weekday_windows
+        }
+    }
+
     /// Check if the given time is within any of the windows
     fn is_in_window(&self, time_str: &str, windows: &[TimeWindow]) -> bool {
         let current_time = match parse_time(time_str) {
@@ -157,18 +242,8 @@ impl TimeWindowEnforcer {
 
     /// Check if a warning should be displayed (window closing soon)
     pub fn should_warn(&self, current_time: DateTime<Local>) -> bool {
-        let weekday = current_time.weekday();
         let time_str = current_time.format("%H:%M").to_string();
-
-        let windows = if self.is_holiday {
-            &self.config.holiday_windows
-        } else if is_weekend(weekday) {
-            &self.config.weekend_windows
-        } else {
-            &self.config.weekday_windows
-        };
-
-        self.is_warning_time(&time_str, windows)
+        self.is_warning_time(&time_str, self.windows_for(current_time))
     }
 
     /// Check if we're in warning period (N minutes before window end)
@@ -178,22 +253,11 @@ impl TimeWindowEnforcer {
             Err(_) => return false,
         };
 
-        for window in windows {
-            let end = match parse_time(&window.end) {
-                Ok(t) => t,
-                Err(_) => continue,
-            };
-
-            // Check if we're within the warning period before window end
-            let warning_duration = chrono::Duration::minutes(self.config.warning_minutes as i64);
-            let warning_start = end - warning_duration;
-
-            if current_time >= warning_start && current_time < end {
-                return true;
-            }
-        }
-
-        false
+        // Back-to-back windows do not end at their shared boundary
+        let warning_duration = chrono::Duration::minutes(self.config.warning_minutes as i64);
+        merged_windows(windows).into_iter().any(|(start, end)| {
+            current_time >= start && current_time < end && current_time >= end - warning_duration
+        })
     }
 
     /// Get warning message with minutes remaining
@@ -205,17 +269,55 @@ impl TimeWindowEnforcer {
         Some(format!("{} minutes remaining in this window", self.config.warning_minutes))
     }
 
+    /// Time left in the current window, or zero when outside every window
+    pub fn time_remaining(&self, current_time: DateTime<Local>) -> Duration {
+        let now = current_time.time();
+
+        merged_windows(self.windows_for(current_time))
+            .into_iter()
+            .find(|(start, end)| now >= *start && now < *end)
+            .map(|(_, end)| end - now)
+            .unwrap_or_else(Duration::zero)
+    }
+
+    pub fn config(&self) -> &TimeWindowConfig {
+        &self.config
+    }
+
     /// Check if session should be locked (outside window or at window end)
     pub fn should_lock(&self, current_time: DateTime<Local>) -> bool {
         matches!(self.check_access(current_time), AccessResult::Denied { .. })
     }
 }
 
+/// Windows as sorted time ranges, with overlapping or touching ones joined
+fn merged_windows(windows: &[TimeWindow]) -> Vec<(NaiveTime, NaiveTime)> {
+    let mut ranges: Vec<(NaiveTime, NaiveTime)> = windows
+        .iter()
+        .filter_map(|window| Some((parse_time(&window.start).ok()?, parse_time(&window.end).ok()?)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    ranges.sort();
+
+    let mut merged: Vec<(NaiveTime, NaiveTime)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
 /// Helper function to check if a weekday is a weekend day
 fn is_weekend(day: Weekday) -> bool {
     matches!(day, Weekday::Sat | Weekday::Sun)
 }
 
+fn minutes_to_seconds(minutes: u32) -> i64 {
+    i64::from(minutes) * 60
+}
+
 /// Helper function to parse time string in HH:MM format
 fn parse_time(time_str: &str) -> Result<NaiveTime, String> {
     NaiveTime::parse_from_str(time_str, "%H:%M").map_err(|e| format!("Invalid time format: {}", e))
@@ -384,4 +486,64 @@ mod tests {
         assert!(!enforcer.is_warning_time("18:54", &enforcer.config.weekday_windows));
         assert!(!enforcer.is_warning_time("19:00", &enforcer.config.weekday_windows));
     }
+
+    #[test]
+    fn test_adjacent_windows_do_not_end_at_their_boundary() {
+        let enforcer = TimeWindowEnforcer::new(TimeWindowConfig {
+            weekday_windows: make_windows(&[("17:00", "19:00"), ("15:00", "17:00")]),
+            warning_minutes: 5,
+            ..Default::default()
+        });
+
+        assert!(!enforcer.is_warning_time("16:57", &enforcer.config.weekday_windows));
+        assert!(enforcer.is_warning_time("18:57", &enforcer.config.weekday_windows));
+
+        let monday = chrono::NaiveDate::from_ymd_opt(2026, 1, 19).unwrap();
+        let at = |h, m| monday.and_hms_opt(h, m, 0).unwrap().and_local_timezone(Local).unwrap();
+        assert_eq!(enforcer.time_remaining(at(16, 30)), Duration::minutes(150));
+    }
+
+    #[test]
+    fn test_time_remaining_in_and_out_of_window() {
+        let enforcer = TimeWindowEnforcer::new(TimeWindowConfig {
+            weekday_windows: make_windows(&[("15:00", "19:00")]),
+            ..Default::default()
+        });
+
+        let monday = chrono::NaiveDate::from_ymd_opt(2026, 1, 19).unwrap();
+        let at = |h, m| monday.and_hms_opt(h, m, 0).unwrap().and_local_timezone(Local).unwrap();
+
+        assert_eq!(enforcer.time_remaining(at(18, 30)), Duration::minutes(30));
+        assert_eq!(enforcer.time_remaining(at(19, 30)), Duration::zero());
+    }
+
+    #[test]
+    fn test_escalation_ladder_stages() {
+        let config = TimeWindowConfig {
+            warning_minutes: 5,
+            countdown_minutes: 1,
+            grace_period_minutes: 2,
+            close_apps_minutes: 1,
+            ..Default::default()
+        };
+        let stage = |seconds| config.escalation_stage(Duration::seconds(seconds), 0);
+
+        assert_eq!(stage(600), EscalationStage::Normal);
+        assert_eq!(stage(300), EscalationStage::Warning { seconds_remaining: 300 });
+        assert_eq!(stage(45), EscalationStage::Countdown { seconds_remaining: 45 });
+        assert_eq!(stage(0), EscalationStage::GracePeriod { seconds_remaining: 120 });
+        assert_eq!(stage(-90), EscalationStage::GracePeriod { seconds_remaining: 30 });
+        assert_eq!(stage(-120), EscalationStage::CloseApps { seconds_remaining: 60 });
+        assert_eq!(stage(-180), EscalationStage::Lock);
+    }
+
+    #[test]
+    fn test_extra_grace_delays_closing_apps() {
+        let config = TimeWindowConfig::default();
+
+        assert_eq!(
+            config.escalation_stage(Duration::minutes(-5), 10),
+            EscalationStage::GracePeriod { seconds_remaining: 7 * 60 }
+        );
+    }
 }
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
 use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Age group classifications for children with pre-configured defaults.
 /// Each age group has appropriate screen time limits and restrictions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum AgeGroup {
     /// Ages 5-7: Early elementary with strict restrictions
     #[serde(rename = "5-7")]
@@ -23,7 +24,7 @@ pub enum AgeGroup {
 ///
 /// This is the primary type used throughout the system to manage
 /// individual child accounts and their associated policies.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Profile {
     /// Unique identifier for the profile
     pub id: Uuid,
@@ -62,20 +63,24 @@ impl Default for Profile {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ProfileConfig {
     pub screen_time: ScreenTimeConfig,
     pub applications: ApplicationConfig,
     pub web_filtering: WebFilteringConfig,
     pub terminal_filtering: TerminalFilteringConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ScreenTimeConfig {
     pub daily_limit_minutes: u32,
     pub weekend_bonus_minutes: u32,
     pub exempt_categories: Vec<String>,
     pub windows: TimeWindows,
+    #[serde(default)]
+    pub enforcement: EnforcementLadder,
 }
 
 impl Default for ScreenTimeConfig {
@@ -85,11 +90,40 @@ impl Default for ScreenTimeConfig {
             weekend_bonus_minutes: 0,
             exempt_categories: Vec::new(),
             windows: TimeWindows { weekday: Vec::new(), weekend: Vec::new(), holiday: Vec::new() },
+            enforcement: EnforcementLadder::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// How enforcement escalates when screen time runs out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct EnforcementLadder {
+    /// Minutes before the end to notify the child
+    pub warning_minutes: u32,
+    /// Minutes before the end to show the countdown overlay
+    pub countdown_minutes: u32,
+    /// Minutes after the end to save work before applications are closed
+    pub grace_period_minutes: u32,
+    /// Minutes between closing applications and locking the session
+    pub close_apps_minutes: u32,
+    /// Extra grace minutes for the once-per-day "finishing up" request
+    pub finishing_up_minutes: u32,
+}
+
+impl Default for EnforcementLadder {
+    fn default() -> Self {
+        Self {
+            warning_minutes: 5,
+            countdown_minutes: 1,
+            grace_period_minutes: 2,
+            close_apps_minutes: 1,
+            finishing_up_minutes: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TimeWindows {
     pub weekday: Vec<TimeWindow>,
     pub weekend: Vec<TimeWindow>,
@@ -97,13 +131,13 @@ pub struct TimeWindows {
     pub holiday: Vec<TimeWindow>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TimeWindow {
     pub start: String,
     pub end: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct ApplicationConfig {
     pub mode: ApplicationMode,
     pub allowed: Vec<String>,
@@ -122,14 +156,14 @@ impl Default for ApplicationConfig {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ApplicationMode {
     Allowlist,
     Blocklist,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct WebFilteringConfig {
     pub enabled: bool,
     pub safe_search: bool,
@@ -150,7 +184,13 @@ impl Default for WebFilteringConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+pub struct NetworkConfig {
+    /// TCP and UDP ports the child can't connect to, e.g. 25 for mail
+    pub blocked_ports: Vec<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TerminalFilteringConfig {
     pub enabled: bool,
     pub block_threshold: String,
@@ -175,7 +215,7 @@ impl Default for TerminalFilteringConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Activity {
     pub id: Uuid,
     pub profile_id: Uuid,
@@ -186,20 +226,29 @@ pub struct Activity {
     pub duration_seconds: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ActivityType {
     ApplicationUsage,
-    WebBrowsing { url: String },
-    TerminalCommand { command: String },
-    PolicyViolation { reason: String },
+    WebBrowsing {
+        url: String,
+    },
+    TerminalCommand {
+        command: String,
+    },
+    PolicyViolation {
+        reason: String,
+    },
+    /// Nothing had focus, e.g. while the screen was locked. Accounts for the
+    /// time without counting as use.
+    Unfocused,
 }
 
 // ============================================================================
 // Exception Management System
 // ============================================================================
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum ExceptionType {
     /// Temporarily allows a blocked application
     ApplicationOverride { app_id: String },
@@ -215,10 +264,10 @@ pub enum ExceptionType {
     CustomOverride { description: String, policy_changes: HashMap<String, String> },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum ExceptionDuration {
-    /// Exception expires after specified duration
-    Duration(Duration),
+    /// Exception expires after specified duration, as seconds and nanoseconds
+    Duration(#[schemars(with = "(i64, i32)")] Duration),
     /// Exception expires at specific time
     UntilTime(DateTime<Utc>),
     /// Exception expires after current session ends
@@ -229,7 +278,22 @@ pub enum ExceptionDuration {
     Manual,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl ExceptionDuration {
+    /// When an exception starting at `now` expires, `None` if it has no fixed end
+    pub fn expires_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            ExceptionDuration::Duration(d) => Some(now + *d),
+            ExceptionDuration::UntilTime(time) => Some(*time),
+            ExceptionDuration::UntilEndOfDay => {
+                let end_of_day = now.date_naive().and_hms_opt(23, 59, 59).unwrap();
+                Some(DateTime::from_naive_utc_and_offset(end_of_day, Utc))
+            }
+            ExceptionDuration::UntilSessionEnd | ExceptionDuration::Manual => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum ExceptionStatus {
     /// Exception is active and being enforced
     Active,
@@ -241,7 +305,7 @@ pub enum ExceptionStatus {
     Scheduled,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Exception {
     pub id: Uuid,
     pub profile_id: Uuid,
@@ -265,15 +329,7 @@ impl Exception {
         created_by: String,
     ) -> Self {
         let now = Utc::now();
-        let expires_at = match &duration {
-            ExceptionDuration::Duration(d) => Some(now + *d),
-            ExceptionDuration::UntilTime(time) => Some(*time),
-            ExceptionDuration::UntilEndOfDay => {
-                let end_of_day = now.date_naive().and_hms_opt(23, 59, 59).unwrap();
-                Some(DateTime::from_naive_utc_and_offset(end_of_day, Utc))
-            }
-            ExceptionDuration::UntilSessionEnd | ExceptionDuration::Manual => None,
-        };
+        let expires_at = duration.expires_at(now);
 
         Self {
             id: Uuid::new_v4(),
@@ -326,9 +382,24 @@ pub enum RequestType {
     TerminalCommand { command: String },
     /// Custom request with free-form description
     Custom { description: String },
+    /// Child spends banked time credits to extend today's allowance
+    TimeCreditSpend { minutes: u32 },
 }
 
 impl RequestType {
+    /// Short identifier used for this request type in the database and in rules
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RequestType::ApplicationAccess { .. } => "app",
+            RequestType::WebsiteAccess { .. } => "website",
+            RequestType::ScreenTimeExtension { .. } => "screen_time",
+            RequestType::TimeExtension { .. } => "time_extension",
+            RequestType::TerminalCommand { .. } => "command",
+            RequestType::Custom { .. } => "custom",
+            RequestType::TimeCreditSpend { .. } => "time_credit",
+        }
+    }
+
     /// Convert a RequestType to the corresponding ExceptionType
     /// This is used when a parent approves a request to automatically create an exception
     pub fn to_exception_type(&self) -> ExceptionType {
@@ -352,6 +423,9 @@ impl RequestType {
                 description: description.clone(),
                 policy_changes: HashMap::new(),
             },
+            RequestType::TimeCreditSpend { minutes } => {
+                ExceptionType::ScreenTimeExtension { extra_minutes: *minutes }
+            }
         }
     }
 
@@ -374,6 +448,8 @@ impl RequestType {
             RequestType::TerminalCommand { .. } => ExceptionDuration::UntilSessionEnd,
             // Custom: Manual (requires explicit parent revocation)
             RequestType::Custom { .. } => ExceptionDuration::Manual,
+            // Spent credits: the extra time is for today
+            RequestType::TimeCreditSpend { .. } => ExceptionDuration::UntilEndOfDay,
         }
     }
 }
@@ -671,6 +747,7 @@ mod tests {
                     weekend_bonus_minutes: 60,
                     exempt_categories: vec!["education".to_string()],
                     windows: TimeWindows { weekday: vec![], weekend: vec![], holiday: vec![] },
+                    enforcement: Default::default(),
                 },
                 applications: ApplicationConfig {
                     mode: ApplicationMode::Allowlist,
@@ -686,6 +763,7 @@ mod tests {
                     blocked_domains: vec![],
                 },
                 terminal_filtering: TerminalFilteringConfig::default(),
+                network: Default::default(),
             },
             active: true,
         };
@@ -720,6 +798,7 @@ mod tests {
                         }],
                         holiday: vec![],
                     },
+                    enforcement: Default::default(),
                 },
                 applications: ApplicationConfig {
                     mode: ApplicationMode::Blocklist,
@@ -735,6 +814,7 @@ mod tests {
                     blocked_domains: vec!["reddit.com".to_string()],
                 },
                 terminal_filtering: TerminalFilteringConfig::default(),
+                network: Default::default(),
             },
             active: true,
         };
@@ -866,6 +946,7 @@ mod tests {
                 weekend: vec![],
                 holiday: vec![],
             },
+            enforcement: Default::default(),
         };
 
         let json = serde_json::to_string(&config).unwrap();
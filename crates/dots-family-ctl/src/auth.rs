@@ -1,7 +1,17 @@
+use std::sync::OnceLock;
+
 use anyhow::{Context, Result};
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::{daemon::FamilyDaemonProxy, error::Error as DaemonError};
 use zbus::Connection;
 
+/// Guardian account chosen with `--guardian`; the shared parent password is used otherwise
+static GUARDIAN: OnceLock<String> = OnceLock::new();
+
+/// Sign in as a guardian account for commands that require authentication
+pub fn set_guardian(name: String) {
+    let _ = GUARDIAN.set(name);
+}
+
 /// Authentication helper functions for CLI commands that require parent authorization
 pub struct AuthHelper {
     proxy: FamilyDaemonProxy<'static>,
@@ -21,27 +31,30 @@ impl AuthHelper {
     /// Prompt for password and authenticate with daemon
     /// Returns session token on success
     pub async fn authenticate(&self) -> Result<String> {
+        let guardian = GUARDIAN.get();
+
         // Prompt for password securely
-        let password = rpassword::prompt_password("Enter parent password: ")
-            .context("Failed to read password")?;
+        let prompt = match guardian {
+            Some(name) => format!("Enter password for {}: ", name),
+            None => "Enter parent password: ".to_string(),
+        };
+        let password = rpassword::prompt_password(prompt).context("Failed to read password")?;
 
         if password.trim().is_empty() {
             anyhow::bail!("Password cannot be empty");
         }
 
         // Authenticate with daemon
-        let token = self
-            .proxy
-            .authenticate_parent(&password)
-            .await
-            .context("Failed to authenticate with daemon")?;
-
-        // Check if authentication failed
-        if let Some(error_msg) = token.strip_prefix("error:") {
-            anyhow::bail!("Authentication failed: {}", error_msg);
+        let reply = match guardian {
+            Some(name) => self.proxy.authenticate_guardian(name, &password).await,
+            None => self.proxy.authenticate_parent(&password).await,
+        };
+
+        match reply {
+            Ok(token) => Ok(token),
+            Err(DaemonError::NotAuthorized(msg)) => anyhow::bail!("Authentication failed: {}", msg),
+            Err(e) => Err(e).context("Failed to authenticate with daemon"),
         }
-
-        Ok(token)
     }
 
     /// Validate an existing session token
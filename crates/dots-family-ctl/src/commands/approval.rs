@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, ApprovalRequestList},
+};
 use zbus::Connection;
 
 use crate::auth;
@@ -46,33 +49,23 @@ pub async fn list() -> Result<()> {
                 .await
                 .context("Failed to list pending requests")?;
 
-            let requests: serde_json::Value =
-                serde_json::from_str(&response).context("Failed to parse response")?;
+            let list: ApprovalRequestList =
+                payload::decode(&response).context("Failed to parse response")?;
 
-            if let Some(requests_array) = requests.as_array() {
-                if requests_array.is_empty() {
-                    println!("✅ No pending approval requests");
-                    return Ok(());
-                }
+            if list.requests.is_empty() {
+                println!("✅ No pending approval requests");
+                return Ok(());
+            }
 
-                println!("📋 Pending Approval Requests:\n");
-
-                for request in requests_array {
-                    let id = request["id"].as_str().unwrap_or("unknown");
-                    let profile_name = request["profile_name"].as_str().unwrap_or("unknown");
-                    let request_type = request["request_type"].as_str().unwrap_or("unknown");
-                    let details = request["details"].as_str().unwrap_or("");
-                    let created_at = request["created_at"].as_str().unwrap_or("unknown");
-
-                    println!("🔔 Request ID: {}", id);
-                    println!("   Profile: {}", profile_name);
-                    println!("   Type: {}", request_type);
-                    println!("   Details: {}", details);
-                    println!("   Created: {}", created_at);
-                    println!();
-                }
-            } else {
-                println!("⚠️  Unexpected response format");
+            println!("📋 Pending Approval Requests:\n");
+
+            for request in list.requests {
+                println!("🔔 Request ID: {}", request.id);
+                println!("   Profile: {}", request.profile_id);
+                println!("   Type: {}", request.request_type);
+                println!("   Details: {}", serde_json::to_string(&request.details)?);
+                println!("   Created: {}", request.requested_at.format("%Y-%m-%d %H:%M:%S"));
+                println!();
             }
 
             Ok(())
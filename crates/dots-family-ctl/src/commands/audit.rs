@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, AuditReport},
+};
+use zbus::Connection;
+
+use crate::auth;
+
+#[derive(Subcommand)]
+pub enum AuditAction {
+    /// Check the audit log for deleted or altered entries
+    Verify,
+}
+
+pub async fn verify() -> Result<()> {
+    auth::require_auth(|token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response =
+                proxy.verify_audit_log(&token).await.context("Failed to verify audit log")?;
+
+            let report: AuditReport =
+                payload::decode(&response).context("Failed to parse response")?;
+
+            println!(
+                "🔍 Checked {} audit entries and {} seals",
+                report.entries_checked, report.seals_checked
+            );
+            if report.legacy_entries > 0 {
+                println!(
+                    "   {} entries predate the hash chain and cannot be verified",
+                    report.legacy_entries
+                );
+            }
+
+            let breaks = report.breaks;
+            if breaks.is_empty() {
+                println!("✅ Audit log is intact");
+                match (report.last_sealed_entry_id, report.head_entry_id) {
+                    (Some(sealed), Some(head)) if head > sealed => {
+                        println!("   Entries {} to {} are not sealed yet", sealed + 1, head)
+                    }
+                    (None, Some(_)) => println!("   No entries have been sealed yet"),
+                    _ => {}
+                }
+                return Ok(());
+            }
+
+            println!("❌ Audit log chain is broken at entry {}", breaks[0].entry_id);
+            for entry in &breaks {
+                println!("   - {}", entry.reason);
+            }
+
+            anyhow::bail!("Audit log verification failed")
+        })
+    })
+    .await
+}
@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    events::{Event, EventFilter},
+    payload::{self, EventList},
+};
+use futures::StreamExt;
+use uuid::Uuid;
+use zbus::Connection;
+
+use crate::auth;
+
+/// Print recent events, then with `follow` keep printing new ones as they happen
+pub async fn watch(kinds: Vec<String>, profile: Option<Uuid>, follow: bool) -> Result<()> {
+    auth::require_auth(move |token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let filter = EventFilter { kinds, profile_id: profile };
+
+            // Subscribe before catching up so nothing falls in between
+            let mut live = if follow {
+                Some(proxy.receive_events(filter.clone(), &token).await?)
+            } else {
+                None
+            };
+
+            let recent: EventList =
+                payload::decode(&proxy.recent_events(&payload::encode(&filter)?, &token).await?)?;
+            for event in &recent.events {
+                print_event(event);
+            }
+
+            if let Some(live) = live.as_mut() {
+                while let Some(event) = live.next().await {
+                    if !recent.events.contains(&event) {
+                        print_event(&event);
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+fn print_event(event: &Event) {
+    println!("{:<26} {}", event.kind(), describe(event));
+}
+
+fn describe(event: &Event) -> String {
+    let profile = event.profile_id().map(|id| format!(" [{}]", id)).unwrap_or_default();
+    let details = match event {
+        Event::PolicyUpdated { .. } => "policy reloaded".to_string(),
+        Event::TimeLimitWarning { minutes_remaining, .. } => {
+            format!("{} minutes remaining", minutes_remaining)
+        }
+        Event::TimeLimitReached { .. } => "daily limit reached".to_string(),
+        Event::ApplicationBlocked { application, reason, .. } => {
+            format!("{} blocked: {}", application, reason)
+        }
+        Event::WebsiteBlocked { url, reason, .. } => format!("{} blocked: {}", url, reason),
+        Event::AppFocused { app_id, .. } => app_id.clone(),
+        Event::ApprovalRequestCreated { request_id, request_type, .. } => {
+            format!("{} ({})", request_id, request_type)
+        }
+        Event::ApprovalRequestResolved { request_id, status, .. } => {
+            format!("{} {}", request_id, status)
+        }
+        Event::ExceptionCreated { exception_id, exception_type, .. } => {
+            format!("{} ({})", exception_id, exception_type)
+        }
+        Event::ExceptionRevoked { exception_id, .. } => exception_id.clone(),
+    };
+    format!("{}{}", details, profile)
+}
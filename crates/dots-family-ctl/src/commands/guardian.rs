@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, GuardianList},
+};
+use zbus::Connection;
+
+use crate::auth;
+
+#[derive(Subcommand)]
+pub enum GuardianAction {
+    /// List guardian accounts
+    List,
+
+    /// Add a guardian account
+    Add {
+        /// Account name used to sign in
+        name: String,
+
+        /// Role: admin, approver or viewer
+        #[arg(long, default_value = "approver")]
+        role: String,
+
+        /// Profile name or ID the guardian may act on (repeat for more; all if omitted)
+        #[arg(long = "profile")]
+        profiles: Vec<String>,
+    },
+
+    /// Remove a guardian account
+    Remove {
+        /// Account name to remove
+        name: String,
+    },
+}
+
+pub async fn list() -> Result<()> {
+    auth::require_auth(|token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response =
+                proxy.list_guardians(&token).await.context("Failed to list guardians")?;
+
+            let list: GuardianList =
+                payload::decode(&response).context("Failed to parse response")?;
+
+            println!("👪 Guardian Accounts:\n");
+
+            for guardian in list.guardians {
+                println!("{} ({})", guardian.name, guardian.role.as_str());
+                if guardian.profile_ids.is_empty() {
+                    println!("   Profiles: all");
+                } else {
+                    println!("   Profiles: {}", guardian.profile_ids.join(", "));
+                }
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn add(name: String, role: String, profiles: Vec<String>) -> Result<()> {
+    let password = rpassword::prompt_password(format!("New password for {}: ", name))
+        .context("Failed to read password")?;
+    let confirm =
+        rpassword::prompt_password("Confirm password: ").context("Failed to read password")?;
+    if password != confirm {
+        anyhow::bail!("Passwords do not match");
+    }
+    if password.trim().is_empty() {
+        anyhow::bail!("Password cannot be empty");
+    }
+
+    auth::require_auth(|token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .add_guardian(&name, &password, &role, profiles.clone(), &token)
+                .await
+                .context("Failed to add guardian")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Guardian {} added as {}", name, role);
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to add guardian: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn remove(name: String) -> Result<()> {
+    auth::require_auth(|token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response =
+                proxy.remove_guardian(&name, &token).await.context("Failed to remove guardian")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Guardian {} removed", name);
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to remove guardian: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
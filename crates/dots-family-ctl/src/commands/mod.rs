@@ -1,7 +1,12 @@
 pub mod approval;
+pub mod audit;
 pub mod check;
+pub mod events;
+pub mod guardian;
 pub mod profile;
 pub mod report;
+pub mod rule;
 pub mod session;
 pub mod status;
+pub mod time_bank;
 pub mod time_window;
@@ -1,5 +1,9 @@
 use anyhow::Result;
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    error::Error as DaemonError,
+    payload::{self, ProfileList},
+};
 use zbus::Connection;
 
 use crate::auth;
@@ -8,16 +12,18 @@ pub async fn list() -> Result<()> {
     let conn = Connection::system().await?;
     let proxy = FamilyDaemonProxy::new(&conn).await?;
 
-    let profiles_json = proxy.list_profiles().await?;
-    let profiles: Vec<serde_json::Value> = serde_json::from_str(&profiles_json)?;
+    let list: ProfileList = payload::decode(&proxy.list_profiles().await?)?;
 
     println!("Available profiles:");
-    for profile in profiles {
-        let name = profile["name"].as_str().unwrap_or("unknown");
-        let age_group = profile["age_group"].as_str().unwrap_or("unknown");
-        let active = profile["active"].as_bool().unwrap_or(false);
-        let active_marker = if active { " (active)" } else { "" };
-        println!("  - {} ({}){}", name, age_group, active_marker);
+    for profile in list.profiles {
+        let age_group = serde_json::to_value(profile.age_group)?;
+        let active_marker = if profile.active { " (active)" } else { "" };
+        println!(
+            "  - {} ({}){}",
+            profile.name,
+            age_group.as_str().unwrap_or("unknown"),
+            active_marker
+        );
     }
 
     Ok(())
@@ -38,14 +44,9 @@ pub async fn create(name: &str, age_group: &str, username: Option<&str>) -> Resu
     let proxy = FamilyDaemonProxy::new(&conn).await?;
 
     // Try to create profile directly first (for initial setup when no parent password exists)
-    let profile_id = proxy.create_profile(name, age_group, username.unwrap_or("")).await?;
-
-    if let Some(error_msg) = profile_id.strip_prefix("error:") {
-        // If direct creation failed, it might be due to authentication requirement
-        if error_msg.contains("authentication not configured") {
-            println!("This appears to be the first-time setup. Creating initial profile...");
-        } else {
-            // For other errors, try with authentication
+    match proxy.create_profile(name, age_group, username.unwrap_or("")).await {
+        Ok(profile_id) => println!("Created profile '{}' with ID: {}", name, profile_id),
+        Err(DaemonError::NotAuthorized(_)) => {
             println!("Profile creation requires parent authentication.");
             let name = name.to_string();
             let age_group = age_group.to_string();
@@ -56,28 +57,22 @@ pub async fn create(name: &str, age_group: &str, username: Option<&str>) -> Resu
                     let conn = Connection::system().await?;
                     let proxy = FamilyDaemonProxy::new(&conn).await?;
 
-                    let profile_id = proxy
+                    match proxy
                         .create_profile(&name, &age_group, username.as_deref().unwrap_or(""))
-                        .await?;
-
-                    if let Some(error_msg) = profile_id.strip_prefix("error:") {
-                        println!("Failed to create profile: {}", error_msg);
-                    } else {
-                        println!("Created profile '{}' with ID: {}", name, profile_id);
+                        .await
+                    {
+                        Ok(profile_id) => {
+                            println!("Created profile '{}' with ID: {}", name, profile_id)
+                        }
+                        Err(e) => println!("Failed to create profile: {}", e),
                     }
 
                     Ok(())
                 })
             })
             .await?;
-            return Ok(());
         }
-    }
-
-    if !profile_id.starts_with("error:") {
-        println!("Created profile '{}' with ID: {}", name, profile_id);
-    } else {
-        println!("Failed to create profile: {}", profile_id);
+        Err(e) => println!("Failed to create profile: {}", e),
     }
 
     Ok(())
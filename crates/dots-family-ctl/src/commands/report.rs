@@ -81,6 +81,8 @@ pub async fn daily(profile: &str, date: Option<&str>) -> Result<()> {
                 }
             }
 
+            print_time_bank(&result);
+
             println!();
             Ok(())
         })
@@ -171,6 +173,8 @@ pub async fn weekly(profile: &str, week_start: Option<&str>) -> Result<()> {
                 }
             }
 
+            print_time_bank(&result);
+
             println!();
             Ok(())
         })
@@ -178,6 +182,32 @@ pub async fn weekly(profile: &str, week_start: Option<&str>) -> Result<()> {
     .await
 }
 
+/// Display the time bank section of a report, if there was any activity
+fn print_time_bank(result: &serde_json::Value) {
+    let Some(bank) = result.get("time_bank") else {
+        return;
+    };
+    let minutes = |key: &str| bank.get(key).and_then(|m| m.as_u64()).unwrap_or(0);
+
+    let earned = minutes("earned_minutes");
+    let rolled_over = minutes("rolled_over_minutes");
+    let spent = minutes("spent_minutes");
+    let expired = minutes("expired_minutes");
+    let balance = minutes("balance_minutes");
+
+    if earned + rolled_over + spent + expired + balance == 0 {
+        return;
+    }
+
+    println!("\n🏦 Time Bank:");
+    println!("─────────────────────────────────────────────");
+    println!("  Earned:      {}m", earned);
+    println!("  Rolled over: {}m", rolled_over);
+    println!("  Spent:       {}m", spent);
+    println!("  Expired:     {}m", expired);
+    println!("  Balance:     {}m", balance);
+}
+
 /// Export reports to a file
 pub async fn export(
     profile: &str,
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use dots_family_common::{RuleAction as Decision, RuleCondition};
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, AutoApprovalRuleList, NewAutoApprovalRule},
+};
+use uuid::Uuid;
+use zbus::Connection;
+
+use crate::auth;
+
+#[derive(Subcommand)]
+pub enum RuleAction {
+    /// List all auto-approval rules
+    List,
+
+    /// Add an auto-approval rule
+    Add(AddRuleArgs),
+
+    /// Enable a rule
+    Enable {
+        /// Rule ID to enable
+        rule_id: String,
+    },
+
+    /// Disable a rule without removing it
+    Disable {
+        /// Rule ID to disable
+        rule_id: String,
+    },
+
+    /// Remove a rule
+    Remove {
+        /// Rule ID to remove
+        rule_id: String,
+    },
+}
+
+#[derive(Args)]
+pub struct AddRuleArgs {
+    /// Human-readable rule name
+    pub name: String,
+
+    /// Automatically deny matching requests instead of approving them
+    #[arg(long)]
+    pub deny: bool,
+
+    /// Profile ID the rule applies to (all profiles if omitted)
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Request type: app, website, screen_time, time_extension, command, custom
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Only match on weekends
+    #[arg(long, conflicts_with = "weekday")]
+    pub weekend: bool,
+
+    /// Only match on weekdays
+    #[arg(long)]
+    pub weekday: bool,
+
+    /// Only match at or after this time (HH:MM)
+    #[arg(long)]
+    pub after: Option<String>,
+
+    /// Only match before this time (HH:MM)
+    #[arg(long)]
+    pub before: Option<String>,
+
+    /// Only match screen time requests of at most this many minutes
+    #[arg(long)]
+    pub max_minutes: Option<u32>,
+
+    /// Only match applications in this category (e.g. Education, Game)
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Only match requests for this application ID
+    #[arg(long)]
+    pub app: Option<String>,
+
+    /// Fire at most this many times per day
+    #[arg(long)]
+    pub max_per_day: Option<u32>,
+}
+
+impl AddRuleArgs {
+    /// Build the rule payload expected by the daemon
+    fn to_new_rule(&self) -> Result<NewAutoApprovalRule> {
+        let mut conditions = Vec::new();
+
+        if let Some(kind) = &self.kind {
+            conditions.push(RuleCondition::RequestKind { kind: kind.clone() });
+        }
+        if self.weekend {
+            conditions.push(RuleCondition::Weekend);
+        }
+        if self.weekday {
+            conditions.push(RuleCondition::Weekday);
+        }
+        if let Some(time) = &self.after {
+            conditions.push(RuleCondition::After { time: time.clone() });
+        }
+        if let Some(time) = &self.before {
+            conditions.push(RuleCondition::Before { time: time.clone() });
+        }
+        if let Some(minutes) = self.max_minutes {
+            conditions.push(RuleCondition::MaxMinutes { minutes });
+        }
+        if let Some(category) = &self.category {
+            conditions.push(RuleCondition::Category { category: category.clone() });
+        }
+        if let Some(app_id) = &self.app {
+            conditions.push(RuleCondition::AppId { app_id: app_id.clone() });
+        }
+        if let Some(count) = self.max_per_day {
+            conditions.push(RuleCondition::MaxPerDay { count });
+        }
+
+        if conditions.is_empty() {
+            return Err(anyhow!("At least one condition must be specified (see --help)"));
+        }
+
+        let profile_id = self
+            .profile
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .context("Profile must be a profile ID")?;
+
+        Ok(NewAutoApprovalRule {
+            name: self.name.clone(),
+            profile_id,
+            conditions,
+            action: if self.deny { Decision::AutoDeny } else { Decision::AutoApprove },
+        })
+    }
+}
+
+pub async fn list() -> Result<()> {
+    auth::require_auth(|token| {
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .list_auto_approval_rules(&token)
+                .await
+                .context("Failed to list auto-approval rules")?;
+
+            let list: AutoApprovalRuleList =
+                payload::decode(&response).context("Failed to parse response")?;
+
+            if list.rules.is_empty() {
+                println!("No auto-approval rules configured");
+                return Ok(());
+            }
+
+            println!("📋 Auto-Approval Rules:\n");
+
+            for rule in list.rules {
+                let action = match rule.action {
+                    Decision::AutoApprove => "auto_approve",
+                    Decision::AutoDeny => "auto_deny",
+                };
+                let profile =
+                    rule.profile_id.map_or_else(|| "all profiles".to_string(), |id| id.to_string());
+
+                println!("{} {} ({})", if rule.enabled { "✅" } else { "⏸️ " }, rule.name, rule.id);
+                println!("   Action: {}", action);
+                println!("   Applies to: {}", profile);
+                println!("   Conditions: {}", serde_json::to_string(&rule.conditions)?);
+                println!();
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn add(args: AddRuleArgs) -> Result<()> {
+    let rule_json = payload::encode(&args.to_new_rule()?)?;
+
+    auth::require_auth(|token| {
+        let rule_json = rule_json.clone();
+
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .add_auto_approval_rule(&rule_json, &token)
+                .await
+                .context("Failed to add auto-approval rule")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Rule added: {}", result["rule_id"].as_str().unwrap_or("unknown"));
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to add rule: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn set_enabled(rule_id: String, enabled: bool) -> Result<()> {
+    auth::require_auth(|token| {
+        let rule_id = rule_id.clone();
+
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .set_auto_approval_rule_enabled(&rule_id, enabled, &token)
+                .await
+                .context("Failed to update auto-approval rule")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Rule {}", if enabled { "enabled" } else { "disabled" });
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to update rule: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn remove(rule_id: String) -> Result<()> {
+    auth::require_auth(|token| {
+        let rule_id = rule_id.clone();
+
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .remove_auto_approval_rule(&rule_id, &token)
+                .await
+                .context("Failed to remove auto-approval rule")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Rule removed");
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to remove rule: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
@@ -1,24 +1,25 @@
 use anyhow::Result;
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_common::types::Profile;
+use dots_family_proto::{daemon::FamilyDaemonProxy, error::Error as DaemonError, payload};
 use zbus::Connection;
 
 pub async fn view() -> Result<()> {
     let conn = Connection::system().await?;
     let proxy = FamilyDaemonProxy::new(&conn).await?;
 
-    let profile_json = proxy.get_active_profile().await?;
-    let profile: serde_json::Value = serde_json::from_str(&profile_json)?;
-
-    if profile.get("error").is_some() {
-        println!("No active session");
-        return Ok(());
-    }
-
-    let name = profile["name"].as_str().unwrap_or("unknown");
+    let profile_json = match proxy.get_active_profile().await {
+        Ok(profile_json) => profile_json,
+        Err(DaemonError::NotFound(_)) => {
+            println!("No active session");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let profile: Profile = payload::decode(&profile_json)?;
     let remaining_minutes = proxy.get_remaining_time().await?;
 
     println!("Active Session:");
-    println!("  Profile: {}", name);
+    println!("  Profile: {}", profile.name);
     println!("  Remaining time: {} minutes", remaining_minutes);
 
     Ok(())
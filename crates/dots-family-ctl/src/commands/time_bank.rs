@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, ApprovalDetails, ApprovalSubmission, TimeBank},
+};
+use zbus::Connection;
+
+use crate::auth;
+
+#[derive(Subcommand)]
+pub enum TimeBankAction {
+    /// Show a profile's banked minutes and ledger history
+    Show {
+        /// Profile name or ID
+        profile: String,
+    },
+
+    /// Reward a profile with banked minutes
+    Grant {
+        /// Profile name or ID
+        profile: String,
+
+        /// Minutes to add to the bank
+        minutes: u32,
+
+        /// Why the minutes were earned (e.g. "Cleaned room")
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+
+    /// Ask to spend banked minutes on extra screen time today
+    Spend {
+        /// Minutes to spend
+        minutes: u32,
+
+        /// Optional message for the parent
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+}
+
+pub async fn show(profile: &str) -> Result<()> {
+    let profile = profile.to_string();
+
+    auth::require_auth(|token| {
+        let profile = profile.clone();
+
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response =
+                proxy.get_time_bank(&profile, &token).await.context("Failed to get time bank")?;
+
+            let bank: TimeBank = payload::decode(&response).context("Failed to parse response")?;
+
+            println!("🏦 Time Bank for {}: {} minutes available\n", profile, bank.balance_minutes);
+
+            if bank.history.is_empty() {
+                println!("No ledger entries yet");
+                return Ok(());
+            }
+
+            for entry in bank.history {
+                println!(
+                    "{:>+5} min  {:<12} {}  {}",
+                    entry.minutes,
+                    entry.kind.as_str(),
+                    entry.created_at.to_rfc3339(),
+                    entry.reason.as_deref().unwrap_or("")
+                );
+                if let Some(expires_at) = entry.expires_at {
+                    println!("            expires {}", expires_at.to_rfc3339());
+                }
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn grant(profile: &str, minutes: u32, reason: Option<String>) -> Result<()> {
+    let profile = profile.to_string();
+    let reason = reason.unwrap_or_default();
+
+    auth::require_auth(|token| {
+        let profile = profile.clone();
+        let reason = reason.clone();
+
+        Box::pin(async move {
+            let connection =
+                Connection::system().await.context("Failed to connect to system bus")?;
+
+            let proxy = FamilyDaemonProxy::new(&connection)
+                .await
+                .context("Failed to create daemon proxy")?;
+
+            let response = proxy
+                .grant_time_credit(&profile, minutes, &reason, &token)
+                .await
+                .context("Failed to grant time credit")?;
+
+            let result: serde_json::Value =
+                serde_json::from_str(&response).context("Failed to parse response")?;
+
+            if result["status"] == "success" {
+                println!("✅ Added {} minutes to {}'s time bank", minutes, profile);
+            } else {
+                let error = result["error"].as_str().unwrap_or("Unknown error");
+                println!("❌ Failed to grant time credit: {}", error);
+            }
+
+            Ok(())
+        })
+    })
+    .await
+}
+
+pub async fn spend(minutes: u32, message: Option<String>) -> Result<()> {
+    let connection = Connection::system().await.context("Failed to connect to system bus")?;
+
+    let proxy =
+        FamilyDaemonProxy::new(&connection).await.context("Failed to create daemon proxy")?;
+
+    let details =
+        payload::encode(&ApprovalDetails { minutes: Some(minutes), ..Default::default() })?;
+    let response = proxy
+        .submit_approval_request("time_credit", message.as_deref().unwrap_or(""), &details)
+        .await
+        .context("Failed to submit time credit request")?;
+
+    let submission: ApprovalSubmission =
+        payload::decode(&response).context("Failed to parse response")?;
+
+    match submission.request_status.as_str() {
+        "auto_approved" => println!("✅ {} banked minutes added to today's time", minutes),
+        "denied" => println!("❌ Request denied"),
+        _ => println!(
+            "⏳ Request sent (ID: {}). A parent needs to approve it.",
+            submission.request_id
+        ),
+    }
+    if let Some(explanation) = submission.explanation {
+        println!("   {}", explanation);
+    }
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use anyhow::{anyhow, Result};
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, TimeWindowList},
+};
 use zbus::Connection;
 
 use crate::auth;
@@ -76,52 +79,19 @@ pub async fn list(profile: &str) -> Result<()> {
 
             let response = proxy.list_time_windows(&profile, &token).await?;
 
-            // Parse response
-            let result: serde_json::Value = serde_json::from_str(&response)?;
-
-            if let Some(error) = result.get("error").and_then(|e| e.as_str()) {
-                println!("Failed to list time windows: {}", error);
-                return Ok(());
-            }
-
-            // Extract profile info
-            let profile_name =
-                result.get("profile_name").and_then(|n| n.as_str()).unwrap_or(&profile);
-
-            println!("Time windows for profile '{}':", profile_name);
+            let windows: TimeWindowList = payload::decode(&response)?;
 
-            // Display weekday windows
-            if let Some(weekday) = result.get("weekday").and_then(|w| w.as_array()) {
-                if !weekday.is_empty() {
-                    println!("\n  Weekday:");
-                    for window in weekday {
-                        let start = window.get("start").and_then(|s| s.as_str()).unwrap_or("?");
-                        let end = window.get("end").and_then(|e| e.as_str()).unwrap_or("?");
-                        println!("    {}–{}", start, end);
-                    }
-                }
-            }
-
-            // Display weekend windows
-            if let Some(weekend) = result.get("weekend").and_then(|w| w.as_array()) {
-                if !weekend.is_empty() {
-                    println!("\n  Weekend:");
-                    for window in weekend {
-                        let start = window.get("start").and_then(|s| s.as_str()).unwrap_or("?");
-                        let end = window.get("end").and_then(|e| e.as_str()).unwrap_or("?");
-                        println!("    {}–{}", start, end);
-                    }
-                }
-            }
+            println!("Time windows for profile '{}':", windows.profile_name);
 
-            // Display holiday windows
-            if let Some(holiday) = result.get("holiday").and_then(|w| w.as_array()) {
-                if !holiday.is_empty() {
-                    println!("\n  Holiday:");
-                    for window in holiday {
-                        let start = window.get("start").and_then(|s| s.as_str()).unwrap_or("?");
-                        let end = window.get("end").and_then(|e| e.as_str()).unwrap_or("?");
-                        println!("    {}–{}", start, end);
+            for (label, list) in [
+                ("Weekday", &windows.weekday),
+                ("Weekend", &windows.weekend),
+                ("Holiday", &windows.holiday),
+            ] {
+                if !list.is_empty() {
+                    println!("\n  {}:", label);
+                    for window in list {
+                        println!("    {}–{}", window.start, window.end);
                     }
                 }
             }
@@ -4,12 +4,19 @@ use clap::{Parser, Subcommand};
 mod auth;
 mod commands;
 
-use commands::approval::ApprovalAction;
+use commands::{
+    approval::ApprovalAction, audit::AuditAction, guardian::GuardianAction, rule::RuleAction,
+    time_bank::TimeBankAction,
+};
 
 #[derive(Parser)]
 #[command(name = "dots-family-ctl")]
 #[command(about = "DOTS Family Mode CLI control tool", long_about = None)]
 struct Cli {
+    /// Sign in as this guardian account instead of with the shared parent password
+    #[arg(long, global = true)]
+    guardian: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -41,6 +48,45 @@ enum Commands {
         action: ApprovalAction,
     },
 
+    /// Manage auto-approval rules for approval requests
+    Rule {
+        #[command(subcommand)]
+        action: RuleAction,
+    },
+
+    /// Manage the screen-time bank of earned and rolled-over minutes
+    TimeBank {
+        #[command(subcommand)]
+        action: TimeBankAction,
+    },
+
+    /// Inspect the tamper-evident audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
+    /// Manage guardian accounts and their roles
+    Guardian {
+        #[command(subcommand)]
+        action: GuardianAction,
+    },
+
+    /// Show live activity events: app focus, blocks, requests, warnings, exceptions
+    Events {
+        /// Only events of this kind, e.g. application_blocked; may be repeated
+        #[arg(long = "kind")]
+        kinds: Vec<String>,
+
+        /// Only events about this profile ID
+        #[arg(long)]
+        profile: Option<uuid::Uuid>,
+
+        /// Keep printing new events as they happen
+        #[arg(short, long)]
+        follow: bool,
+    },
+
     Status,
 
     Check {
@@ -159,6 +205,10 @@ enum ReportAction {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(guardian) = cli.guardian {
+        auth::set_guardian(guardian);
+    }
+
     match cli.command {
         Commands::Profile { action } => match action {
             ProfileAction::List => commands::profile::list().await?,
@@ -216,6 +266,35 @@ async fn main() -> Result<()> {
                 commands::approval::deny(request_id, message).await?
             }
         },
+        Commands::Rule { action } => match action {
+            RuleAction::List => commands::rule::list().await?,
+            RuleAction::Add(args) => commands::rule::add(args).await?,
+            RuleAction::Enable { rule_id } => commands::rule::set_enabled(rule_id, true).await?,
+            RuleAction::Disable { rule_id } => commands::rule::set_enabled(rule_id, false).await?,
+            RuleAction::Remove { rule_id } => commands::rule::remove(rule_id).await?,
+        },
+        Commands::TimeBank { action } => match action {
+            TimeBankAction::Show { profile } => commands::time_bank::show(&profile).await?,
+            TimeBankAction::Grant { profile, minutes, reason } => {
+                commands::time_bank::grant(&profile, minutes, reason).await?
+            }
+            TimeBankAction::Spend { minutes, message } => {
+                commands::time_bank::spend(minutes, message).await?
+            }
+        },
+        Commands::Audit { action } => match action {
+            AuditAction::Verify => commands::audit::verify().await?,
+        },
+        Commands::Guardian { action } => match action {
+            GuardianAction::List => commands::guardian::list().await?,
+            GuardianAction::Add { name, role, profiles } => {
+                commands::guardian::add(name, role, profiles).await?
+            }
+            GuardianAction::Remove { name } => commands::guardian::remove(name).await?,
+        },
+        Commands::Events { kinds, profile, follow } => {
+            commands::events::watch(kinds, profile, follow).await?
+        }
         Commands::Status => commands::status::show().await?,
         Commands::Check { app_id } => commands::check::application(&app_id).await?,
     }
@@ -0,0 +1,36 @@
+//! Lookups between system accounts, uids and processes, shared by the
+//! components that attribute activity to a child or enforce on their account.
+
+use std::os::unix::fs::MetadataExt;
+
+use anyhow::{anyhow, Context, Result};
+
+/// Owner uid of a running process
+pub fn uid_of_process(pid: u32) -> Result<u32> {
+    let metadata = std::fs::metadata(format!("/proc/{}", pid))
+        .with_context(|| format!("Process {} not found", pid))?;
+    Ok(metadata.uid())
+}
+
+/// uid of a local user account
+pub fn uid_of_user(username: &str) -> Result<u32> {
+    nix::unistd::User::from_name(username)
+        .with_context(|| format!("Failed to look up user {}", username))?
+        .map(|user| user.uid.as_raw())
+        .ok_or_else(|| anyhow!("No such user: {}", username))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_process_belongs_to_current_user() {
+        let uid = nix::unistd::getuid().as_raw();
+        assert_eq!(uid_of_process(std::process::id()).unwrap(), uid);
+
+        let name = nix::unistd::User::from_uid(uid.into()).unwrap().unwrap().name;
+        assert_eq!(uid_of_user(&name).unwrap(), uid);
+        assert!(uid_of_user("no-such-user-dots").is_err());
+    }
+}
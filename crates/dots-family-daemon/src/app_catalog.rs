@@ -0,0 +1,134 @@
+//! Application names and categories from installed `.desktop` files.
+//!
+//! Auto-approval rules match requests on the category of the requested app,
+//! which must not come from the child asking for it. The catalogue reads the
+//! `Categories=` key of desktop entries installed system-wide, in directories
+//! only root can write to; entries in a user's own data directory are never
+//! consulted. Lookups are cached in `app_info_cache` by the profile manager.
+
+use std::path::{Path, PathBuf};
+
+use dots_family_db::queries::app_info_cache::NewAppInfoCache;
+use tracing::debug;
+
+/// freedesktop.org main categories; an entry's first one is its category
+const MAIN_CATEGORIES: &[&str] = &[
+    "AudioVideo",
+    "Audio",
+    "Video",
+    "Development",
+    "Education",
+    "Game",
+    "Graphics",
+    "Network",
+    "Office",
+    "Science",
+    "Settings",
+    "System",
+    "Utility",
+];
+
+pub struct AppCatalog {
+    dirs: Vec<PathBuf>,
+}
+
+impl AppCatalog {
+    /// Search `dirs` in order; an entry in an earlier directory wins
+    pub fn new(dirs: &[String]) -> Self {
+        Self { dirs: dirs.iter().map(PathBuf::from).collect() }
+    }
+
+    /// The installed desktop entry for `app_id`, e.g. `supertuxkart` or
+    /// `org.gnome.Nautilus`
+    pub fn lookup(&self, app_id: &str) -> Option<NewAppInfoCache> {
+        // An id is a file name, never a path
+        if app_id.is_empty() || app_id.contains('/') || app_id.starts_with('.') {
+            return None;
+        }
+
+        self.dirs.iter().find_map(|dir| {
+            let path = dir.join(format!("{}.desktop", app_id));
+            let contents = std::fs::read_to_string(&path).ok()?;
+            debug!("Cataloguing {} from {:?}", app_id, path);
+            Some(parse_desktop_entry(app_id, &contents, &path))
+        })
+    }
+}
+
+fn parse_desktop_entry(app_id: &str, contents: &str, path: &Path) -> NewAppInfoCache {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut categories = None;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        match line.split_once('=') {
+            Some(("Name", value)) => name = Some(value.trim().to_string()),
+            Some(("Categories", value)) => categories = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let category = categories.and_then(|categories| {
+        categories
+            .split(';')
+            .find(|category| MAIN_CATEGORIES.contains(category))
+            .map(str::to_string)
+    });
+    NewAppInfoCache {
+        app_id: app_id.to_string(),
+        app_name: name.unwrap_or_else(|| app_id.to_string()),
+        category,
+        desktop_file: Some(path.to_string_lossy().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_category_is_its_first_main_category() {
+        let contents = "[Desktop Entry]\nName=SuperTuxKart\nExec=supertuxkart\n\
+                        Categories=Racing;Game;ArcadeGame;\n\n\
+                        [Desktop Action New]\nName=New Race\nCategories=Education;\n";
+        let entry =
+            parse_desktop_entry("supertuxkart", contents, Path::new("/a/supertuxkart.desktop"));
+        assert_eq!(entry.app_name, "SuperTuxKart");
+        assert_eq!(entry.category.as_deref(), Some("Game"));
+
+        let entry =
+            parse_desktop_entry("tool", "[Desktop Entry]\nCategories=X-Custom;\n", Path::new("/a"));
+        assert_eq!(entry.app_name, "tool");
+        assert_eq!(entry.category, None);
+    }
+
+    #[test]
+    fn test_lookup_prefers_earlier_directories_and_rejects_paths() {
+        let second = tempfile::tempdir().unwrap();
+        let first = tempfile::tempdir().unwrap();
+        std::fs::write(
+            first.path().join("gcompris.desktop"),
+            "[Desktop Entry]\nName=GCompris\nCategories=Education;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            second.path().join("gcompris.desktop"),
+            "[Desktop Entry]\nName=GCompris\nCategories=Game;Education;\n",
+        )
+        .unwrap();
+
+        let catalog = AppCatalog::new(&[
+            first.path().to_string_lossy().to_string(),
+            second.path().to_string_lossy().to_string(),
+        ]);
+        assert_eq!(catalog.lookup("gcompris").unwrap().category.as_deref(), Some("Education"));
+        assert!(catalog.lookup("missing").is_none());
+        assert!(catalog.lookup("../gcompris").is_none());
+    }
+}
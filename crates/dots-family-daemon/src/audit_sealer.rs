@@ -0,0 +1,198 @@
+use std::{
+    fs,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use dots_family_db::{
+    models::DbAuditSeal,
+    queries::audit::{AuditChainBreak, AuditChainReport, AuditQueries, SealCheckpoint},
+    Database,
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use tracing::info;
+
+use crate::{config::AuditConfig, trusted_clock};
+
+/// Holds the daemon's Ed25519 key for sealing the audit log chain head.
+///
+/// The key never leaves the daemon, so a chain rewritten by someone with
+/// database access cannot carry a valid seal. The latest seal is also
+/// recorded next to the key, which catches a database rolled back to an
+/// older, validly sealed state.
+pub struct AuditSealer {
+    key_pair: Ed25519KeyPair,
+    checkpoint_path: PathBuf,
+}
+
+impl AuditSealer {
+    /// Load the sealing key, creating it on first start
+    pub fn load_or_create(config: &AuditConfig) -> Result<Self> {
+        let path = PathBuf::from(&config.sealing_key_path);
+
+        let pkcs8 = if path.exists() {
+            fs::read(&path).with_context(|| format!("Failed to read sealing key: {:?}", path))?
+        } else {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|_| anyhow!("Failed to generate audit sealing key"))?;
+            write_key(&path, pkcs8.as_ref())?;
+            info!("Created audit sealing key at {:?}", path);
+            pkcs8.as_ref().to_vec()
+        };
+
+        let key_pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .map_err(|_| anyhow!("Invalid audit sealing key: {:?}", path))?;
+
+        Ok(Self { key_pair, checkpoint_path: path.with_extension("json") })
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// Seal the chain head if anything was logged since the last seal
+    pub async fn seal(&self, db: &Database) -> Result<Option<DbAuditSeal>> {
+        let checkpoint = self.load_checkpoint()?;
+        let sign = |message: &[u8]| self.key_pair.sign(message).as_ref().to_vec();
+        let Some(seal) =
+            AuditQueries::seal(db, checkpoint.as_ref(), trusted_clock::now(), sign).await?
+        else {
+            return Ok(None);
+        };
+
+        let seal_count = match checkpoint {
+            Some(checkpoint) => checkpoint.seal_count + 1,
+            None => AuditQueries::seal_count(db).await?,
+        };
+        self.store_checkpoint(&SealCheckpoint {
+            entry_id: seal.entry_id,
+            entry_hash: seal.entry_hash.clone(),
+            seal_count,
+        })?;
+
+        Ok(Some(seal))
+    }
+
+    /// Verify the chain and every seal against this daemon's key, and that
+    /// the latest seal the daemon made is still in the log
+    pub async fn verify(&self, db: &Database) -> Result<AuditChainReport> {
+        let checkpoint = self.load_checkpoint();
+        let recorded = checkpoint.as_ref().ok().and_then(Option::as_ref);
+        let mut report = AuditQueries::verify_chain(db, self.public_key(), recorded).await?;
+
+        let missing = match checkpoint {
+            Ok(Some(_)) => None,
+            Ok(None) if report.seals_checked > 0 => {
+                Some(format!("the seal record {:?} is missing", self.checkpoint_path))
+            }
+            Ok(None) => None,
+            Err(e) => Some(format!("the seal record is unreadable: {}", e)),
+        };
+        if let Some(reason) = missing {
+            report.breaks.insert(0, AuditChainBreak { entry_id: 0, seal_id: None, reason });
+        }
+
+        Ok(report)
+    }
+
+    fn load_checkpoint(&self) -> Result<Option<SealCheckpoint>> {
+        match fs::read(&self.checkpoint_path) {
+            Ok(bytes) => Ok(Some(
+                serde_json::from_slice(&bytes)
+                    .with_context(|| format!("Invalid seal record: {:?}", self.checkpoint_path))?,
+            )),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e)
+                .with_context(|| format!("Failed to read seal record: {:?}", self.checkpoint_path)),
+        }
+    }
+
+    /// Replace the record atomically so a crash never leaves half of it
+    fn store_checkpoint(&self, checkpoint: &SealCheckpoint) -> Result<()> {
+        let staging = self.checkpoint_path.with_extension("json.tmp");
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&staging)
+            .with_context(|| format!("Failed to write seal record: {:?}", staging))?;
+        file.write_all(&serde_json::to_vec(checkpoint)?)?;
+        file.sync_all()?;
+        fs::rename(&staging, &self.checkpoint_path)
+            .with_context(|| format!("Failed to write seal record: {:?}", self.checkpoint_path))?;
+        Ok(())
+    }
+}
+
+fn write_key(path: &Path, pkcs8: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+
+    // Readable by the daemon only
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to create sealing key: {:?}", path))?;
+    file.write_all(pkcs8).with_context(|| format!("Failed to write sealing key: {:?}", path))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use dots_family_db::{models::NewAuditLog, DatabaseConfig};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sealing_key_persists_and_seals_verify() {
+        let dir = tempdir().unwrap();
+        let config = AuditConfig {
+            sealing_key_path: dir.path().join("keys/audit-seal.key").to_string_lossy().to_string(),
+            seal_interval_seconds: 300,
+        };
+
+        let sealer = AuditSealer::load_or_create(&config).unwrap();
+        let reloaded = AuditSealer::load_or_create(&config).unwrap();
+        assert_eq!(sealer.public_key(), reloaded.public_key());
+
+        let db_config = DatabaseConfig {
+            path: dir.path().join("test.db").to_string_lossy().to_string(),
+            encryption_key: None,
+        };
+        let db = Database::new(db_config).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let entry = NewAuditLog {
+            actor: "system".to_string(),
+            action: "test".to_string(),
+            resource: "system".to_string(),
+            resource_id: None,
+            ip_address: None,
+            success: true,
+            details: None,
+        };
+        AuditQueries::log(&db, entry, chrono::Utc::now()).await.unwrap();
+
+        assert!(sealer.seal(&db).await.unwrap().is_some());
+        let report = reloaded.verify(&db).await.unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.last_sealed_entry_id, Some(1));
+
+        // Losing the record next to the key is reported, not trusted
+        fs::remove_file(dir.path().join("keys/audit-seal.json")).unwrap();
+        let report = sealer.verify(&db).await.unwrap();
+        assert!(report.first_break().unwrap().reason.contains("seal record"));
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
-use chrono::{Duration, Utc};
+use chrono::Duration;
 use dots_family_common::types::{ActivityPattern, AlertSeverity, AlertType, BehaviorAlert};
 use dots_family_db::Database;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::trusted_clock;
+
 #[allow(dead_code)]
 pub struct BehaviorAnalyzer {
     db: Database,
@@ -46,7 +48,7 @@ impl BehaviorAnalyzer {
         use dots_family_db::queries::activities::ActivityQueries;
 
         let mut alerts = Vec::new();
-        let since = Utc::now() - Duration::hours(24);
+        let since = trusted_clock::now() - Duration::hours(24);
 
         // Get activities for the last 24 hours
         let activities = ActivityQueries::list_by_profile_since(&self.db, profile_id, since)
@@ -80,7 +82,7 @@ impl BehaviorAnalyzer {
                         "Consider setting time limits for {} or discussing usage with child",
                         app_id
                     )),
-                    created_at: Utc::now(),
+                    created_at: trusted_clock::now(),
                     acknowledged_at: None,
                     dismissed: false,
                 };
@@ -93,7 +95,7 @@ impl BehaviorAnalyzer {
 
     async fn check_repeated_violations(&self, profile_id: &str) -> Result<Vec<BehaviorAlert>> {
         let mut alerts = Vec::new();
-        let since = Utc::now() - Duration::hours(6);
+        let since = trusted_clock::now() - Duration::hours(6);
 
         let pool = self.db.pool()?;
         let violation_count: i64 = sqlx::query_scalar(
@@ -126,7 +128,7 @@ impl BehaviorAnalyzer {
                     "Consider reviewing restrictions or discussing appropriate usage with child"
                         .to_string(),
                 ),
-                created_at: Utc::now(),
+                created_at: trusted_clock::now(),
                 acknowledged_at: None,
                 dismissed: false,
             };
@@ -183,7 +185,7 @@ impl BehaviorAnalyzer {
                     recommendation: Some(
                         "Monitor for continued increases and consider adjusting limits".to_string(),
                     ),
-                    created_at: Utc::now(),
+                    created_at: trusted_clock::now(),
                     acknowledged_at: None,
                     dismissed: false,
                 };
@@ -196,7 +198,7 @@ impl BehaviorAnalyzer {
 
     async fn check_off_hours_activity(&self, profile_id: &str) -> Result<Vec<BehaviorAlert>> {
         let mut alerts = Vec::new();
-        let since = Utc::now() - Duration::hours(24);
+        let since = trusted_clock::now() - Duration::hours(24);
 
         let pool = self.db.pool()?;
         let late_activity_count: i64 = sqlx::query_scalar(
@@ -231,7 +233,7 @@ impl BehaviorAnalyzer {
                 recommendation: Some(
                     "Consider enforcing bedtime restrictions or device-free time".to_string(),
                 ),
-                created_at: Utc::now(),
+                created_at: trusted_clock::now(),
                 acknowledged_at: None,
                 dismissed: false,
             };
@@ -274,7 +276,7 @@ impl BehaviorAnalyzer {
                     "Consider reviewing current restrictions or discussing expectations with child"
                         .to_string(),
                 ),
-                created_at: Utc::now(),
+                created_at: trusted_clock::now(),
                 acknowledged_at: None,
                 dismissed: false,
             };
@@ -0,0 +1,564 @@
+use std::{collections::HashMap, fs, os::unix::fs::MetadataExt, path::Path};
+
+use anyhow::Result;
+use dots_family_proto::{error::DaemonError, events::ActivityEvent};
+use nix::unistd::{Gid, Group, Uid, User};
+use tracing::{debug, warn};
+use uuid::Uuid;
+use zbus::{fdo::DBusProxy, message::Header, names::BusName, zvariant::Value, Connection};
+
+use crate::{config::AccessConfig, profile_manager::ProfileManager};
+
+/// Polkit actions a parent may be authorized for instead of presenting a token
+pub mod actions {
+    pub const MANAGE_PROFILES: &str = "org.dots.family.manage-profiles";
+    pub const VIEW_ACTIVITY: &str = "org.dots.family.view-activity";
+    pub const APPROVE_REQUESTS: &str = "org.dots.family.approve-requests";
+    pub const MANAGE_RULES: &str = "org.dots.family.manage-rules";
+    pub const MANAGE_SYSTEM: &str = "org.dots.family.manage-system";
+}
+
+/// Polkit may show an authentication dialog to the caller
+const POLKIT_ALLOW_USER_INTERACTION: u32 = 1;
+
+/// What a D-Bus method requires of its caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Anyone on the bus: status queries and a child's own requests
+    Any,
+    /// Monitors and filters, which run as root or in a managed user's session
+    Component,
+    /// The daemon's own system components only
+    System,
+    /// A parent by group membership or polkit action, or a system component
+    Parent(&'static str),
+    /// A parent session token, or with no token the same checks as `Parent`
+    Token(&'static str),
+}
+
+/// Who a caller is, judged from its Unix account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallerClass {
+    System,
+    Parent,
+    Child,
+    Other,
+}
+
+/// The process behind a D-Bus sender, as reported by the bus daemon
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub sender: String,
+    pub uid: u32,
+    pub pid: Option<u32>,
+    pub username: String,
+    pub class: CallerClass,
+}
+
+impl Caller {
+    /// Actor recorded in the audit log for this caller
+    pub fn actor(&self) -> String {
+        format!("user:{}", self.username)
+    }
+}
+
+/// Access required by each method of `org.dots.FamilyDaemon`.
+/// Methods missing here are refused, so new methods must be added deliberately.
+pub fn method_access(member: &str) -> Option<Access> {
+    use actions::*;
+
+    let access = match member {
+        "Ping"
+        | "GetActiveProfile"
+        | "CheckApplicationAllowed"
+        | "GetRemainingTime"
+        | "ListProfiles"
+        | "AuthenticateParent"
+        | "AuthenticateGuardian"
+        | "ValidateSession"
+        | "RevokeSession"
+        | "RequestCommandApproval"
+        | "CheckExceptionApplies"
+        | "SubmitApprovalRequest"
+        | "GetTimeCreditBalance"
+        | "CheckAppPolicy"
+        | "CheckTimeWindow"
+        | "GetNextTimeWindow"
+        | "RequestFinishingUp" => Access::Any,
+
+        "ReportActivity"
+        | "ReportActivityEvent"
+        | "SendHeartbeat"
+        | "RegisterComponent"
+        | "ProcessActivityForPolicy"
+        | "GetEbpfStatus" => Access::Component,
+
+        "LockSession" => Access::System,
+
+        "CreateProfile" | "SetActiveProfile" | "SyncProfileToPolicy" => {
+            Access::Parent(MANAGE_PROFILES)
+        }
+        "GetDailyReport" | "GetWeeklyReport" | "ExportReports" | "GetMonitoringSnapshot" => {
+            Access::Parent(VIEW_ACTIVITY)
+        }
+
+        "AddTimeWindow" | "RemoveTimeWindow" | "ClearTimeWindows" => Access::Token(MANAGE_PROFILES),
+        "ListTimeWindows"
+        | "ListActiveExceptions"
+        | "ListPendingRequests"
+        | "ListAutoApprovalRules"
+        | "GetTimeBank"
+        | "RecentEvents"
+        | "SubscribeEvents" => Access::Token(VIEW_ACTIVITY),
+        "RequestParentPermission"
+        | "CreateException"
+        | "RevokeException"
+        | "ApproveRequest"
+        | "DenyRequest"
+        | "GrantTimeCredit" => Access::Token(APPROVE_REQUESTS),
+        "AddAutoApprovalRule" | "SetAutoApprovalRuleEnabled" | "RemoveAutoApprovalRule" => {
+            Access::Token(MANAGE_RULES)
+        }
+        "ClearTamperAlert" | "VerifyAuditLog" | "AddGuardian" | "RemoveGuardian"
+        | "ListGuardians" => Access::Token(MANAGE_SYSTEM),
+
+        _ => return None,
+    };
+
+    Some(access)
+}
+
+/// Classify an account; child wins over parent so a managed account cannot escalate
+pub fn classify(
+    config: &AccessConfig,
+    uid: u32,
+    username: &str,
+    groups: &[String],
+    child_accounts: &[String],
+) -> CallerClass {
+    let in_any = |wanted: &[String]| groups.iter().any(|group| wanted.contains(group));
+
+    if uid == 0
+        || uid == Uid::effective().as_raw()
+        || config.system_users.iter().any(|u| u == username)
+    {
+        CallerClass::System
+    } else if child_accounts.iter().any(|u| u == username) || in_any(&config.child_groups) {
+        CallerClass::Child
+    } else if in_any(&config.parent_groups) {
+        CallerClass::Parent
+    } else {
+        CallerClass::Other
+    }
+}
+
+/// Checks the caller of each D-Bus method against `method_access`
+pub struct CallerAuthorizer {
+    config: AccessConfig,
+    profile_manager: ProfileManager,
+}
+
+impl CallerAuthorizer {
+    pub fn new(config: &AccessConfig, profile_manager: ProfileManager) -> Self {
+        Self { config: config.clone(), profile_manager }
+    }
+
+    /// Check a call that carries no token
+    pub async fn authorize(&self, connection: &Connection, header: &Header<'_>) -> Result<()> {
+        self.authorize_call(connection, header, false).await.map(|_| ())
+    }
+
+    /// Check a token-guarded call and return the token to act with. A caller
+    /// without a token gets a session of its own for this call when it passes
+    /// the method's parent checks.
+    pub async fn authorize_token(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+        token: &str,
+    ) -> Result<CallerSession> {
+        if !token.is_empty() || !self.config.enforce {
+            // The profile manager checks the token's guardian role
+            return Ok(CallerSession::borrowed(token));
+        }
+
+        match self.authorize_call(connection, header, true).await? {
+            Some(caller) => {
+                let token = self.profile_manager.open_local_session(&caller.username).await;
+                Ok(CallerSession::local(token, self.profile_manager.clone()))
+            }
+            None => Ok(CallerSession::borrowed(token)),
+        }
+    }
+
+    /// Check a call reporting activity and tell whether the caller may name the
+    /// profile the activity belongs to. Only system components may; activity
+    /// from anyone else belongs to the profile its sender registered for.
+    pub async fn authorize_report(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+    ) -> Result<bool> {
+        let caller = self.authorize_call(connection, header, false).await?;
+        Ok(caller.is_none_or(|caller| caller.class == CallerClass::System))
+    }
+
+    /// Check a call reporting `event` and return the profile it belongs to.
+    /// System components may report any process; a monitor in a user's
+    /// session only processes of its own user.
+    pub async fn authorize_activity(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+        event: &ActivityEvent,
+    ) -> Result<Option<Uuid>> {
+        let caller = self.authorize_call(connection, header, false).await?;
+        if let Some(caller) = caller.filter(|caller| caller.class != CallerClass::System) {
+            if let Some(reason) = foreign_process(Path::new("/proc"), event, caller.uid) {
+                let member = header.member().map(|m| m.as_str()).unwrap_or_default();
+                self.profile_manager.log_denied(&caller.actor(), member, None, &reason).await;
+                return Err(DaemonError::NotAuthorized(format!("Access denied: {}", reason)).into());
+            }
+        }
+
+        let sender = header.sender().map(|sender| sender.as_str());
+        Ok(self.profile_manager.activity_event_profile(sender, event).await)
+    }
+
+    /// Check a call a child makes on its own behalf and return the child's
+    /// profile: the one the sender registered for, else the one of the
+    /// caller's account. Callers with neither are refused.
+    pub async fn authorize_own_profile(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+    ) -> Result<Uuid> {
+        self.authorize_call(connection, header, false).await?;
+
+        if let Some(sender) = header.sender() {
+            if let Some(component) =
+                self.profile_manager.registered_component(sender.as_str()).await
+            {
+                return Ok(component.profile_id);
+            }
+        }
+
+        if !self.config.enforce {
+            // Unenforced, calls act for the active profile
+            return match self.profile_manager.get_active_profile().await? {
+                Some(profile) => Ok(profile.id),
+                None => Err(DaemonError::NotFound("No active profile".to_string()).into()),
+            };
+        }
+
+        let caller = self.caller(connection, header).await?;
+        let profile = self.profile_manager.profile_resolver().resolve_uid(caller.uid).await?;
+        match profile.and_then(|id| Uuid::parse_str(&id).ok()) {
+            Some(profile_id) => Ok(profile_id),
+            None => {
+                let member = header.member().map(|m| m.as_str()).unwrap_or_default();
+                let reason = format!("caller uid {} has no child profile", caller.uid);
+                self.profile_manager.log_denied(&caller.actor(), member, None, &reason).await;
+                Err(DaemonError::NotAuthorized(format!("Access denied: {}", reason)).into())
+            }
+        }
+    }
+
+    async fn authorize_call(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+        tokenless: bool,
+    ) -> Result<Option<Caller>> {
+        if !self.config.enforce {
+            return Ok(None);
+        }
+
+        let member = header.member().map(|m| m.as_str()).unwrap_or_default();
+        let Some(access) = method_access(member) else {
+            return Err(DaemonError::NotAuthorized(format!(
+                "Access denied: {} has no access policy",
+                member
+            ))
+            .into());
+        };
+
+        // A token alone authorizes; the profile manager checks it
+        let access = match access {
+            Access::Token(_) if !tokenless => return Ok(None),
+            Access::Token(action) => Access::Parent(action),
+            access => access,
+        };
+        if access == Access::Any {
+            return Ok(None);
+        }
+
+        let caller = self.caller(connection, header).await?;
+        let allowed = match (access, caller.class) {
+            (_, CallerClass::System) => true,
+            (Access::Component, class) => class != CallerClass::Other,
+            (Access::Parent(_), CallerClass::Parent) => true,
+            (Access::Parent(action), CallerClass::Other) if self.config.polkit => {
+                self.polkit_allows(connection, &caller, action).await
+            }
+            _ => false,
+        };
+
+        if !allowed {
+            let pid = caller.pid.map(|pid| format!(" (pid {})", pid)).unwrap_or_default();
+            let reason = format!(
+                "{:?} caller uid {}{} may not call {}",
+                caller.class, caller.uid, pid, member
+            );
+            self.profile_manager.log_denied(&caller.actor(), member, None, &reason).await;
+            return Err(DaemonError::NotAuthorized(format!("Access denied: {}", reason)).into());
+        }
+
+        debug!("{} ({:?}) authorized for {}", caller.username, caller.class, member);
+        Ok(Some(caller))
+    }
+
+    /// Look up the sender's credentials and classify its account
+    pub async fn caller(&self, connection: &Connection, header: &Header<'_>) -> Result<Caller> {
+        let sender = header.sender().ok_or_else(|| {
+            DaemonError::NotAuthorized("Access denied: call has no sender".to_string())
+        })?;
+
+        let dbus = DBusProxy::new(connection).await?;
+        let credentials =
+            dbus.get_connection_credentials(BusName::Unique(sender.to_owned())).await?;
+        let uid = credentials.unix_user_id().ok_or_else(|| {
+            DaemonError::NotAuthorized(
+                "Access denied: bus did not report the caller's uid".to_string(),
+            )
+        })?;
+
+        let user = User::from_uid(Uid::from_raw(uid))?;
+        let username = user.as_ref().map(|u| u.name.clone()).unwrap_or_else(|| uid.to_string());
+        let groups = match credentials.unix_group_ids() {
+            Some(gids) => group_names(gids.iter().copied()),
+            None => match &user {
+                Some(user) => account_groups(user),
+                None => Vec::new(),
+            },
+        };
+
+        let child_accounts: Vec<String> = self
+            .profile_manager
+            .list_profiles()
+            .await?
+            .into_iter()
+            .filter_map(|profile| profile.username)
+            .collect();
+
+        Ok(Caller {
+            sender: sender.to_string(),
+            uid,
+            pid: credentials.process_id(),
+            class: classify(&self.config, uid, &username, &groups, &child_accounts),
+            username,
+        })
+    }
+
+    async fn polkit_allows(&self, connection: &Connection, caller: &Caller, action: &str) -> bool {
+        let authority = match PolkitAuthorityProxy::new(connection).await {
+            Ok(authority) => authority,
+            Err(e) => {
+                warn!("Polkit is not available: {}", e);
+                return false;
+            }
+        };
+
+        let subject_details = HashMap::from([("name", Value::from(caller.sender.as_str()))]);
+        let subject = ("system-bus-name", subject_details);
+        match authority
+            .check_authorization(
+                &subject,
+                action,
+                HashMap::new(),
+                POLKIT_ALLOW_USER_INTERACTION,
+                "",
+            )
+            .await
+        {
+            Ok((authorized, _, _)) => authorized,
+            Err(e) => {
+                warn!("Polkit check of {} for {} failed: {}", action, caller.username, e);
+                false
+            }
+        }
+    }
+}
+
+/// The token a guarded call acts with; a session opened for the caller ends with the call
+pub struct CallerSession {
+    token: String,
+    profile_manager: Option<ProfileManager>,
+}
+
+impl CallerSession {
+    fn borrowed(token: &str) -> Self {
+        Self { token: token.to_string(), profile_manager: None }
+    }
+
+    fn local(token: String, profile_manager: ProfileManager) -> Self {
+        Self { token, profile_manager: Some(profile_manager) }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Drop for CallerSession {
+    fn drop(&mut self) {
+        if let Some(profile_manager) = self.profile_manager.take() {
+            let token = std::mem::take(&mut self.token);
+            tokio::spawn(async move {
+                profile_manager.revoke_session(&token).await;
+            });
+        }
+    }
+}
+
+/// Why `event` is not the caller's to report, if the process it names does
+/// not belong to `uid`
+fn foreign_process(proc_root: &Path, event: &ActivityEvent, uid: u32) -> Option<String> {
+    let (pid, reported_uid) = match event {
+        ActivityEvent::WindowFocused { pid, .. } | ActivityEvent::NetworkConnection { pid, .. } => {
+            (*pid, None)
+        }
+        ActivityEvent::ProcessStarted { pid, uid, .. } => (*pid, Some(*uid)),
+        // The process is gone, so only the uid it ran as can be checked
+        ActivityEvent::ProcessExited { pid, uid: reported, .. } => {
+            return (*reported != uid)
+                .then(|| format!("uid {} reported pid {} of uid {}", uid, pid, reported));
+        }
+    };
+
+    if let Some(reported) = reported_uid.filter(|reported| *reported != uid) {
+        return Some(format!("uid {} reported pid {} of uid {}", uid, pid, reported));
+    }
+    match fs::metadata(proc_root.join(pid.to_string())) {
+        Ok(metadata) if metadata.uid() == uid => None,
+        Ok(metadata) => Some(format!("uid {} reported pid {} of uid {}", uid, pid, metadata.uid())),
+        Err(_) => Some(format!("uid {} reported pid {}, which is not running", uid, pid)),
+    }
+}
+
+fn group_names(gids: impl Iterator<Item = u32>) -> Vec<String> {
+    gids.filter_map(|gid| Group::from_gid(Gid::from_raw(gid)).ok().flatten())
+        .map(|group| group.name)
+        .collect()
+}
+
+fn account_groups(user: &User) -> Vec<String> {
+    let Ok(name) = std::ffi::CString::new(user.name.as_str()) else {
+        return Vec::new();
+    };
+    match nix::unistd::getgrouplist(&name, user.gid) {
+        Ok(gids) => group_names(gids.into_iter().map(|gid| gid.as_raw())),
+        Err(e) => {
+            warn!("Failed to look up groups of {}: {}", user.name, e);
+            Vec::new()
+        }
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait PolkitAuthority {
+    fn check_authorization(
+        &self,
+        subject: &(&str, HashMap<&str, Value<'_>>),
+        action_id: &str,
+        details: HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, String>)>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_callers() {
+        let config = AccessConfig::default();
+        let children = vec!["alice".to_string()];
+        let parents = vec!["dots-family-parents".to_string()];
+
+        assert_eq!(classify(&config, 0, "root", &[], &children), CallerClass::System);
+        assert_eq!(classify(&config, 990, "dots-family", &[], &children), CallerClass::System);
+        assert_eq!(classify(&config, 991, "dots-filter", &[], &children), CallerClass::System);
+        assert_eq!(classify(&config, 1001, "mum", &parents, &children), CallerClass::Parent);
+        assert_eq!(classify(&config, 1002, "bob", &[], &children), CallerClass::Other);
+
+        // A child added to the parents group is still a child
+        assert_eq!(classify(&config, 1003, "alice", &parents, &children), CallerClass::Child);
+        let child_group = vec!["dots-family-children".to_string()];
+        assert_eq!(classify(&config, 1004, "carol", &child_group, &[]), CallerClass::Child);
+    }
+
+    #[test]
+    fn test_every_daemon_method_has_an_access_policy() {
+        let xml = dots_family_proto::daemon::introspection_xml();
+        for line in xml.lines() {
+            if let Some(rest) = line.trim().strip_prefix("<method name=\"") {
+                let member = &rest[..rest.find('"').unwrap()];
+                assert!(method_access(member).is_some(), "{} has no access policy", member);
+            }
+        }
+    }
+
+    #[test]
+    fn test_profile_changes_need_a_parent() {
+        assert_eq!(
+            method_access("SetActiveProfile"),
+            Some(Access::Parent(actions::MANAGE_PROFILES))
+        );
+        assert_eq!(method_access("CreateProfile"), Some(Access::Parent(actions::MANAGE_PROFILES)));
+        assert_eq!(method_access("LockSession"), Some(Access::System));
+        assert_eq!(method_access("Unknown"), None);
+    }
+
+    #[test]
+    fn test_components_report_only_their_own_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("4242")).unwrap();
+        let owner = fs::metadata(dir.path()).unwrap().uid();
+        let started = |uid| ActivityEvent::ProcessStarted {
+            pid: 4242,
+            executable: "/usr/bin/firefox".to_string(),
+            args: Vec::new(),
+            uid,
+            parent_pids: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+        };
+        let focused = |pid| ActivityEvent::WindowFocused {
+            pid,
+            app_id: "firefox".to_string(),
+            window_title: String::new(),
+            timestamp: std::time::SystemTime::now(),
+        };
+
+        assert_eq!(foreign_process(dir.path(), &started(owner), owner), None);
+        assert_eq!(foreign_process(dir.path(), &focused(4242), owner), None);
+
+        // Someone else's process, a claimed uid that is not the caller's, or
+        // a process that does not exist
+        assert!(foreign_process(dir.path(), &focused(4242), owner + 1).is_some());
+        assert!(foreign_process(dir.path(), &started(owner + 1), owner).is_some());
+        assert!(foreign_process(dir.path(), &focused(4343), owner).is_some());
+
+        let exited = ActivityEvent::ProcessExited {
+            pid: 4343,
+            uid: owner + 1,
+            timestamp: std::time::SystemTime::now(),
+        };
+        assert!(foreign_process(dir.path(), &exited, owner).is_some());
+    }
+}
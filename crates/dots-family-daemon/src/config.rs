@@ -7,6 +7,17 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+/// Used for keys and state when no config directory is known, e.g. for a
+/// system service without `$HOME`. Never a world-writable location.
+const SYSTEM_STATE_DIR: &str = "/var/lib/dots-family";
+
+/// Default directory for the daemon's keys and state
+fn state_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|dir| dir.join("dots-family"))
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_STATE_DIR))
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct DaemonConfig {
     #[serde(default)]
@@ -20,23 +31,66 @@ pub struct DaemonConfig {
 
     #[serde(default)]
     pub dry_run: Option<bool>,
+
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+
+    #[serde(default)]
+    pub time_bank: TimeBankConfig,
+
+    #[serde(default)]
+    pub tamper: TamperConfig,
+
+    #[serde(default)]
+    pub clock: ClockConfig,
+
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    #[serde(default)]
+    pub access: AccessConfig,
+
+    #[serde(default)]
+    pub http_api: HttpApiConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub path: String,
-    pub encryption_key: Option<String>,
+    /// Encrypt the database with SQLCipher, keyed from the parent password
+    pub encrypt: bool,
+    /// Where the derived key is kept so the daemon can start unattended
+    pub key_store: KeyStore,
+    /// Root-only keyfile used when `key_store` is `keyfile`
+    pub key_file: String,
+    /// Salt the key is derived with; not secret, but needed to re-derive the key
+    pub salt_file: String,
+}
+
+/// Where the database key is cached between daemon starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStore {
+    /// Root's kernel user keyring; survives daemon restarts but not reboots
+    Keyring,
+    /// A root-only file next to the database
+    Keyfile,
 }
 
 impl Default for DatabaseConfig {
     fn default() -> Self {
-        let path = std::env::var("DOTS_FAMILY_DB_PATH").unwrap_or_else(|_| {
-            let config_dir =
-                dirs::config_dir().unwrap_or_else(|| PathBuf::from("/tmp")).join("dots-family");
-            config_dir.join("family.db").to_string_lossy().to_string()
-        });
+        let config_dir = state_dir();
+        let path = std::env::var("DOTS_FAMILY_DB_PATH")
+            .unwrap_or_else(|_| config_dir.join("family.db").to_string_lossy().to_string());
 
-        Self { path, encryption_key: None }
+        Self {
+            path,
+            encrypt: true,
+            key_store: KeyStore::Keyfile,
+            key_file: config_dir.join("family.db.key").to_string_lossy().to_string(),
+            salt_file: config_dir.join("family.db.salt").to_string_lossy().to_string(),
+        }
     }
 }
 
@@ -62,6 +116,194 @@ impl Default for DbusConfig {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// Minutes a request may stay pending before it expires
+    pub request_ttl_minutes: u32,
+    /// How often to sweep for expired requests (seconds)
+    pub expiry_check_interval_seconds: u64,
+    /// System directories whose .desktop files give app categories for
+    /// auto-approval rules; never list a directory a child can write to
+    pub application_dirs: Vec<String>,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            request_ttl_minutes: 60,
+            expiry_check_interval_seconds: 60,
+            application_dirs: vec![
+                "/run/current-system/sw/share/applications".to_string(),
+                "/var/lib/flatpak/exports/share/applications".to_string(),
+                "/usr/local/share/applications".to_string(),
+                "/usr/share/applications".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimeBankConfig {
+    /// Carry unused daily minutes over into the time bank
+    pub rollover_enabled: bool,
+    /// Maximum rolled-over minutes a profile can hold at once
+    pub rollover_cap_minutes: u32,
+    /// Days before rolled-over minutes expire
+    pub rollover_expiry_days: u32,
+}
+
+impl Default for TimeBankConfig {
+    fn default() -> Self {
+        Self { rollover_enabled: false, rollover_cap_minutes: 60, rollover_expiry_days: 7 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// Where the trusted clock anchor is kept across restarts and reboots
+    pub state_path: String,
+    /// Local NTP server to resync from, e.g. "127.0.0.1:123"
+    pub ntp_server: Option<String>,
+    /// Resync from the hardware clock when no NTP server answers
+    pub use_rtc: bool,
+    /// How often to resync and persist the anchor (seconds)
+    pub resync_interval_seconds: u64,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            state_path: state_dir().join("clock.json").to_string_lossy().to_string(),
+            ntp_server: None,
+            use_rtc: false,
+            resync_interval_seconds: 900,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Ed25519 key used to seal the audit log chain head (PKCS#8, created on first start)
+    pub sealing_key_path: String,
+    /// How often to seal the chain head when new entries were logged (seconds)
+    pub seal_interval_seconds: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sealing_key_path: state_dir().join("audit-seal.key").to_string_lossy().to_string(),
+            seal_interval_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AccessConfig {
+    /// Check the caller of every D-Bus method; only turn off for development
+    pub enforce: bool,
+    /// Accounts whose calls count as system components (root always does);
+    /// by default those the shipped daemon and web filter units run as
+    pub system_users: Vec<String>,
+    /// Members of these groups are parents
+    pub parent_groups: Vec<String>,
+    /// Members of these groups are children, as are the accounts of profiles
+    pub child_groups: Vec<String>,
+    /// Ask polkit for parent actions when the caller is not a parent by group
+    pub polkit: bool,
+}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        Self {
+            enforce: true,
+            system_users: vec!["dots-family".to_string(), "dots-filter".to_string()],
+            parent_groups: vec!["dots-family-parents".to_string()],
+            child_groups: vec!["dots-family-children".to_string()],
+            polkit: true,
+        }
+    }
+}
+
+/// Local HTTP/JSON management API, served next to D-Bus for web and phone clients
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpApiConfig {
+    pub enabled: bool,
+    /// A loopback address, or with `allow_lan` one on a LAN interface
+    pub bind_address: String,
+    /// Listen on a non-loopback address; only accepted together with TLS
+    pub allow_lan: bool,
+    /// PEM certificate chain to serve HTTPS with
+    pub tls_cert_path: Option<String>,
+    /// PEM private key of `tls_cert_path`
+    pub tls_key_path: Option<String>,
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1:7878".to_string(),
+            allow_lan: false,
+            tls_cert_path: None,
+            tls_key_path: None,
+        }
+    }
+}
+
+/// What to do when tampering is detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperResponse {
+    NotifyParent,
+    /// Lock the affected child's session
+    Lock,
+    /// Block every app launch and all network access until a parent clears it
+    DenyAll,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TamperConfig {
+    pub enabled: bool,
+    pub check_interval_seconds: u64,
+    /// Wall clock drift from boot time beyond which the clock counts as moved
+    pub clock_jump_tolerance_seconds: u64,
+    /// Executables that must keep running once they have started
+    pub watched_processes: Vec<String>,
+    /// Proxy URL graphical programs of children must be started with
+    pub expected_proxy: Option<String>,
+    /// Extra executable names to treat as virtual machines
+    pub vm_executables: Vec<String>,
+    /// Hole in a monitor's activity timeline beyond which activity counts as withheld
+    pub max_activity_gap_seconds: u64,
+    pub responses: Vec<TamperResponse>,
+}
+
+impl Default for TamperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_seconds: 30,
+            clock_jump_tolerance_seconds: 120,
+            watched_processes: vec![
+                "dots-family-monitor".to_string(),
+                "dots-family-filter".to_string(),
+            ],
+            expected_proxy: None,
+            vm_executables: Vec::new(),
+            max_activity_gap_seconds: 900,
+            responses: vec![TamperResponse::NotifyParent],
+        }
+    }
+}
+
 impl DaemonConfig {
     /// Default configuration file path
     pub fn default_config_path() -> PathBuf {
@@ -80,7 +322,14 @@ impl DaemonConfig {
     /// Load configuration from file, creating default if it doesn't exist
     pub fn load() -> Result<Self> {
         let config_path = Self::default_config_path();
-        Self::load_from_path(&config_path)
+        let mut config = Self::load_from_path(&config_path)?;
+
+        // The service unit picks the database location
+        if let Ok(path) = std::env::var("DOTS_FAMILY_DB_PATH") {
+            config.database.path = path;
+        }
+
+        Ok(config)
     }
 
     /// Load configuration from a specific path
@@ -158,7 +407,7 @@ impl DaemonConfig {
             warn!("No parent password hash configured - authentication will fail");
         }
 
-        if self.database.encryption_key.is_none() {
+        if !self.database.encrypt {
             warn!("Database encryption is disabled - family data will be stored in plaintext");
         }
 
@@ -166,3 +415,36 @@ impl DaemonConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_sections_keep_their_defaults() {
+        let config: DaemonConfig = toml::from_str(
+            r#"
+            [time_bank]
+            rollover_enabled = true
+
+            [clock]
+            ntp_server = "127.0.0.1:123"
+
+            [audit]
+            seal_interval_seconds = 60
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.time_bank.rollover_enabled);
+        assert_eq!(config.time_bank.rollover_cap_minutes, 60);
+        assert_eq!(config.time_bank.rollover_expiry_days, 7);
+
+        assert_eq!(config.clock.ntp_server.as_deref(), Some("127.0.0.1:123"));
+        assert_eq!(config.clock.state_path, ClockConfig::default().state_path);
+        assert_eq!(config.clock.resync_interval_seconds, 900);
+
+        assert_eq!(config.audit.seal_interval_seconds, 60);
+        assert_eq!(config.audit.sealing_key_path, AuditConfig::default().sealing_key_path);
+    }
+}
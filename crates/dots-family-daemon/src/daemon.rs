@@ -1,7 +1,13 @@
-use std::sync::Arc;
+use std::{collections::HashSet, path::Path, sync::Arc};
 
 use anyhow::{Context, Result};
+use dots_family_common::types::AlertSeverity;
 use dots_family_db::{migrations, Database, DatabaseConfig};
+use dots_family_proto::{
+    daemon::{FamilyDaemonInterface, OBJECT_PATH},
+    events::{ActivityEvent, Event},
+};
+use futures::StreamExt;
 use tokio::{
     signal,
     sync::RwLock,
@@ -11,17 +17,27 @@ use tracing::{debug, error, info, warn};
 use zbus::ConnectionBuilder;
 
 use crate::{
-    config::DaemonConfig,
+    audit_sealer::AuditSealer,
+    config::{self, DaemonConfig, TamperResponse},
+    database_key::DatabaseKeys,
     dbus_impl::FamilyDaemonService,
     ebpf::{EbpfHealth, EbpfManager},
+    ebpf_event_processor::EbpfEventProcessor,
     edge_case_handler::EdgeCaseHandler,
     enforcement::EnforcementEngine,
+    event_bus::EventBus,
+    event_subscriptions::{EventSubscriptions, SESSION_RECHECK},
+    exec_guard::ExecGuard,
+    http_api,
     monitoring_service::MonitoringService,
     notification_manager::NotificationManager,
     policy_engine::PolicyEngine,
     profile_manager::ProfileManager,
+    profile_resolver::ProfileResolver,
+    tamper_detector::{ChildAccount, TamperDetector, TamperEvent, TamperKind},
     time_window_enforcement_task::TimeWindowEnforcementTask,
     time_window_manager::TimeWindowManager,
+    trusted_clock::{self, TrustedClock},
 };
 
 pub struct Daemon {
@@ -29,6 +45,11 @@ pub struct Daemon {
     policy_engine: RwLock<PolicyEngine>,
     enforcement_engine: RwLock<EnforcementEngine>,
     time_window_manager: RwLock<Option<Arc<TimeWindowManager>>>,
+    audit_sealer: RwLock<Option<Arc<AuditSealer>>>,
+    /// Children whose network the deny-all tamper response cut off
+    tamper_network_blocks: RwLock<HashSet<String>>,
+    events: EventBus,
+    event_subscriptions: EventSubscriptions,
     config: DaemonConfig,
 }
 
@@ -46,10 +67,24 @@ impl Daemon {
             policy_engine: RwLock::new(policy_engine),
             enforcement_engine: RwLock::new(enforcement_engine),
             time_window_manager: RwLock::new(None),
+            audit_sealer: RwLock::new(None),
+            tamper_network_blocks: RwLock::new(HashSet::new()),
+            events: EventBus::new(),
+            event_subscriptions: EventSubscriptions::new(),
             config,
         })
     }
 
+    /// Live events for dashboards, sent on the bus as `FamilyEvent`
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Connections that asked for `FamilyEvent`
+    pub fn event_subscriptions(&self) -> &EventSubscriptions {
+        &self.event_subscriptions
+    }
+
     pub async fn set_ebpf_manager(&self, manager: EbpfManager) {
         let mut ebpf_manager = self.ebpf_manager.write().await;
         *ebpf_manager = Some(manager);
@@ -94,19 +129,38 @@ impl Daemon {
         let time_window_manager = self.time_window_manager.read().await;
         time_window_manager.clone()
     }
+
+    pub async fn set_audit_sealer(&self, sealer: Arc<AuditSealer>) {
+        let mut audit_sealer = self.audit_sealer.write().await;
+        *audit_sealer = Some(sealer);
+    }
+
+    pub async fn get_audit_sealer(&self) -> Option<Arc<AuditSealer>> {
+        let audit_sealer = self.audit_sealer.read().await;
+        audit_sealer.clone()
+    }
+
+    pub async fn record_tamper_network_block(&self, username: &str) {
+        self.tamper_network_blocks.write().await.insert(username.to_string());
+    }
+
+    /// Children blocked by the tamper response, forgetting them
+    pub async fn take_tamper_network_blocks(&self) -> Vec<String> {
+        self.tamper_network_blocks.write().await.drain().collect()
+    }
 }
 
-pub async fn initialize_database() -> Result<Database> {
+pub async fn initialize_database(config: &config::DatabaseConfig) -> Result<Database> {
     info!("Initializing database");
 
-    let database_url =
-        std::env::var("DOTS_FAMILY_DB_PATH").unwrap_or_else(|_| "/tmp/dots-family.db".to_string());
+    let database_url = config.path.clone();
 
     migrations::create_database_if_not_exists(&database_url)
         .await
         .context("Failed to create database")?;
 
-    let database_config = DatabaseConfig { path: database_url, encryption_key: None };
+    let encryption_key = database_key(config).await?;
+    let database_config = DatabaseConfig { path: database_url, encryption_key };
     let database = Database::new(database_config).await.context("Failed to connect to database")?;
 
     migrations::run_migrations(database.pool()?).await.context("Failed to run migrations")?;
@@ -115,15 +169,78 @@ pub async fn initialize_database() -> Result<Database> {
     Ok(database)
 }
 
+/// Pick the key to open the database with, encrypting an unencrypted database first
+async fn database_key(config: &config::DatabaseConfig) -> Result<Option<String>> {
+    if !config.encrypt {
+        warn!("Database encryption is disabled in the configuration");
+        return Ok(None);
+    }
+
+    let plaintext = Database::is_plaintext(&config.path)?;
+    let Some(key) = DatabaseKeys::new(config).cached_key()? else {
+        if !plaintext && std::fs::metadata(&config.path)?.len() > 0 {
+            anyhow::bail!(
+                "Database {} is encrypted but its key is not in the {:?} key store",
+                config.path,
+                config.key_store
+            );
+        }
+        info!("Database will be encrypted once a parent signs in");
+        return Ok(None);
+    };
+
+    if !Database::sqlcipher_available().await? {
+        error!("SQLite was built without SQLCipher - the database stays unencrypted");
+        return Ok(None);
+    }
+
+    if plaintext {
+        info!("Encrypting existing database {}", config.path);
+        let encrypt_config =
+            DatabaseConfig { path: config.path.clone(), encryption_key: Some(key.clone()) };
+        Database::encrypt_plaintext(&encrypt_config)
+            .await
+            .context("Failed to encrypt existing database")?;
+    }
+
+    Ok(Some(key))
+}
+
 pub async fn run() -> Result<()> {
     info!("Initializing daemon");
 
-    // Initialize database first
-    let database = initialize_database().await?;
-
     let daemon = Arc::new(Daemon::new().await?);
     info!("Daemon with policy engine initialized successfully");
 
+    let database = initialize_database(&daemon.config.database).await?;
+
+    // Trusted clock - policy decisions must not follow changes to the system clock
+    let trusted_clock = trusted_clock::install(TrustedClock::load(daemon.config.clock.clone()));
+    let resync_interval = daemon.config.clock.resync_interval_seconds.max(1);
+    tokio::spawn(async move {
+        let mut interval_timer = interval(Duration::from_secs(resync_interval));
+
+        loop {
+            interval_timer.tick().await;
+
+            match tokio::task::spawn_blocking(|| trusted_clock.sync_from_sources()).await {
+                Ok(Ok(Some(source))) => debug!("Trusted clock verified against {:?}", source),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => warn!("Trusted clock resync failed: {}", e),
+                Err(e) => warn!("Trusted clock resync task failed: {}", e),
+            }
+            if let Err(e) = trusted_clock.persist() {
+                warn!("Failed to persist trusted clock: {}", e);
+            }
+        }
+    });
+
+    // Drop network rules left behind if a previous daemon did not shut down cleanly
+    let network_enforcer = daemon.get_enforcement_engine().await.network();
+    if let Err(e) = network_enforcer.cleanup().await {
+        warn!("Failed to clear stale network rules: {}", e);
+    }
+
     let ebpf_manager = match EbpfManager::new().await {
         Ok(mut manager) => {
             info!("eBPF manager initialized successfully");
@@ -150,11 +267,46 @@ pub async fn run() -> Result<()> {
         }
     };
 
-    let monitoring_service = MonitoringService::new().await?;
-
     // Create ProfileManager with shared database instance
+    let finishing_up_database = database.clone();
+    let ebpf_event_database = database.clone();
     let profile_manager = ProfileManager::new(&daemon.config, database).await?;
 
+    // Memory and disk I/O events are stored against the child that caused them
+    let event_processor =
+        EbpfEventProcessor::new(ebpf_event_database, profile_manager.profile_resolver());
+    let monitoring_service =
+        MonitoringService::new().await?.with_event_processor(Arc::new(event_processor));
+
+    // Audit log sealing - signs the hash chain head so a rewritten log is detectable
+    let profile_manager_audit = profile_manager.clone();
+    let audit_sealer = match AuditSealer::load_or_create(&daemon.config.audit) {
+        Ok(sealer) => {
+            let sealer = Arc::new(sealer);
+            daemon.set_audit_sealer(sealer.clone()).await;
+            Some(sealer)
+        }
+        Err(e) => {
+            error!("Failed to load audit sealing key, audit log will not be sealed: {}", e);
+            None
+        }
+    };
+    if let Some(sealer) = audit_sealer.clone() {
+        let profile_manager_seal = profile_manager.clone();
+        let seal_interval = daemon.config.audit.seal_interval_seconds.max(1);
+        tokio::spawn(async move {
+            let mut interval_timer = interval(Duration::from_secs(seal_interval));
+
+            loop {
+                interval_timer.tick().await;
+
+                if let Err(e) = profile_manager_seal.seal_audit_log(&sealer).await {
+                    warn!("Failed to seal audit log: {}", e);
+                }
+            }
+        });
+    }
+
     let service = FamilyDaemonService::new_with_daemon(
         &daemon.config,
         monitoring_service.clone(),
@@ -168,6 +320,20 @@ pub async fn run() -> Result<()> {
 
     monitoring_service.start().await?;
 
+    // Process exec/exit stream - feeds the policy engine as processes start
+    if let Some(mut process_events) = monitoring_service.take_process_events().await {
+        let daemon_clone_processes = daemon.clone();
+        let resolver = profile_manager.profile_resolver();
+        tokio::spawn(async move {
+            while let Some(activity) = process_events.recv().await {
+                if is_child_process(&resolver, &activity).await {
+                    enforce_activity(&daemon_clone_processes, activity).await;
+                }
+            }
+            warn!("Process event stream ended");
+        });
+    }
+
     let conn_builder = if daemon.config.dbus.use_session_bus {
         info!("Using session bus for development mode");
         ConnectionBuilder::session()?
@@ -178,7 +344,7 @@ pub async fn run() -> Result<()> {
 
     let conn = conn_builder
         .name(daemon.config.dbus.service_name.as_str())?
-        .serve_at("/org/dots/FamilyDaemon", service)?
+        .serve_at(OBJECT_PATH, FamilyDaemonInterface(service))?
         .build()
         .await?;
 
@@ -189,6 +355,125 @@ pub async fn run() -> Result<()> {
         warn!("eBPF monitoring service not available - running in degraded mode");
     }
 
+    // Local HTTP/JSON management API - forwards to the D-Bus interface above
+    if daemon.config.http_api.enabled {
+        let http_api_config = daemon.config.http_api.clone();
+        let conn_http = conn.clone();
+        let service_name_http = daemon.config.dbus.service_name.clone();
+        let profile_manager_http = profile_manager.clone();
+        let events_http = daemon.events().clone();
+        tokio::spawn(async move {
+            if let Err(e) = http_api::serve(
+                &http_api_config,
+                conn_http,
+                &service_name_http,
+                profile_manager_http,
+                events_http,
+            )
+            .await
+            {
+                error!("HTTP API stopped: {:#}", e);
+            }
+        });
+    }
+
+    // Event forwarding task - sends each published event as a FamilyEvent
+    // signal to the subscribers who may see it
+    let conn_events = conn.clone();
+    let service_name_events = daemon.config.dbus.service_name.clone();
+    let mut events = daemon.events().subscribe();
+    let subscriptions = daemon.event_subscriptions().clone();
+    let profile_manager_events = profile_manager.clone();
+    tokio::spawn(async move {
+        let mut recheck_timer = interval(SESSION_RECHECK);
+        loop {
+            tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => {
+                        if let Err(e) =
+                            subscriptions.emit(&conn_events, &service_name_events, &event).await
+                        {
+                            warn!("Failed to emit FamilyEvent signal: {}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("FamilyEvent signals fell behind, {} events not sent", missed);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = recheck_timer.tick() => subscriptions.recheck(&profile_manager_events).await,
+            }
+        }
+    });
+
+    // Component registry cleanup - a registration ends with its bus connection
+    let mut owner_changes =
+        zbus::fdo::DBusProxy::new(&conn).await?.receive_name_owner_changed().await?;
+    let profile_manager_components = profile_manager.clone();
+    let subscriptions_components = daemon.event_subscriptions().clone();
+    tokio::spawn(async move {
+        while let Some(change) = owner_changes.next().await {
+            let Ok(args) = change.args() else {
+                continue;
+            };
+            if args.new_owner().is_none() {
+                profile_manager_components.unregister_component(args.name()).await;
+                subscriptions_components.unsubscribe(args.name());
+            }
+        }
+    });
+
+    // Approval request expiry task - expires stale requests and tells the child
+    let conn_expiry = conn.clone();
+    let profile_manager_expiry = profile_manager.clone();
+    let service_name_expiry = daemon.config.dbus.service_name.clone();
+    let events_expiry = daemon.events().clone();
+    let expiry_interval = daemon.config.approvals.expiry_check_interval_seconds.max(1);
+    tokio::spawn(async move {
+        let mut interval_timer = interval(Duration::from_secs(expiry_interval));
+
+        loop {
+            interval_timer.tick().await;
+
+            if let Err(e) = expire_approval_requests(
+                &profile_manager_expiry,
+                &conn_expiry,
+                &service_name_expiry,
+                &events_expiry,
+            )
+            .await
+            {
+                warn!("Approval request expiry error: {}", e);
+            }
+        }
+    });
+
+    // Time bank task - expires lapsed credits, rolls over unused time and
+    // resets the banked extension once a new day starts
+    let profile_manager_time_bank = profile_manager.clone();
+    let daemon_time_bank = daemon.clone();
+    tokio::spawn(async move {
+        let mut interval_timer = interval(Duration::from_secs(900));
+
+        loop {
+            interval_timer.tick().await;
+
+            if let Err(e) = profile_manager_time_bank.process_time_bank().await {
+                warn!("Time bank processing error: {}", e);
+            }
+
+            match profile_manager_time_bank.get_time_credits_spent_today().await {
+                Ok(minutes) => {
+                    daemon_time_bank.get_policy_engine_mut().await.set_banked_minutes_today(minutes)
+                }
+                Err(e) => warn!("Failed to load spent time credits: {}", e),
+            }
+        }
+    });
+
+    let profile_manager_time_windows = profile_manager.clone();
+    let profile_manager_exec = profile_manager.clone();
+    let profile_manager_tamper = profile_manager.clone();
     let conn_clone = conn.clone();
     let daemon_clone_enforcement = daemon.clone();
     tokio::spawn(async move {
@@ -202,6 +487,7 @@ pub async fn run() -> Result<()> {
                 &profile_manager,
                 &conn_clone,
                 &daemon_clone_enforcement.config.dbus.service_name,
+                daemon_clone_enforcement.events(),
                 &mut last_warning_time,
             )
             .await
@@ -227,31 +513,174 @@ pub async fn run() -> Result<()> {
         }
     });
 
-    // Time window enforcement task - runs every 60 seconds
+    // Time window enforcement task - runs every 15 seconds to drive the escalation ladder
     info!("Starting time window enforcement task");
     let notification_manager = NotificationManager::new();
-    let time_window_manager = Arc::new(TimeWindowManager::new(notification_manager));
+    let time_window_manager =
+        Arc::new(TimeWindowManager::new(notification_manager).with_database(finishing_up_database));
 
     // Set time window manager in daemon so it's accessible from DBus service
     daemon.set_time_window_manager(time_window_manager.clone()).await;
 
-    let enforcement_for_time_windows =
-        Arc::new(RwLock::new(EnforcementEngine::new(daemon.config.dry_run.unwrap_or(false))));
+    let enforcement_for_time_windows = Arc::new(RwLock::new(EnforcementEngine::with_network(
+        daemon.config.dry_run.unwrap_or(false),
+        network_enforcer.clone(),
+    )));
     let time_window_task =
         TimeWindowEnforcementTask::new(time_window_manager.clone(), enforcement_for_time_windows);
 
+    let conn_time_windows = conn.clone();
+    let service_name_time_windows = daemon.config.dbus.service_name.clone();
     tokio::spawn(async move {
-        let mut interval_timer = interval(Duration::from_secs(60));
+        let mut interval_timer = interval(Duration::from_secs(15));
 
         loop {
             interval_timer.tick().await;
 
-            if let Err(e) = time_window_task.check_and_enforce().await {
+            if let Err(e) = escalate_time_enforcement(
+                &time_window_task,
+                &profile_manager_time_windows,
+                &conn_time_windows,
+                &service_name_time_windows,
+            )
+            .await
+            {
                 error!("Time window enforcement error: {}", e);
             }
         }
     });
 
+    // Network policy task - drops rules for child sessions that have ended
+    let network_enforcer_prune = network_enforcer.clone();
+    tokio::spawn(async move {
+        let mut interval_timer = interval(Duration::from_secs(60));
+
+        loop {
+            interval_timer.tick().await;
+
+            if let Err(e) = network_enforcer_prune.prune_ended_sessions().await {
+                warn!("Network rule cleanup error: {}", e);
+            }
+        }
+    });
+
+    // Exec guard - denies launches of blocked apps before they start
+    let (exec_guard, mut blocked_launches) = ExecGuard::new(daemon.config.dry_run.unwrap_or(false));
+    match profile_manager_exec.list_profiles().await {
+        Ok(profiles) => {
+            exec_guard.sync_profiles(&profiles);
+            network_enforcer.sync_profiles(&profiles).await;
+        }
+        Err(e) => warn!("Failed to load exec rules: {}", e),
+    }
+    if let Err(e) = exec_guard.start() {
+        warn!("Exec guard unavailable, blocked apps will be closed after launch: {}", e);
+    }
+
+    // Rules follow profile edits at once; the timer picks up accounts
+    // created after their profile and sessions started since
+    let profile_manager_exec_rules = profile_manager_exec.clone();
+    let network_enforcer_rules = network_enforcer.clone();
+    let mut profile_changes = profile_manager_exec.subscribe_profile_changes();
+    tokio::spawn(async move {
+        let mut interval_timer = interval(Duration::from_secs(30));
+
+        loop {
+            tokio::select! {
+                _ = interval_timer.tick() => {}
+                Ok(()) = profile_changes.changed() => {}
+            }
+
+            match profile_manager_exec_rules.list_profiles().await {
+                Ok(profiles) => {
+                    exec_guard.sync_profiles(&profiles);
+                    network_enforcer_rules.sync_profiles(&profiles).await;
+                }
+                Err(e) => warn!("Exec rule refresh error: {}", e),
+            }
+        }
+    });
+
+    let daemon_clone_exec = daemon.clone();
+    tokio::spawn(async move {
+        while let Some(blocked) = blocked_launches.recv().await {
+            if let Err(e) = profile_manager_exec.record_blocked_launch(&blocked).await {
+                warn!("Failed to record blocked launch: {}", e);
+            }
+
+            let app = blocked.path.rsplit('/').next().unwrap_or(&blocked.path);
+            if let Ok(profile_id) = uuid::Uuid::parse_str(&blocked.profile_id) {
+                daemon_clone_exec.events().publish(Event::ApplicationBlocked {
+                    profile_id,
+                    application: app.to_string(),
+                    reason: format!("Matched exec rule {}", blocked.rule),
+                    timestamp: trusted_clock::now(),
+                });
+            }
+            let enforcement = daemon_clone_exec.get_enforcement_engine().await;
+            if let Err(e) = enforcement
+                .notify_user("Access Blocked", &format!("{} is blocked by your profile", app))
+                .await
+            {
+                debug!("Failed to notify about blocked launch: {}", e);
+            }
+        }
+    });
+
+    // Tamper detection - reports attempts to disable or get around monitoring
+    if daemon.config.tamper.enabled {
+        let mut tamper_detector = TamperDetector::new(daemon.config.tamper.clone());
+        tamper_detector.watch_database(Path::new(&daemon.config.database.path));
+
+        let notifications = NotificationManager::new();
+        let conn_tamper = conn.clone();
+        let daemon_clone_tamper = daemon.clone();
+        let check_interval = daemon.config.tamper.check_interval_seconds.max(1);
+        tokio::spawn(async move {
+            let mut interval_timer = interval(Duration::from_secs(check_interval));
+
+            loop {
+                interval_timer.tick().await;
+
+                let children = match profile_manager_tamper.list_profiles().await {
+                    Ok(profiles) => ChildAccount::from_profiles(&profiles),
+                    Err(e) => {
+                        warn!("Failed to load child accounts for tamper checks: {}", e);
+                        Vec::new()
+                    }
+                };
+                let monitor_ok =
+                    profile_manager_tamper.check_monitor_health().await.unwrap_or(true);
+
+                let mut events = tamper_detector.check(&children, monitor_ok);
+                // Holes in a monitor's reports, e.g. from a cut offline spool
+                for gap in profile_manager_tamper.take_activity_gaps().await {
+                    let profile_id = gap.profile_id.to_string();
+                    events.push(TamperEvent {
+                        kind: TamperKind::ActivityGap,
+                        child: children.iter().find(|c| c.profile_id == profile_id).cloned(),
+                        detail: format!(
+                            "{} reported no activity from {} to {}",
+                            gap.component, gap.from, gap.to
+                        ),
+                    });
+                }
+
+                for event in events {
+                    respond_to_tamper(
+                        &daemon_clone_tamper,
+                        &profile_manager_tamper,
+                        &conn_tamper,
+                        &notifications,
+                        &children,
+                        &event,
+                    )
+                    .await;
+                }
+            }
+        });
+    }
+
     info!("Daemon running with policy enforcement, waiting for shutdown signal...");
 
     #[cfg(unix)]
@@ -281,6 +710,20 @@ pub async fn run() -> Result<()> {
     monitoring_service.stop().await?;
     info!("Monitoring service stopped");
 
+    if let Err(e) = trusted_clock.persist() {
+        error!("Failed to persist trusted clock: {}", e);
+    }
+
+    if let Some(sealer) = audit_sealer {
+        if let Err(e) = profile_manager_audit.seal_audit_log(&sealer).await {
+            error!("Failed to seal audit log: {}", e);
+        }
+    }
+
+    if let Err(e) = network_enforcer.cleanup().await {
+        error!("Failed to remove network rules: {}", e);
+    }
+
     info!("Daemon shutdown complete");
 
     Ok(())
@@ -290,6 +733,7 @@ async fn enforce_time_limits(
     profile_manager: &ProfileManager,
     conn: &zbus::Connection,
     service_name: &str,
+    events: &EventBus,
     last_warning_time: &mut Option<u32>,
 ) -> Result<()> {
     if let Ok(Some(profile)) = profile_manager.get_active_profile().await {
@@ -301,6 +745,11 @@ async fn enforce_time_limits(
                         remaining, profile.name
                     );
 
+                    events.publish(Event::TimeLimitWarning {
+                        profile_id: profile.id,
+                        minutes_remaining: remaining,
+                        timestamp: trusted_clock::now(),
+                    });
                     if let Err(e) = emit_time_warning(conn, service_name, remaining).await {
                         warn!("Failed to emit time warning signal: {}", e);
                     } else {
@@ -309,6 +758,11 @@ async fn enforce_time_limits(
                 } else if remaining == 0 && *last_warning_time != Some(0) {
                     warn!("Time limit exceeded for profile: {}", profile.name);
 
+                    events.publish(Event::TimeLimitReached {
+                        profile_id: profile.id,
+                        timestamp: trusted_clock::now(),
+                    });
+
                     if let Err(e) = emit_time_warning(conn, service_name, 0).await {
                         warn!("Failed to emit time exceeded signal: {}", e);
                     } else {
@@ -327,65 +781,228 @@ async fn emit_time_warning(
     service_name: &str,
     minutes_remaining: u32,
 ) -> Result<()> {
-    conn.emit_signal(
-        None::<()>,
-        "/org/dots/FamilyDaemon",
-        service_name,
-        "TimeLimitWarning",
-        &minutes_remaining,
-    )
-    .await?;
+    conn.emit_signal(None::<()>, OBJECT_PATH, service_name, "TimeLimitWarning", &minutes_remaining)
+        .await?;
 
     info!("Emitted TimeLimitWarning signal: {} minutes", minutes_remaining);
     Ok(())
 }
 
+async fn escalate_time_enforcement(
+    time_window_task: &TimeWindowEnforcementTask,
+    profile_manager: &ProfileManager,
+    conn: &zbus::Connection,
+    service_name: &str,
+) -> Result<()> {
+    let remaining_daily_minutes = match profile_manager.get_active_profile().await {
+        Ok(Some(_)) => Some(profile_manager.get_remaining_time().await?),
+        _ => None,
+    };
+    time_window_task.set_remaining_daily_minutes(remaining_daily_minutes).await;
+
+    if let Some(stage) = time_window_task.check_and_enforce().await? {
+        conn.emit_signal(
+            None::<()>,
+            OBJECT_PATH,
+            service_name,
+            "EnforcementStageChanged",
+            &(stage.as_str(), stage.seconds_remaining()),
+        )
+        .await?;
+
+        info!("Emitted EnforcementStageChanged signal: {}", stage.as_str());
+    }
+    Ok(())
+}
+
+async fn expire_approval_requests(
+    profile_manager: &ProfileManager,
+    conn: &zbus::Connection,
+    service_name: &str,
+    events: &EventBus,
+) -> Result<()> {
+    for request in profile_manager.expire_stale_requests().await? {
+        let message = request.response_reason.unwrap_or_default();
+
+        events.publish(Event::ApprovalRequestResolved {
+            request_id: request.id.clone(),
+            status: request.status.clone(),
+            timestamp: trusted_clock::now(),
+        });
+
+        // The request is already expired in the database, so keep going on failure
+        if let Err(e) = conn
+            .emit_signal(
+                None::<()>,
+                OBJECT_PATH,
+                service_name,
+                "ApprovalRequestResolved",
+                &(request.id.as_str(), request.status.as_str(), message.as_str()),
+            )
+            .await
+        {
+            warn!("Failed to emit ApprovalRequestResolved signal for {}: {}", request.id, e);
+            continue;
+        }
+
+        info!("Emitted ApprovalRequestResolved signal: {} expired", request.id);
+    }
+    Ok(())
+}
+
+/// Announce a tamper attempt, audit it and run the configured responses
+async fn respond_to_tamper(
+    daemon: &Arc<Daemon>,
+    profile_manager: &ProfileManager,
+    conn: &zbus::Connection,
+    notifications: &NotificationManager,
+    children: &[ChildAccount],
+    event: &TamperEvent,
+) {
+    let reason = event.reason();
+
+    if let Err(e) = conn
+        .emit_signal(
+            None::<()>,
+            OBJECT_PATH,
+            daemon.config.dbus.service_name.as_str(),
+            "TamperDetected",
+            &reason.as_str(),
+        )
+        .await
+    {
+        warn!("Failed to emit TamperDetected signal: {}", e);
+    }
+
+    if let Err(e) = profile_manager.record_tamper(event).await {
+        error!("Failed to audit tamper event: {}", e);
+    }
+
+    // Events not tied to one child affect every child
+    let affected: Vec<&ChildAccount> = match &event.child {
+        Some(child) => vec![child],
+        None => children.iter().collect(),
+    };
+
+    for response in &daemon.config.tamper.responses {
+        match response {
+            TamperResponse::NotifyParent => {
+                let notification = NotificationManager::create_system_alert_notification(
+                    AlertSeverity::Critical,
+                    format!("Tampering detected: {}", reason),
+                );
+                if let Err(e) = notifications.send_notification(notification).await {
+                    warn!("Failed to notify parent about tampering: {}", e);
+                }
+            }
+            TamperResponse::Lock => {
+                let enforcement = daemon.get_enforcement_engine().await;
+                for child in &affected {
+                    if let Err(e) = enforcement.lock_session(Some(&child.username)).await {
+                        warn!("Failed to lock session of {}: {}", child.username, e);
+                    }
+                }
+            }
+            TamperResponse::DenyAll => {
+                daemon.get_policy_engine_mut().await.set_deny_all(&reason);
+
+                let enforcement = daemon.get_enforcement_engine().await;
+                for child in &affected {
+                    // A block already in force, e.g. outside allowed hours,
+                    // is not ours to lift when the alert is cleared
+                    if enforcement.network_blocked(&child.username).await.unwrap_or(false) {
+                        continue;
+                    }
+                    match enforcement.block_all_network(&child.username).await {
+                        Ok(()) => daemon.record_tamper_network_block(&child.username).await,
+                        Err(e) => warn!("Failed to block network of {}: {}", child.username, e),
+                    }
+                }
+            }
+        }
+    }
+}
+
 async fn process_activity_enforcement(
     daemon: &Arc<Daemon>,
     monitoring_service: &MonitoringService,
 ) -> Result<()> {
     let activities = monitoring_service.get_recent_activities().await?;
 
-    if activities.is_empty() {
-        return Ok(());
+    for activity in activities {
+        enforce_activity(daemon, activity).await;
+    }
+
+    Ok(())
+}
+
+/// Whether a kernel-reported process event belongs to a managed child
+/// account. Everything else on the machine - root, system services, the
+/// parent's own session - is not subject to child policy.
+async fn is_child_process(resolver: &ProfileResolver, activity: &ActivityEvent) -> bool {
+    let profile = match activity {
+        ActivityEvent::ProcessStarted { pid, uid, .. } => {
+            resolver.resolve_process(*pid, *uid).await
+        }
+        ActivityEvent::ProcessExited { uid, .. } => resolver.resolve_uid(*uid).await,
+        _ => return true,
+    };
+
+    match profile {
+        Ok(profile) => profile.is_some(),
+        Err(e) => {
+            warn!("Failed to resolve process owner: {}", e);
+            false
+        }
     }
+}
 
+/// Run one activity through the policy engine and enforce the decision
+async fn enforce_activity(daemon: &Arc<Daemon>, activity: ActivityEvent) {
     let mut policy_engine = daemon.get_policy_engine_mut().await;
     let enforcement_engine = daemon.get_enforcement_engine().await;
 
-    for activity in activities {
+    // Exits are bookkeeping, not activity
+    if !matches!(activity, ActivityEvent::ProcessExited { .. }) {
         policy_engine.update_activity();
+    }
 
-        let activity_clone = activity.clone();
-        let (app_id, pid) = match &activity_clone {
-            dots_family_proto::events::ActivityEvent::WindowFocused { app_id, pid, .. } => {
-                (Some(app_id.as_str()), Some(*pid))
-            }
-            dots_family_proto::events::ActivityEvent::ProcessStarted {
-                executable, pid, ..
-            } => (Some(executable.split('/').next_back().unwrap_or(executable)), Some(*pid)),
-            _ => (None, None),
-        };
-
-        match policy_engine.process_activity(activity).await {
-            Ok(decision) => {
-                if decision.blocked {
-                    warn!("Blocking activity: {} - {}", decision.action, decision.reason);
-
-                    if let Err(e) =
-                        enforcement_engine.enforce_policy_decision(&decision, app_id, pid).await
-                    {
-                        error!("Failed to enforce policy decision: {}", e);
-                    }
-                } else {
-                    debug!("Allowing activity: {} - {}", decision.action, decision.reason);
+    let (app_id, pid) = match &activity {
+        ActivityEvent::WindowFocused { app_id, pid, .. } => (Some(app_id.clone()), Some(*pid)),
+        ActivityEvent::ProcessStarted { executable, pid, .. } => {
+            (Some(executable.split('/').next_back().unwrap_or(executable).to_string()), Some(*pid))
+        }
+        _ => (None, None),
+    };
+
+    match policy_engine.process_activity(activity).await {
+        Ok(decision) => {
+            if decision.blocked {
+                warn!("Blocking activity: {} - {}", decision.action, decision.reason);
+
+                if let (Some(application), Some(profile)) =
+                    (app_id.clone(), policy_engine.get_active_profile().await)
+                {
+                    daemon.events().publish(Event::ApplicationBlocked {
+                        profile_id: profile.id,
+                        application,
+                        reason: decision.reason.clone(),
+                        timestamp: trusted_clock::now(),
+                    });
                 }
-            }
-            Err(e) => {
-                error!("Policy processing error: {}", e);
+
+                if let Err(e) = enforcement_engine
+                    .enforce_policy_decision(&decision, app_id.as_deref(), pid)
+                    .await
+                {
+                    error!("Failed to enforce policy decision: {}", e);
+                }
+            } else {
+                debug!("Allowing activity: {} - {}", decision.action, decision.reason);
             }
         }
+        Err(e) => {
+            error!("Policy processing error: {}", e);
+        }
     }
-
-    Ok(())
 }
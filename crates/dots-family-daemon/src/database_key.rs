@@ -0,0 +1,226 @@
+use std::{
+    ffi::CString,
+    fs,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use dots_family_common::security::EncryptionKey;
+use rand::RngCore;
+use secrecy::SecretString;
+
+use crate::config::{DatabaseConfig, KeyStore};
+
+const SALT_LEN: usize = 16;
+
+/// Description of the database key in root's user keyring
+const KEYRING_DESCRIPTION: &str = "dots-family:database";
+
+// keyutils constants, not exported by libc
+const KEY_SPEC_USER_KEYRING: libc::c_long = -4;
+const KEYCTL_SEARCH: libc::c_long = 10;
+const KEYCTL_READ: libc::c_long = 11;
+
+/// Derives the database key from the parent password and caches it.
+///
+/// The key is PBKDF2 over the password with a random salt stored next to
+/// the database. The derived key is cached in the configured key store so
+/// the daemon can open the database at boot without the password.
+pub struct DatabaseKeys {
+    config: DatabaseConfig,
+}
+
+impl DatabaseKeys {
+    pub fn new(config: &DatabaseConfig) -> Self {
+        Self { config: config.clone() }
+    }
+
+    /// Derive the key with the stored salt, creating the salt on first use
+    pub fn derive(&self, password: &SecretString) -> Result<EncryptionKey> {
+        let salt = match self.load_salt()? {
+            Some(salt) => salt,
+            None => {
+                let salt = Self::new_salt();
+                self.save_salt(&salt)?;
+                salt
+            }
+        };
+
+        Ok(Self::derive_with_salt(password, &salt))
+    }
+
+    pub fn derive_with_salt(password: &SecretString, salt: &[u8]) -> EncryptionKey {
+        EncryptionKey::derive_from_password(password, Some(salt))
+    }
+
+    pub fn new_salt() -> Vec<u8> {
+        let mut salt = vec![0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        salt
+    }
+
+    pub fn load_salt(&self) -> Result<Option<Vec<u8>>> {
+        let path = Path::new(&self.config.salt_file);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let salt = fs::read(path).with_context(|| format!("Failed to read salt: {:?}", path))?;
+        if salt.len() != SALT_LEN {
+            return Err(anyhow!("Corrupt database key salt: {:?}", path));
+        }
+        Ok(Some(salt))
+    }
+
+    pub fn save_salt(&self, salt: &[u8]) -> Result<()> {
+        write_private(Path::new(&self.config.salt_file), salt)
+    }
+
+    /// The cached key in SQLCipher form, if one was stored
+    pub fn cached_key(&self) -> Result<Option<String>> {
+        match self.config.key_store {
+            KeyStore::Keyring => keyring_read(),
+            KeyStore::Keyfile => {
+                let path = Path::new(&self.config.key_file);
+                if !path.exists() {
+                    return Ok(None);
+                }
+                let key = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read database key: {:?}", path))?;
+                Ok(Some(key.trim().to_string()))
+            }
+        }
+    }
+
+    pub fn cache_key(&self, key: &EncryptionKey) -> Result<()> {
+        let key = key.as_sqlcipher_key();
+        match self.config.key_store {
+            KeyStore::Keyring => keyring_write(&key),
+            KeyStore::Keyfile => write_private(Path::new(&self.config.key_file), key.as_bytes()),
+        }
+    }
+}
+
+/// Write a root-only file, replacing it atomically
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    let _ = fs::remove_file(&tmp);
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)
+        .with_context(|| format!("Failed to create {:?}", tmp))?;
+    file.write_all(contents).with_context(|| format!("Failed to write {:?}", tmp))?;
+    file.sync_all()?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to save {:?}", path))?;
+
+    Ok(())
+}
+
+fn keyring_write(key: &str) -> Result<()> {
+    let key_type = CString::new("user")?;
+    let description = CString::new(KEYRING_DESCRIPTION)?;
+
+    // Replaces the payload when the key already exists
+    let serial = unsafe {
+        libc::syscall(
+            libc::SYS_add_key,
+            key_type.as_ptr(),
+            description.as_ptr(),
+            key.as_ptr(),
+            key.len(),
+            KEY_SPEC_USER_KEYRING,
+        )
+    };
+    if serial < 0 {
+        return Err(anyhow!(
+            "Failed to store database key in keyring: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    Ok(())
+}
+
+fn keyring_read() -> Result<Option<String>> {
+    let key_type = CString::new("user")?;
+    let description = CString::new(KEYRING_DESCRIPTION)?;
+
+    let serial = unsafe {
+        libc::syscall(
+            libc::SYS_keyctl,
+            KEYCTL_SEARCH,
+            KEY_SPEC_USER_KEYRING,
+            key_type.as_ptr(),
+            description.as_ptr(),
+            0,
+        )
+    };
+    if serial < 0 {
+        let error = std::io::Error::last_os_error();
+        return match error.raw_os_error() {
+            Some(libc::ENOKEY) | Some(libc::EKEYEXPIRED) | Some(libc::EKEYREVOKED) => Ok(None),
+            _ => Err(anyhow!("Failed to search keyring for database key: {}", error)),
+        };
+    }
+
+    let mut buffer = vec![0u8; 256];
+    let len = unsafe {
+        libc::syscall(libc::SYS_keyctl, KEYCTL_READ, serial, buffer.as_mut_ptr(), buffer.len())
+    };
+    if len < 0 {
+        return Err(anyhow!(
+            "Failed to read database key from keyring: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    buffer.truncate(len as usize);
+
+    let key = String::from_utf8(buffer).map_err(|_| anyhow!("Corrupt database key in keyring"))?;
+    Ok(Some(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_keyfile_round_trip_and_salt_reuse() {
+        let dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            path: dir.path().join("family.db").to_string_lossy().to_string(),
+            encrypt: true,
+            key_store: KeyStore::Keyfile,
+            key_file: dir.path().join("family.db.key").to_string_lossy().to_string(),
+            salt_file: dir.path().join("family.db.salt").to_string_lossy().to_string(),
+        };
+        let keys = DatabaseKeys::new(&config);
+        let password = SecretString::new("parent-password".to_string().into());
+
+        assert!(keys.cached_key().unwrap().is_none());
+
+        // The stored salt makes the key reproducible from the password
+        let key = keys.derive(&password).unwrap();
+        assert_eq!(keys.derive(&password).unwrap().as_sqlcipher_key(), key.as_sqlcipher_key());
+
+        keys.cache_key(&key).unwrap();
+        assert_eq!(keys.cached_key().unwrap(), Some(key.as_sqlcipher_key()));
+
+        let mode = fs::metadata(&config.key_file).unwrap().permissions();
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&mode) & 0o777, 0o600);
+
+        // A new salt gives a different key for the same password
+        let rotated = DatabaseKeys::derive_with_salt(&password, &DatabaseKeys::new_salt());
+        assert_ne!(rotated.as_sqlcipher_key(), key.as_sqlcipher_key());
+    }
+}
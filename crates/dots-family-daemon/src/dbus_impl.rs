@@ -1,19 +1,38 @@
 use std::sync::Arc;
 
-use anyhow::Result;
-use dots_family_proto::events::ActivityEvent;
+use dots_family_common::Permission;
+use dots_family_db::queries::audit::AuditChainReport;
+use dots_family_proto::{
+    daemon::{FamilyDaemonInterface, FamilyDaemonServer, MethodCall},
+    error::DaemonError,
+    events::{ActivityEvent, Event, EventFilter},
+    payload::{
+        self, ActivityReceipt, ApprovalRequestList, AuditBreak, AuditReport, AutoApprovalRuleList,
+        EventList, ExceptionList, FinishingUpGrant, GuardianList, NextTimeWindow, PayloadError,
+        PolicyVerdict, ProfileList, TimeWindowAccess,
+    },
+};
 use tracing::{debug, error, info, warn};
-use zbus::interface;
 
 use crate::{
-    config::DaemonConfig, daemon::Daemon, enforcement::EnforcementEngine,
-    monitoring_service::MonitoringService, profile_manager::ProfileManager,
+    caller_auth::CallerAuthorizer,
+    config::DaemonConfig,
+    daemon::Daemon,
+    enforcement::EnforcementEngine,
+    event_bus::EventBus,
+    event_subscriptions::{visible_to, EventSubscriptions},
+    monitoring_service::MonitoringService,
+    profile_manager::ProfileManager,
+    trusted_clock,
 };
 
 pub struct FamilyDaemonService {
     profile_manager: ProfileManager,
     monitoring_service: MonitoringService,
     daemon: Option<Arc<Daemon>>,
+    authorizer: CallerAuthorizer,
+    events: EventBus,
+    subscriptions: EventSubscriptions,
 }
 
 impl FamilyDaemonService {
@@ -22,315 +41,498 @@ impl FamilyDaemonService {
         config: &DaemonConfig,
         monitoring_service: MonitoringService,
         database: dots_family_db::Database,
-    ) -> Result<Self> {
+    ) -> anyhow::Result<Self> {
         let profile_manager = ProfileManager::new(config, database).await?;
-        Ok(Self { profile_manager, monitoring_service, daemon: None })
+        let authorizer = CallerAuthorizer::new(&config.access, profile_manager.clone());
+        Ok(Self {
+            profile_manager,
+            monitoring_service,
+            daemon: None,
+            authorizer,
+            events: EventBus::new(),
+            subscriptions: EventSubscriptions::new(),
+        })
     }
 
     pub async fn new_with_daemon(
-        _config: &DaemonConfig,
+        config: &DaemonConfig,
         monitoring_service: MonitoringService,
         daemon: Arc<Daemon>,
         profile_manager: ProfileManager,
-    ) -> Result<Self> {
-        Ok(Self { profile_manager, monitoring_service, daemon: Some(daemon) })
+    ) -> anyhow::Result<Self> {
+        let authorizer = CallerAuthorizer::new(&config.access, profile_manager.clone());
+        let events = daemon.events().clone();
+        let subscriptions = daemon.event_subscriptions().clone();
+        Ok(Self {
+            profile_manager,
+            monitoring_service,
+            daemon: Some(daemon),
+            authorizer,
+            events,
+            subscriptions,
+        })
     }
-}
 
-#[interface(name = "org.dots.FamilyDaemon")]
-impl FamilyDaemonService {
-    async fn get_active_profile(&self) -> String {
+    /// ID of the profile whose session is active, for events about it
+    async fn active_profile_id(&self) -> Option<uuid::Uuid> {
         match self.profile_manager.get_active_profile().await {
-            Ok(Some(profile)) => {
-                serde_json::to_string(&profile).unwrap_or_else(|_| "{}".to_string())
-            }
-            Ok(None) => r#"{"error":"no_active_profile"}"#.to_string(),
+            Ok(profile) => profile.map(|profile| profile.id),
             Err(e) => {
-                warn!("Failed to get active profile: {}", e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                debug!("No active profile for event: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Tell subscribers of `profile_id` that one of its windows took focus.
+    /// An event that can't be tied to a profile is not published.
+    fn publish_focus(&self, profile_id: Option<uuid::Uuid>, app_id: &str) {
+        let Some(profile_id) = profile_id else {
+            debug!("Not publishing focus of {}: no profile", app_id);
+            return;
+        };
+        self.events.publish(Event::AppFocused {
+            profile_id,
+            app_id: app_id.to_string(),
+            timestamp: trusted_clock::now(),
+        });
+    }
+
+    /// Push today's spent banked minutes into the policy engine's daily limit
+    async fn sync_banked_minutes(&self) {
+        if let Some(ref daemon) = self.daemon {
+            match self.profile_manager.get_time_credits_spent_today().await {
+                Ok(minutes) => {
+                    daemon.get_policy_engine_mut().await.set_banked_minutes_today(minutes)
+                }
+                Err(e) => warn!("Failed to load spent time credits: {}", e),
             }
         }
     }
+}
+
+/// Interface methods reply with a typed D-Bus error when they fail
+type Result<T> = std::result::Result<T, DaemonError>;
+
+/// The service as it is served on the bus; emits the interface's signals
+type Interface = FamilyDaemonInterface<FamilyDaemonService>;
+
+fn serialization_failed(error: impl std::fmt::Display) -> DaemonError {
+    DaemonError::Failed(format!("Serialization failed: {}", error))
+}
 
-    async fn check_application_allowed(&self, app_id: &str) -> bool {
-        match self.profile_manager.check_application_allowed(app_id).await {
-            Ok(allowed) => allowed,
+fn invalid_payload(what: &str, error: PayloadError) -> DaemonError {
+    DaemonError::InvalidArgument(format!("Invalid {}: {}", what, error))
+}
+
+/// The audit chain check as reported to clients
+fn audit_report(report: AuditChainReport) -> AuditReport {
+    AuditReport {
+        entries_checked: report.entries_checked,
+        legacy_entries: report.legacy_entries,
+        seals_checked: report.seals_checked,
+        head_entry_id: report.head_entry_id,
+        last_sealed_entry_id: report.last_sealed_entry_id,
+        breaks: report
+            .breaks
+            .into_iter()
+            .map(|entry| AuditBreak {
+                entry_id: entry.entry_id,
+                seal_id: entry.seal_id,
+                reason: entry.reason,
+            })
+            .collect(),
+    }
+}
+
+/// Turn an internal error into a D-Bus error reply, keeping its kind when it has one
+fn reply_error(error: anyhow::Error) -> DaemonError {
+    error.downcast::<DaemonError>().unwrap_or_else(|e| DaemonError::Failed(e.to_string()))
+}
+
+impl FamilyDaemonServer for FamilyDaemonService {
+    async fn get_active_profile(&self, call: &MethodCall<'_>) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        match self.profile_manager.get_active_profile().await {
+            Ok(Some(profile)) => payload::encode(&profile).map_err(serialization_failed),
+            Ok(None) => Err(DaemonError::NotFound("No active profile".to_string())),
             Err(e) => {
-                warn!("Failed to check application {}: {}", app_id, e);
-                false
+                warn!("Failed to get active profile: {}", e);
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn get_remaining_time(&self) -> u32 {
+    async fn check_application_allowed(&self, call: &MethodCall<'_>, app_id: &str) -> Result<bool> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        self.profile_manager.check_application_allowed(app_id).await.map_err(|e| {
+            warn!("Failed to check application {}: {}", app_id, e);
+            reply_error(e)
+        })
+    }
+
+    async fn get_remaining_time(&self, call: &MethodCall<'_>) -> Result<u32> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             let policy_engine = daemon.get_policy_engine().await;
-            policy_engine.get_remaining_screen_time().unwrap_or(0)
+            Ok(policy_engine.get_remaining_screen_time().unwrap_or(0))
         } else {
-            match self.profile_manager.get_remaining_time().await {
-                Ok(minutes) => minutes,
-                Err(e) => {
-                    warn!("Failed to get remaining time: {}", e);
-                    0
-                }
-            }
+            self.profile_manager.get_remaining_time().await.map_err(|e| {
+                warn!("Failed to get remaining time: {}", e);
+                reply_error(e)
+            })
         }
     }
 
-    async fn report_activity(&self, activity_json: &str) -> String {
-        match self.profile_manager.report_activity(activity_json).await {
-            Ok(()) => "success".to_string(),
+    async fn report_activity(&self, call: &MethodCall<'_>, activity_json: &str) -> Result<String> {
+        let trusted = self
+            .authorizer
+            .authorize_report(call.connection, call.header)
+            .await
+            .map_err(reply_error)?;
+
+        let sender = call.header.sender().map(|sender| sender.to_string());
+        match self.profile_manager.report_activity(activity_json, sender.as_deref(), trusted).await
+        {
+            Ok(()) => Ok("success".to_string()),
             Err(e) => {
                 warn!("Failed to report activity: {}", e);
-                format!("error:{}", e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn report_activity_event(&self, event_json: &str) -> String {
-        match serde_json::from_str::<ActivityEvent>(event_json) {
-            Ok(event) => {
-                info!("Received activity event: {:?}", event);
+    async fn report_activity_event(
+        &self,
+        call: &MethodCall<'_>,
+        event_json: &str,
+    ) -> Result<String> {
+        let event = payload::decode::<ActivityEvent>(event_json).map_err(|e| {
+            error!("Failed to parse activity event: {}", e);
+            invalid_payload("activity event", e)
+        })?;
+        let profile_id = self
+            .authorizer
+            .authorize_activity(call.connection, call.header, &event)
+            .await
+            .map_err(reply_error)?;
+        info!("Received activity event: {:?}", event);
 
-                match &event {
-                    ActivityEvent::WindowFocused { pid, app_id, window_title, .. } => {
-                        info!(
-                            "Window focused - PID: {}, App: {}, Title: {}",
-                            pid, app_id, window_title
-                        );
-                    }
-                    ActivityEvent::ProcessStarted { pid, executable, args, .. } => {
-                        info!(
-                            "Process started - PID: {}, Executable: {}, Args: {:?}",
-                            pid, executable, args
-                        );
-                    }
-                    ActivityEvent::NetworkConnection { pid, local_addr, remote_addr, .. } => {
-                        info!(
-                            "Network connection - PID: {}, Local: {}, Remote: {}",
-                            pid, local_addr, remote_addr
-                        );
+        match &event {
+            ActivityEvent::WindowFocused { pid, app_id, window_title, .. } => {
+                info!("Window focused - PID: {}, App: {}, Title: {}", pid, app_id, window_title);
+                self.publish_focus(profile_id, app_id);
+            }
+            ActivityEvent::ProcessStarted { pid, executable, args, .. } => {
+                info!(
+                    "Process started - PID: {}, Executable: {}, Args: {:?}",
+                    pid, executable, args
+                );
+            }
+            ActivityEvent::NetworkConnection { pid, local_addr, remote_addr, .. } => {
+                info!(
+                    "Network connection - PID: {}, Local: {}, Remote: {}",
+                    pid, local_addr, remote_addr
+                );
+            }
+            ActivityEvent::ProcessExited { pid, uid, .. } => {
+                info!("Process exited - PID: {}, UID: {}", pid, uid);
+            }
+        }
+
+        if let Some(ref daemon) = self.daemon {
+            let policy_engine = daemon.get_policy_engine().await;
+            match policy_engine.process_activity(event.clone()).await {
+                Ok(decision) => {
+                    info!("Policy decision: {:?}", decision);
+
+                    if !decision.blocked {
+                        if let ActivityEvent::WindowFocused { .. } = event {
+                            drop(policy_engine);
+                            let mut policy_engine_mut = daemon.get_policy_engine_mut().await;
+                            policy_engine_mut.update_activity();
+                        }
                     }
-                }
 
-                if let Some(ref daemon) = self.daemon {
-                    let policy_engine = daemon.get_policy_engine().await;
-                    match policy_engine.process_activity(event.clone()).await {
-                        Ok(decision) => {
-                            info!("Policy decision: {:?}", decision);
-
-                            if !decision.blocked {
-                                if let ActivityEvent::WindowFocused { .. } = event {
-                                    drop(policy_engine);
-                                    let mut policy_engine_mut =
-                                        daemon.get_policy_engine_mut().await;
-                                    policy_engine_mut.update_activity();
-                                }
-                            }
+                    if decision.blocked {
+                        warn!("Activity blocked by policy: {}", decision.reason);
 
-                            if decision.blocked {
-                                warn!("Activity blocked by policy: {}", decision.reason);
+                        let application = match &event {
+                            ActivityEvent::WindowFocused { app_id, .. } => Some(app_id.clone()),
+                            ActivityEvent::ProcessStarted { executable, .. } => Some(
+                                executable.rsplit('/').next().unwrap_or(executable).to_string(),
+                            ),
+                            _ => None,
+                        };
+                        if let (Some(application), Some(profile_id)) = (application, profile_id) {
+                            self.events.publish(Event::ApplicationBlocked {
+                                profile_id,
+                                application,
+                                reason: decision.reason.clone(),
+                                timestamp: trusted_clock::now(),
+                            });
+                        }
 
-                                if let Some(ref daemon) = self.daemon {
-                                    let enforcement_engine: tokio::sync::RwLockReadGuard<
-                                        '_,
-                                        EnforcementEngine,
-                                    > = daemon.get_enforcement_engine().await;
+                        if let Some(ref daemon) = self.daemon {
+                            let enforcement_engine: tokio::sync::RwLockReadGuard<
+                                '_,
+                                EnforcementEngine,
+                            > = daemon.get_enforcement_engine().await;
 
-                                    match &event {
-                                        ActivityEvent::WindowFocused { app_id, pid, .. } => {
-                                            warn!(
-                                                "Enforcing policy: closing window for app {} (PID: {})",
-                                                app_id, pid
-                                            );
-                                            if let Err(e) =
-                                                enforcement_engine.close_window(app_id, *pid).await
-                                            {
-                                                error!("Failed to close window: {}", e);
-                                            }
-                                            if let Err(e) = enforcement_engine.notify_user(
+                            match &event {
+                                ActivityEvent::WindowFocused { app_id, pid, .. } => {
+                                    warn!(
+                                        "Enforcing policy: closing window for app {} (PID: {})",
+                                        app_id, pid
+                                    );
+                                    if let Err(e) =
+                                        enforcement_engine.close_window(app_id, *pid).await
+                                    {
+                                        error!("Failed to close window: {}", e);
+                                    }
+                                    if let Err(e) = enforcement_engine.notify_user(
                                                 "Application Blocked",
                                                 &format!("Access to {} has been restricted by parental controls", app_id)
                                             ).await {
                                                 error!("Failed to send notification: {}", e);
                                             }
-                                        }
-                                        ActivityEvent::ProcessStarted {
-                                            executable, pid, ..
-                                        } => {
-                                            warn!(
-                                                "Enforcing policy: terminating process {} (PID: {})",
-                                                executable, pid
-                                            );
-                                            if let Err(e) = enforcement_engine
-                                                .terminate_process(*pid, &decision.reason)
-                                                .await
-                                            {
-                                                error!("Failed to terminate process: {}", e);
-                                            }
-                                            if let Err(e) = enforcement_engine.notify_user(
-                                                "Application Blocked", 
-                                                &format!("Starting {} has been blocked by parental controls", executable.split('/').next_back().unwrap_or("application"))
-                                            ).await {
-                                                error!("Failed to send notification: {}", e);
-                                            }
-                                        }
-                                        ActivityEvent::NetworkConnection {
-                                            remote_addr,
-                                            pid,
-                                            ..
-                                        } => {
-                                            warn!(
+                                }
+                                ActivityEvent::ProcessStarted { executable, pid, .. } => {
+                                    warn!(
+                                        "Enforcing policy: terminating process {} (PID: {})",
+                                        executable, pid
+                                    );
+                                    if let Err(e) = enforcement_engine
+                                        .terminate_process(*pid, &decision.reason)
+                                        .await
+                                    {
+                                        error!("Failed to terminate process: {}", e);
+                                    }
+                                    if let Err(e) = enforcement_engine
+                                        .notify_user(
+                                            "Application Blocked",
+                                            &format!(
+                                                "Starting {} has been blocked by parental controls",
+                                                executable
+                                                    .split('/')
+                                                    .next_back()
+                                                    .unwrap_or("application")
+                                            ),
+                                        )
+                                        .await
+                                    {
+                                        error!("Failed to send notification: {}", e);
+                                    }
+                                }
+                                ActivityEvent::NetworkConnection { remote_addr, pid, .. } => {
+                                    warn!(
                                                 "Enforcing policy: blocking network connection to {} from PID {}",
                                                 remote_addr, pid
                                             );
-                                            if let Err(e) = enforcement_engine
-                                                .block_network_connection(*pid, remote_addr)
-                                                .await
-                                            {
-                                                error!("Failed to block network connection: {}", e);
-                                            }
-                                            if let Err(e) = enforcement_engine.notify_user(
+                                    if let Err(e) = enforcement_engine
+                                        .block_network_connection(*pid, remote_addr)
+                                        .await
+                                    {
+                                        error!("Failed to block network connection: {}", e);
+                                    }
+                                    if let Err(e) = enforcement_engine.notify_user(
                                                 "Network Access Blocked",
                                                 &format!("Connection to {} has been blocked by parental controls", remote_addr)
                                             ).await {
                                                 error!("Failed to send notification: {}", e);
                                             }
-                                        }
-                                    }
-                                } else {
-                                    match &event {
-                                        ActivityEvent::WindowFocused { app_id, pid, .. } => {
-                                            warn!(
-                                                "Should terminate or hide window for app {} (PID: {})",
-                                                app_id, pid
-                                            );
-                                        }
-                                        ActivityEvent::ProcessStarted {
-                                            executable, pid, ..
-                                        } => {
-                                            warn!(
-                                                "Should terminate process {} (PID: {})",
-                                                executable, pid
-                                            );
-                                        }
-                                        ActivityEvent::NetworkConnection {
-                                            remote_addr,
-                                            pid,
-                                            ..
-                                        } => {
-                                            warn!(
-                                                "Should block network connection to {} from PID {}",
-                                                remote_addr, pid
-                                            );
-                                        }
-                                    }
                                 }
-
-                                serde_json::json!({
-                                    "status": "policy_blocked",
-                                    "action": decision.action,
-                                    "reason": decision.reason,
-                                    "blocked": decision.blocked
-                                })
-                                .to_string()
-                            } else {
-                                debug!("Activity allowed: {}", decision.reason);
-                                serde_json::json!({
-                                    "status": "success",
-                                    "action": decision.action,
-                                    "reason": decision.reason,
-                                    "blocked": decision.blocked
-                                })
-                                .to_string()
+                                ActivityEvent::ProcessExited { .. } => {}
+                            }
+                        } else {
+                            match &event {
+                                ActivityEvent::WindowFocused { app_id, pid, .. } => {
+                                    warn!(
+                                        "Should terminate or hide window for app {} (PID: {})",
+                                        app_id, pid
+                                    );
+                                }
+                                ActivityEvent::ProcessStarted { executable, pid, .. } => {
+                                    warn!("Should terminate process {} (PID: {})", executable, pid);
+                                }
+                                ActivityEvent::NetworkConnection { remote_addr, pid, .. } => {
+                                    warn!(
+                                        "Should block network connection to {} from PID {}",
+                                        remote_addr, pid
+                                    );
+                                }
+                                ActivityEvent::ProcessExited { .. } => {}
                             }
                         }
-                        Err(e) => {
-                            error!("Failed to process activity through policy engine: {}", e);
-                            serde_json::json!({
-                                "status": "policy_error",
-                                "error": e.to_string(),
-                                "blocked": false
-                            })
-                            .to_string()
-                        }
+
+                        payload::encode(&ActivityReceipt {
+                            status: "policy_blocked".to_string(),
+                            blocked: decision.blocked,
+                            action: Some(decision.action),
+                            reason: Some(decision.reason),
+                            error: None,
+                        })
+                        .map_err(serialization_failed)
+                    } else {
+                        debug!("Activity allowed: {}", decision.reason);
+                        payload::encode(&ActivityReceipt {
+                            status: "success".to_string(),
+                            blocked: decision.blocked,
+                            action: Some(decision.action),
+                            reason: Some(decision.reason),
+                            error: None,
+                        })
+                        .map_err(serialization_failed)
                     }
-                } else {
-                    warn!("Policy engine not available - allowing activity by default");
-                    serde_json::json!({
-                        "status": "success",
-                        "blocked": false,
-                        "reason": "Policy engine not available"
+                }
+                Err(e) => {
+                    error!("Failed to process activity through policy engine: {}", e);
+                    payload::encode(&ActivityReceipt {
+                        status: "policy_error".to_string(),
+                        blocked: false,
+                        action: None,
+                        reason: None,
+                        error: Some(e.to_string()),
                     })
-                    .to_string()
+                    .map_err(serialization_failed)
                 }
             }
-            Err(e) => {
-                error!("Failed to parse activity event JSON: {}", e);
-                format!("error:Invalid event JSON: {}", e)
-            }
+        } else {
+            warn!("Policy engine not available - allowing activity by default");
+            payload::encode(&ActivityReceipt {
+                status: "success".to_string(),
+                blocked: false,
+                action: None,
+                reason: Some("Policy engine not available".to_string()),
+                error: None,
+            })
+            .map_err(serialization_failed)
         }
     }
-    async fn ping(&self) -> bool {
+
+    async fn ping(&self, call: &MethodCall<'_>) -> Result<bool> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         debug!("Received ping from monitor");
-        true
+        Ok(true)
     }
 
-    async fn send_heartbeat(&self, monitor_id: &str) -> String {
+    async fn send_heartbeat(&self, call: &MethodCall<'_>, monitor_id: &str) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.send_heartbeat(monitor_id).await {
-            Ok(()) => "success".to_string(),
+            Ok(()) => Ok("success".to_string()),
             Err(e) => {
                 warn!("Failed to process heartbeat from {}: {}", monitor_id, e);
-                format!("error:{}", e)
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn register_component(&self, call: &MethodCall<'_>, kind: &str) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        // The account comes from the bus credentials, never from the component
+        let caller =
+            self.authorizer.caller(call.connection, call.header).await.map_err(reply_error)?;
+        match self
+            .profile_manager
+            .register_component(&caller.sender, kind, caller.uid, &caller.username)
+            .await
+        {
+            Ok(profile_id) => Ok(profile_id.to_string()),
+            Err(e) => {
+                warn!("Failed to register {} for uid {}: {}", kind, caller.uid, e);
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn list_profiles(&self) -> String {
+    async fn list_profiles(&self, call: &MethodCall<'_>) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.list_profiles().await {
-            Ok(profiles) => serde_json::to_string(&profiles).unwrap_or_else(|_| "[]".to_string()),
+            Ok(profiles) => {
+                payload::encode(&ProfileList { profiles }).map_err(serialization_failed)
+            }
             Err(e) => {
                 warn!("Failed to list profiles: {}", e);
-                format!("error:{}", e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn create_profile(&self, name: &str, age_group: &str, username: &str) -> String {
+    async fn create_profile(
+        &self,
+        call: &MethodCall<'_>,
+        name: &str,
+        age_group: &str,
+        username: &str,
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         let username_opt = if username.is_empty() { None } else { Some(username.to_string()) };
 
         match self.profile_manager.create_profile_with_username(name, age_group, username_opt).await
         {
-            Ok(profile_id) => profile_id,
+            Ok(profile_id) => Ok(profile_id),
             Err(e) => {
                 warn!("Failed to create profile: {}", e);
-                format!("error:{}", e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn authenticate_parent(&self, password: &str) -> String {
+    async fn authenticate_parent(&self, call: &MethodCall<'_>, password: &str) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.authenticate_parent(password).await {
-            Ok(token) => token,
+            Ok(token) => Ok(token),
             Err(e) => {
                 warn!("Authentication failed: {}", e);
-                format!("error:{}", e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn validate_session(&self, token: &str) -> bool {
-        self.profile_manager.validate_session(token).await
+    async fn authenticate_guardian(
+        &self,
+        call: &MethodCall<'_>,
+        name: &str,
+        password: &str,
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        match self.profile_manager.authenticate_guardian(name, password).await {
+            Ok(token) => Ok(token),
+            Err(e) => {
+                warn!("Authentication failed for guardian {}: {}", name, e);
+                Err(reply_error(e))
+            }
+        }
     }
 
-    async fn revoke_session(&self, token: &str) -> bool {
-        self.profile_manager.revoke_session(token).await
+    async fn validate_session(&self, call: &MethodCall<'_>, token: &str) -> Result<bool> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        Ok(self.profile_manager.validate_session(token).await)
     }
 
-    async fn set_active_profile(&self, profile_id: &str) {
+    async fn revoke_session(&self, call: &MethodCall<'_>, token: &str) -> Result<bool> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        Ok(self.profile_manager.revoke_session(token).await)
+    }
+
+    async fn set_active_profile(&self, call: &MethodCall<'_>, profile_id: &str) -> Result<()> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Err(e) = self.profile_manager._set_active_profile(profile_id).await {
             warn!("Failed to set active profile: {}", e);
-            return;
+            return Err(reply_error(e));
         }
 
         if let Some(ref daemon) = self.daemon {
@@ -357,34 +559,55 @@ impl FamilyDaemonService {
                 Err(e) => warn!("Failed to load profile for policy sync: {}", e),
             }
         }
+
+        Ok(())
     }
 
     async fn request_parent_permission(
         &self,
+        call: &MethodCall<'_>,
         request_type: &str,
         details: &str,
         token: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.request_parent_permission(request_type, details, token).await {
-            Ok(response) => response,
+            Ok(response) => Ok(response),
             Err(e) => {
                 warn!("Failed to process permission request: {}", e);
-                format!(r#"{{"error":"{}","status":"denied"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
     async fn request_command_approval(
         &self,
+        call: &MethodCall<'_>,
         command: &str,
         risk_level: &str,
         reasons: &str,
-    ) -> String {
-        match self.profile_manager.request_command_approval(command, risk_level, reasons).await {
-            Ok(response) => response,
+    ) -> Result<String> {
+        let profile_id = self
+            .authorizer
+            .authorize_own_profile(call.connection, call.header)
+            .await
+            .map_err(reply_error)?;
+
+        match self
+            .profile_manager
+            .request_command_approval(&profile_id.to_string(), command, risk_level, reasons)
+            .await
+        {
+            Ok(response) => Ok(response),
             Err(e) => {
                 warn!("Failed to process command approval request: {}", e);
-                format!(r#"{{"error":"{}","status":"denied"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
@@ -395,56 +618,106 @@ impl FamilyDaemonService {
 
     async fn create_exception(
         &self,
+        call: &MethodCall<'_>,
         exception_type: &str,
         reason: &str,
         duration_json: &str,
         token: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self
             .profile_manager
             .create_exception(exception_type, reason, duration_json, token)
             .await
         {
             Ok(exception_id) => {
-                format!(r#"{{"status":"success","exception_id":"{}"}}"#, exception_id)
+                self.events.publish(Event::ExceptionCreated {
+                    profile_id: self.active_profile_id().await,
+                    exception_id: exception_id.clone(),
+                    exception_type: exception_type.to_string(),
+                    timestamp: trusted_clock::now(),
+                });
+                Ok(format!(r#"{{"status":"success","exception_id":"{}"}}"#, exception_id))
             }
             Err(e) => {
                 warn!("Failed to create exception: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn list_active_exceptions(&self, profile_id: &str, token: &str) -> String {
+    async fn list_active_exceptions(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.list_active_exceptions(profile_id, token).await {
             Ok(exceptions) => {
-                serde_json::to_string(&exceptions).unwrap_or_else(|_| "[]".to_string())
+                payload::encode(&ExceptionList { exceptions }).map_err(serialization_failed)
             }
             Err(e) => {
                 warn!("Failed to list active exceptions: {}", e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn revoke_exception(&self, exception_id: &str, token: &str) -> String {
+    async fn revoke_exception(
+        &self,
+        call: &MethodCall<'_>,
+        exception_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.revoke_exception(exception_id, token).await {
-            Ok(()) => r#"{"status":"success"}"#.to_string(),
+            Ok(()) => {
+                self.events.publish(Event::ExceptionRevoked {
+                    exception_id: exception_id.to_string(),
+                    timestamp: trusted_clock::now(),
+                });
+                Ok(r#"{"status":"success"}"#.to_string())
+            }
             Err(e) => {
                 warn!("Failed to revoke exception: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn check_exception_applies(&self, exception_type: &str, resource_id: &str) -> bool {
-        match self.profile_manager.check_exception_applies(exception_type, resource_id).await {
-            Ok(applies) => applies,
-            Err(e) => {
+    async fn check_exception_applies(
+        &self,
+        call: &MethodCall<'_>,
+        exception_type: &str,
+        resource_id: &str,
+    ) -> Result<bool> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
+        self.profile_manager.check_exception_applies(exception_type, resource_id).await.map_err(
+            |e| {
                 warn!("Failed to check exception: {}", e);
-                false
-            }
-        }
+                reply_error(e)
+            },
+        )
     }
 
     // ============================================================================
@@ -453,80 +726,344 @@ impl FamilyDaemonService {
 
     async fn submit_approval_request(
         &self,
+        call: &MethodCall<'_>,
         request_type: &str,
         message: &str,
         details_json: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let profile_id = self
+            .authorizer
+            .authorize_own_profile(call.connection, call.header)
+            .await
+            .map_err(reply_error)?;
+
         match self
             .profile_manager
-            .submit_approval_request(request_type, message, details_json)
+            .submit_approval_request(&profile_id.to_string(), request_type, message, details_json)
             .await
         {
-            Ok(request_id) => {
-                format!(r#"{{"status":"success","request_id":"{}"}}"#, request_id)
+            Ok(submission) => {
+                if submission.request_status == "auto_approved" {
+                    self.sync_banked_minutes().await;
+                }
+                if submission.request_status == "pending" {
+                    self.events.publish(Event::ApprovalRequestCreated {
+                        profile_id: Some(profile_id),
+                        request_id: submission.request_id.clone(),
+                        request_type: request_type.to_string(),
+                        timestamp: trusted_clock::now(),
+                    });
+                    if let Err(e) = Interface::approval_request_created(
+                        call.signal_context,
+                        &submission.request_id,
+                        request_type,
+                    )
+                    .await
+                    {
+                        warn!("Failed to emit ApprovalRequestCreated signal: {}", e);
+                    }
+                } else {
+                    self.events.publish(Event::ApprovalRequestResolved {
+                        request_id: submission.request_id.clone(),
+                        status: submission.request_status.clone(),
+                        timestamp: trusted_clock::now(),
+                    });
+                    let explanation = submission.explanation.as_deref().unwrap_or_default();
+                    if let Err(e) = Interface::approval_request_resolved(
+                        call.signal_context,
+                        &submission.request_id,
+                        &submission.request_status,
+                        explanation,
+                    )
+                    .await
+                    {
+                        warn!("Failed to emit ApprovalRequestResolved signal: {}", e);
+                    }
+                }
+
+                payload::encode(&submission).map_err(serialization_failed)
             }
             Err(e) => {
                 warn!("Failed to submit approval request: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn list_pending_requests(&self, token: &str) -> String {
+    async fn list_pending_requests(&self, call: &MethodCall<'_>, token: &str) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.list_pending_requests(token).await {
-            Ok(requests) => serde_json::to_string(&requests).unwrap_or_else(|_| "[]".to_string()),
+            Ok(requests) => {
+                payload::encode(&ApprovalRequestList { requests }).map_err(serialization_failed)
+            }
             Err(e) => {
                 warn!("Failed to list pending requests: {}", e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
     async fn approve_request(
         &self,
+        call: &MethodCall<'_>,
         request_id: &str,
         response_message: &str,
         token: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.approve_request(request_id, response_message, token).await {
             Ok(exception_id) => {
-                format!(
+                self.sync_banked_minutes().await;
+                self.events.publish(Event::ApprovalRequestResolved {
+                    request_id: request_id.to_string(),
+                    status: "approved".to_string(),
+                    timestamp: trusted_clock::now(),
+                });
+                if let Err(e) = Interface::approval_request_resolved(
+                    call.signal_context,
+                    request_id,
+                    "approved",
+                    response_message,
+                )
+                .await
+                {
+                    warn!("Failed to emit ApprovalRequestResolved signal: {}", e);
+                }
+                Ok(format!(
                     r#"{{"status":"success","exception_id":"{}"}}"#,
                     exception_id.unwrap_or_default()
-                )
+                ))
             }
             Err(e) => {
                 warn!("Failed to approve request: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn deny_request(&self, request_id: &str, response_message: &str, token: &str) -> String {
+    async fn deny_request(
+        &self,
+        call: &MethodCall<'_>,
+        request_id: &str,
+        response_message: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.deny_request(request_id, response_message, token).await {
-            Ok(()) => r#"{"status":"success"}"#.to_string(),
+            Ok(()) => {
+                self.events.publish(Event::ApprovalRequestResolved {
+                    request_id: request_id.to_string(),
+                    status: "denied".to_string(),
+                    timestamp: trusted_clock::now(),
+                });
+                if let Err(e) = Interface::approval_request_resolved(
+                    call.signal_context,
+                    request_id,
+                    "denied",
+                    response_message,
+                )
+                .await
+                {
+                    warn!("Failed to emit ApprovalRequestResolved signal: {}", e);
+                }
+                Ok(r#"{"status":"success"}"#.to_string())
+            }
             Err(e) => {
                 warn!("Failed to deny request: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn get_monitoring_snapshot(&self) -> String {
+    // ============================================================================
+    // Auto-Approval Rule Methods
+    // ============================================================================
+
+    async fn list_auto_approval_rules(&self, call: &MethodCall<'_>, token: &str) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.list_auto_approval_rules(token).await {
+            Ok(rules) => {
+                payload::encode(&AutoApprovalRuleList { rules }).map_err(serialization_failed)
+            }
+            Err(e) => {
+                warn!("Failed to list auto-approval rules: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn add_auto_approval_rule(
+        &self,
+        call: &MethodCall<'_>,
+        rule_json: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.add_auto_approval_rule(rule_json, token).await {
+            Ok(rule_id) => Ok(format!(r#"{{"status":"success","rule_id":"{}"}}"#, rule_id)),
+            Err(e) => {
+                warn!("Failed to add auto-approval rule: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn set_auto_approval_rule_enabled(
+        &self,
+        call: &MethodCall<'_>,
+        rule_id: &str,
+        enabled: bool,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.set_auto_approval_rule_enabled(rule_id, enabled, token).await {
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
+            Err(e) => {
+                warn!("Failed to update auto-approval rule: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn remove_auto_approval_rule(
+        &self,
+        call: &MethodCall<'_>,
+        rule_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.remove_auto_approval_rule(rule_id, token).await {
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
+            Err(e) => {
+                warn!("Failed to remove auto-approval rule: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    // ============================================================================
+    // Time Bank Methods
+    // ============================================================================
+
+    async fn grant_time_credit(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        minutes: u32,
+        reason: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.grant_time_credit(profile_id, minutes, reason, token).await {
+            Ok(entry_id) => Ok(format!(r#"{{"status":"success","entry_id":"{}"}}"#, entry_id)),
+            Err(e) => {
+                warn!("Failed to grant time credit: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn get_time_bank(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.get_time_bank(profile_id, token).await {
+            Ok(bank) => payload::encode(&bank).map_err(serialization_failed),
+            Err(e) => {
+                warn!("Failed to get time bank: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn get_time_credit_balance(&self, call: &MethodCall<'_>) -> Result<u32> {
+        let profile_id = self
+            .authorizer
+            .authorize_own_profile(call.connection, call.header)
+            .await
+            .map_err(reply_error)?;
+
+        self.profile_manager.get_time_credit_balance(&profile_id.to_string()).await.map_err(|e| {
+            warn!("Failed to get time credit balance: {}", e);
+            reply_error(e)
+        })
+    }
+
+    async fn get_monitoring_snapshot(&self, call: &MethodCall<'_>) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.monitoring_service.get_monitoring_snapshot().await {
-            Ok(data) => serde_json::to_string(&data)
-                .unwrap_or_else(|_| r#"{"error":"serialization_failed"}"#.to_string()),
+            Ok(snapshot) => payload::encode(&snapshot).map_err(serialization_failed),
             Err(e) => {
                 warn!("Failed to get monitoring snapshot: {}", e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn get_ebpf_status(&self) -> (u32, bool, String) {
+    async fn get_ebpf_status(&self, call: &MethodCall<'_>) -> Result<(u32, bool, String)> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             if let Some(status) = daemon.get_ebpf_health().await {
-                (
+                Ok((
                     status.programs_loaded as u32,
                     status.all_healthy,
                     format!(
@@ -534,16 +1071,18 @@ impl FamilyDaemonService {
                         status.programs_loaded,
                         status.program_status.len()
                     ),
-                )
+                ))
             } else {
-                (0, false, "eBPF manager not available".to_string())
+                Ok((0, false, "eBPF manager not available".to_string()))
             }
         } else {
-            (0, false, "eBPF status not yet connected".to_string())
+            Ok((0, false, "eBPF status not yet connected".to_string()))
         }
     }
 
-    async fn check_app_policy(&self, app_id: &str) -> String {
+    async fn check_app_policy(&self, call: &MethodCall<'_>, app_id: &str) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             let policy_engine = daemon.get_policy_engine().await;
 
@@ -555,172 +1094,430 @@ impl FamilyDaemonService {
             };
 
             match policy_engine.process_activity(activity).await {
-                Ok(decision) => serde_json::json!({
-                    "action": decision.action,
-                    "reason": decision.reason,
-                    "blocked": decision.blocked
+                Ok(decision) => payload::encode(&PolicyVerdict {
+                    action: decision.action,
+                    reason: decision.reason,
+                    blocked: decision.blocked,
                 })
-                .to_string(),
-                Err(e) => {
-                    format!(r#"{{"error":"{}","blocked":false}}"#, e)
-                }
+                .map_err(serialization_failed),
+                Err(e) => Err(reply_error(e)),
             }
         } else {
-            r#"{"error":"Policy engine not available","blocked":false}"#.to_string()
+            Err(DaemonError::Unavailable("Policy engine not available".to_string()))
         }
     }
 
-    async fn process_activity_for_policy(&self, activity_json: &str) -> String {
+    async fn process_activity_for_policy(
+        &self,
+        call: &MethodCall<'_>,
+        activity_json: &str,
+    ) -> Result<String> {
+        let activity = payload::decode::<ActivityEvent>(activity_json)
+            .map_err(|e| invalid_payload("activity event", e))?;
+        self.authorizer
+            .authorize_activity(call.connection, call.header, &activity)
+            .await
+            .map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
-            match serde_json::from_str::<ActivityEvent>(activity_json) {
-                Ok(activity) => {
-                    let policy_engine = daemon.get_policy_engine().await;
-                    match policy_engine.process_activity(activity).await {
-                        Ok(decision) => serde_json::json!({
-                            "action": decision.action,
-                            "reason": decision.reason,
-                            "blocked": decision.blocked
-                        })
-                        .to_string(),
-                        Err(e) => {
-                            format!(r#"{{"error":"{}","blocked":false}}"#, e)
-                        }
-                    }
-                }
-                Err(e) => {
-                    format!(r#"{{"error":"Invalid activity JSON: {}","blocked":false}}"#, e)
-                }
+            let policy_engine = daemon.get_policy_engine().await;
+            match policy_engine.process_activity(activity).await {
+                Ok(decision) => payload::encode(&PolicyVerdict {
+                    action: decision.action,
+                    reason: decision.reason,
+                    blocked: decision.blocked,
+                })
+                .map_err(serialization_failed),
+                Err(e) => Err(reply_error(e)),
             }
         } else {
-            r#"{"error":"Policy engine not available","blocked":false}"#.to_string()
+            Err(DaemonError::Unavailable("Policy engine not available".to_string()))
         }
     }
 
-    async fn sync_profile_to_policy(&self, profile_id: &str) -> String {
+    async fn sync_profile_to_policy(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
-            match self.profile_manager._load_profile(profile_id).await {
-                Ok(profile) => {
-                    // Sync to policy engine
-                    let mut policy_engine = daemon.get_policy_engine_mut().await;
-                    if let Err(e) = policy_engine.set_active_profile(profile.clone()).await {
-                        return format!(r#"{{"error":"{}"}}"#, e);
-                    }
-                    drop(policy_engine);
+            let profile = self.profile_manager._load_profile(profile_id).await.map_err(|e| {
+                warn!("Failed to load profile {}: {}", profile_id, e);
+                reply_error(e)
+            })?;
 
-                    // Sync to time window manager
-                    if let Some(time_window_manager) = daemon.get_time_window_manager().await {
-                        if let Err(e) = time_window_manager.set_active_profile(profile).await {
-                            warn!("Failed to sync profile to time window manager: {}", e);
-                        }
-                    }
+            // Sync to policy engine
+            let mut policy_engine = daemon.get_policy_engine_mut().await;
+            policy_engine.set_active_profile(profile.clone()).await.map_err(reply_error)?;
+            drop(policy_engine);
 
-                    r#"{"status":"success"}"#.to_string()
+            // Sync to time window manager
+            if let Some(time_window_manager) = daemon.get_time_window_manager().await {
+                if let Err(e) = time_window_manager.set_active_profile(profile.clone()).await {
+                    warn!("Failed to sync profile to time window manager: {}", e);
                 }
-                Err(e) => format!(r#"{{"error":"Failed to load profile: {}"}}"#, e),
             }
+
+            self.events.publish(Event::PolicyUpdated {
+                profile_id: profile.id,
+                timestamp: trusted_clock::now(),
+            });
+            Ok(r#"{"status":"success"}"#.to_string())
         } else {
-            r#"{"error":"Policy engine not available"}"#.to_string()
+            Err(DaemonError::Unavailable("Policy engine not available".to_string()))
         }
     }
 
-    async fn get_daily_report(&self, profile_id: &str, date: &str) -> String {
+    async fn recent_events(
+        &self,
+        call: &MethodCall<'_>,
+        filter_json: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+
+        let filter = if filter_json.is_empty() {
+            EventFilter::default()
+        } else {
+            payload::decode::<EventFilter>(filter_json)
+                .map_err(|e| invalid_payload("event filter", e))?
+        };
+        let guardian = self
+            .profile_manager
+            .authorize_session(
+                session.token(),
+                Permission::View,
+                "recent_events",
+                filter.profile_id.map(|id| id.to_string()).as_deref(),
+            )
+            .await
+            .map_err(reply_error)?;
+
+        let events = self
+            .events
+            .recent(&filter)
+            .into_iter()
+            .filter(|event| visible_to(event, &guardian))
+            .collect();
+        payload::encode(&EventList { events }).map_err(serialization_failed)
+    }
+
+    async fn subscribe_events(
+        &self,
+        call: &MethodCall<'_>,
+        filter_json: &str,
+        token: &str,
+    ) -> Result<()> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+
+        let filter = if filter_json.is_empty() {
+            EventFilter::default()
+        } else {
+            payload::decode::<EventFilter>(filter_json)
+                .map_err(|e| invalid_payload("event filter", e))?
+        };
+        let guardian = self
+            .profile_manager
+            .authorize_session(
+                session.token(),
+                Permission::View,
+                "subscribe_events",
+                filter.profile_id.map(|id| id.to_string()).as_deref(),
+            )
+            .await
+            .map_err(reply_error)?;
+
+        let subscriber = call
+            .header
+            .sender()
+            .ok_or_else(|| DaemonError::InvalidArgument("Caller has no bus name".to_string()))?;
+        self.subscriptions.subscribe(subscriber.as_str(), session.token(), guardian, filter);
+        Ok(())
+    }
+
+    async fn get_daily_report(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        date: &str,
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.get_daily_report(profile_id, date).await {
-            Ok(report) => serde_json::to_string(&report)
-                .unwrap_or_else(|_| r#"{"error":"serialization_failed"}"#.to_string()),
+            Ok(report) => payload::encode(&report).map_err(serialization_failed),
             Err(e) => {
                 warn!("Failed to get daily report for {} on {}: {}", profile_id, date, e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn get_weekly_report(&self, profile_id: &str, week_start: &str) -> String {
+    async fn get_weekly_report(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        week_start: &str,
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.get_weekly_report(profile_id, week_start).await {
-            Ok(report) => serde_json::to_string(&report)
-                .unwrap_or_else(|_| r#"{"error":"serialization_failed"}"#.to_string()),
+            Ok(report) => payload::encode(&report).map_err(serialization_failed),
             Err(e) => {
                 warn!(
                     "Failed to get weekly report for {} starting {}: {}",
                     profile_id, week_start, e
                 );
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
     async fn export_reports(
         &self,
+        call: &MethodCall<'_>,
         profile_id: &str,
         format: &str,
         start_date: &str,
         end_date: &str,
-    ) -> String {
+    ) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         match self.profile_manager.export_reports(profile_id, format, start_date, end_date).await {
-            Ok(exported_data) => exported_data,
+            Ok(exported_data) => Ok(exported_data),
             Err(e) => {
                 warn!(
                     "Failed to export reports for {} from {} to {}: {}",
                     profile_id, start_date, end_date, e
                 );
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
     /// Check if current time is within allowed time windows
-    async fn check_time_window(&self) -> String {
+    async fn check_time_window(&self, call: &MethodCall<'_>) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             let policy_engine = daemon.get_policy_engine().await;
             match policy_engine.check_time_window_access().await {
-                Ok(allowed) => serde_json::json!({
-                    "allowed": allowed,
-                    "timestamp": chrono::Local::now().to_rfc3339()
-                })
-                .to_string(),
-                Err(e) => format!(r#"{{"error":"{}"}}"#, e),
+                Ok(allowed) => {
+                    payload::encode(&TimeWindowAccess { allowed, timestamp: trusted_clock::now() })
+                        .map_err(serialization_failed)
+                }
+                Err(e) => Err(reply_error(e)),
             }
         } else {
-            r#"{"error":"Policy engine not available"}"#.to_string()
+            Err(DaemonError::Unavailable("Policy engine not available".to_string()))
         }
     }
 
     /// Get the next available time window
-    async fn get_next_time_window(&self) -> String {
+    async fn get_next_time_window(&self, call: &MethodCall<'_>) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             let policy_engine = daemon.get_policy_engine().await;
             match policy_engine.get_next_time_window().await {
-                Ok(Some(window)) => serde_json::json!({
-                    "start": window.start,
-                    "end": window.end,
-                    "available": true
+                Ok(Some(window)) => payload::encode(&NextTimeWindow {
+                    available: true,
+                    start: Some(window.start),
+                    end: Some(window.end),
+                    reason: None,
                 })
-                .to_string(),
-                Ok(None) => {
-                    r#"{"available":false,"reason":"No upcoming windows configured"}"#.to_string()
-                }
-                Err(e) => format!(r#"{{"error":"{}"}}"#, e),
+                .map_err(serialization_failed),
+                Ok(None) => payload::encode(&NextTimeWindow {
+                    available: false,
+                    start: None,
+                    end: None,
+                    reason: Some("No upcoming windows configured".to_string()),
+                })
+                .map_err(serialization_failed),
+                Err(e) => Err(reply_error(e)),
             }
         } else {
-            r#"{"error":"Policy engine not available"}"#.to_string()
+            Err(DaemonError::Unavailable("Policy engine not available".to_string()))
         }
     }
 
     /// Lock the current user session
-    async fn lock_session(&self, username: &str) -> String {
+    async fn lock_session(&self, call: &MethodCall<'_>, username: &str) -> Result<String> {
+        self.authorizer.authorize(call.connection, call.header).await.map_err(reply_error)?;
+
         if let Some(ref daemon) = self.daemon {
             let enforcement = daemon.get_enforcement_engine().await;
             match enforcement
                 .lock_session(if username.is_empty() { None } else { Some(username) })
                 .await
             {
-                Ok(()) => r#"{"status":"success","session_locked":true}"#.to_string(),
+                Ok(()) => Ok(r#"{"status":"success","session_locked":true}"#.to_string()),
                 Err(e) => {
                     error!("Failed to lock session for {}: {}", username, e);
-                    format!(r#"{{"error":"{}","session_locked":false}}"#, e)
+                    Err(reply_error(e))
                 }
             }
         } else {
-            r#"{"error":"Enforcement engine not available"}"#.to_string()
+            Err(DaemonError::Unavailable("Enforcement engine not available".to_string()))
+        }
+    }
+
+    /// Acknowledge a tamper alert and lift any deny-all response
+    async fn clear_tamper_alert(&self, call: &MethodCall<'_>, token: &str) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        if let Err(e) = self.profile_manager.clear_tamper(token).await {
+            warn!("Failed to clear tamper alert: {}", e);
+            return Err(reply_error(e));
+        }
+
+        if let Some(ref daemon) = self.daemon {
+            daemon.get_policy_engine_mut().await.clear_deny_all();
+
+            // Only blocks the tamper response set; time-window blocks stay
+            let enforcement = daemon.get_enforcement_engine().await;
+            for username in daemon.take_tamper_network_blocks().await {
+                if let Err(e) = enforcement.restore_network(&username).await {
+                    warn!("Failed to restore network of {}: {}", username, e);
+                }
+            }
+        }
+
+        Ok(r#"{"status":"success"}"#.to_string())
+    }
+
+    /// Verify the audit log hash chain and its seals
+    async fn verify_audit_log(&self, call: &MethodCall<'_>, token: &str) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        let unavailable = || DaemonError::Unavailable("Audit sealing not available".to_string());
+        let daemon = self.daemon.as_ref().ok_or_else(unavailable)?;
+        let sealer = daemon.get_audit_sealer().await.ok_or_else(unavailable)?;
+
+        match self.profile_manager.verify_audit_log(token, &sealer).await {
+            Ok(report) => payload::encode(&audit_report(report)).map_err(serialization_failed),
+            Err(e) => {
+                warn!("Failed to verify audit log: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    // ============================================================================
+    // Guardian Account Methods
+    // ============================================================================
+
+    async fn add_guardian(
+        &self,
+        call: &MethodCall<'_>,
+        name: &str,
+        password: &str,
+        role: &str,
+        profile_ids: Vec<String>,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.add_guardian(name, password, role, &profile_ids, token).await {
+            Ok(guardian_id) => {
+                Ok(format!(r#"{{"status":"success","guardian_id":"{}"}}"#, guardian_id))
+            }
+            Err(e) => {
+                warn!("Failed to add guardian: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn remove_guardian(
+        &self,
+        call: &MethodCall<'_>,
+        name: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.remove_guardian(name, token).await {
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
+            Err(e) => {
+                warn!("Failed to remove guardian: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    async fn list_guardians(&self, call: &MethodCall<'_>, token: &str) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
+        match self.profile_manager.list_guardians(token).await {
+            Ok(guardians) => {
+                payload::encode(&GuardianList { guardians }).map_err(serialization_failed)
+            }
+            Err(e) => {
+                warn!("Failed to list guardians: {}", e);
+                Err(reply_error(e))
+            }
+        }
+    }
+
+    /// Ask for extra grace time to finish up before apps close (once per day)
+    async fn request_finishing_up(&self, call: &MethodCall<'_>) -> Result<String> {
+        let profile_id = self
+            .authorizer
+            .authorize_own_profile(call.connection, call.header)
+            .await
+            .map_err(reply_error)?;
+
+        let unavailable =
+            || DaemonError::Unavailable("Time window manager not available".to_string());
+        let daemon = self.daemon.as_ref().ok_or_else(unavailable)?;
+        let time_window_manager = daemon.get_time_window_manager().await.ok_or_else(unavailable)?;
+        let profile = self
+            .profile_manager
+            ._load_profile(&profile_id.to_string())
+            .await
+            .map_err(reply_error)?;
+
+        match time_window_manager.request_finishing_up(&profile).await {
+            Ok(extra_minutes) => {
+                info!("Finishing-up request granted: {} extra minutes", extra_minutes);
+                payload::encode(&FinishingUpGrant { extra_minutes }).map_err(serialization_failed)
+            }
+            Err(e) => Err(reply_error(e)),
         }
     }
 
@@ -730,85 +1527,174 @@ impl FamilyDaemonService {
 
     async fn add_time_window(
         &self,
+        call: &MethodCall<'_>,
         profile_id: &str,
         window_type: &str,
         start: &str,
         end: &str,
         token: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.add_time_window(profile_id, window_type, start, end, token).await
         {
-            Ok(()) => r#"{"status":"success"}"#.to_string(),
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
             Err(e) => {
                 warn!("Failed to add time window: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
     async fn remove_time_window(
         &self,
+        call: &MethodCall<'_>,
         profile_id: &str,
         window_type: &str,
         start: &str,
         end: &str,
         token: &str,
-    ) -> String {
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self
             .profile_manager
             .remove_time_window(profile_id, window_type, start, end, token)
             .await
         {
-            Ok(()) => r#"{"status":"success"}"#.to_string(),
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
             Err(e) => {
                 warn!("Failed to remove time window: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn list_time_windows(&self, profile_id: &str, token: &str) -> String {
+    async fn list_time_windows(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.list_time_windows(profile_id, token).await {
-            Ok(windows) => serde_json::to_string(&windows)
-                .unwrap_or_else(|_| r#"{"error":"serialization_failed"}"#.to_string()),
+            Ok(windows) => payload::encode(&windows).map_err(serialization_failed),
             Err(e) => {
                 warn!("Failed to list time windows: {}", e);
-                format!(r#"{{"error":"{}"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
 
-    async fn clear_time_windows(&self, profile_id: &str, window_type: &str, token: &str) -> String {
+    async fn clear_time_windows(
+        &self,
+        call: &MethodCall<'_>,
+        profile_id: &str,
+        window_type: &str,
+        token: &str,
+    ) -> Result<String> {
+        let session = self
+            .authorizer
+            .authorize_token(call.connection, call.header, token)
+            .await
+            .map_err(reply_error)?;
+        let token = session.token();
+
         match self.profile_manager.clear_time_windows(profile_id, window_type, token).await {
-            Ok(()) => r#"{"status":"success"}"#.to_string(),
+            Ok(()) => Ok(r#"{"status":"success"}"#.to_string()),
             Err(e) => {
                 warn!("Failed to clear time windows: {}", e);
-                format!(r#"{{"error":"{}","status":"failed"}}"#, e)
+                Err(reply_error(e))
             }
         }
     }
+}
 
-    #[zbus(signal)]
-    async fn time_window_ending(
-        signal_ctxt: &zbus::SignalContext<'_>,
-        minutes_remaining: u32,
-    ) -> zbus::Result<()>;
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
 
-    #[zbus(signal)]
-    async fn policy_updated(
-        signal_ctxt: &zbus::SignalContext<'_>,
-        profile_id: &str,
-    ) -> zbus::Result<()>;
+    use tempfile::tempdir;
+    use uuid::Uuid;
 
-    #[zbus(signal)]
-    async fn time_limit_warning(
-        signal_ctxt: &zbus::SignalContext<'_>,
-        minutes_remaining: u32,
-    ) -> zbus::Result<()>;
+    use super::*;
 
-    #[zbus(signal)]
-    async fn tamper_detected(
-        signal_ctxt: &zbus::SignalContext<'_>,
-        reason: &str,
-    ) -> zbus::Result<()>;
+    async fn test_service() -> (FamilyDaemonService, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db").to_str().unwrap().to_string();
+        let config = DaemonConfig {
+            database: crate::config::DatabaseConfig {
+                path: path.clone(),
+                encrypt: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let db = dots_family_db::Database::new(dots_family_db::DatabaseConfig {
+            path,
+            encryption_key: None,
+        })
+        .await
+        .unwrap();
+        db.run_migrations().await.unwrap();
+
+        let monitoring = MonitoringService::new().await.unwrap();
+        (FamilyDaemonService::new(&config, monitoring, db).await.unwrap(), dir)
+    }
+
+    #[tokio::test]
+    async fn test_focus_events_reach_only_their_profiles_subscribers() {
+        let (service, _dir) = test_service().await;
+        let profiles = &service.profile_manager;
+        let mut children = Vec::new();
+        for (name, username) in [("Alice", "alice"), ("Bob", "bob")] {
+            let id = profiles
+                .create_profile_with_username(name, "8-12", Some(username.to_string()))
+                .await
+                .unwrap();
+            children.push(Uuid::parse_str(&id).unwrap());
+        }
+        let uid = nix::unistd::getuid().as_raw();
+        profiles.register_component(":1.42", "monitor", uid, "alice").await.unwrap();
+        profiles.register_component(":1.43", "monitor", uid, "bob").await.unwrap();
+
+        // The third sender never registered and runs under no child's account
+        for (sender, app_id) in
+            [(":1.42", "firefox"), (":1.43", "minecraft"), (":1.44", "nautilus")]
+        {
+            let event = ActivityEvent::WindowFocused {
+                pid: std::process::id(),
+                app_id: app_id.to_string(),
+                window_title: String::new(),
+                timestamp: SystemTime::now(),
+            };
+            let profile_id = profiles.activity_event_profile(Some(sender), &event).await;
+            service.publish_focus(profile_id, app_id);
+        }
+
+        assert_eq!(service.events.recent(&EventFilter::default()).len(), 2);
+        for (profile_id, app) in children.into_iter().zip(["firefox", "minecraft"]) {
+            let filter = EventFilter { kinds: Vec::new(), profile_id: Some(profile_id) };
+            match service.events.recent(&filter).as_slice() {
+                [Event::AppFocused { app_id, .. }] => assert_eq!(app_id, app),
+                other => panic!("unexpected events for {}: {:?}", profile_id, other),
+            }
+        }
+    }
 }
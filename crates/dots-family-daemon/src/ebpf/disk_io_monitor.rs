@@ -1,57 +1,75 @@
 use anyhow::Result;
-use aya::Bpf;
+use aya::{maps::RingBuf, programs::TracePoint, Bpf};
 use serde_json::{json, Value};
 use std::{
     path::Path,
     time::{SystemTime, UNIX_EPOCH},
 };
-use tracing::{debug, info};
+use tokio::{io::unix::AsyncFd, sync::mpsc, task::JoinHandle};
+use tracing::{debug, error, info, warn};
 
-/// Disk I/O event from eBPF program
+/// Disk I/O event from eBPF program, mirrors `DiskIOEvent` in the kernel side
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct DiskIoEvent {
     pub pid: u32,
-    pub dev: u32,       // Device ID (major:minor)
-    pub sector: u64,    // Starting sector
-    pub nr_sector: u32, // Number of sectors
-    pub latency: u64,   // I/O latency in nanoseconds (for complete events)
-    pub event_type: u8, // 0=issue, 1=complete, 2=bio_queue
+    pub uid: u32, // User the kernel attributed the event to
+    pub comm: [u8; 16],
+    pub device_major: u32,
+    pub device_minor: u32,
+    pub operation: u8, // 0=read, 1=write
+    pub sector: u64,   // Starting sector
+    pub num_sectors: u32,
+    pub bytes: u64,
+    pub latency_ns: u64, // Issue to completion; 0 for queued bios
+    pub timestamp: u64,  // Kernel monotonic clock, ns
 }
 
 impl DiskIoEvent {
-    /// Get device major number
-    pub fn device_major(&self) -> u32 {
-        (self.dev >> 20) & 0xfff
+    /// Decode an event from a ring buffer record
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < std::mem::size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: the length was checked and every bit pattern is a valid DiskIoEvent
+        Some(unsafe { std::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
+    pub fn comm(&self) -> String {
+        let len = self.comm.iter().position(|&b| b == 0).unwrap_or(self.comm.len());
+        String::from_utf8_lossy(&self.comm[..len]).into_owned()
     }
 
-    /// Get device minor number
-    pub fn device_minor(&self) -> u32 {
-        self.dev & 0xfffff
+    /// Completed requests carry the latency measured from issue; bios
+    /// reported when queued have none yet
+    pub fn event_type(&self) -> u8 {
+        if self.latency_ns > 0 {
+            1
+        } else {
+            2
+        }
     }
 
-    /// Get I/O size in bytes (assuming 512-byte sectors)
+    /// Get I/O size in bytes
     pub fn io_size_bytes(&self) -> u64 {
-        self.nr_sector as u64 * 512
+        self.bytes
     }
 
     /// Get latency in microseconds
     pub fn latency_us(&self) -> f64 {
-        self.latency as f64 / 1000.0
+        self.latency_ns as f64 / 1000.0
     }
 
     /// Get latency in milliseconds
     pub fn latency_ms(&self) -> f64 {
-        self.latency as f64 / 1_000_000.0
+        self.latency_ns as f64 / 1_000_000.0
     }
 
     /// Get event type name
     pub fn event_type_name(&self) -> &'static str {
-        match self.event_type {
-            0 => "issue",
+        match self.event_type() {
             1 => "complete",
-            2 => "bio_queue",
-            _ => "unknown",
+            _ => "bio_queue",
         }
     }
 }
@@ -60,13 +78,14 @@ impl DiskIoEvent {
 pub struct DiskIoMonitorEbpf {
     ebpf: Option<Bpf>,
     loaded: bool,
+    reader: Option<JoinHandle<()>>,
 }
 
 impl DiskIoMonitorEbpf {
     /// Create a new disk I/O monitor instance
     pub fn new() -> Result<Self> {
         info!("Initializing eBPF disk I/O monitor");
-        Ok(Self { ebpf: None, loaded: false })
+        Ok(Self { ebpf: None, loaded: false, reader: None })
     }
 
     /// Load the eBPF program from a file
@@ -104,6 +123,67 @@ impl DiskIoMonitorEbpf {
         self.loaded
     }
 
+    /// Attach the block request and bio tracepoints
+    pub async fn attach_programs(&mut self) -> Result<()> {
+        let ebpf = self.ebpf.as_mut().ok_or_else(|| anyhow::anyhow!("eBPF program not loaded"))?;
+
+        for name in ["block_rq_issue", "block_rq_complete", "block_bio_queue"] {
+            let program: &mut TracePoint = ebpf
+                .program_mut(name)
+                .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+                .try_into()?;
+            program.load()?;
+            program.attach("block", name)?;
+        }
+
+        info!("Attached disk I/O monitor eBPF programs");
+        Ok(())
+    }
+
+    /// Forward kernel events until the receiver is dropped, dropping events
+    /// while the receiver falls behind
+    pub async fn start_monitoring(&mut self, events: mpsc::Sender<DiskIoEvent>) -> Result<()> {
+        if self.reader.is_some() {
+            return Ok(());
+        }
+
+        let ebpf = self.ebpf.as_mut().ok_or_else(|| anyhow::anyhow!("eBPF program not loaded"))?;
+        let map = ebpf
+            .take_map("DISK_IO_EVENTS")
+            .ok_or_else(|| anyhow::anyhow!("DISK_IO_EVENTS map not found"))?;
+        let mut ring = AsyncFd::new(RingBuf::try_from(map)?)?;
+
+        self.reader = Some(tokio::spawn(async move {
+            loop {
+                let mut guard = match ring.readable_mut().await {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        error!("Disk I/O event ring buffer failed: {}", e);
+                        break;
+                    }
+                };
+
+                let ring = guard.get_inner_mut();
+                while let Some(item) = ring.next() {
+                    let Some(event) = DiskIoEvent::from_bytes(&item) else {
+                        warn!("Dropping truncated disk I/O event ({} bytes)", item.len());
+                        continue;
+                    };
+                    match events.try_send(event) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            debug!("Disk I/O event queue full, dropping event")
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => return,
+                    }
+                }
+                guard.clear_ready();
+            }
+        }));
+
+        Ok(())
+    }
+
     /// Collect a snapshot of disk I/O monitoring data
     pub async fn collect_snapshot(&self) -> Result<Value> {
         if !self.loaded {
@@ -133,6 +213,9 @@ impl DiskIoMonitorEbpf {
         if let Some(ref mut _ebpf) = self.ebpf {
             info!("Cleaning up disk I/O monitor eBPF programs");
         }
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
         self.loaded = false;
     }
 
@@ -141,8 +224,8 @@ impl DiskIoMonitorEbpf {
         debug!(
             "Disk I/O event: pid={} dev={}:{} type={} size={} bytes latency={:.2} ms",
             event.pid,
-            event.device_major(),
-            event.device_minor(),
+            event.device_major,
+            event.device_minor,
             event.event_type_name(),
             event.io_size_bytes(),
             event.latency_ms()
@@ -179,3 +262,32 @@ pub struct DiskIoStats {
     pub write_count: u64,
     pub avg_latency_ms: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_io_event_matches_kernel_layout() {
+        assert_eq!(std::mem::size_of::<DiskIoEvent>(), 80);
+
+        let mut record = [0u8; 80];
+        record[0..4].copy_from_slice(&42u32.to_ne_bytes());
+        record[4..8].copy_from_slice(&1000u32.to_ne_bytes());
+        record[8..12].copy_from_slice(b"dd\0\0");
+        record[24..28].copy_from_slice(&8u32.to_ne_bytes());
+        record[32] = 1;
+        record[56..64].copy_from_slice(&65536u64.to_ne_bytes());
+        record[64..72].copy_from_slice(&2_000_000u64.to_ne_bytes());
+
+        let event = DiskIoEvent::from_bytes(&record).unwrap();
+        assert_eq!(event.pid, 42);
+        assert_eq!(event.uid, 1000);
+        assert_eq!(event.comm(), "dd");
+        assert_eq!(event.device_major, 8);
+        assert_eq!(event.operation, 1);
+        assert_eq!(event.io_size_bytes(), 65536);
+        assert_eq!(event.event_type(), 1);
+        assert!(DiskIoEvent::from_bytes(&record[..79]).is_none());
+    }
+}
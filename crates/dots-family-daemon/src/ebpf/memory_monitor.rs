@@ -1,41 +1,55 @@
 use anyhow::Result;
-use aya::Bpf;
+use aya::{maps::RingBuf, programs::TracePoint, Bpf};
 use serde_json::{json, Value};
 use std::{
     path::Path,
     time::{SystemTime, UNIX_EPOCH},
 };
-use tracing::{debug, info};
+use tokio::{io::unix::AsyncFd, sync::mpsc, task::JoinHandle};
+use tracing::{debug, error, info, warn};
 
-/// Memory event from eBPF program
+/// Memory event from eBPF program, mirrors `MemoryEvent` in the kernel side
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct MemoryEvent {
     pub pid: u32,
-    pub size: u64,
-    pub event_type: u8, // 0=kmalloc, 1=kfree, 2=page_alloc, 3=page_free
-    pub order: u32,     // Page order (for page events)
+    pub uid: u32, // User the kernel attributed the event to
+    pub comm: [u8; 16],
+    pub event_type: u32, // 1=alloc, 2=free, 3=mmap, 4=munmap
+    pub size: u64,       // Bytes; page events are already converted from their order
+    pub rss_bytes: u64,
+    pub vms_bytes: u64,
+    pub shared_bytes: u64,
+    pub timestamp: u64, // Kernel monotonic clock, ns
 }
 
 impl MemoryEvent {
-    /// Convert page order to bytes
-    pub fn size_bytes(&self) -> u64 {
-        match self.event_type {
-            2 | 3 => {
-                // Page allocation/free: size = (2^order) * 4096
-                (1u64 << self.order) * 4096
-            }
-            _ => self.size,
+    /// Decode an event from a ring buffer record
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < std::mem::size_of::<Self>() {
+            return None;
         }
+        // SAFETY: the length was checked and every bit pattern is a valid MemoryEvent
+        Some(unsafe { std::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
+    pub fn comm(&self) -> String {
+        let len = self.comm.iter().position(|&b| b == 0).unwrap_or(self.comm.len());
+        String::from_utf8_lossy(&self.comm[..len]).into_owned()
+    }
+
+    /// Size of the allocation or free in bytes; 0 when the kernel does not report it
+    pub fn size_bytes(&self) -> u64 {
+        self.size
     }
 
     /// Get event type name
     pub fn event_type_name(&self) -> &'static str {
         match self.event_type {
-            0 => "kmalloc",
-            1 => "kfree",
-            2 => "page_alloc",
-            3 => "page_free",
+            1 => "alloc",
+            2 => "free",
+            3 => "mmap",
+            4 => "munmap",
             _ => "unknown",
         }
     }
@@ -45,13 +59,14 @@ impl MemoryEvent {
 pub struct MemoryMonitorEbpf {
     ebpf: Option<Bpf>,
     loaded: bool,
+    reader: Option<JoinHandle<()>>,
 }
 
 impl MemoryMonitorEbpf {
     /// Create a new memory monitor instance
     pub fn new() -> Result<Self> {
         info!("Initializing eBPF memory monitor");
-        Ok(Self { ebpf: None, loaded: false })
+        Ok(Self { ebpf: None, loaded: false, reader: None })
     }
 
     /// Load the eBPF program from a file
@@ -89,6 +104,73 @@ impl MemoryMonitorEbpf {
         self.loaded
     }
 
+    /// Attach the allocation and page tracepoints
+    pub async fn attach_programs(&mut self) -> Result<()> {
+        let ebpf = self.ebpf.as_mut().ok_or_else(|| anyhow::anyhow!("eBPF program not loaded"))?;
+
+        for (name, tracepoint) in [
+            ("kmem_kmalloc", "kmalloc"),
+            ("kmem_kfree", "kfree"),
+            ("kmem_mm_page_alloc", "mm_page_alloc"),
+            ("kmem_mm_page_free", "mm_page_free"),
+        ] {
+            let program: &mut TracePoint = ebpf
+                .program_mut(name)
+                .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+                .try_into()?;
+            program.load()?;
+            program.attach("kmem", tracepoint)?;
+        }
+
+        info!("Attached memory monitor eBPF programs");
+        Ok(())
+    }
+
+    /// Forward kernel events until the receiver is dropped. Events are
+    /// dropped rather than queued while the receiver falls behind, so a busy
+    /// allocator cannot stall the ring buffer.
+    pub async fn start_monitoring(&mut self, events: mpsc::Sender<MemoryEvent>) -> Result<()> {
+        if self.reader.is_some() {
+            return Ok(());
+        }
+
+        let ebpf = self.ebpf.as_mut().ok_or_else(|| anyhow::anyhow!("eBPF program not loaded"))?;
+        let map = ebpf
+            .take_map("MEMORY_EVENTS")
+            .ok_or_else(|| anyhow::anyhow!("MEMORY_EVENTS map not found"))?;
+        let mut ring = AsyncFd::new(RingBuf::try_from(map)?)?;
+
+        self.reader = Some(tokio::spawn(async move {
+            loop {
+                let mut guard = match ring.readable_mut().await {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        error!("Memory event ring buffer failed: {}", e);
+                        break;
+                    }
+                };
+
+                let ring = guard.get_inner_mut();
+                while let Some(item) = ring.next() {
+                    let Some(event) = MemoryEvent::from_bytes(&item) else {
+                        warn!("Dropping truncated memory event ({} bytes)", item.len());
+                        continue;
+                    };
+                    match events.try_send(event) {
+                        Ok(()) => {}
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            debug!("Memory event queue full, dropping event")
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => return,
+                    }
+                }
+                guard.clear_ready();
+            }
+        }));
+
+        Ok(())
+    }
+
     /// Collect a snapshot of memory monitoring data
     pub async fn collect_snapshot(&self) -> Result<Value> {
         if !self.loaded {
@@ -117,6 +199,9 @@ impl MemoryMonitorEbpf {
         if let Some(ref mut _ebpf) = self.ebpf {
             info!("Cleaning up memory monitor eBPF programs");
         }
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
         self.loaded = false;
     }
 
@@ -151,3 +236,28 @@ pub struct MemoryStats {
     pub freed_bytes: u64,
     pub net_allocation: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_event_matches_kernel_layout() {
+        assert_eq!(std::mem::size_of::<MemoryEvent>(), 72);
+
+        let mut record = [0u8; 72];
+        record[0..4].copy_from_slice(&42u32.to_ne_bytes());
+        record[4..8].copy_from_slice(&1000u32.to_ne_bytes());
+        record[8..12].copy_from_slice(b"vim\0");
+        record[24..28].copy_from_slice(&1u32.to_ne_bytes());
+        record[32..40].copy_from_slice(&4096u64.to_ne_bytes());
+
+        let event = MemoryEvent::from_bytes(&record).unwrap();
+        assert_eq!(event.pid, 42);
+        assert_eq!(event.uid, 1000);
+        assert_eq!(event.comm(), "vim");
+        assert_eq!(event.event_type_name(), "alloc");
+        assert_eq!(event.size_bytes(), 4096);
+        assert!(MemoryEvent::from_bytes(&record[..71]).is_none());
+    }
+}
@@ -1,231 +1,160 @@
-use std::collections::HashMap;
+use std::path::Path;
 
 use anyhow::Result;
-use aya::Bpf;
-use tracing::{debug, error, info, warn};
-
-/// Process monitor using eBPF for kernel-level monitoring
-pub struct ProcessMonitorEbpf {
-    programs: HashMap<String, Bpf>,
-    health_status: EbpfHealth,
-}
-
-/// Health status for eBPF programs
-#[derive(Debug, Clone)]
-pub struct EbpfHealth {
-    pub programs_loaded: usize,
-    pub all_healthy: bool,
-    pub program_status: HashMap<String, bool>,
+use aya::{maps::RingBuf, programs::TracePoint, Bpf};
+use tokio::{io::unix::AsyncFd, sync::mpsc, task::JoinHandle};
+use tracing::{error, info, warn};
+
+use crate::process_monitor::RawProcessEvent;
+
+/// Process event from eBPF program, mirrors `ProcessEvent` in the kernel side
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ProcessEvent {
+    pub pid: u32,
+    pub ppid: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub comm: [u8; 16],
+    pub cmdline: [u8; 256], // Executable path passed to execve
+    pub event_type: u32,    // 0 = exec, 1 = exit
 }
 
-impl ProcessMonitorEbpf {
-    /// Create a new eBPF process monitor instance
-    pub async fn new() -> Result<Self> {
-        info!("Initializing eBPF process monitor");
-
-        let program_status = HashMap::new();
-        let health_status = EbpfHealth { programs_loaded: 0, all_healthy: false, program_status };
-
-        let mut instance = Self { programs: HashMap::new(), health_status };
-
-        instance.update_health_status();
-        Ok(instance)
-    }
-
-    /// Update health status based on current program state
-    pub fn update_health_status(&mut self) {
-        let actually_loaded =
-            self.health_status.program_status.values().filter(|&loaded| *loaded).count();
-        let all_programs_loaded = actually_loaded == 3; // All expected programs: process_monitor, network_monitor, filesystem_monitor
-
-        self.health_status = EbpfHealth {
-            programs_loaded: actually_loaded,
-            all_healthy: all_programs_loaded,
-            program_status: self.health_status.program_status.clone(),
-        };
-
-        debug!(
-            "eBPF health updated: {} programs loaded, all healthy: {}",
-            actually_loaded, all_programs_loaded
-        );
-    }
-
-    /// Load the process monitor
-    pub async fn load(&mut self) -> Result<()> {
-        info!("Loading process monitor");
-        self.load_all_programs().await
-    }
-
-    /// Load all eBPF programs from environment variables
-    pub async fn load_all_programs(&mut self) -> Result<()> {
-        info!("Loading eBPF programs");
-
-        // Environment variables for eBPF program paths
-        let env_vars = [
-            ("process_monitor", "BPF_PROCESS_MONITOR_PATH"),
-            ("network_monitor", "BPF_NETWORK_MONITOR_PATH"),
-            ("filesystem_monitor", "BPF_FILESYSTEM_MONITOR_PATH"),
-        ];
-
-        // Try to load each program
-        for (program_name, env_var) in &env_vars {
-            match std::env::var(env_var) {
-                Ok(path) if !path.is_empty() => {
-                    match self.load_program(program_name, &path).await {
-                        Ok(_) => {
-                            info!("Successfully loaded {} eBPF program", program_name);
-                        }
-                        Err(e) => {
-                            warn!(
-                                "Failed to load {} eBPF program from {}: {}",
-                                program_name, path, e
-                            );
-                        }
-                    }
-                }
-                Ok(_) => {
-                    info!(
-                        "Environment variable {} is empty, skipping {} program",
-                        env_var, program_name
-                    );
-                }
-                Err(_) => {
-                    info!(
-                        "Environment variable {} not set, skipping {} program",
-                        env_var, program_name
-                    );
-                }
-            }
+impl ProcessEvent {
+    /// Decode an event from a ring buffer record
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < std::mem::size_of::<Self>() {
+            return None;
         }
-
-        // Fallback: use simple monitor when eBPF is not available
-        if !Self::ebpf_available() {
-            return Err(anyhow::anyhow!(
-                "eBPF not available on this system - process monitoring requires eBPF capabilities"
-            ));
-        }
-
-        self.update_health_status();
-
-        debug!(
-            "eBPF loading completed. {} programs loaded, health: {}",
-            self.health_status.programs_loaded, self.health_status.all_healthy
-        );
-
-        Ok(())
+        // SAFETY: the length was checked and every bit pattern is a valid ProcessEvent
+        Some(unsafe { std::ptr::read_unaligned(data.as_ptr() as *const Self) })
     }
 
-    /// Load a specific eBPF program from file path
-    pub async fn load_program(&mut self, name: &str, path: &str) -> Result<()> {
-        info!("Attempting to load {} eBPF program from {}", name, path);
-
-        // Read the ELF bytecode
-        let elf_bytes = match std::fs::read(path) {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                error!("Failed to read eBPF program file {}: {}", path, e);
-                return Err(anyhow::anyhow!("Failed to read eBPF program file: {}", e));
-            }
-        };
-
-        // Load the eBPF program
-        match Bpf::load(&elf_bytes) {
-            Ok(bpf) => {
-                info!("Successfully loaded {} eBPF program ({} bytes)", name, elf_bytes.len());
-                self.programs.insert(name.to_string(), bpf);
-                self.health_status.program_status.insert(name.to_string(), true);
-                Ok(())
-            }
-            Err(e) => {
-                error!("Failed to load {} eBPF program: {}", name, e);
-                self.health_status.program_status.insert(name.to_string(), false);
-                Err(anyhow::anyhow!("Failed to load eBPF program: {}", e))
+    pub fn into_raw(self) -> Option<RawProcessEvent> {
+        match self.event_type {
+            0 => {
+                let len = self.cmdline.iter().position(|&b| b == 0).unwrap_or(self.cmdline.len());
+                let filename = String::from_utf8_lossy(&self.cmdline[..len]).into_owned();
+                Some(RawProcessEvent::Exec {
+                    pid: self.pid,
+                    uid: Some(self.uid),
+                    filename: (!filename.is_empty()).then_some(filename),
+                })
             }
+            1 => Some(RawProcessEvent::Exit { pid: self.pid }),
+            _ => None,
         }
     }
+}
 
-    /// Get health status of all eBPF programs
-    #[allow(dead_code)]
-    pub async fn get_health_status(&self) -> EbpfHealth {
-        self.health_status.clone()
-    }
-
-    /// Check if eBPF is available on the current system
-    pub fn ebpf_available() -> bool {
-        // First check basic kernel eBPF support
-        if !std::path::Path::new("/proc/sys/kernel/bpf_stats_enabled").exists() {
-            return false;
-        }
-
-        // Check capabilities using more comprehensive approach
-        Self::check_capabilities()
-    }
+/// eBPF backend of the process monitor, reports exec and exit from the
+/// `sched_process_exec` and `sched_process_exit` tracepoints
+pub struct ProcessMonitorEbpf {
+    ebpf: Bpf,
+    reader: Option<JoinHandle<()>>,
+}
 
-    /// Check if we have the required capabilities to load eBPF programs
-    fn check_capabilities() -> bool {
-        // First check if we're root
-        let is_root = std::process::Command::new("id")
-            .arg("-u")
-            .output()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
-            .unwrap_or(false);
-
-        if is_root {
-            info!("Running as root - eBPF should be available");
-            return true;
+impl ProcessMonitorEbpf {
+    /// Load the eBPF program from a file and attach its tracepoints
+    pub fn load(bpf_path: &Path) -> Result<Self> {
+        info!("Loading process monitor eBPF program from {:?}", bpf_path);
+
+        let elf_bytes = std::fs::read(bpf_path).map_err(|e| {
+            anyhow::anyhow!("Failed to read eBPF program file {:?}: {}", bpf_path, e)
+        })?;
+        let mut ebpf = Bpf::load(&elf_bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to load process monitor eBPF program: {}", e))?;
+
+        for name in ["sched_process_exec", "sched_process_exit"] {
+            let program: &mut TracePoint = ebpf
+                .program_mut(name)
+                .ok_or_else(|| anyhow::anyhow!("{} program not found", name))?
+                .try_into()?;
+            program.load()?;
+            program.attach("sched", name)?;
         }
 
-        // For non-root, try to detect eBPF availability through other means
-        Self::ebpf_detection_fallback()
+        info!("Attached process monitor eBPF programs ({} bytes)", elf_bytes.len());
+        Ok(Self { ebpf, reader: None })
     }
 
-    /// Fallback eBPF detection method for non-root environments
-    fn ebpf_detection_fallback() -> bool {
-        // Try to access eBPF-related files to test availability
-        if std::fs::File::open("/sys/kernel/debug/tracing/events").is_ok() {
-            info!("Can access /sys/kernel/debug/tracing/events - eBPF likely available");
-            return true;
-        }
+    /// Forward kernel events until the receiver is dropped
+    pub fn start(&mut self, events: mpsc::Sender<RawProcessEvent>) -> Result<()> {
+        let map = self
+            .ebpf
+            .take_map("PROCESS_EVENTS")
+            .ok_or_else(|| anyhow::anyhow!("PROCESS_EVENTS map not found"))?;
+        let mut ring = AsyncFd::new(RingBuf::try_from(map)?)?;
+
+        self.reader = Some(tokio::spawn(async move {
+            loop {
+                let mut guard = match ring.readable_mut().await {
+                    Ok(guard) => guard,
+                    Err(e) => {
+                        error!("Process event ring buffer failed: {}", e);
+                        break;
+                    }
+                };
+
+                let mut raw_events = Vec::new();
+                let ring = guard.get_inner_mut();
+                while let Some(item) = ring.next() {
+                    match ProcessEvent::from_bytes(&item).and_then(ProcessEvent::into_raw) {
+                        Some(event) => raw_events.push(event),
+                        None => warn!("Dropping malformed process event ({} bytes)", item.len()),
+                    }
+                }
+                guard.clear_ready();
 
-        if std::fs::File::open("/proc/sys/kernel/bpf_stats_enabled").is_ok() {
-            info!("Can access BPF stats - BPF subsystem available");
-            return true;
-        }
+                for event in raw_events {
+                    if events.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }));
 
-        // For now, let's be optimistic and allow eBPF if we can read related files
-        warn!("eBPF might be available (detected through file access)");
-        false
+        Ok(())
     }
+}
 
-    /// Collect snapshot of current process information
-    pub async fn collect_snapshot(&self) -> Result<serde_json::Value> {
-        // This would normally collect data from eBPF programs
-        // For now, return a mock snapshot
-        Ok(serde_json::json!({
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "recent_processes": [],
-            "active_connections": [],
-            "file_operations": []
-        }))
+impl Drop for ProcessMonitorEbpf {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
     }
 }
 
-#[allow(dead_code)]
-/// Test if we can actually load an eBPF program
-pub fn test_ebpf_loading() -> bool {
-    // Try to access eBPF-related files to test if we have proper permissions
-    if std::fs::File::open("/sys/kernel/debug/tracing/events").is_ok() {
-        info!("Can access /sys/kernel/debug/tracing/events - eBPF likely available");
-        return true;
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exec_event_into_raw() {
+        let mut cmdline = [0u8; 256];
+        cmdline[..12].copy_from_slice(b"/usr/bin/vim");
+        let event = ProcessEvent {
+            pid: 42,
+            ppid: 0,
+            uid: 1000,
+            gid: 100,
+            comm: [0; 16],
+            cmdline,
+            event_type: 0,
+        };
 
-    if std::fs::File::open("/proc/sys/kernel/bpf_stats_enabled").is_ok() {
-        info!("Can access BPF stats - BPF subsystem available");
-        return true;
+        assert_eq!(
+            event.into_raw(),
+            Some(RawProcessEvent::Exec {
+                pid: 42,
+                uid: Some(1000),
+                filename: Some("/usr/bin/vim".to_string())
+            })
+        );
+        assert_eq!(
+            ProcessEvent { event_type: 1, ..event }.into_raw(),
+            Some(RawProcessEvent::Exit { pid: 42 })
+        );
     }
-
-    // Try to create a BPF map to test if syscall is available
-    // For now, let's be optimistic and allow eBPF if we have file access capabilities
-    info!("eBPF loading test completed - permissions seem sufficient");
-    false
 }
@@ -3,42 +3,52 @@ use dots_family_db::{
     models::{NewDiskIoEvent, NewMemoryEvent},
     Database,
 };
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::{debug, warn};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
 
-use crate::ebpf::{disk_io_monitor::DiskIoEvent, memory_monitor::MemoryEvent};
+use crate::{
+    ebpf::{disk_io_monitor::DiskIoEvent, memory_monitor::MemoryEvent},
+    profile_resolver::ProfileResolver,
+};
 
 /// Event processor for bridging eBPF events to database storage
 pub struct EbpfEventProcessor {
     db: Database,
+    resolver: Arc<ProfileResolver>,
 }
 
 impl EbpfEventProcessor {
-    /// Create a new event processor
-    pub fn new(db: Database) -> Self {
-        Self { db }
+    /// Create a new event processor attributing events through `resolver`,
+    /// which profile changes invalidate
+    pub fn new(db: Database, resolver: Arc<ProfileResolver>) -> Self {
+        Self { db, resolver }
     }
 
     /// Process a memory event and store it in the database
     pub async fn process_memory_event(
         &self,
         event: MemoryEvent,
-        profile_id: Option<i64>,
+        profile_id: Option<String>,
     ) -> Result<()> {
-        // Get process name from /proc
-        let comm = get_process_name(event.pid).unwrap_or_else(|| format!("pid:{}", event.pid));
-
-        // Use provided profile_id or try to determine from active session
+        // Use provided profile_id or attribute the event by its uid
         let profile_id = match profile_id {
             Some(id) => id,
-            None => {
-                // TODO: Look up active profile for this process
-                // For now, log and skip events without a profile
-                debug!("No active profile for memory event from PID {}, skipping", event.pid);
-                return Ok(());
-            }
+            None => match self.resolver.resolve_process(event.pid, event.uid).await? {
+                Some(id) => id,
+                None => {
+                    debug!(
+                        "Memory event from PID {} (uid {}) is not a child's, skipping",
+                        event.pid, event.uid
+                    );
+                    return Ok(());
+                }
+            },
         };
 
+        let comm = process_name(event.pid, event.comm());
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
 
         let new_event = NewMemoryEvent {
@@ -46,8 +56,9 @@ impl EbpfEventProcessor {
             pid: event.pid as i32,
             comm,
             event_type: event.event_type as i32,
-            size: event.size as i64,
-            page_order: Some(event.order as i32),
+            size: event.size_bytes() as i64,
+            // The kernel side reports page events in bytes already
+            page_order: None,
             timestamp,
         };
 
@@ -64,40 +75,38 @@ impl EbpfEventProcessor {
     pub async fn process_disk_io_event(
         &self,
         event: DiskIoEvent,
-        profile_id: Option<i64>,
+        profile_id: Option<String>,
     ) -> Result<()> {
-        // Get process name from /proc
-        let comm = get_process_name(event.pid).unwrap_or_else(|| format!("pid:{}", event.pid));
-
-        // Use provided profile_id or try to determine from active session
+        // Use provided profile_id or attribute the event by its uid
         let profile_id = match profile_id {
             Some(id) => id,
-            None => {
-                // TODO: Look up active profile for this process
-                // For now, log and skip events without a profile
-                debug!("No active profile for disk I/O event from PID {}, skipping", event.pid);
-                return Ok(());
-            }
+            None => match self.resolver.resolve_process(event.pid, event.uid).await? {
+                Some(id) => id,
+                None => {
+                    debug!(
+                        "Disk I/O event from PID {} (uid {}) is not a child's, skipping",
+                        event.pid, event.uid
+                    );
+                    return Ok(());
+                }
+            },
         };
 
+        let comm = process_name(event.pid, event.comm());
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
 
-        let latency_ns = if event.event_type == 1 {
-            // Only complete events have latency
-            Some(event.latency as i64)
-        } else {
-            None
-        };
+        // Only complete events have latency
+        let latency_ns = (event.event_type() == 1).then_some(event.latency_ns as i64);
 
         let new_event = NewDiskIoEvent {
             profile_id,
             pid: event.pid as i32,
             comm,
-            device_major: event.device_major() as i32,
-            device_minor: event.device_minor() as i32,
+            device_major: event.device_major as i32,
+            device_minor: event.device_minor as i32,
             sector: event.sector as i64,
-            nr_sectors: event.nr_sector as i32,
-            event_type: event.event_type as i32,
+            nr_sectors: event.num_sectors as i32,
+            event_type: event.event_type() as i32,
             latency_ns,
             timestamp,
         };
@@ -110,17 +119,14 @@ impl EbpfEventProcessor {
 
         Ok(())
     }
+}
 
-    /// Get the active profile ID from the database
-    /// Returns None if no profile is currently active
-    #[allow(dead_code)]
-    pub async fn get_active_profile_id(&self) -> Result<Option<i64>> {
-        // TODO: Implement profile lookup
-        // This should query the database for the currently active profile
-        // For now, return None
-        warn!("Active profile lookup not yet implemented");
-        Ok(None)
+/// Name the kernel reported for the task, or from /proc when it sent none
+fn process_name(pid: u32, reported: String) -> String {
+    if !reported.is_empty() {
+        return reported;
     }
+    get_process_name(pid).unwrap_or_else(|| format!("pid:{}", pid))
 }
 
 /// Get process name from /proc/[pid]/comm
@@ -43,7 +43,7 @@ impl EdgeCaseHandler {
 
     /// Check all edge cases
     async fn check_all_edge_cases(&mut self) -> Result<()> {
-        self.check_time_manipulation()?;
+        // Clock changes are handled by the tamper detector
         self.check_timezone_changes()?;
         self.check_network_connectivity().await?;
         self.check_daemon_health().await?;
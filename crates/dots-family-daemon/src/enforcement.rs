@@ -1,16 +1,36 @@
-use std::process::Command;
+use std::{process::Command, sync::Arc};
 
 use anyhow::{Context, Result};
+use dots_wm_bridge::{WindowManagerBridge, WindowTarget};
 use tracing::{debug, error, info, warn};
 
+use crate::{
+    accounts::{uid_of_process, uid_of_user},
+    network_enforcement::{parse_remote_addr, NetworkEnforcer, NetworkRule},
+};
+
+/// Workspace for windows the window manager cannot minimise; Niri clamps an
+/// index past the last workspace to its always-empty trailing one
+const OUT_OF_VIEW_WORKSPACE: &str = "255";
+
 pub struct EnforcementEngine {
     dry_run: bool,
+    network: Arc<NetworkEnforcer>,
 }
 
 impl EnforcementEngine {
     pub fn new(dry_run: bool) -> Self {
+        Self::with_network(dry_run, Arc::new(NetworkEnforcer::new(dry_run)))
+    }
+
+    /// Create an engine that shares network rules with other engines
+    pub fn with_network(dry_run: bool, network: Arc<NetworkEnforcer>) -> Self {
         info!("Initializing enforcement engine (dry_run: {})", dry_run);
-        Self { dry_run }
+        Self { dry_run, network }
+    }
+
+    pub fn network(&self) -> Arc<NetworkEnforcer> {
+        self.network.clone()
     }
 
     pub async fn terminate_process(&self, pid: u32, reason: &str) -> Result<()> {
@@ -58,118 +78,133 @@ impl EnforcementEngine {
             return Ok(());
         }
 
-        match self.detect_window_manager().await? {
-            WindowManager::Niri => self.close_window_niri(app_id, pid).await,
-            WindowManager::Sway => self.close_window_sway(app_id, pid).await,
-            WindowManager::Hyprland => self.close_window_hyprland(app_id, pid).await,
-            WindowManager::Unknown => {
-                warn!("Unknown window manager, falling back to process termination");
-                self.terminate_process(pid, "Window manager not supported").await
-            }
+        let bridge = WindowManagerBridge::for_user(uid_of_process(pid)?)?;
+        if !bridge.get_capabilities().can_close_windows {
+            warn!("Window manager not supported, falling back to process termination");
+            return self.terminate_process(pid, "Window manager not supported").await;
         }
-    }
 
-    async fn detect_window_manager(&self) -> Result<WindowManager> {
-        if std::env::var("NIRI_SOCKET").is_ok() {
-            Ok(WindowManager::Niri)
-        } else if std::env::var("SWAYSOCK").is_ok() {
-            Ok(WindowManager::Sway)
-        } else if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
-            Ok(WindowManager::Hyprland)
-        } else {
-            Ok(WindowManager::Unknown)
+        // Not every compositor reports pids, so fall back to matching the app ID
+        let result = match bridge.close_windows(&WindowTarget::Pid(pid)).await {
+            Ok(0) => bridge.close_windows(&WindowTarget::AppId(app_id.to_string())).await,
+            result => result,
+        };
+
+        match result {
+            Ok(0) => {
+                warn!("No window found for app {}, falling back to process termination", app_id);
+                self.terminate_process(pid, "No window found to close").await
+            }
+            Ok(closed) => {
+                info!("Closed {} {} windows for app {}", closed, bridge.get_adapter_name(), app_id);
+                Ok(())
+            }
+            Err(e) => {
+                warn!(
+                    "{} window close failed: {}, falling back to process termination",
+                    bridge.get_adapter_name(),
+                    e
+                );
+                self.terminate_process(pid, "Window close failed").await
+            }
         }
     }
 
-    async fn close_window_niri(&self, app_id: &str, pid: u32) -> Result<()> {
-        debug!("Closing Niri window for app {} (PID: {})", app_id, pid);
-
-        let output = Command::new("niri")
-            .args(["msg", "action", "close-window"])
-            .output()
-            .context("Failed to execute niri command")?;
+    /// Ask every open window in the user's session to close so applications
+    /// get a chance to save
+    pub async fn close_all_windows(&self, username: &str) -> Result<()> {
+        info!("Attempting to close all windows of {}", username);
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            warn!("Niri close-window failed: {}, falling back to process termination", error_msg);
-            self.terminate_process(pid, "Niri window close failed").await?;
-        } else {
-            info!("Successfully closed Niri window for app {}", app_id);
+        if self.dry_run {
+            warn!("DRY RUN: Would close all windows of {}", username);
+            return Ok(());
         }
 
-        Ok(())
-    }
-
-    async fn close_window_sway(&self, app_id: &str, pid: u32) -> Result<()> {
-        debug!("Closing Sway window for app {} (PID: {})", app_id, pid);
-
-        let kill_command = format!("[app_id=\"{}\"] kill", app_id);
-        let output = Command::new("swaymsg")
-            .arg(&kill_command)
-            .output()
-            .context("Failed to execute swaymsg command")?;
+        let bridge = WindowManagerBridge::for_user(uid_of_user(username)?)?;
+        let capabilities = bridge.get_capabilities();
+        if !capabilities.can_close_windows {
+            warn!("Window manager not supported, windows will stay open until the session locks");
+            return Ok(());
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            warn!("Sway kill command failed: {}, falling back to process termination", error_msg);
-            self.terminate_process(pid, "Sway window kill failed").await?;
-        } else {
-            info!("Successfully closed Sway window for app {}", app_id);
+        // Fullscreen windows are taken out of fullscreen first so save dialogs stay visible
+        if capabilities.can_exit_fullscreen {
+            if let Err(e) = bridge.exit_fullscreen(&WindowTarget::All).await {
+                debug!("Could not leave fullscreen: {}", e);
+            }
         }
 
+        let closed = bridge.close_windows(&WindowTarget::All).await?;
+        info!("Closed {} {} windows", closed, bridge.get_adapter_name());
         Ok(())
     }
 
-    async fn close_window_hyprland(&self, app_id: &str, pid: u32) -> Result<()> {
-        debug!("Closing Hyprland window for app {} (PID: {})", app_id, pid);
+    /// Take the user's remaining windows out of view: minimised where the
+    /// window manager can, otherwise moved to an empty workspace
+    pub async fn minimize_all_windows(&self, username: &str) -> Result<()> {
+        debug!("Attempting to minimize all windows of {}", username);
 
-        let kill_command = format!("dispatch closewindow pid:{}", pid);
-        let output = Command::new("hyprctl")
-            .arg(&kill_command)
-            .output()
-            .context("Failed to execute hyprctl command")?;
+        if self.dry_run {
+            warn!("DRY RUN: Would minimize all windows of {}", username);
+            return Ok(());
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            warn!(
-                "Hyprland closewindow failed: {}, falling back to process termination",
-                error_msg
-            );
-            self.terminate_process(pid, "Hyprland window close failed").await?;
+        let bridge = WindowManagerBridge::for_user(uid_of_user(username)?)?;
+        let capabilities = bridge.get_capabilities();
+        let hidden = if capabilities.can_minimize_windows {
+            bridge.minimize_windows(&WindowTarget::All).await?
+        } else if capabilities.can_move_to_workspace {
+            bridge.move_to_workspace(&WindowTarget::All, OUT_OF_VIEW_WORKSPACE).await?
         } else {
-            info!("Successfully closed Hyprland window for app {}", app_id);
+            debug!("Window manager cannot minimize, windows stay in view until the session locks");
+            return Ok(());
+        };
+        if hidden > 0 {
+            info!("Minimized {} {} windows", hidden, bridge.get_adapter_name());
         }
-
         Ok(())
     }
 
     pub async fn block_network_connection(&self, pid: u32, remote_addr: &str) -> Result<()> {
         info!("Attempting to block network connection from PID {} to {}", pid, remote_addr);
 
-        if self.dry_run {
-            warn!("DRY RUN: Would block network connection from PID {} to {}", pid, remote_addr);
-            return Ok(());
+        let result = match (uid_of_process(pid), parse_remote_addr(remote_addr)) {
+            (Ok(uid), Ok(addr)) => self.network.block(uid, NetworkRule::Destination(addr)).await,
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+
+        match result {
+            Ok(()) => {
+                info!(
+                    "Successfully blocked network connection from PID {} to {}",
+                    pid, remote_addr
+                );
+            }
+            Err(e) => {
+                warn!("Network blocking failed: {}, falling back to process termination", e);
+                self.terminate_process(pid, "Network blocking failed").await?;
+            }
         }
 
-        let iptables_rule = format!(
-            "-I OUTPUT -p tcp --dport 80,443 -m owner --pid-owner {} -d {} -j DROP",
-            pid, remote_addr
-        );
+        Ok(())
+    }
 
-        let output = Command::new("iptables")
-            .args(iptables_rule.split_whitespace())
-            .output()
-            .context("Failed to execute iptables command")?;
+    /// Cut off all network access for a user, e.g. outside allowed hours
+    pub async fn block_all_network(&self, username: &str) -> Result<()> {
+        let uid = uid_of_user(username)?;
+        self.network.block(uid, NetworkRule::All).await
+    }
 
-        if output.status.success() {
-            info!("Successfully blocked network connection from PID {} to {}", pid, remote_addr);
-        } else {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            warn!("iptables rule failed: {}, falling back to process termination", error_msg);
-            self.terminate_process(pid, "Network blocking failed").await?;
-        }
+    /// Whether a block set by [`Self::block_all_network`] is in force
+    pub async fn network_blocked(&self, username: &str) -> Result<bool> {
+        let uid = uid_of_user(username)?;
+        Ok(self.network.is_blocked(uid, NetworkRule::All).await)
+    }
 
-        Ok(())
+    /// Lift a block set by [`Self::block_all_network`]
+    pub async fn restore_network(&self, username: &str) -> Result<()> {
+        let uid = uid_of_user(username)?;
+        self.network.unblock(uid, NetworkRule::All).await
     }
 
     pub async fn notify_user(&self, title: &str, message: &str) -> Result<()> {
@@ -300,6 +335,10 @@ impl EnforcementEngine {
                     self.terminate_process(process_id, &decision.reason).await?;
                 }
             }
+            "time_limit" => {
+                // Time limits escalate gradually so unsaved work isn't lost
+                debug!("Time limit reached, handled by the escalation ladder: {}", decision.reason);
+            }
             "allow" => {
                 debug!("Policy allows access: {}", decision.reason);
             }
@@ -316,14 +355,6 @@ impl EnforcementEngine {
     }
 }
 
-#[derive(Debug, Clone)]
-enum WindowManager {
-    Niri,
-    Sway,
-    Hyprland,
-    Unknown,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +386,20 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_dry_run_close_all_windows() {
+        let engine = EnforcementEngine::new(true);
+        let result = engine.close_all_windows("child").await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_minimize_all_windows() {
+        let engine = EnforcementEngine::new(true);
+        let result = engine.minimize_all_windows("child").await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_notification() {
         let engine = EnforcementEngine::new(true);
@@ -0,0 +1,102 @@
+//! Live event bus. Components publish typed [`Event`]s here; the daemon
+//! sends each one to subscribed guardians as the `FamilyEvent` D-Bus signal
+//! and keeps the most recent ones so a dashboard can fill in what happened
+//! before it connected.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use dots_family_proto::events::{Event, EventFilter};
+use tokio::sync::broadcast;
+
+/// Events kept for `RecentEvents` and queued for each slow subscriber
+const EVENT_CAPACITY: usize = 256;
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    recent: Arc<Mutex<VecDeque<Event>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { sender, recent: Arc::new(Mutex::new(VecDeque::with_capacity(EVENT_CAPACITY))) }
+    }
+
+    pub fn publish(&self, event: Event) {
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == EVENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+        // Nobody listening is not an error
+        let _ = self.sender.send(event);
+    }
+
+    /// Events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Recently published events matching `filter`, oldest first
+    pub fn recent(&self, filter: &EventFilter) -> Vec<Event> {
+        self.recent.lock().unwrap().iter().filter(|event| filter.matches(event)).cloned().collect()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn revoked(exception_id: &str) -> Event {
+        Event::ExceptionRevoked { exception_id: exception_id.to_string(), timestamp: Utc::now() }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_get_published_events() {
+        let bus = EventBus::new();
+        let mut subscriber = bus.subscribe();
+
+        bus.publish(revoked("exc-1"));
+        assert_eq!(subscriber.recv().await.unwrap(), bus.recent(&EventFilter::default())[0]);
+    }
+
+    #[test]
+    fn test_recent_events_are_bounded_and_filtered() {
+        let bus = EventBus::new();
+        for i in 0..EVENT_CAPACITY + 10 {
+            bus.publish(revoked(&format!("exc-{}", i)));
+        }
+        let profile_id = Uuid::new_v4();
+        bus.publish(Event::AppFocused {
+            profile_id,
+            app_id: "firefox".to_string(),
+            timestamp: Utc::now(),
+        });
+
+        let all = bus.recent(&EventFilter::default());
+        assert_eq!(all.len(), EVENT_CAPACITY);
+        match &all[0] {
+            Event::ExceptionRevoked { exception_id, .. } => assert_eq!(exception_id, "exc-11"),
+            other => panic!("unexpected oldest event: {:?}", other),
+        }
+
+        let focused = bus.recent(&EventFilter { kinds: Vec::new(), profile_id: Some(profile_id) });
+        assert_eq!(focused.len(), 1);
+        assert_eq!(focused[0].kind(), "app_focused");
+    }
+}
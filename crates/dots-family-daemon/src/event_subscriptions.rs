@@ -0,0 +1,235 @@
+//! Who gets the live `FamilyEvent` signal.
+//!
+//! Live events carry every child's activity, so they are never broadcast. A
+//! client calls `SubscribeEvents` with a session token and each event its
+//! guardian may see is then sent to that connection alone, filtered the way
+//! `RecentEvents` filters the backlog. A subscription ends when the client
+//! leaves the bus or its session is revoked or expires.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use dots_family_common::{Guardian, Permission};
+use dots_family_proto::{
+    daemon::OBJECT_PATH,
+    events::{Event, EventFilter},
+    payload,
+};
+use tracing::{debug, info};
+
+use crate::profile_manager::ProfileManager;
+
+/// How often subscribers' sessions are checked again
+pub const SESSION_RECHECK: Duration = Duration::from_secs(30);
+
+struct Subscriber {
+    token: String,
+    guardian: Guardian,
+    filter: EventFilter,
+}
+
+/// Subscribed connections, by unique bus name
+#[derive(Clone, Default)]
+pub struct EventSubscriptions {
+    subscribers: Arc<Mutex<HashMap<String, Subscriber>>>,
+}
+
+impl EventSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `subscriber` the events matching `filter` that `guardian` may see,
+    /// in place of what it subscribed to before
+    pub fn subscribe(
+        &self,
+        subscriber: &str,
+        token: &str,
+        guardian: Guardian,
+        filter: EventFilter,
+    ) {
+        let subscription = Subscriber { token: token.to_string(), guardian, filter };
+        self.subscribers.lock().unwrap().insert(subscriber.to_string(), subscription);
+        debug!("{} subscribed to family events", subscriber);
+    }
+
+    /// Forget a connection that left the bus
+    pub fn unsubscribe(&self, subscriber: &str) {
+        if self.subscribers.lock().unwrap().remove(subscriber).is_some() {
+            debug!("{} unsubscribed from family events", subscriber);
+        }
+    }
+
+    /// Connections `event` is sent to
+    fn recipients(&self, event: &Event) -> Vec<String> {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, subscriber)| {
+                subscriber.filter.matches(event) && visible_to(event, &subscriber.guardian)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Send `event` as `FamilyEvent` to each subscriber who may see it
+    pub async fn emit(
+        &self,
+        conn: &zbus::Connection,
+        service_name: &str,
+        event: &Event,
+    ) -> Result<()> {
+        let recipients = self.recipients(event);
+        if recipients.is_empty() {
+            return Ok(());
+        }
+
+        let profile_id = event.profile_id().map(|id| id.to_string()).unwrap_or_default();
+        let event_json = payload::encode(event)?;
+        for recipient in &recipients {
+            conn.emit_signal(
+                Some(recipient.as_str()),
+                OBJECT_PATH,
+                service_name,
+                "FamilyEvent",
+                &(event.kind(), profile_id.as_str(), event_json.as_str()),
+            )
+            .await?;
+        }
+
+        debug!("Sent FamilyEvent {} to {} subscribers", event.kind(), recipients.len());
+        Ok(())
+    }
+
+    /// Check each subscriber's session again, picking up role and scope
+    /// changes and dropping revoked or expired sessions
+    pub async fn recheck(&self, profile_manager: &ProfileManager) {
+        let sessions: Vec<(String, String)> = self
+            .subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, subscriber)| (name.clone(), subscriber.token.clone()))
+            .collect();
+
+        for (name, token) in sessions {
+            let result = profile_manager
+                .authorize_session(&token, Permission::View, "subscribe_events", None)
+                .await;
+            let mut subscribers = self.subscribers.lock().unwrap();
+            match result {
+                Ok(guardian) => {
+                    if let Some(subscriber) = subscribers.get_mut(&name) {
+                        subscriber.guardian = guardian;
+                    }
+                }
+                Err(e) => {
+                    info!("Ending family event subscription of {}: {}", name, e);
+                    subscribers.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+/// Guardians limited to some profiles only see events about those
+pub fn visible_to(event: &Event, guardian: &Guardian) -> bool {
+    match event.profile_id() {
+        Some(profile_id) => guardian.can_access_profile(&profile_id.to_string()),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use dots_family_common::GuardianRole;
+    use futures::StreamExt;
+    use uuid::Uuid;
+    use zbus::{message::Type, MatchRule, MessageStream};
+
+    use super::*;
+
+    const INTERFACE: &str = "org.dots.FamilyDaemon";
+
+    fn guardian(profile_ids: &[Uuid]) -> Guardian {
+        Guardian {
+            id: "g1".to_string(),
+            name: "grandma".to_string(),
+            role: GuardianRole::Viewer,
+            profile_ids: profile_ids.iter().map(Uuid::to_string).collect(),
+        }
+    }
+
+    fn focused(profile_id: Uuid, app_id: &str) -> Event {
+        Event::AppFocused { profile_id, app_id: app_id.to_string(), timestamp: Utc::now() }
+    }
+
+    #[tokio::test]
+    async fn test_events_only_reach_subscribers_who_may_see_them() {
+        let (client_stream, server_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+        let server = zbus::connection::Builder::unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = tokio::try_join!(server, client).unwrap();
+
+        // Everything the daemon sends, whoever it is addressed to
+        let rule = MatchRule::builder().msg_type(Type::Signal).build();
+        let mut sent = MessageStream::for_match_rule(rule, &client, None).await.unwrap();
+
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let subscriptions = EventSubscriptions::new();
+        subscriptions.subscribe(":1.50", "token-a", guardian(&[alice]), EventFilter::default());
+        subscriptions.subscribe(":1.51", "token-b", guardian(&[]), EventFilter::default());
+        subscriptions.subscribe(":1.52", "token-c", guardian(&[]), EventFilter::default());
+        subscriptions.unsubscribe(":1.52");
+
+        subscriptions.emit(&server, INTERFACE, &focused(bob, "minecraft")).await.unwrap();
+        subscriptions.emit(&server, INTERFACE, &focused(alice, "firefox")).await.unwrap();
+
+        let mut deliveries = Vec::new();
+        let quiet = Duration::from_millis(200);
+        while let Ok(Some(message)) = tokio::time::timeout(quiet, sent.next()).await {
+            let message = message.unwrap();
+            let header = message.header();
+            let (_, _, event_json): (String, String, String) =
+                message.body().deserialize().unwrap();
+            let Event::AppFocused { app_id, .. } = payload::decode(&event_json).unwrap() else {
+                panic!("unexpected event: {}", event_json);
+            };
+            deliveries.push((header.destination().unwrap().to_string(), app_id));
+        }
+        deliveries.sort();
+
+        // Nothing is broadcast, the guardian of Alice alone never sees Bob's
+        // activity, and a connection that left gets nothing
+        assert_eq!(
+            deliveries,
+            [
+                (":1.50".to_string(), "firefox".to_string()),
+                (":1.51".to_string(), "firefox".to_string()),
+                (":1.51".to_string(), "minecraft".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nothing_is_sent_without_subscribers() {
+        let subscriptions = EventSubscriptions::new();
+        let event = focused(Uuid::new_v4(), "firefox");
+        assert!(subscriptions.recipients(&event).is_empty());
+
+        let filter = EventFilter { kinds: vec!["exception_revoked".to_string()], profile_id: None };
+        subscriptions.subscribe(":1.60", "token", guardian(&[]), filter);
+        assert!(subscriptions.recipients(&event).is_empty());
+    }
+}
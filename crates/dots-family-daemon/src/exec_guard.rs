@@ -0,0 +1,487 @@
+//! Denies launches of blocked applications before they run.
+//!
+//! Reactive blocking only kills an application after its `ProcessStarted` or
+//! `WindowFocused` event arrives. The exec guard answers the kernel's
+//! `FAN_OPEN_EXEC_PERM` permission events instead, so `execve` of a blocked
+//! binary by a child account fails with `EPERM` and the program never starts.
+//!
+//! Every filesystem mounted when the guard starts is watched, and
+//! filesystems mounted later (removable media, bind mounts) are added as
+//! `/proc/self/mountinfo` reports them.
+//!
+//! The deny map holds, per child uid, the application names from the
+//! profile's blocklist. Tests build the guard in test mode, which evaluates
+//! launches against the same map without registering with the kernel.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    os::fd::{AsFd, AsRawFd},
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Context, Result};
+use dots_family_common::types::Profile;
+use nix::{
+    errno::Errno,
+    poll::{poll, PollFd, PollFlags, PollTimeout},
+    sys::fanotify::{
+        EventFFlags, Fanotify, FanotifyResponse, InitFlags, MarkFlags, MaskFlags, Response,
+    },
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use crate::accounts::{uid_of_process, uid_of_user};
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+
+/// Kernel filesystems nothing can be executed from
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "proc",
+    "pstore",
+    "securityfs",
+    "sysfs",
+    "tracefs",
+];
+
+/// A launch denied by the exec guard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedExec {
+    pub pid: u32,
+    pub uid: u32,
+    pub profile_id: String,
+    pub path: String,
+    /// Blocklist entry the executable matched
+    pub rule: String,
+}
+
+struct ChildRules {
+    profile_id: String,
+    blocked: HashSet<String>,
+}
+
+/// Blocked application names per child uid
+#[derive(Default)]
+pub struct ExecDenyMap {
+    by_uid: HashMap<u32, ChildRules>,
+}
+
+impl ExecDenyMap {
+    pub fn set(&mut self, uid: u32, profile_id: &str, blocked: &[String]) {
+        let blocked: HashSet<String> = blocked
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .filter(|n| !n.is_empty())
+            .collect();
+
+        if blocked.is_empty() {
+            self.by_uid.remove(&uid);
+        } else {
+            self.by_uid.insert(uid, ChildRules { profile_id: profile_id.to_string(), blocked });
+        }
+    }
+
+    #[cfg(test)]
+    pub fn remove(&mut self, uid: u32) {
+        self.by_uid.remove(&uid);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_uid.is_empty()
+    }
+
+    /// The profile and matching rule if `uid` may not execute `path`
+    pub fn check(&self, uid: u32, path: &str) -> Option<(&str, &str)> {
+        let rules = self.by_uid.get(&uid)?;
+        let path = path.to_lowercase();
+
+        if let Some(rule) = rules.blocked.get(&path) {
+            return Some((&rules.profile_id, rule));
+        }
+
+        let name = executable_name(&path);
+        rules.blocked.get(name).map(|rule| (rules.profile_id.as_str(), rule.as_str()))
+    }
+}
+
+/// Name an application is blocked by, seeing through Nix wrappers such as
+/// `.firefox-wrapped`
+pub(crate) fn executable_name(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.strip_prefix('.').and_then(|n| n.strip_suffix("-wrapped")) {
+        Some(wrapped) => wrapped,
+        None => name,
+    }
+}
+
+struct GuardState {
+    deny: RwLock<ExecDenyMap>,
+    /// Report denials without enforcing them
+    dry_run: bool,
+    reports: mpsc::UnboundedSender<BlockedExec>,
+}
+
+impl GuardState {
+    /// Decide whether a launch may proceed, reporting it if not
+    fn allow(&self, pid: u32, uid: u32, path: &str) -> bool {
+        let blocked = {
+            let deny = self.deny.read().unwrap();
+            deny.check(uid, path).map(|(profile_id, rule)| BlockedExec {
+                pid,
+                uid,
+                profile_id: profile_id.to_string(),
+                path: path.to_string(),
+                rule: rule.to_string(),
+            })
+        };
+
+        let Some(blocked) = blocked else {
+            return true;
+        };
+
+        if self.dry_run {
+            warn!("DRY RUN: Would deny launch of {} by uid {}", path, uid);
+        } else {
+            info!("Denied launch of {} by uid {} (rule: {})", path, uid, blocked.rule);
+        }
+        let _ = self.reports.send(blocked);
+
+        self.dry_run
+    }
+}
+
+pub struct ExecGuard {
+    state: Arc<GuardState>,
+    test_mode: bool,
+}
+
+impl ExecGuard {
+    pub fn new(dry_run: bool) -> (Self, mpsc::UnboundedReceiver<BlockedExec>) {
+        Self::build(dry_run, false)
+    }
+
+    /// A guard that only evaluates the deny map, without kernel enforcement
+    #[cfg(test)]
+    pub fn new_test_mode() -> (Self, mpsc::UnboundedReceiver<BlockedExec>) {
+        Self::build(false, true)
+    }
+
+    fn build(dry_run: bool, test_mode: bool) -> (Self, mpsc::UnboundedReceiver<BlockedExec>) {
+        let (reports, receiver) = mpsc::unbounded_channel();
+        let state = Arc::new(GuardState { deny: RwLock::default(), dry_run, reports });
+        (Self { state, test_mode }, receiver)
+    }
+
+    /// Start answering exec permission events. Fails if fanotify is not
+    /// available, in which case blocked apps are still closed after launch.
+    pub fn start(&self) -> Result<()> {
+        if self.test_mode {
+            info!("Exec guard running in test mode, launches are not intercepted");
+            return Ok(());
+        }
+
+        let fanotify = Arc::new(
+            Fanotify::init(
+                InitFlags::FAN_CLASS_CONTENT | InitFlags::FAN_CLOEXEC,
+                EventFFlags::O_RDONLY | EventFFlags::O_LARGEFILE | EventFFlags::O_CLOEXEC,
+            )
+            .context("Failed to initialize fanotify")?,
+        );
+
+        // Opened before the first scan so no mount in between goes unnoticed
+        let mountinfo = File::open(MOUNTINFO).context("Failed to open mount table")?;
+        let mut marked = HashSet::new();
+        mark_filesystems(&fanotify, &mut marked);
+        if marked.is_empty() {
+            return Err(anyhow::anyhow!("No filesystem could be watched for executions"));
+        }
+
+        let state = self.state.clone();
+        let events = fanotify.clone();
+        std::thread::Builder::new()
+            .name("exec-guard".to_string())
+            .spawn(move || {
+                if let Err(e) = answer_exec_events(&events, &state) {
+                    error!("Exec guard stopped: {}", e);
+                }
+            })
+            .context("Failed to start exec guard thread")?;
+
+        info!("Exec guard watching {} filesystems", marked.len());
+
+        std::thread::Builder::new()
+            .name("exec-guard-mounts".to_string())
+            .spawn(move || {
+                if let Err(e) = follow_mounts(&fanotify, &mountinfo, &mut marked) {
+                    error!("Exec guard stopped following mounts: {}", e);
+                }
+            })
+            .context("Failed to start exec guard mount thread")?;
+
+        Ok(())
+    }
+
+    /// Decide a launch the same way the kernel hook would
+    #[cfg(test)]
+    pub fn check_exec(&self, pid: u32, uid: u32, path: &str) -> bool {
+        self.state.allow(pid, uid, path)
+    }
+
+    /// Replace the deny map with the blocklists of the given profiles
+    pub fn sync_profiles(&self, profiles: &[Profile]) {
+        let mut deny = ExecDenyMap::default();
+
+        for profile in profiles.iter().filter(|profile| profile.active) {
+            let Some(username) = &profile.username else {
+                continue;
+            };
+            match uid_of_user(username) {
+                Ok(uid) => {
+                    deny.set(uid, &profile.id.to_string(), &profile.config.applications.blocked)
+                }
+                Err(e) => debug!("Skipping exec rules for {}: {}", profile.name, e),
+            }
+        }
+
+        *self.state.deny.write().unwrap() = deny;
+    }
+
+    #[cfg(test)]
+    pub fn set_rules(&self, uid: u32, profile_id: &str, blocked: &[String]) {
+        self.state.deny.write().unwrap().set(uid, profile_id, blocked);
+    }
+
+    #[cfg(test)]
+    pub fn clear_rules(&self, uid: u32) {
+        self.state.deny.write().unwrap().remove(uid);
+    }
+}
+
+/// A mounted filesystem, by device and the first place it is mounted at
+#[derive(Debug, PartialEq, Eq)]
+struct MountedFilesystem {
+    device: String,
+    mount_point: String,
+}
+
+/// Filesystems in a `/proc/<pid>/mountinfo` table that can hold programs,
+/// each listed once however often it is mounted
+fn executable_filesystems(mountinfo: &str) -> Vec<MountedFilesystem> {
+    let mut seen = HashSet::new();
+
+    mountinfo
+        .lines()
+        .filter_map(|line| {
+            let (mount, filesystem) = line.split_once(" - ")?;
+            let mut mount = mount.split(' ');
+            let device = mount.nth(2)?;
+            let mount_point = mount.nth(1)?;
+            let fs_type = filesystem.split(' ').next()?;
+
+            if PSEUDO_FILESYSTEMS.contains(&fs_type) || !seen.insert(device) {
+                return None;
+            }
+            Some(MountedFilesystem {
+                device: device.to_string(),
+                mount_point: unescape_mount_point(mount_point),
+            })
+        })
+        .collect()
+}
+
+/// Undo the octal escapes (`\040` for a space) the kernel uses in mount points
+fn unescape_mount_point(escaped: &str) -> String {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let code = tail.get(..3).and_then(|digits| {
+            std::str::from_utf8(digits).ok().and_then(|d| u8::from_str_radix(d, 8).ok())
+        });
+        match (byte, code) {
+            (b'\\', Some(code)) => {
+                bytes.push(code);
+                rest = &tail[3..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Watch every filesystem in the mount table not already in `marked`.
+/// Filesystems that were unmounted are forgotten so a remount is watched again.
+fn mark_filesystems(fanotify: &Fanotify, marked: &mut HashSet<String>) {
+    let mountinfo = match std::fs::read_to_string(MOUNTINFO) {
+        Ok(mountinfo) => mountinfo,
+        Err(e) => {
+            warn!("Failed to read mount table: {}", e);
+            return;
+        }
+    };
+
+    let filesystems = executable_filesystems(&mountinfo);
+    marked.retain(|device| filesystems.iter().any(|fs| &fs.device == device));
+
+    for filesystem in filesystems {
+        if marked.contains(&filesystem.device) {
+            continue;
+        }
+        match fanotify.mark(
+            MarkFlags::FAN_MARK_ADD | MarkFlags::FAN_MARK_FILESYSTEM,
+            MaskFlags::FAN_OPEN_EXEC_PERM,
+            None,
+            Some(filesystem.mount_point.as_str()),
+        ) {
+            Ok(()) => {
+                debug!("Watching executions on {}", filesystem.mount_point);
+                marked.insert(filesystem.device);
+            }
+            Err(e) => {
+                debug!("Cannot watch executions on {}: {}", filesystem.mount_point, e)
+            }
+        }
+    }
+}
+
+/// Mark filesystems as they are mounted. The kernel flags the mount table
+/// with `POLLPRI` whenever it changes.
+fn follow_mounts(
+    fanotify: &Fanotify,
+    mountinfo: &File,
+    marked: &mut HashSet<String>,
+) -> Result<()> {
+    loop {
+        let mut fds = [PollFd::new(mountinfo.as_fd(), PollFlags::POLLPRI)];
+        match poll(&mut fds, PollTimeout::NONE) {
+            Ok(_) => mark_filesystems(fanotify, marked),
+            Err(Errno::EINTR) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn answer_exec_events(fanotify: &Fanotify, state: &GuardState) -> Result<()> {
+    let own_pid = std::process::id();
+
+    loop {
+        let events = match fanotify.read_events() {
+            Ok(events) => events,
+            Err(Errno::EINTR) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        for event in events {
+            let Some(fd) = event.fd() else {
+                warn!("Exec guard event queue overflowed");
+                continue;
+            };
+
+            let pid = event.pid() as u32;
+            // Every exec on the system waits for this answer, so anything
+            // that cannot be resolved is allowed rather than retried
+            let allowed = pid == own_pid
+                || !event.mask().contains(MaskFlags::FAN_OPEN_EXEC_PERM)
+                || state.deny.read().unwrap().is_empty()
+                || match (
+                    uid_of_process(pid),
+                    std::fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd())),
+                ) {
+                    (Ok(uid), Ok(path)) => state.allow(pid, uid, &path.to_string_lossy()),
+                    _ => true,
+                };
+
+            let response = if allowed { Response::FAN_ALLOW } else { Response::FAN_DENY };
+            if let Err(e) = fanotify.write_response(FanotifyResponse::new(fd, response)) {
+                warn!("Failed to answer exec permission event for PID {}: {}", pid, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_map_matches_name_and_path() {
+        let mut deny = ExecDenyMap::default();
+        deny.set(1001, "child", &["Steam".to_string(), "/opt/games/run.sh".to_string()]);
+
+        assert_eq!(deny.check(1001, "/usr/bin/steam"), Some(("child", "steam")));
+        assert_eq!(deny.check(1001, "/opt/games/run.sh"), Some(("child", "/opt/games/run.sh")));
+        assert_eq!(
+            deny.check(1001, "/nix/store/abc-firefox/bin/.steam-wrapped"),
+            Some(("child", "steam"))
+        );
+        assert_eq!(deny.check(1001, "/usr/bin/steamcmd"), None);
+        assert_eq!(deny.check(1002, "/usr/bin/steam"), None);
+
+        deny.set(1001, "child", &[]);
+        assert!(deny.is_empty());
+    }
+
+    #[test]
+    fn test_executable_filesystems_from_mountinfo() {
+        let mountinfo = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid shared:5 - proc proc rw
+24 22 259:2 /nix/store /nix/store ro,relatime shared:2 - ext4 /dev/nvme0n1p2 rw
+25 22 0:32 / /tmp rw,nosuid shared:7 - tmpfs tmpfs rw
+26 22 8:17 / /run/media/child/USB\\040STICK rw,nosuid shared:9 - vfat /dev/sdb1 rw
+27 22 0:26 / /sys/fs/cgroup rw shared:4 - cgroup2 cgroup2 rw
+";
+
+        let filesystems = executable_filesystems(mountinfo);
+        let mount_points: Vec<&str> =
+            filesystems.iter().map(|fs| fs.mount_point.as_str()).collect();
+        assert_eq!(mount_points, vec!["/", "/tmp", "/run/media/child/USB STICK"]);
+        assert_eq!(filesystems[2].device, "8:17");
+    }
+
+    #[test]
+    fn test_test_mode_denies_and_reports() {
+        let (guard, mut reports) = ExecGuard::new_test_mode();
+        guard.start().unwrap();
+        guard.set_rules(1001, "child", &["minecraft".to_string()]);
+
+        assert!(guard.check_exec(4242, 1000, "/usr/bin/minecraft"));
+        assert!(guard.check_exec(4242, 1001, "/usr/bin/firefox"));
+        assert!(!guard.check_exec(4242, 1001, "/usr/bin/minecraft"));
+
+        let blocked = reports.try_recv().unwrap();
+        assert_eq!(blocked.uid, 1001);
+        assert_eq!(blocked.profile_id, "child");
+        assert_eq!(blocked.rule, "minecraft");
+        assert!(reports.try_recv().is_err());
+
+        guard.clear_rules(1001);
+        assert!(guard.check_exec(4242, 1001, "/usr/bin/minecraft"));
+    }
+
+    #[test]
+    fn test_dry_run_reports_but_allows() {
+        let (guard, mut reports) = ExecGuard::build(true, true);
+        guard.set_rules(1001, "child", &["minecraft".to_string()]);
+
+        assert!(guard.check_exec(4242, 1001, "/usr/bin/minecraft"));
+        assert_eq!(reports.try_recv().unwrap().path, "/usr/bin/minecraft");
+    }
+}
@@ -0,0 +1,1058 @@
+//! Local HTTP/JSON management API for web and phone clients.
+//!
+//! Routes under `/api/v1` call the daemon's own `org.dots.FamilyDaemon`
+//! interface over its bus connection, so they go through the same checks and
+//! emit the same signals as D-Bus clients. Callers sign in with the parent
+//! password or a guardian account and send the session token as
+//! `Authorization: Bearer <token>`. The daemon's signals and live family
+//! events are streamed from `/api/v1/events` as server-sent events.
+//!
+//! By default the API speaks plain HTTP on a loopback address. It serves
+//! HTTPS when a certificate and key are configured, and only then may it be
+//! bound to a LAN interface with `allow_lan`, as passwords and session tokens
+//! must not cross the network unencrypted. Sign-ins are rate-limited per
+//! account by the daemon, like those made over D-Bus.
+
+use std::{net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post},
+    Json, Router,
+};
+use dots_family_common::{types::ExceptionDuration, Guardian, Permission};
+use dots_family_proto::{
+    daemon::{FamilyDaemonProxy, OBJECT_PATH},
+    error::{DaemonError, Error},
+    events::{Event as FamilyEvent, EventFilter},
+    payload::{self, ExceptionTerm, ProfileList},
+};
+use futures::{Stream, StreamExt};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use openssl::ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::broadcast};
+use tokio_openssl::SslStream;
+use tracing::{debug, info, warn};
+use zbus::{message::Type, Connection, MatchRule, Message, MessageStream};
+
+use crate::{config::HttpApiConfig, event_bus::EventBus, profile_manager::ProfileManager};
+
+const INTERFACE: &str = "org.dots.FamilyDaemon";
+
+/// Signals kept for each event stream before a slow client starts missing them
+const EVENT_BACKLOG: usize = 64;
+
+/// How often an open event stream checks its session is still valid
+const SESSION_RECHECK: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+struct ApiState {
+    daemon: FamilyDaemonProxy<'static>,
+    profile_manager: ProfileManager,
+    events: broadcast::Sender<SignalEvent>,
+}
+
+/// A daemon signal as sent to event stream clients
+#[derive(Debug, Clone, PartialEq)]
+struct SignalEvent {
+    name: String,
+    data: Value,
+}
+
+impl SignalEvent {
+    /// Events about a profile only go to guardians who may see that profile;
+    /// events not tied to a profile only go to guardians of every profile
+    fn visible_to(&self, guardian: &Guardian) -> bool {
+        match self.data.get("profile_id").and_then(Value::as_str) {
+            Some(profile_id) => guardian.can_access_profile(profile_id),
+            None => guardian.is_unscoped(),
+        }
+    }
+}
+
+/// Serve the API on the configured address until the daemon exits
+pub async fn serve(
+    config: &HttpApiConfig,
+    connection: Connection,
+    service_name: &str,
+    profile_manager: ProfileManager,
+    family_events: EventBus,
+) -> Result<()> {
+    let addr = bind_address(config)?;
+    let tls = tls_acceptor(config)?;
+
+    let daemon = FamilyDaemonProxy::builder(&connection)
+        .destination(service_name.to_string())?
+        .build()
+        .await?;
+
+    let (events, _) = broadcast::channel(EVENT_BACKLOG);
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface(INTERFACE)?
+        .path(OBJECT_PATH)?
+        .build();
+    let signals = MessageStream::for_match_rule(rule, &connection, None).await?;
+    tokio::spawn(forward_signals(signals, events.clone(), profile_manager.clone()));
+    tokio::spawn(forward_family_events(family_events.subscribe(), events.clone()));
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind HTTP API to {}", addr))?;
+    let app = router(ApiState { daemon, profile_manager, events });
+
+    match tls {
+        Some(acceptor) => {
+            info!("HTTP API listening on https://{}/api/v1", addr);
+            serve_tls(listener, acceptor, app).await;
+        }
+        None => {
+            info!("HTTP API listening on http://{}/api/v1", addr);
+            axum::serve(listener, app).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse the bind address. One other machines can reach needs `allow_lan`
+/// and TLS, as session tokens and passwords would otherwise travel in clear.
+fn bind_address(config: &HttpApiConfig) -> Result<SocketAddr> {
+    let addr: SocketAddr = config
+        .bind_address
+        .parse()
+        .with_context(|| format!("Invalid HTTP API bind address: {}", config.bind_address))?;
+
+    if !addr.ip().is_loopback() {
+        if !config.allow_lan {
+            bail!(
+                "HTTP API bind address {} is not loopback; set allow_lan and a TLS certificate \
+                 to serve other machines",
+                addr
+            );
+        }
+        if !tls_configured(config)? {
+            bail!("HTTP API bind address {} needs tls_cert_path and tls_key_path", addr);
+        }
+    }
+    Ok(addr)
+}
+
+/// Whether HTTPS is configured; a certificate without its key or the other
+/// way round is a mistake rather than a request for plain HTTP
+fn tls_configured(config: &HttpApiConfig) -> Result<bool> {
+    match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(_), Some(_)) => Ok(true),
+        (None, None) => Ok(false),
+        _ => bail!("HTTP API TLS needs both tls_cert_path and tls_key_path"),
+    }
+}
+
+fn tls_acceptor(config: &HttpApiConfig) -> Result<Option<SslAcceptor>> {
+    let (Some(cert), Some(key)) = (&config.tls_cert_path, &config.tls_key_path) else {
+        tls_configured(config)?;
+        return Ok(None);
+    };
+
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+    builder
+        .set_certificate_chain_file(cert)
+        .with_context(|| format!("Failed to load HTTP API certificate {}", cert))?;
+    builder
+        .set_private_key_file(key, SslFiletype::PEM)
+        .with_context(|| format!("Failed to load HTTP API key {}", key))?;
+    builder.check_private_key().context("HTTP API key does not match its certificate")?;
+    Ok(Some(builder.build()))
+}
+
+/// Serve `app` over TLS, one task per connection
+async fn serve_tls(listener: TcpListener, acceptor: SslAcceptor, app: Router) {
+    let acceptor = Arc::new(acceptor);
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                // Usually out of file descriptors; give connections time to close
+                warn!("Failed to accept HTTP API connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        tokio::spawn(async move {
+            let ssl = Ssl::new(acceptor.context()).and_then(|ssl| SslStream::new(ssl, stream));
+            let mut stream = match ssl {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Failed to set up TLS for {}: {}", peer, e);
+                    return;
+                }
+            };
+            if let Err(e) = Pin::new(&mut stream).accept().await {
+                debug!("TLS handshake with {} failed: {}", peer, e);
+                return;
+            }
+
+            let service = TowerToHyperService::new(app);
+            if let Err(e) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("HTTP API connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn forward_signals(
+    mut signals: MessageStream,
+    events: broadcast::Sender<SignalEvent>,
+    profile_manager: ProfileManager,
+) {
+    while let Some(message) = signals.next().await {
+        match message {
+            Ok(message) => {
+                if let Some(mut event) = signal_event(&message) {
+                    attach_profile(&mut event, &profile_manager).await;
+                    // No receivers just means no client is listening
+                    let _ = events.send(event);
+                }
+            }
+            Err(e) => warn!("Failed to read daemon signal for HTTP clients: {}", e),
+        }
+    }
+    warn!("Daemon signal stream for HTTP clients ended");
+}
+
+/// Live events are only sent on the bus to subscribed connections, so they
+/// are taken from the event bus as `FamilyEvent`
+async fn forward_family_events(
+    mut family_events: broadcast::Receiver<FamilyEvent>,
+    events: broadcast::Sender<SignalEvent>,
+) {
+    loop {
+        match family_events.recv().await {
+            Ok(event) => {
+                let data = payload::encode(&event)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Ok(serde_json::from_str(&json)?));
+                match data {
+                    Ok(data) => {
+                        let _ = events.send(SignalEvent { name: "FamilyEvent".to_string(), data });
+                    }
+                    Err(e) => warn!("Failed to encode family event for HTTP clients: {}", e),
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("Family events for HTTP clients fell behind, {} dropped", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Name and arguments of an `org.dots.FamilyDaemon` signal
+fn signal_event(message: &Message) -> Option<SignalEvent> {
+    let header = message.header();
+    let name = header.member()?.to_string();
+    let body = message.body();
+
+    let data = match name.as_str() {
+        "PolicyUpdated" => {
+            let (profile_id,): (String,) = body.deserialize().ok()?;
+            json!({ "profile_id": profile_id })
+        }
+        "ApprovalRequestCreated" => {
+            let (request_id, request_type): (String, String) = body.deserialize().ok()?;
+            json!({ "request_id": request_id, "request_type": request_type })
+        }
+        "ApprovalRequestResolved" => {
+            let (request_id, status, message): (String, String, String) =
+                body.deserialize().ok()?;
+            json!({ "request_id": request_id, "status": status, "message": message })
+        }
+        "TimeLimitWarning" | "TimeWindowEnding" => {
+            let (minutes_remaining,): (u32,) = body.deserialize().ok()?;
+            json!({ "minutes_remaining": minutes_remaining })
+        }
+        "EnforcementStageChanged" => {
+            let (stage, seconds_remaining): (String, i64) = body.deserialize().ok()?;
+            json!({ "stage": stage, "seconds_remaining": seconds_remaining })
+        }
+        "TamperDetected" => {
+            let (reason,): (String,) = body.deserialize().ok()?;
+            json!({ "reason": reason })
+        }
+        _ => return None,
+    };
+
+    Some(SignalEvent { name, data })
+}
+
+/// Add the profile an approval request signal is about, so the event reaches
+/// that profile's guardians
+async fn attach_profile(event: &mut SignalEvent, profile_manager: &ProfileManager) {
+    if event.data.get("profile_id").is_some() {
+        return;
+    }
+    let Some(request_id) = event.data.get("request_id").and_then(Value::as_str) else {
+        return;
+    };
+
+    match profile_manager.approval_request_profile(request_id).await {
+        Ok(Some(profile_id)) => event.data["profile_id"] = json!(profile_id),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to look up the profile of request {}: {}", request_id, e),
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    let api = Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/auth", post(sign_in).delete(sign_out))
+        .route("/events", get(events))
+        .route("/events/recent", get(recent_events))
+        .route("/profiles", get(list_profiles))
+        .route("/profiles/:profile_id/reports/daily", get(daily_report))
+        .route("/profiles/:profile_id/reports/weekly", get(weekly_report))
+        .route("/profiles/:profile_id/exceptions", get(list_exceptions))
+        .route("/profiles/:profile_id/time-windows", get(list_time_windows).post(add_time_window))
+        .route("/profiles/:profile_id/time-windows/:window_type", delete(remove_time_windows))
+        .route("/requests", get(list_requests))
+        .route("/requests/:request_id/approve", post(approve_request))
+        .route("/requests/:request_id/deny", post(deny_request))
+        .route("/exceptions", post(create_exception))
+        .route("/exceptions/:exception_id", delete(revoke_exception));
+
+    Router::new().nest("/api/v1", api).with_state(state)
+}
+
+/// Session token from the `Authorization: Bearer` header
+struct Bearer(String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Bearer {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ApiError> {
+        parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(bearer_token)
+            .map(|token| Bearer(token.to_string()))
+            .ok_or(ApiError::Unauthenticated)
+    }
+}
+
+/// The token of a bearer `Authorization` value. An empty token is refused, as
+/// the daemon would otherwise treat the call as its own.
+fn bearer_token(value: &str) -> Option<&str> {
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+enum ApiError {
+    /// No usable session token was sent
+    Unauthenticated,
+    Daemon(Error),
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        Self::Daemon(error)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<DaemonError>() {
+            Ok(error) => Self::Daemon(error.into()),
+            Err(error) => Self::Daemon(Error::Failed(error.to_string())),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, kind, message) = match self {
+            Self::Unauthenticated => (
+                StatusCode::UNAUTHORIZED,
+                "NotAuthenticated",
+                "Sign in at /api/v1/auth and send the token as a bearer token".to_string(),
+            ),
+            Self::Daemon(error) => {
+                let (status, kind) = error_status(&error);
+                (status, kind, error.to_string())
+            }
+        };
+        (status, Json(json!({ "error": kind, "message": message }))).into_response()
+    }
+}
+
+/// HTTP status and error name for a daemon error reply
+fn error_status(error: &Error) -> (StatusCode, &'static str) {
+    match error {
+        Error::NotAuthorized(_) => (StatusCode::FORBIDDEN, "NotAuthorized"),
+        Error::NotFound(_) => (StatusCode::NOT_FOUND, "NotFound"),
+        Error::InvalidArgument(_) => (StatusCode::BAD_REQUEST, "InvalidArgument"),
+        Error::RateLimited(_) => (StatusCode::TOO_MANY_REQUESTS, "RateLimited"),
+        Error::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, "Unavailable"),
+        Error::Failed(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed"),
+        Error::Dbus(_) => (StatusCode::BAD_GATEWAY, "Dbus"),
+    }
+}
+
+/// A daemon reply, which is already JSON
+fn json_reply(body: String) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+type ApiResult = Result<Response, ApiError>;
+
+async fn openapi() -> Json<Value> {
+    Json(openapi_document())
+}
+
+#[derive(Deserialize)]
+struct SignIn {
+    /// Guardian account name; the shared parent password when absent
+    name: Option<String>,
+    password: String,
+}
+
+async fn sign_in(State(state): State<ApiState>, Json(body): Json<SignIn>) -> ApiResult {
+    let token = match body.name.as_deref() {
+        Some(name) if name != Guardian::PRIMARY_NAME => {
+            state.daemon.authenticate_guardian(name, &body.password).await?
+        }
+        _ => state.daemon.authenticate_parent(&body.password).await?,
+    };
+    Ok(Json(json!({ "token": token })).into_response())
+}
+
+async fn sign_out(State(state): State<ApiState>, Bearer(token): Bearer) -> ApiResult {
+    if !state.daemon.revoke_session(&token).await? {
+        return Err(ApiError::Unauthenticated);
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn events(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let guardian = state
+        .profile_manager
+        .authorize_session(&token, Permission::View, "stream_events", None)
+        .await?;
+
+    let subscription = EventSubscription {
+        receiver: state.events.subscribe(),
+        recheck: tokio::time::interval_at(
+            tokio::time::Instant::now() + SESSION_RECHECK,
+            SESSION_RECHECK,
+        ),
+        profile_manager: state.profile_manager,
+        token,
+        guardian,
+    };
+    let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        Some((event, subscription))
+    })
+    .map(|event| Event::default().event(event.name).json_data(event.data));
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// One client's event stream, which ends once its session is revoked or expires
+struct EventSubscription {
+    receiver: broadcast::Receiver<SignalEvent>,
+    recheck: tokio::time::Interval,
+    profile_manager: ProfileManager,
+    token: String,
+    guardian: Guardian,
+}
+
+impl EventSubscription {
+    /// The next event the session's guardian may see, or None when the stream ends
+    async fn next(&mut self) -> Option<SignalEvent> {
+        loop {
+            tokio::select! {
+                received = self.receiver.recv() => match received {
+                    Ok(event) if event.visible_to(&self.guardian) => return Some(event),
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("HTTP event stream fell behind, {} signals dropped", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = self.recheck.tick() => {
+                    // Picks up role and scope changes as well as revocation
+                    match self
+                        .profile_manager
+                        .authorize_session(&self.token, Permission::View, "stream_events", None)
+                        .await
+                    {
+                        Ok(guardian) => self.guardian = guardian,
+                        Err(e) => {
+                            info!("Ending HTTP event stream: {}", e);
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentEventsQuery {
+    /// Comma-separated event kinds
+    kind: Option<String>,
+    profile_id: Option<uuid::Uuid>,
+}
+
+async fn recent_events(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Query(query): Query<RecentEventsQuery>,
+) -> ApiResult {
+    let kinds = query
+        .kind
+        .map(|kinds| kinds.split(',').map(str::trim).map(str::to_string).collect())
+        .unwrap_or_default();
+    let filter = EventFilter { kinds, profile_id: query.profile_id };
+    let filter_json = payload::encode(&filter).map_err(|e| Error::Failed(e.to_string()))?;
+
+    Ok(json_reply(state.daemon.recent_events(&filter_json, &token).await?))
+}
+
+async fn list_profiles(State(state): State<ApiState>, Bearer(token): Bearer) -> ApiResult {
+    let guardian = state
+        .profile_manager
+        .authorize_session(&token, Permission::View, "list_profiles", None)
+        .await?;
+
+    let reply = state.daemon.list_profiles().await?;
+    let mut list: ProfileList =
+        payload::decode(&reply).map_err(|e| Error::Failed(e.to_string()))?;
+    list.profiles.retain(|profile| guardian.can_access_profile(&profile.id.to_string()));
+
+    Ok(json_reply(payload::encode(&list).map_err(|e| Error::Failed(e.to_string()))?))
+}
+
+#[derive(Deserialize)]
+struct DailyReportQuery {
+    date: String,
+}
+
+async fn daily_report(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(profile_id): Path<String>,
+    Query(query): Query<DailyReportQuery>,
+) -> ApiResult {
+    state
+        .profile_manager
+        .authorize_session(&token, Permission::View, "get_daily_report", Some(&profile_id))
+        .await?;
+    Ok(json_reply(state.daemon.get_daily_report(&profile_id, &query.date).await?))
+}
+
+#[derive(Deserialize)]
+struct WeeklyReportQuery {
+    week_start: String,
+}
+
+async fn weekly_report(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(profile_id): Path<String>,
+    Query(query): Query<WeeklyReportQuery>,
+) -> ApiResult {
+    state
+        .profile_manager
+        .authorize_session(&token, Permission::View, "get_weekly_report", Some(&profile_id))
+        .await?;
+    Ok(json_reply(state.daemon.get_weekly_report(&profile_id, &query.week_start).await?))
+}
+
+async fn list_exceptions(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(profile_id): Path<String>,
+) -> ApiResult {
+    Ok(json_reply(state.daemon.list_active_exceptions(&profile_id, &token).await?))
+}
+
+#[derive(Deserialize)]
+struct NewException {
+    exception_type: String,
+    reason: String,
+    duration: ExceptionDuration,
+}
+
+async fn create_exception(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Json(body): Json<NewException>,
+) -> ApiResult {
+    let duration = payload::encode(&ExceptionTerm { duration: body.duration })
+        .map_err(|e| Error::Failed(e.to_string()))?;
+    Ok(json_reply(
+        state
+            .daemon
+            .create_exception(&body.exception_type, &body.reason, &duration, &token)
+            .await?,
+    ))
+}
+
+async fn revoke_exception(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(exception_id): Path<String>,
+) -> ApiResult {
+    Ok(json_reply(state.daemon.revoke_exception(&exception_id, &token).await?))
+}
+
+async fn list_requests(State(state): State<ApiState>, Bearer(token): Bearer) -> ApiResult {
+    Ok(json_reply(state.daemon.list_pending_requests(&token).await?))
+}
+
+#[derive(Deserialize, Default)]
+struct Resolution {
+    #[serde(default)]
+    message: String,
+}
+
+async fn approve_request(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(request_id): Path<String>,
+    body: Option<Json<Resolution>>,
+) -> ApiResult {
+    let Json(body) = body.unwrap_or_default();
+    Ok(json_reply(state.daemon.approve_request(&request_id, &body.message, &token).await?))
+}
+
+async fn deny_request(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(request_id): Path<String>,
+    body: Option<Json<Resolution>>,
+) -> ApiResult {
+    let Json(body) = body.unwrap_or_default();
+    Ok(json_reply(state.daemon.deny_request(&request_id, &body.message, &token).await?))
+}
+
+async fn list_time_windows(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(profile_id): Path<String>,
+) -> ApiResult {
+    Ok(json_reply(state.daemon.list_time_windows(&profile_id, &token).await?))
+}
+
+#[derive(Deserialize)]
+struct NewTimeWindow {
+    window_type: String,
+    start: String,
+    end: String,
+}
+
+async fn add_time_window(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path(profile_id): Path<String>,
+    Json(body): Json<NewTimeWindow>,
+) -> ApiResult {
+    Ok(json_reply(
+        state
+            .daemon
+            .add_time_window(&profile_id, &body.window_type, &body.start, &body.end, &token)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct TimeWindowRange {
+    start: Option<String>,
+    end: Option<String>,
+}
+
+/// Remove one window when `start` and `end` are given, otherwise all of the type
+async fn remove_time_windows(
+    State(state): State<ApiState>,
+    Bearer(token): Bearer,
+    Path((profile_id, window_type)): Path<(String, String)>,
+    Query(range): Query<TimeWindowRange>,
+) -> ApiResult {
+    let reply = match (range.start, range.end) {
+        (Some(start), Some(end)) => {
+            state.daemon.remove_time_window(&profile_id, &window_type, &start, &end, &token).await?
+        }
+        (None, None) => state.daemon.clear_time_windows(&profile_id, &window_type, &token).await?,
+        _ => {
+            return Err(Error::InvalidArgument(
+                "Give both start and end to remove one window, or neither to clear them all"
+                    .to_string(),
+            )
+            .into())
+        }
+    };
+    Ok(json_reply(reply))
+}
+
+/// OpenAPI description of the routes above, served at `/api/v1/openapi.json`
+fn openapi_document() -> Value {
+    fn operation(summary: &str, parameters: &[&str], body: Option<&str>) -> Value {
+        let mut operation = json!({
+            "summary": summary,
+            "parameters": parameters
+                .iter()
+                .map(|parameter| json!({ "$ref": format!("#/components/parameters/{}", parameter) }))
+                .collect::<Vec<_>>(),
+            "responses": {
+                "200": { "description": "Reply of the matching D-Bus method, as JSON" },
+                "default": { "$ref": "#/components/responses/Error" },
+            },
+        });
+        if let Some(schema) = body {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": {
+                    "schema": { "$ref": format!("#/components/schemas/{}", schema) },
+                } },
+            });
+        }
+        operation
+    }
+
+    fn string_param(name: &str, location: &str, description: &str) -> Value {
+        json!({
+            "name": name,
+            "in": location,
+            "required": location == "path",
+            "description": description,
+            "schema": { "type": "string" },
+        })
+    }
+
+    fn object(properties: &[&str], required: &[&str]) -> Value {
+        let properties: serde_json::Map<String, Value> =
+            properties.iter().map(|name| (name.to_string(), json!({ "type": "string" }))).collect();
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+
+    let mut new_exception = object(&["exception_type", "reason"], &["exception_type", "reason"]);
+    new_exception["properties"]["duration"] =
+        json!({ "description": "An ExceptionDuration, as taken by CreateException" });
+    new_exception["required"] = json!(["exception_type", "reason", "duration"]);
+
+    let mut sign_in = operation("Sign in and get a session token", &[], Some("SignIn"));
+    sign_in["security"] = json!([]);
+    let mut document = operation("This document", &[], None);
+    document["security"] = json!([]);
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "DOTS Family Mode management API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Parent management over HTTP. Each route forwards to the \
+                org.dots.FamilyDaemon D-Bus method of the same name and replies with its JSON.",
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/openapi.json": { "get": document },
+            "/auth": {
+                "post": sign_in,
+                "delete": operation("Revoke the session token", &[], None),
+            },
+            "/events": {
+                "get": {
+                    "summary": "Daemon signals as server-sent events, named after the signal",
+                    "responses": {
+                        "200": {
+                            "description": "Event stream",
+                            "content": { "text/event-stream": { "schema": { "type": "string" } } },
+                        },
+                        "default": { "$ref": "#/components/responses/Error" },
+                    },
+                },
+            },
+            "/events/recent": {
+                "get": operation("Recently published events", &["kind", "event_profile_id"], None),
+            },
+            "/profiles": { "get": operation("List profiles", &[], None) },
+            "/profiles/{profile_id}/reports/daily": {
+                "get": operation("Daily activity report", &["profile_id", "date"], None),
+            },
+            "/profiles/{profile_id}/reports/weekly": {
+                "get": operation("Weekly activity report", &["profile_id", "week_start"], None),
+            },
+            "/profiles/{profile_id}/exceptions": {
+                "get": operation("Active exceptions of a profile", &["profile_id"], None),
+            },
+            "/profiles/{profile_id}/time-windows": {
+                "get": operation("Time windows of a profile", &["profile_id"], None),
+                "post": operation("Add a time window", &["profile_id"], Some("NewTimeWindow")),
+            },
+            "/profiles/{profile_id}/time-windows/{window_type}": {
+                "delete": operation(
+                    "Remove the window from start to end, or every window of the type",
+                    &["profile_id", "window_type", "start", "end"],
+                    None,
+                ),
+            },
+            "/requests": { "get": operation("Pending approval requests", &[], None) },
+            "/requests/{request_id}/approve": {
+                "post": operation("Approve a request", &["request_id"], Some("Resolution")),
+            },
+            "/requests/{request_id}/deny": {
+                "post": operation("Deny a request", &["request_id"], Some("Resolution")),
+            },
+            "/exceptions": {
+                "post": operation("Create an exception", &[], Some("NewException")),
+            },
+            "/exceptions/{exception_id}": {
+                "delete": operation("Revoke an exception", &["exception_id"], None),
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "Session token from POST /auth",
+                },
+            },
+            "parameters": {
+                "profile_id": string_param("profile_id", "path", "Profile ID"),
+                "request_id": string_param("request_id", "path", "Approval request ID"),
+                "exception_id": string_param("exception_id", "path", "Exception ID"),
+                "window_type": string_param("window_type", "path", "weekday, weekend or holiday"),
+                "date": string_param("date", "query", "Day as YYYY-MM-DD"),
+                "week_start": string_param("week_start", "query", "First day as YYYY-MM-DD"),
+                "start": string_param("start", "query", "Window start as HH:MM"),
+                "end": string_param("end", "query", "Window end as HH:MM"),
+                "kind": string_param("kind", "query", "Comma-separated event kinds"),
+                "event_profile_id": string_param("profile_id", "query", "Only events about this profile"),
+            },
+            "schemas": {
+                "SignIn": object(&["name", "password"], &["password"]),
+                "Resolution": object(&["message"], &[]),
+                "NewTimeWindow": object(&["window_type", "start", "end"], &["window_type", "start", "end"]),
+                "NewException": new_exception,
+                "Error": object(&["error", "message"], &["error", "message"]),
+            },
+            "responses": {
+                "Error": {
+                    "description": "The error the daemon replied with, named as on D-Bus",
+                    "content": { "application/json": {
+                        "schema": { "$ref": "#/components/schemas/Error" },
+                    } },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lan_binds_need_allow_lan_and_tls() {
+        let mut config = HttpApiConfig::default();
+        assert!(bind_address(&config).unwrap().ip().is_loopback());
+
+        config.bind_address = "[::1]:7879".to_string();
+        assert_eq!(bind_address(&config).unwrap().port(), 7879);
+
+        config.bind_address = "0.0.0.0:7878".to_string();
+        assert!(bind_address(&config).is_err());
+        config.bind_address = "192.168.1.10:7878".to_string();
+        assert!(bind_address(&config).is_err());
+
+        // Opting in is not enough without TLS
+        config.allow_lan = true;
+        assert!(bind_address(&config).is_err());
+        config.tls_cert_path = Some("/etc/dots-family/api.crt".to_string());
+        assert!(bind_address(&config).is_err());
+
+        config.tls_key_path = Some("/etc/dots-family/api.key".to_string());
+        assert_eq!(bind_address(&config).unwrap().to_string(), "192.168.1.10:7878");
+
+        // TLS alone does not open the API to the network
+        config.allow_lan = false;
+        assert!(bind_address(&config).is_err());
+    }
+
+    fn self_signed_certificate(dir: &std::path::Path) -> HttpApiConfig {
+        use openssl::{
+            asn1::Asn1Time, hash::MessageDigest, pkey::PKey, rsa::Rsa, x509::X509NameBuilder,
+            x509::X509,
+        };
+
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+        let cert_path = dir.join("api.crt");
+        let key_path = dir.join("api.key");
+        std::fs::write(&cert_path, cert.build().to_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        HttpApiConfig {
+            tls_cert_path: Some(cert_path.to_string_lossy().to_string()),
+            tls_key_path: Some(key_path.to_string_lossy().to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_is_served_over_tls() {
+        use openssl::ssl::{SslConnector, SslVerifyMode};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = self_signed_certificate(dir.path());
+        let acceptor = tls_acceptor(&config).unwrap().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/api/v1/ping", get(|| async { "pong" }));
+        tokio::spawn(serve_tls(listener, acceptor, app));
+
+        let mut connector = SslConnector::builder(SslMethod::tls_client()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+        let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut stream = SslStream::new(ssl, tcp).unwrap();
+        Pin::new(&mut stream).connect().await.unwrap();
+
+        stream
+            .write_all(b"GET /api/v1/ping HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("pong"));
+
+        // Half of a TLS configuration is refused rather than served in clear
+        let config = HttpApiConfig { tls_key_path: None, ..config };
+        assert!(tls_acceptor(&config).is_err());
+    }
+
+    #[test]
+    fn test_bearer_token_parsing() {
+        assert_eq!(bearer_token("Bearer abc"), Some("abc"));
+        assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("Basic abc"), None);
+        assert_eq!(bearer_token("abc"), None);
+    }
+
+    #[test]
+    fn test_daemon_errors_map_to_statuses() {
+        let cases = [
+            (Error::NotAuthorized(String::new()), StatusCode::FORBIDDEN),
+            (Error::NotFound(String::new()), StatusCode::NOT_FOUND),
+            (Error::InvalidArgument(String::new()), StatusCode::BAD_REQUEST),
+            (Error::RateLimited(String::new()), StatusCode::TOO_MANY_REQUESTS),
+            (Error::Unavailable(String::new()), StatusCode::SERVICE_UNAVAILABLE),
+        ];
+        for (error, status) in cases {
+            assert_eq!(error_status(&error).0, status);
+        }
+
+        let refused: anyhow::Error = DaemonError::NotFound("no such profile".to_string()).into();
+        match ApiError::from(refused) {
+            ApiError::Daemon(Error::NotFound(msg)) => assert_eq!(msg, "no such profile"),
+            _ => panic!("daemon error kind was lost"),
+        }
+    }
+
+    #[test]
+    fn test_signals_become_events() {
+        let message = Message::signal(OBJECT_PATH, INTERFACE, "ApprovalRequestResolved")
+            .unwrap()
+            .build(&("req-1", "approved", "ok"))
+            .unwrap();
+        assert_eq!(
+            signal_event(&message),
+            Some(SignalEvent {
+                name: "ApprovalRequestResolved".to_string(),
+                data: json!({ "request_id": "req-1", "status": "approved", "message": "ok" }),
+            })
+        );
+
+        let message = Message::signal(OBJECT_PATH, INTERFACE, "PolicyUpdated")
+            .unwrap()
+            .build(&("child-1",))
+            .unwrap();
+        let event = signal_event(&message).unwrap();
+
+        let mut guardian = Guardian::primary();
+        assert!(event.visible_to(&guardian));
+        guardian.profile_ids = vec!["child-2".to_string()];
+        assert!(!event.visible_to(&guardian));
+    }
+
+    #[test]
+    fn test_events_without_a_profile_only_reach_unscoped_guardians() {
+        let message = Message::signal(OBJECT_PATH, INTERFACE, "TamperDetected")
+            .unwrap()
+            .build(&("monitor stopped",))
+            .unwrap();
+        let event = signal_event(&message).unwrap();
+
+        let mut guardian = Guardian::primary();
+        assert!(event.visible_to(&guardian));
+        guardian.profile_ids = vec!["child-1".to_string()];
+        assert!(!event.visible_to(&guardian));
+    }
+
+    #[test]
+    fn test_openapi_documents_every_parameter() {
+        let document = openapi_document();
+        let parameters = document["components"]["parameters"].as_object().unwrap();
+
+        for (path, operations) in document["paths"].as_object().unwrap() {
+            for (method, operation) in operations.as_object().unwrap() {
+                for parameter in operation["parameters"].as_array().into_iter().flatten() {
+                    let reference = parameter["$ref"].as_str().unwrap();
+                    let name = reference.rsplit('/').next().unwrap();
+                    assert!(parameters.contains_key(name), "{} {} uses {}", method, path, name);
+                }
+            }
+        }
+    }
+}
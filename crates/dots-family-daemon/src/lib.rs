@@ -1,19 +1,32 @@
+pub mod accounts;
+pub mod app_catalog;
+pub mod audit_sealer;
 pub mod behavior_analyzer;
+pub mod caller_auth;
 pub mod config;
 pub mod daemon;
+pub mod database_key;
 pub mod dbus_impl;
 pub mod ebpf;
 pub mod ebpf_event_processor;
 pub mod edge_case_handler;
 pub mod enforcement;
+pub mod event_bus;
+pub mod event_subscriptions;
+pub mod exec_guard;
+pub mod http_api;
 pub mod monitoring_service;
+pub mod network_enforcement;
 pub mod notification_manager;
 pub mod policy_engine;
+pub mod process_monitor;
 pub mod profile_manager;
-pub mod reports;
+pub mod profile_resolver;
 pub mod session_manager;
+pub mod tamper_detector;
 pub mod time_window_enforcement_task;
 pub mod time_window_manager;
+pub mod trusted_clock;
 
 #[cfg(test)]
 pub mod dbus_communication_test;
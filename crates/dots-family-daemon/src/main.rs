@@ -1,21 +1,35 @@
 use anyhow::Result;
 use tracing::{error, info};
 
+mod accounts;
+mod app_catalog;
+mod audit_sealer;
 mod behavior_analyzer;
+mod caller_auth;
 mod config;
 mod daemon;
+mod database_key;
 mod dbus_impl;
 mod ebpf;
+mod ebpf_event_processor;
 mod edge_case_handler;
 mod enforcement;
+mod event_bus;
+mod event_subscriptions;
+mod exec_guard;
+mod http_api;
 mod monitoring_service;
+mod network_enforcement;
 mod notification_manager;
 mod policy_engine;
+mod process_monitor;
 mod profile_manager;
-mod reports;
+mod profile_resolver;
 mod session_manager;
+mod tamper_detector;
 mod time_window_enforcement_task;
 mod time_window_manager;
+mod trusted_clock;
 
 #[tokio::main]
 async fn main() -> Result<()> {
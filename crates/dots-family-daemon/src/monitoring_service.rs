@@ -1,41 +1,54 @@
 use std::{sync::Arc, time::SystemTime};
 
 use anyhow::Result;
-use dots_family_proto::events::ActivityEvent;
-use serde_json::Value;
+use dots_family_proto::{events::ActivityEvent, payload::MonitoringSnapshot};
 use tokio::{
-    sync::Mutex,
+    sync::{mpsc, Mutex},
     time::{interval, Duration},
 };
 use tracing::{error, info, warn};
 
-use crate::ebpf::{
-    DiskIoMonitorEbpf, FilesystemMonitorEbpf, MemoryMonitorEbpf, NetworkMonitorEbpf,
-    ProcessMonitorEbpf,
+use crate::{
+    ebpf::{DiskIoMonitorEbpf, FilesystemMonitorEbpf, MemoryMonitorEbpf, NetworkMonitorEbpf},
+    ebpf_event_processor::EbpfEventProcessor,
+    process_monitor::ProcessMonitor,
+    trusted_clock,
 };
 
+/// Kernel events queued for storage before new ones are dropped
+const EBPF_EVENT_QUEUE: usize = 1024;
+
 #[derive(Clone)]
 pub struct MonitoringService {
-    process_monitor: Arc<Mutex<ProcessMonitorEbpf>>,
+    process_monitor: Arc<ProcessMonitor>,
     network_monitor: Arc<Mutex<NetworkMonitorEbpf>>,
     filesystem_monitor: Arc<Mutex<FilesystemMonitorEbpf>>,
     memory_monitor: Arc<Mutex<MemoryMonitorEbpf>>,
     disk_io_monitor: Arc<Mutex<DiskIoMonitorEbpf>>,
+    /// Stores memory and disk I/O events of child processes
+    event_processor: Option<Arc<EbpfEventProcessor>>,
     running: Arc<Mutex<bool>>,
 }
 
 impl MonitoringService {
     pub async fn new() -> Result<Self> {
         Ok(Self {
-            process_monitor: Arc::new(Mutex::new(ProcessMonitorEbpf::new().await?)),
+            process_monitor: Arc::new(ProcessMonitor::new()),
             network_monitor: Arc::new(Mutex::new(NetworkMonitorEbpf::new())),
             filesystem_monitor: Arc::new(Mutex::new(FilesystemMonitorEbpf::new())),
             memory_monitor: Arc::new(Mutex::new(MemoryMonitorEbpf::new()?)),
             disk_io_monitor: Arc::new(Mutex::new(DiskIoMonitorEbpf::new()?)),
+            event_processor: None,
             running: Arc::new(Mutex::new(false)),
         })
     }
 
+    /// Store memory and disk I/O events through `processor` once started
+    pub fn with_event_processor(mut self, processor: Arc<EbpfEventProcessor>) -> Self {
+        self.event_processor = Some(processor);
+        self
+    }
+
     pub async fn start(&self) -> Result<()> {
         info!("Starting monitoring service");
 
@@ -46,9 +59,17 @@ impl MonitoringService {
         *running = true;
 
         {
-            let mut process_monitor = self.process_monitor.lock().await;
-            if let Err(e) = process_monitor.load().await {
-                warn!("Failed to load eBPF process monitor: {}, using fallback", e);
+            // Use compile-time path from build.rs if available (Nix build)
+            // Otherwise fall back to runtime environment variable (local development)
+            let process_path = option_env!("BPF_PROCESS_MONITOR_FILE")
+                .map(str::to_string)
+                .or_else(|| std::env::var("BPF_PROCESS_MONITOR_PATH").ok())
+                .filter(|path| !path.is_empty());
+
+            if let Err(e) =
+                self.process_monitor.start(process_path.as_deref().map(std::path::Path::new)).await
+            {
+                warn!("Failed to start process monitor: {}, continuing without it", e);
             }
         }
 
@@ -77,6 +98,12 @@ impl MonitoringService {
                     e
                 ));
             }
+
+            if let Err(e) = network_monitor.attach_programs().await {
+                warn!("Failed to attach eBPF network monitor: {}, continuing without it", e);
+            } else if let Err(e) = network_monitor.start_monitoring().await {
+                warn!("Failed to read eBPF network events: {}, continuing without them", e);
+            }
         }
 
         {
@@ -121,6 +148,8 @@ impl MonitoringService {
             if !memory_path.is_empty() {
                 if let Err(e) = memory_monitor.load(std::path::Path::new(&memory_path)).await {
                     warn!("Failed to load eBPF memory monitor: {}, continuing without it", e);
+                } else if let Err(e) = self.start_memory_events(&mut memory_monitor).await {
+                    warn!("Failed to read eBPF memory events: {}, continuing without them", e);
                 }
             } else {
                 warn!("BPF_MEMORY_MONITOR_PATH not set, continuing without memory monitoring");
@@ -142,6 +171,8 @@ impl MonitoringService {
             if !disk_io_path.is_empty() {
                 if let Err(e) = disk_io_monitor.load(std::path::Path::new(&disk_io_path)).await {
                     warn!("Failed to load eBPF disk I/O monitor: {}, continuing without it", e);
+                } else if let Err(e) = self.start_disk_io_events(&mut disk_io_monitor).await {
+                    warn!("Failed to read eBPF disk I/O events: {}, continuing without them", e);
                 }
             } else {
                 warn!("BPF_DISK_IO_MONITOR_PATH not set, continuing without disk I/O monitoring");
@@ -188,6 +219,46 @@ impl MonitoringService {
         Ok(())
     }
 
+    /// Attach the memory tracepoints and store what they report
+    async fn start_memory_events(&self, monitor: &mut MemoryMonitorEbpf) -> Result<()> {
+        let Some(processor) = self.event_processor.clone() else {
+            return Ok(());
+        };
+
+        monitor.attach_programs().await?;
+        let (sender, mut events) = mpsc::channel(EBPF_EVENT_QUEUE);
+        monitor.start_monitoring(sender).await?;
+
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = processor.process_memory_event(event, None).await {
+                    warn!("Failed to store memory event: {}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Attach the block I/O tracepoints and store what they report
+    async fn start_disk_io_events(&self, monitor: &mut DiskIoMonitorEbpf) -> Result<()> {
+        let Some(processor) = self.event_processor.clone() else {
+            return Ok(());
+        };
+
+        monitor.attach_programs().await?;
+        let (sender, mut events) = mpsc::channel(EBPF_EVENT_QUEUE);
+        monitor.start_monitoring(sender).await?;
+
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = processor.process_disk_io_event(event, None).await {
+                    warn!("Failed to store disk I/O event: {}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+
     pub async fn stop(&self) -> Result<()> {
         info!("Stopping monitoring service");
         let mut running = self.running.lock().await;
@@ -196,14 +267,17 @@ impl MonitoringService {
         Ok(())
     }
 
-    pub async fn get_monitoring_snapshot(&self) -> Result<Value> {
-        let process_data = {
-            let monitor = self.process_monitor.lock().await;
-            monitor
-                .collect_snapshot()
-                .await
-                .map_err(|e| anyhow::anyhow!("Process monitor error: {}", e))?
-        };
+    /// Process activity for the policy engine, available once after start
+    pub async fn take_process_events(&self) -> Option<mpsc::Receiver<ActivityEvent>> {
+        self.process_monitor.take_events().await
+    }
+
+    pub async fn get_monitoring_snapshot(&self) -> Result<MonitoringSnapshot> {
+        let process_data = self
+            .process_monitor
+            .collect_snapshot()
+            .await
+            .map_err(|e| anyhow::anyhow!("Process monitor error: {}", e))?;
 
         let network_data = {
             let monitor = self.network_monitor.lock().await;
@@ -237,81 +311,42 @@ impl MonitoringService {
                 .map_err(|e| anyhow::anyhow!("Disk I/O monitor error: {}", e))?
         };
 
-        Ok(serde_json::json!({
-            "timestamp": chrono::Utc::now().timestamp(),
-            "process_monitoring": process_data,
-            "network_monitoring": network_data,
-            "filesystem_monitoring": filesystem_data,
-            "memory_monitoring": memory_data,
-            "disk_io_monitoring": disk_io_data
-        }))
+        Ok(MonitoringSnapshot {
+            timestamp: trusted_clock::now().timestamp(),
+            process_monitoring: process_data,
+            network_monitoring: network_data,
+            filesystem_monitoring: filesystem_data,
+            memory_monitoring: memory_data,
+            disk_io_monitoring: disk_io_data,
+        })
     }
 
+    /// Network connections opened since the previous call
     pub async fn get_recent_activities(&self) -> Result<Vec<ActivityEvent>> {
-        let mut activities = Vec::new();
-
-        let process_data = {
-            let monitor = self.process_monitor.lock().await;
-            monitor
-                .collect_snapshot()
-                .await
-                .map_err(|e| anyhow::anyhow!("Process monitor error: {}", e))?
-        };
-
-        if let Some(recent_processes) = process_data["recent_processes"].as_array() {
-            for process in recent_processes {
-                if let (Some(pid), Some(executable), Some(args)) = (
-                    process["pid"].as_u64(),
-                    process["executable"].as_str(),
-                    process["args"].as_array(),
-                ) {
-                    let args_vec: Vec<String> =
-                        args.iter().filter_map(|arg| arg.as_str().map(|s| s.to_string())).collect();
-
-                    activities.push(ActivityEvent::ProcessStarted {
-                        pid: pid as u32,
-                        executable: executable.to_string(),
-                        args: args_vec,
-                        timestamp: SystemTime::now(),
-                    });
-                }
-            }
+        let monitor = self.network_monitor.lock().await;
+        if !monitor.is_loaded() {
+            return Err(anyhow::anyhow!("Network monitor error: Monitor not loaded"));
         }
 
-        let network_data = {
-            let monitor = self.network_monitor.lock().await;
-            monitor
-                .collect_snapshot()
-                .await
-                .map_err(|e| anyhow::anyhow!("Network monitor error: {}", e))?
-        };
-
-        if let Some(connections) = network_data["connections"].as_array() {
-            for conn in connections {
-                if let (Some(pid), Some(local_addr), Some(remote_addr)) = (
-                    conn["pid"].as_u64(),
-                    conn["local_addr"].as_str(),
-                    conn["remote_addr"].as_str(),
-                ) {
-                    activities.push(ActivityEvent::NetworkConnection {
-                        pid: pid as u32,
-                        local_addr: local_addr.to_string(),
-                        remote_addr: remote_addr.to_string(),
-                        timestamp: SystemTime::now(),
-                    });
-                }
-            }
-        }
+        let activities = monitor
+            .take_new_connections()
+            .into_iter()
+            .filter_map(|(seen_at, event)| {
+                Some(ActivityEvent::NetworkConnection {
+                    pid: event.pid,
+                    local_addr: event.local_addr()?.to_string(),
+                    remote_addr: event.remote_addr()?.to_string(),
+                    timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(seen_at),
+                })
+            })
+            .collect();
 
         Ok(activities)
     }
 
     #[allow(dead_code)]
     pub async fn health_check(&self) -> Result<bool> {
-        let process_healthy = {
-            let monitor = self.process_monitor.lock().await;
-            monitor.collect_snapshot().await.is_ok()
-        };
+        let process_healthy = self.process_monitor.collect_snapshot().await.is_ok();
 
         let network_healthy = {
             let monitor = self.network_monitor.lock().await;
@@ -342,19 +377,16 @@ impl MonitoringService {
 }
 
 async fn collect_monitoring_data(
-    process_monitor: &Arc<Mutex<ProcessMonitorEbpf>>,
+    process_monitor: &ProcessMonitor,
     network_monitor: &Arc<Mutex<NetworkMonitorEbpf>>,
     filesystem_monitor: &Arc<Mutex<FilesystemMonitorEbpf>>,
     memory_monitor: &Arc<Mutex<MemoryMonitorEbpf>>,
     disk_io_monitor: &Arc<Mutex<DiskIoMonitorEbpf>>,
 ) -> Result<()> {
-    let process_snapshot = {
-        let monitor = process_monitor.lock().await;
-        monitor
-            .collect_snapshot()
-            .await
-            .map_err(|e| anyhow::anyhow!("Process monitor error: {}", e))?
-    };
+    let process_snapshot = process_monitor
+        .collect_snapshot()
+        .await
+        .map_err(|e| anyhow::anyhow!("Process monitor error: {}", e))?;
 
     let network_snapshot = {
         let monitor = network_monitor.lock().await;
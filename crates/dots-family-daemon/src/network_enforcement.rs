@@ -0,0 +1,538 @@
+//! Per-child network policy.
+//!
+//! Every child gets a cgroup of their own: the `user-<uid>.slice` that
+//! systemd-logind already creates, or a `dots-family/child-<uid>` cgroup we
+//! create and move their processes into when there is no systemd slice.
+//! Blocking is then done with nftables `socket cgroupv2` rules in a dedicated
+//! `inet dots_family` table, which works on kernels that no longer support
+//! `iptables --pid-owner`.
+//!
+//! The whole table is replaced atomically on every change, so a failed update
+//! never leaves half a ruleset behind. The table is removed on startup (in
+//! case a previous daemon crashed), on shutdown, and rules for a child are
+//! dropped as soon as their session has ended.
+
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    fmt::Write as _,
+    net::{IpAddr, SocketAddr},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use anyhow::{anyhow, Context, Result};
+use dots_family_common::types::Profile;
+use tokio::{io::AsyncWriteExt, process::Command, sync::RwLock};
+use tracing::{debug, info, warn};
+
+use crate::accounts::uid_of_user;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const OWN_CGROUP_DIR: &str = "dots-family";
+const NFT_TABLE: &str = "dots_family";
+
+/// A single network restriction for a child
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkRule {
+    /// Block traffic to one address
+    Destination(IpAddr),
+    /// Block TCP and UDP traffic to one port
+    Port(u16),
+    /// Block all traffic except loopback
+    All,
+}
+
+/// The restrictions in force for one child
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ChildNetworkPolicy {
+    /// cgroup path relative to the cgroup2 mount
+    cgroup: String,
+    /// Whether we created the cgroup and must remove it again
+    owned_cgroup: bool,
+    blocked_destinations: BTreeSet<IpAddr>,
+    blocked_ports: BTreeSet<u16>,
+    block_all: bool,
+}
+
+impl ChildNetworkPolicy {
+    fn apply_rule(&mut self, rule: NetworkRule, blocked: bool) {
+        match rule {
+            NetworkRule::Destination(addr) if blocked => {
+                self.blocked_destinations.insert(addr);
+            }
+            NetworkRule::Destination(addr) => {
+                self.blocked_destinations.remove(&addr);
+            }
+            NetworkRule::Port(port) if blocked => {
+                self.blocked_ports.insert(port);
+            }
+            NetworkRule::Port(port) => {
+                self.blocked_ports.remove(&port);
+            }
+            NetworkRule::All => self.block_all = blocked,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.block_all && self.blocked_destinations.is_empty() && self.blocked_ports.is_empty()
+    }
+}
+
+pub struct NetworkEnforcer {
+    dry_run: bool,
+    cgroup_root: PathBuf,
+    children: RwLock<HashMap<u32, ChildNetworkPolicy>>,
+}
+
+impl NetworkEnforcer {
+    pub fn new(dry_run: bool) -> Self {
+        Self { dry_run, cgroup_root: PathBuf::from(CGROUP_ROOT), children: RwLock::default() }
+    }
+
+    /// Block `rule` for the child with the given uid
+    pub async fn block(&self, uid: u32, rule: NetworkRule) -> Result<()> {
+        self.update(uid, rule, true).await
+    }
+
+    /// Lift a previously blocked `rule` for the child with the given uid
+    pub async fn unblock(&self, uid: u32, rule: NetworkRule) -> Result<()> {
+        self.update(uid, rule, false).await
+    }
+
+    /// Whether `rule` is blocked for the child with the given uid
+    pub async fn is_blocked(&self, uid: u32, rule: NetworkRule) -> bool {
+        let children = self.children.read().await;
+        children.get(&uid).is_some_and(|policy| match rule {
+            NetworkRule::Destination(addr) => policy.blocked_destinations.contains(&addr),
+            NetworkRule::Port(port) => policy.blocked_ports.contains(&port),
+            NetworkRule::All => policy.block_all,
+        })
+    }
+
+    async fn update(&self, uid: u32, rule: NetworkRule, blocked: bool) -> Result<()> {
+        let mut children = self.children.write().await;
+
+        match children.entry(uid) {
+            Entry::Vacant(_) if !blocked => return Ok(()),
+            Entry::Vacant(entry) => {
+                // Rules are dropped when a session ends and set again on the
+                // next login, so there is nothing to do without one
+                if !self.dry_run && processes_owned_by(uid).await?.is_empty() {
+                    debug!("uid {} has no session, not blocking {:?} yet", uid, rule);
+                    return Ok(());
+                }
+                let (cgroup, owned_cgroup) = self.child_cgroup(uid).await?;
+                let mut policy = ChildNetworkPolicy { cgroup, owned_cgroup, ..Default::default() };
+                policy.apply_rule(rule, blocked);
+                entry.insert(policy);
+            }
+            Entry::Occupied(mut entry) => {
+                let before = entry.get().clone();
+                entry.get_mut().apply_rule(rule, blocked);
+                if *entry.get() == before {
+                    return Ok(());
+                }
+            }
+        }
+
+        let action = if blocked { "Blocking" } else { "Unblocking" };
+        info!("{} network for uid {}: {:?}", action, uid, rule);
+        self.apply(&children).await?;
+
+        if children.get(&uid).is_some_and(ChildNetworkPolicy::is_empty) {
+            if let Some(policy) = children.remove(&uid) {
+                self.release_cgroup(&policy).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Block exactly `ports` for the child with the given uid
+    pub async fn set_blocked_ports(&self, uid: u32, ports: &BTreeSet<u16>) -> Result<()> {
+        let current = {
+            let children = self.children.read().await;
+            children.get(&uid).map(|policy| policy.blocked_ports.clone()).unwrap_or_default()
+        };
+
+        for port in current.difference(ports) {
+            self.unblock(uid, NetworkRule::Port(*port)).await?;
+        }
+        for port in ports.difference(&current) {
+            self.block(uid, NetworkRule::Port(*port)).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the blocked ports with those of the given profiles
+    pub async fn sync_profiles(&self, profiles: &[Profile]) {
+        let mut wanted: HashMap<u32, BTreeSet<u16>> = HashMap::new();
+        for profile in profiles.iter().filter(|profile| profile.active) {
+            let Some(username) = &profile.username else {
+                continue;
+            };
+            match uid_of_user(username) {
+                Ok(uid) => {
+                    wanted.entry(uid).or_default().extend(&profile.config.network.blocked_ports)
+                }
+                Err(e) => debug!("Skipping network rules for {}: {}", profile.name, e),
+            }
+        }
+
+        // Children whose profile is gone or no longer blocks anything
+        for uid in self.children.read().await.keys() {
+            wanted.entry(*uid).or_default();
+        }
+
+        for (uid, ports) in wanted {
+            if let Err(e) = self.set_blocked_ports(uid, &ports).await {
+                warn!("Failed to update blocked ports for uid {}: {}", uid, e);
+            }
+        }
+    }
+
+    /// Drop every rule for the child with the given uid
+    pub async fn release_child(&self, uid: u32) -> Result<()> {
+        let mut children = self.children.write().await;
+        let Some(policy) = children.remove(&uid) else {
+            return Ok(());
+        };
+
+        info!("Removing network rules for uid {}", uid);
+        self.apply(&children).await?;
+        self.release_cgroup(&policy).await;
+        Ok(())
+    }
+
+    /// Drop rules for children whose session has ended
+    pub async fn prune_ended_sessions(&self) -> Result<()> {
+        let ended: Vec<u32> = {
+            let children = self.children.read().await;
+            children
+                .iter()
+                .filter(|(_, policy)| !self.cgroup_has_processes(&policy.cgroup))
+                .map(|(uid, _)| *uid)
+                .collect()
+        };
+
+        for uid in ended {
+            info!("Session for uid {} has ended", uid);
+            self.release_child(uid).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove every rule and cgroup this daemon created, including leftovers
+    /// from a previous run
+    pub async fn cleanup(&self) -> Result<()> {
+        let mut children = self.children.write().await;
+
+        info!("Removing all network enforcement rules");
+        let result = self.apply(&HashMap::new()).await;
+        for (_, policy) in children.drain() {
+            self.release_cgroup(&policy).await;
+        }
+        result
+    }
+
+    /// Find or create the cgroup for a child. Returns the path relative to the
+    /// cgroup mount and whether we created it.
+    async fn child_cgroup(&self, uid: u32) -> Result<(String, bool)> {
+        let user_slice = format!("user.slice/user-{}.slice", uid);
+        if self.cgroup_root.join(&user_slice).is_dir() {
+            return Ok((user_slice, false));
+        }
+
+        let own = format!("{}/child-{}", OWN_CGROUP_DIR, uid);
+        if self.dry_run {
+            warn!("DRY RUN: Would create cgroup {} for uid {}", own, uid);
+            return Ok((own, true));
+        }
+
+        let path = self.cgroup_root.join(&own);
+        tokio::fs::create_dir_all(&path)
+            .await
+            .with_context(|| format!("Failed to create cgroup {}", path.display()))?;
+
+        let pids = processes_owned_by(uid).await?;
+        for pid in &pids {
+            // Processes may exit between listing and moving them
+            if let Err(e) = tokio::fs::write(path.join("cgroup.procs"), pid.to_string()).await {
+                debug!("Could not move PID {} into {}: {}", pid, own, e);
+            }
+        }
+        info!("Moved {} processes of uid {} into cgroup {}", pids.len(), uid, own);
+
+        Ok((own, true))
+    }
+
+    async fn release_cgroup(&self, policy: &ChildNetworkPolicy) {
+        if !policy.owned_cgroup || self.dry_run {
+            return;
+        }
+
+        let path = self.cgroup_root.join(&policy.cgroup);
+        let parent = self.cgroup_root.join(OWN_CGROUP_DIR);
+
+        // Any remaining processes go back to the parent so the cgroup can be removed
+        if let Ok(procs) = tokio::fs::read_to_string(path.join("cgroup.procs")).await {
+            for pid in procs.lines() {
+                let _ = tokio::fs::write(parent.join("cgroup.procs"), pid).await;
+            }
+        }
+
+        if let Err(e) = tokio::fs::remove_dir(&path).await {
+            warn!("Failed to remove cgroup {}: {}", path.display(), e);
+        }
+    }
+
+    fn cgroup_has_processes(&self, cgroup: &str) -> bool {
+        if self.dry_run {
+            return true;
+        }
+
+        std::fs::read_to_string(self.cgroup_root.join(cgroup).join("cgroup.events"))
+            .map(|events| events.lines().any(|line| line == "populated 1"))
+            .unwrap_or(false)
+    }
+
+    async fn apply(&self, children: &HashMap<u32, ChildNetworkPolicy>) -> Result<()> {
+        let ruleset = render_ruleset(children);
+
+        if self.dry_run {
+            warn!("DRY RUN: Would load nftables ruleset:\n{}", ruleset);
+            return Ok(());
+        }
+
+        let mut child = Command::new("nft")
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute nft command")?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(ruleset.as_bytes()).await?;
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            let error_msg = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to load nftables ruleset: {}", error_msg));
+        }
+
+        debug!("Loaded nftables ruleset for {} children", children.len());
+        Ok(())
+    }
+}
+
+/// Build an nftables script that atomically replaces the `dots_family` table
+fn render_ruleset(children: &HashMap<u32, ChildNetworkPolicy>) -> String {
+    // Adding first makes the delete succeed whether or not the table exists
+    let mut script = format!("table inet {table}\ndelete table inet {table}\n", table = NFT_TABLE);
+
+    let mut uids: Vec<&u32> = children.keys().filter(|uid| !children[uid].is_empty()).collect();
+    if uids.is_empty() {
+        return script;
+    }
+    uids.sort();
+
+    let _ = writeln!(script, "table inet {} {{", NFT_TABLE);
+    script.push_str("    chain output {\n");
+    script.push_str("        type filter hook output priority filter; policy accept;\n");
+    for uid in &uids {
+        let policy = &children[uid];
+        let _ = writeln!(
+            script,
+            "        socket cgroupv2 level {} \"{}\" jump child_{}",
+            policy.cgroup.split('/').count(),
+            policy.cgroup,
+            uid
+        );
+    }
+    script.push_str("    }\n");
+
+    for uid in uids {
+        let policy = &children[uid];
+        let _ = writeln!(script, "    chain child_{} {{", uid);
+        script.push_str("        oifname \"lo\" accept\n");
+        for addr in &policy.blocked_destinations {
+            let family = if addr.is_ipv4() { "ip" } else { "ip6" };
+            let _ = writeln!(script, "        {} daddr {} reject", family, addr);
+        }
+        for port in &policy.blocked_ports {
+            let _ =
+                writeln!(script, "        meta l4proto {{ tcp, udp }} th dport {} reject", port);
+        }
+        if policy.block_all {
+            script.push_str("        reject\n");
+        }
+        script.push_str("    }\n");
+    }
+    script.push_str("}\n");
+
+    script
+}
+
+/// Parse the destination of a connection, with or without a port
+pub fn parse_remote_addr(remote_addr: &str) -> Result<IpAddr> {
+    remote_addr
+        .parse::<SocketAddr>()
+        .map(|addr| addr.ip())
+        .or_else(|_| remote_addr.parse::<IpAddr>())
+        .map_err(|_| anyhow!("Invalid remote address: {}", remote_addr))
+}
+
+async fn processes_owned_by(uid: u32) -> Result<Vec<u32>> {
+    let mut pids = Vec::new();
+    let mut entries = tokio::fs::read_dir(Path::new("/proc")).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        if let Ok(metadata) = entry.metadata().await {
+            if metadata.uid() == uid {
+                pids.push(pid);
+            }
+        }
+    }
+
+    Ok(pids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(cgroup: &str) -> ChildNetworkPolicy {
+        ChildNetworkPolicy { cgroup: cgroup.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_empty_ruleset_only_removes_table() {
+        let script = render_ruleset(&HashMap::new());
+        assert_eq!(script, "table inet dots_family\ndelete table inet dots_family\n");
+    }
+
+    #[test]
+    fn test_ruleset_matches_child_cgroup() {
+        let mut child = policy("user.slice/user-1001.slice");
+        child.apply_rule(NetworkRule::Destination("93.184.216.34".parse().unwrap()), true);
+        child.apply_rule(NetworkRule::Destination("2001:db8::1".parse().unwrap()), true);
+        child.apply_rule(NetworkRule::Port(25), true);
+
+        let script = render_ruleset(&HashMap::from([(1001, child)]));
+
+        assert!(script
+            .contains("socket cgroupv2 level 2 \"user.slice/user-1001.slice\" jump child_1001"));
+        assert!(script.contains("ip daddr 93.184.216.34 reject"));
+        assert!(script.contains("ip6 daddr 2001:db8::1 reject"));
+        assert!(script.contains("meta l4proto { tcp, udp } th dport 25 reject"));
+        assert!(!script.contains("        reject\n"));
+    }
+
+    #[test]
+    fn test_block_all_keeps_loopback() {
+        let mut child = policy("dots-family/child-1002");
+        child.apply_rule(NetworkRule::All, true);
+
+        let script = render_ruleset(&HashMap::from([(1002, child)]));
+
+        let chain = script.split("chain child_1002").nth(1).unwrap();
+        assert!(chain.find("oifname \"lo\" accept").unwrap() < chain.find("reject").unwrap());
+    }
+
+    #[test]
+    fn test_children_without_rules_are_skipped() {
+        let mut child = policy("user.slice/user-1003.slice");
+        let addr = "93.184.216.34".parse().unwrap();
+        child.apply_rule(NetworkRule::Destination(addr), true);
+        child.apply_rule(NetworkRule::Destination(addr), false);
+        assert!(child.is_empty());
+
+        let script = render_ruleset(&HashMap::from([(1003, child)]));
+        assert!(!script.contains("child_1003"));
+    }
+
+    #[test]
+    fn test_parse_remote_addr() {
+        assert_eq!(
+            parse_remote_addr("10.0.0.1:443").unwrap(),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(parse_remote_addr("[::1]:80").unwrap(), "::1".parse::<IpAddr>().unwrap());
+        assert_eq!(parse_remote_addr("10.0.0.2").unwrap(), "10.0.0.2".parse::<IpAddr>().unwrap());
+        assert!(parse_remote_addr("example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_block_and_release() {
+        let enforcer = NetworkEnforcer::new(true);
+
+        enforcer.block(1004, NetworkRule::All).await.unwrap();
+        assert!(enforcer.children.read().await.contains_key(&1004));
+
+        enforcer.unblock(1004, NetworkRule::All).await.unwrap();
+        assert!(!enforcer.children.read().await.contains_key(&1004));
+
+        let addr = "93.184.216.34".parse().unwrap();
+        enforcer.block(1004, NetworkRule::Destination(addr)).await.unwrap();
+        enforcer.cleanup().await.unwrap();
+        assert!(enforcer.children.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_block_keeps_policy() {
+        let enforcer = NetworkEnforcer::new(true);
+
+        enforcer.block(1005, NetworkRule::All).await.unwrap();
+        enforcer.block(1005, NetworkRule::All).await.unwrap();
+
+        let children = enforcer.children.read().await;
+        assert!(children[&1005].block_all);
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_port_rules_live_in_the_child_chain_until_the_session_ends() {
+        let enforcer = NetworkEnforcer::new(true);
+
+        enforcer.set_blocked_ports(1006, &BTreeSet::from([25, 6667])).await.unwrap();
+        enforcer.set_blocked_ports(1006, &BTreeSet::from([25])).await.unwrap();
+        assert!(enforcer.is_blocked(1006, NetworkRule::Port(25)).await);
+        assert!(!enforcer.is_blocked(1006, NetworkRule::Port(6667)).await);
+
+        let script = render_ruleset(&*enforcer.children.read().await);
+        let jump = script.find("jump child_1006").unwrap();
+        assert!(script[..jump].ends_with(&format!(
+            "socket cgroupv2 level 2 \"{}\" ",
+            enforcer.children.read().await[&1006].cgroup
+        )));
+        let chain = script.find("chain child_1006 {").unwrap();
+        let rule = script.find("meta l4proto { tcp, udp } th dport 25 reject").unwrap();
+        assert!(jump < chain && chain < rule);
+        assert!(!script.contains("dport 6667"));
+
+        enforcer.release_child(1006).await.unwrap();
+        let script = render_ruleset(&*enforcer.children.read().await);
+        assert!(!script.contains("dport 25"));
+        assert!(!script.contains("child_1006"));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_ports_follow_profile_config() {
+        let enforcer = NetworkEnforcer::new(true);
+        let mut profile =
+            Profile { username: Some("root".to_string()), active: true, ..Default::default() };
+        profile.config.network.blocked_ports = vec![25];
+
+        enforcer.sync_profiles(std::slice::from_ref(&profile)).await;
+        assert!(enforcer.is_blocked(0, NetworkRule::Port(25)).await);
+
+        profile.config.network.blocked_ports.clear();
+        enforcer.sync_profiles(&[profile]).await;
+        assert!(enforcer.children.read().await.is_empty());
+    }
+}
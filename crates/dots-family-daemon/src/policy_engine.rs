@@ -1,12 +1,14 @@
 use std::time::SystemTime;
 
 use anyhow::Result;
-use chrono::{Datelike, Local, NaiveTime};
+use chrono::{Datelike, NaiveTime};
 use dots_family_common::types::{ApplicationMode, Profile, TimeWindow};
 use dots_family_proto::events::ActivityEvent;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+use crate::trusted_clock;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyDecision {
     pub action: String,
@@ -25,14 +27,14 @@ pub struct ScreenTimeTracker {
 impl ScreenTimeTracker {
     #[allow(dead_code)]
     pub fn start_session(&mut self) {
-        let now = SystemTime::now();
+        let now = trusted_clock::system_time();
         self.session_start = Some(now);
         self.last_activity = Some(now);
         debug!("Started screen time tracking session");
     }
 
     pub fn update_activity(&mut self) {
-        self.last_activity = Some(SystemTime::now());
+        self.last_activity = Some(trusted_clock::system_time());
     }
 
     #[allow(dead_code)]
@@ -53,7 +55,7 @@ impl ScreenTimeTracker {
 
     pub fn get_current_session_minutes(&self) -> u32 {
         if let Some(start) = self.session_start {
-            if let Ok(duration) = SystemTime::now().duration_since(start) {
+            if let Ok(duration) = trusted_clock::system_time().duration_since(start) {
                 return (duration.as_secs() / 60) as u32;
             }
         }
@@ -73,12 +75,21 @@ impl ScreenTimeTracker {
 pub struct PolicyEngine {
     active_profile: Option<Profile>,
     screen_time_tracker: ScreenTimeTracker,
+    /// Banked time credits spent today, added to the daily limit
+    banked_minutes_today: u32,
+    /// Set when tampering triggers deny-all, holds the tamper reason
+    deny_all: Option<String>,
 }
 
 impl PolicyEngine {
     pub async fn new() -> Result<Self> {
         info!("Initializing policy engine");
-        Ok(Self { active_profile: None, screen_time_tracker: ScreenTimeTracker::default() })
+        Ok(Self {
+            active_profile: None,
+            screen_time_tracker: ScreenTimeTracker::default(),
+            banked_minutes_today: 0,
+            deny_all: None,
+        })
     }
 
     pub async fn set_active_profile(&mut self, profile: Profile) -> Result<()> {
@@ -103,6 +114,25 @@ impl PolicyEngine {
             }
         };
 
+        // Exits need no decision and must not re-trigger time limit enforcement
+        if let ActivityEvent::ProcessExited { .. } = event {
+            return Ok(PolicyDecision {
+                action: "allow".to_string(),
+                reason: "Process exit requires no enforcement".to_string(),
+                blocked: false,
+            });
+        }
+
+        if let Some(reason) = &self.deny_all {
+            if !matches!(event, ActivityEvent::NetworkConnection { .. }) {
+                return Ok(PolicyDecision {
+                    action: "block".to_string(),
+                    reason: format!("Locked down after tampering ({})", reason),
+                    blocked: true,
+                });
+            }
+        }
+
         // First check time-based restrictions
         if let Some(time_decision) = self.check_time_restrictions(profile).await? {
             return Ok(time_decision);
@@ -117,11 +147,13 @@ impl PolicyEngine {
                 let app_id = executable.split('/').next_back().unwrap_or(&executable);
                 self.check_app_policy(profile, app_id).await
             }
-            ActivityEvent::NetworkConnection { .. } => Ok(PolicyDecision {
-                action: "allow".to_string(),
-                reason: "Network activity allowed by default".to_string(),
-                blocked: false,
-            }),
+            ActivityEvent::NetworkConnection { .. } | ActivityEvent::ProcessExited { .. } => {
+                Ok(PolicyDecision {
+                    action: "allow".to_string(),
+                    reason: "Network activity allowed by default".to_string(),
+                    blocked: false,
+                })
+            }
         }
     }
 
@@ -129,7 +161,7 @@ impl PolicyEngine {
         // Check if we're within allowed time windows
         if !self.is_within_allowed_time_window(profile) {
             return Ok(Some(PolicyDecision {
-                action: "block".to_string(),
+                action: "time_limit".to_string(),
                 reason: "Outside allowed time window".to_string(),
                 blocked: true,
             }));
@@ -141,7 +173,7 @@ impl PolicyEngine {
 
         if total_usage >= daily_limit {
             return Ok(Some(PolicyDecision {
-                action: "block".to_string(),
+                action: "time_limit".to_string(),
                 reason: format!(
                     "Daily screen time limit exceeded ({} >= {} minutes)",
                     total_usage, daily_limit
@@ -155,7 +187,7 @@ impl PolicyEngine {
     }
 
     fn is_within_allowed_time_window(&self, profile: &Profile) -> bool {
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         let is_weekend = now.weekday().num_days_from_monday() >= 5;
 
         let time_windows = if is_weekend {
@@ -204,10 +236,10 @@ impl PolicyEngine {
     }
 
     fn get_daily_limit(&self, profile: &Profile) -> u32 {
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         let is_weekend = now.weekday().num_days_from_monday() >= 5;
 
-        let base_limit = profile.config.screen_time.daily_limit_minutes;
+        let base_limit = profile.config.screen_time.daily_limit_minutes + self.banked_minutes_today;
         if is_weekend {
             base_limit + profile.config.screen_time.weekend_bonus_minutes
         } else {
@@ -215,6 +247,23 @@ impl PolicyEngine {
         }
     }
 
+    /// Block all activity until [`Self::clear_deny_all`] is called
+    pub fn set_deny_all(&mut self, reason: &str) {
+        warn!("Denying all activity: {}", reason);
+        self.deny_all = Some(reason.to_string());
+    }
+
+    pub fn clear_deny_all(&mut self) {
+        if self.deny_all.take().is_some() {
+            info!("Deny-all lifted");
+        }
+    }
+
+    /// Set how many banked minutes the active profile has spent today
+    pub fn set_banked_minutes_today(&mut self, minutes: u32) {
+        self.banked_minutes_today = minutes;
+    }
+
     #[allow(dead_code)]
     pub fn start_activity_session(&mut self) {
         self.screen_time_tracker.start_session();
@@ -305,7 +354,7 @@ impl PolicyEngine {
     /// Get the next available time window for the active profile
     pub async fn get_next_time_window(&self) -> Result<Option<TimeWindow>> {
         if let Some(profile) = &self.active_profile {
-            let now = Local::now();
+            let now = trusted_clock::now_local();
             let current_time = now.time();
             let is_weekend = now.weekday().num_days_from_monday() >= 5;
 
@@ -340,7 +389,7 @@ impl PolicyEngine {
 mod tests {
     use std::time::SystemTime;
 
-    use chrono::Utc;
+    use chrono::{Local, Utc};
     use dots_family_common::types::{
         AgeGroup, ApplicationConfig, ProfileConfig, ScreenTimeConfig, TimeWindows,
     };
@@ -367,6 +416,7 @@ mod tests {
                     weekend_bonus_minutes: 30,
                     exempt_categories: vec!["education".to_string()],
                     windows: time_windows,
+                    enforcement: Default::default(),
                 },
                 applications: ApplicationConfig {
                     mode: ApplicationMode::Allowlist,
@@ -430,7 +480,7 @@ mod tests {
         };
 
         let result = engine.process_activity(event).await.unwrap();
-        assert_eq!(result.action, "block");
+        assert_eq!(result.action, "time_limit");
         assert!(result.blocked);
         assert!(result.reason.contains("screen time limit exceeded"));
     }
@@ -514,6 +564,25 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_banked_minutes_extend_remaining_time() {
+        let mut engine = PolicyEngine::new().await.unwrap();
+
+        let profile = create_test_profile(
+            AgeGroup::EarlyElementary,
+            60,
+            TimeWindows { weekday: vec![], weekend: vec![], holiday: vec![] },
+        );
+
+        engine.set_active_profile(profile).await.unwrap();
+        engine.screen_time_tracker.daily_usage_minutes = 60;
+        let before = engine.get_remaining_screen_time().unwrap();
+
+        engine.set_banked_minutes_today(20);
+
+        assert_eq!(engine.get_remaining_screen_time().unwrap(), before + 20);
+    }
+
     #[tokio::test]
     async fn test_allowlist_blocked_app() {
         let mut engine = PolicyEngine::new().await.unwrap();
@@ -673,6 +742,8 @@ mod tests {
             pid: 9999,
             executable: "/usr/bin/malicious-app".to_string(),
             args: vec!["malicious-app".to_string()],
+            uid: 1000,
+            parent_pids: vec![1],
             timestamp: SystemTime::now(),
         };
 
@@ -682,6 +753,32 @@ mod tests {
         assert!(result.reason.contains("malicious-app"));
     }
 
+    #[tokio::test]
+    async fn test_deny_all_blocks_allowed_app() {
+        let mut engine = PolicyEngine::new().await.unwrap();
+        let profile = create_test_profile(
+            AgeGroup::EarlyElementary,
+            120,
+            TimeWindows { weekday: vec![], weekend: vec![], holiday: vec![] },
+        );
+        engine.set_active_profile(profile).await.unwrap();
+
+        let event = || ActivityEvent::WindowFocused {
+            pid: 1234,
+            app_id: "firefox".to_string(),
+            window_title: "Firefox".to_string(),
+            timestamp: SystemTime::now(),
+        };
+
+        engine.set_deny_all("clock_jump: system clock moved forward by 3600s");
+        let result = engine.process_activity(event()).await.unwrap();
+        assert_eq!(result.action, "block");
+        assert!(result.reason.contains("clock_jump"));
+
+        engine.clear_deny_all();
+        assert!(!engine.process_activity(event()).await.unwrap().blocked);
+    }
+
     #[tokio::test]
     async fn test_no_active_profile() {
         let engine = PolicyEngine::new().await.unwrap();
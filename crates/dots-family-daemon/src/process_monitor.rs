@@ -0,0 +1,538 @@
+//! One process event pipeline for policy enforcement.
+//!
+//! Exec and exit notifications come from the first backend that works on
+//! this machine: the eBPF tracepoints, the netlink proc connector, or, when
+//! neither is permitted, a periodic scan of `/proc`. Each exec is enriched
+//! from `/proc` with the full argv, the real uid and the parent chain and
+//! becomes an [`ActivityEvent::ProcessStarted`] for the policy engine.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use dots_family_proto::events::ActivityEvent;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::{ebpf::ProcessMonitorEbpf, trusted_clock};
+
+/// How often the `/proc` scan backend looks for new processes
+const SCAN_INTERVAL: Duration = Duration::from_millis(500);
+/// Limit for walking the parent chain, guards against pid reuse loops
+const MAX_PARENT_DEPTH: usize = 32;
+/// How many started processes are kept for snapshots
+const MAX_RECENT_PROCESSES: usize = 100;
+
+/// Exec or exit as reported by a backend, before `/proc` enrichment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawProcessEvent {
+    Exec { pid: u32, uid: Option<u32>, filename: Option<String> },
+    Exit { pid: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessBackend {
+    Ebpf,
+    ProcConnector,
+    ProcScan,
+}
+
+impl fmt::Display for ProcessBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessBackend::Ebpf => write!(f, "eBPF"),
+            ProcessBackend::ProcConnector => write!(f, "proc connector"),
+            ProcessBackend::ProcScan => write!(f, "/proc scan"),
+        }
+    }
+}
+
+/// What `/proc` knows about a running process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub executable: String,
+    pub argv: Vec<String>,
+    pub uid: u32,
+    pub ppid: u32,
+}
+
+impl ProcessInfo {
+    pub fn read(pid: u32) -> Option<Self> {
+        Self::read_from(Path::new("/proc"), pid)
+    }
+
+    fn read_from(proc_root: &Path, pid: u32) -> Option<Self> {
+        let dir = proc_root.join(pid.to_string());
+        let status = std::fs::read_to_string(dir.join("status")).ok()?;
+
+        let field = |name: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.split_whitespace().next())
+                .and_then(|value| value.parse::<u32>().ok())
+        };
+        // Real uid, the first of the Uid: line
+        let uid = field("Uid:")?;
+        let ppid = field("PPid:").unwrap_or(0);
+
+        let argv: Vec<String> = std::fs::read(dir.join("cmdline"))
+            .map(|cmdline| {
+                cmdline
+                    .split(|&b| b == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let executable = std::fs::read_link(dir.join("exe"))
+            .map(|exe| exe.to_string_lossy().into_owned())
+            .ok()
+            .or_else(|| argv.first().cloned())?;
+
+        Some(Self { executable, argv, uid, ppid })
+    }
+}
+
+/// Parent pids of a process, nearest first, ending before pid 0
+pub fn parent_chain(ppid: u32) -> Vec<u32> {
+    let mut chain = Vec::new();
+    let mut pid = ppid;
+
+    while pid != 0 && chain.len() < MAX_PARENT_DEPTH && !chain.contains(&pid) {
+        chain.push(pid);
+        pid = ProcessInfo::read(pid).map(|info| info.ppid).unwrap_or(0);
+    }
+
+    chain
+}
+
+#[derive(Default)]
+struct ProcessTable {
+    /// uid of each process reported as started, to attribute its exit
+    running: HashMap<u32, u32>,
+    recent: VecDeque<Value>,
+}
+
+impl ProcessTable {
+    fn activity(&mut self, event: RawProcessEvent) -> Option<ActivityEvent> {
+        match event {
+            RawProcessEvent::Exec { pid, uid, filename } => {
+                // Short-lived processes may be gone already, keep what the backend saw
+                let (executable, args, uid, parent_pids) = match ProcessInfo::read(pid) {
+                    Some(info) => (info.executable, info.argv, info.uid, parent_chain(info.ppid)),
+                    None => {
+                        let executable = filename?;
+                        (executable.clone(), vec![executable], uid?, Vec::new())
+                    }
+                };
+
+                self.running.insert(pid, uid);
+                if self.recent.len() == MAX_RECENT_PROCESSES {
+                    self.recent.pop_front();
+                }
+                self.recent.push_back(json!({
+                    "pid": pid,
+                    "uid": uid,
+                    "executable": executable,
+                    "args": args,
+                    "parent_pids": parent_pids,
+                    "timestamp": trusted_clock::now().to_rfc3339(),
+                }));
+
+                Some(ActivityEvent::ProcessStarted {
+                    pid,
+                    executable,
+                    args,
+                    uid,
+                    parent_pids,
+                    timestamp: SystemTime::now(),
+                })
+            }
+            RawProcessEvent::Exit { pid } => {
+                let uid = self.running.remove(&pid)?;
+                Some(ActivityEvent::ProcessExited { pid, uid, timestamp: SystemTime::now() })
+            }
+        }
+    }
+}
+
+pub struct ProcessMonitor {
+    backend: Mutex<Option<ProcessBackend>>,
+    table: Arc<Mutex<ProcessTable>>,
+    events: Mutex<Option<mpsc::Receiver<ActivityEvent>>>,
+    ebpf: Mutex<Option<ProcessMonitorEbpf>>,
+}
+
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessMonitor {
+    pub fn new() -> Self {
+        Self {
+            backend: Mutex::default(),
+            table: Arc::default(),
+            events: Mutex::default(),
+            ebpf: Mutex::default(),
+        }
+    }
+
+    /// Start with the best available backend. `ebpf_path` is the compiled
+    /// process monitor program, if one was built.
+    pub async fn start(&self, ebpf_path: Option<&Path>) -> Result<ProcessBackend> {
+        let mut candidates = Vec::new();
+        if ebpf_path.is_some() {
+            candidates.push(ProcessBackend::Ebpf);
+        }
+        candidates.extend([ProcessBackend::ProcConnector, ProcessBackend::ProcScan]);
+
+        for backend in candidates {
+            match self.start_backend(backend, ebpf_path).await {
+                Ok(()) => return Ok(backend),
+                Err(e) => warn!("Process monitor {} backend unavailable: {}", backend, e),
+            }
+        }
+
+        Err(anyhow::anyhow!("No process monitor backend could be started"))
+    }
+
+    /// Start a specific backend
+    pub async fn start_backend(
+        &self,
+        backend: ProcessBackend,
+        ebpf_path: Option<&Path>,
+    ) -> Result<()> {
+        let mut current = self.backend.lock().await;
+        if let Some(running) = *current {
+            return Err(anyhow::anyhow!("Process monitor already running with {}", running));
+        }
+
+        let (raw_tx, raw_rx) = mpsc::channel(1024);
+        match backend {
+            ProcessBackend::Ebpf => {
+                let path = ebpf_path.context("No process monitor eBPF program")?;
+                let mut ebpf = ProcessMonitorEbpf::load(path)?;
+                ebpf.start(raw_tx)?;
+                *self.ebpf.lock().await = Some(ebpf);
+            }
+            ProcessBackend::ProcConnector => {
+                let socket = ProcConnector::connect()?;
+                std::thread::Builder::new()
+                    .name("proc-connector".to_string())
+                    .spawn(move || socket.run(raw_tx))
+                    .context("Failed to start proc connector thread")?;
+            }
+            ProcessBackend::ProcScan => {
+                // Processes already running are not reported
+                let proc_root = PathBuf::from("/proc");
+                let known = list_pids(&proc_root)?;
+                tokio::spawn(scan_proc(proc_root, known, raw_tx));
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(1024);
+        tokio::spawn(run_pipeline(raw_rx, self.table.clone(), tx));
+        *self.events.lock().await = Some(rx);
+        *current = Some(backend);
+
+        info!("Process monitor started with {} backend", backend);
+        Ok(())
+    }
+
+    /// The stream of process activity, available once after start
+    pub async fn take_events(&self) -> Option<mpsc::Receiver<ActivityEvent>> {
+        self.events.lock().await.take()
+    }
+
+    pub async fn collect_snapshot(&self) -> Result<Value> {
+        let backend = self.backend.lock().await.map(|backend| backend.to_string());
+        let table = self.table.lock().await;
+
+        Ok(json!({
+            "timestamp": trusted_clock::now().to_rfc3339(),
+            "backend": backend,
+            "running_processes": table.running.len(),
+            "recent_processes": table.recent,
+        }))
+    }
+}
+
+async fn run_pipeline(
+    mut raw_events: mpsc::Receiver<RawProcessEvent>,
+    table: Arc<Mutex<ProcessTable>>,
+    events: mpsc::Sender<ActivityEvent>,
+) {
+    while let Some(raw) = raw_events.recv().await {
+        let activity = table.lock().await.activity(raw);
+        if let Some(activity) = activity {
+            // Events are dropped until someone takes the stream
+            if events.is_closed() {
+                break;
+            }
+            if events.try_send(activity).is_err() {
+                debug!("Process event stream is full, dropping event");
+            }
+        }
+    }
+}
+
+/// Fallback backend: diff the pids in `/proc` at a fixed interval
+async fn scan_proc(
+    proc_root: PathBuf,
+    mut known: HashSet<u32>,
+    events: mpsc::Sender<RawProcessEvent>,
+) {
+    let mut interval_timer = tokio::time::interval(SCAN_INTERVAL);
+
+    loop {
+        interval_timer.tick().await;
+
+        let current = match list_pids(&proc_root) {
+            Ok(current) => current,
+            Err(e) => {
+                warn!("Failed to scan {}: {}", proc_root.display(), e);
+                continue;
+            }
+        };
+
+        let started = current.difference(&known).map(|&pid| RawProcessEvent::Exec {
+            pid,
+            uid: std::fs::metadata(proc_root.join(pid.to_string())).ok().map(|m| m.uid()),
+            filename: None,
+        });
+        let exited = known.difference(&current).map(|&pid| RawProcessEvent::Exit { pid });
+
+        for event in started.chain(exited).collect::<Vec<_>>() {
+            if events.send(event).await.is_err() {
+                return;
+            }
+        }
+        known = current;
+    }
+}
+
+fn list_pids(proc_root: &Path) -> Result<HashSet<u32>> {
+    Ok(std::fs::read_dir(proc_root)?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .collect())
+}
+
+// Netlink proc connector, see include/uapi/linux/cn_proc.h
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+const NLMSG_HDR_LEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+/// Offset of `proc_event.what`; the event data follows `cpu` and `timestamp_ns`
+const PROC_EVENT_OFFSET: usize = NLMSG_HDR_LEN + CN_MSG_LEN;
+const PROC_EVENT_DATA_OFFSET: usize = PROC_EVENT_OFFSET + 16;
+
+struct ProcConnector {
+    fd: std::os::fd::OwnedFd,
+}
+
+impl ProcConnector {
+    /// Subscribe to process events, requires CAP_NET_ADMIN
+    fn connect() -> Result<Self> {
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+        let raw = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_CONNECTOR,
+            )
+        };
+        if raw < 0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to open netlink socket");
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_pid = std::process::id();
+        addr.nl_groups = CN_IDX_PROC;
+        let bound = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if bound < 0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to bind proc connector");
+        }
+
+        let message = subscribe_message(std::process::id());
+        let sent = unsafe {
+            libc::send(fd.as_raw_fd(), message.as_ptr() as *const libc::c_void, message.len(), 0)
+        };
+        if sent < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("Failed to subscribe to process events");
+        }
+
+        Ok(Self { fd })
+    }
+
+    fn run(self, events: mpsc::Sender<RawProcessEvent>) {
+        use std::os::fd::AsRawFd;
+
+        let mut buf = [0u8; 4096];
+        loop {
+            let len = unsafe {
+                libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0)
+            };
+            if len < 0 {
+                let error = std::io::Error::last_os_error();
+                // ENOBUFS means events were lost, the socket itself is still usable
+                if matches!(error.raw_os_error(), Some(libc::EINTR) | Some(libc::ENOBUFS)) {
+                    continue;
+                }
+                warn!("Proc connector stopped: {}", error);
+                return;
+            }
+
+            if let Some(event) = parse_proc_event(&buf[..len as usize]) {
+                if events.blocking_send(event).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn subscribe_message(pid: u32) -> Vec<u8> {
+    let total = NLMSG_HDR_LEN + CN_MSG_LEN + 4;
+    let mut message = Vec::with_capacity(total);
+
+    // struct nlmsghdr
+    message.extend_from_slice(&(total as u32).to_ne_bytes());
+    message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+    message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+    message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+    message.extend_from_slice(&pid.to_ne_bytes());
+
+    // struct cn_msg
+    message.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+    message.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+    message.extend_from_slice(&0u32.to_ne_bytes()); // seq
+    message.extend_from_slice(&0u32.to_ne_bytes()); // ack
+    message.extend_from_slice(&4u16.to_ne_bytes()); // len
+    message.extend_from_slice(&0u16.to_ne_bytes()); // flags
+
+    message.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    message
+}
+
+fn parse_proc_event(message: &[u8]) -> Option<RawProcessEvent> {
+    let read_u32 = |offset: usize| {
+        message.get(offset..offset + 4).map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+    };
+
+    let what = read_u32(PROC_EVENT_OFFSET)?;
+    let pid = read_u32(PROC_EVENT_DATA_OFFSET)?;
+    let tgid = read_u32(PROC_EVENT_DATA_OFFSET + 4)?;
+
+    match what {
+        PROC_EVENT_EXEC => Some(RawProcessEvent::Exec { pid: tgid, uid: None, filename: None }),
+        // Exits are reported per thread, only the thread group leader ends the process
+        PROC_EVENT_EXIT if pid == tgid => Some(RawProcessEvent::Exit { pid }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc_event(what: u32, pid: u32, tgid: u32) -> Vec<u8> {
+        let mut message = vec![0u8; PROC_EVENT_DATA_OFFSET];
+        message[PROC_EVENT_OFFSET..PROC_EVENT_OFFSET + 4].copy_from_slice(&what.to_ne_bytes());
+        message.extend_from_slice(&pid.to_ne_bytes());
+        message.extend_from_slice(&tgid.to_ne_bytes());
+        message
+    }
+
+    #[test]
+    fn test_parse_proc_connector_events() {
+        assert_eq!(
+            parse_proc_event(&proc_event(PROC_EVENT_EXEC, 10, 10)),
+            Some(RawProcessEvent::Exec { pid: 10, uid: None, filename: None })
+        );
+        assert_eq!(
+            parse_proc_event(&proc_event(PROC_EVENT_EXIT, 10, 10)),
+            Some(RawProcessEvent::Exit { pid: 10 })
+        );
+        assert_eq!(parse_proc_event(&proc_event(PROC_EVENT_EXIT, 11, 10)), None);
+        assert_eq!(parse_proc_event(&proc_event(PROC_EVENT_EXEC, 10, 10)[..20]), None);
+        assert_eq!(subscribe_message(1).len(), 40);
+    }
+
+    #[test]
+    fn test_process_info_of_self() {
+        let info = ProcessInfo::read(std::process::id()).unwrap();
+        assert_eq!(info.uid, nix::unistd::getuid().as_raw());
+        assert!(!info.argv.is_empty());
+        assert!(parent_chain(info.ppid).starts_with(&[info.ppid]));
+    }
+
+    #[tokio::test]
+    async fn test_proc_scan_backend_reports_exec_and_exit() {
+        let monitor = ProcessMonitor::new();
+        monitor.start_backend(ProcessBackend::ProcScan, None).await.unwrap();
+        let mut events = monitor.take_events().await.unwrap();
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let child_pid = child.id();
+
+        let started = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(ActivityEvent::ProcessStarted { pid, args, uid, parent_pids, .. }) =
+                    events.recv().await
+                {
+                    if pid == child_pid {
+                        return (args, uid, parent_pids);
+                    }
+                }
+            }
+        })
+        .await
+        .expect("no ProcessStarted event for the child");
+
+        assert_eq!(started.0, vec!["sleep".to_string(), "30".to_string()]);
+        assert_eq!(started.1, nix::unistd::getuid().as_raw());
+        assert_eq!(started.2.first(), Some(&std::process::id()));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(ActivityEvent::ProcessExited { pid, .. }) = events.recv().await {
+                    if pid == child_pid {
+                        return;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("no ProcessExited event for the child");
+
+        let snapshot = monitor.collect_snapshot().await.unwrap();
+        assert_eq!(snapshot["backend"], "/proc scan");
+    }
+}
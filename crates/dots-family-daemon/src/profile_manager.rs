@@ -1,22 +1,38 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use dots_family_common::{
-    security::{EncryptionKey, PasswordManager, SessionToken},
-    types::{ApplicationMode, Profile},
+    security::{PasswordManager, RateLimiter, SessionToken},
+    types::{Activity, ActivityType, ApplicationMode, Profile},
+    Guardian, GuardianRole, Permission,
 };
-use dots_family_db::{queries::profiles::ProfileQueries, Database};
+use dots_family_db::{
+    models::DbProfile,
+    queries::{
+        activity_coverage::ActivityCoverage, guardians::GuardianRow, profiles::ProfileQueries,
+    },
+    Database, DbError,
+};
+use dots_family_proto::{error::DaemonError, events::ActivityEvent, payload};
 use secrecy::SecretString;
 use sqlx::Row;
-use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tokio::sync::{watch, RwLock};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::{config::DaemonConfig, notification_manager::NotificationManager};
+use crate::{
+    accounts::uid_of_process, app_catalog::AppCatalog, audit_sealer::AuditSealer,
+    config::DaemonConfig, database_key::DatabaseKeys, notification_manager::NotificationManager,
+    profile_resolver::ProfileResolver, trusted_clock,
+};
 
 #[allow(dead_code)]
 const HEARTBEAT_TIMEOUT_SECS: u64 = 30;
 
+/// logind's per-user state files
+const USER_STATE_DIR: &str = "/run/systemd/users";
+
 #[derive(Debug, Clone)]
 struct MonitorHeartbeat {
     #[allow(dead_code)]
@@ -25,6 +41,24 @@ struct MonitorHeartbeat {
     last_seen: Instant,
 }
 
+/// A per-user component (monitor, filter, terminal filter) registered from its
+/// session; activities it reports are recorded for the profile of its account
+#[derive(Debug, Clone)]
+pub struct RegisteredComponent {
+    pub kind: String,
+    pub uid: u32,
+    pub profile_id: Uuid,
+}
+
+/// A stretch of time a registered component reported no activity for
+#[derive(Debug, Clone)]
+pub struct ActivityGap {
+    pub profile_id: Uuid,
+    pub component: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
 #[derive(Clone)]
 pub struct ProfileManager {
     _db: Database,
@@ -32,11 +66,21 @@ pub struct ProfileManager {
     active_profile: Arc<RwLock<Option<Profile>>>,
     active_session_id: Arc<RwLock<Option<String>>>,
     monitor_heartbeats: Arc<RwLock<HashMap<String, MonitorHeartbeat>>>,
+    /// Registered components by unique bus name
+    components: Arc<RwLock<HashMap<String, RegisteredComponent>>>,
+    /// Activity gaps not yet handed to tamper detection
+    activity_gaps: Arc<RwLock<Vec<ActivityGap>>>,
     tamper_detected: Arc<RwLock<bool>>,
-    /// Active session tokens for parent authentication
+    /// Active session tokens for parent authentication, each bound to a guardian account
     active_sessions: Arc<RwLock<HashMap<String, SessionToken>>>,
+    /// Recent failed sign-ins by guardian name
+    sign_in_limits: Arc<RwLock<HashMap<String, RateLimiter>>>,
     /// Notification manager for desktop and system notifications
     notification_manager: NotificationManager,
+    /// uid -> profile table for kernel-reported activity
+    profile_resolver: Arc<ProfileResolver>,
+    /// Ticks whenever a profile is created or its configuration changes
+    profile_changes: Arc<watch::Sender<()>>,
 }
 
 impl ProfileManager {
@@ -44,14 +88,19 @@ impl ProfileManager {
         info!("Initializing ProfileManager with existing database instance");
 
         let manager = Self {
+            profile_resolver: Arc::new(ProfileResolver::new(database.clone())),
             _db: database,
             config: config.clone(),
             active_profile: Arc::new(RwLock::new(None)),
             active_session_id: Arc::new(RwLock::new(None)),
             monitor_heartbeats: Arc::new(RwLock::new(HashMap::new())),
+            components: Arc::new(RwLock::new(HashMap::new())),
+            activity_gaps: Arc::new(RwLock::new(Vec::new())),
             tamper_detected: Arc::new(RwLock::new(false)),
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            sign_in_limits: Arc::new(RwLock::new(HashMap::new())),
             notification_manager: NotificationManager::new(),
+            profile_changes: Arc::new(watch::Sender::new(())),
         };
 
         manager.load_active_profile_from_db().await?;
@@ -59,6 +108,21 @@ impl ProfileManager {
         Ok(manager)
     }
 
+    /// Maps uids to child profiles; invalidated whenever profiles change
+    pub fn profile_resolver(&self) -> Arc<ProfileResolver> {
+        self.profile_resolver.clone()
+    }
+
+    /// Notified after a profile is created or its configuration is saved
+    pub fn subscribe_profile_changes(&self) -> watch::Receiver<()> {
+        self.profile_changes.subscribe()
+    }
+
+    async fn profile_changed(&self) {
+        self.profile_resolver.invalidate().await;
+        self.profile_changes.send_replace(());
+    }
+
     async fn load_active_profile_from_db(&self) -> Result<()> {
         let pool = self._db.pool()?;
 
@@ -112,7 +176,7 @@ impl ProfileManager {
         let new_session = NewSession::new(profile_id.to_string());
         let session_id = new_session.id.clone();
 
-        SessionQueries::create(&self._db, new_session).await?;
+        SessionQueries::create(&self._db, new_session, trusted_clock::now()).await?;
 
         let mut session = self.active_session_id.write().await;
         *session = Some(session_id.clone());
@@ -168,7 +232,7 @@ impl ProfileManager {
         let new_session = NewSession::new(profile_id.to_string());
         let session_id = new_session.id.clone();
 
-        SessionQueries::create(&self._db, new_session).await?;
+        SessionQueries::create(&self._db, new_session, trusted_clock::now()).await?;
 
         let mut active = self.active_profile.write().await;
         *active = Some(profile);
@@ -188,7 +252,7 @@ impl ProfileManager {
 
     #[allow(dead_code)]
     pub async fn deactivate_profile(&self, end_reason: &str) -> Result<()> {
-        use dots_family_db::queries::sessions::SessionQueries;
+        use dots_family_db::{models::SessionTotals, queries::sessions::SessionQueries};
 
         let session_id_opt = {
             let session_id = self.active_session_id.read().await;
@@ -196,7 +260,14 @@ impl ProfileManager {
         };
 
         if let Some(session_id) = session_id_opt {
-            SessionQueries::end_session(&self._db, &session_id, end_reason, 0, 0, 0, 0).await?;
+            SessionQueries::end_session(
+                &self._db,
+                &session_id,
+                end_reason,
+                &SessionTotals::default(),
+                trusted_clock::now(),
+            )
+            .await?;
 
             *self.active_profile.write().await = None;
             *self.active_session_id.write().await = None;
@@ -279,8 +350,11 @@ impl ProfileManager {
                     success: false,
                     details: Some(format!("Invalid age group: {}", age_group)),
                 };
-                let _ = AuditQueries::log(&self._db, audit).await;
-                return Err(anyhow!("Invalid age group. Use: 5-7, 8-12, or 13-17"));
+                let _ = AuditQueries::log(&self._db, audit, trusted_clock::now()).await;
+                return Err(DaemonError::InvalidArgument(
+                    "Invalid age group. Use: 5-7, 8-12, or 13-17".to_string(),
+                )
+                .into());
             }
         };
 
@@ -306,6 +380,7 @@ impl ProfileManager {
                     }],
                     holiday: vec![],
                 },
+                enforcement: Default::default(),
             },
             applications: ApplicationConfig {
                 mode: ApplicationMode::Allowlist,
@@ -321,6 +396,7 @@ impl ProfileManager {
                 blocked_domains: vec![],
             },
             terminal_filtering: TerminalFilteringConfig::default(),
+            network: Default::default(),
         };
 
         let config_json = serde_json::to_string(&config)?;
@@ -336,6 +412,7 @@ impl ProfileManager {
 
         let profile_id = new_profile.id.clone();
         ProfileQueries::create(&self._db, new_profile).await?;
+        self.profile_changed().await;
 
         let audit = NewAuditLog {
             actor: "parent".to_string(),
@@ -346,7 +423,7 @@ impl ProfileManager {
             success: true,
             details: Some(format!("Created profile '{}' with age group {}", name, age_group)),
         };
-        let _ = AuditQueries::log(&self._db, audit).await;
+        let _ = AuditQueries::log(&self._db, audit, trusted_clock::now()).await;
 
         info!("Created profile: {} ({})", name, profile_id);
         Ok(profile_id)
@@ -364,7 +441,11 @@ impl ProfileManager {
             return Ok(true);
         };
 
-        let daily_limit_seconds = profile.config.screen_time.daily_limit_minutes as i64 * 60;
+        // Banked minutes spent today extend today's allowance
+        let spent_credit_minutes = self.time_credits_spent_today(&profile.id.to_string()).await?;
+
+        let daily_limit_seconds =
+            (profile.config.screen_time.daily_limit_minutes + spent_credit_minutes) as i64 * 60;
         let used_seconds = self.get_used_time_today().await?;
 
         if used_seconds >= daily_limit_seconds {
@@ -390,11 +471,9 @@ impl ProfileManager {
             return Ok(0);
         };
 
-        use chrono::{DateTime, Utc};
         use dots_family_db::queries::activities::ActivityQueries;
 
-        let today_start = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap();
-        let today_start_dt = DateTime::<Utc>::from_naive_utc_and_offset(today_start, Utc);
+        let (today_start_dt, _) = Self::local_day_bounds(trusted_clock::now_local().date_naive());
 
         let profile_id_str = profile.id.to_string();
         let activities =
@@ -413,37 +492,155 @@ impl ProfileManager {
             return Ok(0);
         };
 
-        let daily_limit_seconds = profile.config.screen_time.daily_limit_minutes as i64 * 60;
+        // Banked minutes spent today extend today's allowance
+        let spent_credit_minutes = self.time_credits_spent_today(&profile.id.to_string()).await?;
+
+        let daily_limit_seconds =
+            (profile.config.screen_time.daily_limit_minutes + spent_credit_minutes) as i64 * 60;
         let used_seconds = self.get_used_time_today().await?;
         let remaining_seconds = (daily_limit_seconds - used_seconds).max(0);
 
         Ok((remaining_seconds / 60) as u32)
     }
 
-    pub async fn report_activity(&self, activity_json: &str) -> Result<()> {
-        use dots_family_common::types::Activity;
+    /// Register a component for the child profile linked to its account. The
+    /// uid and username come from the bus, not from the component.
+    pub async fn register_component(
+        &self,
+        sender: &str,
+        kind: &str,
+        uid: u32,
+        username: &str,
+    ) -> Result<Uuid> {
+        let profile = self
+            .list_profiles()
+            .await?
+            .into_iter()
+            .find(|profile| profile.active && profile.username.as_deref() == Some(username))
+            .ok_or_else(|| {
+                DaemonError::NotFound(format!("No child profile for account {}", username))
+            })?;
+
+        let component = RegisteredComponent { kind: kind.to_string(), uid, profile_id: profile.id };
+        self.components.write().await.insert(sender.to_string(), component);
+
+        info!("Registered {} ({}) of {} for profile {}", kind, sender, username, profile.id);
+        Ok(profile.id)
+    }
+
+    /// Forget a component whose bus connection went away
+    pub async fn unregister_component(&self, sender: &str) {
+        if let Some(component) = self.components.write().await.remove(sender) {
+            debug!("Unregistered {} ({})", component.kind, sender);
+        }
+    }
+
+    pub async fn registered_component(&self, sender: &str) -> Option<RegisteredComponent> {
+        self.components.read().await.get(sender).cloned()
+    }
+
+    /// Profile an activity event belongs to: the registered sender's, or for
+    /// anyone else that of the account the process runs as
+    pub async fn activity_event_profile(
+        &self,
+        sender: Option<&str>,
+        event: &ActivityEvent,
+    ) -> Option<Uuid> {
+        if let Some(component) = match sender {
+            Some(sender) => self.registered_component(sender).await,
+            None => None,
+        } {
+            return Some(component.profile_id);
+        }
+
+        let profile = match event {
+            ActivityEvent::ProcessStarted { pid, uid, .. } => {
+                self.profile_resolver.resolve_process(*pid, *uid).await
+            }
+            ActivityEvent::ProcessExited { uid, .. } => {
+                self.profile_resolver.resolve_uid(*uid).await
+            }
+            ActivityEvent::WindowFocused { pid, .. }
+            | ActivityEvent::NetworkConnection { pid, .. } => match uid_of_process(*pid) {
+                Ok(uid) => self.profile_resolver.resolve_uid(uid).await,
+                Err(_) => Ok(None),
+            },
+        };
+        match profile {
+            Ok(profile) => profile.and_then(|id| Uuid::parse_str(&id).ok()),
+            Err(e) => {
+                warn!("Failed to resolve the profile of {:?}: {}", event, e);
+                None
+            }
+        }
+    }
+
+    /// Record an activity. A registered sender's activities are stamped with
+    /// its profile. The profile named in the activity is only used for a
+    /// `trusted` (system) caller; anyone else must register first. An activity
+    /// already recorded under the same id, e.g. replayed from a monitor's
+    /// offline spool, is accepted and not stored again.
+    pub async fn report_activity(
+        &self,
+        activity_json: &str,
+        sender: Option<&str>,
+        trusted: bool,
+    ) -> Result<()> {
         use dots_family_db::{models::NewActivity, queries::activities::ActivityQueries};
 
         info!("Activity reported: {}", activity_json);
 
-        let activity: Activity = serde_json::from_str(activity_json)?;
+        let mut activity: Activity = payload::decode(activity_json)
+            .map_err(|e| DaemonError::InvalidArgument(format!("Invalid activity: {}", e)))?;
 
-        let session_id = {
-            let active_session = self.active_session_id.read().await;
-            active_session.clone().unwrap_or_else(|| activity.profile_id.to_string())
+        let registered = match sender {
+            Some(sender) => self.registered_component(sender).await,
+            None => None,
         };
+        match &registered {
+            Some(component) => activity.profile_id = component.profile_id,
+            None if !trusted => {
+                return Err(DaemonError::NotAuthorized(
+                    "Unauthorized: activity reports need a registered component".to_string(),
+                )
+                .into());
+            }
+            None => {}
+        }
+        if activity.profile_id.is_nil() {
+            return Err(DaemonError::InvalidArgument(
+                "Activity has no profile; register the component first".to_string(),
+            )
+            .into());
+        }
+
+        if let Some(component) = &registered {
+            self.track_activity_gap(component, &activity).await;
+        }
+
+        // Time with nothing focused only accounts for the gap above
+        if matches!(activity.activity_type, ActivityType::Unfocused) {
+            return Ok(());
+        }
+
+        let session_id = self.session_for_profile(activity.profile_id).await?;
 
         let new_activity = NewActivity {
+            activity_id: Some(activity.id.to_string()),
             session_id,
             profile_id: activity.profile_id.to_string(),
             app_id: activity.application.as_deref().unwrap_or("unknown").to_string(),
             app_name: activity.application.as_deref().unwrap_or("Unknown Application").to_string(),
             category: None,
-            window_title: activity.window_title,
+            window_title: activity.window_title.clone(),
             duration_seconds: activity.duration_seconds as i64,
+            timestamp: Some(activity.timestamp),
         };
 
-        ActivityQueries::create(&self._db, new_activity).await?;
+        if ActivityQueries::create(&self._db, new_activity).await?.is_none() {
+            debug!("Activity {} was already recorded", activity.id);
+            return Ok(());
+        }
         info!(
             "Activity stored in database: app_id={}, duration={}s, profile_id={}",
             activity.application.as_deref().unwrap_or("unknown"),
@@ -454,10 +651,126 @@ impl ProfileManager {
         Ok(())
     }
 
+    /// Compare an activity with how far its profile's timeline is accounted
+    /// for; a hole longer than the tamper limit means activity was withheld or
+    /// lost. The timeline is kept in the database, so reconnecting or a daemon
+    /// restart does not start it afresh.
+    async fn track_activity_gap(&self, component: &RegisteredComponent, activity: &Activity) {
+        use dots_family_db::queries::activity_coverage::ActivityCoverageQueries;
+
+        let end = activity.timestamp;
+        let start = end - chrono::Duration::seconds(activity.duration_seconds as i64);
+        let profile_id = component.profile_id.to_string();
+        let boot = trusted_clock::current_boot();
+
+        let covered = match ActivityCoverageQueries::get(&self._db, &profile_id).await {
+            Ok(covered) => covered,
+            Err(e) => {
+                warn!("Failed to read activity coverage of profile {}: {}", profile_id, e);
+                return;
+            }
+        };
+
+        if let Some(covered) = covered {
+            let limit = self.config.tamper.max_activity_gap_seconds as i64;
+            let logged_in_since = logged_in_since(Path::new(USER_STATE_DIR), component.uid);
+            let unaccounted = unaccounted_time(&covered, start, &boot, logged_in_since);
+            if self.config.tamper.enabled && unaccounted.num_seconds() > limit {
+                warn!(
+                    "{} of profile {} reported no activity from {} to {}",
+                    component.kind, component.profile_id, covered.covered_until, start
+                );
+                self.activity_gaps.write().await.push(ActivityGap {
+                    profile_id: component.profile_id,
+                    component: component.kind.clone(),
+                    from: covered.covered_until,
+                    to: start,
+                });
+            }
+        }
+
+        let coverage = ActivityCoverage {
+            covered_until: end,
+            boot_id: boot.id,
+            asleep_ms: boot.asleep.as_millis() as i64,
+        };
+        if let Err(e) = ActivityCoverageQueries::extend(&self._db, &profile_id, &coverage).await {
+            warn!("Failed to record activity coverage of profile {}: {}", profile_id, e);
+        }
+    }
+
+    /// Activity gaps found since the last call
+    pub async fn take_activity_gaps(&self) -> Vec<ActivityGap> {
+        std::mem::take(&mut *self.activity_gaps.write().await)
+    }
+
+    /// Session an activity of this profile belongs to: the active session for
+    /// the active profile, otherwise the profile's open session or a new one
+    async fn session_for_profile(&self, profile_id: Uuid) -> Result<String> {
+        use dots_family_db::{models::NewSession, queries::sessions::SessionQueries};
+
+        {
+            let active_profile = self.active_profile.read().await;
+            let active_session = self.active_session_id.read().await;
+            if let (Some(profile), Some(session)) =
+                (active_profile.as_ref(), active_session.as_ref())
+            {
+                if profile.id == profile_id {
+                    return Ok(session.clone());
+                }
+            }
+        }
+
+        let profile_id = profile_id.to_string();
+        if let Some(session) = SessionQueries::get_active_session(&self._db, &profile_id).await? {
+            return Ok(session.id);
+        }
+
+        let session = SessionQueries::create(
+            &self._db,
+            NewSession::new(profile_id.clone()),
+            trusted_clock::now(),
+        )
+        .await?;
+        info!("Created session {} for profile {}", session.id, profile_id);
+        Ok(session.id)
+    }
+
+    /// Record a launch denied by the exec guard as a policy violation
+    pub async fn record_blocked_launch(
+        &self,
+        blocked: &crate::exec_guard::BlockedExec,
+    ) -> Result<()> {
+        use dots_family_db::{models::NewEvent, queries::events::EventQueries};
+
+        let details = serde_json::json!({
+            "type": "blocked_launch",
+            "path": blocked.path,
+            "rule": blocked.rule,
+            "pid": blocked.pid,
+            "uid": blocked.uid,
+        });
+
+        EventQueries::create(
+            &self._db,
+            NewEvent {
+                profile_id: Some(blocked.profile_id.clone()),
+                event_type: "policy_violation".to_string(),
+                severity: "warning".to_string(),
+                details: Some(details.to_string()),
+                metadata: None,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn authenticate_parent(&self, password: &str) -> Result<String> {
         if password.is_empty() {
-            return Err(anyhow!("Invalid password"));
+            return Err(DaemonError::NotAuthorized("Invalid password".to_string()).into());
         }
+        self.check_sign_in_limit(Guardian::PRIMARY_NAME).await?;
 
         // Get stored password hash from config
         let stored_hash = match &self.config.auth.parent_password_hash {
@@ -475,9 +788,10 @@ impl ProfileManager {
         match PasswordManager::verify_password(&password_secret, stored_hash) {
             Ok(true) => {
                 info!("Parent authentication successful");
+                self.record_sign_in(Guardian::PRIMARY_NAME, true).await;
 
-                // Generate secure session token
-                let session_token = SessionToken::generate();
+                // Generate secure session token for the primary parent account
+                let session_token = SessionToken::generate(&Guardian::primary().id);
                 let token_string = session_token.token().to_string();
 
                 // Store the session token for validation
@@ -487,11 +801,19 @@ impl ProfileManager {
                 }
 
                 info!("Session token created and stored");
+
+                if self.config.database.encrypt {
+                    if let Err(e) = self.ensure_database_key(password_secret).await {
+                        warn!("Failed to set up database encryption key: {}", e);
+                    }
+                }
+
                 Ok(token_string)
             }
             Ok(false) => {
                 warn!("Parent authentication failed - invalid password");
-                Err(anyhow!("Invalid password"))
+                self.record_sign_in(Guardian::PRIMARY_NAME, false).await;
+                Err(DaemonError::NotAuthorized("Invalid password".to_string()).into())
             }
             Err(e) => {
                 warn!("Parent authentication error: {}", e);
@@ -500,15 +822,373 @@ impl ProfileManager {
         }
     }
 
+    /// Sign in to a guardian account; "parent" uses the shared parent password
+    pub async fn authenticate_guardian(&self, name: &str, password: &str) -> Result<String> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, guardians::GuardianQueries},
+        };
+
+        if name == Guardian::PRIMARY_NAME {
+            return self.authenticate_parent(password).await;
+        }
+        if password.is_empty() {
+            return Err(DaemonError::NotAuthorized("Invalid password".to_string()).into());
+        }
+        self.check_sign_in_limit(name).await?;
+
+        let row = GuardianQueries::get_by_name(&self._db, name).await?;
+        let password_secret = SecretString::new(password.to_string().into());
+        let verified = match &row {
+            Some(row) => PasswordManager::verify_password(&password_secret, &row.password_hash)
+                .map_err(|e| anyhow!("Authentication failed: {}", e))?,
+            None => false,
+        };
+
+        let audit = NewAuditLog {
+            actor: format!("guardian:{}", name),
+            action: "authenticate".to_string(),
+            resource: "session".to_string(),
+            resource_id: row.as_ref().map(|row| row.id.clone()),
+            ip_address: None,
+            success: verified,
+            details: (!verified).then(|| "Invalid name or password".to_string()),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+        self.record_sign_in(name, verified).await;
+
+        match row {
+            Some(row) if verified => {
+                let session_token = SessionToken::generate(&row.id);
+                let token_string = session_token.token().to_string();
+                self.active_sessions.write().await.insert(token_string.clone(), session_token);
+
+                info!("Guardian {} signed in", name);
+                Ok(token_string)
+            }
+            _ => {
+                warn!("Guardian authentication failed for {}", name);
+                Err(DaemonError::NotAuthorized("Invalid name or password".to_string()).into())
+            }
+        }
+    }
+
+    /// Refuse a sign-in to `name` after too many recent failures
+    async fn check_sign_in_limit(&self, name: &str) -> Result<()> {
+        let mut limits = self.sign_in_limits.write().await;
+        limits.retain(|_, limiter| !limiter.is_idle());
+
+        if limits.get_mut(name).is_some_and(|limiter| !limiter.check_rate_limit(None)) {
+            return Err(DaemonError::RateLimited(format!(
+                "Too many failed sign-ins for {}; try again later",
+                name
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Count a failed sign-in; a successful one starts the count afresh
+    async fn record_sign_in(&self, name: &str, successful: bool) {
+        let mut limits = self.sign_in_limits.write().await;
+        if successful {
+            limits.remove(name);
+        } else {
+            limits.entry(name.to_string()).or_default().record_attempt(false, None);
+        }
+    }
+
+    /// Look up a profile given by ID or by name
+    async fn find_profile(&self, id_or_name: &str) -> Result<DbProfile> {
+        match ProfileQueries::get_by_id(&self._db, id_or_name).await {
+            Err(DbError::NotFound(_)) => {}
+            found => return Ok(found?),
+        }
+
+        match ProfileQueries::get_by_name(&self._db, id_or_name).await {
+            Err(DbError::NotFound(_)) => {
+                Err(DaemonError::NotFound(format!("Profile not found: {}", id_or_name)).into())
+            }
+            found => Ok(found?),
+        }
+    }
+
+    /// Open a session for a parent the D-Bus caller checks already authorized
+    /// by polkit or group membership, so token-guarded methods work without a password
+    pub async fn open_local_session(&self, username: &str) -> String {
+        let session_token = SessionToken::generate(&Guardian::local(username).id);
+        let token_string = session_token.token().to_string();
+        self.active_sessions.write().await.insert(token_string.clone(), session_token);
+        token_string
+    }
+
     /// Validate a session token and return whether it's still valid
     pub async fn validate_session(&self, token: &str) -> bool {
-        let sessions = self.active_sessions.read().await;
+        matches!(self.session_guardian(token).await, Ok(Some(_)))
+    }
 
-        if let Some(session_token) = sessions.get(token) {
-            session_token.is_valid()
-        } else {
-            false
+    /// The guardian a valid session token belongs to
+    async fn session_guardian(&self, token: &str) -> Result<Option<Guardian>> {
+        use dots_family_db::queries::guardians::GuardianQueries;
+
+        let guardian_id = {
+            let sessions = self.active_sessions.read().await;
+            match sessions.get(token) {
+                Some(session_token) if session_token.is_valid() => {
+                    session_token.guardian_id().to_string()
+                }
+                _ => return Ok(None),
+            }
+        };
+
+        if guardian_id == Guardian::primary().id {
+            return Ok(Some(Guardian::primary()));
+        }
+        if let Some(username) = guardian_id.strip_prefix(Guardian::LOCAL_PREFIX) {
+            return Ok(Some(Guardian::local(username)));
+        }
+
+        // Looked up on every call so role changes and removals apply at once
+        GuardianQueries::get_by_id(&self._db, &guardian_id)
+            .await?
+            .map(Self::guardian_from_row)
+            .transpose()
+    }
+
+    /// Check a token's role allows an operation and return its guardian.
+    /// Denials are written to the audit log under the attempted action.
+    async fn authorize(
+        &self,
+        token: &str,
+        permission: Permission,
+        action: &str,
+    ) -> Result<Guardian> {
+        let Some(guardian) = self.session_guardian(token).await? else {
+            self.log_denied("unknown", action, None, "Invalid session token").await;
+            return Err(DaemonError::NotAuthorized(
+                "Unauthorized: Invalid session token".to_string(),
+            )
+            .into());
+        };
+
+        if !guardian.role.allows(permission) {
+            let reason =
+                format!("Role {} lacks {:?} permission", guardian.role.as_str(), permission);
+            self.log_denied(&guardian.actor(), action, None, &reason).await;
+            return Err(DaemonError::NotAuthorized(format!("Unauthorized: {}", reason)).into());
+        }
+
+        Ok(guardian)
+    }
+
+    /// Check a guardian may act on a profile, or on everything when `profile_id` is None
+    async fn authorize_scope(
+        &self,
+        guardian: &Guardian,
+        profile_id: Option<&str>,
+        action: &str,
+    ) -> Result<()> {
+        let allowed = match profile_id {
+            Some(profile_id) => guardian.can_access_profile(profile_id),
+            None => guardian.is_unscoped(),
+        };
+        if allowed {
+            return Ok(());
+        }
+
+        let reason = match profile_id {
+            Some(profile_id) => {
+                format!("Guardian {} has no access to profile {}", guardian.name, profile_id)
+            }
+            None => format!("Guardian {} is limited to specific profiles", guardian.name),
+        };
+        self.log_denied(&guardian.actor(), action, profile_id, &reason).await;
+        Err(DaemonError::NotAuthorized(format!("Unauthorized: {}", reason)).into())
+    }
+
+    /// Check a session token may perform an operation that takes no token of its
+    /// own, on one profile or on everything when `profile_id` is None
+    pub async fn authorize_session(
+        &self,
+        token: &str,
+        permission: Permission,
+        action: &str,
+        profile_id: Option<&str>,
+    ) -> Result<Guardian> {
+        let guardian = self.authorize(token, permission, action).await?;
+        if profile_id.is_some() {
+            self.authorize_scope(&guardian, profile_id, action).await?;
+        }
+        Ok(guardian)
+    }
+
+    /// Profile an approval request was made for, if the request exists
+    pub async fn approval_request_profile(&self, request_id: &str) -> Result<Option<String>> {
+        use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
+
+        Ok(ApprovalRequestQueries::get_by_id(&self._db, request_id)
+            .await?
+            .map(|request| request.profile_id))
+    }
+
+    /// Record a refused operation in the audit log
+    pub async fn log_denied(
+        &self,
+        actor: &str,
+        action: &str,
+        resource_id: Option<&str>,
+        reason: &str,
+    ) {
+        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
+
+        warn!("Denied {} for {}: {}", action, actor, reason);
+
+        let audit = NewAuditLog {
+            actor: actor.to_string(),
+            action: action.to_string(),
+            resource: "authorization".to_string(),
+            resource_id: resource_id.map(str::to_string),
+            ip_address: None,
+            success: false,
+            details: Some(reason.to_string()),
+        };
+        let _ = AuditQueries::log(&self._db, audit, trusted_clock::now()).await;
+    }
+
+    fn guardian_from_row(row: GuardianRow) -> Result<Guardian> {
+        let role = GuardianRole::parse(&row.role)
+            .ok_or_else(|| anyhow!("Unknown guardian role: {}", row.role))?;
+
+        Ok(Guardian { id: row.id, name: row.name, role, profile_ids: row.profile_ids })
+    }
+
+    /// Create a guardian account (admin only)
+    pub async fn add_guardian(
+        &self,
+        name: &str,
+        password: &str,
+        role: &str,
+        profile_ids: &[String],
+        token: &str,
+    ) -> Result<String> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, guardians::GuardianQueries},
+        };
+
+        let guardian = self.authorize(token, Permission::Manage, "add_guardian").await?;
+        self.authorize_scope(&guardian, None, "add_guardian").await?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(
+                DaemonError::InvalidArgument("Guardian name cannot be empty".to_string()).into()
+            );
+        }
+        if name == Guardian::PRIMARY_NAME {
+            return Err(DaemonError::InvalidArgument(format!(
+                "'{}' is reserved for the shared parent password",
+                name
+            ))
+            .into());
+        }
+        if password.is_empty() {
+            return Err(DaemonError::InvalidArgument("Password cannot be empty".to_string()).into());
+        }
+        let role = GuardianRole::parse(role).ok_or_else(|| {
+            DaemonError::InvalidArgument(format!(
+                "Invalid role '{}'. Must be one of: admin, approver, viewer",
+                role
+            ))
+        })?;
+
+        // Profiles may be given by ID or name
+        let mut scope = Vec::with_capacity(profile_ids.len());
+        for profile_id in profile_ids {
+            let profile = self.find_profile(profile_id).await?;
+            scope.push(profile.id);
+        }
+
+        let password_secret = SecretString::new(password.to_string().into());
+        let password_hash = PasswordManager::hash_password(&password_secret)
+            .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
+
+        let guardian_id = Uuid::new_v4().to_string();
+        GuardianQueries::create(
+            &self._db,
+            &guardian_id,
+            name,
+            &password_hash,
+            role.as_str(),
+            &scope,
+            &guardian.name,
+        )
+        .await?;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "add_guardian".to_string(),
+            resource: "guardian".to_string(),
+            resource_id: Some(guardian_id.clone()),
+            ip_address: None,
+            success: true,
+            details: Some(format!(
+                "Name: {}, Role: {}, Profiles: {}",
+                name,
+                role.as_str(),
+                if scope.is_empty() { "all".to_string() } else { scope.join(",") }
+            )),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        info!("Added {} guardian {}", role.as_str(), name);
+        Ok(guardian_id)
+    }
+
+    /// Remove a guardian account and end its sessions (admin only)
+    pub async fn remove_guardian(&self, name: &str, token: &str) -> Result<()> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, guardians::GuardianQueries},
+        };
+
+        let guardian = self.authorize(token, Permission::Manage, "remove_guardian").await?;
+        self.authorize_scope(&guardian, None, "remove_guardian").await?;
+
+        let removed = GuardianQueries::get_by_name(&self._db, name)
+            .await?
+            .ok_or_else(|| DaemonError::NotFound(format!("Guardian not found: {}", name)))?;
+        GuardianQueries::delete_by_name(&self._db, name).await?;
+
+        self.active_sessions.write().await.retain(|_, session| session.guardian_id() != removed.id);
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "remove_guardian".to_string(),
+            resource: "guardian".to_string(),
+            resource_id: Some(removed.id),
+            ip_address: None,
+            success: true,
+            details: Some(format!("Name: {}", name)),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        info!("Removed guardian {}", name);
+        Ok(())
+    }
+
+    /// List guardian accounts, including the shared parent account (admin only)
+    pub async fn list_guardians(&self, token: &str) -> Result<Vec<Guardian>> {
+        use dots_family_db::queries::guardians::GuardianQueries;
+
+        let guardian = self.authorize(token, Permission::Manage, "list_guardians").await?;
+        self.authorize_scope(&guardian, None, "list_guardians").await?;
+
+        let mut guardians = vec![Guardian::primary()];
+        for row in GuardianQueries::list_all(&self._db).await? {
+            guardians.push(Self::guardian_from_row(row)?);
         }
+        Ok(guardians)
     }
 
     /// Clean up expired session tokens
@@ -534,7 +1214,7 @@ impl ProfileManager {
     #[allow(dead_code)] // Will be used by CLI/GUI applications
     pub async fn set_parent_password(&mut self, password: &str) -> Result<()> {
         if password.is_empty() {
-            return Err(anyhow!("Password cannot be empty"));
+            return Err(DaemonError::InvalidArgument("Password cannot be empty".to_string()).into());
         }
 
         let password_secret = SecretString::new(password.to_string().into());
@@ -543,11 +1223,11 @@ impl ProfileManager {
         let password_hash = PasswordManager::hash_password(&password_secret)
             .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
 
-        // Derive encryption key from password
-        let encryption_key = EncryptionKey::derive_from_password(&password_secret, None);
+        if self.config.database.encrypt {
+            self.rotate_database_key(password_secret).await?;
+        }
 
         self.config.auth.parent_password_hash = Some(password_hash);
-        self.config.database.encryption_key = Some(encryption_key.as_sqlcipher_key());
 
         // Save configuration to disk
         if let Err(e) = self.config.save() {
@@ -556,18 +1236,60 @@ impl ProfileManager {
         }
 
         info!("Parent password set successfully - configuration saved to disk");
-        warn!("Database encryption key derived but requires daemon restart to apply");
 
         Ok(())
     }
 
-    #[allow(dead_code)] // Will be used by CLI/GUI applications
-    pub fn get_database_encryption_key(&self) -> Option<String> {
-        self.config.database.encryption_key.clone()
-    }
+    /// Re-key the database for a new parent password under a fresh salt
+    async fn rotate_database_key(&self, password: SecretString) -> Result<()> {
+        let keys = DatabaseKeys::new(&self.config.database);
+        let salt = DatabaseKeys::new_salt();
+        let derive_salt = salt.clone();
+        let key = tokio::task::spawn_blocking(move || {
+            DatabaseKeys::derive_with_salt(&password, &derive_salt)
+        })
+        .await?;
 
-    pub async fn send_heartbeat(&self, monitor_id: &str) -> Result<()> {
-        let mut heartbeats = self.monitor_heartbeats.write().await;
+        // An unencrypted database picks the new key up on the next start
+        if Database::is_plaintext(&self.config.database.path)? {
+            keys.save_salt(&salt)?;
+            keys.cache_key(&key)?;
+            return Ok(());
+        }
+
+        let old_key = keys.cached_key()?;
+        self._db.rekey(&key.as_sqlcipher_key()).await?;
+
+        if let Err(e) = keys.save_salt(&salt).and_then(|_| keys.cache_key(&key)) {
+            // Without the new key cached the database could not be opened again
+            if let Some(old_key) = old_key {
+                self._db.rekey(&old_key).await?;
+            }
+            return Err(e);
+        }
+
+        info!("Database re-encrypted for the new parent password");
+        Ok(())
+    }
+
+    /// Derive and cache the database key the first time a parent signs in
+    async fn ensure_database_key(&self, password: SecretString) -> Result<()> {
+        let keys = DatabaseKeys::new(&self.config.database);
+        if keys.cached_key()?.is_some() {
+            return Ok(());
+        }
+
+        // PBKDF2 takes a while, keep it off the async workers
+        let salted = DatabaseKeys::new(&self.config.database);
+        let key = tokio::task::spawn_blocking(move || salted.derive(&password)).await??;
+        keys.cache_key(&key)?;
+
+        info!("Database key derived - the database is encrypted on the next daemon start");
+        Ok(())
+    }
+
+    pub async fn send_heartbeat(&self, monitor_id: &str) -> Result<()> {
+        let mut heartbeats = self.monitor_heartbeats.write().await;
 
         let heartbeat =
             MonitorHeartbeat { monitor_id: monitor_id.to_string(), last_seen: Instant::now() };
@@ -583,7 +1305,6 @@ impl ProfileManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn check_monitor_health(&self) -> Result<bool> {
         let heartbeats = self.monitor_heartbeats.read().await;
 
@@ -614,38 +1335,101 @@ impl ProfileManager {
         *self.tamper_detected.read().await
     }
 
+    /// Write a detected tamper attempt to the audit log
+    pub async fn record_tamper(&self, event: &crate::tamper_detector::TamperEvent) -> Result<()> {
+        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
+
+        *self.tamper_detected.write().await = true;
+
+        let audit = NewAuditLog {
+            actor: "system".to_string(),
+            action: "tamper_detected".to_string(),
+            resource: if event.child.is_some() { "profile" } else { "system" }.to_string(),
+            resource_id: event.child.as_ref().map(|child| child.profile_id.clone()),
+            ip_address: None,
+            success: true,
+            details: Some(event.reason()),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        Ok(())
+    }
+
+    /// Seal the audit log chain head with the daemon's key
+    pub async fn seal_audit_log(&self, sealer: &AuditSealer) -> Result<()> {
+        if let Some(seal) = sealer.seal(&self._db).await? {
+            debug!("Sealed audit log at entry {}", seal.entry_id);
+        }
+        Ok(())
+    }
+
+    /// Check the audit log for removed or altered entries
+    pub async fn verify_audit_log(
+        &self,
+        token: &str,
+        sealer: &AuditSealer,
+    ) -> Result<dots_family_db::queries::audit::AuditChainReport> {
+        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
+
+        let guardian = self.authorize(token, Permission::View, "verify_audit_log").await?;
+        self.authorize_scope(&guardian, None, "verify_audit_log").await?;
+
+        let report = sealer.verify(&self._db).await?;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "verify_audit_log".to_string(),
+            resource: "audit_log".to_string(),
+            resource_id: None,
+            ip_address: None,
+            success: report.is_intact(),
+            details: report.first_break().map(|b| b.reason.clone()),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        if !report.is_intact() {
+            warn!("Audit log verification failed: {} break(s)", report.breaks.len());
+        }
+        Ok(report)
+    }
+
+    /// Acknowledge tampering, lifting any deny-all response
+    pub async fn clear_tamper(&self, token: &str) -> Result<()> {
+        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
+
+        let guardian = self.authorize(token, Permission::Manage, "clear_tamper").await?;
+        self.authorize_scope(&guardian, None, "clear_tamper").await?;
+
+        *self.tamper_detected.write().await = false;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "clear_tamper".to_string(),
+            resource: "system".to_string(),
+            resource_id: None,
+            ip_address: None,
+            success: true,
+            details: None,
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        info!("Tamper alert cleared by {}", guardian.name);
+        Ok(())
+    }
+
     pub async fn request_parent_permission(
         &self,
         request_type: &str,
         details: &str,
         token: &str,
     ) -> Result<String> {
-        self.ensure_valid_session(token).await?;
+        self.authorize(token, Permission::View, "request_permission").await?;
 
         let approval_id = self.log_permission_request(request_type, details).await?;
 
         self.create_pending_approval_response(approval_id, request_type, details)
     }
 
-    async fn ensure_valid_session(&self, token: &str) -> Result<()> {
-        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
-
-        if !self.validate_session(token).await {
-            let audit = NewAuditLog {
-                actor: "child".to_string(),
-                action: "request_permission".to_string(),
-                resource: "permission".to_string(),
-                resource_id: None,
-                ip_address: None,
-                success: false,
-                details: Some("Invalid session token".to_string()),
-            };
-            let _ = AuditQueries::log(&self._db, audit).await;
-            return Err(anyhow!("Unauthorized: Invalid session token"));
-        }
-        Ok(())
-    }
-
     async fn log_permission_request(&self, request_type: &str, details: &str) -> Result<String> {
         use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
 
@@ -660,7 +1444,7 @@ impl ProfileManager {
             success: true,
             details: Some(format!("Type: {}, Details: {}", request_type, details)),
         };
-        AuditQueries::log(&self._db, audit).await?;
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
 
         Ok(approval_id)
     }
@@ -682,15 +1466,34 @@ impl ProfileManager {
         Ok(response.to_string())
     }
 
+    /// Record a child's request to run a risky terminal command
     pub async fn request_command_approval(
         &self,
+        profile_id: &str,
         command: &str,
         risk_level: &str,
         reasons: &str,
     ) -> Result<String> {
-        use dots_family_db::{models::NewAuditLog, queries::audit::AuditQueries};
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{approval_requests::ApprovalRequestQueries, audit::AuditQueries},
+        };
 
-        let approval_id = Uuid::new_v4().to_string();
+        // Persist the request so a parent can review it and the outcome can be
+        // signalled back to the waiting terminal
+        let details = serde_json::json!({
+            "command": command,
+            "risk_level": risk_level,
+            "reasons": reasons,
+        });
+        let approval_id = ApprovalRequestQueries::create(
+            &self._db,
+            profile_id,
+            "command",
+            &details,
+            trusted_clock::now(),
+        )
+        .await?;
 
         let audit = NewAuditLog {
             actor: "child".to_string(),
@@ -704,7 +1507,7 @@ impl ProfileManager {
                 command, risk_level, reasons
             )),
         };
-        AuditQueries::log(&self._db, audit).await?;
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
 
         let response = serde_json::json!({
             "approval_id": approval_id,
@@ -731,18 +1534,21 @@ impl ProfileManager {
         token: &str,
     ) -> Result<String> {
         use chrono::Duration;
-        use dots_family_common::types::{Exception, ExceptionDuration, ExceptionType};
+        use dots_family_common::types::{Exception, ExceptionType};
         use serde_json;
 
-        // Verify parent authentication
-        self.ensure_valid_session(token).await?;
+        let guardian = self.authorize(token, Permission::Approve, "create_exception").await?;
 
-        let active_profile =
-            self.get_active_profile().await?.ok_or_else(|| anyhow!("No active profile"))?;
+        let active_profile = self
+            .get_active_profile()
+            .await?
+            .ok_or_else(|| DaemonError::NotFound("No active profile".to_string()))?;
+        self.authorize_scope(&guardian, Some(&active_profile.id.to_string()), "create_exception")
+            .await?;
 
-        // Parse duration from JSON
-        let duration: ExceptionDuration = serde_json::from_str(duration_json)
-            .map_err(|e| anyhow!("Invalid duration JSON: {}", e))?;
+        let duration = payload::decode::<payload::ExceptionTerm>(duration_json)
+            .map_err(|e| DaemonError::InvalidArgument(format!("Invalid duration: {}", e)))?
+            .duration;
 
         // Parse exception type
         let exception_type_enum: ExceptionType = match exception_type {
@@ -764,7 +1570,13 @@ impl ProfileManager {
                     extra_minutes: data["extra_minutes"].as_u64().unwrap_or(30) as u32,
                 }
             }
-            _ => return Err(anyhow!("Unknown exception type: {}", exception_type)),
+            _ => {
+                return Err(DaemonError::InvalidArgument(format!(
+                    "Unknown exception type: {}",
+                    exception_type
+                ))
+                .into())
+            }
         };
 
         // Create exception
@@ -773,18 +1585,19 @@ impl ProfileManager {
             exception_type_enum,
             reason.to_string(),
             duration,
-            "parent".to_string(),
+            guardian.name.clone(),
         );
 
+        // Expiry follows the trusted clock, not the system clock
+        let now = trusted_clock::now();
+
         // Convert to database format and store
         let db_exception = dots_family_db::models::NewException {
             id: exception.id.to_string(),
             profile_id: exception.profile_id.to_string(),
             exception_type: exception_type.to_string(),
             granted_by: exception.created_by.clone(),
-            expires_at: exception
-                .expires_at
-                .unwrap_or_else(|| chrono::Utc::now() + Duration::hours(1)),
+            expires_at: exception.duration.expires_at(now).unwrap_or(now + Duration::hours(1)),
             reason: Some(exception.reason),
             amount_minutes: None,
             app_id: None,
@@ -792,7 +1605,7 @@ impl ProfileManager {
             scope: None,
         };
 
-        dots_family_db::queries::exceptions::ExceptionQueries::create(&self._db, db_exception)
+        dots_family_db::queries::exceptions::ExceptionQueries::create(&self._db, db_exception, now)
             .await?;
 
         Ok(exception.id.to_string())
@@ -804,11 +1617,14 @@ impl ProfileManager {
         profile_id: &str,
         token: &str,
     ) -> Result<Vec<dots_family_common::types::Exception>> {
-        self.ensure_valid_session(token).await?;
+        let guardian = self.authorize(token, Permission::View, "list_exceptions").await?;
+        self.authorize_scope(&guardian, Some(profile_id), "list_exceptions").await?;
 
         let db_exceptions =
             dots_family_db::queries::exceptions::ExceptionQueries::list_active_for_profile(
-                &self._db, profile_id,
+                &self._db,
+                profile_id,
+                trusted_clock::now(),
             )
             .await?;
 
@@ -842,13 +1658,32 @@ impl ProfileManager {
 
     /// Revoke an active exception
     pub async fn revoke_exception(&self, exception_id: &str, token: &str) -> Result<()> {
-        self.ensure_valid_session(token).await?;
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, exceptions::ExceptionQueries},
+        };
+
+        let guardian = self.authorize(token, Permission::Approve, "revoke_exception").await?;
+
+        let exception =
+            ExceptionQueries::get_by_id(&self._db, exception_id).await?.ok_or_else(|| {
+                DaemonError::NotFound(format!("Exception not found: {}", exception_id))
+            })?;
+        self.authorize_scope(&guardian, Some(&exception.profile_id), "revoke_exception").await?;
+
+        ExceptionQueries::revoke_exception(&self._db, exception_id).await?;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "revoke_exception".to_string(),
+            resource: "exception".to_string(),
+            resource_id: Some(exception_id.to_string()),
+            ip_address: None,
+            success: true,
+            details: None,
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
 
-        dots_family_db::queries::exceptions::ExceptionQueries::revoke_exception(
-            &self._db,
-            exception_id,
-        )
-        .await?;
         Ok(())
     }
 
@@ -858,8 +1693,10 @@ impl ProfileManager {
         exception_type: &str,
         resource_id: &str,
     ) -> Result<bool> {
-        let active_profile =
-            self.get_active_profile().await?.ok_or_else(|| anyhow!("No active profile"))?;
+        let active_profile = self
+            .get_active_profile()
+            .await?
+            .ok_or_else(|| DaemonError::NotFound("No active profile".to_string()))?;
 
         let exception =
             dots_family_db::queries::exceptions::ExceptionQueries::check_active_exception(
@@ -867,6 +1704,7 @@ impl ProfileManager {
                 &active_profile.id.to_string(),
                 exception_type,
                 Some(resource_id),
+                trusted_clock::now(),
             )
             .await?;
 
@@ -877,33 +1715,112 @@ impl ProfileManager {
     // Approval Request Methods
     // ============================================================================
 
-    /// Submit a new approval request from child
+    /// Submit a new approval request from the child with `profile_id`.
+    ///
+    /// Auto-approval rules are evaluated first; a matching rule resolves the
+    /// request immediately, otherwise it stays pending and parents are notified.
     pub async fn submit_approval_request(
         &self,
+        profile_id: &str,
         request_type: &str,
         _message: &str,
         details_json: &str,
-    ) -> Result<String> {
-        use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
+    ) -> Result<payload::ApprovalSubmission> {
+        use dots_family_common::RuleAction;
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{approval_requests::ApprovalRequestQueries, audit::AuditQueries},
+        };
         use serde_json::Value;
 
-        let active_profile =
-            self.get_active_profile().await?.ok_or_else(|| anyhow!("No active profile"))?;
+        let profile = self._load_profile(profile_id).await?;
+
+        let details = payload::decode::<payload::ApprovalDetails>(details_json)
+            .map_err(|e| DaemonError::InvalidArgument(format!("Invalid details: {}", e)))?;
+        let details: Value = serde_json::to_value(details)?;
 
-        let details: Value = serde_json::from_str(details_json)
-            .map_err(|e| anyhow!("Invalid details JSON: {}", e))?;
+        if request_type == "time_credit" {
+            self.ensure_time_credit_available(&profile.id.to_string(), &details).await?;
+        }
 
         let request_id = ApprovalRequestQueries::create(
             &self._db,
-            &active_profile.id.to_string(),
+            &profile.id.to_string(),
             request_type,
             &details,
+            trusted_clock::now(),
         )
         .await?;
 
+        let decision =
+            match self.evaluate_auto_approval_rules(&profile, request_type, &details).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    warn!("Failed to evaluate auto-approval rules: {}", e);
+                    None
+                }
+            };
+
+        if let Some(decision) = decision {
+            let (status, action) = match decision.action {
+                RuleAction::AutoApprove => ("auto_approved", "auto_approve_request"),
+                RuleAction::AutoDeny => ("denied", "auto_deny_request"),
+            };
+            let rule_id = decision.rule_id.to_string();
+
+            ApprovalRequestQueries::auto_resolve(
+                &self._db,
+                &request_id,
+                status,
+                &rule_id,
+                &decision.explanation,
+                trusted_clock::now(),
+            )
+            .await?;
+
+            let audit = NewAuditLog {
+                actor: "system".to_string(),
+                action: action.to_string(),
+                resource: "approval_request".to_string(),
+                resource_id: Some(request_id.clone()),
+                ip_address: None,
+                success: true,
+                details: Some(
+                    serde_json::json!({
+                        "rule_id": rule_id,
+                        "request_type": request_type,
+                        "explanation": decision.explanation,
+                    })
+                    .to_string(),
+                ),
+            };
+            AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+            let exception_id = match decision.action {
+                RuleAction::AutoApprove => {
+                    let request = ApprovalRequestQueries::get_by_id(&self._db, &request_id)
+                        .await?
+                        .ok_or_else(|| {
+                            DaemonError::NotFound("Approval request not found".to_string())
+                        })?;
+                    self.fulfil_approved_request(&request, "auto", &decision.explanation).await?
+                }
+                RuleAction::AutoDeny => None,
+            };
+
+            info!("Approval request {} resolved by rule: {}", request_id, decision.explanation);
+
+            return Ok(payload::ApprovalSubmission {
+                request_id,
+                request_status: status.to_string(),
+                explanation: Some(decision.explanation),
+                exception_id,
+            });
+        }
+
         let notification = NotificationManager::create_approval_request_notification(
             uuid::Uuid::parse_str(&request_id).unwrap_or_default(),
-            &active_profile.name,
+            &profile.name,
             &format!("{} request", request_type),
         );
 
@@ -911,24 +1828,142 @@ impl ProfileManager {
             warn!("Failed to send approval request notification: {}", e);
         }
 
-        Ok(request_id)
+        Ok(payload::ApprovalSubmission {
+            request_id,
+            request_status: "pending".to_string(),
+            explanation: None,
+            exception_id: None,
+        })
+    }
+
+    /// Find the auto-approval rule decision for a new request, if any rule matches
+    async fn evaluate_auto_approval_rules(
+        &self,
+        profile: &Profile,
+        request_type: &str,
+        details: &serde_json::Value,
+    ) -> Result<Option<dots_family_common::RuleDecision>> {
+        use dots_family_common::{auto_approval::evaluate_rules, RuleCondition, RuleContext};
+        use dots_family_db::queries::{
+            approval_requests::ApprovalRequestQueries, auto_approval_rules::AutoApprovalRuleQueries,
+        };
+
+        let profile_id = profile.id.to_string();
+        let rows = AutoApprovalRuleQueries::list_for_profile(&self._db, &profile_id).await?;
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let rules: Vec<_> = rows
+            .into_iter()
+            .filter_map(|row| match Self::rule_from_row(row) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    warn!("Skipping invalid auto-approval rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        // Requests whose details we cannot interpret are always left for a parent
+        let request = match self.parse_request_type_from_db(request_type, details) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Not evaluating auto-approval rules: {}", e);
+                return Ok(None);
+            }
+        };
+
+        let now = trusted_clock::now_local();
+        let (start_of_day, _) = Self::local_day_bounds(now.date_naive());
+
+        let mut fired_today = HashMap::new();
+        for rule in &rules {
+            if rule.conditions.iter().any(|c| matches!(c, RuleCondition::MaxPerDay { .. })) {
+                let count = ApprovalRequestQueries::count_auto_resolved_since(
+                    &self._db,
+                    &rule.id.to_string(),
+                    &profile_id,
+                    start_of_day,
+                )
+                .await?;
+                fired_today.insert(rule.id, count);
+            }
+        }
+
+        // Children label their own requests; only our own catalogue decides
+        let category = self.request_category(&request).await?;
+        let context = RuleContext { now, category, fired_today };
+
+        Ok(evaluate_rules(&rules, &request, &context))
+    }
+
+    /// Category of the requested app or site from the app cache and filter
+    /// lists. Apps missing from the cache are looked up in the installed
+    /// desktop entries and cached.
+    async fn request_category(
+        &self,
+        request: &dots_family_common::types::RequestType,
+    ) -> Result<Option<String>> {
+        use dots_family_common::types::RequestType;
+        use dots_family_db::queries::{app_info_cache, filter_rules::FilterRuleQueries};
+
+        match request {
+            RequestType::ApplicationAccess { app_id } => {
+                let pool = self._db.pool()?;
+                if let Some(entry) = app_info_cache::get_app_cache_entry(pool, app_id).await? {
+                    return Ok(entry.category);
+                }
+
+                let catalog = AppCatalog::new(&self.config.approvals.application_dirs);
+                let Some(entry) = catalog.lookup(app_id) else {
+                    return Ok(None);
+                };
+                app_info_cache::upsert_app_cache_entry(pool, &entry).await?;
+                Ok(entry.category)
+            }
+            RequestType::WebsiteAccess { domain, .. } => {
+                FilterRuleQueries::category_for_domain(&self._db, domain).await
+            }
+            _ => Ok(None),
+        }
     }
 
     /// List pending approval requests (for parent)
     pub async fn list_pending_requests(
         &self,
         token: &str,
-    ) -> Result<Vec<dots_family_db::queries::approval_requests::ApprovalRequest>> {
+    ) -> Result<Vec<payload::ApprovalRequest>> {
         use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
 
-        self.ensure_valid_session(token).await?;
+        let guardian = self.authorize(token, Permission::View, "list_pending_requests").await?;
 
-        let active_profile =
-            self.get_active_profile().await?.ok_or_else(|| anyhow!("No active profile"))?;
+        // Every child the guardian looks after, not only the active one
+        let mut pending = Vec::new();
+        for profile in self.list_profiles().await? {
+            let profile_id = profile.id.to_string();
+            if guardian.can_access_profile(&profile_id) {
+                pending.extend(ApprovalRequestQueries::list_pending(&self._db, &profile_id).await?);
+            }
+        }
+        pending.sort_by_key(|request| std::cmp::Reverse(request.requested_at));
 
-        let requests =
-            ApprovalRequestQueries::list_pending(&self._db, &active_profile.id.to_string()).await?;
-        Ok(requests)
+        pending
+            .into_iter()
+            .map(|request| {
+                Ok(payload::ApprovalRequest {
+                    details: serde_json::from_value(request.details)?,
+                    id: request.id,
+                    profile_id: request.profile_id,
+                    request_type: request.request_type,
+                    requested_at: request.requested_at,
+                    status: request.status,
+                    reviewed_by: request.reviewed_by,
+                    reviewed_at: request.reviewed_at,
+                    response_reason: request.response_reason,
+                })
+            })
+            .collect()
     }
 
     /// Approve an approval request and create corresponding exception
@@ -938,28 +1973,73 @@ impl ProfileManager {
         response_message: &str,
         token: &str,
     ) -> Result<Option<String>> {
-        use chrono::Utc;
-        use dots_family_db::{
-            models::NewException,
-            queries::{approval_requests::ApprovalRequestQueries, exceptions::ExceptionQueries},
-        };
+        use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
 
-        self.ensure_valid_session(token).await?;
+        let guardian = self.authorize(token, Permission::Approve, "approve_request").await?;
 
         // Get the approval request details before marking it as approved
         let request = ApprovalRequestQueries::get_by_id(&self._db, request_id)
             .await?
-            .ok_or_else(|| anyhow!("Approval request not found"))?;
+            .ok_or_else(|| DaemonError::NotFound("Approval request not found".to_string()))?;
+        self.authorize_scope(&guardian, Some(&request.profile_id), "approve_request").await?;
+        if request.status != "pending" {
+            return Err(DaemonError::InvalidArgument(format!(
+                "Approval request is no longer pending ({})",
+                request.status
+            ))
+            .into());
+        }
 
-        // Mark the request as approved
-        ApprovalRequestQueries::review_request(
+        // Claim the request first so a racing expiry cannot leave a grant behind
+        let claimed = ApprovalRequestQueries::review_request(
             &self._db,
             request_id,
             "approved",
-            "parent",
+            &guardian.name,
             Some(response_message),
+            trusted_clock::now(),
         )
         .await?;
+        if !claimed {
+            return Err(DaemonError::InvalidArgument(
+                "Approval request is no longer pending".to_string(),
+            )
+            .into());
+        }
+
+        match self.fulfil_approved_request(&request, &guardian.name, response_message).await {
+            Ok(exception_id) => Ok(exception_id),
+            Err(e) => {
+                // A failed grant leaves the request for another try
+                ApprovalRequestQueries::reopen(&self._db, request_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Grant what an approved request asked for: banked time or an exception
+    async fn fulfil_approved_request(
+        &self,
+        request: &dots_family_db::queries::approval_requests::ApprovalRequest,
+        granted_by: &str,
+        reason: &str,
+    ) -> Result<Option<String>> {
+        if request.request_type == "time_credit" {
+            self.spend_time_credit(request).await?;
+            return Ok(None);
+        }
+
+        self.grant_exception_for_request(request, granted_by, reason).await.map(Some)
+    }
+
+    /// Create the exception that an approved request grants
+    async fn grant_exception_for_request(
+        &self,
+        request: &dots_family_db::queries::approval_requests::ApprovalRequest,
+        granted_by: &str,
+        reason: &str,
+    ) -> Result<String> {
+        use dots_family_db::{models::NewException, queries::exceptions::ExceptionQueries};
 
         // Parse the request type from the stored string and details
         let request_type =
@@ -972,16 +2052,9 @@ impl ProfileManager {
         let duration = request_type.default_exception_duration();
 
         // Calculate expiration time based on duration
-        let expires_at = match duration {
-            dots_family_common::types::ExceptionDuration::Duration(d) => Utc::now() + d,
-            dots_family_common::types::ExceptionDuration::UntilTime(t) => t,
-            dots_family_common::types::ExceptionDuration::UntilEndOfDay => {
-                let end_of_day = Utc::now().date_naive().and_hms_opt(23, 59, 59).unwrap();
-                chrono::DateTime::from_naive_utc_and_offset(end_of_day, Utc)
-            }
-            // For session-based or manual exceptions, set a far future date
-            _ => Utc::now() + chrono::Duration::days(365),
-        };
+        // For session-based or manual exceptions, set a far future date
+        let now = trusted_clock::now();
+        let expires_at = duration.expires_at(now).unwrap_or(now + chrono::Duration::days(365));
 
         // Create the exception in the database
         let exception_id = uuid::Uuid::new_v4().to_string();
@@ -1014,16 +2087,16 @@ impl ProfileManager {
             id: exception_id.clone(),
             profile_id: request.profile_id.clone(),
             exception_type: db_exception_type.clone(),
-            granted_by: "parent".to_string(),
+            granted_by: granted_by.to_string(),
             expires_at,
-            reason: Some(response_message.to_string()),
+            reason: Some(reason.to_string()),
             amount_minutes,
             app_id,
             website,
             scope: None,
         };
 
-        ExceptionQueries::create(&self._db, new_exception).await?;
+        ExceptionQueries::create(&self._db, new_exception, trusted_clock::now()).await?;
 
         // Send notification about exception creation
         let profile_uuid = Uuid::parse_str(&request.profile_id)?;
@@ -1039,7 +2112,7 @@ impl ProfileManager {
             warn!("Failed to send exception creation notification: {}", e);
         }
 
-        Ok(Some(exception_id))
+        Ok(exception_id)
     }
 
     /// Helper to parse request type from database format
@@ -1048,39 +2121,52 @@ impl ProfileManager {
         request_type_str: &str,
         details: &serde_json::Value,
     ) -> Result<dots_family_common::types::RequestType> {
-        use chrono::Utc;
         use dots_family_common::types::RequestType;
 
         match request_type_str {
             "app" => {
                 let app_id = details["app_id"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Missing app_id in request details"))?
+                    .ok_or_else(|| {
+                        DaemonError::InvalidArgument(
+                            "Missing app_id in request details".to_string(),
+                        )
+                    })?
                     .to_string();
                 Ok(RequestType::ApplicationAccess { app_id })
             }
             "website" => {
                 let url = details["url"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Missing url in request details"))?
+                    .ok_or_else(|| {
+                        DaemonError::InvalidArgument("Missing url in request details".to_string())
+                    })?
                     .to_string();
                 let domain = details["domain"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Missing domain in request details"))?
+                    .ok_or_else(|| {
+                        DaemonError::InvalidArgument(
+                            "Missing domain in request details".to_string(),
+                        )
+                    })?
                     .to_string();
                 Ok(RequestType::WebsiteAccess { url, domain })
             }
             "screen_time" => {
-                let requested_minutes = details["requested_minutes"]
-                    .as_u64()
-                    .ok_or_else(|| anyhow!("Missing requested_minutes in request details"))?
-                    as u32;
+                let requested_minutes = details["requested_minutes"].as_u64().ok_or_else(|| {
+                    DaemonError::InvalidArgument(
+                        "Missing requested_minutes in request details".to_string(),
+                    )
+                })? as u32;
                 Ok(RequestType::ScreenTimeExtension { requested_minutes })
             }
             "time_extension" => {
-                let requested_end_time_str = details["requested_end_time"]
-                    .as_str()
-                    .ok_or_else(|| anyhow!("Missing requested_end_time in request details"))?;
+                let requested_end_time_str =
+                    details["requested_end_time"].as_str().ok_or_else(|| {
+                        DaemonError::InvalidArgument(
+                            "Missing requested_end_time in request details".to_string(),
+                        )
+                    })?;
                 let requested_end_time =
                     chrono::DateTime::parse_from_rfc3339(requested_end_time_str)?
                         .with_timezone(&Utc);
@@ -1089,61 +2175,653 @@ impl ProfileManager {
             "command" => {
                 let command = details["command"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Missing command in request details"))?
+                    .ok_or_else(|| {
+                        DaemonError::InvalidArgument(
+                            "Missing command in request details".to_string(),
+                        )
+                    })?
                     .to_string();
                 Ok(RequestType::TerminalCommand { command })
             }
             "custom" => {
                 let description = details["description"]
                     .as_str()
-                    .ok_or_else(|| anyhow!("Missing description in request details"))?
+                    .ok_or_else(|| {
+                        DaemonError::InvalidArgument(
+                            "Missing description in request details".to_string(),
+                        )
+                    })?
                     .to_string();
                 Ok(RequestType::Custom { description })
             }
-            _ => Err(anyhow!("Unknown request type: {}", request_type_str)),
-        }
-    }
+            "time_credit" => {
+                let minutes = details["minutes"].as_u64().ok_or_else(|| {
+                    DaemonError::InvalidArgument("Missing minutes in request details".to_string())
+                })? as u32;
+                Ok(RequestType::TimeCreditSpend { minutes })
+            }
+            _ => Err(DaemonError::InvalidArgument(format!(
+                "Unknown request type: {}",
+                request_type_str
+            ))
+            .into()),
+        }
+    }
+
+    /// Deny an approval request  
+    pub async fn deny_request(
+        &self,
+        request_id: &str,
+        response_message: &str,
+        token: &str,
+    ) -> Result<()> {
+        use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
+
+        let guardian = self.authorize(token, Permission::Approve, "deny_request").await?;
+
+        let request = ApprovalRequestQueries::get_by_id(&self._db, request_id)
+            .await?
+            .ok_or_else(|| DaemonError::NotFound("Approval request not found".to_string()))?;
+        self.authorize_scope(&guardian, Some(&request.profile_id), "deny_request").await?;
+        if request.status != "pending" {
+            return Err(DaemonError::InvalidArgument(format!(
+                "Approval request is no longer pending ({})",
+                request.status
+            ))
+            .into());
+        }
+
+        let denied = ApprovalRequestQueries::review_request(
+            &self._db,
+            request_id,
+            "denied",
+            &guardian.name,
+            Some(response_message),
+            trusted_clock::now(),
+        )
+        .await?;
+        if !denied {
+            return Err(DaemonError::InvalidArgument(
+                "Approval request is no longer pending".to_string(),
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Expire requests that have been pending longer than the configured TTL
+    pub async fn expire_stale_requests(
+        &self,
+    ) -> Result<Vec<dots_family_db::queries::approval_requests::ApprovalRequest>> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{approval_requests::ApprovalRequestQueries, audit::AuditQueries},
+        };
+
+        let expired = ApprovalRequestQueries::expire_pending_older_than(
+            &self._db,
+            self.config.approvals.request_ttl_minutes,
+            trusted_clock::now(),
+        )
+        .await?;
+
+        for request in &expired {
+            info!("Approval request {} expired without a response", request.id);
+
+            let audit = NewAuditLog {
+                actor: "system".to_string(),
+                action: "expire_request".to_string(),
+                resource: "approval_request".to_string(),
+                resource_id: Some(request.id.clone()),
+                ip_address: None,
+                success: true,
+                details: Some(format!(
+                    "Type: {}, TTL: {} minutes",
+                    request.request_type, self.config.approvals.request_ttl_minutes
+                )),
+            };
+            AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+        }
+
+        Ok(expired)
+    }
+
+    // ============================================================================
+    // Auto-Approval Rule Methods
+    // ============================================================================
+
+    /// List all auto-approval rules (for parent)
+    pub async fn list_auto_approval_rules(
+        &self,
+        token: &str,
+    ) -> Result<Vec<dots_family_common::AutoApprovalRule>> {
+        use dots_family_db::queries::auto_approval_rules::AutoApprovalRuleQueries;
+
+        let guardian = self.authorize(token, Permission::View, "list_auto_approval_rules").await?;
+
+        // Scoped guardians see global rules and the rules of their own profiles
+        let rows = AutoApprovalRuleQueries::list_all(&self._db).await?;
+        rows.into_iter()
+            .filter(|row| {
+                row.profile_id.as_deref().is_none_or(|id| guardian.can_access_profile(id))
+            })
+            .map(Self::rule_from_row)
+            .collect()
+    }
+
+    /// Add an auto-approval rule from JSON
+    /// (`{"name", "profile_id"?, "conditions": [...], "action"}`)
+    pub async fn add_auto_approval_rule(&self, rule_json: &str, token: &str) -> Result<String> {
+        use dots_family_common::AutoApprovalRule;
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, auto_approval_rules::AutoApprovalRuleQueries},
+        };
+
+        let guardian =
+            self.authorize(token, Permission::Manage, "create_auto_approval_rule").await?;
+
+        let new_rule = payload::decode::<payload::NewAutoApprovalRule>(rule_json)
+            .map_err(|e| DaemonError::InvalidArgument(format!("Invalid rule: {}", e)))?;
+
+        let rule = AutoApprovalRule::new(
+            new_rule.profile_id,
+            new_rule.name,
+            new_rule.conditions,
+            new_rule.action,
+        );
+        rule.validate()
+            .map_err(|e| DaemonError::InvalidArgument(format!("Invalid rule: {}", e)))?;
+
+        let rule_id = rule.id.to_string();
+        let profile_id = rule.profile_id.map(|id| id.to_string());
+        self.authorize_scope(&guardian, profile_id.as_deref(), "create_auto_approval_rule").await?;
+
+        let action = serde_json::to_value(rule.action)?;
+        AutoApprovalRuleQueries::create(
+            &self._db,
+            &rule_id,
+            profile_id.as_deref(),
+            &rule.name,
+            &serde_json::to_value(&rule.conditions)?,
+            action.as_str().unwrap_or_default(),
+            &guardian.name,
+        )
+        .await?;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "create_auto_approval_rule".to_string(),
+            resource: "auto_approval_rule".to_string(),
+            resource_id: Some(rule_id.clone()),
+            ip_address: None,
+            success: true,
+            details: Some(rule.explain()),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        info!("Added auto-approval rule '{}': {}", rule.name, rule.describe());
+        Ok(rule_id)
+    }
+
+    /// Enable or disable an auto-approval rule
+    pub async fn set_auto_approval_rule_enabled(
+        &self,
+        rule_id: &str,
+        enabled: bool,
+        token: &str,
+    ) -> Result<()> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, auto_approval_rules::AutoApprovalRuleQueries},
+        };
+
+        let guardian = self.authorize(token, Permission::Manage, "set_auto_approval_rule").await?;
+        self.authorize_rule(&guardian, rule_id, "set_auto_approval_rule").await?;
+
+        if !AutoApprovalRuleQueries::set_enabled(&self._db, rule_id, enabled).await? {
+            return Err(DaemonError::NotFound(format!(
+                "Auto-approval rule not found: {}",
+                rule_id
+            ))
+            .into());
+        }
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: if enabled {
+                "enable_auto_approval_rule"
+            } else {
+                "disable_auto_approval_rule"
+            }
+            .to_string(),
+            resource: "auto_approval_rule".to_string(),
+            resource_id: Some(rule_id.to_string()),
+            ip_address: None,
+            success: true,
+            details: None,
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        Ok(())
+    }
+
+    /// Remove an auto-approval rule
+    pub async fn remove_auto_approval_rule(&self, rule_id: &str, token: &str) -> Result<()> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{audit::AuditQueries, auto_approval_rules::AutoApprovalRuleQueries},
+        };
+
+        let guardian =
+            self.authorize(token, Permission::Manage, "remove_auto_approval_rule").await?;
+        self.authorize_rule(&guardian, rule_id, "remove_auto_approval_rule").await?;
+
+        if !AutoApprovalRuleQueries::delete(&self._db, rule_id).await? {
+            return Err(DaemonError::NotFound(format!(
+                "Auto-approval rule not found: {}",
+                rule_id
+            ))
+            .into());
+        }
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "remove_auto_approval_rule".to_string(),
+            resource: "auto_approval_rule".to_string(),
+            resource_id: Some(rule_id.to_string()),
+            ip_address: None,
+            success: true,
+            details: None,
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        Ok(())
+    }
+
+    /// Check a guardian may change a rule; global rules need an unscoped guardian
+    async fn authorize_rule(&self, guardian: &Guardian, rule_id: &str, action: &str) -> Result<()> {
+        use dots_family_db::queries::auto_approval_rules::AutoApprovalRuleQueries;
+
+        let rule =
+            AutoApprovalRuleQueries::get_by_id(&self._db, rule_id).await?.ok_or_else(|| {
+                DaemonError::NotFound(format!("Auto-approval rule not found: {}", rule_id))
+            })?;
+        self.authorize_scope(guardian, rule.profile_id.as_deref(), action).await
+    }
+
+    /// Convert a stored rule row into the domain rule type
+    fn rule_from_row(
+        row: dots_family_db::queries::auto_approval_rules::AutoApprovalRuleRow,
+    ) -> Result<dots_family_common::AutoApprovalRule> {
+        Ok(dots_family_common::AutoApprovalRule {
+            id: Uuid::parse_str(&row.id)?,
+            profile_id: row.profile_id.as_deref().map(Uuid::parse_str).transpose()?,
+            name: row.name,
+            conditions: serde_json::from_value(row.conditions)?,
+            action: serde_json::from_value(serde_json::Value::String(row.action))?,
+            enabled: row.enabled,
+            created_at: row.created_at,
+        })
+    }
+
+    // ============================================================================
+    // Time Bank Methods
+    // ============================================================================
+
+    /// Grant earned minutes to a profile's time bank (parent reward)
+    pub async fn grant_time_credit(
+        &self,
+        profile_id: &str,
+        minutes: u32,
+        reason: &str,
+        token: &str,
+    ) -> Result<String> {
+        use dots_family_db::{
+            models::NewAuditLog,
+            queries::{
+                audit::AuditQueries,
+                time_credits::{NewTimeCredit, TimeCreditQueries},
+            },
+        };
+
+        let guardian = self.authorize(token, Permission::Approve, "grant_time_credit").await?;
+
+        if minutes == 0 || minutes > 24 * 60 {
+            return Err(DaemonError::InvalidArgument(format!(
+                "Minutes must be between 1 and {}",
+                24 * 60
+            ))
+            .into());
+        }
+
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "grant_time_credit").await?;
+
+        let entry = NewTimeCredit {
+            profile_id: profile.id.clone(),
+            entry_type: "earned".to_string(),
+            minutes: minutes as i64,
+            reason: (!reason.is_empty()).then(|| reason.to_string()),
+            source_id: None,
+            expires_at: None,
+            created_by: guardian.name.clone(),
+        };
+        let entry_id = TimeCreditQueries::insert(&self._db, &entry, trusted_clock::now()).await?;
+
+        let audit = NewAuditLog {
+            actor: guardian.actor(),
+            action: "grant_time_credit".to_string(),
+            resource: "time_bank".to_string(),
+            resource_id: Some(entry_id.clone()),
+            ip_address: None,
+            success: true,
+            details: Some(format!(
+                "Profile: {}, Minutes: {}, Reason: {}",
+                profile.name, minutes, reason
+            )),
+        };
+        AuditQueries::log(&self._db, audit, trusted_clock::now()).await?;
+
+        info!("Granted {} banked minutes to profile {}", minutes, profile.name);
+        Ok(entry_id)
+    }
+
+    /// Get a profile's time bank balance and full history (for parent)
+    pub async fn get_time_bank(&self, profile_id: &str, token: &str) -> Result<payload::TimeBank> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        let guardian = self.authorize(token, Permission::View, "get_time_bank").await?;
+
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "get_time_bank").await?;
+
+        let balance_minutes =
+            TimeCreditQueries::balance(&self._db, &profile.id, trusted_clock::now()).await?;
+        let history = TimeCreditQueries::list_for_profile(&self._db, &profile.id)
+            .await?
+            .into_iter()
+            .map(Self::credit_entry_from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(payload::TimeBank { profile_id: profile.id, balance_minutes, history })
+    }
+
+    /// Banked minutes the profile can spend
+    pub async fn get_time_credit_balance(&self, profile_id: &str) -> Result<u32> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        TimeCreditQueries::balance(&self._db, profile_id, trusted_clock::now()).await
+    }
+
+    /// Banked minutes the active profile has spent today
+    pub async fn get_time_credits_spent_today(&self) -> Result<u32> {
+        let Some(profile) = self.get_active_profile().await? else {
+            return Ok(0);
+        };
+
+        self.time_credits_spent_today(&profile.id.to_string()).await
+    }
+
+    async fn time_credits_spent_today(&self, profile_id: &str) -> Result<u32> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        let (today_start, today_end) =
+            Self::local_day_bounds(trusted_clock::now_local().date_naive());
+        TimeCreditQueries::spent_between(&self._db, profile_id, today_start, today_end).await
+    }
+
+    /// Ledger totals for a period, with the current balance
+    pub async fn time_bank_summary(
+        &self,
+        profile_id: &str,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> Result<dots_family_common::TimeBankSummary> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        let entries = TimeCreditQueries::list_between(&self._db, profile_id, start, end)
+            .await?
+            .into_iter()
+            .map(Self::credit_entry_from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let balance =
+            TimeCreditQueries::balance(&self._db, profile_id, trusted_clock::now()).await?;
+
+        Ok(dots_family_common::TimeBankSummary::from_entries(&entries, balance))
+    }
+
+    /// Reject a spend request the profile cannot cover
+    async fn ensure_time_credit_available(
+        &self,
+        profile_id: &str,
+        details: &serde_json::Value,
+    ) -> Result<()> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        let minutes = details["minutes"].as_u64().filter(|m| *m > 0).ok_or_else(|| {
+            DaemonError::InvalidArgument("Missing minutes in request details".to_string())
+        })?;
+        let balance =
+            TimeCreditQueries::balance(&self._db, profile_id, trusted_clock::now()).await?;
+
+        if minutes > balance as u64 {
+            return Err(anyhow!(
+                "Not enough banked time: {} minutes requested, {} available",
+                minutes,
+                balance
+            ));
+        }
+        Ok(())
+    }
+
+    /// Move banked minutes into today's allowance for an approved request
+    async fn spend_time_credit(
+        &self,
+        request: &dots_family_db::queries::approval_requests::ApprovalRequest,
+    ) -> Result<String> {
+        use dots_family_common::time_bank::plan_spend;
+        use dots_family_db::queries::time_credits::{NewTimeCredit, TimeCreditQueries};
+
+        let minutes = request.details["minutes"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Missing minutes in request details"))?
+            as u32;
+
+        let now = trusted_clock::now();
+        let credits = TimeCreditQueries::list_open_credits(&self._db, &request.profile_id, now)
+            .await?
+            .into_iter()
+            .map(Self::credit_entry_from_row)
+            .collect::<Result<Vec<_>>>()?;
+
+        let available: u32 = credits.iter().map(|c| c.remaining_minutes).sum();
+        let draws = plan_spend(&credits, minutes, now).ok_or_else(|| {
+            anyhow!(
+                "Not enough banked time: {} minutes requested, {} available",
+                minutes,
+                available
+            )
+        })?;
+        let draws: Vec<(String, u32)> =
+            draws.into_iter().map(|(id, take)| (id.to_string(), take)).collect();
+
+        let spend = NewTimeCredit {
+            profile_id: request.profile_id.clone(),
+            entry_type: "spent".to_string(),
+            minutes: minutes as i64,
+            reason: Some("Spent on extra screen time".to_string()),
+            source_id: Some(request.id.clone()),
+            expires_at: None,
+            created_by: "child".to_string(),
+        };
+        let entry_id = TimeCreditQueries::spend(&self._db, &spend, &draws, now).await?;
+
+        info!("Profile {} spent {} banked minutes", request.profile_id, minutes);
+        Ok(entry_id)
+    }
+
+    /// Expire lapsed credits and roll over yesterday's unused time.
+    ///
+    /// Safe to call repeatedly; each day rolls over at most once per profile.
+    pub async fn process_time_bank(&self) -> Result<()> {
+        use dots_family_db::queries::TimeCreditQueries;
+
+        for entry in TimeCreditQueries::expire_credits(&self._db, trusted_clock::now()).await? {
+            info!(
+                "Expired {} banked minutes for profile {}",
+                entry.minutes.unsigned_abs(),
+                entry.profile_id
+            );
+        }
+
+        if self.config.time_bank.rollover_enabled {
+            let yesterday = trusted_clock::now_local().date_naive() - chrono::Duration::days(1);
+            self.roll_over_unused_time(yesterday).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Bank each profile's unused minutes from the given day, within the cap.
+    /// Only days the child was allowed screen time and actually logged in count.
+    pub async fn roll_over_unused_time(&self, day: chrono::NaiveDate) -> Result<()> {
+        use chrono::Datelike;
+        use dots_family_common::time_bank::rollover_amount;
+        use dots_family_db::queries::{
+            activities::ActivityQueries,
+            time_credits::{NewTimeCredit, TimeCreditQueries},
+            SessionQueries,
+        };
+
+        let config = &self.config.time_bank;
+        let source_id = day.format("%Y-%m-%d").to_string();
+        let (day_start, day_end) = Self::local_day_bounds(day);
+        let now = trusted_clock::now();
+
+        for profile in self.list_profiles().await? {
+            let profile_id = profile.id.to_string();
+            if TimeCreditQueries::rollover_exists(&self._db, &profile_id, &source_id).await? {
+                continue;
+            }
+
+            let screen_time = &profile.config.screen_time;
+            let weekend = matches!(day.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun);
+            let limit_minutes = if weekend {
+                screen_time.daily_limit_minutes + screen_time.weekend_bonus_minutes
+            } else {
+                screen_time.daily_limit_minutes
+            };
+            if limit_minutes == 0
+                || !SessionQueries::had_session_between(&self._db, &profile_id, day_start, day_end)
+                    .await?
+            {
+                continue;
+            }
 
-    /// Deny an approval request  
-    pub async fn deny_request(
-        &self,
-        request_id: &str,
-        response_message: &str,
-        token: &str,
-    ) -> Result<()> {
-        use dots_family_db::queries::approval_requests::ApprovalRequestQueries;
+            let used_seconds: i64 =
+                ActivityQueries::list_by_profile_since(&self._db, &profile_id, day_start)
+                    .await?
+                    .iter()
+                    .filter(|a| a.timestamp < day_end)
+                    .map(|a| a.duration_seconds)
+                    .sum();
+            let unused_minutes = limit_minutes.saturating_sub((used_seconds / 60) as u32);
+
+            let outstanding: u32 =
+                TimeCreditQueries::list_open_credits(&self._db, &profile_id, now)
+                    .await?
+                    .iter()
+                    .filter(|c| c.entry_type == "rolled_over")
+                    .map(|c| c.remaining_minutes as u32)
+                    .sum();
 
-        self.ensure_valid_session(token).await?;
+            let minutes = rollover_amount(unused_minutes, outstanding, config.rollover_cap_minutes);
+            if minutes == 0 {
+                continue;
+            }
 
-        ApprovalRequestQueries::review_request(
-            &self._db,
-            request_id,
-            "denied",
-            "parent",
-            Some(response_message),
-        )
-        .await?;
+            let entry = NewTimeCredit {
+                profile_id: profile_id.clone(),
+                entry_type: "rolled_over".to_string(),
+                minutes: minutes as i64,
+                reason: Some(format!("Unused time from {}", source_id)),
+                source_id: Some(source_id.clone()),
+                expires_at: Some(now + chrono::Duration::days(config.rollover_expiry_days as i64)),
+                created_by: "system".to_string(),
+            };
+            TimeCreditQueries::insert(&self._db, &entry, now).await?;
+
+            info!("Rolled over {} unused minutes for profile {}", minutes, profile.name);
+        }
 
         Ok(())
     }
 
+    /// Convert a ledger row into the shared entry type
+    fn credit_entry_from_row(
+        row: dots_family_db::queries::time_credits::TimeCreditRow,
+    ) -> Result<dots_family_common::TimeCreditEntry> {
+        Ok(dots_family_common::TimeCreditEntry {
+            id: Uuid::parse_str(&row.id)?,
+            profile_id: Uuid::parse_str(&row.profile_id)?,
+            kind: dots_family_common::CreditEntryKind::parse(&row.entry_type)
+                .ok_or_else(|| anyhow!("Unknown ledger entry type: {}", row.entry_type))?,
+            minutes: row.minutes as i32,
+            remaining_minutes: row.remaining_minutes.max(0) as u32,
+            reason: row.reason,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            created_by: row.created_by,
+        })
+    }
+
+    /// Start and end of a local calendar day, the day the policy engine and
+    /// time windows count in
+    fn local_day_bounds(
+        day: chrono::NaiveDate,
+    ) -> (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>) {
+        let start_of = |day: chrono::NaiveDate| {
+            let midnight = day.and_hms_opt(0, 0, 0).unwrap();
+            // A DST change can skip midnight; the day then starts at the first hour it has
+            (0..3)
+                .find_map(|hour| {
+                    Local
+                        .from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                        .earliest()
+                })
+                .map(|start| start.with_timezone(&Utc))
+                .unwrap_or_else(|| midnight.and_utc())
+        };
+        (start_of(day), start_of(day + chrono::Duration::days(1)))
+    }
+
     pub async fn get_daily_report(
         &self,
         profile_id: &str,
         date_str: &str,
-    ) -> Result<crate::reports::ActivityReport> {
+    ) -> Result<payload::ActivityReport> {
         use chrono::NaiveDate;
         use dots_family_db::queries::daily_summaries::DailySummaryQueries;
 
-        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|e| anyhow!("Invalid date format: {}. Expected YYYY-MM-DD", e))?;
+        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|e| {
+            DaemonError::InvalidArgument(format!("Invalid date format: {}. Expected YYYY-MM-DD", e))
+        })?;
+
+        let (day_start, day_end) = Self::local_day_bounds(date);
+        let time_bank =
+            self.time_bank_summary(profile_id, day_start, day_end).await.unwrap_or_else(|e| {
+                warn!("Failed to load time bank for report: {}", e);
+                Default::default()
+            });
 
         match DailySummaryQueries::get_by_profile_and_date(&self._db, profile_id, date).await {
             Ok(summary) => {
                 let top_apps: Vec<serde_json::Value> =
                     serde_json::from_str(&summary.top_apps).unwrap_or_else(|_| vec![]);
 
-                let apps_used: Vec<crate::reports::AppUsage> = top_apps
+                let apps_used: Vec<payload::AppUsage> = top_apps
                     .into_iter()
                     .filter_map(|app| {
                         if let (Some(app_id), Some(duration)) = (
@@ -1158,7 +2836,7 @@ impl ProfileManager {
                                 0.0
                             };
 
-                            Some(crate::reports::AppUsage {
+                            Some(payload::AppUsage {
                                 app_id: app_id.to_string(),
                                 app_name: app
                                     .get("app_name")
@@ -1197,7 +2875,7 @@ impl ProfileManager {
                     ("No Activity".to_string(), "None".to_string())
                 };
 
-                Ok(crate::reports::ActivityReport {
+                Ok(payload::ActivityReport {
                     date,
                     screen_time_minutes: (summary.screen_time_seconds / 60) as u32,
                     top_activity,
@@ -1205,9 +2883,10 @@ impl ProfileManager {
                     violations: summary.violations_count as u32,
                     blocked_attempts: summary.blocks_count as u32,
                     apps_used,
+                    time_bank,
                 })
             }
-            Err(_) => Ok(crate::reports::ActivityReport {
+            Err(_) => Ok(payload::ActivityReport {
                 date,
                 screen_time_minutes: 0,
                 top_activity: "No Activity".to_string(),
@@ -1215,6 +2894,7 @@ impl ProfileManager {
                 violations: 0,
                 blocked_attempts: 0,
                 apps_used: vec![],
+                time_bank,
             }),
         }
     }
@@ -1223,12 +2903,22 @@ impl ProfileManager {
         &self,
         profile_id: &str,
         week_start_str: &str,
-    ) -> Result<crate::reports::WeeklyReport> {
+    ) -> Result<payload::WeeklyReport> {
         use chrono::NaiveDate;
         use dots_family_db::queries::weekly_summaries::WeeklySummaryQueries;
 
-        let week_start = NaiveDate::parse_from_str(week_start_str, "%Y-%m-%d")
-            .map_err(|e| anyhow!("Invalid date format: {}. Expected YYYY-MM-DD", e))?;
+        let week_start = NaiveDate::parse_from_str(week_start_str, "%Y-%m-%d").map_err(|e| {
+            DaemonError::InvalidArgument(format!("Invalid date format: {}. Expected YYYY-MM-DD", e))
+        })?;
+
+        let (week_start_dt, _) = Self::local_day_bounds(week_start);
+        let time_bank = self
+            .time_bank_summary(profile_id, week_start_dt, week_start_dt + chrono::Duration::days(7))
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to load time bank for report: {}", e);
+                Default::default()
+            });
 
         match WeeklySummaryQueries::get_by_profile_and_week(&self._db, profile_id, week_start).await
         {
@@ -1236,7 +2926,7 @@ impl ProfileManager {
                 let top_categories: Vec<serde_json::Value> =
                     serde_json::from_str(&summary.top_categories).unwrap_or_else(|_| vec![]);
 
-                let category_usage: Vec<crate::reports::CategoryUsage> = top_categories
+                let category_usage: Vec<payload::CategoryUsage> = top_categories
                     .into_iter()
                     .filter_map(|cat| {
                         if let (Some(category), Some(duration)) = (
@@ -1251,7 +2941,7 @@ impl ProfileManager {
                                 0.0
                             };
 
-                            Some(crate::reports::CategoryUsage {
+                            Some(payload::CategoryUsage {
                                 category: category.to_string(),
                                 duration_minutes,
                                 percentage,
@@ -1268,7 +2958,7 @@ impl ProfileManager {
                     .map(|c| c.percentage)
                     .unwrap_or(0.0);
 
-                Ok(crate::reports::WeeklyReport {
+                Ok(payload::WeeklyReport {
                     week_start,
                     total_screen_time_minutes: (summary.total_screen_time_seconds / 60) as u32,
                     average_daily_minutes: (summary.daily_average_seconds / 60) as u32,
@@ -1276,9 +2966,10 @@ impl ProfileManager {
                     top_categories: category_usage,
                     policy_violations: summary.violations_count as u32,
                     educational_percentage,
+                    time_bank,
                 })
             }
-            Err(_) => Ok(crate::reports::WeeklyReport {
+            Err(_) => Ok(payload::WeeklyReport {
                 week_start,
                 total_screen_time_minutes: 0,
                 average_daily_minutes: 0,
@@ -1286,6 +2977,7 @@ impl ProfileManager {
                 top_categories: vec![],
                 policy_violations: 0,
                 educational_percentage: 0.0,
+                time_bank,
             }),
         }
     }
@@ -1299,15 +2991,23 @@ impl ProfileManager {
     ) -> Result<String> {
         use chrono::{Duration, NaiveDate};
 
-        let start_date = NaiveDate::parse_from_str(start_date_str, "%Y-%m-%d")
-            .map_err(|e| anyhow!("Invalid start date format: {}. Expected YYYY-MM-DD", e))?;
+        let start_date = NaiveDate::parse_from_str(start_date_str, "%Y-%m-%d").map_err(|e| {
+            DaemonError::InvalidArgument(format!(
+                "Invalid start date format: {}. Expected YYYY-MM-DD",
+                e
+            ))
+        })?;
 
-        let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-            .map_err(|e| anyhow!("Invalid end date format: {}. Expected YYYY-MM-DD", e))?;
+        let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d").map_err(|e| {
+            DaemonError::InvalidArgument(format!(
+                "Invalid end date format: {}. Expected YYYY-MM-DD",
+                e
+            ))
+        })?;
 
         match format {
             "json" => {
-                let mut reports: Vec<crate::reports::ActivityReport> = Vec::new();
+                let mut reports: Vec<payload::ActivityReport> = Vec::new();
                 let mut current_date = start_date;
 
                 while current_date <= end_date {
@@ -1321,7 +3021,7 @@ impl ProfileManager {
                 Ok(serde_json::to_string_pretty(&reports)?)
             }
             "csv" => {
-                let mut csv_content = String::from("Date,Screen Time (minutes),Top Activity,Top Category,Violations,Blocked Attempts\n");
+                let mut csv_content = String::from("Date,Screen Time (minutes),Top Activity,Top Category,Violations,Blocked Attempts,Credits Earned,Credits Spent\n");
                 let mut current_date = start_date;
 
                 while current_date <= end_date {
@@ -1329,20 +3029,25 @@ impl ProfileManager {
                         .get_daily_report(profile_id, &current_date.format("%Y-%m-%d").to_string())
                         .await?;
                     csv_content.push_str(&format!(
-                        "{},{},{},{},{},{}\n",
+                        "{},{},{},{},{},{},{},{}\n",
                         report.date,
                         report.screen_time_minutes,
                         report.top_activity,
                         report.top_category,
                         report.violations,
-                        report.blocked_attempts
+                        report.blocked_attempts,
+                        report.time_bank.earned_minutes + report.time_bank.rolled_over_minutes,
+                        report.time_bank.spent_minutes
                     ));
                     current_date += Duration::days(1);
                 }
 
                 Ok(csv_content)
             }
-            _ => Err(anyhow!("Unsupported export format: {}", format)),
+            _ => {
+                Err(DaemonError::InvalidArgument(format!("Unsupported export format: {}", format))
+                    .into())
+            }
         }
     }
 
@@ -1361,10 +3066,7 @@ impl ProfileManager {
     ) -> Result<()> {
         use dots_family_common::types::TimeWindow;
 
-        // Validate parent authentication
-        if !self.validate_session(token).await {
-            return Err(anyhow!("Invalid or expired session token"));
-        }
+        let guardian = self.authorize(token, Permission::Manage, "add_time_window").await?;
 
         // Validate time format (HH:MM)
         Self::validate_time_format(start)?;
@@ -1372,22 +3074,23 @@ impl ProfileManager {
 
         // Validate start < end
         if start >= end {
-            return Err(anyhow!("Start time must be before end time"));
+            return Err(DaemonError::InvalidArgument(
+                "Start time must be before end time".to_string(),
+            )
+            .into());
         }
 
         // Validate window type
         if !matches!(window_type, "weekday" | "weekend" | "holiday") {
-            return Err(anyhow!(
+            return Err(DaemonError::InvalidArgument(format!(
                 "Invalid window type '{}'. Must be one of: weekday, weekend, holiday",
                 window_type
-            ));
+            ))
+            .into());
         }
 
-        // Try to find profile by ID first, then by name
-        let profile = match ProfileQueries::get_by_id(&self._db, profile_id).await {
-            Ok(p) => p,
-            Err(_) => ProfileQueries::get_by_name(&self._db, profile_id).await?,
-        };
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "add_time_window").await?;
 
         // Parse existing config
         let mut config: dots_family_common::types::ProfileConfig =
@@ -1406,13 +3109,11 @@ impl ProfileManager {
 
         for existing in target_windows {
             if Self::windows_overlap(&new_window, existing) {
-                return Err(anyhow!(
+                return Err(DaemonError::InvalidArgument(format!(
                     "Time window {}–{} overlaps with existing window {}–{}",
-                    start,
-                    end,
-                    existing.start,
-                    existing.end
-                ));
+                    start, end, existing.start, existing.end
+                ))
+                .into());
             }
         }
 
@@ -1435,6 +3136,7 @@ impl ProfileManager {
         // Save updated config
         let updated_config_json = serde_json::to_string(&config)?;
         ProfileQueries::update_config(&self._db, &profile.id, &updated_config_json).await?;
+        self.profile_changed().await;
 
         info!("Added {} time window {}–{} to profile {}", window_type, start, end, profile.name);
 
@@ -1450,24 +3152,19 @@ impl ProfileManager {
         end: &str,
         token: &str,
     ) -> Result<()> {
-        // Validate parent authentication
-        if !self.validate_session(token).await {
-            return Err(anyhow!("Invalid or expired session token"));
-        }
+        let guardian = self.authorize(token, Permission::Manage, "remove_time_window").await?;
 
         // Validate window type
         if !matches!(window_type, "weekday" | "weekend" | "holiday") {
-            return Err(anyhow!(
+            return Err(DaemonError::InvalidArgument(format!(
                 "Invalid window type '{}'. Must be one of: weekday, weekend, holiday",
                 window_type
-            ));
+            ))
+            .into());
         }
 
-        // Try to find profile by ID first, then by name
-        let profile = match ProfileQueries::get_by_id(&self._db, profile_id).await {
-            Ok(p) => p,
-            Err(_) => ProfileQueries::get_by_name(&self._db, profile_id).await?,
-        };
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "remove_time_window").await?;
 
         // Parse existing config
         let mut config: dots_family_common::types::ProfileConfig =
@@ -1494,17 +3191,17 @@ impl ProfileManager {
         };
 
         if !removed {
-            return Err(anyhow!(
+            return Err(DaemonError::NotFound(format!(
                 "Time window {}–{} not found in {} windows",
-                start,
-                end,
-                window_type
-            ));
+                start, end, window_type
+            ))
+            .into());
         }
 
         // Save updated config
         let updated_config_json = serde_json::to_string(&config)?;
         ProfileQueries::update_config(&self._db, &profile.id, &updated_config_json).await?;
+        self.profile_changed().await;
 
         info!(
             "Removed {} time window {}–{} from profile {}",
@@ -1519,29 +3216,23 @@ impl ProfileManager {
         &self,
         profile_id: &str,
         token: &str,
-    ) -> Result<serde_json::Value> {
-        // Validate parent authentication
-        if !self.validate_session(token).await {
-            return Err(anyhow!("Invalid or expired session token"));
-        }
+    ) -> Result<payload::TimeWindowList> {
+        let guardian = self.authorize(token, Permission::View, "list_time_windows").await?;
 
-        // Try to find profile by ID first, then by name
-        let profile = match ProfileQueries::get_by_id(&self._db, profile_id).await {
-            Ok(p) => p,
-            Err(_) => ProfileQueries::get_by_name(&self._db, profile_id).await?,
-        };
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "list_time_windows").await?;
 
         // Parse existing config
         let config: dots_family_common::types::ProfileConfig =
             serde_json::from_str(&profile.config)?;
 
-        Ok(serde_json::json!({
-            "profile_id": profile.id,
-            "profile_name": profile.name,
-            "weekday": config.screen_time.windows.weekday,
-            "weekend": config.screen_time.windows.weekend,
-            "holiday": config.screen_time.windows.holiday,
-        }))
+        Ok(payload::TimeWindowList {
+            profile_id: profile.id,
+            profile_name: profile.name,
+            weekday: config.screen_time.windows.weekday,
+            weekend: config.screen_time.windows.weekend,
+            holiday: config.screen_time.windows.holiday,
+        })
     }
 
     /// Clear all time windows of a specific type from a profile
@@ -1551,24 +3242,19 @@ impl ProfileManager {
         window_type: &str,
         token: &str,
     ) -> Result<()> {
-        // Validate parent authentication
-        if !self.validate_session(token).await {
-            return Err(anyhow!("Invalid or expired session token"));
-        }
+        let guardian = self.authorize(token, Permission::Manage, "clear_time_windows").await?;
 
         // Validate window type
         if !matches!(window_type, "weekday" | "weekend" | "holiday") {
-            return Err(anyhow!(
+            return Err(DaemonError::InvalidArgument(format!(
                 "Invalid window type '{}'. Must be one of: weekday, weekend, holiday",
                 window_type
-            ));
+            ))
+            .into());
         }
 
-        // Try to find profile by ID first, then by name
-        let profile = match ProfileQueries::get_by_id(&self._db, profile_id).await {
-            Ok(p) => p,
-            Err(_) => ProfileQueries::get_by_name(&self._db, profile_id).await?,
-        };
+        let profile = self.find_profile(profile_id).await?;
+        self.authorize_scope(&guardian, Some(&profile.id), "clear_time_windows").await?;
 
         // Parse existing config
         let mut config: dots_family_common::types::ProfileConfig =
@@ -1597,6 +3283,7 @@ impl ProfileManager {
         // Save updated config
         let updated_config_json = serde_json::to_string(&config)?;
         ProfileQueries::update_config(&self._db, &profile.id, &updated_config_json).await?;
+        self.profile_changed().await;
 
         info!("Cleared {} {} time windows from profile {}", count, window_type, profile.name);
 
@@ -1617,30 +3304,70 @@ impl ProfileManager {
     fn validate_time_format(time: &str) -> Result<()> {
         let parts: Vec<&str> = time.split(':').collect();
         if parts.len() != 2 {
-            return Err(anyhow!(
+            return Err(DaemonError::InvalidArgument(format!(
                 "Invalid time format '{}'. Expected HH:MM (e.g., 08:00, 15:30)",
                 time
-            ));
+            ))
+            .into());
         }
 
-        let hours = parts[0]
-            .parse::<u32>()
-            .map_err(|_| anyhow!("Invalid hours '{}' in time '{}'", parts[0], time))?;
-        let minutes = parts[1]
-            .parse::<u32>()
-            .map_err(|_| anyhow!("Invalid minutes '{}' in time '{}'", parts[1], time))?;
+        let hours = parts[0].parse::<u32>().map_err(|_| {
+            DaemonError::InvalidArgument(format!("Invalid hours '{}' in time '{}'", parts[0], time))
+        })?;
+        let minutes = parts[1].parse::<u32>().map_err(|_| {
+            DaemonError::InvalidArgument(format!(
+                "Invalid minutes '{}' in time '{}'",
+                parts[1], time
+            ))
+        })?;
 
         if hours > 23 {
-            return Err(anyhow!("Hours must be 0-23, got {}", hours));
+            return Err(
+                DaemonError::InvalidArgument(format!("Hours must be 0-23, got {}", hours)).into()
+            );
         }
         if minutes > 59 {
-            return Err(anyhow!("Minutes must be 0-59, got {}", minutes));
+            return Err(DaemonError::InvalidArgument(format!(
+                "Minutes must be 0-59, got {}",
+                minutes
+            ))
+            .into());
         }
 
         Ok(())
     }
 }
 
+/// When the account's current login began, from logind's record of it
+fn logged_in_since(users_root: &Path, uid: u32) -> Option<DateTime<Utc>> {
+    let state = std::fs::read_to_string(users_root.join(uid.to_string())).ok()?;
+    let micros = state.lines().find_map(|line| line.strip_prefix("REALTIME="))?.parse().ok()?;
+    DateTime::from_timestamp_micros(micros)
+}
+
+/// How much of the time from the end of `covered` to `start` the machine was
+/// on and awake with the child logged in, yet no activity was reported
+fn unaccounted_time(
+    covered: &ActivityCoverage,
+    start: DateTime<Utc>,
+    boot: &trusted_clock::Boot,
+    logged_in_since: Option<DateTime<Utc>>,
+) -> chrono::Duration {
+    let (mut from, asleep_before) = if covered.boot_id == boot.id {
+        (covered.covered_until, std::time::Duration::from_millis(covered.asleep_ms.max(0) as u64))
+    } else {
+        // The machine was off in between, so only this boot counts
+        (covered.covered_until.max(boot.started_at), std::time::Duration::ZERO)
+    };
+    if let Some(login) = logged_in_since {
+        from = from.max(login);
+    }
+
+    let asleep = chrono::Duration::from_std(boot.asleep.saturating_sub(asleep_before))
+        .unwrap_or_else(|_| chrono::Duration::zero());
+    start - from - asleep
+}
+
 #[cfg(test)]
 mod tests {
     use dots_family_common::types::{
@@ -1660,7 +3387,8 @@ mod tests {
         let daemon_config = DaemonConfig {
             database: crate::config::DatabaseConfig {
                 path: db_path.to_str().unwrap().to_string(),
-                encryption_key: None,
+                encrypt: false,
+                ..Default::default()
             },
             auth: crate::config::AuthConfig { parent_password_hash: None },
             dbus: crate::config::DbusConfig {
@@ -1668,6 +3396,13 @@ mod tests {
                 use_session_bus: false,
             },
             dry_run: Some(false),
+            approvals: crate::config::ApprovalConfig::default(),
+            time_bank: crate::config::TimeBankConfig::default(),
+            tamper: crate::config::TamperConfig::default(),
+            clock: crate::config::ClockConfig::default(),
+            audit: crate::config::AuditConfig::default(),
+            access: crate::config::AccessConfig::default(),
+            http_api: crate::config::HttpApiConfig::default(),
         };
 
         let db_config = dots_family_db::DatabaseConfig {
@@ -1687,6 +3422,7 @@ mod tests {
                 weekend_bonus_minutes: 60,
                 exempt_categories: vec![],
                 windows: TimeWindows { weekday: vec![], weekend: vec![], holiday: vec![] },
+                enforcement: Default::default(),
             },
             applications: ApplicationConfig {
                 mode: ApplicationMode::Allowlist,
@@ -1702,6 +3438,7 @@ mod tests {
                 blocked_domains: vec![],
             },
             terminal_filtering: TerminalFilteringConfig::default(),
+            network: Default::default(),
         };
 
         let new_profile = dots_family_db::models::NewProfile {
@@ -1789,7 +3526,7 @@ mod tests {
 
         // When: Reporting incomplete activity JSON (missing required fields)
         let activity_json = r#"{"app_id":"firefox","duration":60}"#;
-        let result = manager.report_activity(activity_json).await;
+        let result = manager.report_activity(activity_json, None, true).await;
 
         // Then: It should fail with missing field error
         assert!(result.is_err());
@@ -1815,7 +3552,7 @@ mod tests {
             profile_id,
             chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ")
         );
-        let result = manager.report_activity(&activity_json).await;
+        let result = manager.report_activity(&activity_json, None, true).await;
 
         // Then: Activity should be stored in database
         if let Err(e) = &result {
@@ -1832,6 +3569,249 @@ mod tests {
         assert_eq!(activities[0].duration_seconds, 60);
     }
 
+    fn activity(profile_id: Uuid) -> Activity {
+        Activity {
+            id: Uuid::new_v4(),
+            profile_id,
+            timestamp: chrono::Utc::now(),
+            activity_type: dots_family_common::types::ActivityType::ApplicationUsage,
+            application: Some("firefox".to_string()),
+            window_title: None,
+            duration_seconds: 60,
+        }
+    }
+
+    fn activity_json(profile_id: Uuid) -> String {
+        payload::encode(&activity(profile_id)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_registered_component_activities_are_stamped_with_its_profile() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        let profile_id = manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+
+        let registered =
+            manager.register_component(":1.42", "monitor", 1001, "alice").await.unwrap();
+        assert_eq!(registered.to_string(), profile_id);
+
+        // Whatever the component claims, the activity belongs to its account's profile
+        manager.report_activity(&activity_json(Uuid::nil()), Some(":1.42"), false).await.unwrap();
+        manager
+            .report_activity(&activity_json(Uuid::new_v4()), Some(":1.42"), false)
+            .await
+            .unwrap();
+
+        use dots_family_db::queries::activities::ActivityQueries;
+        let activities = ActivityQueries::list_for_profile(&db, &profile_id, 10).await.unwrap();
+        assert_eq!(activities.len(), 2);
+
+        manager.unregister_component(":1.42").await;
+        assert!(manager.registered_component(":1.42").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_filter_reports_for_the_profile_it_names() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        let profile_id = manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+
+        // The web filter runs as a system account and never registers; it
+        // names the profile of the connection the request came in on
+        let mut report = activity(Uuid::parse_str(&profile_id).unwrap());
+        report.activity_type = dots_family_common::types::ActivityType::WebBrowsing {
+            url: "https://example.com/".to_string(),
+        };
+        report.application = Some("web-filter".to_string());
+        let json = payload::encode(&report).unwrap();
+
+        assert!(manager.report_activity(&json, Some(":1.50"), false).await.is_err());
+        manager.report_activity(&json, Some(":1.50"), true).await.unwrap();
+
+        use dots_family_db::queries::activities::ActivityQueries;
+        let activities = ActivityQueries::list_for_profile(&db, &profile_id, 10).await.unwrap();
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].app_id, "web-filter");
+    }
+
+    #[tokio::test]
+    async fn test_replayed_activity_is_stored_once() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        let profile_id = manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+        manager.register_component(":1.42", "monitor", 1001, "alice").await.unwrap();
+
+        let spooled = activity_json(Uuid::nil());
+        manager.report_activity(&spooled, Some(":1.42"), false).await.unwrap();
+        manager.report_activity(&spooled, Some(":1.42"), false).await.unwrap();
+
+        use dots_family_db::queries::activities::ActivityQueries;
+        let activities = ActivityQueries::list_for_profile(&db, &profile_id, 10).await.unwrap();
+        assert_eq!(activities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gap_in_component_activity_is_flagged() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+        manager.register_component(":1.42", "monitor", 1001, "alice").await.unwrap();
+
+        let mut earlier = activity(Uuid::nil());
+        earlier.timestamp = chrono::Utc::now() - chrono::Duration::hours(2);
+        let mut adjacent = activity(Uuid::nil());
+        adjacent.timestamp = earlier.timestamp + chrono::Duration::seconds(60);
+        for reported in [&earlier, &adjacent] {
+            let json = payload::encode(reported).unwrap();
+            manager.report_activity(&json, Some(":1.42"), false).await.unwrap();
+        }
+        assert!(manager.take_activity_gaps().await.is_empty());
+
+        manager.report_activity(&activity_json(Uuid::nil()), Some(":1.42"), false).await.unwrap();
+        let gaps = manager.take_activity_gaps().await;
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].component, "monitor");
+        assert_eq!(gaps[0].from, adjacent.timestamp);
+        assert!(manager.take_activity_gaps().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gap_is_flagged_across_reconnects() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+        manager.register_component(":1.42", "monitor", 1001, "alice").await.unwrap();
+
+        let mut earlier = activity(Uuid::nil());
+        earlier.timestamp = chrono::Utc::now() - chrono::Duration::hours(2);
+        let json = payload::encode(&earlier).unwrap();
+        manager.report_activity(&json, Some(":1.42"), false).await.unwrap();
+
+        // A fresh bus connection doesn't wipe the record of the last activity
+        manager.unregister_component(":1.42").await;
+        manager.register_component(":1.43", "monitor", 1001, "alice").await.unwrap();
+        manager.report_activity(&activity_json(Uuid::nil()), Some(":1.43"), false).await.unwrap();
+
+        let gaps = manager.take_activity_gaps().await;
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].from, earlier.timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_unfocused_time_covers_the_timeline_without_being_stored() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+        let profile_id = manager
+            .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+            .await
+            .unwrap();
+        manager.register_component(":1.42", "monitor", 1001, "alice").await.unwrap();
+
+        let now = chrono::Utc::now();
+        let mut earlier = activity(Uuid::nil());
+        earlier.timestamp = now - chrono::Duration::hours(2);
+        let mut locked = activity(Uuid::nil());
+        locked.activity_type = ActivityType::Unfocused;
+        locked.application = None;
+        locked.timestamp = now - chrono::Duration::seconds(60);
+        locked.duration_seconds = (locked.timestamp - earlier.timestamp).num_seconds() as u32 - 30;
+        for reported in [&earlier, &locked, &activity(Uuid::nil())] {
+            let json = payload::encode(reported).unwrap();
+            manager.report_activity(&json, Some(":1.42"), false).await.unwrap();
+        }
+        assert!(manager.take_activity_gaps().await.is_empty());
+
+        use dots_family_db::queries::activities::ActivityQueries;
+        let activities = ActivityQueries::list_for_profile(&db, &profile_id, 10).await.unwrap();
+        assert_eq!(activities.len(), 2);
+    }
+
+    fn boot(id: &str, started_at: DateTime<Utc>, asleep_secs: u64) -> trusted_clock::Boot {
+        trusted_clock::Boot {
+            id: id.to_string(),
+            started_at,
+            asleep: std::time::Duration::from_secs(asleep_secs),
+        }
+    }
+
+    #[test]
+    fn test_unaccounted_time_leaves_out_sleep_power_off_and_logged_out_time() {
+        let now = chrono::Utc::now();
+        let covered = ActivityCoverage {
+            covered_until: now - chrono::Duration::hours(3),
+            boot_id: "boot-1".to_string(),
+            asleep_ms: 60_000,
+        };
+        let hours = chrono::Duration::hours;
+
+        // Same boot: suspended for an hour since the last report
+        let unaccounted =
+            unaccounted_time(&covered, now, &boot("boot-1", now - hours(5), 3660), None);
+        assert_eq!(unaccounted, hours(2));
+
+        // Rebooted an hour ago and slept ten minutes of it
+        let unaccounted =
+            unaccounted_time(&covered, now, &boot("boot-2", now - hours(1), 600), None);
+        assert_eq!(unaccounted, chrono::Duration::minutes(50));
+
+        // Logged in half an hour ago
+        let unaccounted = unaccounted_time(
+            &covered,
+            now,
+            &boot("boot-1", now - hours(5), 60),
+            Some(now - chrono::Duration::minutes(30)),
+        );
+        assert_eq!(unaccounted, chrono::Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_login_time_is_read_from_logind_state() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("1001"),
+            "# This is private data. Do not parse.\nNAME=alice\nSTATE=active\nREALTIME=1700000000123456\nMONOTONIC=5000000\n",
+        )
+        .unwrap();
+
+        let since = logged_in_since(dir.path(), 1001).unwrap();
+        assert_eq!(since.timestamp_micros(), 1_700_000_000_123_456);
+        assert_eq!(logged_in_since(dir.path(), 1002), None);
+    }
+
+    #[tokio::test]
+    async fn test_activities_without_a_profile_are_rejected() {
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+
+        let unnamed =
+            manager.report_activity(&activity_json(Uuid::nil()), Some(":1.7"), true).await;
+        assert!(matches!(
+            unnamed.unwrap_err().downcast::<DaemonError>(),
+            Ok(DaemonError::InvalidArgument(_))
+        ));
+
+        let unknown_account = manager.register_component(":1.7", "filter", 1002, "bob").await;
+        assert!(matches!(
+            unknown_account.unwrap_err().downcast::<DaemonError>(),
+            Ok(DaemonError::NotFound(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_bdd_given_monitor_when_heartbeat_sent_then_health_check_passes() {
         // Given: A profile manager
@@ -1897,6 +3877,38 @@ mod tests {
         assert!(!manager.is_tampered().await);
     }
 
+    #[tokio::test]
+    async fn test_bdd_given_tamper_event_when_recorded_then_audit_logged() {
+        use crate::tamper_detector::{TamperEvent, TamperKind};
+        use dots_family_db::queries::audit::AuditQueries;
+
+        // Given: A profile manager
+        let (db, _dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+
+        // When: A tamper event is recorded
+        let event = TamperEvent {
+            kind: TamperKind::DatabaseReplaced,
+            child: None,
+            detail: "/var/lib/dots-family/family.db was replaced".to_string(),
+        };
+        manager.record_tamper(&event).await.unwrap();
+
+        // Then: It is in the audit log and the tamper flag is set
+        let entries = AuditQueries::list_by_actor(&db, "system", 10).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "tamper_detected");
+        assert_eq!(
+            entries[0].details.as_deref(),
+            Some("database_replaced: /var/lib/dots-family/family.db was replaced")
+        );
+        assert!(manager.is_tampered().await);
+
+        // And: Clearing requires a parent session
+        assert!(manager.clear_tamper("bogus-token").await.is_err());
+        assert!(manager.is_tampered().await);
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_bdd_given_profile_when_set_active_then_loaded_on_next_startup() {
@@ -1910,7 +3922,7 @@ mod tests {
         // When: Creating a new manager (simulating daemon restart)
         let db2_config = dots_family_db::DatabaseConfig {
             path: config.database.path.clone(),
-            encryption_key: config.database.encryption_key.clone(),
+            encryption_key: None,
         };
         let db2 = Database::new(db2_config).await.unwrap();
         let manager2 = ProfileManager::new(&config, db2).await.unwrap();
@@ -1983,6 +3995,7 @@ mod tests {
         let session_id = manager.get_active_session_id().await.unwrap();
 
         let activity1 = NewActivity {
+            activity_id: None,
             session_id: session_id.clone(),
             profile_id: profile_id.clone(),
             app_id: "firefox".to_string(),
@@ -1990,9 +4003,11 @@ mod tests {
             category: Some("browser".to_string()),
             window_title: Some("Example".to_string()),
             duration_seconds: 300,
+            timestamp: None,
         };
 
         let activity2 = NewActivity {
+            activity_id: None,
             session_id: session_id.clone(),
             profile_id: profile_id.clone(),
             app_id: "code".to_string(),
@@ -2000,6 +4015,7 @@ mod tests {
             category: Some("editor".to_string()),
             window_title: Some("main.rs".to_string()),
             duration_seconds: 450,
+            timestamp: None,
         };
 
         ActivityQueries::create(&db, activity1).await.unwrap();
@@ -2021,6 +4037,7 @@ mod tests {
         let session_id = manager.get_active_session_id().await.unwrap();
 
         let activity = NewActivity {
+            activity_id: None,
             session_id: session_id.clone(),
             profile_id: profile_id.clone(),
             app_id: "firefox".to_string(),
@@ -2028,6 +4045,7 @@ mod tests {
             category: Some("browser".to_string()),
             window_title: Some("Example".to_string()),
             duration_seconds: 3600,
+            timestamp: None,
         };
 
         ActivityQueries::create(&db, activity).await.unwrap();
@@ -2048,6 +4066,7 @@ mod tests {
         let session_id = manager.get_active_session_id().await.unwrap();
 
         let activity = NewActivity {
+            activity_id: None,
             session_id: session_id.clone(),
             profile_id: profile_id.clone(),
             app_id: "firefox".to_string(),
@@ -2055,6 +4074,7 @@ mod tests {
             category: Some("browser".to_string()),
             window_title: Some("Example".to_string()),
             duration_seconds: 7200,
+            timestamp: None,
         };
 
         ActivityQueries::create(&db, activity).await.unwrap();
@@ -2149,6 +4169,38 @@ mod tests {
         assert!(error.to_string().contains("Invalid password"));
     }
 
+    #[tokio::test]
+    async fn test_bdd_given_repeated_wrong_passwords_when_authenticate_then_rate_limited() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+
+        // Given a parent password is set and five wrong guesses were made
+        manager.set_parent_password("correct_password").await.unwrap();
+        for _ in 0..5 {
+            assert!(manager.authenticate_parent("wrong_password").await.is_err());
+        }
+
+        // When authenticating again, even with the right password
+        let result = manager.authenticate_guardian("parent", "correct_password").await;
+
+        // Then the attempt is refused as rate limited
+        let error = result.unwrap_err().downcast::<DaemonError>().unwrap();
+        assert!(matches!(error, DaemonError::RateLimited(_)));
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_unknown_profile_name_when_looked_up_then_not_found() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let manager = ProfileManager::new(&config, db).await.unwrap();
+
+        // When looking up a profile that does not exist
+        let result = manager.find_profile("nobody").await;
+
+        // Then the caller is told it was not found
+        let error = result.unwrap_err().downcast::<DaemonError>().unwrap();
+        assert!(matches!(error, DaemonError::NotFound(_)));
+    }
+
     #[tokio::test]
     async fn test_bdd_given_no_password_configured_when_authenticate_then_fails() {
         let (db, _temp_dir, config) = setup_test_db().await;
@@ -2164,4 +4216,358 @@ mod tests {
         let error = result.unwrap_err();
         assert!(error.to_string().contains("authentication not configured"));
     }
+
+    #[tokio::test]
+    async fn test_bdd_given_auto_approval_rule_when_request_submitted_then_resolved_once_per_day() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&profile_id).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given a rule auto-approving short screen time extensions once per day
+        let rule = serde_json::json!({
+            "name": "Bonus time",
+            "conditions": [
+                {"type": "request_kind", "kind": "screen_time"},
+                {"type": "max_minutes", "minutes": 15},
+                {"type": "max_per_day", "count": 1}
+            ],
+            "action": "auto_approve"
+        });
+        manager.add_auto_approval_rule(&rule.to_string(), &token).await.unwrap();
+
+        // When the child asks for 10 extra minutes
+        let details = r#"{"requested_minutes": 10}"#;
+        let first =
+            manager.submit_approval_request(&profile_id, "screen_time", "", details).await.unwrap();
+
+        // Then the request is auto-approved with an explanation and an exception
+        assert_eq!(first.request_status, "auto_approved");
+        assert!(first.explanation.unwrap().contains("Bonus time"));
+        assert!(first.exception_id.is_some());
+
+        // And a second request the same day waits for a parent
+        let second =
+            manager.submit_approval_request(&profile_id, "screen_time", "", details).await.unwrap();
+        assert_eq!(second.request_status, "pending");
+        assert!(second.explanation.is_none());
+
+        let pending = manager.list_pending_requests(&token).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, second.request_id);
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_deny_rule_when_matching_request_submitted_then_denied() {
+        let (db, _temp_dir, mut config) = setup_test_db().await;
+        let applications = tempfile::tempdir().unwrap();
+        std::fs::write(
+            applications.path().join("supertuxkart.desktop"),
+            "[Desktop Entry]\nName=SuperTuxKart\nCategories=Game;ArcadeGame;\n",
+        )
+        .unwrap();
+        config.approvals.application_dirs = vec![applications.path().to_string_lossy().to_string()];
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&profile_id).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given rules approving every app but denying games
+        let approve = serde_json::json!({
+            "name": "Any app",
+            "conditions": [{"type": "request_kind", "kind": "app"}],
+            "action": "auto_approve"
+        });
+        let deny = serde_json::json!({
+            "name": "No games",
+            "conditions": [{"type": "category", "category": "Game"}],
+            "action": "auto_deny"
+        });
+        manager.add_auto_approval_rule(&approve.to_string(), &token).await.unwrap();
+        manager.add_auto_approval_rule(&deny.to_string(), &token).await.unwrap();
+        assert_eq!(manager.list_auto_approval_rules(&token).await.unwrap().len(), 2);
+
+        // When the child asks for an installed game, labelling it as something else
+        let details = r#"{"app_id": "supertuxkart", "category": "Education"}"#;
+        let result =
+            manager.submit_approval_request(&profile_id, "app", "", details).await.unwrap();
+
+        // Then the deny rule wins on the catalogued category
+        assert_eq!(result.request_status, "denied");
+        assert!(result.explanation.unwrap().starts_with("Auto-denied by rule 'No games'"));
+        assert!(result.exception_id.is_none());
+
+        // And the installed category was cached for later requests
+        let cached = dots_family_db::queries::app_info_cache::get_app_cache_entry(
+            manager._db.pool().unwrap(),
+            "supertuxkart",
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(cached.category.as_deref(), Some("Game"));
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_category_rule_when_child_claims_category_then_left_pending() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&profile_id).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given a rule approving educational apps
+        let approve = serde_json::json!({
+            "name": "Learning",
+            "conditions": [{"type": "category", "category": "Education"}],
+            "action": "auto_approve"
+        });
+        manager.add_auto_approval_rule(&approve.to_string(), &token).await.unwrap();
+
+        // When the child labels an uncatalogued app as educational
+        let details = r#"{"app_id": "supertuxkart", "category": "Education"}"#;
+        let result =
+            manager.submit_approval_request(&profile_id, "app", "", details).await.unwrap();
+
+        // Then a parent still has to decide
+        assert_eq!(result.request_status, "pending");
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_stale_request_when_expiry_runs_then_expired_and_cannot_be_approved() {
+        let (db, _temp_dir, mut config) = setup_test_db().await;
+        config.approvals.request_ttl_minutes = 0;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&profile_id).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given a pending request older than the TTL
+        let details = r#"{"requested_minutes": 30}"#;
+        let submission =
+            manager.submit_approval_request(&profile_id, "screen_time", "", details).await.unwrap();
+        assert_eq!(submission.request_status, "pending");
+
+        // When the expiry sweep runs
+        let expired = manager.expire_stale_requests().await.unwrap();
+
+        // Then the request is expired and a parent can no longer approve it
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, submission.request_id);
+        assert_eq!(expired[0].status, "expired");
+        assert!(manager.list_pending_requests(&token).await.unwrap().is_empty());
+        let error =
+            manager.approve_request(&submission.request_id, "ok", &token).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DaemonError>(),
+            Some(DaemonError::InvalidArgument(_))
+        ));
+        let error = manager.deny_request(&submission.request_id, "no", &token).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DaemonError>(),
+            Some(DaemonError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_two_children_when_one_asks_then_request_and_balance_are_its_own() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let active_child = create_test_profile(&db, "Active Child").await;
+        let sibling = create_test_profile(&db, "Sibling").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&active_child).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given banked time for the sibling only
+        manager.grant_time_credit(&sibling, 15, "Homework", &token).await.unwrap();
+
+        // When the sibling asks while another child is active
+        let details = r#"{"requested_minutes": 10}"#;
+        manager.submit_approval_request(&sibling, "screen_time", "", details).await.unwrap();
+        manager.request_command_approval(&sibling, "rm -rf /tmp/x", "high", "").await.unwrap();
+
+        // Then the requests and the balance belong to the sibling
+        let pending = manager.list_pending_requests(&token).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending.iter().all(|request| request.profile_id == sibling));
+        assert_eq!(manager.get_time_credit_balance(&sibling).await.unwrap(), 15);
+        assert_eq!(manager.get_time_credit_balance(&active_child).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_earned_credits_when_spend_approved_then_remaining_time_extended() {
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+        let profile = manager._load_profile(&profile_id).await.unwrap();
+        *manager.active_profile.write().await = Some(profile);
+
+        manager.set_parent_password("test_password_123").await.unwrap();
+        let token = manager.authenticate_parent("test_password_123").await.unwrap();
+
+        // Given 30 minutes earned for chores
+        manager.grant_time_credit(&profile_id, 30, "Cleaned room", &token).await.unwrap();
+        assert_eq!(manager.get_time_credit_balance(&profile_id).await.unwrap(), 30);
+        let remaining_before = manager.get_remaining_time().await.unwrap();
+
+        // When the child asks to spend more than they have, the request is refused
+        let too_much = r#"{"minutes": 45}"#;
+        assert!(manager
+            .submit_approval_request(&profile_id, "time_credit", "", too_much)
+            .await
+            .is_err());
+
+        // When the child spends 20 minutes and a parent approves
+        let submission = manager
+            .submit_approval_request(&profile_id, "time_credit", "", r#"{"minutes": 20}"#)
+            .await
+            .unwrap();
+        let exception_id =
+            manager.approve_request(&submission.request_id, "Enjoy", &token).await.unwrap();
+
+        // Then the credits move into today's allowance and the ledger records it
+        assert!(exception_id.is_none());
+        assert_eq!(manager.get_time_credit_balance(&profile_id).await.unwrap(), 10);
+        assert_eq!(manager.get_remaining_time().await.unwrap(), remaining_before + 20);
+
+        let bank = manager.get_time_bank(&profile_id, &token).await.unwrap();
+        assert_eq!(bank.balance_minutes, 10);
+        assert_eq!(bank.history.len(), 2);
+
+        let today = chrono::Local::now().date_naive().format("%Y-%m-%d").to_string();
+        let report = manager.get_daily_report(&profile_id, &today).await.unwrap();
+        assert_eq!(report.time_bank.earned_minutes, 30);
+        assert_eq!(report.time_bank.spent_minutes, 20);
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_unused_time_when_rolled_over_then_capped_and_recorded_once() {
+        let (db, _temp_dir, mut config) = setup_test_db().await;
+        config.time_bank.rollover_enabled = true;
+        config.time_bank.rollover_cap_minutes = 45;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let manager = ProfileManager::new(&config, db).await.unwrap();
+
+        // Given a day the child logged in but used no screen time (120+ minutes unused)
+        let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
+        let (day_start, _) = ProfileManager::local_day_bounds(yesterday);
+        sqlx::query(
+            "INSERT INTO sessions (id, profile_id, start_time, end_time) VALUES (?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&profile_id)
+        .bind(day_start + chrono::Duration::hours(15))
+        .bind(day_start + chrono::Duration::hours(15) + chrono::Duration::minutes(5))
+        .execute(manager._db.pool().unwrap())
+        .await
+        .unwrap();
+
+        // When the rollover runs twice
+        manager.roll_over_unused_time(yesterday).await.unwrap();
+        manager.roll_over_unused_time(yesterday).await.unwrap();
+
+        // Then only the capped amount is banked, once
+        assert_eq!(manager.get_time_credit_balance(&profile_id).await.unwrap(), 45);
+    }
+
+    #[test]
+    fn test_day_bounds_follow_the_local_calendar() {
+        use chrono::Timelike;
+
+        let day = chrono::NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        let (start, end) = ProfileManager::local_day_bounds(day);
+        assert_eq!(start.with_timezone(&Local).date_naive(), day);
+        assert_eq!(start.with_timezone(&Local).hour(), 0);
+        assert_eq!(end.with_timezone(&Local).date_naive(), day.succ_opt().unwrap());
+        assert_eq!(end.with_timezone(&Local).hour(), 0);
+        assert!((end - start - chrono::Duration::hours(24)).num_hours().abs() <= 1);
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_day_without_session_when_rolled_over_then_nothing_banked() {
+        let (db, _temp_dir, mut config) = setup_test_db().await;
+        config.time_bank.rollover_enabled = true;
+        let profile_id = create_test_profile(&db, "Test Child").await;
+        let manager = ProfileManager::new(&config, db).await.unwrap();
+
+        // Given a day the child never logged in
+        let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
+
+        // When the rollover runs
+        manager.roll_over_unused_time(yesterday).await.unwrap();
+
+        // Then no time is credited for it
+        assert_eq!(manager.get_time_credit_balance(&profile_id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bdd_given_scoped_guardians_when_acting_then_role_and_profile_checked() {
+        use dots_family_db::queries::audit::AuditQueries;
+
+        let (db, _temp_dir, config) = setup_test_db().await;
+        let first_child = create_test_profile(&db, "First Child").await;
+        let second_child = create_test_profile(&db, "Second Child").await;
+        let audit_db = db.clone();
+        let mut manager = ProfileManager::new(&config, db).await.unwrap();
+
+        // Given the primary parent adds a babysitter for one child and a read-only grandparent
+        manager.set_parent_password("parent_password").await.unwrap();
+        let parent = manager.authenticate_parent("parent_password").await.unwrap();
+        let babysitter_scope = vec![first_child.clone()];
+        manager
+            .add_guardian("babysitter", "sitter_password", "approver", &babysitter_scope, &parent)
+            .await
+            .unwrap();
+        manager.add_guardian("gran", "gran_password", "viewer", &[], &parent).await.unwrap();
+        assert!(manager.add_guardian("parent", "pw", "admin", &[], &parent).await.is_err());
+        assert_eq!(manager.list_guardians(&parent).await.unwrap().len(), 3);
+
+        let babysitter =
+            manager.authenticate_guardian("babysitter", "sitter_password").await.unwrap();
+        let gran = manager.authenticate_guardian("gran", "gran_password").await.unwrap();
+        assert!(manager.authenticate_guardian("gran", "wrong").await.is_err());
+
+        // Then the babysitter may grant time only to their own child and cannot change rules
+        manager.grant_time_credit(&first_child, 15, "Homework done", &babysitter).await.unwrap();
+        assert!(manager.grant_time_credit(&second_child, 15, "", &babysitter).await.is_err());
+        assert!(manager.get_time_bank(&second_child, &babysitter).await.is_err());
+        assert!(manager
+            .add_time_window(&first_child, "weekday", "08:00", "09:00", &babysitter)
+            .await
+            .is_err());
+        assert!(manager.list_guardians(&babysitter).await.is_err());
+
+        // And the grandparent may look but not grant
+        assert_eq!(manager.get_time_bank(&first_child, &gran).await.unwrap().balance_minutes, 15);
+        assert!(manager.grant_time_credit(&first_child, 15, "", &gran).await.is_err());
+
+        // And the audit log records which guardian acted or was refused
+        let entries =
+            AuditQueries::list_by_actor(&audit_db, "guardian:babysitter", 20).await.unwrap();
+        assert!(entries.iter().any(|e| e.action == "grant_time_credit" && e.success));
+        assert!(entries.iter().any(|e| e.action == "add_time_window" && !e.success));
+        let entries = AuditQueries::list_by_actor(&audit_db, "guardian:gran", 20).await.unwrap();
+        assert!(entries.iter().any(|e| e.action == "grant_time_credit" && !e.success));
+
+        // When the babysitter account is removed, its session stops working
+        manager.remove_guardian("babysitter", &parent).await.unwrap();
+        assert!(!manager.validate_session(&babysitter).await);
+        assert!(manager.validate_session(&gran).await);
+    }
 }
@@ -0,0 +1,194 @@
+//! Maps kernel-reported processes to child profiles.
+//!
+//! eBPF programs report the uid of the task that caused an event. A child
+//! profile is linked to a system account through `Profile.username`, so the
+//! resolver keeps a uid -> profile id table built from the active profiles.
+//! The table is rebuilt when the user database changes (accounts created,
+//! removed or renumbered), periodically to pick up profile edits, and on
+//! demand through [`ProfileResolver::invalidate`].
+
+use std::{
+    collections::HashMap,
+    os::unix::fs::MetadataExt,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+use dots_family_db::{queries::profiles::ProfileQueries, Database};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::accounts::uid_of_user;
+
+const PASSWD_PATH: &str = "/etc/passwd";
+/// How often the user database is checked for changes
+const REVALIDATE_INTERVAL: Duration = Duration::from_secs(5);
+/// Maximum age of the table before profiles are re-read
+const MAX_CACHE_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct ResolverCache {
+    profiles_by_uid: HashMap<u32, String>,
+    /// When the table was built; `None` forces a rebuild
+    built_at: Option<Instant>,
+    last_validated: Option<Instant>,
+    /// Modification time of the user database the table was built from
+    passwd_modified: Option<SystemTime>,
+}
+
+pub struct ProfileResolver {
+    db: Database,
+    cache: RwLock<ResolverCache>,
+}
+
+impl ProfileResolver {
+    pub fn new(db: Database) -> Self {
+        Self { db, cache: RwLock::default() }
+    }
+
+    /// Profile id of the child owning this uid, if any
+    pub async fn resolve_uid(&self, uid: u32) -> Result<Option<String>> {
+        self.refresh_if_stale().await?;
+
+        let cache = self.cache.read().await;
+        Ok(cache.profiles_by_uid.get(&uid).cloned())
+    }
+
+    /// Profile id for a process, using the uid reported by the kernel and
+    /// falling back to the current owner of the process
+    pub async fn resolve_process(&self, pid: u32, uid: u32) -> Result<Option<String>> {
+        if let Some(profile_id) = self.resolve_uid(uid).await? {
+            return Ok(Some(profile_id));
+        }
+
+        match std::fs::metadata(format!("/proc/{}", pid)) {
+            Ok(metadata) if metadata.uid() != uid => self.resolve_uid(metadata.uid()).await,
+            _ => Ok(None),
+        }
+    }
+
+    /// Force the table to be rebuilt on the next lookup, e.g. after a
+    /// profile was created, deleted or linked to another account
+    pub async fn invalidate(&self) {
+        debug!("Invalidating process-to-profile cache");
+        self.cache.write().await.built_at = None;
+    }
+
+    async fn refresh_if_stale(&self) -> Result<()> {
+        {
+            let cache = self.cache.read().await;
+            let recently_validated =
+                cache.last_validated.is_some_and(|at| at.elapsed() < REVALIDATE_INTERVAL);
+            if cache.built_at.is_some() && recently_validated {
+                return Ok(());
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+        let passwd_modified = std::fs::metadata(PASSWD_PATH).and_then(|m| m.modified()).ok();
+
+        let stale = match cache.built_at {
+            None => true,
+            Some(built_at) => {
+                built_at.elapsed() >= MAX_CACHE_AGE || cache.passwd_modified != passwd_modified
+            }
+        };
+
+        if stale {
+            cache.profiles_by_uid = self.build_table().await?;
+            cache.built_at = Some(Instant::now());
+            cache.passwd_modified = passwd_modified;
+        }
+        cache.last_validated = Some(Instant::now());
+
+        Ok(())
+    }
+
+    async fn build_table(&self) -> Result<HashMap<u32, String>> {
+        let mut profiles_by_uid = HashMap::new();
+
+        for profile in ProfileQueries::list_all(&self.db).await? {
+            if !profile.active {
+                continue;
+            }
+            let Some(username) = profile.username else {
+                continue;
+            };
+
+            match uid_of_user(&username) {
+                Ok(uid) => {
+                    profiles_by_uid.insert(uid, profile.id);
+                }
+                Err(e) => warn!("Profile {} has no usable system account: {}", profile.name, e),
+            }
+        }
+
+        info!("Mapped {} system accounts to child profiles", profiles_by_uid.len());
+        Ok(profiles_by_uid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dots_family_db::models::NewProfile;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_config = dots_family_db::DatabaseConfig {
+            path: dir.path().join("test.db").to_str().unwrap().to_string(),
+            encryption_key: None,
+        };
+
+        let db = Database::new(db_config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    fn current_user() -> (u32, String) {
+        let uid = nix::unistd::getuid();
+        let user = nix::unistd::User::from_uid(uid).unwrap().unwrap();
+        (uid.as_raw(), user.name)
+    }
+
+    #[tokio::test]
+    async fn test_resolves_uid_of_linked_account() {
+        let (db, _dir) = setup_test_db().await;
+        let (uid, username) = current_user();
+
+        let mut new_profile =
+            NewProfile::new("Child".to_string(), "8-12".to_string(), "{}".to_string());
+        new_profile.username = Some(username);
+        let profile = ProfileQueries::create(&db, new_profile).await.unwrap();
+
+        let resolver = ProfileResolver::new(db);
+        assert_eq!(resolver.resolve_uid(uid).await.unwrap(), Some(profile.id.clone()));
+        assert_eq!(resolver.resolve_uid(uid.wrapping_add(4242)).await.unwrap(), None);
+        assert_eq!(
+            resolver.resolve_process(std::process::id(), uid.wrapping_add(4242)).await.unwrap(),
+            Some(profile.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_picks_up_profile_changes() {
+        let (db, _dir) = setup_test_db().await;
+        let (uid, username) = current_user();
+
+        let mut new_profile =
+            NewProfile::new("Child".to_string(), "8-12".to_string(), "{}".to_string());
+        new_profile.username = Some(username);
+        let profile = ProfileQueries::create(&db, new_profile).await.unwrap();
+
+        let resolver = ProfileResolver::new(db.clone());
+        assert!(resolver.resolve_uid(uid).await.unwrap().is_some());
+
+        ProfileQueries::deactivate(&db, &profile.id).await.unwrap();
+        assert!(resolver.resolve_uid(uid).await.unwrap().is_some());
+
+        resolver.invalidate().await;
+        assert_eq!(resolver.resolve_uid(uid).await.unwrap(), None);
+    }
+}
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use dots_family_db::{queries::SessionQueries, Database, NewSession};
+use dots_family_db::{queries::SessionQueries, Database, NewSession, SessionTotals};
 use tokio::sync::RwLock;
 use tracing::info;
 
+use crate::trusted_clock;
+
 pub struct _SessionManager {
     db: Database,
     active_session: Arc<RwLock<Option<String>>>,
@@ -19,7 +21,7 @@ impl _SessionManager {
         let new_session = NewSession::new(profile_id.to_string());
         let session_id = new_session.id.clone();
 
-        SessionQueries::create(&self.db, new_session).await?;
+        SessionQueries::create(&self.db, new_session, trusted_clock::now()).await?;
 
         let mut active = self.active_session.write().await;
         *active = Some(session_id.clone());
@@ -38,7 +40,14 @@ impl _SessionManager {
             return Ok(());
         };
 
-        SessionQueries::end_session(&self.db, &session_id, reason, 0, 0, 0, 0).await?;
+        SessionQueries::end_session(
+            &self.db,
+            &session_id,
+            reason,
+            &SessionTotals::default(),
+            trusted_clock::now(),
+        )
+        .await?;
 
         let mut active = self.active_session.write().await;
         *active = None;
@@ -0,0 +1,651 @@
+//! Detects attempts to get around parental controls.
+//!
+//! Each check looks at one way a child could disable or bypass the system:
+//! stopping the monitor or web filter, starting programs without the
+//! filtering proxy, moving the clock, swapping out the database file, or
+//! running another operating system in a VM or from live media. The daemon
+//! turns each [`TamperEvent`] into a `TamperDetected` signal, an audit log
+//! entry and the responses configured in [`TamperConfig`].
+
+use std::{
+    collections::HashSet,
+    fmt,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use dots_family_common::types::Profile;
+use nix::time::{clock_gettime, ClockId};
+use tracing::{debug, warn};
+
+use crate::{accounts::uid_of_user, config::TamperConfig, exec_guard::executable_name};
+
+/// Kernel command line options set when booted from live media
+const LIVE_BOOT_OPTIONS: &[&str] = &["boot=live", "rd.live.image", "root=live:", "copytoram"];
+/// Filesystems live media images are mounted with
+const LIVE_MEDIA_FILESYSTEMS: &[&str] = &["iso9660", "squashfs", "udf"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TamperKind {
+    /// Monitor or filter killed, stopped or silent
+    ComponentStopped,
+    /// Program started without the filtering proxy
+    ProxyRemoved,
+    ClockJump,
+    DatabaseReplaced,
+    VirtualMachine,
+    LiveSession,
+    /// Activity missing between a monitor's reports, e.g. a cut offline spool
+    ActivityGap,
+}
+
+impl TamperKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TamperKind::ComponentStopped => "component_stopped",
+            TamperKind::ProxyRemoved => "proxy_removed",
+            TamperKind::ClockJump => "clock_jump",
+            TamperKind::DatabaseReplaced => "database_replaced",
+            TamperKind::VirtualMachine => "virtual_machine",
+            TamperKind::LiveSession => "live_session",
+            TamperKind::ActivityGap => "activity_gap",
+        }
+    }
+}
+
+impl fmt::Display for TamperKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TamperEvent {
+    pub kind: TamperKind,
+    /// Child the event is attributed to, `None` for system-wide events
+    pub child: Option<ChildAccount>,
+    pub detail: String,
+}
+
+impl TamperEvent {
+    /// Reason sent with the `TamperDetected` signal
+    pub fn reason(&self) -> String {
+        format!("{}: {}", self.kind, self.detail)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildAccount {
+    pub uid: u32,
+    pub username: String,
+    pub profile_id: String,
+}
+
+impl ChildAccount {
+    /// Local accounts of the active profiles
+    pub fn from_profiles(profiles: &[Profile]) -> Vec<Self> {
+        profiles
+            .iter()
+            .filter(|profile| profile.active)
+            .filter_map(|profile| {
+                let username = profile.username.as_ref()?;
+                match uid_of_user(username) {
+                    Ok(uid) => Some(Self {
+                        uid,
+                        username: username.clone(),
+                        profile_id: profile.id.to_string(),
+                    }),
+                    Err(e) => {
+                        debug!("Skipping tamper checks for {}: {}", profile.name, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// A running process as seen in `/proc`
+#[derive(Debug, Clone)]
+struct ProcEntry {
+    pid: u32,
+    uid: u32,
+    name: String,
+    stopped: bool,
+}
+
+pub struct TamperDetector {
+    config: TamperConfig,
+    proc_root: PathBuf,
+    /// Wall clock and boot clock at the previous check
+    clock: Option<(SystemTime, Duration)>,
+    /// Database path with the device and inode it was opened at
+    database: Option<(PathBuf, (u64, u64))>,
+    /// Watched processes that have been seen running, with their owner
+    started: HashSet<(String, u32)>,
+    /// logind's per-user state files, `/run/systemd/users`
+    sessions_root: PathBuf,
+    /// Ongoing conditions already reported, so each is reported once
+    active: HashSet<String>,
+}
+
+impl TamperDetector {
+    pub fn new(config: TamperConfig) -> Self {
+        Self::with_proc_root(config, PathBuf::from("/proc"))
+    }
+
+    fn with_proc_root(config: TamperConfig, proc_root: PathBuf) -> Self {
+        Self {
+            config,
+            proc_root,
+            clock: None,
+            database: None,
+            started: HashSet::new(),
+            sessions_root: PathBuf::from("/run/systemd/users"),
+            active: HashSet::new(),
+        }
+    }
+
+    /// Watch the database file for being replaced or removed
+    pub fn watch_database(&mut self, path: &Path) {
+        match file_identity(path) {
+            Some(identity) => self.database = Some((path.to_path_buf(), identity)),
+            None => warn!("Cannot watch database file {:?}", path),
+        }
+    }
+
+    /// Run every check. `monitor_heartbeat_ok` is the result of the monitor
+    /// heartbeat check, which lives with the profile manager.
+    pub fn check(
+        &mut self,
+        children: &[ChildAccount],
+        monitor_heartbeat_ok: bool,
+    ) -> Vec<TamperEvent> {
+        let mut events = Vec::new();
+        let processes = self.scan_processes();
+
+        if !monitor_heartbeat_ok {
+            self.report_once(
+                &mut events,
+                "heartbeat".to_string(),
+                TamperEvent {
+                    kind: TamperKind::ComponentStopped,
+                    child: None,
+                    detail: "activity monitor stopped sending heartbeats".to_string(),
+                },
+            );
+        } else {
+            self.active.remove("heartbeat");
+        }
+
+        self.check_components(children, &processes, &mut events);
+        self.check_clock(&mut events);
+        self.check_database(&mut events);
+        self.check_children(children, &processes, &mut events);
+
+        for event in &events {
+            warn!("Tamper detected: {}", event.reason());
+        }
+        events
+    }
+
+    fn report_once(&mut self, events: &mut Vec<TamperEvent>, key: String, event: TamperEvent) {
+        if self.active.insert(key) {
+            events.push(event);
+        }
+    }
+
+    fn check_components(
+        &mut self,
+        children: &[ChildAccount],
+        processes: &[ProcEntry],
+        events: &mut Vec<TamperEvent>,
+    ) {
+        for watched in self.config.watched_processes.clone() {
+            let running: Vec<&ProcEntry> =
+                processes.iter().filter(|process| process.name == watched).collect();
+
+            for process in &running {
+                self.started.insert((watched.clone(), process.uid));
+                let key = format!("component:{}:{}", watched, process.uid);
+                if process.stopped {
+                    self.report_once(
+                        events,
+                        key,
+                        TamperEvent {
+                            kind: TamperKind::ComponentStopped,
+                            child: children.iter().find(|c| c.uid == process.uid).cloned(),
+                            detail: format!("{} (PID {}) was stopped", watched, process.pid),
+                        },
+                    );
+                } else {
+                    self.active.remove(&key);
+                }
+            }
+
+            // Only a component that was running can have been killed
+            let gone: Vec<u32> = self
+                .started
+                .iter()
+                .filter(|(name, uid)| {
+                    *name == watched && !running.iter().any(|process| process.uid == *uid)
+                })
+                .map(|(_, uid)| *uid)
+                .collect();
+
+            for uid in gone {
+                let key = format!("component:{}:{}", watched, uid);
+                let child = children.iter().find(|c| c.uid == uid);
+
+                // A component running in a child's session ends with it on logout
+                if child.is_some() && !self.session_live(uid) {
+                    self.started.remove(&(watched.clone(), uid));
+                    self.active.remove(&key);
+                    continue;
+                }
+
+                self.report_once(
+                    events,
+                    key,
+                    TamperEvent {
+                        kind: TamperKind::ComponentStopped,
+                        child: child.cloned(),
+                        detail: format!("{} is no longer running", watched),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Whether logind still has a session open for `uid`
+    fn session_live(&self, uid: u32) -> bool {
+        let Ok(state) = std::fs::read_to_string(self.sessions_root.join(uid.to_string())) else {
+            return false;
+        };
+        state
+            .lines()
+            .find_map(|line| line.strip_prefix("STATE="))
+            .is_some_and(|state| matches!(state, "active" | "online"))
+    }
+
+    fn check_clock(&mut self, events: &mut Vec<TamperEvent>) {
+        // The boot clock keeps counting through suspend, unlike the monotonic clock
+        let Ok(boot) = clock_gettime(ClockId::CLOCK_BOOTTIME) else {
+            return;
+        };
+        let now = (SystemTime::now(), Duration::from(boot));
+
+        if let Some(previous) = self.clock.replace(now) {
+            if let Some(event) = clock_jump(previous, now, self.config.clock_jump_tolerance_seconds)
+            {
+                events.push(event);
+            }
+        }
+    }
+
+    fn check_database(&mut self, events: &mut Vec<TamperEvent>) {
+        let Some((path, identity)) = self.database.clone() else {
+            return;
+        };
+
+        let detail = match file_identity(&path) {
+            Some(current) if current == identity => return,
+            Some(current) => {
+                // Watch the new file so a further swap is noticed too
+                self.database = Some((path.clone(), current));
+                format!("{} was replaced", path.display())
+            }
+            None => format!("{} was removed", path.display()),
+        };
+
+        self.report_once(
+            events,
+            format!("database:{}", detail),
+            TamperEvent { kind: TamperKind::DatabaseReplaced, child: None, detail },
+        );
+    }
+
+    fn check_children(
+        &mut self,
+        children: &[ChildAccount],
+        processes: &[ProcEntry],
+        events: &mut Vec<TamperEvent>,
+    ) {
+        if let Ok(cmdline) = std::fs::read_to_string(self.proc_root.join("cmdline")) {
+            if let Some(option) = live_boot_option(&cmdline) {
+                self.report_once(
+                    events,
+                    "live_boot".to_string(),
+                    TamperEvent {
+                        kind: TamperKind::LiveSession,
+                        child: None,
+                        detail: format!("system was booted from live media ({})", option),
+                    },
+                );
+            }
+        }
+        let mounts = std::fs::read_to_string(self.proc_root.join("mounts")).unwrap_or_default();
+
+        for child in children {
+            let owned: Vec<&ProcEntry> =
+                processes.iter().filter(|process| process.uid == child.uid).collect();
+
+            let vms: Vec<&ProcEntry> =
+                owned.iter().copied().filter(|p| self.is_vm_executable(&p.name)).collect();
+            for process in &vms {
+                self.report_once(
+                    events,
+                    format!("vm:{}:{}", child.uid, process.pid),
+                    TamperEvent {
+                        kind: TamperKind::VirtualMachine,
+                        child: Some(child.clone()),
+                        detail: format!(
+                            "{} started {} (PID {})",
+                            child.username, process.name, process.pid
+                        ),
+                    },
+                );
+            }
+            if vms.is_empty() {
+                self.active.retain(|key| !key.starts_with(&format!("vm:{}:", child.uid)));
+            }
+
+            for mount_point in live_media_mounts(&mounts, &child.username) {
+                self.report_once(
+                    events,
+                    format!("live_media:{}", mount_point),
+                    TamperEvent {
+                        kind: TamperKind::LiveSession,
+                        child: Some(child.clone()),
+                        detail: format!("{} mounted live media at {}", child.username, mount_point),
+                    },
+                );
+            }
+
+            if let Some(expected) = self.config.expected_proxy.clone() {
+                let key = format!("proxy:{}", child.uid);
+                let unproxied = owned.iter().find(|process| {
+                    std::fs::read(self.proc_root.join(process.pid.to_string()).join("environ"))
+                        .map(|environ| proxy_missing(&environ, &expected))
+                        .unwrap_or(false)
+                });
+
+                match unproxied {
+                    Some(process) => self.report_once(
+                        events,
+                        key,
+                        TamperEvent {
+                            kind: TamperKind::ProxyRemoved,
+                            child: Some(child.clone()),
+                            detail: format!(
+                                "{} (PID {}) of {} runs without the filtering proxy",
+                                process.name, process.pid, child.username
+                            ),
+                        },
+                    ),
+                    None => {
+                        self.active.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_vm_executable(&self, name: &str) -> bool {
+        is_vm_executable(name) || self.config.vm_executables.iter().any(|vm| vm == name)
+    }
+
+    fn scan_processes(&self) -> Vec<ProcEntry> {
+        let Ok(entries) = std::fs::read_dir(&self.proc_root) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| self.read_process(pid))
+            .collect()
+    }
+
+    fn read_process(&self, pid: u32) -> Option<ProcEntry> {
+        let dir = self.proc_root.join(pid.to_string());
+        let status = std::fs::read_to_string(dir.join("status")).ok()?;
+
+        let field = |name: &str| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .and_then(|value| value.split_whitespace().next())
+        };
+        let uid = field("Uid:")?.parse().ok()?;
+        // T is stopped by a signal, t is stopped by a debugger
+        let stopped = matches!(field("State:"), Some("T") | Some("t"));
+
+        let path = match std::fs::read_link(dir.join("exe")) {
+            Ok(exe) => exe.to_string_lossy().into_owned(),
+            Err(_) => {
+                let cmdline = std::fs::read(dir.join("cmdline")).ok()?;
+                let argv0 = cmdline.split(|&b| b == 0).next()?;
+                String::from_utf8_lossy(argv0).into_owned()
+            }
+        };
+        let name = executable_name(&path).to_string();
+
+        Some(ProcEntry { pid, uid, name, stopped })
+    }
+}
+
+/// Device and inode of a file
+fn file_identity(path: &Path) -> Option<(u64, u64)> {
+    std::fs::metadata(path).ok().map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+/// Compare how far the wall clock and the boot clock moved between two checks
+fn clock_jump(
+    previous: (SystemTime, Duration),
+    now: (SystemTime, Duration),
+    tolerance_seconds: u64,
+) -> Option<TamperEvent> {
+    let elapsed = now.1.saturating_sub(previous.1).as_secs() as i64;
+    let wall_elapsed = match now.0.duration_since(previous.0) {
+        Ok(forward) => forward.as_secs() as i64,
+        Err(backward) => -(backward.duration().as_secs() as i64),
+    };
+
+    let drift = wall_elapsed - elapsed;
+    if drift.unsigned_abs() <= tolerance_seconds {
+        return None;
+    }
+
+    let direction = if drift > 0 { "forward" } else { "back" };
+    Some(TamperEvent {
+        kind: TamperKind::ClockJump,
+        child: None,
+        detail: format!("system clock moved {} by {}s", direction, drift.unsigned_abs()),
+    })
+}
+
+fn live_boot_option(cmdline: &str) -> Option<&str> {
+    cmdline
+        .split_whitespace()
+        .find(|option| LIVE_BOOT_OPTIONS.iter().any(|live| option.starts_with(live)))
+}
+
+/// Mount points of live media images in a user's removable media directory
+fn live_media_mounts(mounts: &str, username: &str) -> Vec<String> {
+    let user_dirs = [format!("/run/media/{}/", username), format!("/media/{}/", username)];
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_source, mount_point, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+            let is_live = LIVE_MEDIA_FILESYSTEMS.contains(&fs_type)
+                && user_dirs.iter().any(|dir| mount_point.starts_with(dir.as_str()));
+            is_live.then(|| mount_point.replace("\\040", " "))
+        })
+        .collect()
+}
+
+fn is_vm_executable(name: &str) -> bool {
+    name.starts_with("qemu-system-")
+        || matches!(
+            name,
+            "qemu-kvm"
+                | "VirtualBoxVM"
+                | "VBoxHeadless"
+                | "vmware-vmx"
+                | "vmplayer"
+                | "gnome-boxes"
+                | "firecracker"
+                | "crosvm"
+                | "cloud-hypervisor"
+        )
+}
+
+/// Whether a graphical program's environment lacks the expected proxy
+fn proxy_missing(environ: &[u8], expected: &str) -> bool {
+    let vars: Vec<(&str, &str)> = environ
+        .split(|&b| b == 0)
+        .filter_map(|var| std::str::from_utf8(var).ok()?.split_once('='))
+        .collect();
+
+    let graphical = vars.iter().any(|(name, _)| matches!(*name, "DISPLAY" | "WAYLAND_DISPLAY"));
+    if !graphical {
+        return false;
+    }
+
+    let expected = expected.trim_end_matches('/');
+    !vars.iter().any(|(name, value)| {
+        matches!(name.to_ascii_lowercase().as_str(), "http_proxy" | "https_proxy")
+            && value.trim_end_matches('/') == expected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_jump_detection() {
+        let start = SystemTime::now();
+        let boot = Duration::from_secs(1000);
+
+        let steady = (start + Duration::from_secs(30), boot + Duration::from_secs(30));
+        assert!(clock_jump((start, boot), steady, 120).is_none());
+
+        // Suspend moves both clocks, which is not tampering
+        let resumed = (start + Duration::from_secs(7200), boot + Duration::from_secs(7200));
+        assert!(clock_jump((start, boot), resumed, 120).is_none());
+
+        let forward = (start + Duration::from_secs(3630), boot + Duration::from_secs(30));
+        let event = clock_jump((start, boot), forward, 120).unwrap();
+        assert_eq!(event.kind, TamperKind::ClockJump);
+        assert_eq!(event.detail, "system clock moved forward by 3600s");
+
+        let back = (start - Duration::from_secs(600), boot + Duration::from_secs(30));
+        assert!(clock_jump((start, boot), back, 120).unwrap().detail.contains("back by 630s"));
+    }
+
+    #[test]
+    fn test_live_media_and_vm_detection() {
+        assert_eq!(live_boot_option("BOOT_IMAGE=/vmlinuz boot=live quiet"), Some("boot=live"));
+        assert_eq!(live_boot_option("BOOT_IMAGE=/vmlinuz root=/dev/sda1"), None);
+
+        let mounts = "/dev/sdb1 /run/media/alice/Ubuntu\\04024.04 iso9660 ro 0 0\n\
+                      /dev/sdc1 /run/media/alice/USB vfat rw 0 0\n\
+                      /dev/sdd1 /run/media/bob/Live iso9660 ro 0 0\n";
+        assert_eq!(live_media_mounts(mounts, "alice"), vec!["/run/media/alice/Ubuntu 24.04"]);
+
+        assert!(is_vm_executable("qemu-system-x86_64"));
+        assert!(is_vm_executable("VirtualBoxVM"));
+        assert!(!is_vm_executable("firefox"));
+    }
+
+    #[test]
+    fn test_proxy_missing() {
+        let proxy = "http://127.0.0.1:8080";
+        let with_proxy = b"DISPLAY=:0\0HTTP_PROXY=http://127.0.0.1:8080/\0";
+        let without_proxy = b"WAYLAND_DISPLAY=wayland-0\0HOME=/home/alice\0";
+        let other_proxy = b"DISPLAY=:0\0https_proxy=http://10.0.0.1:3128\0";
+        let no_display = b"HOME=/home/alice\0";
+
+        assert!(!proxy_missing(with_proxy, proxy));
+        assert!(proxy_missing(without_proxy, proxy));
+        assert!(proxy_missing(other_proxy, proxy));
+        assert!(!proxy_missing(no_display, proxy));
+    }
+
+    #[test]
+    fn test_database_replacement_reported_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("family.db");
+        std::fs::write(&db_path, b"original").unwrap();
+
+        let mut detector = TamperDetector::with_proc_root(
+            TamperConfig { watched_processes: Vec::new(), ..Default::default() },
+            dir.path().join("proc"),
+        );
+        detector.watch_database(&db_path);
+        assert!(detector.check(&[], true).is_empty());
+
+        let replacement = dir.path().join("other.db");
+        std::fs::write(&replacement, b"replacement").unwrap();
+        std::fs::rename(&replacement, &db_path).unwrap();
+
+        let events = detector.check(&[], true);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TamperKind::DatabaseReplaced);
+        assert!(detector.check(&[], true).is_empty());
+
+        let events = detector.check(&[], false);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TamperKind::ComponentStopped);
+    }
+
+    fn write_process(proc_root: &Path, pid: u32, uid: u32, exe: &str) {
+        let dir = proc_root.join(pid.to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("status"), format!("State:\tS (sleeping)\nUid:\t{0}\t{0}\n", uid))
+            .unwrap();
+        std::fs::write(dir.join("cmdline"), format!("{}\0", exe)).unwrap();
+    }
+
+    #[test]
+    fn test_component_stopping_at_logout_is_not_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let proc_root = dir.path().join("proc");
+        let sessions = dir.path().join("users");
+        std::fs::create_dir_all(&sessions).unwrap();
+
+        let mut detector =
+            TamperDetector::with_proc_root(TamperConfig::default(), proc_root.clone());
+        detector.sessions_root = sessions.clone();
+        let child =
+            ChildAccount { uid: 1001, username: "alice".to_string(), profile_id: "p".to_string() };
+        let children = [child.clone()];
+
+        std::fs::write(sessions.join("1001"), "NAME=alice\nSTATE=active\n").unwrap();
+        write_process(&proc_root, 4000, 1001, "/usr/bin/dots-family-monitor");
+        assert!(detector.check(&children, true).is_empty());
+
+        // Killed while the child is still logged in
+        std::fs::remove_dir_all(proc_root.join("4000")).unwrap();
+        let events = detector.check(&children, true);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TamperKind::ComponentStopped);
+        assert_eq!(events[0].child, Some(child));
+
+        // Restarted, then the child logs out
+        write_process(&proc_root, 4100, 1001, "/usr/bin/dots-family-monitor");
+        assert!(detector.check(&children, true).is_empty());
+        std::fs::remove_dir_all(proc_root.join("4100")).unwrap();
+        std::fs::write(sessions.join("1001"), "NAME=alice\nSTATE=closing\n").unwrap();
+        assert!(detector.check(&children, true).is_empty());
+
+        // The next login starts from scratch
+        std::fs::write(sessions.join("1001"), "NAME=alice\nSTATE=active\n").unwrap();
+        assert!(detector.check(&children, true).is_empty());
+    }
+}
@@ -1,18 +1,31 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use dots_family_common::AccessResult;
+use chrono::{DateTime, Duration, Local};
+use dots_family_common::{AccessResult, EscalationStage};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
-use crate::{enforcement::EnforcementEngine, time_window_manager::TimeWindowManager};
+use crate::{
+    enforcement::EnforcementEngine, time_window_manager::TimeWindowManager, trusted_clock,
+};
 
-/// Handles periodic time window enforcement checks
+/// Handles periodic time window enforcement checks.
+///
+/// Enforcement escalates step by step as time runs out: a warning, a
+/// countdown, a grace period to save work, closing applications, and
+/// finally locking the session. Both the end of the current time window and
+/// the daily screen-time limit drive the ladder, whichever comes first.
 pub struct TimeWindowEnforcementTask {
     time_window_manager: Arc<TimeWindowManager>,
     enforcement_engine: Arc<RwLock<EnforcementEngine>>,
-    last_warning_sent: Arc<RwLock<bool>>,
-    session_locked: Arc<RwLock<bool>>,
+    /// Daily screen time left, as tracked by the policy engine
+    remaining_daily_minutes: Arc<RwLock<Option<u32>>>,
+    /// When allowed time ran out, used to time the grace period
+    time_up_at: Arc<RwLock<Option<DateTime<Local>>>>,
+    current_stage: Arc<RwLock<EscalationStage>>,
+    /// User whose network access is cut off outside their time windows
+    network_blocked_user: Arc<RwLock<Option<String>>>,
 }
 
 impl TimeWindowEnforcementTask {
@@ -23,91 +36,200 @@ impl TimeWindowEnforcementTask {
         Self {
             time_window_manager,
             enforcement_engine,
-            last_warning_sent: Arc::new(RwLock::new(false)),
-            session_locked: Arc::new(RwLock::new(false)),
+            remaining_daily_minutes: Arc::new(RwLock::new(None)),
+            time_up_at: Arc::new(RwLock::new(None)),
+            current_stage: Arc::new(RwLock::new(EscalationStage::Normal)),
+            network_blocked_user: Arc::new(RwLock::new(None)),
         }
     }
 
-    /// Run one iteration of time window enforcement check
-    pub async fn check_and_enforce(&self) -> Result<()> {
-        // Check if we have an active profile
-        let profile = self.time_window_manager.get_active_profile().await;
-        if profile.is_none() {
-            debug!("No active profile for time window enforcement");
-            return Ok(());
-        }
+    /// Update the daily screen time left before the next check
+    pub async fn set_remaining_daily_minutes(&self, minutes: Option<u32>) {
+        *self.remaining_daily_minutes.write().await = minutes;
+    }
 
-        let profile = profile.unwrap();
+    /// Run one iteration of time window enforcement check.
+    ///
+    /// Returns the new stage when the escalation ladder moved on.
+    pub async fn check_and_enforce(&self) -> Result<Option<EscalationStage>> {
+        // Check if we have an active profile
+        let profile = match self.time_window_manager.get_active_profile().await {
+            Some(profile) => profile,
+            None => {
+                debug!("No active profile for time window enforcement");
+                self.reset().await;
+                return Ok(None);
+            }
+        };
         debug!("Checking time window enforcement for profile: {}", profile.name);
 
-        // Check current access status
-        let access_result = self.time_window_manager.check_access().await?;
+        let window_remaining = self.time_window_manager.time_remaining().await;
+        let limit_remaining =
+            self.remaining_daily_minutes.read().await.map(|m| Duration::minutes(m.into()));
 
-        match access_result {
-            AccessResult::Allowed => {
-                debug!("Access allowed - within time window");
+        let remaining = match (window_remaining, limit_remaining) {
+            (Some(window), Some(limit)) => window.min(limit),
+            (Some(remaining), None) | (None, Some(remaining)) => remaining,
+            (None, None) => {
+                self.reset().await;
+                return Ok(None);
+            }
+        };
+        let window_ends_first = window_remaining == Some(remaining);
+
+        // Once time is up, count the grace period from the moment it ran out
+        let now = trusted_clock::now_local();
+        let remaining = {
+            let mut time_up_at = self.time_up_at.write().await;
+            if remaining > Duration::zero() {
+                *time_up_at = None;
+                remaining
+            } else {
+                *time_up_at.get_or_insert(now) - now
+            }
+        };
 
-                // Reset session locked flag if it was locked
-                let mut locked = self.session_locked.write().await;
-                if *locked {
-                    info!("Session was locked but now in valid time window - unlocked");
-                    *locked = false;
+        let stage = self.time_window_manager.escalation_stage(remaining).await;
+        let previous = {
+            let mut current_stage = self.current_stage.write().await;
+            std::mem::replace(&mut *current_stage, stage)
+        };
+
+        if previous.as_str() == stage.as_str() {
+            debug!("Escalation stage unchanged: {}", stage.as_str());
+            let user = profile.username.as_deref().unwrap_or(&profile.name);
+            match stage {
+                // Windows still open after being asked to close, e.g. behind a
+                // save prompt left unanswered, are kept out of view until the lock
+                EscalationStage::CloseApps { .. } => {
+                    self.enforcement_engine.read().await.minimize_all_windows(user).await?;
                 }
+                EscalationStage::Lock => self.enforce_network_lock(user).await?,
+                _ => {}
+            }
+            return Ok(None);
+        }
 
-                // Check if we should send a warning (window ending soon)
-                if self.time_window_manager.should_warn().await? {
-                    let warning_sent = self.last_warning_sent.read().await;
-                    if !*warning_sent {
-                        // Send warning notification using TimeWindowManager
-                        self.time_window_manager.send_warning_notification().await?;
-
-                        // Mark that we sent the warning
-                        drop(warning_sent);
-                        let mut warning_sent_mut = self.last_warning_sent.write().await;
-                        *warning_sent_mut = true;
-                    }
-                } else {
-                    // Reset warning flag when we're not in warning period
-                    let mut warning_sent = self.last_warning_sent.write().await;
-                    *warning_sent = false;
+        info!(
+            "Escalation stage for {} changed from {} to {}",
+            profile.name,
+            previous.as_str(),
+            stage.as_str()
+        );
+
+        if previous == EscalationStage::Lock {
+            self.restore_network().await;
+        }
+
+        let enforcement = self.enforcement_engine.read().await;
+        match stage {
+            EscalationStage::Normal => {
+                if previous == EscalationStage::Lock {
+                    info!("Session was locked but time is available again - unlocked");
                 }
             }
-            AccessResult::Denied { reason, next_window } => {
-                warn!("Access denied - outside time window: {}", reason);
-
-                // Check if we should lock the session
-                if self.time_window_manager.should_lock().await? {
-                    let locked = self.session_locked.read().await;
-                    if !*locked {
-                        // Lock the session
-                        info!("Locking session for user: {}", profile.name);
-
-                        // Send notification before locking
-                        self.send_lockout_notification(&reason, next_window.as_deref()).await?;
-
-                        // Lock the session
-                        let enforcement = self.enforcement_engine.read().await;
-
-                        // Use username if available, otherwise use profile name (fallback)
-                        let user_to_lock = profile.username.as_deref().or(Some(&profile.name));
-                        enforcement.lock_session(user_to_lock).await?;
-
-                        // Mark session as locked
-                        drop(locked);
-                        let mut locked_mut = self.session_locked.write().await;
-                        *locked_mut = true;
-
-                        info!("Session locked for user: {}", profile.name);
-                    } else {
-                        debug!("Session already locked for user: {}", profile.name);
-                    }
+            EscalationStage::Warning { seconds_remaining } => {
+                if window_ends_first {
+                    self.time_window_manager.send_warning_notification().await?;
+                } else {
+                    let message = format!(
+                        "{} minutes of screen time left today",
+                        minutes_ceil(seconds_remaining)
+                    );
+                    enforcement.notify_user("Screen Time Ending Soon", &message).await?;
                 }
             }
+            EscalationStage::Countdown { seconds_remaining } => {
+                let message =
+                    format!("Time is up in {} seconds. Save your work now.", seconds_remaining);
+                enforcement.notify_user("Time Almost Up", &message).await?;
+            }
+            EscalationStage::GracePeriod { seconds_remaining } => {
+                let message = format!(
+                    "Please save your work. Applications will close in {} minutes.\n\n\
+                     Need a little longer? You can ask to finish up once a day.",
+                    minutes_ceil(seconds_remaining)
+                );
+                enforcement.notify_user("Time's Up", &message).await?;
+            }
+            EscalationStage::CloseApps { seconds_remaining } => {
+                let message = format!(
+                    "Closing applications. The session will lock in {} minutes.",
+                    minutes_ceil(seconds_remaining)
+                );
+                enforcement.notify_user("Closing Applications", &message).await?;
+                let user = profile.username.as_deref().unwrap_or(&profile.name);
+                enforcement.close_all_windows(user).await?;
+            }
+            EscalationStage::Lock => {
+                info!("Locking session for user: {}", profile.name);
+
+                let (reason, next_window) = match self.time_window_manager.check_access().await? {
+                    AccessResult::Denied { reason, next_window } => (reason, next_window),
+                    AccessResult::Allowed => ("Daily screen time limit reached".to_string(), None),
+                };
+
+                // Send notification before locking
+                drop(enforcement);
+                self.send_lockout_notification(&reason, next_window.as_deref()).await?;
+
+                // Use username if available, otherwise use profile name (fallback)
+                let enforcement = self.enforcement_engine.read().await;
+                let user_to_lock = profile.username.as_deref().unwrap_or(&profile.name);
+                enforcement.lock_session(Some(user_to_lock)).await?;
+
+                drop(enforcement);
+                self.enforce_network_lock(user_to_lock).await?;
+
+                info!("Session locked for user: {}", profile.name);
+            }
+        }
+
+        Ok(Some(stage))
+    }
+
+    /// Forget any escalation in progress
+    async fn reset(&self) {
+        self.restore_network().await;
+        *self.time_up_at.write().await = None;
+        *self.current_stage.write().await = EscalationStage::Normal;
+    }
+
+    /// Outside allowed hours a locked child's background apps lose network
+    /// access too. Checked on every tick while locked: the rules are dropped
+    /// when the child's session ends, so a new login must be blocked again.
+    async fn enforce_network_lock(&self, user: &str) -> Result<()> {
+        let off_hours =
+            matches!(self.time_window_manager.check_access().await?, AccessResult::Denied { .. });
+        let blocked_other =
+            self.network_blocked_user.read().await.as_deref().is_some_and(|u| u != user);
+        if !off_hours || blocked_other {
+            self.restore_network().await;
+        }
+        if !off_hours {
+            return Ok(());
         }
 
+        let enforcement = self.enforcement_engine.read().await;
+        match enforcement.block_all_network(user).await {
+            Ok(()) => *self.network_blocked_user.write().await = Some(user.to_string()),
+            Err(e) => warn!("Failed to block network for {}: {}", user, e),
+        }
         Ok(())
     }
 
+    /// Lift an off-hours network block, if one is in force
+    async fn restore_network(&self) {
+        let Some(user) = self.network_blocked_user.write().await.take() else {
+            return;
+        };
+
+        let enforcement = self.enforcement_engine.read().await;
+        if let Err(e) = enforcement.restore_network(&user).await {
+            warn!("Failed to restore network for {}: {}", user, e);
+        }
+    }
+
     /// Send warning notification that window is ending soon
     #[allow(dead_code)]
     async fn send_warning_notification(&self) -> Result<()> {
@@ -142,6 +264,10 @@ impl TimeWindowEnforcementTask {
     }
 }
 
+fn minutes_ceil(seconds: i64) -> i64 {
+    (seconds + 59) / 60
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
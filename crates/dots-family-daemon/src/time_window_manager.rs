@@ -1,15 +1,17 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use anyhow::Result;
-use chrono::{DateTime, Local};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate};
 use dots_family_common::{
     types::{Profile, TimeWindow},
-    AccessResult, TimeWindowConfig, TimeWindowEnforcer,
+    AccessResult, EscalationStage, TimeWindowConfig, TimeWindowEnforcer,
 };
+use dots_family_db::{queries::FinishingUpQueries, Database};
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
 
-use crate::notification_manager::NotificationManager;
+use crate::{notification_manager::NotificationManager, trusted_clock};
 
 /// Manages time window enforcement for user sessions
 pub struct TimeWindowManager {
@@ -17,6 +19,10 @@ pub struct TimeWindowManager {
     notification_manager: NotificationManager,
     active_profile: Arc<RwLock<Option<Profile>>>,
     last_warning_sent: Arc<RwLock<Option<DateTime<Local>>>>,
+    /// Day each profile last used its "finishing up" request
+    finishing_up_used: Arc<RwLock<HashMap<Uuid, NaiveDate>>>,
+    /// Where finishing-up grants are recorded across restarts
+    database: Option<Database>,
 }
 
 impl TimeWindowManager {
@@ -27,25 +33,47 @@ impl TimeWindowManager {
             notification_manager,
             active_profile: Arc::new(RwLock::new(None)),
             last_warning_sent: Arc::new(RwLock::new(None)),
+            finishing_up_used: Arc::new(RwLock::new(HashMap::new())),
+            database: None,
         }
     }
 
+    /// Record finishing-up grants in `database` so a restart does not reset them
+    pub fn with_database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
+    }
+
     /// Set the active profile and configure time window enforcement
     pub async fn set_active_profile(&self, profile: Profile) -> Result<()> {
         info!("Setting active profile for time window enforcement: {}", profile.name);
 
         // Create TimeWindowConfig from profile
+        let ladder = &profile.config.screen_time.enforcement;
         let config = TimeWindowConfig {
             weekday_windows: profile.config.screen_time.windows.weekday.clone(),
             weekend_windows: profile.config.screen_time.windows.weekend.clone(),
             holiday_windows: profile.config.screen_time.windows.holiday.clone(),
-            grace_period_minutes: 2, // Default grace period
-            warning_minutes: 5,      // Default warning time
+            grace_period_minutes: ladder.grace_period_minutes,
+            warning_minutes: ladder.warning_minutes,
+            countdown_minutes: ladder.countdown_minutes,
+            close_apps_minutes: ladder.close_apps_minutes,
         };
 
         // Create enforcer
         let enforcer = TimeWindowEnforcer::new(config);
 
+        if let Some(database) = &self.database {
+            let today = trusted_clock::now_local().date_naive();
+            match FinishingUpQueries::claimed(database, &profile.id.to_string(), today).await {
+                Ok(true) => {
+                    self.finishing_up_used.write().await.insert(profile.id, today);
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Failed to load finishing-up grants: {}", e),
+            }
+        }
+
         // Update state
         let mut enforcer_lock = self.enforcer.write().await;
         *enforcer_lock = Some(enforcer);
@@ -67,14 +95,75 @@ impl TimeWindowManager {
             }
         };
 
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         let result = enforcer.check_access(now);
 
         debug!("Time window check result: {:?}", result);
         Ok(result)
     }
 
+    /// Time left in the current window (`None` when no enforcer is configured)
+    pub async fn time_remaining(&self) -> Option<Duration> {
+        let enforcer_lock = self.enforcer.read().await;
+        enforcer_lock.as_ref().map(|enforcer| enforcer.time_remaining(trusted_clock::now_local()))
+    }
+
+    /// Escalation stage for the given time left, including any finishing-up extension
+    pub async fn escalation_stage(&self, remaining: Duration) -> EscalationStage {
+        let extra_grace_minutes = self.finishing_up_minutes_today().await;
+
+        let enforcer_lock = self.enforcer.read().await;
+        match enforcer_lock.as_ref() {
+            Some(enforcer) => enforcer.config().escalation_stage(remaining, extra_grace_minutes),
+            None => TimeWindowConfig::default().escalation_stage(remaining, extra_grace_minutes),
+        }
+    }
+
+    /// Grant `profile` its once-per-day "finishing up" extension. It extends
+    /// the grace period whenever that profile is the one being enforced.
+    ///
+    /// Returns the number of extra grace minutes granted.
+    pub async fn request_finishing_up(&self, profile: &Profile) -> Result<u32> {
+        let now = trusted_clock::now();
+        let today = now.with_timezone(&Local).date_naive();
+
+        let minutes = profile.config.screen_time.enforcement.finishing_up_minutes;
+
+        let mut used = self.finishing_up_used.write().await;
+        if used.get(&profile.id) == Some(&today) {
+            return Err(anyhow!("Finishing up has already been used today"));
+        }
+        if let Some(database) = &self.database {
+            let claimed =
+                FinishingUpQueries::claim(database, &profile.id.to_string(), today, minutes, now)
+                    .await?;
+            if !claimed {
+                used.insert(profile.id, today);
+                return Err(anyhow!("Finishing up has already been used today"));
+            }
+        }
+        used.insert(profile.id, today);
+
+        info!("Granted {} finishing-up minutes to profile {}", minutes, profile.name);
+        Ok(minutes)
+    }
+
+    /// Extra grace minutes from a finishing-up request made today
+    async fn finishing_up_minutes_today(&self) -> u32 {
+        let Some(profile) = self.get_active_profile().await else {
+            return 0;
+        };
+
+        let used = self.finishing_up_used.read().await;
+        if used.get(&profile.id) == Some(&trusted_clock::now_local().date_naive()) {
+            profile.config.screen_time.enforcement.finishing_up_minutes
+        } else {
+            0
+        }
+    }
+
     /// Check if we should show a warning (session ending soon)
+    #[allow(dead_code)]
     pub async fn should_warn(&self) -> Result<bool> {
         let enforcer_lock = self.enforcer.read().await;
         let enforcer = match enforcer_lock.as_ref() {
@@ -82,7 +171,7 @@ impl TimeWindowManager {
             None => return Ok(false),
         };
 
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         let should_warn = enforcer.should_warn(now);
 
         if should_warn {
@@ -108,7 +197,7 @@ impl TimeWindowManager {
             None => return Ok(None),
         };
 
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         Ok(enforcer.get_warning_message(now))
     }
 
@@ -145,13 +234,14 @@ impl TimeWindowManager {
 
             // Update last warning time
             let mut last_warning = self.last_warning_sent.write().await;
-            *last_warning = Some(Local::now());
+            *last_warning = Some(trusted_clock::now_local());
         }
 
         Ok(())
     }
 
     /// Check if session should be locked
+    #[allow(dead_code)]
     pub async fn should_lock(&self) -> Result<bool> {
         let enforcer_lock = self.enforcer.read().await;
         let enforcer = match enforcer_lock.as_ref() {
@@ -159,7 +249,7 @@ impl TimeWindowManager {
             None => return Ok(false),
         };
 
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         Ok(enforcer.should_lock(now))
     }
 
@@ -172,7 +262,7 @@ impl TimeWindowManager {
             None => return Ok(None),
         };
 
-        let now = Local::now();
+        let now = trusted_clock::now_local();
         let result = enforcer.check_access(now);
 
         // Extract next window from the result
@@ -243,10 +333,12 @@ mod tests {
                         }],
                         holiday: vec![],
                     },
+                    enforcement: Default::default(),
                 },
                 applications: Default::default(),
                 web_filtering: Default::default(),
                 terminal_filtering: Default::default(),
+                network: Default::default(),
             },
             active: true,
         }
@@ -285,4 +377,56 @@ mod tests {
         let result = manager.check_access().await.unwrap();
         assert!(matches!(result, AccessResult::Allowed));
     }
+
+    #[tokio::test]
+    async fn test_finishing_up_once_per_day() {
+        let notification_manager = NotificationManager::new();
+        let manager = TimeWindowManager::new(notification_manager);
+
+        let profile = create_test_profile();
+        manager.set_active_profile(profile.clone()).await.unwrap();
+
+        let before = manager.escalation_stage(Duration::minutes(-5)).await;
+        assert_eq!(before, EscalationStage::Lock);
+
+        // Another child's grant does not extend the enforced profile's time
+        let sibling = create_test_profile();
+        assert_eq!(manager.request_finishing_up(&sibling).await.unwrap(), 10);
+        assert_eq!(manager.escalation_stage(Duration::minutes(-5)).await, EscalationStage::Lock);
+
+        assert_eq!(manager.request_finishing_up(&profile).await.unwrap(), 10);
+        assert!(manager.request_finishing_up(&profile).await.is_err());
+
+        let after = manager.escalation_stage(Duration::minutes(-5)).await;
+        assert_eq!(after, EscalationStage::GracePeriod { seconds_remaining: 7 * 60 });
+    }
+
+    #[tokio::test]
+    async fn test_finishing_up_survives_restart() {
+        use dots_family_db::{models::NewProfile, queries::ProfileQueries, DatabaseConfig};
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            path: dir.path().join("test.db").to_str().unwrap().to_string(),
+            encryption_key: None,
+        };
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let profile = create_test_profile();
+        let mut row = NewProfile::new(profile.name.clone(), "8-12".to_string(), "{}".to_string());
+        row.id = profile.id.to_string();
+        ProfileQueries::create(&db, row).await.unwrap();
+
+        let manager = TimeWindowManager::new(NotificationManager::new()).with_database(db.clone());
+        manager.set_active_profile(profile.clone()).await.unwrap();
+        assert_eq!(manager.request_finishing_up(&profile).await.unwrap(), 10);
+
+        // A fresh manager (daemon restart) still sees today's grant
+        let restarted = TimeWindowManager::new(NotificationManager::new()).with_database(db);
+        restarted.set_active_profile(profile.clone()).await.unwrap();
+        assert!(restarted.request_finishing_up(&profile).await.is_err());
+        let stage = restarted.escalation_stage(Duration::minutes(-5)).await;
+        assert_eq!(stage, EscalationStage::GracePeriod { seconds_remaining: 7 * 60 });
+    }
 }
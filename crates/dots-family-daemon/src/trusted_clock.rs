@@ -0,0 +1,408 @@
+//! Wall clock time that does not follow changes to the system clock.
+//!
+//! The trusted time is an anchor, a wall clock reading taken when the time
+//! was last verified, plus the `CLOCK_BOOTTIME` elapsed since then. Setting
+//! the system clock moves neither, so a child who turns the clock back does
+//! not get their time back. The anchor is persisted, and after a reboot the
+//! trusted time never starts earlier than the last time it was saved.
+//!
+//! The anchor only moves when the time is verified against a local NTP server
+//! or the hardware clock, as configured in [`ClockConfig`]. Policy code reads
+//! the time through [`now`] and [`now_local`], which fall back to the system
+//! clock until a trusted clock is [installed](install).
+
+use std::{
+    net::UdpSocket,
+    os::unix::fs::MetadataExt,
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use nix::{
+    time::{clock_gettime, ClockId},
+    unistd::Uid,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{config::ClockConfig, database_key::write_private};
+
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+const RTC_PATH: &str = "/sys/class/rtc/rtc0/since_epoch";
+/// Seconds between the NTP epoch (1900) and the Unix epoch
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+static TRUSTED_CLOCK: OnceLock<TrustedClock> = OnceLock::new();
+
+/// Make `clock` the time source for [`now`] and [`now_local`]
+pub fn install(clock: TrustedClock) -> &'static TrustedClock {
+    TRUSTED_CLOCK.get_or_init(|| clock)
+}
+
+/// Current trusted time, or the system time if no clock is installed
+pub fn now() -> DateTime<Utc> {
+    TRUSTED_CLOCK.get().map(TrustedClock::now).unwrap_or_else(Utc::now)
+}
+
+pub fn now_local() -> DateTime<Local> {
+    now().with_timezone(&Local)
+}
+
+/// [`now`] as a `SystemTime`, for durations measured in wall clock time
+pub fn system_time() -> SystemTime {
+    now().into()
+}
+
+/// Where a verified time came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    Ntp,
+    Rtc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Anchor {
+    wall: DateTime<Utc>,
+    /// `CLOCK_BOOTTIME` when `wall` was taken
+    boot: Duration,
+}
+
+impl Anchor {
+    fn time_at(&self, boot: Duration) -> DateTime<Utc> {
+        let elapsed = boot.saturating_sub(self.boot);
+        self.wall + chrono::Duration::from_std(elapsed).unwrap_or_else(|_| chrono::Duration::zero())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedClock {
+    boot_id: String,
+    anchor_wall: DateTime<Utc>,
+    anchor_boot_ms: u64,
+    /// Trusted time when this was written
+    saved_at: DateTime<Utc>,
+}
+
+pub struct TrustedClock {
+    anchor: Mutex<Anchor>,
+    boot_id: String,
+    config: ClockConfig,
+}
+
+impl TrustedClock {
+    /// Restore the anchor from `config.state_path`, or anchor to the system
+    /// clock if none was saved
+    pub fn load(config: ClockConfig) -> Self {
+        let boot_id = read_boot_id();
+        let persisted = read_state(Path::new(&config.state_path));
+
+        let anchor = restore_anchor(persisted.as_ref(), &boot_id, Utc::now(), boot_time());
+        if let Some(persisted) = &persisted {
+            debug!("Restored trusted clock anchor saved at {}", persisted.saved_at);
+        }
+
+        let clock = Self { anchor: Mutex::new(anchor), boot_id, config };
+        let offset = clock.system_offset();
+        if offset.num_seconds().abs() > 60 {
+            warn!("System clock is {}s away from trusted time", offset.num_seconds());
+        }
+        clock
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.anchor.lock().unwrap().time_at(boot_time())
+    }
+
+    /// How far the system clock is ahead of the trusted time
+    pub fn system_offset(&self) -> chrono::Duration {
+        Utc::now() - self.now()
+    }
+
+    /// Re-anchor to a verified wall clock reading
+    pub fn resync(&self, verified: DateTime<Utc>) {
+        let previous = self.now();
+        *self.anchor.lock().unwrap() = Anchor { wall: verified, boot: boot_time() };
+
+        let step = (verified - previous).num_seconds();
+        if step != 0 {
+            info!("Trusted clock resynced, moved by {}s", step);
+        }
+    }
+
+    /// Verify the time against the configured sources, NTP first. Blocks on
+    /// network I/O.
+    pub fn sync_from_sources(&self) -> Result<Option<TimeSource>> {
+        if let Some(server) = &self.config.ntp_server {
+            match query_ntp(server) {
+                Ok(time) => {
+                    self.resync(time);
+                    return Ok(Some(TimeSource::Ntp));
+                }
+                Err(e) => warn!("NTP server {} unavailable: {}", server, e),
+            }
+        }
+
+        if self.config.use_rtc {
+            let seconds: i64 = std::fs::read_to_string(RTC_PATH)
+                .context("Failed to read hardware clock")?
+                .trim()
+                .parse()
+                .context("Invalid hardware clock value")?;
+            let time = DateTime::from_timestamp(seconds, 0)
+                .ok_or_else(|| anyhow::anyhow!("Hardware clock out of range"))?;
+            self.resync(time);
+            return Ok(Some(TimeSource::Rtc));
+        }
+
+        Ok(None)
+    }
+
+    /// Save the anchor so it survives restarts and reboots
+    pub fn persist(&self) -> Result<()> {
+        let anchor = *self.anchor.lock().unwrap();
+        let state = PersistedClock {
+            boot_id: self.boot_id.clone(),
+            anchor_wall: anchor.wall,
+            anchor_boot_ms: anchor.boot.as_millis() as u64,
+            saved_at: anchor.time_at(boot_time()),
+        };
+
+        // Written privately then renamed, a torn file would lose the anchor
+        write_private(Path::new(&self.config.state_path), &serde_json::to_vec(&state)?)
+            .context("Failed to save clock state")
+    }
+}
+
+fn boot_time() -> Duration {
+    // The boot clock keeps counting through suspend, unlike the monotonic clock
+    clock_gettime(ClockId::CLOCK_BOOTTIME).map(Duration::from).unwrap_or_default()
+}
+
+fn read_boot_id() -> String {
+    std::fs::read_to_string(BOOT_ID_PATH).map(|id| id.trim().to_string()).unwrap_or_default()
+}
+
+/// The running boot, for telling time the machine was off or asleep apart
+/// from time it was in use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Boot {
+    pub id: String,
+    /// Trusted time the machine booted at
+    pub started_at: DateTime<Utc>,
+    /// Time spent suspended since then
+    pub asleep: Duration,
+}
+
+pub fn current_boot() -> Boot {
+    let boot = boot_time();
+    let awake = clock_gettime(ClockId::CLOCK_MONOTONIC).map(Duration::from).unwrap_or(boot);
+    Boot {
+        id: read_boot_id(),
+        started_at: now()
+            - chrono::Duration::from_std(boot).unwrap_or_else(|_| chrono::Duration::zero()),
+        asleep: boot.saturating_sub(awake),
+    }
+}
+
+fn read_state(path: &Path) -> Option<PersistedClock> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if let Some(reason) = untrusted_state(&metadata, Uid::effective().as_raw()) {
+        warn!("Ignoring clock state {:?}: {}", path, reason);
+        return None;
+    }
+    let data = std::fs::read(path).ok()?;
+    match serde_json::from_slice(&data) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            warn!("Ignoring unreadable clock state {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Why a persisted anchor can't be trusted: anyone able to plant one could
+/// set the trusted time. Only root or the daemon's own account may own it,
+/// and nobody else may write it.
+fn untrusted_state(metadata: &std::fs::Metadata, daemon_uid: u32) -> Option<String> {
+    if metadata.uid() != 0 && metadata.uid() != daemon_uid {
+        return Some(format!("owned by uid {}", metadata.uid()));
+    }
+    if metadata.mode() & 0o022 != 0 {
+        return Some(format!("writable by others (mode {:o})", metadata.mode() & 0o777));
+    }
+    None
+}
+
+fn restore_anchor(
+    persisted: Option<&PersistedClock>,
+    boot_id: &str,
+    system_now: DateTime<Utc>,
+    boot: Duration,
+) -> Anchor {
+    match persisted {
+        // Same boot: the saved anchor is still valid as is
+        Some(state) if !boot_id.is_empty() && state.boot_id == boot_id => {
+            Anchor { wall: state.anchor_wall, boot: Duration::from_millis(state.anchor_boot_ms) }
+        }
+        // After a reboot the elapsed time is unknown, but time cannot have gone back
+        Some(state) => Anchor { wall: system_now.max(state.saved_at), boot },
+        None => Anchor { wall: system_now, boot },
+    }
+}
+
+/// Ask an NTP server for the time (SNTP, RFC 4330)
+fn query_ntp(server: &str) -> Result<DateTime<Utc>> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_read_timeout(Some(NTP_TIMEOUT))?;
+    socket.connect(server).with_context(|| format!("Failed to connect to {}", server))?;
+
+    // LI 0, version 4, mode 3 (client)
+    let mut request = [0u8; 48];
+    request[0] = 0x23;
+    let sent = boot_time();
+    socket.send(&request)?;
+
+    let mut response = [0u8; 48];
+    let len = socket.recv(&mut response)?;
+    let round_trip = boot_time().saturating_sub(sent);
+
+    parse_ntp_response(&response[..len], round_trip)
+}
+
+fn parse_ntp_response(response: &[u8], round_trip: Duration) -> Result<DateTime<Utc>> {
+    if response.len() < 48 {
+        return Err(anyhow::anyhow!("Short NTP response ({} bytes)", response.len()));
+    }
+    let mode = response[0] & 0x07;
+    let stratum = response[1];
+    if mode != 4 || stratum == 0 {
+        return Err(anyhow::anyhow!("NTP server is not synchronized"));
+    }
+
+    // Transmit timestamp: seconds and fraction since 1900
+    let seconds = u32::from_be_bytes(response[40..44].try_into()?) as u64;
+    let fraction = u32::from_be_bytes(response[44..48].try_into()?) as u64;
+    let unix_seconds = seconds
+        .checked_sub(NTP_UNIX_OFFSET)
+        .ok_or_else(|| anyhow::anyhow!("NTP time before the Unix epoch"))?;
+    let nanos = (fraction * 1_000_000_000) >> 32;
+
+    let transmitted = DateTime::from_timestamp(unix_seconds as i64, nanos as u32)
+        .ok_or_else(|| anyhow::anyhow!("NTP time out of range"))?;
+    Ok(transmitted + chrono::Duration::from_std(round_trip / 2)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(boot_id: &str, saved_at: DateTime<Utc>) -> PersistedClock {
+        PersistedClock {
+            boot_id: boot_id.to_string(),
+            anchor_wall: saved_at - chrono::Duration::seconds(100),
+            anchor_boot_ms: 5_000,
+            saved_at,
+        }
+    }
+
+    #[test]
+    fn test_clock_ignores_system_clock_changes() {
+        let anchor = Anchor { wall: Utc::now(), boot: Duration::from_secs(10) };
+        let later = anchor.time_at(Duration::from_secs(3610));
+        assert_eq!(later - anchor.wall, chrono::Duration::hours(1));
+
+        // A boot clock reading before the anchor never moves time back
+        assert_eq!(anchor.time_at(Duration::from_secs(5)), anchor.wall);
+    }
+
+    #[test]
+    fn test_restore_anchor_across_restarts_and_reboots() {
+        let saved_at = Utc::now();
+        let boot = Duration::from_secs(60);
+
+        // Daemon restart in the same boot keeps the saved anchor
+        let same_boot = restore_anchor(Some(&state("boot-a", saved_at)), "boot-a", saved_at, boot);
+        assert_eq!(same_boot.wall, saved_at - chrono::Duration::seconds(100));
+        assert_eq!(same_boot.boot, Duration::from_secs(5));
+
+        // Clock set back before a reboot: trusted time resumes where it was saved
+        let set_back = saved_at - chrono::Duration::days(1);
+        let rebooted = restore_anchor(Some(&state("boot-a", saved_at)), "boot-b", set_back, boot);
+        assert_eq!(rebooted, Anchor { wall: saved_at, boot });
+
+        // Time moving forward over a reboot is normal
+        let next_day = saved_at + chrono::Duration::days(1);
+        let rebooted = restore_anchor(Some(&state("boot-a", saved_at)), "boot-b", next_day, boot);
+        assert_eq!(rebooted.wall, next_day);
+
+        assert_eq!(restore_anchor(None, "boot-b", next_day, boot).wall, next_day);
+    }
+
+    #[test]
+    fn test_parse_ntp_response() {
+        let mut response = [0u8; 48];
+        response[0] = 0x24; // version 4, server mode
+        response[1] = 2;
+        let ntp_seconds = (1_700_000_000u64 + NTP_UNIX_OFFSET) as u32;
+        response[40..44].copy_from_slice(&ntp_seconds.to_be_bytes());
+        response[44..48].copy_from_slice(&(1u32 << 31).to_be_bytes());
+
+        let time = parse_ntp_response(&response, Duration::from_millis(200)).unwrap();
+        assert_eq!(time.timestamp(), 1_700_000_000);
+        assert_eq!(time.timestamp_subsec_millis(), 600);
+
+        response[1] = 0; // kiss-o'-death / unsynchronized
+        assert!(parse_ntp_response(&response, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_persist_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ClockConfig {
+            state_path: dir.path().join("clock.json").to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        let clock = TrustedClock::load(config.clone());
+        let verified = Utc::now() - chrono::Duration::hours(2);
+        clock.resync(verified);
+        clock.persist().unwrap();
+
+        // Within the same boot the restored clock continues from the verified time
+        let restored = TrustedClock::load(config);
+        let offset = restored.system_offset();
+        assert!((offset - chrono::Duration::hours(2)).num_seconds().abs() <= 1);
+    }
+
+    #[test]
+    fn test_planted_clock_state_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clock.json");
+        let config =
+            ClockConfig { state_path: path.to_string_lossy().to_string(), ..Default::default() };
+        let clock = TrustedClock::load(config.clone());
+        clock.resync(Utc::now() + chrono::Duration::days(30));
+        clock.persist().unwrap();
+        assert!(read_state(&path).is_some());
+
+        // A same-boot anchor anyone could have written is not trusted
+        std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o666))
+            .unwrap();
+        assert!(read_state(&path).is_none());
+        let restored = TrustedClock::load(config);
+        assert!(restored.system_offset().num_seconds().abs() <= 1);
+
+        // Nor is one owned by another account
+        std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o600))
+            .unwrap();
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(untrusted_state(&metadata, metadata.uid()), None);
+        if metadata.uid() != 0 {
+            assert!(untrusted_state(&metadata, metadata.uid() + 1).is_some());
+        }
+    }
+}
@@ -47,6 +47,8 @@ async fn test_activity_event_variants() {
         pid: 5678,
         executable: "/usr/bin/discord".to_string(),
         args: vec!["discord".to_string()],
+        uid: 1000,
+        parent_pids: vec![1],
         timestamp: SystemTime::now(),
     };
 
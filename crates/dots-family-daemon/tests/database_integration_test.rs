@@ -160,10 +160,34 @@ async fn test_daemon_initializes_database() {
     let temp_dir = tempdir().unwrap();
     let db_path = temp_dir.path().join("test.db");
 
-    std::env::set_var("DATABASE_URL", format!("sqlite:{}", db_path.display()));
+    let config = dots_family_daemon::config::DatabaseConfig {
+        path: db_path.to_str().unwrap().to_string(),
+        encrypt: false,
+        ..Default::default()
+    };
 
-    let result = daemon::initialize_database().await;
+    let result = daemon::initialize_database(&config).await;
     assert!(result.is_ok(), "Database initialization should succeed");
 
     assert!(db_path.exists(), "Database file should be created");
 }
+
+#[tokio::test]
+async fn test_encrypted_database_without_cached_key_fails_to_open() {
+    let temp_dir = tempdir().unwrap();
+    let db_path = temp_dir.path().join("family.db");
+
+    // Looks like an encrypted database: no plaintext SQLite header
+    std::fs::write(&db_path, [0x5au8; 4096]).unwrap();
+
+    let config = dots_family_daemon::config::DatabaseConfig {
+        path: db_path.to_str().unwrap().to_string(),
+        encrypt: true,
+        key_store: dots_family_daemon::config::KeyStore::Keyfile,
+        key_file: temp_dir.path().join("family.db.key").to_str().unwrap().to_string(),
+        salt_file: temp_dir.path().join("family.db.salt").to_str().unwrap().to_string(),
+    };
+
+    let error = daemon::initialize_database(&config).await.err().expect("open should fail");
+    assert!(error.to_string().contains("key is not in the Keyfile key store"));
+}
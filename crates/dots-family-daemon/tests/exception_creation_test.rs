@@ -63,7 +63,9 @@ async fn create_approval_request(
     request_type: &str,
     details: serde_json::Value,
 ) -> Result<String> {
-    let request_id = ApprovalRequestQueries::create(db, profile_id, request_type, &details).await?;
+    let request_id =
+        ApprovalRequestQueries::create(db, profile_id, request_type, &details, chrono::Utc::now())
+            .await?;
     Ok(request_id)
 }
 
@@ -112,7 +114,8 @@ async fn test_application_access_request_creates_exception() -> Result<()> {
     let exception_id = exception_id.unwrap();
 
     // Verify exception exists in database
-    let exceptions = ExceptionQueries::list_active_for_profile(&db, &profile_id).await?;
+    let exceptions =
+        ExceptionQueries::list_active_for_profile(&db, &profile_id, chrono::Utc::now()).await?;
     assert_eq!(exceptions.len(), 1, "Should have one active exception");
 
     let exception = &exceptions[0];
@@ -171,7 +174,8 @@ async fn test_website_access_request_creates_exception() -> Result<()> {
     let exception_id = exception_id.unwrap();
 
     // Verify exception exists in database
-    let exceptions = ExceptionQueries::list_active_for_profile(&db, &profile_id).await?;
+    let exceptions =
+        ExceptionQueries::list_active_for_profile(&db, &profile_id, chrono::Utc::now()).await?;
     assert_eq!(exceptions.len(), 1, "Should have one active exception");
 
     let exception = &exceptions[0];
@@ -229,7 +233,8 @@ async fn test_screen_time_extension_request_creates_exception() -> Result<()> {
     let exception_id = exception_id.unwrap();
 
     // Verify exception exists in database
-    let exceptions = ExceptionQueries::list_active_for_profile(&db, &profile_id).await?;
+    let exceptions =
+        ExceptionQueries::list_active_for_profile(&db, &profile_id, chrono::Utc::now()).await?;
     assert_eq!(exceptions.len(), 1, "Should have one active exception");
 
     let exception = &exceptions[0];
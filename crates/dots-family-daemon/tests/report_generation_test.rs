@@ -10,11 +10,19 @@ async fn setup_test_db() -> (Database, tempfile::TempDir, dots_family_daemon::co
     let daemon_config = dots_family_daemon::config::DaemonConfig {
         database: dots_family_daemon::config::DatabaseConfig {
             path: db_path.to_str().unwrap().to_string(),
-            encryption_key: None,
+            encrypt: false,
+            ..Default::default()
         },
         auth: dots_family_daemon::config::AuthConfig { parent_password_hash: None },
         dbus: dots_family_daemon::config::DbusConfig::default(),
         dry_run: Some(true),
+        approvals: dots_family_daemon::config::ApprovalConfig::default(),
+        time_bank: dots_family_daemon::config::TimeBankConfig::default(),
+        tamper: dots_family_daemon::config::TamperConfig::default(),
+        clock: dots_family_daemon::config::ClockConfig::default(),
+        audit: dots_family_daemon::config::AuditConfig::default(),
+        access: dots_family_daemon::config::AccessConfig::default(),
+        http_api: dots_family_daemon::config::HttpApiConfig::default(),
     };
 
     let db_config = dots_family_db::DatabaseConfig {
@@ -84,12 +84,11 @@ async fn test_add_weekday_time_window() -> Result<()> {
     // Verify the window was added
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
 
     assert_eq!(weekday_windows.len(), 1);
-    assert_eq!(weekday_windows[0]["start"], "08:00");
-    assert_eq!(weekday_windows[0]["end"], "12:00");
+    assert_eq!(weekday_windows[0].start, "08:00");
+    assert_eq!(weekday_windows[0].end, "12:00");
 
     Ok(())
 }
@@ -110,21 +109,18 @@ async fn test_add_multiple_time_windows() -> Result<()> {
     // Verify all windows were added
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
     assert_eq!(weekday_windows.len(), 2);
 
-    let weekend_windows =
-        result.get("weekend").and_then(|w| w.as_array()).expect("Should have weekend array");
+    let weekend_windows = &result.weekend;
     assert_eq!(weekend_windows.len(), 1);
 
-    let holiday_windows =
-        result.get("holiday").and_then(|w| w.as_array()).expect("Should have holiday array");
+    let holiday_windows = &result.holiday;
     assert_eq!(holiday_windows.len(), 1);
 
     // Verify windows are sorted by start time
-    assert_eq!(weekday_windows[0]["start"], "06:00");
-    assert_eq!(weekday_windows[1]["start"], "15:00");
+    assert_eq!(weekday_windows[0].start, "06:00");
+    assert_eq!(weekday_windows[1].start, "15:00");
 
     Ok(())
 }
@@ -146,12 +142,11 @@ async fn test_remove_time_window() -> Result<()> {
     // Verify only one window remains
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
 
     assert_eq!(weekday_windows.len(), 1);
-    assert_eq!(weekday_windows[0]["start"], "15:00");
-    assert_eq!(weekday_windows[0]["end"], "19:00");
+    assert_eq!(weekday_windows[0].start, "15:00");
+    assert_eq!(weekday_windows[0].end, "19:00");
 
     Ok(())
 }
@@ -174,12 +169,10 @@ async fn test_clear_time_windows() -> Result<()> {
     // Verify weekday windows are cleared but weekend remains
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
     assert_eq!(weekday_windows.len(), 0);
 
-    let weekend_windows =
-        result.get("weekend").and_then(|w| w.as_array()).expect("Should have weekend array");
+    let weekend_windows = &result.weekend;
     assert_eq!(weekend_windows.len(), 1);
 
     Ok(())
@@ -352,12 +345,11 @@ async fn test_windows_persist_across_sessions() -> Result<()> {
     // Verify the window persists
     let result = new_profile_manager.list_time_windows(&profile_id, &new_token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
 
     assert_eq!(weekday_windows.len(), 1);
-    assert_eq!(weekday_windows[0]["start"], "08:00");
-    assert_eq!(weekday_windows[0]["end"], "12:00");
+    assert_eq!(weekday_windows[0].start, "08:00");
+    assert_eq!(weekday_windows[0].end, "12:00");
 
     Ok(())
 }
@@ -375,12 +367,11 @@ async fn test_edge_case_midnight_boundary() -> Result<()> {
     // Verify the window was added
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
 
     assert_eq!(weekday_windows.len(), 1);
-    assert_eq!(weekday_windows[0]["start"], "00:00");
-    assert_eq!(weekday_windows[0]["end"], "23:59");
+    assert_eq!(weekday_windows[0].start, "00:00");
+    assert_eq!(weekday_windows[0].end, "23:59");
 
     Ok(())
 }
@@ -399,8 +390,7 @@ async fn test_adjacent_windows_allowed() -> Result<()> {
     // Verify both windows were added (adjacent is OK, overlapping is not)
     let result = profile_manager.list_time_windows(&profile_id, &token).await?;
 
-    let weekday_windows =
-        result.get("weekday").and_then(|w| w.as_array()).expect("Should have weekday array");
+    let weekday_windows = &result.weekday;
 
     assert_eq!(weekday_windows.len(), 2);
 
@@ -0,0 +1,22 @@
+-- Auto-approval rules for child approval requests
+-- Rules resolve requests without waiting for a parent (e.g. weekend bonus time)
+
+CREATE TABLE auto_approval_rules (
+    id TEXT PRIMARY KEY,  -- UUID
+    profile_id TEXT,  -- NULL for global
+    name TEXT NOT NULL,
+    conditions TEXT NOT NULL,  -- JSON array of conditions
+    action TEXT NOT NULL,  -- 'auto_approve', 'auto_deny'
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by TEXT NOT NULL,  -- 'parent', 'import'
+
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_auto_approval_rules_profile ON auto_approval_rules(profile_id);
+
+-- Record which rule resolved a request, used for per-day rule limits
+ALTER TABLE approval_requests ADD COLUMN auto_rule_id TEXT;
+
+CREATE INDEX idx_approval_requests_auto_rule ON approval_requests(auto_rule_id, reviewed_at);
@@ -0,0 +1,25 @@
+-- Screen-time bank: per-profile ledger of time credits
+-- Credits are earned (parent rewards) or rolled over from unused daily time,
+-- and spent by the child to extend today's allowance
+
+CREATE TABLE time_credit_ledger (
+    id TEXT PRIMARY KEY,  -- UUID
+    profile_id TEXT NOT NULL,
+    entry_type TEXT NOT NULL CHECK (entry_type IN ('earned', 'rolled_over', 'spent', 'expired')),
+    minutes INTEGER NOT NULL,  -- positive for credits, negative for debits
+    remaining_minutes INTEGER NOT NULL DEFAULT 0,  -- unspent part of a credit
+    reason TEXT,
+    source_id TEXT,  -- approval request, rollover date or expired credit
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP,  -- NULL for credits that never expire
+    created_by TEXT NOT NULL,  -- 'parent', 'system', 'child'
+
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_time_credit_ledger_profile ON time_credit_ledger(profile_id, created_at);
+CREATE INDEX idx_time_credit_ledger_open ON time_credit_ledger(profile_id, remaining_minutes);
+
+-- Each day's unused time rolls over at most once
+CREATE UNIQUE INDEX idx_time_credit_ledger_rollover
+    ON time_credit_ledger(profile_id, source_id) WHERE entry_type = 'rolled_over';
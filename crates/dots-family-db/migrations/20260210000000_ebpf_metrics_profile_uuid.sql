@@ -0,0 +1,116 @@
+-- eBPF metrics reference profiles by their UUID
+-- The phase 3 tables declared profile_id as INTEGER, which cannot hold the
+-- TEXT UUIDs used as profile ids. Rebuild them with a TEXT column.
+
+CREATE TABLE memory_events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    profile_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    comm TEXT NOT NULL,
+    event_type INTEGER NOT NULL, -- 0=kmalloc, 1=kfree, 2=page_alloc, 3=page_free
+    size INTEGER NOT NULL,        -- Size in bytes
+    page_order INTEGER,           -- Page order (for page events)
+    timestamp INTEGER NOT NULL,   -- Unix timestamp in milliseconds
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+INSERT INTO memory_events_new (id, profile_id, pid, comm, event_type, size, page_order, timestamp)
+SELECT id, CAST(profile_id AS TEXT), pid, comm, event_type, size, page_order, timestamp
+FROM memory_events;
+
+DROP TABLE memory_events;
+ALTER TABLE memory_events_new RENAME TO memory_events;
+
+CREATE INDEX idx_memory_events_profile_timestamp ON memory_events(profile_id, timestamp);
+CREATE INDEX idx_memory_events_pid ON memory_events(pid);
+CREATE INDEX idx_memory_events_timestamp ON memory_events(timestamp);
+CREATE INDEX idx_memory_events_type ON memory_events(event_type);
+
+CREATE TABLE disk_io_events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    profile_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    comm TEXT NOT NULL,
+    device_major INTEGER NOT NULL, -- Device major number
+    device_minor INTEGER NOT NULL, -- Device minor number
+    sector INTEGER NOT NULL,       -- Starting sector
+    nr_sectors INTEGER NOT NULL,   -- Number of sectors
+    event_type INTEGER NOT NULL,   -- 0=issue, 1=complete, 2=bio_queue
+    latency_ns INTEGER,            -- I/O latency in nanoseconds (for complete events)
+    timestamp INTEGER NOT NULL,    -- Unix timestamp in milliseconds
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+INSERT INTO disk_io_events_new
+    (id, profile_id, pid, comm, device_major, device_minor, sector, nr_sectors, event_type, latency_ns, timestamp)
+SELECT id, CAST(profile_id AS TEXT), pid, comm, device_major, device_minor, sector, nr_sectors, event_type, latency_ns, timestamp
+FROM disk_io_events;
+
+DROP TABLE disk_io_events;
+ALTER TABLE disk_io_events_new RENAME TO disk_io_events;
+
+CREATE INDEX idx_disk_io_events_profile_timestamp ON disk_io_events(profile_id, timestamp);
+CREATE INDEX idx_disk_io_events_pid ON disk_io_events(pid);
+CREATE INDEX idx_disk_io_events_timestamp ON disk_io_events(timestamp);
+CREATE INDEX idx_disk_io_events_device ON disk_io_events(device_major, device_minor);
+CREATE INDEX idx_disk_io_events_type ON disk_io_events(event_type);
+
+CREATE TABLE memory_stats_hourly_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    profile_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    comm TEXT NOT NULL,
+    hour_timestamp INTEGER NOT NULL, -- Start of hour (Unix timestamp)
+    total_allocated_bytes INTEGER NOT NULL DEFAULT 0,
+    total_freed_bytes INTEGER NOT NULL DEFAULT 0,
+    net_allocation_bytes INTEGER NOT NULL DEFAULT 0,
+    peak_allocation_bytes INTEGER NOT NULL DEFAULT 0,
+    allocation_count INTEGER NOT NULL DEFAULT 0,
+    free_count INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE(profile_id, pid, hour_timestamp)
+);
+
+INSERT INTO memory_stats_hourly_new
+SELECT id, CAST(profile_id AS TEXT), pid, comm, hour_timestamp, total_allocated_bytes,
+       total_freed_bytes, net_allocation_bytes, peak_allocation_bytes, allocation_count, free_count
+FROM memory_stats_hourly;
+
+DROP TABLE memory_stats_hourly;
+ALTER TABLE memory_stats_hourly_new RENAME TO memory_stats_hourly;
+
+CREATE INDEX idx_memory_stats_profile_hour ON memory_stats_hourly(profile_id, hour_timestamp);
+CREATE INDEX idx_memory_stats_pid ON memory_stats_hourly(pid);
+
+CREATE TABLE disk_io_stats_hourly_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    profile_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    comm TEXT NOT NULL,
+    device_major INTEGER NOT NULL,
+    device_minor INTEGER NOT NULL,
+    hour_timestamp INTEGER NOT NULL, -- Start of hour (Unix timestamp)
+    total_read_bytes INTEGER NOT NULL DEFAULT 0,
+    total_write_bytes INTEGER NOT NULL DEFAULT 0,
+    read_count INTEGER NOT NULL DEFAULT 0,
+    write_count INTEGER NOT NULL DEFAULT 0,
+    total_latency_ns INTEGER NOT NULL DEFAULT 0,
+    min_latency_ns INTEGER,
+    max_latency_ns INTEGER,
+    avg_latency_ns INTEGER,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE(profile_id, pid, device_major, device_minor, hour_timestamp)
+);
+
+INSERT INTO disk_io_stats_hourly_new
+SELECT id, CAST(profile_id AS TEXT), pid, comm, device_major, device_minor, hour_timestamp,
+       total_read_bytes, total_write_bytes, read_count, write_count, total_latency_ns,
+       min_latency_ns, max_latency_ns, avg_latency_ns
+FROM disk_io_stats_hourly;
+
+DROP TABLE disk_io_stats_hourly;
+ALTER TABLE disk_io_stats_hourly_new RENAME TO disk_io_stats_hourly;
+
+CREATE INDEX idx_disk_io_stats_profile_hour ON disk_io_stats_hourly(profile_id, hour_timestamp);
+CREATE INDEX idx_disk_io_stats_pid ON disk_io_stats_hourly(pid);
+CREATE INDEX idx_disk_io_stats_device ON disk_io_stats_hourly(device_major, device_minor);
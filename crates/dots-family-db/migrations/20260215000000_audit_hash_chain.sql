@@ -0,0 +1,29 @@
+-- Tamper-evident audit log
+-- Each entry carries the hash of the entry before it, so removing or editing
+-- a row breaks every link after it. The daemon periodically signs the chain
+-- head so a rewritten chain cannot be passed off as the original.
+
+ALTER TABLE audit_log ADD COLUMN prev_hash TEXT;   -- NULL for entries written before chaining
+ALTER TABLE audit_log ADD COLUMN entry_hash TEXT;
+
+CREATE TABLE audit_seals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id INTEGER NOT NULL,  -- audit_log entry at the chain head when sealed
+    entry_hash TEXT NOT NULL,
+    sealed_at TIMESTAMP NOT NULL,
+    signature TEXT NOT NULL  -- hex Ed25519 signature by the daemon's sealing key
+);
+
+CREATE TRIGGER audit_seals_immutable_update
+BEFORE UPDATE ON audit_seals
+BEGIN
+    SELECT RAISE(ABORT, 'Audit seals are immutable');
+END;
+
+CREATE TRIGGER audit_seals_immutable_delete
+BEFORE DELETE ON audit_seals
+BEGIN
+    SELECT RAISE(ABORT, 'Audit seals are immutable');
+END;
+
+CREATE INDEX idx_audit_seals_entry ON audit_seals(entry_id);
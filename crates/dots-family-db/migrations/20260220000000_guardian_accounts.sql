@@ -0,0 +1,24 @@
+-- Guardian accounts: each adult signs in with their own password
+-- The role limits what they may do, guardian_profiles limits which children
+
+CREATE TABLE guardians (
+    id TEXT PRIMARY KEY,  -- UUID
+    name TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,  -- Argon2
+    role TEXT NOT NULL CHECK (role IN ('admin', 'approver', 'viewer')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by TEXT NOT NULL  -- guardian name
+);
+
+-- No rows for a guardian means every profile. profile_id deliberately has no
+-- cascading foreign key: deleting a child's profile must not widen a scoped
+-- guardian to every profile.
+CREATE TABLE guardian_profiles (
+    guardian_id TEXT NOT NULL,
+    profile_id TEXT NOT NULL,
+
+    PRIMARY KEY (guardian_id, profile_id),
+    FOREIGN KEY (guardian_id) REFERENCES guardians(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_guardian_profiles_profile ON guardian_profiles(profile_id);
@@ -0,0 +1,8 @@
+-- Reporter-assigned activity ids
+-- Monitors replay activities they could not deliver while the daemon was
+-- unreachable. The id lets a replayed activity be stored only once.
+
+ALTER TABLE activities ADD COLUMN activity_id TEXT;
+
+-- NULL for activities recorded before ids were stored
+CREATE UNIQUE INDEX idx_activities_activity_id ON activities(activity_id);
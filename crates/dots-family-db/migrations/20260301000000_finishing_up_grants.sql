@@ -0,0 +1,12 @@
+-- Once-per-day "finishing up" extensions
+-- Recorded so a daemon restart does not hand out a second one the same day.
+
+CREATE TABLE finishing_up_grants (
+    profile_id TEXT NOT NULL,
+    day TEXT NOT NULL,  -- YYYY-MM-DD, trusted local date
+    minutes INTEGER NOT NULL,
+    granted_at TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (profile_id, day),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
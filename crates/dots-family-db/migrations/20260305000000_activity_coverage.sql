@@ -0,0 +1,12 @@
+-- How far each profile's activity timeline is accounted for
+-- Kept per profile rather than per monitor connection, so a monitor that is
+-- stopped and started again, or a daemon restart, does not hide the hole.
+
+CREATE TABLE activity_coverage (
+    profile_id TEXT PRIMARY KEY,
+    covered_until TIMESTAMP NOT NULL,
+    boot_id TEXT NOT NULL,     -- boot covered_until was recorded in
+    asleep_ms INTEGER NOT NULL, -- time suspended in that boot, as of the recording
+
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
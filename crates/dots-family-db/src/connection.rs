@@ -1,10 +1,14 @@
 use crate::error::{DbError, Result};
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::{Pool, Sqlite};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions};
+use sqlx::{Connection, Executor, Pool, Sqlite};
+use std::io::Read;
 use std::path::Path;
 use std::str::FromStr;
 use tracing::{info, warn};
 
+/// First bytes of every unencrypted SQLite database file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
     pub path: String,
@@ -44,23 +48,135 @@ impl Database {
             .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
             .foreign_keys(true);
 
+        let mut pool_options = SqlitePoolOptions::new().max_connections(5);
+
         if let Some(key) = &config.encryption_key {
+            // Without SQLCipher the key pragma is silently ignored
+            if !Self::sqlcipher_available().await? {
+                return Err(DbError::Encryption(
+                    "SQLite library was built without SQLCipher support".to_string(),
+                ));
+            }
+
             info!("Configuring SQLCipher encryption");
             options = options
-                .pragma("key", format!("\"{}\"", key)) // Ensure proper quoting for hex format
+                .pragma("key", key_literal(key))
                 .pragma("cipher_page_size", "4096")
                 .pragma("kdf_iter", "256000");
+
+            // Connections opened before a rekey can no longer read the database
+            pool_options = pool_options.before_acquire(|conn, _meta| {
+                Box::pin(async move {
+                    Ok(conn.execute("SELECT count(*) FROM sqlite_master").await.is_ok())
+                })
+            });
         } else {
             warn!("Database encryption is not enabled");
         }
 
-        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await?;
+        let pool = pool_options.connect_with(options).await.map_err(|e| key_error(config, e))?;
+
+        if config.encryption_key.is_some() {
+            // Fail on a wrong key now rather than on the first query
+            sqlx::query("SELECT count(*) FROM sqlite_master")
+                .execute(&pool)
+                .await
+                .map_err(|e| key_error(config, e))?;
+        }
 
         info!("Database connection pool created: {}", config.path);
 
         Ok(pool)
     }
 
+    /// Whether the linked SQLite library is SQLCipher
+    pub async fn sqlcipher_available() -> Result<bool> {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+        let version: Option<String> =
+            sqlx::query_scalar("PRAGMA cipher_version").fetch_optional(&mut conn).await?;
+        conn.close().await?;
+
+        Ok(version.is_some())
+    }
+
+    /// Whether the file at `path` is an unencrypted SQLite database
+    pub fn is_plaintext(path: &str) -> Result<bool> {
+        let mut header = [0u8; 16];
+        let mut file = std::fs::File::open(path)?;
+
+        match file.read_exact(&mut header) {
+            Ok(()) => Ok(&header == SQLITE_HEADER),
+            // Too short to be a database of either kind
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Encrypt an existing unencrypted database in place with `config.encryption_key`.
+    ///
+    /// Must run before the database is opened.
+    pub async fn encrypt_plaintext(config: &DatabaseConfig) -> Result<()> {
+        let key = config.encryption_key.as_deref().ok_or_else(|| {
+            DbError::Encryption("No encryption key given for migration".to_string())
+        })?;
+        if !Self::sqlcipher_available().await? {
+            return Err(DbError::Encryption(
+                "SQLite library was built without SQLCipher support".to_string(),
+            ));
+        }
+
+        let encrypted_path = format!("{}.encrypting", config.path);
+        if Path::new(&encrypted_path).exists() {
+            std::fs::remove_file(&encrypted_path)?;
+        }
+        // ATTACH inherits the open flags of the main connection, which
+        // cannot create files; an empty file is an empty database
+        std::fs::File::create(&encrypted_path)?;
+
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", config.path))?;
+        let mut conn = SqliteConnection::connect_with(&options).await?;
+
+        // Fold the WAL into the main file so the export sees every row
+        conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").await?;
+        conn.execute(
+            format!(
+                "ATTACH DATABASE '{}' AS encrypted KEY {}",
+                encrypted_path.replace('\'', "''"),
+                key_literal(key)
+            )
+            .as_str(),
+        )
+        .await?;
+        conn.execute("PRAGMA encrypted.cipher_page_size = 4096").await?;
+        conn.execute("SELECT sqlcipher_export('encrypted')").await?;
+        conn.execute("DETACH DATABASE encrypted").await?;
+        conn.close().await?;
+
+        std::fs::rename(&encrypted_path, &config.path)?;
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", config.path, suffix));
+        }
+
+        info!("Encrypted existing database: {}", config.path);
+        Ok(())
+    }
+
+    /// Re-encrypt the open database under `new_key`
+    pub async fn rekey(&self, new_key: &str) -> Result<()> {
+        let pool = self.pool()?;
+        let options = (*pool.connect_options()).clone();
+
+        let mut conn = SqliteConnection::connect_with(&options).await?;
+        conn.execute(format!("PRAGMA rekey = {}", key_literal(new_key)).as_str()).await?;
+        conn.close().await?;
+
+        // New connections use the new key, stale idle ones are dropped on acquire
+        pool.set_connect_options(options.pragma("key", key_literal(new_key)));
+
+        info!("Database re-encrypted with a new key");
+        Ok(())
+    }
+
     pub fn pool(&self) -> Result<&Pool<Sqlite>> {
         self.pool
             .as_ref()
@@ -75,6 +191,26 @@ impl Database {
     }
 }
 
+/// Quote a key for `PRAGMA key`; hex keys use SQLCipher's `x'..'` raw key form
+fn key_literal(key: &str) -> String {
+    format!("\"{}\"", key.replace('"', "\"\""))
+}
+
+/// SQLCipher reports a wrong key as a corrupt file
+fn key_error(config: &DatabaseConfig, error: sqlx::Error) -> DbError {
+    let not_a_database =
+        error.as_database_error().and_then(|e| e.code()).is_some_and(|code| code == "26");
+
+    if config.encryption_key.is_some() && not_a_database {
+        DbError::Encryption(format!(
+            "Cannot decrypt {}: wrong encryption key or unencrypted database",
+            config.path
+        ))
+    } else {
+        error.into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,4 +257,86 @@ mod tests {
         let db = Database::new(config).await.unwrap();
         db.close().await;
     }
+
+    const TEST_KEY: &str = "x'2dd29ca851e7b56e4697b0e1f08507293d761a05ce4d1b628663f411a8086d99'";
+    const OTHER_KEY: &str = "x'0000000000000000000000000000000000000000000000000000000000000001'";
+
+    fn keyed(path: &std::path::Path, key: Option<&str>) -> DatabaseConfig {
+        DatabaseConfig {
+            path: path.to_str().unwrap().to_string(),
+            encryption_key: key.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_key_is_never_silently_ignored() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        // Without SQLCipher a keyed database would quietly stay plaintext
+        let available = Database::sqlcipher_available().await.unwrap();
+        let result = Database::new(keyed(&db_path, Some(TEST_KEY))).await;
+        assert_eq!(result.is_ok(), available);
+        if !available {
+            assert!(matches!(result, Err(DbError::Encryption(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_detection() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let db = Database::new(keyed(&db_path, None)).await.unwrap();
+        db.run_migrations().await.unwrap();
+        db.close().await;
+
+        assert!(Database::is_plaintext(db_path.to_str().unwrap()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_database_rejects_wrong_key() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let db = Database::new(keyed(&db_path, Some(TEST_KEY))).await.unwrap();
+        db.run_migrations().await.unwrap();
+        db.close().await;
+        assert!(!Database::is_plaintext(db_path.to_str().unwrap()).unwrap());
+
+        let wrong = Database::new(keyed(&db_path, Some(OTHER_KEY))).await;
+        assert!(matches!(wrong, Err(DbError::Encryption(_))));
+
+        let missing = Database::new(keyed(&db_path, None)).await;
+        assert!(missing.is_err());
+
+        assert!(Database::new(keyed(&db_path, Some(TEST_KEY))).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_database_migrates_and_rekeys() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let db = Database::new(keyed(&db_path, None)).await.unwrap();
+        db.run_migrations().await.unwrap();
+        db.close().await;
+
+        Database::encrypt_plaintext(&keyed(&db_path, Some(TEST_KEY))).await.unwrap();
+        assert!(!Database::is_plaintext(db_path.to_str().unwrap()).unwrap());
+
+        let db = Database::new(keyed(&db_path, Some(TEST_KEY))).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations")
+            .fetch_one(db.pool().unwrap())
+            .await
+            .unwrap();
+        assert!(count > 0);
+
+        db.rekey(OTHER_KEY).await.unwrap();
+        db.close().await;
+
+        let old = Database::new(keyed(&db_path, Some(TEST_KEY))).await;
+        assert!(matches!(old, Err(DbError::Encryption(_))));
+        assert!(Database::new(keyed(&db_path, Some(OTHER_KEY))).await.is_ok());
+    }
 }
@@ -120,6 +120,18 @@ pub struct DbAuditLog {
     pub ip_address: Option<String>,
     pub success: bool,
     pub details: Option<String>,
+    pub prev_hash: Option<String>,
+    pub entry_hash: Option<String>,
+}
+
+/// Signed checkpoint of the audit log chain head
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DbAuditSeal {
+    pub id: i64,
+    pub entry_id: i64,
+    pub entry_hash: String,
+    pub sealed_at: DateTime<Utc>,
+    pub signature: String,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -238,8 +250,19 @@ impl NewSession {
     }
 }
 
+/// Time accounted to a session when it ends
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SessionTotals {
+    pub duration_seconds: i64,
+    pub screen_time_seconds: i64,
+    pub active_time_seconds: i64,
+    pub idle_time_seconds: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewActivity {
+    /// Id assigned by the reporter; an activity with a known id is not stored again
+    pub activity_id: Option<String>,
     pub session_id: String,
     pub profile_id: String,
     pub app_id: String,
@@ -247,6 +270,8 @@ pub struct NewActivity {
     pub category: Option<String>,
     pub window_title: Option<String>,
     pub duration_seconds: i64,
+    /// When the activity was reported; now if unset
+    pub timestamp: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -328,7 +353,7 @@ pub struct NewPolicyVersion {
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbMemoryEvent {
     pub id: i64,
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub event_type: i32,         // 0=kmalloc, 1=kfree, 2=page_alloc, 3=page_free
@@ -339,7 +364,7 @@ pub struct DbMemoryEvent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewMemoryEvent {
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub event_type: i32,
@@ -352,7 +377,7 @@ pub struct NewMemoryEvent {
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbDiskIoEvent {
     pub id: i64,
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub device_major: i32,
@@ -366,7 +391,7 @@ pub struct DbDiskIoEvent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewDiskIoEvent {
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub device_major: i32,
@@ -382,7 +407,7 @@ pub struct NewDiskIoEvent {
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbMemoryStatsHourly {
     pub id: i64,
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub hour_timestamp: i64,
@@ -398,7 +423,7 @@ pub struct DbMemoryStatsHourly {
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbDiskIoStatsHourly {
     pub id: i64,
-    pub profile_id: i64,
+    pub profile_id: String,
     pub pid: i32,
     pub comm: String,
     pub device_major: i32,
@@ -6,19 +6,22 @@ use chrono::Utc;
 pub struct ActivityQueries;
 
 impl ActivityQueries {
-    pub async fn create(db: &Database, activity: NewActivity) -> Result<i64> {
+    /// Store an activity; `None` when one with the same activity id was already stored
+    pub async fn create(db: &Database, activity: NewActivity) -> Result<Option<i64>> {
         let pool = db.pool()?;
 
         let result = sqlx::query(
             r#"
             INSERT INTO activities 
-            (session_id, profile_id, timestamp, app_id, app_name, category, window_title, duration_seconds)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            (activity_id, session_id, profile_id, timestamp, app_id, app_name, category, window_title, duration_seconds)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(activity_id) DO NOTHING
             "#
         )
+        .bind(&activity.activity_id)
         .bind(&activity.session_id)
         .bind(&activity.profile_id)
-        .bind(Utc::now())
+        .bind(activity.timestamp.unwrap_or_else(Utc::now))
         .bind(&activity.app_id)
         .bind(&activity.app_name)
         .bind(&activity.category)
@@ -27,7 +30,10 @@ impl ActivityQueries {
         .execute(pool)
         .await?;
 
-        Ok(result.last_insert_rowid())
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(result.last_insert_rowid()))
     }
 
     pub async fn list_for_session(db: &Database, session_id: &str) -> Result<Vec<DbActivity>> {
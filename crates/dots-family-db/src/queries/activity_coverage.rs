@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use crate::Database;
+
+/// How far a profile's activity timeline is accounted for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityCoverage {
+    pub covered_until: DateTime<Utc>,
+    /// Boot `covered_until` was recorded in
+    pub boot_id: String,
+    /// Time the machine had been suspended in that boot, as of the recording
+    pub asleep_ms: i64,
+}
+
+pub struct ActivityCoverageQueries;
+
+impl ActivityCoverageQueries {
+    pub async fn get(db: &Database, profile_id: &str) -> Result<Option<ActivityCoverage>> {
+        let pool = db.pool()?;
+
+        let row = sqlx::query(
+            "SELECT covered_until, boot_id, asleep_ms FROM activity_coverage WHERE profile_id = ?",
+        )
+        .bind(profile_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| ActivityCoverage {
+            covered_until: row.get("covered_until"),
+            boot_id: row.get("boot_id"),
+            asleep_ms: row.get("asleep_ms"),
+        }))
+    }
+
+    /// Record the profile's activity as accounted for up to
+    /// `coverage.covered_until`. Coverage never moves back, so a late report
+    /// of an earlier interval leaves it as it is.
+    pub async fn extend(
+        db: &Database,
+        profile_id: &str,
+        coverage: &ActivityCoverage,
+    ) -> Result<()> {
+        let pool = db.pool()?;
+
+        sqlx::query(
+            r#"INSERT INTO activity_coverage (profile_id, covered_until, boot_id, asleep_ms)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(profile_id) DO UPDATE SET
+                   covered_until = excluded.covered_until,
+                   boot_id = excluded.boot_id,
+                   asleep_ms = excluded.asleep_ms
+               WHERE julianday(excluded.covered_until) > julianday(activity_coverage.covered_until)"#,
+        )
+        .bind(profile_id)
+        .bind(coverage.covered_until)
+        .bind(&coverage.boot_id)
+        .bind(coverage.asleep_ms)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig, models::NewProfile, queries::profiles::ProfileQueries,
+    };
+
+    #[tokio::test]
+    async fn test_coverage_only_moves_forward() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+        assert_eq!(ActivityCoverageQueries::get(&db, &profile.id).await.unwrap(), None);
+
+        let now = Utc::now();
+        let latest =
+            ActivityCoverage { covered_until: now, boot_id: "boot-1".to_string(), asleep_ms: 500 };
+        ActivityCoverageQueries::extend(&db, &profile.id, &latest).await.unwrap();
+
+        let earlier =
+            ActivityCoverage { covered_until: now - Duration::minutes(5), ..latest.clone() };
+        ActivityCoverageQueries::extend(&db, &profile.id, &earlier).await.unwrap();
+        assert_eq!(ActivityCoverageQueries::get(&db, &profile.id).await.unwrap(), Some(latest));
+
+        let later = ActivityCoverage {
+            covered_until: now + Duration::milliseconds(1),
+            boot_id: "boot-2".to_string(),
+            asleep_ms: 0,
+        };
+        ActivityCoverageQueries::extend(&db, &profile.id, &later).await.unwrap();
+        assert_eq!(ActivityCoverageQueries::get(&db, &profile.id).await.unwrap(), Some(later));
+    }
+}
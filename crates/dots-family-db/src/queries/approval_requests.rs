@@ -12,7 +12,7 @@ pub struct ApprovalRequest {
     pub profile_id: String,
     pub request_type: String, // 'app', 'website', 'command', 'exception'
     pub requested_at: DateTime<Utc>,
-    pub status: String, // 'pending', 'approved', 'denied'
+    pub status: String, // 'pending', 'approved', 'denied', 'auto_approved', 'expired'
     pub details: serde_json::Value,
     pub reviewed_by: Option<String>,
     pub reviewed_at: Option<DateTime<Utc>>,
@@ -28,19 +28,21 @@ impl ApprovalRequestQueries {
         profile_id: &str,
         request_type: &str,
         details: &serde_json::Value,
+        now: DateTime<Utc>,
     ) -> Result<String> {
         let pool = db.pool()?;
         let id = uuid::Uuid::new_v4().to_string();
 
         sqlx::query(
             r#"INSERT INTO approval_requests 
-               (id, profile_id, request_type, details)
-               VALUES (?, ?, ?, ?)"#,
+               (id, profile_id, request_type, details, requested_at)
+               VALUES (?, ?, ?, ?, ?)"#,
         )
         .bind(&id)
         .bind(profile_id)
         .bind(request_type)
         .bind(details.to_string())
+        .bind(now)
         .execute(pool)
         .await?;
 
@@ -119,31 +121,162 @@ impl ApprovalRequestQueries {
         Ok(requests)
     }
 
-    /// Approve or deny an approval request
+    /// Approve or deny a pending approval request at `now`. Returns false when
+    /// the request was no longer pending, e.g. because the expiry sweep got to it.
     pub async fn review_request(
         db: &Database,
         request_id: &str,
         status: &str, // 'approved' or 'denied'
         reviewed_by: &str,
         response_reason: Option<&str>,
-    ) -> Result<()> {
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
         let pool = db.pool()?;
 
-        sqlx::query(
+        let result = sqlx::query(
             r#"UPDATE approval_requests 
-               SET status = ?, reviewed_by = ?, reviewed_at = CURRENT_TIMESTAMP, response_reason = ?
-               WHERE id = ?"#,
+               SET status = ?, reviewed_by = ?, reviewed_at = ?, response_reason = ?
+               WHERE id = ? AND status = 'pending'"#,
         )
         .bind(status)
         .bind(reviewed_by)
+        .bind(now)
         .bind(response_reason)
         .bind(request_id)
         .execute(pool)
         .await?;
 
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Put a reviewed request back to pending, e.g. when granting it failed
+    pub async fn reopen(db: &Database, request_id: &str) -> Result<()> {
+        let pool = db.pool()?;
+
+        sqlx::query(
+            r#"UPDATE approval_requests
+               SET status = 'pending', reviewed_by = NULL, reviewed_at = NULL,
+                   response_reason = NULL
+               WHERE id = ?"#,
+        )
+        .bind(request_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Resolve a request through an auto-approval rule at `now`
+    pub async fn auto_resolve(
+        db: &Database,
+        request_id: &str,
+        status: &str, // 'auto_approved' or 'denied'
+        rule_id: &str,
+        explanation: &str,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let pool = db.pool()?;
+
+        sqlx::query(
+            r#"UPDATE approval_requests
+               SET status = ?, reviewed_by = 'system', reviewed_at = ?,
+                   response_reason = ?, auto_rule_id = ?
+               WHERE id = ?"#,
+        )
+        .bind(status)
+        .bind(now)
+        .bind(explanation)
+        .bind(rule_id)
+        .bind(request_id)
+        .execute(pool)
+        .await?;
+
         Ok(())
     }
 
+    /// Count requests a rule has resolved for a profile since the given time
+    pub async fn count_auto_resolved_since(
+        db: &Database,
+        rule_id: &str,
+        profile_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<u32> {
+        let pool = db.pool()?;
+
+        let count: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM approval_requests
+               WHERE auto_rule_id = ? AND profile_id = ?
+               AND datetime(reviewed_at) >= datetime(?)"#,
+        )
+        .bind(rule_id)
+        .bind(profile_id)
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count as u32)
+    }
+
+    /// Expire requests that have been pending for longer than the TTL.
+    ///
+    /// Returns the requests that were expired, with their updated status.
+    pub async fn expire_pending_older_than(
+        db: &Database,
+        ttl_minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ApprovalRequest>> {
+        let pool = db.pool()?;
+        let cutoff = now - chrono::Duration::minutes(ttl_minutes as i64);
+        let reason = "Request expired without a response";
+
+        let rows = sqlx::query(
+            r#"SELECT id, profile_id, request_type, requested_at, status, details,
+                      reviewed_by, reviewed_at, response_reason
+               FROM approval_requests
+               WHERE status = 'pending' AND datetime(requested_at) <= datetime(?)"#,
+        )
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await?;
+
+        let mut expired = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+
+            // Re-check the status so a parent response racing the sweep wins
+            let result = sqlx::query(
+                r#"UPDATE approval_requests
+                   SET status = 'expired', reviewed_by = 'system',
+                       reviewed_at = ?, response_reason = ?
+                   WHERE id = ? AND status = 'pending'"#,
+            )
+            .bind(now)
+            .bind(reason)
+            .bind(&id)
+            .execute(pool)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                continue;
+            }
+
+            let details_str: String = row.get("details");
+            expired.push(ApprovalRequest {
+                id,
+                profile_id: row.get("profile_id"),
+                request_type: row.get("request_type"),
+                requested_at: row.get("requested_at"),
+                status: "expired".to_string(),
+                details: serde_json::from_str(&details_str)?,
+                reviewed_by: Some("system".to_string()),
+                reviewed_at: Some(Utc::now()),
+                response_reason: Some(reason.to_string()),
+            });
+        }
+
+        Ok(expired)
+    }
+
     /// Get a specific approval request by ID
     pub async fn get_by_id(db: &Database, request_id: &str) -> Result<Option<ApprovalRequest>> {
         let pool = db.pool()?;
@@ -178,19 +311,169 @@ impl ApprovalRequestQueries {
         }
     }
 
-    /// Delete old reviewed approval requests (for cleanup)
-    pub async fn cleanup_old_requests(db: &Database, days_old: i32) -> Result<u64> {
+    /// Delete requests reviewed more than `days_old` days before `now` (for cleanup)
+    pub async fn cleanup_old_requests(
+        db: &Database,
+        days_old: i32,
+        now: DateTime<Utc>,
+    ) -> Result<u64> {
         let pool = db.pool()?;
+        let cutoff = now - chrono::Duration::days(days_old as i64);
 
         let result = sqlx::query(
             r#"DELETE FROM approval_requests 
                WHERE status != 'pending' 
-               AND reviewed_at < datetime('now', '-' || ? || ' days')"#,
+               AND datetime(reviewed_at) < datetime(?)"#,
         )
-        .bind(days_old)
+        .bind(cutoff)
         .execute(pool)
         .await?;
 
         Ok(result.rows_affected())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig, models::NewProfile, queries::profiles::ProfileQueries,
+    };
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn test_expire_pending_older_than() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        // Requests are stamped with the time passed in, not the database's clock
+        let now = Utc::now();
+        let details = serde_json::json!({"requested_minutes": 15});
+        let stale_id = ApprovalRequestQueries::create(
+            &db,
+            &profile.id,
+            "screen_time",
+            &details,
+            now - chrono::Duration::hours(2),
+        )
+        .await
+        .unwrap();
+        let fresh_id =
+            ApprovalRequestQueries::create(&db, &profile.id, "screen_time", &details, now)
+                .await
+                .unwrap();
+
+        // Nothing is old enough at an earlier time, whatever the system clock says
+        let earlier = now - chrono::Duration::minutes(90);
+        assert!(ApprovalRequestQueries::expire_pending_older_than(&db, 60, earlier)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let expired =
+            ApprovalRequestQueries::expire_pending_older_than(&db, 60, now).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, stale_id);
+        assert_eq!(expired[0].status, "expired");
+
+        let stale = ApprovalRequestQueries::get_by_id(&db, &stale_id).await.unwrap().unwrap();
+        assert_eq!(stale.status, "expired");
+        assert_eq!(stale.reviewed_by.as_deref(), Some("system"));
+
+        let pending = ApprovalRequestQueries::list_pending(&db, &profile.id).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, fresh_id);
+
+        // A second sweep has nothing left to expire
+        assert!(ApprovalRequestQueries::expire_pending_older_than(&db, 60, now)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_review_only_applies_to_pending_requests() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let details = serde_json::json!({"requested_minutes": 15});
+        let id =
+            ApprovalRequestQueries::create(&db, &profile.id, "screen_time", &details, Utc::now())
+                .await
+                .unwrap();
+
+        // The expiry sweep wins the race
+        ApprovalRequestQueries::expire_pending_older_than(&db, 0, Utc::now()).await.unwrap();
+
+        let approved = ApprovalRequestQueries::review_request(
+            &db,
+            &id,
+            "approved",
+            "parent",
+            None,
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert!(!approved);
+        let request = ApprovalRequestQueries::get_by_id(&db, &id).await.unwrap().unwrap();
+        assert_eq!(request.status, "expired");
+    }
+
+    #[tokio::test]
+    async fn test_review_and_cleanup_use_the_given_time() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let reviewed = Utc::now() - chrono::Duration::days(10);
+        let details = serde_json::json!({"requested_minutes": 15});
+        let id =
+            ApprovalRequestQueries::create(&db, &profile.id, "screen_time", &details, reviewed)
+                .await
+                .unwrap();
+        assert!(ApprovalRequestQueries::review_request(
+            &db, &id, "denied", "parent", None, reviewed
+        )
+        .await
+        .unwrap());
+        let request = ApprovalRequestQueries::get_by_id(&db, &id).await.unwrap().unwrap();
+        assert_eq!(request.reviewed_at.unwrap().timestamp(), reviewed.timestamp());
+
+        // Ten days on, a 30-day retention keeps it and a 7-day one only drops it
+        // when measured from the given time
+        assert_eq!(
+            ApprovalRequestQueries::cleanup_old_requests(&db, 30, Utc::now()).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            ApprovalRequestQueries::cleanup_old_requests(&db, 7, reviewed).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            ApprovalRequestQueries::cleanup_old_requests(&db, 7, Utc::now()).await.unwrap(),
+            1
+        );
+    }
+}
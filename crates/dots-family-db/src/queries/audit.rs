@@ -1,22 +1,122 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
 use crate::connection::Database;
 use crate::error::{DbError, Result};
-use crate::models::{DbAuditLog, NewAuditLog};
-use chrono::Utc;
+use crate::models::{DbAuditLog, DbAuditSeal, NewAuditLog};
+
+/// `prev_hash` of the first chained entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serializes appends so two writers never chain onto the same head
+static APPEND_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Where and why the audit chain no longer verifies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditChainBreak {
+    /// First audit entry affected by the break
+    pub entry_id: i64,
+    /// Seal that exposed the break, if it was found through a seal
+    pub seal_id: Option<i64>,
+    pub reason: String,
+}
+
+/// Result of walking the audit chain and checking its seals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditChainReport {
+    pub entries_checked: usize,
+    /// Entries written before the log was chained
+    pub legacy_entries: usize,
+    pub seals_checked: usize,
+    pub head_entry_id: Option<i64>,
+    /// Newest entry covered by a valid seal
+    pub last_sealed_entry_id: Option<i64>,
+    /// Breaks ordered by entry, earliest first
+    pub breaks: Vec<AuditChainBreak>,
+}
+
+impl AuditChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.breaks.is_empty()
+    }
+
+    pub fn first_break(&self) -> Option<&AuditChainBreak> {
+        self.breaks.first()
+    }
+}
+
+/// The newest seal as the daemon remembers it outside the database, so
+/// removing seals together with the entries they cover is still noticed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealCheckpoint {
+    pub entry_id: i64,
+    pub entry_hash: String,
+    /// Seals made up to and including this one
+    pub seal_count: usize,
+}
+
+/// Hash of an entry chained onto `prev_hash`
+pub fn chain_hash(prev_hash: &str, timestamp: &DateTime<Utc>, entry: &NewAuditLog) -> String {
+    let fields = serde_json::json!([
+        prev_hash,
+        timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        entry.actor,
+        entry.action,
+        entry.resource,
+        entry.resource_id,
+        entry.ip_address,
+        entry.success,
+        entry.details,
+    ]);
+
+    format!("{:x}", Sha256::digest(fields.to_string().as_bytes()))
+}
+
+/// Bytes signed when sealing the chain head at `entry_id`
+pub fn seal_message(entry_id: i64, entry_hash: &str, sealed_at: &DateTime<Utc>) -> Vec<u8> {
+    format!(
+        "dots-family-audit-seal:v1:{}:{}:{}",
+        entry_id,
+        entry_hash,
+        sealed_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+    .into_bytes()
+}
 
 pub struct AuditQueries;
 
 impl AuditQueries {
-    pub async fn log(db: &Database, audit: NewAuditLog) -> Result<i64> {
+    /// Append an entry made at `now`, chaining it onto the current head
+    pub async fn log(db: &Database, audit: NewAuditLog, now: DateTime<Utc>) -> Result<i64> {
         let pool = db.pool()?;
 
+        let _guard = APPEND_LOCK.lock().await;
+        let mut tx = pool.begin().await?;
+
+        let head: Option<Option<String>> =
+            sqlx::query_scalar("SELECT entry_hash FROM audit_log ORDER BY id DESC LIMIT 1")
+                .fetch_optional(&mut *tx)
+                .await?;
+        let prev_hash = head.flatten().unwrap_or_else(|| GENESIS_HASH.to_string());
+
+        // Stored and hashed at the same precision so verification can recompute it
+        let timestamp = now.trunc_subsecs(6);
+        let entry_hash = chain_hash(&prev_hash, &timestamp, &audit);
+
         let result = sqlx::query(
             r#"
             INSERT INTO audit_log 
-            (timestamp, actor, action, resource, resource_id, ip_address, success, details)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            (timestamp, actor, action, resource, resource_id, ip_address, success, details,
+             prev_hash, entry_hash)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(Utc::now())
+        .bind(timestamp)
         .bind(&audit.actor)
         .bind(&audit.action)
         .bind(&audit.resource)
@@ -24,12 +124,262 @@ impl AuditQueries {
         .bind(&audit.ip_address)
         .bind(audit.success)
         .bind(&audit.details)
-        .execute(pool)
+        .bind(&prev_hash)
+        .bind(&entry_hash)
+        .execute(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         Ok(result.last_insert_rowid())
     }
 
+    /// Sign the current chain head, stamping the seal with `now`.
+    ///
+    /// Returns `None` when there is nothing new to seal. Refuses to seal a
+    /// log that no longer holds the seal in `checkpoint`, which would cover
+    /// up the rollback.
+    pub async fn seal<F>(
+        db: &Database,
+        checkpoint: Option<&SealCheckpoint>,
+        now: DateTime<Utc>,
+        sign: F,
+    ) -> Result<Option<DbAuditSeal>>
+    where
+        F: FnOnce(&[u8]) -> Vec<u8>,
+    {
+        let pool = db.pool()?;
+
+        let _guard = APPEND_LOCK.lock().await;
+
+        if let Some(checkpoint) = checkpoint {
+            let kept: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM audit_seals WHERE entry_id = ? AND entry_hash = ?",
+            )
+            .bind(checkpoint.entry_id)
+            .bind(&checkpoint.entry_hash)
+            .fetch_optional(pool)
+            .await?;
+            if kept.is_none() {
+                return Err(DbError::InvalidData(format!(
+                    "the seal over audit entry {} was removed; not sealing over it",
+                    checkpoint.entry_id
+                )));
+            }
+        }
+
+        let head: Option<(i64, Option<String>)> =
+            sqlx::query_as("SELECT id, entry_hash FROM audit_log ORDER BY id DESC LIMIT 1")
+                .fetch_optional(pool)
+                .await?;
+        let Some((entry_id, Some(entry_hash))) = head else {
+            return Ok(None);
+        };
+
+        let last_sealed: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(entry_id) FROM audit_seals").fetch_one(pool).await?;
+        if last_sealed == Some(entry_id) {
+            return Ok(None);
+        }
+
+        let sealed_at = now.trunc_subsecs(6);
+        let signature = hex::encode(sign(&seal_message(entry_id, &entry_hash, &sealed_at)));
+
+        let result = sqlx::query(
+            "INSERT INTO audit_seals (entry_id, entry_hash, sealed_at, signature) VALUES (?, ?, ?, ?)",
+        )
+        .bind(entry_id)
+        .bind(&entry_hash)
+        .bind(sealed_at)
+        .bind(&signature)
+        .execute(pool)
+        .await?;
+
+        Ok(Some(DbAuditSeal {
+            id: result.last_insert_rowid(),
+            entry_id,
+            entry_hash,
+            sealed_at,
+            signature,
+        }))
+    }
+
+    /// Number of seals in the log
+    pub async fn seal_count(db: &Database) -> Result<usize> {
+        let pool = db.pool()?;
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM audit_seals").fetch_one(pool).await?;
+        Ok(count as usize)
+    }
+
+    /// Walk the whole chain and check every seal against `public_key`, and
+    /// that the seals in `checkpoint` are all still there
+    pub async fn verify_chain(
+        db: &Database,
+        public_key: &[u8],
+        checkpoint: Option<&SealCheckpoint>,
+    ) -> Result<AuditChainReport> {
+        let pool = db.pool()?;
+
+        let entries = sqlx::query_as::<_, DbAuditLog>("SELECT * FROM audit_log ORDER BY id ASC")
+            .fetch_all(pool)
+            .await?;
+        let seals = sqlx::query_as::<_, DbAuditSeal>("SELECT * FROM audit_seals ORDER BY id ASC")
+            .fetch_all(pool)
+            .await?;
+
+        let mut report =
+            AuditChainReport { head_entry_id: entries.last().map(|e| e.id), ..Default::default() };
+        let mut prev: Option<&DbAuditLog> = None;
+
+        for entry in &entries {
+            report.entries_checked += 1;
+
+            let Some(stored_hash) = &entry.entry_hash else {
+                if prev.is_some_and(|p| p.entry_hash.is_some()) {
+                    report.breaks.push(AuditChainBreak {
+                        entry_id: entry.id,
+                        seal_id: None,
+                        reason: format!(
+                            "entry {} has no chain hash; it was not written through the daemon",
+                            entry.id
+                        ),
+                    });
+                } else {
+                    report.legacy_entries += 1;
+                }
+                prev = Some(entry);
+                continue;
+            };
+
+            let expected_prev = prev.and_then(|p| p.entry_hash.as_deref()).unwrap_or(GENESIS_HASH);
+            if entry.prev_hash.as_deref() != Some(expected_prev) {
+                let reason = match prev {
+                    Some(p) if entry.id > p.id + 1 => format!(
+                        "entries {} to {} were deleted from before entry {}",
+                        p.id + 1,
+                        entry.id - 1,
+                        entry.id
+                    ),
+                    Some(p) => format!(
+                        "entry {} does not link to entry {}; the chain was rewritten or spliced",
+                        entry.id, p.id
+                    ),
+                    None => format!(
+                        "entry {} does not link to the start of the chain; earlier entries were deleted",
+                        entry.id
+                    ),
+                };
+                report.breaks.push(AuditChainBreak { entry_id: entry.id, seal_id: None, reason });
+            }
+
+            let content = NewAuditLog {
+                actor: entry.actor.clone(),
+                action: entry.action.clone(),
+                resource: entry.resource.clone(),
+                resource_id: entry.resource_id.clone(),
+                ip_address: entry.ip_address.clone(),
+                success: entry.success,
+                details: entry.details.clone(),
+            };
+            let prev_hash = entry.prev_hash.as_deref().unwrap_or_default();
+            if chain_hash(prev_hash, &entry.timestamp, &content) != *stored_hash {
+                report.breaks.push(AuditChainBreak {
+                    entry_id: entry.id,
+                    seal_id: None,
+                    reason: format!("entry {} was modified after it was written", entry.id),
+                });
+            }
+
+            prev = Some(entry);
+        }
+
+        let hashes: HashMap<i64, Option<&str>> =
+            entries.iter().map(|e| (e.id, e.entry_hash.as_deref())).collect();
+        let first_entry_id = entries.first().map(|e| e.id).unwrap_or(1);
+        let key = UnparsedPublicKey::new(&ED25519, public_key);
+
+        for seal in &seals {
+            report.seals_checked += 1;
+
+            let message = seal_message(seal.entry_id, &seal.entry_hash, &seal.sealed_at);
+            let signed = hex::decode(&seal.signature)
+                .map(|signature| key.verify(&message, &signature).is_ok())
+                .unwrap_or(false);
+
+            // Where the break starts and why
+            let broken = if !signed {
+                Some((
+                    seal.entry_id,
+                    format!(
+                        "seal {} over entry {} was not signed by this daemon's sealing key",
+                        seal.id, seal.entry_id
+                    ),
+                ))
+            } else {
+                match hashes.get(&seal.entry_id) {
+                    None => Some((
+                        seal.entry_id,
+                        format!(
+                            "sealed entry {} was deleted; the log was truncated",
+                            seal.entry_id
+                        ),
+                    )),
+                    Some(hash) if *hash != Some(seal.entry_hash.as_str()) => {
+                        let from =
+                            report.last_sealed_entry_id.map(|id| id + 1).unwrap_or(first_entry_id);
+                        Some((
+                            from,
+                            format!(
+                                "the chain was rewritten between entry {} and sealed entry {}",
+                                from, seal.entry_id
+                            ),
+                        ))
+                    }
+                    Some(_) => {
+                        report.last_sealed_entry_id =
+                            report.last_sealed_entry_id.max(Some(seal.entry_id));
+                        None
+                    }
+                }
+            };
+
+            if let Some((entry_id, reason)) = broken {
+                report.breaks.push(AuditChainBreak { entry_id, seal_id: Some(seal.id), reason });
+            }
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            let kept = seals.iter().any(|seal| {
+                seal.entry_id == checkpoint.entry_id && seal.entry_hash == checkpoint.entry_hash
+            });
+            if !kept {
+                report.breaks.push(AuditChainBreak {
+                    entry_id: checkpoint.entry_id,
+                    seal_id: None,
+                    reason: format!(
+                        "the latest seal, over entry {}, was removed; the log was rolled back",
+                        checkpoint.entry_id
+                    ),
+                });
+            } else if seals.len() < checkpoint.seal_count {
+                report.breaks.push(AuditChainBreak {
+                    entry_id: first_entry_id,
+                    seal_id: None,
+                    reason: format!(
+                        "{} of {} seals were removed",
+                        checkpoint.seal_count - seals.len(),
+                        checkpoint.seal_count
+                    ),
+                });
+            }
+        }
+
+        report.breaks.sort_by_key(|b| b.entry_id);
+        Ok(report)
+    }
+
     pub async fn list_recent(db: &Database, limit: i64) -> Result<Vec<DbAuditLog>> {
         let pool = db.pool()?;
 
@@ -83,3 +433,206 @@ impl AuditQueries {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ring::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::connection::DatabaseConfig;
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn entry(action: &str) -> NewAuditLog {
+        NewAuditLog {
+            actor: "parent".to_string(),
+            action: action.to_string(),
+            resource: "profile".to_string(),
+            resource_id: Some("child-1".to_string()),
+            ip_address: None,
+            success: true,
+            details: None,
+        }
+    }
+
+    /// Simulate someone with direct database access
+    async fn drop_immutability(db: &Database) {
+        let pool = db.pool().unwrap();
+        for trigger in ["audit_log_immutable_update", "audit_log_immutable_delete"] {
+            sqlx::query(&format!("DROP TRIGGER {}", trigger)).execute(pool).await.unwrap();
+        }
+    }
+
+    async fn log_entries(db: &Database, count: usize) {
+        for i in 0..count {
+            AuditQueries::log(db, entry(&format!("action_{}", i)), Utc::now()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chain_and_seals_verify() {
+        let (db, _dir) = setup_test_db().await;
+        let key = key_pair();
+
+        log_entries(&db, 3).await;
+        let seal = AuditQueries::seal(&db, None, Utc::now(), |m| key.sign(m).as_ref().to_vec())
+            .await
+            .unwrap();
+        assert_eq!(seal.unwrap().entry_id, 3);
+        assert!(AuditQueries::seal(&db, None, Utc::now(), |m| key.sign(m).as_ref().to_vec())
+            .await
+            .unwrap()
+            .is_none());
+        log_entries(&db, 2).await;
+
+        let report =
+            AuditQueries::verify_chain(&db, key.public_key().as_ref(), None).await.unwrap();
+        assert!(report.is_intact(), "{:?}", report.breaks);
+        assert_eq!(report.entries_checked, 5);
+        assert_eq!(report.seals_checked, 1);
+        assert_eq!(report.last_sealed_entry_id, Some(3));
+        assert_eq!(report.head_entry_id, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_entries_and_seals_carry_the_given_time() {
+        let (db, _dir) = setup_test_db().await;
+        let key = key_pair();
+
+        // A trusted time well away from the wall clock
+        let then = Utc::now() - chrono::Duration::days(2);
+        AuditQueries::log(&db, entry("stamped"), then).await.unwrap();
+        let seal = AuditQueries::seal(&db, None, then, |m| key.sign(m).as_ref().to_vec())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let logged = AuditQueries::list_recent(&db, 1).await.unwrap();
+        assert_eq!(logged[0].timestamp, then.trunc_subsecs(6));
+        assert_eq!(seal.sealed_at, then.trunc_subsecs(6));
+        let report =
+            AuditQueries::verify_chain(&db, key.public_key().as_ref(), None).await.unwrap();
+        assert!(report.is_intact(), "{:?}", report.breaks);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_and_modified_entries_are_pinpointed() {
+        let (db, _dir) = setup_test_db().await;
+        let key = key_pair();
+        log_entries(&db, 6).await;
+        drop_immutability(&db).await;
+
+        let pool = db.pool().unwrap();
+        sqlx::query("DELETE FROM audit_log WHERE id = 2").execute(pool).await.unwrap();
+        sqlx::query("UPDATE audit_log SET success = 0 WHERE id = 5").execute(pool).await.unwrap();
+
+        let report =
+            AuditQueries::verify_chain(&db, key.public_key().as_ref(), None).await.unwrap();
+        let broken: Vec<i64> = report.breaks.iter().map(|b| b.entry_id).collect();
+        assert_eq!(broken, vec![3, 5]);
+        assert!(report.first_break().unwrap().reason.contains("entries 2 to 2 were deleted"));
+    }
+
+    #[tokio::test]
+    async fn test_rewritten_chain_is_caught_by_seal() {
+        let (db, _dir) = setup_test_db().await;
+        let key = key_pair();
+        log_entries(&db, 4).await;
+        AuditQueries::seal(&db, None, Utc::now(), |m| key.sign(m).as_ref().to_vec()).await.unwrap();
+        drop_immutability(&db).await;
+
+        // Rewrite entry 3 onwards with freshly computed, consistent hashes
+        let pool = db.pool().unwrap();
+        let entries = sqlx::query_as::<_, DbAuditLog>("SELECT * FROM audit_log ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap();
+        let mut prev_hash = entries[1].entry_hash.clone().unwrap();
+        for e in &entries[2..] {
+            let forged = entry("harmless");
+            let hash = chain_hash(&prev_hash, &e.timestamp, &forged);
+            sqlx::query(
+                "UPDATE audit_log SET action = ?, prev_hash = ?, entry_hash = ? WHERE id = ?",
+            )
+            .bind(&forged.action)
+            .bind(&prev_hash)
+            .bind(&hash)
+            .bind(e.id)
+            .execute(pool)
+            .await
+            .unwrap();
+            prev_hash = hash;
+        }
+
+        let report =
+            AuditQueries::verify_chain(&db, key.public_key().as_ref(), None).await.unwrap();
+        assert_eq!(report.breaks.len(), 1);
+        let first = report.first_break().unwrap();
+        assert_eq!(first.seal_id, Some(1));
+        assert_eq!(first.entry_id, 1);
+
+        // A seal forged with another key does not verify either
+        let other = key_pair();
+        AuditQueries::log(&db, entry("later"), Utc::now()).await.unwrap();
+        AuditQueries::seal(&db, None, Utc::now(), |m| other.sign(m).as_ref().to_vec())
+            .await
+            .unwrap();
+        let report =
+            AuditQueries::verify_chain(&db, key.public_key().as_ref(), None).await.unwrap();
+        assert!(report.breaks.iter().any(|b| b.seal_id == Some(2) && b.entry_id == 5));
+    }
+
+    #[tokio::test]
+    async fn test_rolled_back_seals_are_caught_by_checkpoint() {
+        let (db, _dir) = setup_test_db().await;
+        let key = key_pair();
+        let sign = |m: &[u8]| key.sign(m).as_ref().to_vec();
+
+        log_entries(&db, 2).await;
+        AuditQueries::seal(&db, None, Utc::now(), sign).await.unwrap().unwrap();
+        log_entries(&db, 2).await;
+        let latest = AuditQueries::seal(&db, None, Utc::now(), sign).await.unwrap().unwrap();
+        let checkpoint = SealCheckpoint {
+            entry_id: latest.entry_id,
+            entry_hash: latest.entry_hash.clone(),
+            seal_count: AuditQueries::seal_count(&db).await.unwrap(),
+        };
+
+        let public_key = key.public_key().as_ref();
+        let report = AuditQueries::verify_chain(&db, public_key, Some(&checkpoint)).await.unwrap();
+        assert!(report.is_intact(), "{:?}", report.breaks);
+
+        // Roll the log back to the first seal: the remaining chain is consistent
+        drop_immutability(&db).await;
+        let pool = db.pool().unwrap();
+        sqlx::query("DROP TRIGGER audit_seals_immutable_delete").execute(pool).await.unwrap();
+        sqlx::query("DELETE FROM audit_log WHERE id > 2").execute(pool).await.unwrap();
+        sqlx::query("DELETE FROM audit_seals WHERE id = 2").execute(pool).await.unwrap();
+        assert!(AuditQueries::verify_chain(&db, public_key, None).await.unwrap().is_intact());
+
+        let report = AuditQueries::verify_chain(&db, public_key, Some(&checkpoint)).await.unwrap();
+        assert!(report.first_break().unwrap().reason.contains("was rolled back"));
+
+        log_entries(&db, 1).await;
+        assert!(AuditQueries::seal(&db, Some(&checkpoint), Utc::now(), sign).await.is_err());
+    }
+}
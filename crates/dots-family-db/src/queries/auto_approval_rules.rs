@@ -0,0 +1,248 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Row};
+
+use crate::Database;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoApprovalRuleRow {
+    pub id: String,
+    pub profile_id: Option<String>, // None for global rules
+    pub name: String,
+    pub conditions: serde_json::Value, // JSON array of conditions
+    pub action: String,                // 'auto_approve', 'auto_deny'
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String, // 'parent', 'import'
+}
+
+pub struct AutoApprovalRuleQueries;
+
+impl AutoApprovalRuleQueries {
+    /// Create a new auto-approval rule
+    pub async fn create(
+        db: &Database,
+        id: &str,
+        profile_id: Option<&str>,
+        name: &str,
+        conditions: &serde_json::Value,
+        action: &str,
+        created_by: &str,
+    ) -> Result<()> {
+        let pool = db.pool()?;
+
+        sqlx::query(
+            r#"INSERT INTO auto_approval_rules
+               (id, profile_id, name, conditions, action, created_by)
+               VALUES (?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(id)
+        .bind(profile_id)
+        .bind(name)
+        .bind(conditions.to_string())
+        .bind(action)
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List rules that apply to a profile (including global rules), oldest first
+    pub async fn list_for_profile(
+        db: &Database,
+        profile_id: &str,
+    ) -> Result<Vec<AutoApprovalRuleRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT id, profile_id, name, conditions, action, enabled, created_at, created_by
+               FROM auto_approval_rules
+               WHERE profile_id = ? OR profile_id IS NULL
+               ORDER BY created_at ASC"#,
+        )
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// List every rule regardless of profile
+    pub async fn list_all(db: &Database) -> Result<Vec<AutoApprovalRuleRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT id, profile_id, name, conditions, action, enabled, created_at, created_by
+               FROM auto_approval_rules
+               ORDER BY created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Get a specific rule by ID
+    pub async fn get_by_id(db: &Database, rule_id: &str) -> Result<Option<AutoApprovalRuleRow>> {
+        let pool = db.pool()?;
+
+        let row = sqlx::query(
+            r#"SELECT id, profile_id, name, conditions, action, enabled, created_at, created_by
+               FROM auto_approval_rules
+               WHERE id = ?"#,
+        )
+        .bind(rule_id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.as_ref().map(Self::from_row).transpose()
+    }
+
+    /// Enable or disable a rule, returning whether it existed
+    pub async fn set_enabled(db: &Database, rule_id: &str, enabled: bool) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let result = sqlx::query("UPDATE auto_approval_rules SET enabled = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(rule_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a rule, returning whether it existed
+    pub async fn delete(db: &Database, rule_id: &str) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let result = sqlx::query("DELETE FROM auto_approval_rules WHERE id = ?")
+            .bind(rule_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<AutoApprovalRuleRow> {
+        let conditions_str: String = row.get("conditions");
+
+        Ok(AutoApprovalRuleRow {
+            id: row.get("id"),
+            profile_id: row.get("profile_id"),
+            name: row.get("name"),
+            conditions: serde_json::from_str(&conditions_str)?,
+            action: row.get("action"),
+            enabled: row.get("enabled"),
+            created_at: row.get("created_at"),
+            created_by: row.get("created_by"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig,
+        models::NewProfile,
+        queries::{approval_requests::ApprovalRequestQueries, profiles::ProfileQueries},
+    };
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_rules() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let conditions = serde_json::json!([{"type": "weekend"}]);
+        AutoApprovalRuleQueries::create(
+            &db,
+            "rule-1",
+            Some(&profile.id),
+            "Weekend bonus",
+            &conditions,
+            "auto_approve",
+            "parent",
+        )
+        .await
+        .unwrap();
+        AutoApprovalRuleQueries::create(
+            &db,
+            "rule-2",
+            None,
+            "Global",
+            &conditions,
+            "auto_deny",
+            "parent",
+        )
+        .await
+        .unwrap();
+
+        let rules = AutoApprovalRuleQueries::list_for_profile(&db, &profile.id).await.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert!(rules.iter().all(|r| r.enabled));
+        assert_eq!(rules[0].conditions, conditions);
+
+        assert!(AutoApprovalRuleQueries::set_enabled(&db, "rule-1", false).await.unwrap());
+        let rule = AutoApprovalRuleQueries::get_by_id(&db, "rule-1").await.unwrap().unwrap();
+        assert!(!rule.enabled);
+
+        assert!(AutoApprovalRuleQueries::delete(&db, "rule-2").await.unwrap());
+        assert!(!AutoApprovalRuleQueries::delete(&db, "rule-2").await.unwrap());
+        assert_eq!(AutoApprovalRuleQueries::list_all(&db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_count_auto_resolved_requests() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let details = serde_json::json!({"requested_minutes": 15});
+        let request_id =
+            ApprovalRequestQueries::create(&db, &profile.id, "screen_time", &details, Utc::now())
+                .await
+                .unwrap();
+        ApprovalRequestQueries::auto_resolve(
+            &db,
+            &request_id,
+            "auto_approved",
+            "rule-1",
+            "Auto-approved by rule 'Weekend bonus'",
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let count =
+            ApprovalRequestQueries::count_auto_resolved_since(&db, "rule-1", &profile.id, since)
+                .await
+                .unwrap();
+        assert_eq!(count, 1);
+
+        let request = ApprovalRequestQueries::get_by_id(&db, &request_id).await.unwrap().unwrap();
+        assert_eq!(request.status, "auto_approved");
+        assert_eq!(request.reviewed_by.as_deref(), Some("system"));
+    }
+}
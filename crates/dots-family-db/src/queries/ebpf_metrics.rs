@@ -12,7 +12,7 @@ pub async fn insert_memory_event(pool: &SqlitePool, event: NewMemoryEvent) -> Re
         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
         "#,
     )
-    .bind(event.profile_id)
+    .bind(&event.profile_id)
     .bind(event.pid)
     .bind(&event.comm)
     .bind(event.event_type)
@@ -29,7 +29,7 @@ pub async fn insert_memory_event(pool: &SqlitePool, event: NewMemoryEvent) -> Re
 /// Get memory events for a profile within a time range
 pub async fn get_memory_events(
     pool: &SqlitePool,
-    profile_id: i64,
+    profile_id: &str,
     start_timestamp: i64,
     end_timestamp: i64,
     limit: i64,
@@ -70,7 +70,7 @@ pub async fn get_memory_events(
 /// Get memory statistics for a process
 pub async fn get_process_memory_stats(
     pool: &SqlitePool,
-    profile_id: i64,
+    profile_id: &str,
     pid: i32,
     start_timestamp: i64,
     end_timestamp: i64,
@@ -105,7 +105,7 @@ pub async fn insert_disk_io_event(pool: &SqlitePool, event: NewDiskIoEvent) -> R
         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
         "#,
     )
-    .bind(event.profile_id)
+    .bind(&event.profile_id)
     .bind(event.pid)
     .bind(&event.comm)
     .bind(event.device_major)
@@ -125,7 +125,7 @@ pub async fn insert_disk_io_event(pool: &SqlitePool, event: NewDiskIoEvent) -> R
 /// Get disk I/O events for a profile within a time range
 pub async fn get_disk_io_events(
     pool: &SqlitePool,
-    profile_id: i64,
+    profile_id: &str,
     start_timestamp: i64,
     end_timestamp: i64,
     limit: i64,
@@ -170,7 +170,7 @@ pub async fn get_disk_io_events(
 /// Get disk I/O statistics for a process
 pub async fn get_process_disk_io_stats(
     pool: &SqlitePool,
-    profile_id: i64,
+    profile_id: &str,
     pid: i32,
     start_timestamp: i64,
     end_timestamp: i64,
@@ -251,7 +251,7 @@ mod tests {
         .unwrap();
 
         let event = NewMemoryEvent {
-            profile_id: 1,
+            profile_id: "1".to_string(),
             pid: 1234,
             comm: "test".to_string(),
             event_type: 0, // kmalloc
@@ -278,7 +278,7 @@ mod tests {
         .unwrap();
 
         let event = NewDiskIoEvent {
-            profile_id: 1,
+            profile_id: "1".to_string(),
             pid: 1234,
             comm: "test".to_string(),
             device_major: 8,
@@ -310,7 +310,7 @@ mod tests {
         // Insert a test event
         let timestamp = chrono::Utc::now().timestamp_millis();
         let event = NewMemoryEvent {
-            profile_id: 1,
+            profile_id: "1".to_string(),
             pid: 1234,
             comm: "test".to_string(),
             event_type: 0,
@@ -322,7 +322,7 @@ mod tests {
 
         // Query events
         let events =
-            get_memory_events(pool, 1, timestamp - 1000, timestamp + 1000, 10).await.unwrap();
+            get_memory_events(pool, "1", timestamp - 1000, timestamp + 1000, 10).await.unwrap();
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].pid, 1234);
     }
@@ -344,7 +344,7 @@ mod tests {
 
         // Insert allocation
         let alloc_event = NewMemoryEvent {
-            profile_id: 1,
+            profile_id: "1".to_string(),
             pid: 1234,
             comm: "test".to_string(),
             event_type: 0, // kmalloc
@@ -356,7 +356,7 @@ mod tests {
 
         // Insert free
         let free_event = NewMemoryEvent {
-            profile_id: 1,
+            profile_id: "1".to_string(),
             pid: 1234,
             comm: "test".to_string(),
             event_type: 1, // kfree
@@ -367,7 +367,7 @@ mod tests {
         insert_memory_event(pool, free_event).await.unwrap();
 
         let (allocated, freed, _count) =
-            get_process_memory_stats(pool, 1, 1234, timestamp - 1000, timestamp + 2000)
+            get_process_memory_stats(pool, "1", 1234, timestamp - 1000, timestamp + 2000)
                 .await
                 .unwrap();
 
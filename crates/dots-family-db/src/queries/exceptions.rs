@@ -1,13 +1,17 @@
 use crate::connection::Database;
 use crate::error::{DbError, Result};
 use crate::models::{DbException, NewException};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 pub struct ExceptionQueries;
 
 impl ExceptionQueries {
-    /// Create a new exception for temporary policy overrides
-    pub async fn create(db: &Database, exception: NewException) -> Result<String> {
+    /// Create a new exception for temporary policy overrides, granted at `now`
+    pub async fn create(
+        db: &Database,
+        exception: NewException,
+        now: DateTime<Utc>,
+    ) -> Result<String> {
         let pool = db.pool()?;
 
         sqlx::query(
@@ -22,7 +26,7 @@ impl ExceptionQueries {
         .bind(&exception.profile_id)
         .bind(&exception.exception_type)
         .bind(&exception.granted_by)
-        .bind(Utc::now())
+        .bind(now)
         .bind(exception.expires_at)
         .bind(&exception.reason)
         .bind(exception.amount_minutes)
@@ -35,10 +39,22 @@ impl ExceptionQueries {
         Ok(exception.id)
     }
 
-    /// Get active exceptions for a profile
+    /// Get an exception by ID
+    pub async fn get_by_id(db: &Database, exception_id: &str) -> Result<Option<DbException>> {
+        let pool = db.pool()?;
+
+        sqlx::query_as::<_, DbException>("SELECT * FROM exceptions WHERE id = ?")
+            .bind(exception_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(DbError::Sqlx)
+    }
+
+    /// Get exceptions of a profile that are active at `now`
     pub async fn list_active_for_profile(
         db: &Database,
         profile_id: &str,
+        now: DateTime<Utc>,
     ) -> Result<Vec<DbException>> {
         let pool = db.pool()?;
 
@@ -46,7 +62,7 @@ impl ExceptionQueries {
             "SELECT * FROM exceptions WHERE profile_id = ? AND active = 1 AND expires_at > ? ORDER BY granted_at DESC",
         )
         .bind(profile_id)
-        .bind(Utc::now())
+        .bind(now)
         .fetch_all(pool)
         .await
         .map_err(DbError::Sqlx)
@@ -70,12 +86,13 @@ impl ExceptionQueries {
         .map_err(DbError::Sqlx)
     }
 
-    /// Check if there's an active exception for a specific resource
+    /// Check if there's an exception for a specific resource active at `now`
     pub async fn check_active_exception(
         db: &Database,
         profile_id: &str,
         exception_type: &str,
         resource_id: Option<&str>,
+        now: DateTime<Utc>,
     ) -> Result<Option<DbException>> {
         let pool = db.pool()?;
 
@@ -95,7 +112,7 @@ impl ExceptionQueries {
                 )
                 .bind(profile_id)
                 .bind(app_id)
-                .bind(Utc::now())
+                .bind(now)
                 .fetch_optional(pool)
                 .await
             }
@@ -114,7 +131,7 @@ impl ExceptionQueries {
                 )
                 .bind(profile_id)
                 .bind(website)
-                .bind(Utc::now())
+                .bind(now)
                 .fetch_optional(pool)
                 .await
             }
@@ -131,7 +148,7 @@ impl ExceptionQueries {
                     "#,
                 )
                 .bind(profile_id)
-                .bind(Utc::now())
+                .bind(now)
                 .fetch_optional(pool)
                 .await
             }
@@ -150,7 +167,7 @@ impl ExceptionQueries {
                 )
                 .bind(profile_id)
                 .bind(exception_type)
-                .bind(Utc::now())
+                .bind(now)
                 .fetch_optional(pool)
                 .await
             }
@@ -201,28 +218,29 @@ impl ExceptionQueries {
         .map_err(DbError::Sqlx)
     }
 
-    /// Clean up expired exceptions
-    pub async fn cleanup_expired(db: &Database) -> Result<u64> {
+    /// Deactivate exceptions that expired by `now`
+    pub async fn cleanup_expired(db: &Database, now: DateTime<Utc>) -> Result<u64> {
         let pool = db.pool()?;
 
         let result =
             sqlx::query("UPDATE exceptions SET active = 0 WHERE expires_at <= ? AND active = 1")
-                .bind(Utc::now())
+                .bind(now)
                 .execute(pool)
                 .await?;
 
         Ok(result.rows_affected())
     }
 
-    /// Get exception usage statistics for a profile
+    /// Get exception usage statistics for a profile over the `days` before `now`
     pub async fn get_usage_stats(
         db: &Database,
         profile_id: &str,
         days: i64,
+        now: DateTime<Utc>,
     ) -> Result<(i64, i64, i64)> {
         let pool = db.pool()?;
 
-        let since = Utc::now() - chrono::Duration::days(days);
+        let since = now - chrono::Duration::days(days);
         let row: (i64, i64, i64) = sqlx::query_as(
             r#"
             SELECT 
@@ -233,7 +251,7 @@ impl ExceptionQueries {
             WHERE profile_id = ? AND granted_at >= ?
             "#,
         )
-        .bind(Utc::now())
+        .bind(now)
         .bind(profile_id)
         .bind(since)
         .fetch_one(pool)
@@ -263,21 +281,22 @@ impl ExceptionQueries {
         .map_err(DbError::Sqlx)
     }
 
-    /// Get recently expired exceptions for review
+    /// Get exceptions that expired in the `hours` before `now`
     pub async fn list_recently_expired(
         db: &Database,
         profile_id: &str,
         hours: i64,
+        now: DateTime<Utc>,
     ) -> Result<Vec<DbException>> {
         let pool = db.pool()?;
 
-        let since = Utc::now() - chrono::Duration::hours(hours);
+        let since = now - chrono::Duration::hours(hours);
         sqlx::query_as::<_, DbException>(
             "SELECT * FROM exceptions WHERE profile_id = ? AND expires_at BETWEEN ? AND ? ORDER BY expires_at DESC",
         )
         .bind(profile_id)
         .bind(since)
-        .bind(Utc::now())
+        .bind(now)
         .fetch_all(pool)
         .await
         .map_err(DbError::Sqlx)
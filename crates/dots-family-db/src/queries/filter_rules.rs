@@ -154,6 +154,29 @@ impl FilterRuleQueries {
         Ok(count > 0)
     }
 
+    /// Category of the most specific enabled domain rule covering `domain`
+    pub async fn category_for_domain(db: &Database, domain: &str) -> Result<Option<String>> {
+        let pool = db.pool()?;
+
+        let category: Option<String> = sqlx::query_scalar(
+            r#"SELECT fr.category
+               FROM filter_rules fr
+               JOIN filter_lists fl ON fr.list_id = fl.id
+               WHERE fr.rule_type = 'domain'
+               AND fr.category IS NOT NULL
+               AND fl.enabled = 1
+               AND (fr.pattern = ? OR ? LIKE '%.' || fr.pattern)
+               ORDER BY LENGTH(fr.pattern) DESC
+               LIMIT 1"#,
+        )
+        .bind(domain)
+        .bind(domain)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(category)
+    }
+
     /// Get rules by category
     pub async fn get_by_category(db: &Database, category: &str) -> Result<Vec<FilterRule>> {
         let pool = db.pool()?;
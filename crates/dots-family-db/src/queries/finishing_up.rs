@@ -0,0 +1,82 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::Database;
+
+pub struct FinishingUpQueries;
+
+impl FinishingUpQueries {
+    /// Record the profile's finishing-up grant for `day`, made at `now`.
+    /// Returns false when it already had one that day.
+    pub async fn claim(
+        db: &Database,
+        profile_id: &str,
+        day: NaiveDate,
+        minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let result = sqlx::query(
+            r#"INSERT OR IGNORE INTO finishing_up_grants (profile_id, day, minutes, granted_at)
+               VALUES (?, ?, ?, ?)"#,
+        )
+        .bind(profile_id)
+        .bind(day.format("%Y-%m-%d").to_string())
+        .bind(minutes as i64)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether the profile was granted finishing up on `day`
+    pub async fn claimed(db: &Database, profile_id: &str, day: NaiveDate) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM finishing_up_grants WHERE profile_id = ? AND day = ?",
+        )
+        .bind(profile_id)
+        .bind(day.format("%Y-%m-%d").to_string())
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig, models::NewProfile, queries::profiles::ProfileQueries,
+    };
+
+    #[tokio::test]
+    async fn test_claim_once_per_day() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        assert!(!FinishingUpQueries::claimed(&db, &profile.id, day).await.unwrap());
+        let now = Utc::now();
+        assert!(FinishingUpQueries::claim(&db, &profile.id, day, 10, now).await.unwrap());
+        assert!(!FinishingUpQueries::claim(&db, &profile.id, day, 10, now).await.unwrap());
+        assert!(FinishingUpQueries::claimed(&db, &profile.id, day).await.unwrap());
+        assert!(FinishingUpQueries::claim(&db, &profile.id, day.succ_opt().unwrap(), 10, now)
+            .await
+            .unwrap());
+    }
+}
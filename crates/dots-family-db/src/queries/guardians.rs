@@ -0,0 +1,205 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Row};
+
+use crate::Database;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardianRow {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+    pub role: String,             // 'admin', 'approver', 'viewer'
+    pub profile_ids: Vec<String>, // empty for every profile
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+}
+
+pub struct GuardianQueries;
+
+impl GuardianQueries {
+    /// Create a guardian account and its profile scope
+    pub async fn create(
+        db: &Database,
+        id: &str,
+        name: &str,
+        password_hash: &str,
+        role: &str,
+        profile_ids: &[String],
+        created_by: &str,
+    ) -> Result<()> {
+        let pool = db.pool()?;
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"INSERT INTO guardians (id, name, password_hash, role, created_by)
+               VALUES (?, ?, ?, ?, ?)"#,
+        )
+        .bind(id)
+        .bind(name)
+        .bind(password_hash)
+        .bind(role)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?;
+
+        for profile_id in profile_ids {
+            sqlx::query("INSERT INTO guardian_profiles (guardian_id, profile_id) VALUES (?, ?)")
+                .bind(id)
+                .bind(profile_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_by_id(db: &Database, id: &str) -> Result<Option<GuardianRow>> {
+        let pool = db.pool()?;
+
+        let row = sqlx::query(
+            r#"SELECT id, name, password_hash, role, created_at, created_by
+               FROM guardians WHERE id = ?"#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(Self::with_profiles(db, &row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_by_name(db: &Database, name: &str) -> Result<Option<GuardianRow>> {
+        let pool = db.pool()?;
+
+        let row = sqlx::query(
+            r#"SELECT id, name, password_hash, role, created_at, created_by
+               FROM guardians WHERE name = ?"#,
+        )
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(Self::with_profiles(db, &row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List every guardian, oldest first
+    pub async fn list_all(db: &Database) -> Result<Vec<GuardianRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT id, name, password_hash, role, created_at, created_by
+               FROM guardians
+               ORDER BY created_at ASC, name ASC"#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut guardians = Vec::with_capacity(rows.len());
+        for row in &rows {
+            guardians.push(Self::with_profiles(db, row).await?);
+        }
+        Ok(guardians)
+    }
+
+    /// Delete a guardian by name, returning whether it existed
+    pub async fn delete_by_name(db: &Database, name: &str) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let result =
+            sqlx::query("DELETE FROM guardians WHERE name = ?").bind(name).execute(pool).await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn with_profiles(db: &Database, row: &SqliteRow) -> Result<GuardianRow> {
+        let pool = db.pool()?;
+        let id: String = row.get("id");
+
+        let profile_ids = sqlx::query_scalar(
+            "SELECT profile_id FROM guardian_profiles WHERE guardian_id = ? ORDER BY profile_id",
+        )
+        .bind(&id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(GuardianRow {
+            id,
+            name: row.get("name"),
+            password_hash: row.get("password_hash"),
+            role: row.get("role"),
+            profile_ids,
+            created_at: row.get("created_at"),
+            created_by: row.get("created_by"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig, models::NewProfile, queries::profiles::ProfileQueries,
+    };
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_delete_guardians() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        GuardianQueries::create(&db, "g-1", "mum", "hash-1", "admin", &[], "parent").await.unwrap();
+        GuardianQueries::create(
+            &db,
+            "g-2",
+            "babysitter",
+            "hash-2",
+            "approver",
+            std::slice::from_ref(&profile.id),
+            "mum",
+        )
+        .await
+        .unwrap();
+
+        // Names are unique and roles are constrained
+        assert!(GuardianQueries::create(&db, "g-3", "mum", "hash", "viewer", &[], "parent")
+            .await
+            .is_err());
+        assert!(GuardianQueries::create(&db, "g-4", "gran", "hash", "owner", &[], "parent")
+            .await
+            .is_err());
+
+        let babysitter = GuardianQueries::get_by_name(&db, "babysitter").await.unwrap().unwrap();
+        assert_eq!(babysitter.role, "approver");
+        assert_eq!(babysitter.profile_ids, vec![profile.id.clone()]);
+        assert_eq!(GuardianQueries::list_all(&db).await.unwrap().len(), 2);
+
+        assert!(GuardianQueries::delete_by_name(&db, "babysitter").await.unwrap());
+        assert!(!GuardianQueries::delete_by_name(&db, "babysitter").await.unwrap());
+        assert!(GuardianQueries::get_by_id(&db, "g-2").await.unwrap().is_none());
+    }
+}
@@ -1,7 +1,9 @@
 pub mod activities;
+pub mod activity_coverage;
 pub mod app_info_cache;
 pub mod approval_requests;
 pub mod audit;
+pub mod auto_approval_rules;
 pub mod custom_rules;
 pub mod daily_summaries;
 pub mod ebpf_metrics; // Phase 3 eBPF metrics
@@ -9,6 +11,8 @@ pub mod events;
 pub mod exceptions;
 pub mod filter_lists;
 pub mod filter_rules;
+pub mod finishing_up;
+pub mod guardians;
 pub mod network_activity;
 pub mod policy_cache;
 pub mod policy_versions;
@@ -16,18 +20,24 @@ pub mod profiles;
 pub mod sessions;
 // pub mod terminal;  // Disabled due to schema mismatch
 pub mod terminal_activity;
+pub mod time_credits;
 pub mod weekly_summaries;
 
 pub use activities::ActivityQueries;
+pub use activity_coverage::ActivityCoverageQueries;
 pub use approval_requests::ApprovalRequestQueries;
 pub use audit::AuditQueries;
+pub use auto_approval_rules::AutoApprovalRuleQueries;
 pub use daily_summaries::DailySummaryQueries;
 pub use events::EventQueries;
 pub use exceptions::ExceptionQueries;
+pub use finishing_up::FinishingUpQueries;
+pub use guardians::GuardianQueries;
 pub use network_activity::NetworkActivityQueries;
 pub use policy_versions::PolicyVersionQueries;
 pub use profiles::ProfileQueries;
 pub use sessions::SessionQueries;
 // pub use terminal::*;  // Disabled due to missing table migrations
 pub use terminal_activity::TerminalActivityQueries;
+pub use time_credits::TimeCreditQueries;
 pub use weekly_summaries::WeeklySummaryQueries;
@@ -1,16 +1,19 @@
 use crate::connection::Database;
 use crate::error::{DbError, Result};
-use crate::models::{DbSession, NewSession};
-use chrono::Utc;
+use crate::models::{DbSession, NewSession, SessionTotals};
+use chrono::{DateTime, Utc};
 
 pub struct SessionQueries;
 
 impl SessionQueries {
-    pub async fn create(db: &Database, session: NewSession) -> Result<DbSession> {
+    /// Start a session at `now`
+    pub async fn create(
+        db: &Database,
+        session: NewSession,
+        now: DateTime<Utc>,
+    ) -> Result<DbSession> {
         let pool = db.pool()?;
 
-        let now = Utc::now();
-
         sqlx::query(
             r#"
             INSERT INTO sessions (id, profile_id, start_time)
@@ -48,14 +51,13 @@ impl SessionQueries {
         .map_err(DbError::Sqlx)
     }
 
+    /// End a session at `now`
     pub async fn end_session(
         db: &Database,
         id: &str,
         end_reason: &str,
-        duration_seconds: i64,
-        screen_time_seconds: i64,
-        active_time_seconds: i64,
-        idle_time_seconds: i64,
+        totals: &SessionTotals,
+        now: DateTime<Utc>,
     ) -> Result<()> {
         let pool = db.pool()?;
 
@@ -67,12 +69,12 @@ impl SessionQueries {
             WHERE id = ?
             "#,
         )
-        .bind(Utc::now())
+        .bind(now)
         .bind(end_reason)
-        .bind(duration_seconds)
-        .bind(screen_time_seconds)
-        .bind(active_time_seconds)
-        .bind(idle_time_seconds)
+        .bind(totals.duration_seconds)
+        .bind(totals.screen_time_seconds)
+        .bind(totals.active_time_seconds)
+        .bind(totals.idle_time_seconds)
         .bind(id)
         .execute(pool)
         .await?;
@@ -84,6 +86,32 @@ impl SessionQueries {
         }
     }
 
+    /// Whether the profile had a session at any point in `[start, end)`
+    pub async fn had_session_between(
+        db: &Database,
+        profile_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM sessions
+            WHERE profile_id = ?
+            AND datetime(start_time) < datetime(?)
+            AND (end_time IS NULL OR datetime(end_time) >= datetime(?))
+            "#,
+        )
+        .bind(profile_id)
+        .bind(end)
+        .bind(start)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
     pub async fn list_for_profile(
         db: &Database,
         profile_id: &str,
@@ -0,0 +1,457 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteRow, Row};
+
+use crate::Database;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeCreditRow {
+    pub id: String,
+    pub profile_id: String,
+    pub entry_type: String, // 'earned', 'rolled_over', 'spent', 'expired'
+    pub minutes: i64,       // positive for credits, negative for debits
+    pub remaining_minutes: i64,
+    pub reason: Option<String>,
+    pub source_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: String, // 'parent', 'system', 'child'
+}
+
+/// A ledger entry to be inserted
+#[derive(Debug, Clone)]
+pub struct NewTimeCredit {
+    pub profile_id: String,
+    pub entry_type: String,
+    pub minutes: i64,
+    pub reason: Option<String>,
+    pub source_id: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: String,
+}
+
+pub struct TimeCreditQueries;
+
+impl TimeCreditQueries {
+    /// Insert a ledger entry created at `now`, returning its ID.
+    ///
+    /// Credit entries ('earned', 'rolled_over') start with all minutes unspent.
+    pub async fn insert(
+        db: &Database,
+        entry: &NewTimeCredit,
+        now: DateTime<Utc>,
+    ) -> Result<String> {
+        let pool = db.pool()?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let remaining = match entry.entry_type.as_str() {
+            "earned" | "rolled_over" => entry.minutes.max(0),
+            _ => 0,
+        };
+
+        sqlx::query(
+            r#"INSERT INTO time_credit_ledger
+               (id, profile_id, entry_type, minutes, remaining_minutes, reason, source_id,
+                created_at, expires_at, created_by)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(&id)
+        .bind(&entry.profile_id)
+        .bind(&entry.entry_type)
+        .bind(entry.minutes)
+        .bind(remaining)
+        .bind(&entry.reason)
+        .bind(&entry.source_id)
+        .bind(now)
+        .bind(entry.expires_at)
+        .bind(&entry.created_by)
+        .execute(pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Full ledger history for a profile, newest first
+    pub async fn list_for_profile(db: &Database, profile_id: &str) -> Result<Vec<TimeCreditRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM time_credit_ledger
+               WHERE profile_id = ?
+               ORDER BY datetime(created_at) DESC"#,
+        )
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Ledger entries created in `[start, end)`, oldest first
+    pub async fn list_between(
+        db: &Database,
+        profile_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeCreditRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM time_credit_ledger
+               WHERE profile_id = ?
+               AND datetime(created_at) >= datetime(?) AND datetime(created_at) < datetime(?)
+               ORDER BY datetime(created_at) ASC"#,
+        )
+        .bind(profile_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Credit entries that still have unspent minutes and have not expired
+    pub async fn list_open_credits(
+        db: &Database,
+        profile_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TimeCreditRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM time_credit_ledger
+               WHERE profile_id = ? AND remaining_minutes > 0
+               AND (expires_at IS NULL OR datetime(expires_at) > datetime(?))
+               ORDER BY datetime(created_at) ASC"#,
+        )
+        .bind(profile_id)
+        .bind(now)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Minutes available to spend
+    pub async fn balance(db: &Database, profile_id: &str, now: DateTime<Utc>) -> Result<u32> {
+        let pool = db.pool()?;
+
+        let balance: Option<i64> = sqlx::query_scalar(
+            r#"SELECT SUM(remaining_minutes) FROM time_credit_ledger
+               WHERE profile_id = ? AND remaining_minutes > 0
+               AND (expires_at IS NULL OR datetime(expires_at) > datetime(?))"#,
+        )
+        .bind(profile_id)
+        .bind(now)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(balance.unwrap_or(0).max(0) as u32)
+    }
+
+    /// Minutes spent in `[start, end)`
+    pub async fn spent_between(
+        db: &Database,
+        profile_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<u32> {
+        let pool = db.pool()?;
+
+        let spent: Option<i64> = sqlx::query_scalar(
+            r#"SELECT SUM(-minutes) FROM time_credit_ledger
+               WHERE profile_id = ? AND entry_type = 'spent'
+               AND datetime(created_at) >= datetime(?) AND datetime(created_at) < datetime(?)"#,
+        )
+        .bind(profile_id)
+        .bind(start)
+        .bind(end)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(spent.unwrap_or(0).max(0) as u32)
+    }
+
+    /// Whether a rollover has already been recorded for the given source (day)
+    pub async fn rollover_exists(db: &Database, profile_id: &str, source_id: &str) -> Result<bool> {
+        let pool = db.pool()?;
+
+        let count: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM time_credit_ledger
+               WHERE profile_id = ? AND entry_type = 'rolled_over' AND source_id = ?"#,
+        )
+        .bind(profile_id)
+        .bind(source_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Record a spend at `now`, drawing the given amounts from credit entries.
+    ///
+    /// Runs in a transaction so the ledger never shows a partial spend.
+    pub async fn spend(
+        db: &Database,
+        spend: &NewTimeCredit,
+        draws: &[(String, u32)],
+        now: DateTime<Utc>,
+    ) -> Result<String> {
+        let pool = db.pool()?;
+        let mut tx = pool.begin().await?;
+        let id = uuid::Uuid::new_v4().to_string();
+
+        for (credit_id, minutes) in draws {
+            let result = sqlx::query(
+                r#"UPDATE time_credit_ledger
+                   SET remaining_minutes = remaining_minutes - ?
+                   WHERE id = ? AND remaining_minutes >= ?"#,
+            )
+            .bind(*minutes as i64)
+            .bind(credit_id)
+            .bind(*minutes as i64)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(anyhow::anyhow!(
+                    "Time credit {} no longer has enough minutes",
+                    credit_id
+                ));
+            }
+        }
+
+        sqlx::query(
+            r#"INSERT INTO time_credit_ledger
+               (id, profile_id, entry_type, minutes, remaining_minutes, reason, source_id,
+                created_at, expires_at, created_by)
+               VALUES (?, ?, 'spent', ?, 0, ?, ?, ?, NULL, ?)"#,
+        )
+        .bind(&id)
+        .bind(&spend.profile_id)
+        .bind(-spend.minutes.abs())
+        .bind(&spend.reason)
+        .bind(&spend.source_id)
+        .bind(now)
+        .bind(&spend.created_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Expire unspent credits past their expiry, recording an 'expired' entry for each.
+    ///
+    /// Returns the new 'expired' entries.
+    pub async fn expire_credits(db: &Database, now: DateTime<Utc>) -> Result<Vec<TimeCreditRow>> {
+        let pool = db.pool()?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM time_credit_ledger
+               WHERE remaining_minutes > 0 AND expires_at IS NOT NULL
+               AND datetime(expires_at) <= datetime(?)"#,
+        )
+        .bind(now)
+        .fetch_all(pool)
+        .await?;
+
+        let mut expired = Vec::new();
+        for row in rows.iter().map(Self::from_row) {
+            let credit = row?;
+            let mut tx = pool.begin().await?;
+
+            sqlx::query("UPDATE time_credit_ledger SET remaining_minutes = 0 WHERE id = ?")
+                .bind(&credit.id)
+                .execute(&mut *tx)
+                .await?;
+
+            let id = uuid::Uuid::new_v4().to_string();
+            let reason = format!("{} unspent minutes expired", credit.remaining_minutes);
+            sqlx::query(
+                r#"INSERT INTO time_credit_ledger
+                   (id, profile_id, entry_type, minutes, remaining_minutes, reason, source_id,
+                    created_at, expires_at, created_by)
+                   VALUES (?, ?, 'expired', ?, 0, ?, ?, ?, NULL, 'system')"#,
+            )
+            .bind(&id)
+            .bind(&credit.profile_id)
+            .bind(-credit.remaining_minutes)
+            .bind(&reason)
+            .bind(&credit.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+
+            expired.push(TimeCreditRow {
+                id,
+                profile_id: credit.profile_id,
+                entry_type: "expired".to_string(),
+                minutes: -credit.remaining_minutes,
+                remaining_minutes: 0,
+                reason: Some(reason),
+                source_id: Some(credit.id),
+                created_at: now,
+                expires_at: None,
+                created_by: "system".to_string(),
+            });
+        }
+
+        Ok(expired)
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<TimeCreditRow> {
+        Ok(TimeCreditRow {
+            id: row.get("id"),
+            profile_id: row.get("profile_id"),
+            entry_type: row.get("entry_type"),
+            minutes: row.get("minutes"),
+            remaining_minutes: row.get("remaining_minutes"),
+            reason: row.get("reason"),
+            source_id: row.get("source_id"),
+            created_at: row.get("created_at"),
+            expires_at: row.get("expires_at"),
+            created_by: row.get("created_by"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        connection::DatabaseConfig, models::NewProfile, queries::profiles::ProfileQueries,
+    };
+
+    async fn setup_test_db() -> (Database, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let config =
+            DatabaseConfig { path: db_path.to_str().unwrap().to_string(), encryption_key: None };
+
+        let db = Database::new(config).await.unwrap();
+        db.run_migrations().await.unwrap();
+        (db, dir)
+    }
+
+    fn credit(profile_id: &str, entry_type: &str, minutes: i64) -> NewTimeCredit {
+        NewTimeCredit {
+            profile_id: profile_id.to_string(),
+            entry_type: entry_type.to_string(),
+            minutes,
+            reason: None,
+            source_id: None,
+            expires_at: None,
+            created_by: "parent".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spend_and_balance() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let now = Utc::now();
+        let earned_id =
+            TimeCreditQueries::insert(&db, &credit(&profile.id, "earned", 30), now).await.unwrap();
+        assert_eq!(TimeCreditQueries::balance(&db, &profile.id, now).await.unwrap(), 30);
+
+        TimeCreditQueries::spend(
+            &db,
+            &credit(&profile.id, "spent", 20),
+            &[(earned_id.clone(), 20)],
+            now,
+        )
+        .await
+        .unwrap();
+        assert_eq!(TimeCreditQueries::balance(&db, &profile.id, now).await.unwrap(), 10);
+
+        // Overdrawing a credit fails without recording a spend
+        assert!(TimeCreditQueries::spend(
+            &db,
+            &credit(&profile.id, "spent", 20),
+            &[(earned_id, 20)],
+            now
+        )
+        .await
+        .is_err());
+
+        let start = now - chrono::Duration::hours(1);
+        let end = now + chrono::Duration::hours(1);
+        assert_eq!(
+            TimeCreditQueries::spent_between(&db, &profile.id, start, end).await.unwrap(),
+            20
+        );
+        assert_eq!(TimeCreditQueries::list_for_profile(&db, &profile.id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_dated_by_the_given_time() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        // A trusted time well away from the wall clock
+        let then = Utc::now() - chrono::Duration::days(3);
+        let earned_id =
+            TimeCreditQueries::insert(&db, &credit(&profile.id, "earned", 30), then).await.unwrap();
+        TimeCreditQueries::spend(&db, &credit(&profile.id, "spent", 5), &[(earned_id, 5)], then)
+            .await
+            .unwrap();
+
+        let hour = chrono::Duration::hours(1);
+        assert_eq!(
+            TimeCreditQueries::spent_between(&db, &profile.id, then - hour, then + hour)
+                .await
+                .unwrap(),
+            5
+        );
+        let today = Utc::now();
+        assert_eq!(
+            TimeCreditQueries::spent_between(&db, &profile.id, today - hour, today + hour)
+                .await
+                .unwrap(),
+            0
+        );
+        for entry in TimeCreditQueries::list_for_profile(&db, &profile.id).await.unwrap() {
+            assert_eq!(entry.created_at.timestamp(), then.timestamp());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expire_credits() {
+        let (db, _dir) = setup_test_db().await;
+
+        let profile =
+            NewProfile::new("TestChild".to_string(), "8-12".to_string(), "{}".to_string());
+        let profile = ProfileQueries::create(&db, profile).await.unwrap();
+
+        let mut rolled = credit(&profile.id, "rolled_over", 15);
+        rolled.source_id = Some("2026-01-01".to_string());
+        rolled.expires_at = Some(Utc::now() - chrono::Duration::minutes(1));
+        TimeCreditQueries::insert(&db, &rolled, Utc::now()).await.unwrap();
+        TimeCreditQueries::insert(&db, &credit(&profile.id, "earned", 10), Utc::now())
+            .await
+            .unwrap();
+
+        assert!(TimeCreditQueries::rollover_exists(&db, &profile.id, "2026-01-01").await.unwrap());
+        assert_eq!(TimeCreditQueries::balance(&db, &profile.id, Utc::now()).await.unwrap(), 10);
+
+        let expired = TimeCreditQueries::expire_credits(&db, Utc::now()).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].minutes, -15);
+        assert!(TimeCreditQueries::expire_credits(&db, Utc::now()).await.unwrap().is_empty());
+        assert_eq!(TimeCreditQueries::list_for_profile(&db, &profile.id).await.unwrap().len(), 3);
+    }
+}
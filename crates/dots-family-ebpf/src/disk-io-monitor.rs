@@ -2,7 +2,9 @@
 #![no_main]
 
 use aya_ebpf::{
-    helpers::{bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_ktime_get_ns},
+    helpers::{
+        bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_get_current_uid_gid, bpf_ktime_get_ns,
+    },
     macros::{map, tracepoint},
     maps::{HashMap, RingBuf},
     programs::TracePointContext,
@@ -12,6 +14,7 @@ use aya_ebpf::{
 #[derive(Clone, Copy)]
 pub struct DiskIOEvent {
     pub pid: u32,
+    pub uid: u32, // Used by userspace to attribute the event to a child profile
     pub comm: [u8; 16],
     pub device_major: u32,
     pub device_minor: u32,
@@ -26,10 +29,17 @@ pub struct DiskIOEvent {
 #[map]
 static DISK_IO_EVENTS: RingBuf = RingBuf::with_byte_size(512 * 1024, 0);
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PendingIo {
+    pub start_ns: u64,
+    pub uid: u32, // Issuing user; completion runs in interrupt context
+}
+
 // Store pending I/O requests to calculate latency
-// Key: request pointer, Value: (timestamp, pid)
+// Key: sector, Value: issue time and issuing user
 #[map]
-static PENDING_IO: HashMap<u64, u64> = HashMap::with_max_entries(10240, 0);
+static PENDING_IO: HashMap<u64, PendingIo> = HashMap::with_max_entries(10240, 0);
 
 #[tracepoint]
 pub fn block_rq_issue(ctx: TracePointContext) -> u32 {
@@ -41,10 +51,11 @@ pub fn block_rq_issue(ctx: TracePointContext) -> u32 {
     // Use sector as key (unique per request)
     // Store timestamp for latency calculation
     let timestamp = unsafe { bpf_ktime_get_ns() };
+    let pending = PendingIo { start_ns: timestamp, uid: bpf_get_current_uid_gid() as u32 };
 
     // Store the start time in the map
     unsafe {
-        let _ = PENDING_IO.insert(&sector, &timestamp, 0);
+        let _ = PENDING_IO.insert(&sector, &pending, 0);
     }
 
     0
@@ -74,8 +85,13 @@ pub fn block_rq_complete(ctx: TracePointContext) -> u32 {
     let current_time = unsafe { bpf_ktime_get_ns() };
 
     // Calculate latency by looking up start time
-    let start_time = unsafe { PENDING_IO.get(&sector).copied().unwrap_or(0) };
-    let latency = if start_time > 0 { current_time.saturating_sub(start_time) } else { 0 };
+    let pending = unsafe { PENDING_IO.get(&sector).copied() };
+    let latency = match pending {
+        Some(pending) if pending.start_ns > 0 => current_time.saturating_sub(pending.start_ns),
+        _ => 0,
+    };
+    // The current task at completion is unrelated to the request, so prefer the issuer
+    let uid = pending.map(|pending| pending.uid).unwrap_or(bpf_get_current_uid_gid() as u32);
 
     // Try to read operation type from context (offset may vary)
     // 0 = read, 1 = write
@@ -83,6 +99,7 @@ pub fn block_rq_complete(ctx: TracePointContext) -> u32 {
 
     let event = DiskIOEvent {
         pid,
+        uid,
         comm,
         device_major,
         device_minor,
@@ -111,6 +128,7 @@ pub fn block_rq_complete(ctx: TracePointContext) -> u32 {
 pub fn block_bio_queue(ctx: TracePointContext) -> u32 {
     let pid_tgid = bpf_get_current_pid_tgid();
     let pid = (pid_tgid >> 32) as u32;
+    let uid = bpf_get_current_uid_gid() as u32;
 
     let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
 
@@ -128,6 +146,7 @@ pub fn block_bio_queue(ctx: TracePointContext) -> u32 {
     // Bio queue events don't have latency (not completed yet)
     let event = DiskIOEvent {
         pid,
+        uid,
         comm,
         device_major,
         device_minor,
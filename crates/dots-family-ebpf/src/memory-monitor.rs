@@ -2,7 +2,9 @@
 #![no_main]
 
 use aya_ebpf::{
-    helpers::{bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_ktime_get_ns},
+    helpers::{
+        bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_get_current_uid_gid, bpf_ktime_get_ns,
+    },
     macros::{map, tracepoint},
     maps::RingBuf,
     programs::TracePointContext,
@@ -12,6 +14,7 @@ use aya_ebpf::{
 #[derive(Clone, Copy)]
 pub struct MemoryEvent {
     pub pid: u32,
+    pub uid: u32, // Used by userspace to attribute the event to a child profile
     pub comm: [u8; 16],
     pub event_type: u32, // 1=alloc, 2=free, 3=mmap, 4=munmap
     pub size: u64,
@@ -28,6 +31,7 @@ static MEMORY_EVENTS: RingBuf = RingBuf::with_byte_size(1024 * 1024, 0);
 pub fn kmem_kmalloc(ctx: TracePointContext) -> u32 {
     let pid_tgid = bpf_get_current_pid_tgid();
     let pid = (pid_tgid >> 32) as u32;
+    let uid = bpf_get_current_uid_gid() as u32;
 
     // Get process name
     let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
@@ -43,6 +47,7 @@ pub fn kmem_kmalloc(ctx: TracePointContext) -> u32 {
     // For now, we'll use placeholder values and focus on tracking allocations
     let event = MemoryEvent {
         pid,
+        uid,
         comm,
         event_type: 1, // kmalloc
         size,
@@ -64,6 +69,7 @@ pub fn kmem_kmalloc(ctx: TracePointContext) -> u32 {
 pub fn kmem_kfree(ctx: TracePointContext) -> u32 {
     let pid_tgid = bpf_get_current_pid_tgid();
     let pid = (pid_tgid >> 32) as u32;
+    let uid = bpf_get_current_uid_gid() as u32;
 
     let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
 
@@ -74,6 +80,7 @@ pub fn kmem_kfree(ctx: TracePointContext) -> u32 {
 
     let event = MemoryEvent {
         pid,
+        uid,
         comm,
         event_type: 2, // kfree
         size: 0,       // Size not available on kfree
@@ -95,6 +102,7 @@ pub fn kmem_kfree(ctx: TracePointContext) -> u32 {
 pub fn kmem_mm_page_alloc(ctx: TracePointContext) -> u32 {
     let pid_tgid = bpf_get_current_pid_tgid();
     let pid = (pid_tgid >> 32) as u32;
+    let uid = bpf_get_current_uid_gid() as u32;
 
     let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
 
@@ -111,6 +119,7 @@ pub fn kmem_mm_page_alloc(ctx: TracePointContext) -> u32 {
 
     let event = MemoryEvent {
         pid,
+        uid,
         comm,
         event_type: 1, // Page allocation (treat as alloc)
         size,
@@ -132,6 +141,7 @@ pub fn kmem_mm_page_alloc(ctx: TracePointContext) -> u32 {
 pub fn kmem_mm_page_free(ctx: TracePointContext) -> u32 {
     let pid_tgid = bpf_get_current_pid_tgid();
     let pid = (pid_tgid >> 32) as u32;
+    let uid = bpf_get_current_uid_gid() as u32;
 
     let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
 
@@ -147,6 +157,7 @@ pub fn kmem_mm_page_free(ctx: TracePointContext) -> u32 {
 
     let event = MemoryEvent {
         pid,
+        uid,
         comm,
         event_type: 2, // Page free
         size,
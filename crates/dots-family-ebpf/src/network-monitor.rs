@@ -2,211 +2,283 @@
 #![no_main]
 
 use aya_ebpf::{
-    helpers::{bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_probe_read_kernel},
-    macros::{kprobe, map},
-    maps::RingBuf,
-    programs::ProbeContext,
-    EbpfContext,
+    bindings::bpf_sock_addr,
+    helpers::{
+        bpf_get_current_comm, bpf_get_current_pid_tgid, bpf_get_current_uid_gid, bpf_ktime_get_ns,
+    },
+    macros::{cgroup_sock_addr, kprobe, map, tracepoint},
+    maps::{LruHashMap, RingBuf},
+    programs::{ProbeContext, SockAddrContext, TracePointContext},
 };
 
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+const TCP_ESTABLISHED: i32 = 1;
+const TCP_SYN_SENT: i32 = 2;
+const TCP_SYN_RECV: i32 = 3;
+const TCP_CLOSE: i32 = 7;
+
+/// UDP flows are only reported once per interval, sendmsg fires per datagram
+const UDP_REPORT_INTERVAL_NS: u64 = 30_000_000_000;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct NetworkEvent {
-    pub event_type: u32, // 1 = connect, 2 = bind, 3 = accept, 4 = close
+    pub event_type: u32, // 1 = connect, 2 = send, 3 = receive, 4 = close
     pub pid: u32,
+    pub uid: u32,
     pub comm: [u8; 16], // Process name
-    pub src_addr: u32,
-    pub dst_addr: u32,
-    pub src_port: u16,
-    pub dst_port: u16,
-    pub protocol: u8,    // TCP = 6, UDP = 17
+    pub family: u16,    // AF_INET = 2, AF_INET6 = 10
+    pub protocol: u8,   // TCP = 6, UDP = 17
+    pub _padding: u8,
+    pub src_port: u16, // Host byte order
+    pub dst_port: u16, // Host byte order
+    pub src_addr: [u8; 16], // Network byte order, IPv4 uses the first 4 bytes
+    pub dst_addr: [u8; 16],
     pub bytes_sent: u64, // For data transfer events
     pub bytes_received: u64,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UdpFlow {
+    pub pid: u32,
+    pub family: u16,
+    pub dst_port: u16,
+    pub dst_addr: [u8; 16],
+}
+
 #[map]
 static NETWORK_EVENTS: RingBuf = RingBuf::with_byte_size(512 * 1024, 0);
 
-#[kprobe]
-pub fn tcp_connect(ctx: ProbeContext) -> u32 {
-    // Get PID from current process
-    // bpf_get_current_pid_tgid() returns u64 where high 32 bits = tgid, low 32 bits = pid
-    let pid_tgid = bpf_get_current_pid_tgid();
-    let pid = (pid_tgid >> 32) as u32; // TGID (actual process ID)
-
-    // Get process name
-    let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
-
-    // Phase 2: Extract socket information from struct sock *
-    // The first argument to tcp_connect is struct sock *sk
-    let mut src_addr: u32 = 0;
-    let mut dst_addr: u32 = 0;
-    let mut src_port: u16 = 0;
-    let mut dst_port: u16 = 0;
-
-    // Try to get the socket pointer from the first argument
-    if let Some(sk_ptr) = ctx.arg::<u64>(0) {
-        if sk_ptr != 0 {
-            // struct sock has a __sk_common field at the beginning
-            // struct sock_common contains network addressing fields
-            // Offsets are approximate and may need adjustment for different kernels
-            // This is a best-effort approach without BTF/CO-RE
-
-            // Try to read source address (skc_rcv_saddr offset ~24 bytes in sock_common)
-            let src_addr_ptr = (sk_ptr + 24) as *const u32;
-            src_addr = unsafe { bpf_probe_read_kernel(src_addr_ptr) }.unwrap_or(0);
-
-            // Try to read destination address (skc_daddr offset ~28 bytes)
-            let dst_addr_ptr = (sk_ptr + 28) as *const u32;
-            dst_addr = unsafe { bpf_probe_read_kernel(dst_addr_ptr) }.unwrap_or(0);
-
-            // Try to read source port (skc_num offset ~32 bytes)
-            let src_port_ptr = (sk_ptr + 32) as *const u16;
-            src_port = unsafe { bpf_probe_read_kernel(src_port_ptr) }.unwrap_or(0);
-
-            // Try to read destination port (skc_dport offset ~34 bytes)
-            let dst_port_ptr = (sk_ptr + 34) as *const u16;
-            dst_port = unsafe { bpf_probe_read_kernel(dst_port_ptr) }.unwrap_or(0);
-
-            // Convert from network byte order (big endian) to host byte order
-            dst_port = u16::from_be(dst_port);
-        }
-    }
+/// TCP sockets seen by `inet_sock_set_state`, keyed by `struct sock *`.
+/// Send and receive probes look up addresses here instead of reading
+/// `struct sock` at offsets that differ between kernels.
+#[map]
+static TCP_SOCKETS: LruHashMap<u64, NetworkEvent> = LruHashMap::with_max_entries(16384, 0);
 
-    let event = NetworkEvent {
-        event_type: 1, // TCP connect
+/// Last time each UDP flow was reported
+#[map]
+static UDP_FLOWS: LruHashMap<UdpFlow, u64> = LruHashMap::with_max_entries(8192, 0);
+
+// Field offsets from /sys/kernel/tracing/events/sock/inet_sock_set_state/format.
+// Tracepoint formats are a stable ABI, unlike the layout of struct sock.
+const TP_SKADDR: usize = 8;
+const TP_OLDSTATE: usize = 16;
+const TP_NEWSTATE: usize = 20;
+const TP_SPORT: usize = 24;
+const TP_DPORT: usize = 26;
+const TP_FAMILY: usize = 28;
+const TP_PROTOCOL: usize = 30;
+const TP_SADDR: usize = 32;
+const TP_DADDR: usize = 36;
+const TP_SADDR_V6: usize = 40;
+const TP_DADDR_V6: usize = 56;
+
+fn current_event(event_type: u32) -> NetworkEvent {
+    let pid = (bpf_get_current_pid_tgid() >> 32) as u32; // TGID (actual process ID)
+
+    NetworkEvent {
+        event_type,
         pid,
-        comm,
-        src_addr,
-        dst_addr,
-        src_port,
-        dst_port,
-        protocol: 6, // TCP
+        uid: bpf_get_current_uid_gid() as u32,
+        comm: bpf_get_current_comm().unwrap_or([0u8; 16]),
+        family: 0,
+        protocol: 0,
+        _padding: 0,
+        src_port: 0,
+        dst_port: 0,
+        src_addr: [0u8; 16],
+        dst_addr: [0u8; 16],
         bytes_sent: 0,
         bytes_received: 0,
-    };
+    }
+}
 
+fn submit(event: &NetworkEvent) {
     if let Some(mut buf) = NETWORK_EVENTS.reserve::<NetworkEvent>(0) {
-        buf.write(event);
+        buf.write(*event);
         buf.submit(0);
     }
+}
 
+#[tracepoint]
+pub fn inet_sock_set_state(ctx: TracePointContext) -> u32 {
+    let _ = try_inet_sock_set_state(&ctx);
     0
 }
 
-// Phase 3: Track TCP send bandwidth
-#[kprobe]
-pub fn tcp_sendmsg(ctx: ProbeContext) -> u32 {
-    let pid_tgid = bpf_get_current_pid_tgid();
-    let pid = (pid_tgid >> 32) as u32;
+fn try_inet_sock_set_state(ctx: &TracePointContext) -> Result<(), i64> {
+    let protocol: u16 = unsafe { ctx.read_at(TP_PROTOCOL)? };
+    if protocol != IPPROTO_TCP as u16 {
+        return Ok(());
+    }
 
-    let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
+    let skaddr: u64 = unsafe { ctx.read_at(TP_SKADDR)? };
+    let oldstate: i32 = unsafe { ctx.read_at(TP_OLDSTATE)? };
+    let newstate: i32 = unsafe { ctx.read_at(TP_NEWSTATE)? };
 
-    // tcp_sendmsg signature: int tcp_sendmsg(struct sock *sk, struct msghdr *msg, size_t size)
-    // Third argument is the size being sent
-    let bytes_sent = ctx.arg::<u64>(2).unwrap_or(0);
+    if newstate == TCP_CLOSE {
+        // The close may run in softirq context, so report the owner recorded at connect
+        if let Some(socket) = unsafe { TCP_SOCKETS.get(&skaddr) } {
+            let mut event = *socket;
+            event.event_type = 4;
+            submit(&event);
+        }
+        let _ = TCP_SOCKETS.remove(&skaddr);
+        return Ok(());
+    }
 
-    // Try to get socket info from first argument
-    let mut src_addr: u32 = 0;
-    let mut dst_addr: u32 = 0;
-    let mut src_port: u16 = 0;
-    let mut dst_port: u16 = 0;
+    // SYN_SENT is entered from connect() in the calling process. Accepted
+    // sockets become ESTABLISHED in softirq context, where the current task
+    // is unrelated, so they are only recorded for the send/receive probes.
+    let outgoing = newstate == TCP_SYN_SENT;
+    let accepted = oldstate == TCP_SYN_RECV && newstate == TCP_ESTABLISHED;
+    if !outgoing && !accepted {
+        return Ok(());
+    }
 
-    if let Some(sk_ptr) = ctx.arg::<u64>(0) {
-        if sk_ptr != 0 {
-            let src_addr_ptr = (sk_ptr + 24) as *const u32;
-            src_addr = unsafe { bpf_probe_read_kernel(src_addr_ptr) }.unwrap_or(0);
+    let mut event = if outgoing {
+        current_event(1)
+    } else {
+        NetworkEvent { pid: 0, uid: u32::MAX, comm: [0u8; 16], ..current_event(1) }
+    };
 
-            let dst_addr_ptr = (sk_ptr + 28) as *const u32;
-            dst_addr = unsafe { bpf_probe_read_kernel(dst_addr_ptr) }.unwrap_or(0);
+    event.family = unsafe { ctx.read_at(TP_FAMILY)? };
+    event.protocol = IPPROTO_TCP;
+    event.src_port = unsafe { ctx.read_at(TP_SPORT)? };
+    event.dst_port = unsafe { ctx.read_at(TP_DPORT)? };
+
+    match event.family {
+        AF_INET => {
+            let saddr: [u8; 4] = unsafe { ctx.read_at(TP_SADDR)? };
+            let daddr: [u8; 4] = unsafe { ctx.read_at(TP_DADDR)? };
+            event.src_addr[..4].copy_from_slice(&saddr);
+            event.dst_addr[..4].copy_from_slice(&daddr);
+        }
+        AF_INET6 => {
+            event.src_addr = unsafe { ctx.read_at(TP_SADDR_V6)? };
+            event.dst_addr = unsafe { ctx.read_at(TP_DADDR_V6)? };
+        }
+        _ => return Ok(()),
+    }
 
-            let src_port_ptr = (sk_ptr + 32) as *const u16;
-            src_port = unsafe { bpf_probe_read_kernel(src_port_ptr) }.unwrap_or(0);
+    let _ = TCP_SOCKETS.insert(&skaddr, &event, 0);
+    if outgoing {
+        submit(&event);
+    }
 
-            let dst_port_ptr = (sk_ptr + 34) as *const u16;
-            dst_port = unsafe { bpf_probe_read_kernel(dst_port_ptr) }.unwrap_or(0);
+    Ok(())
+}
 
-            dst_port = u16::from_be(dst_port);
-        }
-    }
+/// Report a transfer on a TCP socket already known from `inet_sock_set_state`
+fn tcp_transfer(ctx: &ProbeContext, event_type: u32) -> u32 {
+    let Some(sk_ptr) = ctx.arg::<u64>(0) else {
+        return 0;
+    };
+    let Some(socket) = (unsafe { TCP_SOCKETS.get(&sk_ptr) }) else {
+        return 0;
+    };
+
+    // Third argument is the size being sent or the requested size being received
+    let size = ctx.arg::<u64>(2).unwrap_or(0);
+    let current = current_event(event_type);
 
     let event = NetworkEvent {
-        event_type: 2, // TCP send
-        pid,
-        comm,
-        src_addr,
-        dst_addr,
-        src_port,
-        dst_port,
-        protocol: 6, // TCP
-        bytes_sent,
-        bytes_received: 0,
+        pid: current.pid,
+        uid: current.uid,
+        comm: current.comm,
+        event_type,
+        bytes_sent: if event_type == 2 { size } else { 0 },
+        bytes_received: if event_type == 3 { size } else { 0 },
+        ..*socket
     };
-
-    if let Some(mut buf) = NETWORK_EVENTS.reserve::<NetworkEvent>(0) {
-        buf.write(event);
-        buf.submit(0);
-    }
+    submit(&event);
 
     0
 }
 
+// Phase 3: Track TCP send bandwidth
+// int tcp_sendmsg(struct sock *sk, struct msghdr *msg, size_t size)
+#[kprobe]
+pub fn tcp_sendmsg(ctx: ProbeContext) -> u32 {
+    tcp_transfer(&ctx, 2)
+}
+
 // Phase 3: Track TCP receive bandwidth
+// int tcp_recvmsg(struct sock *sk, struct msghdr *msg, size_t len, ...)
 #[kprobe]
 pub fn tcp_recvmsg(ctx: ProbeContext) -> u32 {
-    let pid_tgid = bpf_get_current_pid_tgid();
-    let pid = (pid_tgid >> 32) as u32;
-
-    let comm = bpf_get_current_comm().unwrap_or([0u8; 16]);
+    tcp_transfer(&ctx, 3)
+}
 
-    // tcp_recvmsg signature: int tcp_recvmsg(struct sock *sk, struct msghdr *msg, size_t len, ...)
-    // Third argument is the requested size
-    // Note: The actual received size is the return value, but we track the requested size here
-    let bytes_received = ctx.arg::<u64>(2).unwrap_or(0);
+/// Report a UDP destination (including QUIC) from the stable `bpf_sock_addr` context
+fn udp_destination(ctx: &SockAddrContext, ipv6: bool) {
+    let sock_addr: &bpf_sock_addr = unsafe { &*ctx.sock_addr };
+    if sock_addr.protocol != IPPROTO_UDP as u32 {
+        return;
+    }
 
-    // Try to get socket info from first argument
-    let mut src_addr: u32 = 0;
-    let mut dst_addr: u32 = 0;
-    let mut src_port: u16 = 0;
-    let mut dst_port: u16 = 0;
+    let mut event = current_event(1);
+    event.protocol = IPPROTO_UDP;
+    // user_port holds the port in network byte order in its low 16 bits
+    event.dst_port = u16::from_be(sock_addr.user_port as u16);
 
-    if let Some(sk_ptr) = ctx.arg::<u64>(0) {
-        if sk_ptr != 0 {
-            let src_addr_ptr = (sk_ptr + 24) as *const u32;
-            src_addr = unsafe { bpf_probe_read_kernel(src_addr_ptr) }.unwrap_or(0);
+    if ipv6 {
+        event.family = AF_INET6;
+        for (i, word) in sock_addr.user_ip6.iter().enumerate() {
+            event.dst_addr[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+        }
+    } else {
+        event.family = AF_INET;
+        event.dst_addr[..4].copy_from_slice(&sock_addr.user_ip4.to_ne_bytes());
+    }
 
-            let dst_addr_ptr = (sk_ptr + 28) as *const u32;
-            dst_addr = unsafe { bpf_probe_read_kernel(dst_addr_ptr) }.unwrap_or(0);
+    let flow = UdpFlow {
+        pid: event.pid,
+        family: event.family,
+        dst_port: event.dst_port,
+        dst_addr: event.dst_addr,
+    };
+    let now = unsafe { bpf_ktime_get_ns() };
+    if let Some(last_seen) = unsafe { UDP_FLOWS.get(&flow) } {
+        if now.saturating_sub(*last_seen) < UDP_REPORT_INTERVAL_NS {
+            return;
+        }
+    }
+    let _ = UDP_FLOWS.insert(&flow, &now, 0);
 
-            let src_port_ptr = (sk_ptr + 32) as *const u16;
-            src_port = unsafe { bpf_probe_read_kernel(src_port_ptr) }.unwrap_or(0);
+    submit(&event);
+}
 
-            let dst_port_ptr = (sk_ptr + 34) as *const u16;
-            dst_port = unsafe { bpf_probe_read_kernel(dst_port_ptr) }.unwrap_or(0);
+// The cgroup hooks only observe; returning 1 always lets the call through.
+// TCP connects are ignored here because the tracepoint already reports them.
 
-            dst_port = u16::from_be(dst_port);
-        }
-    }
+#[cgroup_sock_addr(connect4)]
+pub fn udp_connect4(ctx: SockAddrContext) -> i32 {
+    udp_destination(&ctx, false);
+    1
+}
 
-    let event = NetworkEvent {
-        event_type: 3, // TCP receive
-        pid,
-        comm,
-        src_addr,
-        dst_addr,
-        src_port,
-        dst_port,
-        protocol: 6, // TCP
-        bytes_sent: 0,
-        bytes_received,
-    };
+#[cgroup_sock_addr(connect6)]
+pub fn udp_connect6(ctx: SockAddrContext) -> i32 {
+    udp_destination(&ctx, true);
+    1
+}
 
-    if let Some(mut buf) = NETWORK_EVENTS.reserve::<NetworkEvent>(0) {
-        buf.write(event);
-        buf.submit(0);
-    }
+#[cgroup_sock_addr(sendmsg4)]
+pub fn udp_sendmsg4(ctx: SockAddrContext) -> i32 {
+    udp_destination(&ctx, false);
+    1
+}
 
-    0
+#[cgroup_sock_addr(sendmsg6)]
+pub fn udp_sendmsg6(ctx: SockAddrContext) -> i32 {
+    udp_destination(&ctx, true);
+    1
 }
 
 #[panic_handler]
@@ -39,20 +39,17 @@ pub fn sched_process_exec(ctx: TracePointContext) -> u32 {
     let pid = (pid_tgid >> 32) as u32; // TGID (actual process ID)
     let tid = (pid_tgid & 0xFFFFFFFF) as u32; // TID (thread ID)
 
-    // Extract UID and GID
+    // Extract UID and GID: the low 32 bits hold the uid, the high 32 bits the gid
     let uid_gid = unsafe { bpf_get_current_uid_gid() };
-    let uid = (uid_gid >> 32) as u32;
-    let gid = (uid_gid & 0xFFFFFFFF) as u32;
+    let uid = uid_gid as u32;
+    let gid = (uid_gid >> 32) as u32;
 
     // Extract process name (comm)
     let comm = unsafe { bpf_get_current_comm() }.unwrap_or([0u8; 16]);
 
-    // Phase 2: Extract PPID from tracepoint context
-    // The sched_process_exec tracepoint provides parent_pid in its context
-    // We attempt to read it from offset 12 bytes (after common fields)
-    // Format: filename (ptr), pid (4), old_pid (4)
-    // If this fails, we'll fall back to 0 (same as Phase 1)
-    let ppid = unsafe { ctx.read_at::<i32>(12).unwrap_or(0) as u32 };
+    // The parent is not part of the sched_process_exec format, userspace
+    // resolves the parent chain from /proc while the process is alive
+    let ppid = 0;
 
     // Phase 2: Extract executable path from filename field
     // Use per-CPU buffer to avoid eBPF stack limit (512 bytes)
@@ -62,13 +59,13 @@ pub fn sched_process_exec(ctx: TracePointContext) -> u32 {
         // Initialize buffer to zeros
         *cmdline_buf = [0u8; 256];
 
-        // Try to read the filename pointer from the tracepoint context
-        // The filename is at offset 8 in the tracepoint (after common_pid at offset 4)
-        if let Ok(filename_ptr) = unsafe { ctx.read_at::<u64>(8) } {
-            // The filename_ptr is actually encoded as __data_loc type:
-            // high 16 bits = size, low 16 bits = offset from context start
-            let offset = (filename_ptr & 0xFFFF) as usize;
-            let size = ((filename_ptr >> 16) & 0xFFFF) as usize;
+        // sched_process_exec format: `__data_loc char[] filename` at offset 8,
+        // pid at 12 and old_pid at 16. A __data_loc field is a u32 whose low
+        // 16 bits are the offset of the data from the context start and whose
+        // high 16 bits are its length.
+        if let Ok(data_loc) = unsafe { ctx.read_at::<u32>(8) } {
+            let offset = (data_loc & 0xFFFF) as usize;
+            let size = (data_loc >> 16) as usize;
 
             // Limit size to our buffer (256 bytes - 1 for null terminator)
             let read_size = if size > 255 { 255 } else { size };
@@ -109,10 +106,15 @@ pub fn sched_process_exit(_ctx: TracePointContext) -> u32 {
     let pid_tgid = unsafe { bpf_get_current_pid_tgid() };
     let pid = (pid_tgid >> 32) as u32;
 
-    // Extract UID and GID
+    // Every thread exits through here; the process ends with its leader
+    if pid_tgid as u32 != pid {
+        return 0;
+    }
+
+    // Extract UID and GID: the low 32 bits hold the uid, the high 32 bits the gid
     let uid_gid = unsafe { bpf_get_current_uid_gid() };
-    let uid = (uid_gid >> 32) as u32;
-    let gid = (uid_gid & 0xFFFFFFFF) as u32;
+    let uid = uid_gid as u32;
+    let gid = (uid_gid >> 32) as u32;
 
     // Extract process name
     let comm = unsafe { bpf_get_current_comm() }.unwrap_or([0u8; 16]);
@@ -0,0 +1,154 @@
+//! Which local account a proxied connection comes from.
+//!
+//! The filter runs as its own system account, so the daemon cannot tell from
+//! the caller whose browsing is being reported. Clients reach the proxy over
+//! a local TCP connection, and the kernel's socket tables list the uid owning
+//! the client's end of it. That account is mapped to its child profile once
+//! per connection and the profile is reported with each request.
+
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::Path,
+};
+
+const PROC_NET: &str = "/proc/net";
+
+/// Both ends of a proxied connection, as seen by the proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientConnection {
+    /// The client's address, i.e. `TcpStream::peer_addr`
+    pub peer: SocketAddr,
+    /// The proxy's address, i.e. `TcpStream::local_addr`
+    pub local: SocketAddr,
+}
+
+impl ClientConnection {
+    /// uid owning the client's socket; `None` for remote or vanished clients
+    pub fn client_uid(&self) -> Option<u32> {
+        ["tcp", "tcp6"].iter().find_map(|table| {
+            let contents = fs::read_to_string(Path::new(PROC_NET).join(table)).ok()?;
+            socket_owner(&contents, self.peer, self.local)
+        })
+    }
+}
+
+/// Account name of a uid
+pub fn username_of_uid(uid: u32) -> Option<String> {
+    nix::unistd::User::from_uid(uid.into()).ok().flatten().map(|user| user.name)
+}
+
+/// Find the socket whose local end is `client` and remote end is `proxy` in
+/// a `/proc/net/tcp{,6}` table and return its uid
+fn socket_owner(table: &str, client: SocketAddr, proxy: SocketAddr) -> Option<u32> {
+    let (client, proxy) = (canonical(client), canonical(proxy));
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let local = canonical(parse_socket_addr(fields.get(1)?)?);
+        let remote = canonical(parse_socket_addr(fields.get(2)?)?);
+        if local == client && remote == proxy {
+            fields.get(7)?.parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// Parse an address as printed in `/proc/net/tcp{,6}`: the address in
+/// 32-bit words of host byte order, a colon, and the port in hex
+fn parse_socket_addr(field: &str) -> Option<SocketAddr> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let mut octets = Vec::with_capacity(16);
+    for word in address.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        octets.extend_from_slice(&word.to_ne_bytes());
+    }
+    let ip = match octets.len() {
+        4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(octets).ok()?)),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(octets).ok()?)),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// IPv4 clients of a dual-stack listener show up as IPv4-mapped IPv6
+fn canonical(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(addr.ip().to_canonical(), addr.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_v4(ip: Ipv4Addr) -> String {
+        format!("{:08X}", u32::from_ne_bytes(ip.octets()))
+    }
+
+    fn table(rows: &[(SocketAddr, SocketAddr, u32)]) -> String {
+        let mut table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n".to_string();
+        for (i, (local, remote, uid)) in rows.iter().enumerate() {
+            let (SocketAddr::V4(local), SocketAddr::V4(remote)) = (local, remote) else {
+                unreachable!()
+            };
+            table.push_str(&format!(
+                "{:4}: {}:{:04X} {}:{:04X} 01 00000000:00000000 00:00000000 00000000 {:5} 0 {} 1\n",
+                i,
+                hex_v4(*local.ip()),
+                local.port(),
+                hex_v4(*remote.ip()),
+                remote.port(),
+                uid,
+                1000 + i
+            ));
+        }
+        table
+    }
+
+    #[test]
+    fn test_client_socket_owner_is_found() {
+        let proxy: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let alice: SocketAddr = "127.0.0.1:40001".parse().unwrap();
+        let bob: SocketAddr = "127.0.0.1:40002".parse().unwrap();
+        let table = table(&[
+            // The proxy's own ends of the connections
+            (proxy, alice, 990),
+            (proxy, bob, 990),
+            (alice, proxy, 1001),
+            (bob, proxy, 1002),
+        ]);
+
+        assert_eq!(socket_owner(&table, alice, proxy), Some(1001));
+        assert_eq!(socket_owner(&table, bob, proxy), Some(1002));
+
+        // A dual-stack listener sees the client as an IPv4-mapped address
+        let mapped: SocketAddr = "[::ffff:127.0.0.1]:40001".parse().unwrap();
+        let mapped_proxy: SocketAddr = "[::ffff:127.0.0.1]:8080".parse().unwrap();
+        assert_eq!(socket_owner(&table, mapped, mapped_proxy), Some(1001));
+
+        let gone: SocketAddr = "127.0.0.1:40003".parse().unwrap();
+        assert_eq!(socket_owner(&table, gone, proxy), None);
+    }
+
+    #[test]
+    fn test_parse_ipv6_socket_addr() {
+        let loopback = format!("{}{:08X}", "0".repeat(24), u32::from_ne_bytes([0, 0, 0, 1]));
+        let addr = parse_socket_addr(&format!("{}:1F90", loopback)).unwrap();
+        assert_eq!(addr, "[::1]:8080".parse().unwrap());
+    }
+
+    #[test]
+    fn test_own_connection_belongs_to_current_user() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let connection = ClientConnection {
+            peer: server.peer_addr().unwrap(),
+            local: server.local_addr().unwrap(),
+        };
+        assert_eq!(connection.client_uid(), Some(nix::unistd::getuid().as_raw()));
+        drop(client);
+    }
+}
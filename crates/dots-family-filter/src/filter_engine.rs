@@ -1,12 +1,17 @@
 use anyhow::Result;
 use dots_family_common::types::Activity;
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, ProfileList},
+};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 use url::Url;
+use uuid::Uuid;
 use zbus::Connection;
 
+use crate::client_identity::{username_of_uid, ClientConnection};
 use crate::config::FilterConfig;
 use crate::rules::{FilterAction, FilterDecision, RuleEngine};
 
@@ -51,7 +56,42 @@ impl FilterEngine {
         }
     }
 
-    pub async fn evaluate_request(&self, url: &str, method: &str) -> Result<FilterDecision> {
+    /// Child profile a proxied connection belongs to, resolved from the
+    /// account owning the client's socket. Called once per connection; the
+    /// profile is then passed to [`Self::evaluate_request`] for its requests.
+    pub async fn profile_for_connection(&self, connection: &ClientConnection) -> Option<Uuid> {
+        let proxy = self.daemon_proxy.as_ref()?;
+        let username = connection.client_uid().and_then(username_of_uid)?;
+
+        let profiles = match proxy.list_profiles().await {
+            Ok(json) => match payload::decode::<ProfileList>(&json) {
+                Ok(list) => list.profiles,
+                Err(e) => {
+                    warn!("Failed to parse profiles from daemon: {}", e);
+                    return None;
+                }
+            },
+            Err(e) => {
+                warn!("Failed to list profiles: {}", e);
+                return None;
+            }
+        };
+        let profile_id = profiles
+            .into_iter()
+            .find(|profile| profile.username.as_deref() == Some(username.as_str()))
+            .map(|profile| profile.id);
+        debug!("Connection {} of {} is for profile {:?}", connection.peer, username, profile_id);
+        profile_id
+    }
+
+    /// Decide on a request made for `profile_id`, the child whose connection
+    /// it arrived on, if any
+    pub async fn evaluate_request(
+        &self,
+        url: &str,
+        method: &str,
+        profile_id: Option<Uuid>,
+    ) -> Result<FilterDecision> {
         debug!("Evaluating request: {} {}", method, url);
 
         if !self.config.filtering.enabled {
@@ -99,7 +139,7 @@ impl FilterEngine {
         }
 
         if self.config.daemon.log_activity {
-            self.log_activity(url, &decision).await;
+            self.log_activity(url, &decision, profile_id).await;
         }
 
         Ok(decision)
@@ -235,24 +275,25 @@ impl FilterEngine {
         )
     }
 
-    async fn log_activity(&self, url: &str, _decision: &FilterDecision) {
-        if let Some(ref proxy) = self.daemon_proxy {
-            let activity = Activity {
-                id: uuid::Uuid::new_v4(),
-                profile_id: uuid::Uuid::nil(),
-                timestamp: chrono::Utc::now(),
-                activity_type: dots_family_common::types::ActivityType::WebBrowsing {
-                    url: url.to_string(),
-                },
-                application: Some("web-filter".to_string()),
-                window_title: Some(url.to_string()),
-                duration_seconds: 0,
-            };
-
-            let activity_json = serde_json::to_string(&activity).unwrap_or_default();
-            if let Err(e) = proxy.report_activity(&activity_json).await {
-                warn!("Failed to log activity to daemon: {}", e);
+    async fn log_activity(&self, url: &str, _decision: &FilterDecision, profile_id: Option<Uuid>) {
+        let Some(ref proxy) = self.daemon_proxy else {
+            return;
+        };
+        // Browsing of accounts without a child profile is not recorded
+        let Some(profile_id) = profile_id else {
+            debug!("Not reporting {}: no child profile", url);
+            return;
+        };
+
+        // As a system component the daemon takes the profile we name
+        let activity = web_activity(url, profile_id);
+        match payload::encode(&activity) {
+            Ok(activity_json) => {
+                if let Err(e) = proxy.report_activity(&activity_json).await {
+                    warn!("Failed to log activity to daemon: {}", e);
+                }
             }
+            Err(e) => warn!("Failed to encode activity: {}", e),
         }
     }
 
@@ -285,6 +326,21 @@ impl FilterEngine {
     }
 }
 
+/// The activity reported for a request the filter saw
+fn web_activity(url: &str, profile_id: Uuid) -> Activity {
+    Activity {
+        id: Uuid::new_v4(),
+        profile_id,
+        timestamp: chrono::Utc::now(),
+        activity_type: dots_family_common::types::ActivityType::WebBrowsing {
+            url: url.to_string(),
+        },
+        application: Some("web-filter".to_string()),
+        window_title: Some(url.to_string()),
+        duration_seconds: 0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +379,16 @@ mod tests {
         assert!(content.contains("blocked.com"));
     }
 
+    #[test]
+    fn test_web_activity_is_reported_for_the_connections_profile() {
+        let profile_id = Uuid::new_v4();
+        let json = payload::encode(&web_activity("https://example.com/", profile_id)).unwrap();
+
+        let activity: Activity = payload::decode(&json).unwrap();
+        assert_eq!(activity.profile_id, profile_id);
+        assert_eq!(activity.application.as_deref(), Some("web-filter"));
+    }
+
     #[tokio::test]
     async fn test_custom_rule_addition() {
         let engine = create_test_engine().await;
@@ -332,7 +398,8 @@ mod tests {
             .await
             .unwrap();
 
-        let decision = engine.evaluate_request("https://badsite.com/path", "GET").await.unwrap();
+        let decision =
+            engine.evaluate_request("https://badsite.com/path", "GET", None).await.unwrap();
 
         matches!(decision.action, FilterAction::Block);
     }
@@ -1,4 +1,5 @@
 pub mod certificate_manager;
+pub mod client_identity;
 pub mod config;
 pub mod filter_engine;
 pub mod proxy;
@@ -6,6 +7,7 @@ pub mod rules;
 pub mod shuttle;
 
 pub use certificate_manager::*;
+pub use client_identity::*;
 pub use config::*;
 pub use filter_engine::*;
 pub use proxy::*;
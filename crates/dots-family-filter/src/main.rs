@@ -3,6 +3,7 @@ use clap::Parser;
 use tracing::info;
 
 mod certificate_manager;
+mod client_identity;
 mod config;
 mod filter_engine;
 mod proxy;
@@ -1,17 +1,6 @@
+use dots_family_proto::payload::ApprovalRequest;
 use gtk4::prelude::*;
 use relm4::prelude::*;
-use serde::{Deserialize, Serialize};
-
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct ApprovalRequest {
-    pub id: String,
-    pub profile_id: String,
-    pub profile_name: String,
-    pub request_type: String,
-    pub details: String,
-    pub created_at: String,
-    pub status: String,
-}
 
 #[derive(Debug)]
 pub enum ApprovalRequestCardMsg {
@@ -45,7 +34,7 @@ impl FactoryComponent for ApprovalRequestCard {
 
                     gtk4::Label {
                         #[watch]
-                        set_label: &self.request.profile_name,
+                        set_label: &self.request.profile_id,
                         add_css_class: "heading",
                         set_halign: gtk4::Align::Start,
                         set_hexpand: true,
@@ -61,7 +50,7 @@ impl FactoryComponent for ApprovalRequestCard {
                 // Details
                 gtk4::Label {
                     #[watch]
-                    set_label: &self.request.details,
+                    set_label: &serde_json::to_string(&self.request.details).unwrap_or_default(),
                     set_halign: gtk4::Align::Start,
                     set_wrap: true,
                     set_max_width_chars: 60,
@@ -70,7 +59,10 @@ impl FactoryComponent for ApprovalRequestCard {
                 // Timestamp
                 gtk4::Label {
                     #[watch]
-                    set_label: &format!("Requested: {}", self.request.created_at),
+                    set_label: &format!(
+                        "Requested: {}",
+                        self.request.requested_at.format("%Y-%m-%d %H:%M")
+                    ),
                     add_css_class: "caption",
                     add_css_class: "dim-label",
                     set_halign: gtk4::Align::Start,
@@ -2,50 +2,12 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
-use dots_family_proto::daemon::FamilyDaemonProxy;
+pub use dots_family_proto::payload::{ActivityReport, WeeklyReport};
+use dots_family_proto::{daemon::FamilyDaemonProxy, payload};
 use futures::StreamExt;
-use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use zbus::Connection;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ActivityReport {
-    pub date: NaiveDate,
-    pub screen_time_minutes: u32,
-    pub top_activity: String,
-    pub top_category: String,
-    pub violations: u32,
-    pub blocked_attempts: u32,
-    pub apps_used: Vec<AppUsage>,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct AppUsage {
-    pub app_id: String,
-    pub app_name: String,
-    pub category: String,
-    pub duration_minutes: u32,
-    pub percentage: f32,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct WeeklyReport {
-    pub week_start: NaiveDate,
-    pub total_screen_time_minutes: u32,
-    pub average_daily_minutes: u32,
-    pub most_active_day: String,
-    pub top_categories: Vec<CategoryUsage>,
-    pub policy_violations: u32,
-    pub educational_percentage: f32,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CategoryUsage {
-    pub category: String,
-    pub duration_minutes: u32,
-    pub percentage: f32,
-}
-
 #[derive(Clone, Debug)]
 pub struct DaemonClient {
     proxy: Arc<Mutex<Option<FamilyDaemonProxy<'static>>>>,
@@ -96,6 +58,28 @@ impl DaemonClient {
         proxy.get_remaining_time().await.map_err(|e| anyhow!("D-Bus error: {}", e))
     }
 
+    pub async fn request_finishing_up(&self) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy.request_finishing_up().await.map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
+    pub async fn submit_approval_request(
+        &self,
+        request_type: &str,
+        message: &str,
+        details_json: &str,
+    ) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy
+            .submit_approval_request(request_type, message, details_json)
+            .await
+            .map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
     pub async fn check_application_allowed(&self, app_id: &str) -> Result<bool> {
         let proxy_guard = self.proxy.lock().await;
         let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
@@ -140,11 +124,7 @@ impl DaemonClient {
 
         match proxy.get_daily_report(profile_id, &date_str).await {
             Ok(response_json) => {
-                if response_json.starts_with(r#"{"error""#) {
-                    return Err(anyhow!("Daemon error: {}", response_json));
-                }
-
-                let report: ActivityReport = serde_json::from_str(&response_json)
+                let report: ActivityReport = payload::decode(&response_json)
                     .map_err(|e| anyhow!("Failed to parse daily report: {}", e))?;
 
                 Ok(report)
@@ -165,11 +145,7 @@ impl DaemonClient {
 
         match proxy.get_weekly_report(profile_id, &week_start_str).await {
             Ok(response_json) => {
-                if response_json.starts_with(r#"{"error""#) {
-                    return Err(anyhow!("Daemon error: {}", response_json));
-                }
-
-                let report: WeeklyReport = serde_json::from_str(&response_json)
+                let report: WeeklyReport = payload::decode(&response_json)
                     .map_err(|e| anyhow!("Failed to parse weekly report: {}", e))?;
 
                 Ok(report)
@@ -191,15 +167,10 @@ impl DaemonClient {
         let start_date_str = start_date.format("%Y-%m-%d").to_string();
         let end_date_str = end_date.format("%Y-%m-%d").to_string();
 
-        match proxy.export_reports(profile_id, format, &start_date_str, &end_date_str).await {
-            Ok(response) => {
-                if response.starts_with(r#"{"error""#) {
-                    return Err(anyhow!("Daemon error: {}", response));
-                }
-                Ok(response)
-            }
-            Err(e) => Err(anyhow!("D-Bus error exporting reports: {}", e)),
-        }
+        proxy
+            .export_reports(profile_id, format, &start_date_str, &end_date_str)
+            .await
+            .map_err(|e| anyhow!("D-Bus error exporting reports: {}", e))
     }
 
     pub async fn list_pending_requests(&self, token: &str) -> Result<String> {
@@ -239,6 +210,48 @@ impl DaemonClient {
             .map_err(|e| anyhow!("D-Bus error: {}", e))
     }
 
+    pub async fn list_auto_approval_rules(&self, token: &str) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy.list_auto_approval_rules(token).await.map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
+    pub async fn add_auto_approval_rule(&self, rule_json: &str, token: &str) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy
+            .add_auto_approval_rule(rule_json, token)
+            .await
+            .map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
+    pub async fn set_auto_approval_rule_enabled(
+        &self,
+        rule_id: &str,
+        enabled: bool,
+        token: &str,
+    ) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy
+            .set_auto_approval_rule_enabled(rule_id, enabled, token)
+            .await
+            .map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
+    pub async fn remove_auto_approval_rule(&self, rule_id: &str, token: &str) -> Result<String> {
+        let proxy_guard = self.proxy.lock().await;
+        let proxy = proxy_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?;
+
+        proxy
+            .remove_auto_approval_rule(rule_id, token)
+            .await
+            .map_err(|e| anyhow!("D-Bus error: {}", e))
+    }
+
     /// Subscribe to approval_request_created signals
     /// This spawns a background task that listens for signals
     pub async fn subscribe_approval_requests<F>(&self, callback: F) -> Result<()>
@@ -268,4 +281,62 @@ impl DaemonClient {
 
         Ok(())
     }
+
+    /// Subscribe to approval_request_resolved signals
+    /// The callback receives the request ID, final status and parent's message
+    pub async fn subscribe_approval_resolutions<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(String, String, String) + Send + 'static,
+    {
+        let conn_guard = self.connection.lock().await;
+        let connection =
+            conn_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?.clone();
+        drop(conn_guard);
+
+        let proxy = FamilyDaemonProxy::new(&connection)
+            .await
+            .map_err(|e| anyhow!("Failed to create proxy for signals: {}", e))?;
+
+        let mut stream = proxy.receive_approval_request_resolved().await?;
+
+        tokio::spawn(async move {
+            while let Some(signal) = stream.next().await {
+                if let Ok(args) = signal.args() {
+                    callback(
+                        args.request_id().to_string(),
+                        args.status().to_string(),
+                        args.message().to_string(),
+                    );
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    pub async fn subscribe_enforcement_stages<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(String, i64) + Send + 'static,
+    {
+        let conn_guard = self.connection.lock().await;
+        let connection =
+            conn_guard.as_ref().ok_or_else(|| anyhow!("Not connected to daemon"))?.clone();
+        drop(conn_guard);
+
+        let proxy = FamilyDaemonProxy::new(&connection)
+            .await
+            .map_err(|e| anyhow!("Failed to create proxy for signals: {}", e))?;
+
+        let mut stream = proxy.receive_enforcement_stage_changed().await?;
+
+        tokio::spawn(async move {
+            while let Some(signal) = stream.next().await {
+                if let Ok(args) = signal.args() {
+                    callback(args.stage().to_string(), *args.seconds_remaining());
+                }
+            }
+        });
+
+        Ok(())
+    }
 }
@@ -30,6 +30,7 @@ impl ProfileStore {
                     weekend_bonus_minutes: 60,
                     exempt_categories: vec![],
                     windows: TimeWindows { weekday: vec![], weekend: vec![], holiday: vec![] },
+                    enforcement: Default::default(),
                 },
                 applications: ApplicationConfig {
                     mode: ApplicationMode::Blocklist,
@@ -45,6 +46,7 @@ impl ProfileStore {
                     blocked_domains: vec![],
                 },
                 terminal_filtering: TerminalFilteringConfig::default(),
+                network: Default::default(),
             },
             active: true,
         };
@@ -1,11 +1,11 @@
-use dots_family_common::types::Profile;
+use dots_family_common::{types::Profile, AutoApprovalRule, RuleAction, RuleCondition};
+use dots_family_proto::payload::{
+    self, ApprovalRequest, ApprovalRequestList, AutoApprovalRuleList, NewAutoApprovalRule,
+};
 use gtk4::prelude::*;
 use relm4::{factory::FactoryVecDeque, prelude::*};
 
-use crate::{
-    components::approval_request_card::{ApprovalRequest, ApprovalRequestCard},
-    daemon_client::DaemonClient,
-};
+use crate::{components::approval_request_card::ApprovalRequestCard, daemon_client::DaemonClient};
 
 pub struct ApprovalRequests {
     profile: Profile,
@@ -19,6 +19,55 @@ pub struct ApprovalRequests {
     auth_failed: bool,
     error_message: Option<String>,
     show_loading: bool,
+    rules_box: gtk4::Box,
+    rule_draft: RuleDraft,
+}
+
+/// Request kinds offered in the rule editor, in drop-down order
+const RULE_KINDS: [&str; 4] = ["screen_time", "app", "website", "command"];
+
+/// Form state for a new auto-approval rule
+#[derive(Debug, Default)]
+struct RuleDraft {
+    name: String,
+    kind_index: u32,
+    max_minutes: u32,
+    category: String,
+    after: String,
+    weekend_only: bool,
+    once_per_day: bool,
+    deny: bool,
+}
+
+impl RuleDraft {
+    /// Build the rule payload expected by the daemon
+    fn to_new_rule(&self) -> NewAutoApprovalRule {
+        let kind = RULE_KINDS.get(self.kind_index as usize).copied().unwrap_or("screen_time");
+        let mut conditions = vec![RuleCondition::RequestKind { kind: kind.to_string() }];
+
+        if kind == "screen_time" && self.max_minutes > 0 {
+            conditions.push(RuleCondition::MaxMinutes { minutes: self.max_minutes });
+        }
+        if !self.category.trim().is_empty() {
+            conditions.push(RuleCondition::Category { category: self.category.trim().to_string() });
+        }
+        if !self.after.trim().is_empty() {
+            conditions.push(RuleCondition::After { time: self.after.trim().to_string() });
+        }
+        if self.weekend_only {
+            conditions.push(RuleCondition::Weekend);
+        }
+        if self.once_per_day {
+            conditions.push(RuleCondition::MaxPerDay { count: 1 });
+        }
+
+        NewAutoApprovalRule {
+            name: self.name.trim().to_string(),
+            profile_id: None,
+            conditions,
+            action: if self.deny { RuleAction::AutoDeny } else { RuleAction::AutoApprove },
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -39,6 +88,19 @@ pub enum ApprovalRequestsMsg {
     ApprovalRequestSignal(String, String), // request_id, request_type
     ShowError(String),
     DismissError,
+    RefreshRules,
+    UpdateRules(Vec<AutoApprovalRule>),
+    ToggleRule(String, bool),
+    RemoveRule(String),
+    AddRule,
+    RuleNameChanged(String),
+    RuleKindChanged(u32),
+    RuleMaxMinutesChanged(u32),
+    RuleCategoryChanged(String),
+    RuleAfterChanged(String),
+    RuleWeekendOnlyToggled(bool),
+    RuleOncePerDayToggled(bool),
+    RuleDenyToggled(bool),
 }
 
 #[relm4::component(pub)]
@@ -359,6 +421,155 @@ impl SimpleComponent for ApprovalRequests {
                                 connect_clicked => ApprovalRequestsMsg::ApproveSelected,
                             }
                         }
+                    },
+
+                    // Auto-approval rules section
+                    gtk4::Box {
+                        set_orientation: gtk4::Orientation::Vertical,
+                        set_spacing: 12,
+                        set_margin_top: 20,
+
+                        gtk4::Separator {},
+
+                        gtk4::Label {
+                            set_label: "Auto-Approval Rules",
+                            add_css_class: "title-2",
+                            set_halign: gtk4::Align::Start,
+                        },
+
+                        gtk4::Label {
+                            set_label: "Rules resolve matching requests immediately. Deny rules take precedence.",
+                            add_css_class: "dim-label",
+                            set_halign: gtk4::Align::Start,
+                            set_wrap: true,
+                        },
+
+                        #[local_ref]
+                        rules_box -> gtk4::Box {
+                            set_orientation: gtk4::Orientation::Vertical,
+                            set_spacing: 8,
+                        },
+
+                        gtk4::Frame {
+                            add_css_class: "card",
+
+                            gtk4::Box {
+                                set_orientation: gtk4::Orientation::Vertical,
+                                set_spacing: 8,
+                                set_margin_all: 12,
+
+                                gtk4::Label {
+                                    set_label: "New Rule",
+                                    add_css_class: "heading",
+                                    set_halign: gtk4::Align::Start,
+                                },
+
+                                gtk4::Entry {
+                                    set_placeholder_text: Some("Rule name (e.g. Weekend bonus)"),
+                                    #[watch]
+                                    set_text: &model.rule_draft.name,
+                                    connect_changed[sender] => move |entry| {
+                                        sender.input(ApprovalRequestsMsg::RuleNameChanged(
+                                            entry.text().to_string()
+                                        ));
+                                    }
+                                },
+
+                                gtk4::Box {
+                                    set_orientation: gtk4::Orientation::Horizontal,
+                                    set_spacing: 12,
+
+                                    gtk4::Label {
+                                        set_label: "Request type",
+                                    },
+
+                                    gtk4::DropDown::from_strings(&RULE_KINDS) {
+                                        connect_selected_notify[sender] => move |dropdown| {
+                                            sender.input(ApprovalRequestsMsg::RuleKindChanged(
+                                                dropdown.selected()
+                                            ));
+                                        }
+                                    },
+
+                                    gtk4::Label {
+                                        set_label: "Max minutes",
+                                    },
+
+                                    gtk4::SpinButton::with_range(0.0, 240.0, 5.0) {
+                                        connect_value_changed[sender] => move |spin| {
+                                            sender.input(ApprovalRequestsMsg::RuleMaxMinutesChanged(
+                                                spin.value() as u32
+                                            ));
+                                        }
+                                    }
+                                },
+
+                                gtk4::Box {
+                                    set_orientation: gtk4::Orientation::Horizontal,
+                                    set_spacing: 12,
+
+                                    gtk4::Entry {
+                                        set_placeholder_text: Some("App category (optional)"),
+                                        set_hexpand: true,
+                                        connect_changed[sender] => move |entry| {
+                                            sender.input(ApprovalRequestsMsg::RuleCategoryChanged(
+                                                entry.text().to_string()
+                                            ));
+                                        }
+                                    },
+
+                                    gtk4::Entry {
+                                        set_placeholder_text: Some("From HH:MM (optional)"),
+                                        connect_changed[sender] => move |entry| {
+                                            sender.input(ApprovalRequestsMsg::RuleAfterChanged(
+                                                entry.text().to_string()
+                                            ));
+                                        }
+                                    }
+                                },
+
+                                gtk4::Box {
+                                    set_orientation: gtk4::Orientation::Horizontal,
+                                    set_spacing: 12,
+
+                                    gtk4::CheckButton {
+                                        set_label: Some("Weekends only"),
+                                        connect_toggled[sender] => move |check| {
+                                            sender.input(ApprovalRequestsMsg::RuleWeekendOnlyToggled(
+                                                check.is_active()
+                                            ));
+                                        }
+                                    },
+
+                                    gtk4::CheckButton {
+                                        set_label: Some("Once per day"),
+                                        connect_toggled[sender] => move |check| {
+                                            sender.input(ApprovalRequestsMsg::RuleOncePerDayToggled(
+                                                check.is_active()
+                                            ));
+                                        }
+                                    },
+
+                                    gtk4::CheckButton {
+                                        set_label: Some("Deny instead of approve"),
+                                        connect_toggled[sender] => move |check| {
+                                            sender.input(ApprovalRequestsMsg::RuleDenyToggled(
+                                                check.is_active()
+                                            ));
+                                        }
+                                    }
+                                },
+
+                                gtk4::Button {
+                                    set_label: "Add Rule",
+                                    set_halign: gtk4::Align::End,
+                                    add_css_class: "suggested-action",
+                                    #[watch]
+                                    set_sensitive: !model.rule_draft.name.trim().is_empty(),
+                                    connect_clicked => ApprovalRequestsMsg::AddRule,
+                                }
+                            }
+                        }
                     }
                 }
             }
@@ -381,6 +592,10 @@ impl SimpleComponent for ApprovalRequests {
                 ApprovalRequestsMsg::SelectRequest(request_id)
             });
 
+        // Container for auto-approval rule rows, rebuilt on every refresh
+        let rules_box =
+            gtk4::Box::builder().orientation(gtk4::Orientation::Vertical).spacing(8).build();
+
         let model = ApprovalRequests {
             profile,
             daemon_client: None,
@@ -393,6 +608,8 @@ impl SimpleComponent for ApprovalRequests {
             auth_failed: false,
             error_message: None,
             show_loading: false,
+            rules_box: rules_box.clone(),
+            rule_draft: RuleDraft::default(),
         };
 
         let widgets = view_output!();
@@ -428,9 +645,11 @@ impl SimpleComponent for ApprovalRequests {
                     relm4::spawn(async move {
                         match daemon_client.list_pending_requests(&token).await {
                             Ok(response_json) => {
-                                match serde_json::from_str::<Vec<ApprovalRequest>>(&response_json) {
-                                    Ok(requests) => {
-                                        sender.input(ApprovalRequestsMsg::UpdateRequests(requests));
+                                match payload::decode::<ApprovalRequestList>(&response_json) {
+                                    Ok(list) => {
+                                        sender.input(ApprovalRequestsMsg::UpdateRequests(
+                                            list.requests,
+                                        ));
                                     }
                                     Err(e) => {
                                         sender.input(ApprovalRequestsMsg::ShowError(format!(
@@ -580,8 +799,9 @@ impl SimpleComponent for ApprovalRequests {
                     self.show_auth_dialog = false;
                     self.auth_failed = false;
                     self.parent_password.clear();
-                    // Now fetch the requests
+                    // Now fetch the requests and rules
                     sender.input(ApprovalRequestsMsg::RefreshRequests);
+                    sender.input(ApprovalRequestsMsg::RefreshRules);
 
                     // Subscribe to approval request signals for real-time updates
                     if let Some(daemon_client) = &self.daemon_client {
@@ -621,6 +841,195 @@ impl SimpleComponent for ApprovalRequests {
             ApprovalRequestsMsg::DismissError => {
                 self.error_message = None;
             }
+            ApprovalRequestsMsg::RefreshRules => {
+                if let (Some(daemon_client), Some(token)) = (&self.daemon_client, &self.auth_token)
+                {
+                    let daemon_client = daemon_client.clone();
+                    let token = token.clone();
+                    relm4::spawn(async move {
+                        match daemon_client.list_auto_approval_rules(&token).await {
+                            Ok(response_json) => {
+                                match payload::decode::<AutoApprovalRuleList>(&response_json) {
+                                    Ok(list) => {
+                                        sender.input(ApprovalRequestsMsg::UpdateRules(list.rules));
+                                    }
+                                    Err(e) => {
+                                        sender.input(ApprovalRequestsMsg::ShowError(format!(
+                                            "Failed to parse rules: {}",
+                                            e
+                                        )));
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                sender.input(ApprovalRequestsMsg::ShowError(format!(
+                                    "Failed to load auto-approval rules: {}",
+                                    e
+                                )));
+                            }
+                        }
+                    });
+                }
+            }
+            ApprovalRequestsMsg::UpdateRules(rules) => {
+                while let Some(child) = self.rules_box.first_child() {
+                    self.rules_box.remove(&child);
+                }
+
+                if rules.is_empty() {
+                    let label = gtk4::Label::builder()
+                        .label("No auto-approval rules configured")
+                        .halign(gtk4::Align::Start)
+                        .css_classes(["dim-label"])
+                        .build();
+                    self.rules_box.append(&label);
+                }
+
+                for rule in rules {
+                    let row = gtk4::Box::builder()
+                        .orientation(gtk4::Orientation::Horizontal)
+                        .spacing(12)
+                        .build();
+
+                    let label = gtk4::Label::builder()
+                        .label(format!("{}: {}", rule.name, rule.explain()))
+                        .halign(gtk4::Align::Start)
+                        .hexpand(true)
+                        .wrap(true)
+                        .build();
+
+                    let switch = gtk4::Switch::builder()
+                        .active(rule.enabled)
+                        .valign(gtk4::Align::Center)
+                        .tooltip_text("Enable or disable this rule")
+                        .build();
+                    let rule_id = rule.id.to_string();
+                    switch.connect_active_notify({
+                        let sender = sender.clone();
+                        let rule_id = rule_id.clone();
+                        move |switch| {
+                            sender.input(ApprovalRequestsMsg::ToggleRule(
+                                rule_id.clone(),
+                                switch.is_active(),
+                            ));
+                        }
+                    });
+
+                    let remove = gtk4::Button::builder()
+                        .icon_name("user-trash-symbolic")
+                        .tooltip_text("Remove rule")
+                        .valign(gtk4::Align::Center)
+                        .build();
+                    remove.connect_clicked({
+                        let sender = sender.clone();
+                        move |_| {
+                            sender.input(ApprovalRequestsMsg::RemoveRule(rule_id.clone()));
+                        }
+                    });
+
+                    row.append(&label);
+                    row.append(&switch);
+                    row.append(&remove);
+                    self.rules_box.append(&row);
+                }
+            }
+            ApprovalRequestsMsg::ToggleRule(rule_id, enabled) => {
+                if let (Some(daemon_client), Some(token)) = (&self.daemon_client, &self.auth_token)
+                {
+                    let daemon_client = daemon_client.clone();
+                    let token = token.clone();
+                    relm4::spawn(async move {
+                        if let Err(e) = daemon_client
+                            .set_auto_approval_rule_enabled(&rule_id, enabled, &token)
+                            .await
+                        {
+                            sender.input(ApprovalRequestsMsg::ShowMessage(format!(
+                                "Failed to update rule: {}",
+                                e
+                            )));
+                        }
+                        sender.input(ApprovalRequestsMsg::RefreshRules);
+                    });
+                }
+            }
+            ApprovalRequestsMsg::RemoveRule(rule_id) => {
+                if let (Some(daemon_client), Some(token)) = (&self.daemon_client, &self.auth_token)
+                {
+                    let daemon_client = daemon_client.clone();
+                    let token = token.clone();
+                    relm4::spawn(async move {
+                        match daemon_client.remove_auto_approval_rule(&rule_id, &token).await {
+                            Ok(_response) => {
+                                sender.input(ApprovalRequestsMsg::ShowMessage(
+                                    "Rule removed".to_string(),
+                                ));
+                            }
+                            Err(e) => {
+                                sender.input(ApprovalRequestsMsg::ShowMessage(format!(
+                                    "Failed to remove rule: {}",
+                                    e
+                                )));
+                            }
+                        }
+                        sender.input(ApprovalRequestsMsg::RefreshRules);
+                    });
+                }
+            }
+            ApprovalRequestsMsg::AddRule => {
+                if let (Some(daemon_client), Some(token)) = (&self.daemon_client, &self.auth_token)
+                {
+                    let rule_json = match payload::encode(&self.rule_draft.to_new_rule()) {
+                        Ok(rule_json) => rule_json,
+                        Err(e) => {
+                            self.error_message = Some(format!("Failed to build rule: {}", e));
+                            return;
+                        }
+                    };
+                    let daemon_client = daemon_client.clone();
+                    let token = token.clone();
+                    relm4::spawn(async move {
+                        match daemon_client.add_auto_approval_rule(&rule_json, &token).await {
+                            Ok(_) => {
+                                sender.input(ApprovalRequestsMsg::ShowMessage(
+                                    "Rule added".to_string(),
+                                ));
+                            }
+                            Err(e) => {
+                                sender.input(ApprovalRequestsMsg::ShowMessage(format!(
+                                    "Failed to add rule: {}",
+                                    e
+                                )));
+                            }
+                        }
+                        sender.input(ApprovalRequestsMsg::RefreshRules);
+                    });
+                    self.rule_draft.name.clear();
+                }
+            }
+            ApprovalRequestsMsg::RuleNameChanged(name) => {
+                self.rule_draft.name = name;
+            }
+            ApprovalRequestsMsg::RuleKindChanged(index) => {
+                self.rule_draft.kind_index = index;
+            }
+            ApprovalRequestsMsg::RuleMaxMinutesChanged(minutes) => {
+                self.rule_draft.max_minutes = minutes;
+            }
+            ApprovalRequestsMsg::RuleCategoryChanged(category) => {
+                self.rule_draft.category = category;
+            }
+            ApprovalRequestsMsg::RuleAfterChanged(after) => {
+                self.rule_draft.after = after;
+            }
+            ApprovalRequestsMsg::RuleWeekendOnlyToggled(active) => {
+                self.rule_draft.weekend_only = active;
+            }
+            ApprovalRequestsMsg::RuleOncePerDayToggled(active) => {
+                self.rule_draft.once_per_day = active;
+            }
+            ApprovalRequestsMsg::RuleDenyToggled(active) => {
+                self.rule_draft.deny = active;
+            }
         }
     }
 }
@@ -1,4 +1,7 @@
+use std::collections::HashSet;
+
 use dots_family_common::types::Profile;
+use dots_family_proto::payload::{self, ApprovalDetails, ApprovalSubmission, FinishingUpGrant};
 use gtk4::prelude::*;
 use relm4::prelude::*;
 
@@ -10,6 +13,13 @@ pub struct ChildInterface {
     remaining_time: u32,
     current_activity: String,
     time_limit_warning: bool,
+    /// Outcome of the most recently resolved approval request
+    request_outcome: Option<String>,
+    /// Requests this child submitted from here that are still pending
+    own_requests: HashSet<String>,
+    /// Current escalation stage and seconds until the next one
+    enforcement_stage: Option<(String, i64)>,
+    finishing_up_status: Option<String>,
 }
 
 #[derive(Debug)]
@@ -21,6 +31,12 @@ pub enum ChildInterfaceMsg {
     RefreshData,
     RequestExtraTime,
     RequestPermission(String),
+    RequestSubmitted { request_id: String, status: String, message: String },
+    ApprovalResolved { request_id: String, status: String, message: String },
+    EnforcementStageChanged { stage: String, seconds_remaining: i64 },
+    CountdownTick,
+    RequestFinishingUp,
+    FinishingUpResult(String),
 }
 
 #[relm4::component(pub)]
@@ -158,6 +174,69 @@ impl SimpleComponent for ChildInterface {
                 }
             },
 
+            gtk4::Frame {
+                add_css_class: "card",
+                #[watch]
+                set_visible: model.countdown_message().is_some(),
+                #[wrap(Some)]
+                set_child = &gtk4::Box {
+                    set_orientation: gtk4::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_all: 20,
+
+                    gtk4::Label {
+                        #[watch]
+                        set_label: &model.countdown_message().unwrap_or_default(),
+                        add_css_class: "title-2",
+                        set_wrap: true,
+                    },
+
+                    gtk4::Label {
+                        #[watch]
+                        set_visible: model.finishing_up_status.is_some(),
+                        #[watch]
+                        set_label: model.finishing_up_status.as_deref().unwrap_or_default(),
+                        add_css_class: "body",
+                        set_wrap: true,
+                    },
+
+                    gtk4::Button {
+                        set_label: "I'm Finishing Up",
+                        set_halign: gtk4::Align::Center,
+                        add_css_class: "suggested-action",
+                        #[watch]
+                        set_visible: model.finishing_up_status.is_none(),
+                        connect_clicked => ChildInterfaceMsg::RequestFinishingUp,
+                    }
+                }
+            },
+
+            gtk4::Frame {
+                add_css_class: "card",
+                #[watch]
+                set_visible: model.request_outcome.is_some(),
+                #[wrap(Some)]
+                set_child = &gtk4::Box {
+                    set_orientation: gtk4::Orientation::Horizontal,
+                    set_spacing: 12,
+                    set_margin_all: 20,
+
+                    gtk4::Image {
+                        set_icon_name: Some("mail-unread-symbolic"),
+                        set_pixel_size: 24,
+                    },
+
+                    gtk4::Label {
+                        #[watch]
+                        set_label: model.request_outcome.as_deref().unwrap_or_default(),
+                        add_css_class: "body",
+                        set_wrap: true,
+                        set_hexpand: true,
+                        set_halign: gtk4::Align::Start,
+                    }
+                }
+            },
+
             gtk4::Box {
                 set_orientation: gtk4::Orientation::Horizontal,
                 set_spacing: 12,
@@ -231,8 +310,21 @@ impl SimpleComponent for ChildInterface {
             remaining_time: 45,
             current_activity: "Reading app".to_string(),
             time_limit_warning: false,
+            request_outcome: None,
+            own_requests: HashSet::new(),
+            enforcement_stage: None,
+            finishing_up_status: None,
         };
 
+        let sender_clone = sender.clone();
+        relm4::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                sender_clone.input(ChildInterfaceMsg::CountdownTick);
+            }
+        });
+
         let sender_clone = sender.clone();
         relm4::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
@@ -242,6 +334,47 @@ impl SimpleComponent for ChildInterface {
             }
         });
 
+        // Listen for parent responses to this child's approval requests and
+        // for the enforcement countdown
+        let daemon_client = model.daemon_client.clone();
+        let sender_clone = sender.clone();
+        let stage_sender = sender.clone();
+        relm4::spawn(async move {
+            if !daemon_client.is_connected().await {
+                if let Err(e) = daemon_client.connect().await {
+                    eprintln!("Failed to connect to daemon: {}", e);
+                    return;
+                }
+            }
+
+            let result = daemon_client
+                .subscribe_approval_resolutions(move |request_id, status, message| {
+                    sender_clone.input(ChildInterfaceMsg::ApprovalResolved {
+                        request_id,
+                        status,
+                        message,
+                    });
+                })
+                .await;
+
+            if let Err(e) = result {
+                eprintln!("Failed to subscribe to approval resolutions: {}", e);
+            }
+
+            let result = daemon_client
+                .subscribe_enforcement_stages(move |stage, seconds_remaining| {
+                    stage_sender.input(ChildInterfaceMsg::EnforcementStageChanged {
+                        stage,
+                        seconds_remaining,
+                    });
+                })
+                .await;
+
+            if let Err(e) = result {
+                eprintln!("Failed to subscribe to enforcement stages: {}", e);
+            }
+        });
+
         let _ = sender.input(ChildInterfaceMsg::RefreshData);
 
         let widgets = view_output!();
@@ -279,8 +412,128 @@ impl SimpleComponent for ChildInterface {
                     }
                 });
             }
-            ChildInterfaceMsg::RequestExtraTime => {}
+            ChildInterfaceMsg::RequestExtraTime => {
+                let daemon_client = self.daemon_client.clone();
+                relm4::spawn(async move {
+                    let details = match payload::encode(&ApprovalDetails {
+                        requested_minutes: Some(15),
+                        ..Default::default()
+                    }) {
+                        Ok(details) => details,
+                        Err(e) => {
+                            eprintln!("Failed to request extra time: {}", e);
+                            return;
+                        }
+                    };
+                    let response = daemon_client
+                        .submit_approval_request("screen_time", "Can I have more time?", &details)
+                        .await;
+                    let submission = match response
+                        .and_then(|response| Ok(payload::decode::<ApprovalSubmission>(&response)?))
+                    {
+                        Ok(submission) => submission,
+                        Err(e) => {
+                            eprintln!("Failed to request extra time: {}", e);
+                            return;
+                        }
+                    };
+
+                    sender.input(ChildInterfaceMsg::RequestSubmitted {
+                        request_id: submission.request_id,
+                        status: submission.request_status,
+                        message: submission.explanation.unwrap_or_default(),
+                    });
+                });
+            }
             ChildInterfaceMsg::RequestPermission(_request) => {}
+            ChildInterfaceMsg::RequestSubmitted { request_id, status, message } => {
+                if status == "pending" {
+                    self.own_requests.insert(request_id);
+                    self.request_outcome =
+                        Some("⏳ Your request was sent to a parent.".to_string());
+                } else {
+                    // Rules resolved it on the spot
+                    self.show_outcome(&status, &message, &sender);
+                }
+            }
+            ChildInterfaceMsg::ApprovalResolved { request_id, status, message } => {
+                // Resolutions of every child's requests are broadcast
+                if self.own_requests.remove(&request_id) {
+                    self.show_outcome(&status, &message, &sender);
+                }
+            }
+            ChildInterfaceMsg::EnforcementStageChanged { stage, seconds_remaining } => {
+                if stage == "normal" {
+                    self.enforcement_stage = None;
+                    self.finishing_up_status = None;
+                } else {
+                    self.enforcement_stage = Some((stage, seconds_remaining));
+                }
+            }
+            ChildInterfaceMsg::CountdownTick => {
+                if let Some((_, seconds_remaining)) = &mut self.enforcement_stage {
+                    *seconds_remaining = (*seconds_remaining - 1).max(0);
+                }
+            }
+            ChildInterfaceMsg::RequestFinishingUp => {
+                let daemon_client = self.daemon_client.clone();
+                relm4::spawn(async move {
+                    let grant = daemon_client
+                        .request_finishing_up()
+                        .await
+                        .and_then(|response| Ok(payload::decode::<FinishingUpGrant>(&response)?));
+                    let status = match grant {
+                        Ok(grant) => format!(
+                            "👍 You have {} more minutes to finish up.",
+                            grant.extra_minutes
+                        ),
+                        Err(e) => format!("❌ {}", e),
+                    };
+                    sender.input(ChildInterfaceMsg::FinishingUpResult(status));
+                });
+            }
+            ChildInterfaceMsg::FinishingUpResult(status) => {
+                self.finishing_up_status = Some(status);
+            }
+        }
+    }
+}
+
+impl ChildInterface {
+    fn show_outcome(&mut self, status: &str, message: &str, sender: &ComponentSender<Self>) {
+        let outcome = match status {
+            "approved" | "auto_approved" => "✅ Your request was approved!",
+            "denied" => "❌ Your request was denied.",
+            "expired" => "⌛ Your request expired before a parent responded.",
+            other => {
+                eprintln!("Ignoring approval resolution with unknown status '{}'", other);
+                return;
+            }
+        };
+
+        self.request_outcome = Some(if message.is_empty() {
+            outcome.to_string()
+        } else {
+            format!("{}\n\"{}\"", outcome, message)
+        });
+
+        // An approval may have granted extra screen time
+        let _ = sender.input(ChildInterfaceMsg::RefreshData);
+    }
+
+    /// Countdown shown while time is running out, if any
+    fn countdown_message(&self) -> Option<String> {
+        let (stage, seconds_remaining) = self.enforcement_stage.as_ref()?;
+        let countdown = format!("{}:{:02}", seconds_remaining / 60, seconds_remaining % 60);
+
+        match stage.as_str() {
+            "warning" | "countdown" => Some(format!("⏳ Time's up in {}", countdown)),
+            "grace_period" => {
+                Some(format!("💾 Time's up! Save your work. Apps close in {}", countdown))
+            }
+            "close_apps" => Some(format!("🔒 Closing apps. Locking in {}", countdown)),
+            "lock" => Some("🔒 Screen time is over for now".to_string()),
+            _ => None,
         }
     }
 }
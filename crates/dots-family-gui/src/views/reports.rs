@@ -205,6 +205,50 @@ impl SimpleComponent for Reports {
                                 }
                             },
 
+                            gtk4::ListBoxRow {
+                                #[wrap(Some)]
+                                set_child = &gtk4::Box {
+                                    set_orientation: gtk4::Orientation::Horizontal,
+                                    set_spacing: 12,
+                                    set_margin_all: 12,
+
+                                    gtk4::Box {
+                                        set_orientation: gtk4::Orientation::Vertical,
+                                        set_hexpand: true,
+
+                                        gtk4::Label {
+                                            set_label: "Time Bank",
+                                            add_css_class: "heading",
+                                            set_halign: gtk4::Align::Start,
+                                        },
+
+                                        gtk4::Label {
+                                            #[watch]
+                                            set_label: &if let Some(ref weekly) = model.current_weekly {
+                                                format!("{} min earned, {} rolled over, {} spent this week",
+                                                    weekly.time_bank.earned_minutes,
+                                                    weekly.time_bank.rolled_over_minutes,
+                                                    weekly.time_bank.spent_minutes)
+                                            } else {
+                                                "Loading...".to_string()
+                                            },
+                                            add_css_class: "body",
+                                            set_halign: gtk4::Align::Start,
+                                        }
+                                    },
+
+                                    gtk4::Label {
+                                        #[watch]
+                                        set_label: &if let Some(ref weekly) = model.current_weekly {
+                                            format!("{} min", weekly.time_bank.balance_minutes)
+                                        } else {
+                                            "...".to_string()
+                                        },
+                                        add_css_class: "title-3",
+                                    }
+                                }
+                            },
+
                             gtk4::ListBoxRow {
                                 #[wrap(Some)]
                                 set_child = &gtk4::Box {
@@ -8,6 +8,10 @@ pub struct MonitorConfig {
 
     #[serde(default = "default_idle_threshold")]
     pub report_idle_threshold_seconds: u64,
+
+    /// Activities kept while the daemon is unreachable
+    #[serde(default = "default_spool_max_entries")]
+    pub spool_max_entries: usize,
 }
 
 fn default_polling_interval() -> u64 {
@@ -18,9 +22,17 @@ fn default_idle_threshold() -> u64 {
     60
 }
 
+fn default_spool_max_entries() -> usize {
+    10_000
+}
+
 impl Default for MonitorConfig {
     fn default() -> Self {
-        Self { polling_interval_ms: 1000, report_idle_threshold_seconds: 60 }
+        Self {
+            polling_interval_ms: 1000,
+            report_idle_threshold_seconds: 60,
+            spool_max_entries: default_spool_max_entries(),
+        }
     }
 }
 
@@ -1,67 +1,71 @@
 use anyhow::Result;
 use dots_family_common::types::Activity;
-use dots_family_proto::daemon::FamilyDaemonProxy;
-use serde_json;
-use tracing::{debug, warn};
+use dots_family_proto::{
+    daemon::FamilyDaemonProxy,
+    payload::{self, ActivityReceipt},
+};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 use zbus::Connection;
 
 pub struct DaemonClient {
     proxy: Option<FamilyDaemonProxy<'static>>,
+    /// Profile the daemon records this session's activities for
+    profile_id: Option<Uuid>,
 }
 
 impl DaemonClient {
     pub async fn new() -> Self {
         match Self::connect().await {
-            Ok(proxy) => {
-                debug!("Successfully connected to daemon via DBus");
-                Self { proxy: Some(proxy) }
+            Ok((proxy, profile_id)) => {
+                debug!("Successfully connected to daemon via DBus, profile {}", profile_id);
+                Self { proxy: Some(proxy), profile_id: Some(profile_id) }
             }
             Err(e) => {
                 warn!("Failed to connect to daemon via DBus: {}. Activity will be logged only.", e);
-                Self { proxy: None }
+                Self { proxy: None, profile_id: None }
             }
         }
     }
 
-    async fn connect() -> Result<FamilyDaemonProxy<'static>> {
+    /// Connect and register this session's monitor; the daemon resolves the
+    /// profile from our uid and stamps it on every activity we report
+    async fn connect() -> Result<(FamilyDaemonProxy<'static>, Uuid)> {
         let conn = Connection::system().await?;
         let proxy = FamilyDaemonProxy::new(&conn).await?;
 
         proxy.send_heartbeat("monitor").await?;
-        Ok(proxy)
+        let profile_id = Uuid::parse_str(&proxy.register_component("monitor").await?)?;
+        Ok((proxy, profile_id))
     }
 
-    pub async fn get_active_profile_id(&self) -> Result<Uuid> {
-        if let Some(proxy) = &self.proxy {
-            let profile_json = proxy.get_active_profile().await?;
-
-            let profile: serde_json::Value = serde_json::from_str(&profile_json)?;
-
-            if let Some(error) = profile.get("error") {
-                return Err(anyhow::anyhow!("Daemon returned error: {}", error));
-            }
+    pub fn is_connected(&self) -> bool {
+        self.proxy.is_some()
+    }
 
-            if let Some(id_str) = profile.get("id").and_then(|v| v.as_str()) {
-                Ok(Uuid::parse_str(id_str)?)
-            } else {
-                Err(anyhow::anyhow!("No profile ID found in daemon response"))
-            }
-        } else {
-            Err(anyhow::anyhow!("No daemon connection available"))
-        }
+    /// Profile this session is registered for, while connected
+    pub fn profile_id(&self) -> Option<Uuid> {
+        self.profile_id
     }
 
     pub async fn report_activity(&self, activity: &Activity) -> Result<()> {
         if let Some(proxy) = &self.proxy {
-            let activity_json = serde_json::to_string(activity)?;
+            let activity_json = payload::encode(activity)?;
 
             match proxy.report_activity(&activity_json).await {
-                Ok(_) => {
+                Ok(reply) => {
+                    let receipt: ActivityReceipt = payload::decode(&reply)?;
                     debug!(
-                        "Successfully reported activity: app={:?}, duration={}s",
-                        activity.application, activity.duration_seconds
+                        "Successfully reported activity: app={:?}, duration={}s, status={}",
+                        activity.application, activity.duration_seconds, receipt.status
                     );
+                    if receipt.blocked {
+                        info!(
+                            "Daemon blocked {:?}: {}",
+                            activity.application,
+                            receipt.reason.as_deref().unwrap_or("policy")
+                        );
+                    }
                     Ok(())
                 }
                 Err(e) => {
@@ -86,14 +90,16 @@ impl DaemonClient {
 
     pub async fn reconnect(&mut self) -> Result<()> {
         match Self::connect().await {
-            Ok(proxy) => {
+            Ok((proxy, profile_id)) => {
                 debug!("Reconnected to daemon via DBus");
                 self.proxy = Some(proxy);
+                self.profile_id = Some(profile_id);
                 Ok(())
             }
             Err(e) => {
                 warn!("Failed to reconnect to daemon: {}", e);
                 self.proxy = None;
+                self.profile_id = None;
                 Err(e)
             }
         }
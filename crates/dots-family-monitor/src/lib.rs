@@ -1,4 +1,5 @@
 pub mod config;
 pub mod daemon_client;
 pub mod monitor;
+pub mod spool;
 pub mod wayland;
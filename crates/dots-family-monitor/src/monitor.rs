@@ -1,14 +1,21 @@
 use anyhow::Result;
 use chrono::Utc;
 use dots_family_common::types::{Activity, ActivityType};
-use dots_wm_bridge::{WindowInfo, WindowManagerBridge};
+use dots_family_proto::error::Error as DaemonError;
+use dots_wm_bridge::{WMEvent, WMEventStream, WindowInfo, WindowManagerBridge};
+use futures::StreamExt;
 use std::time::{Duration, Instant};
-use tokio::time::sleep;
-use tracing::{debug, info, warn};
+use tokio::time::interval;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::config::MonitorConfig;
 use crate::daemon_client::DaemonClient;
+use crate::spool::ActivitySpool;
+
+/// How often the daemon connection, the event subscription and the spool are
+/// looked after; well inside the daemon's heartbeat timeout
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Debug)]
 struct FocusedWindow {
@@ -19,6 +26,8 @@ struct FocusedWindow {
 #[derive(Debug, Default)]
 pub struct ActivityTracker {
     current_focus: Option<FocusedWindow>,
+    /// Since when nothing has had focus, e.g. behind the lock screen
+    unfocused_since: Option<Instant>,
 }
 
 impl ActivityTracker {
@@ -65,13 +74,28 @@ impl ActivityTracker {
                 };
 
                 self.current_focus = None;
+                self.unfocused_since = Some(Instant::now());
                 Some(report)
             }
             (None, Some(new)) => {
                 self.current_focus = Some(FocusedWindow { info: new, start_time: Instant::now() });
+
+                // Account for the time without focus so it doesn't look like
+                // the monitor stopped reporting
+                self.unfocused_since.take().map(|since| Activity {
+                    id: Uuid::new_v4(),
+                    profile_id: Uuid::nil(),
+                    timestamp: Utc::now(),
+                    activity_type: ActivityType::Unfocused,
+                    application: None,
+                    window_title: None,
+                    duration_seconds: since.elapsed().as_secs() as u32,
+                })
+            }
+            (None, None) => {
+                self.unfocused_since.get_or_insert_with(Instant::now);
                 None
             }
-            (None, None) => None,
         }
     }
 }
@@ -87,50 +111,133 @@ pub async fn run() -> Result<()> {
     info!("Monitor capabilities: {:?}", wm_bridge.get_capabilities());
 
     let mut daemon_client = DaemonClient::new().await;
-    let mut heartbeat_counter = 0;
-    let heartbeat_interval = 100;
+    let mut spool = ActivitySpool::open(ActivitySpool::default_path()?, config.spool_max_entries)?;
+
+    let mut events = subscribe(&wm_bridge).await;
+    match events {
+        Some(_) => info!("Monitor running on compositor events"),
+        None => info!("Monitor running, polling every {}ms", config.polling_interval_ms),
+    }
+
+    // Streams only report changes, so start from whatever is focused now
+    tracker.update_focus(wm_bridge.get_focused_window().await?);
 
-    info!("Monitor running, polling every {}ms", config.polling_interval_ms);
+    let mut poll = interval(Duration::from_millis(config.polling_interval_ms));
+    let mut maintenance = interval(MAINTENANCE_INTERVAL);
 
     loop {
-        let window = wm_bridge.get_focused_window().await?;
+        let focus = tokio::select! {
+            event = next_event(&mut events) => match event {
+                Some(WMEvent::WindowFocused(window)) => Some(window),
+                Some(_) => None,
+                None => {
+                    warn!("Compositor event stream ended, polling until it is back");
+                    events = None;
+                    None
+                }
+            },
+            _ = poll.tick(), if events.is_none() => Some(wm_bridge.get_focused_window().await?),
+            _ = maintenance.tick() => {
+                if !daemon_client.is_connected() || daemon_client.send_heartbeat().await.is_err() {
+                    warn!("Daemon unreachable, attempting to reconnect");
+                    let _ = daemon_client.reconnect().await;
+                }
 
-        if let Some(mut activity) = tracker.update_focus(window) {
-            match daemon_client.get_active_profile_id().await {
-                Ok(profile_id) => {
-                    activity.profile_id = profile_id;
-                    debug!("Updated activity with real profile ID: {}", profile_id);
+                if daemon_client.is_connected() && !spool.is_empty() {
+                    replay_spool(&mut daemon_client, &mut spool).await;
                 }
-                Err(e) => {
-                    warn!("Failed to get active profile ID: {}. Using nil UUID.", e);
-                    activity.profile_id = Uuid::nil();
+
+                if events.is_none() {
+                    events = subscribe(&wm_bridge).await;
+                    // Focus may have moved while nothing was listening
+                    if events.is_some() {
+                        Some(wm_bridge.get_focused_window().await?)
+                    } else {
+                        None
+                    }
+                } else {
+                    None
                 }
             }
+        };
 
+        if let Some(activity) = focus.and_then(|window| tracker.update_focus(window)) {
+            // The daemon stamps the profile this session registered for
             info!(
-                "Activity completed: app={:?}, duration={}s, profile_id={}",
-                activity.application, activity.duration_seconds, activity.profile_id
+                "Activity completed: app={:?}, duration={}s, profile_id={:?}",
+                activity.application,
+                activity.duration_seconds,
+                daemon_client.profile_id()
             );
 
-            if let Err(e) = daemon_client.report_activity(&activity).await {
-                warn!("Failed to report activity to daemon: {}", e);
+            deliver(&mut daemon_client, &mut spool, activity).await;
+        }
+    }
+}
 
-                if daemon_client.reconnect().await.is_err() {
-                    warn!("Failed to reconnect to daemon. Will continue monitoring locally.");
-                }
-            }
+/// The compositor's event stream, if it has one we can reach
+async fn subscribe(wm_bridge: &WindowManagerBridge) -> Option<WMEventStream> {
+    if !wm_bridge.get_capabilities().can_subscribe_to_events {
+        return None;
+    }
+
+    match wm_bridge.subscribe_to_events().await {
+        Ok(events) => Some(events),
+        Err(e) => {
+            warn!("Failed to subscribe to compositor events, polling instead: {}", e);
+            None
         }
+    }
+}
 
-        heartbeat_counter += 1;
-        if heartbeat_counter >= heartbeat_interval {
-            if daemon_client.send_heartbeat().await.is_err() {
-                warn!("Heartbeat failed, attempting to reconnect to daemon");
-                let _ = daemon_client.reconnect().await;
-            }
-            heartbeat_counter = 0;
+/// Next compositor event; never resolves while there is no stream
+async fn next_event(events: &mut Option<WMEventStream>) -> Option<WMEvent> {
+    match events {
+        Some(events) => events.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Send an activity, or spool it until the daemon is back. Nothing overtakes
+/// activity that is already waiting in the spool.
+async fn deliver(daemon_client: &mut DaemonClient, spool: &mut ActivitySpool, activity: Activity) {
+    if daemon_client.is_connected() && spool.is_empty() {
+        match daemon_client.report_activity(&activity).await {
+            Ok(()) => return,
+            Err(e) => warn!("Failed to report activity to daemon: {}", e),
         }
+        if daemon_client.reconnect().await.is_err() {
+            warn!("Failed to reconnect to daemon. Spooling activity until it is back.");
+        }
+    }
+
+    if let Err(e) = spool.append(&activity) {
+        error!("Failed to spool activity {}: {}", activity.id, e);
+    }
+}
 
-        sleep(Duration::from_millis(config.polling_interval_ms)).await;
+async fn replay_spool(daemon_client: &mut DaemonClient, spool: &mut ActivitySpool) {
+    let client = &*daemon_client;
+    let replayed = spool
+        .replay(|activity| async move {
+            match client.report_activity(&activity).await {
+                Err(e) if matches!(e.downcast_ref(), Some(DaemonError::InvalidArgument(_))) => {
+                    // Retrying cannot help; drop it rather than block the spool
+                    warn!("Daemon rejected spooled activity {}: {}", activity.id, e);
+                    Ok(())
+                }
+                result => result,
+            }
+        })
+        .await;
+
+    match replayed {
+        Ok(count) if spool.is_empty() => info!("Replayed {} spooled activities", count),
+        Ok(count) => {
+            warn!("Replayed {} spooled activities, {} still waiting", count, spool.len());
+            let _ = daemon_client.reconnect().await;
+        }
+        Err(e) => error!("Failed to replay activity spool: {}", e),
     }
 }
 
@@ -241,4 +348,27 @@ mod tests {
         assert_eq!(report.application, Some("firefox".to_string()));
         assert!(report.duration_seconds > 0);
     }
+
+    #[test]
+    fn test_time_without_focus_is_reported_as_unfocused() {
+        let mut tracker = ActivityTracker::new();
+
+        let window = WindowInfo {
+            app_id: Some("firefox".to_string()),
+            title: Some("GitHub".to_string()),
+            pid: None,
+            workspace: None,
+            geometry: None,
+            state: Default::default(),
+        };
+        tracker.update_focus(Some(window.clone()));
+        tracker.update_focus(None);
+
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        let report = tracker.update_focus(Some(window)).unwrap();
+        assert!(matches!(report.activity_type, ActivityType::Unfocused));
+        assert_eq!(report.application, None);
+        assert!(report.duration_seconds > 0);
+    }
 }
@@ -0,0 +1,291 @@
+//! Offline activity spool. Activities the daemon could not take are appended
+//! to a file in the user's state directory, one checksummed JSON record per
+//! line, and replayed in order once the daemon is reachable again. The daemon
+//! stores each activity id once, so a record replayed twice is harmless.
+
+use std::{
+    collections::HashSet,
+    fs::{self, DirBuilder, File, OpenOptions},
+    future::Future,
+    io::{BufRead, BufReader, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use dots_family_common::types::Activity;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+const SPOOL_FILE: &str = "monitor-spool.jsonl";
+
+pub struct ActivitySpool {
+    path: PathBuf,
+    max_entries: usize,
+    entries: usize,
+}
+
+impl ActivitySpool {
+    /// Open the spool at `path`, keeping at most `max_entries` activities
+    pub fn open(path: impl Into<PathBuf>, max_entries: usize) -> Result<Self> {
+        let mut spool = Self { path: path.into(), max_entries: max_entries.max(1), entries: 0 };
+        spool.entries = spool.load()?.len();
+        if spool.entries > 0 {
+            info!("{} spooled activities waiting for the daemon", spool.entries);
+        }
+        Ok(spool)
+    }
+
+    /// This user's spool file, in the per-user state directory or failing
+    /// that `$XDG_RUNTIME_DIR`; never a directory shared with other users
+    pub fn default_path() -> Result<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .or_else(dirs::runtime_dir)
+            .map(|dir| dir.join("dots-family").join(SPOOL_FILE))
+            .context("No per-user state or runtime directory for the activity spool")
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Append an activity; the oldest ones are dropped beyond the bound
+    pub fn append(&mut self, activity: &Activity) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .with_context(|| format!("Failed to create spool directory {}", dir.display()))?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open spool {}", self.path.display()))?;
+        file.write_all(encode_record(activity)?.as_bytes())?;
+        file.sync_data()?;
+        self.entries += 1;
+
+        if self.entries > self.max_entries {
+            let activities = self.load()?;
+            let dropped = activities.len().saturating_sub(self.max_entries);
+            warn!("Activity spool is full, dropping {} oldest activities", dropped);
+            self.rewrite(&activities[dropped..])?;
+        }
+        Ok(())
+    }
+
+    /// Spooled activities in order, without corrupt records or repeated ids
+    pub fn load(&self) -> Result<Vec<Activity>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).context(format!("Failed to open spool {}", self.path.display()))
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut activities = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            match decode_record(&line) {
+                Some(activity) => {
+                    if seen.insert(activity.id) {
+                        activities.push(activity);
+                    }
+                }
+                None => warn!("Skipping corrupt spool record on line {}", number + 1),
+            }
+        }
+        Ok(activities)
+    }
+
+    /// Hand spooled activities to `send` in order, stopping at the first
+    /// failure. Delivered activities leave the spool; returns how many.
+    pub async fn replay<F, Fut>(&mut self, mut send: F) -> Result<usize>
+    where
+        F: FnMut(Activity) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let activities = self.load()?;
+        let mut delivered = 0;
+        for activity in &activities {
+            if let Err(e) = send(activity.clone()).await {
+                debug!("Replay stopped at activity {}: {}", activity.id, e);
+                break;
+            }
+            delivered += 1;
+        }
+
+        if delivered > 0 || activities.len() != self.entries {
+            self.rewrite(&activities[delivered..])?;
+        }
+        Ok(delivered)
+    }
+
+    /// Replace the spool with `activities` in one step
+    fn rewrite(&mut self, activities: &[Activity]) -> Result<()> {
+        if activities.is_empty() {
+            match fs::remove_file(&self.path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            self.entries = 0;
+            return Ok(());
+        }
+
+        let staged = staging_path(&self.path);
+        {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .mode(0o600)
+                .open(&staged)?;
+            for activity in activities {
+                file.write_all(encode_record(activity)?.as_bytes())?;
+            }
+            file.sync_all()?;
+        }
+        fs::rename(&staged, &self.path)?;
+        self.entries = activities.len();
+        Ok(())
+    }
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".tmp");
+    PathBuf::from(staged)
+}
+
+fn checksum(json: &str) -> String {
+    format!("{:x}", Sha256::digest(json.as_bytes()))
+}
+
+/// `<sha256 of the JSON>\t<JSON>\n`
+fn encode_record(activity: &Activity) -> Result<String> {
+    let json = serde_json::to_string(activity)?;
+    Ok(format!("{}\t{}\n", checksum(&json), json))
+}
+
+fn decode_record(line: &str) -> Option<Activity> {
+    let (sum, json) = line.split_once('\t')?;
+    if checksum(json) != sum {
+        return None;
+    }
+    serde_json::from_str(json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use dots_family_common::types::ActivityType;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn activity(app: &str) -> Activity {
+        Activity {
+            id: Uuid::new_v4(),
+            profile_id: Uuid::nil(),
+            timestamp: Utc::now(),
+            activity_type: ActivityType::ApplicationUsage,
+            application: Some(app.to_string()),
+            window_title: None,
+            duration_seconds: 30,
+        }
+    }
+
+    fn apps(activities: &[Activity]) -> Vec<String> {
+        activities.iter().filter_map(|a| a.application.clone()).collect()
+    }
+
+    #[test]
+    fn test_spool_survives_reopen_and_is_bounded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state").join(SPOOL_FILE);
+
+        let mut spool = ActivitySpool::open(&path, 3).unwrap();
+        for app in ["a", "b", "c", "d"] {
+            spool.append(&activity(app)).unwrap();
+        }
+        assert_eq!(spool.len(), 3);
+
+        let reopened = ActivitySpool::open(&path, 3).unwrap();
+        assert_eq!(reopened.len(), 3);
+        assert_eq!(apps(&reopened.load().unwrap()), ["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_spool_is_private_to_its_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state").join(SPOOL_FILE);
+        let mut spool = ActivitySpool::open(&path, 3).unwrap();
+        spool.append(&activity("a")).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().unwrap()), 0o700);
+        assert!(!ActivitySpool::default_path().unwrap().starts_with("/tmp"));
+    }
+
+    #[test]
+    fn test_corrupt_and_repeated_records_are_skipped() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SPOOL_FILE);
+
+        let mut spool = ActivitySpool::open(&path, 10).unwrap();
+        let first = activity("a");
+        spool.append(&first).unwrap();
+        spool.append(&first).unwrap();
+        spool.append(&activity("b")).unwrap();
+
+        let tampered = fs::read_to_string(&path).unwrap().replacen("\"b\"", "\"x\"", 1);
+        fs::write(&path, tampered).unwrap();
+
+        assert_eq!(apps(&spool.load().unwrap()), ["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_delivers_in_order_until_failure() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(SPOOL_FILE);
+
+        let mut spool = ActivitySpool::open(&path, 10).unwrap();
+        for app in ["a", "b", "c"] {
+            spool.append(&activity(app)).unwrap();
+        }
+
+        let mut sent = Vec::new();
+        let delivered = spool
+            .replay(|activity| {
+                let app = activity.application.unwrap();
+                let result = if app == "c" { Err(anyhow::anyhow!("daemon gone")) } else { Ok(()) };
+                sent.push(app);
+                async move { result }
+            })
+            .await
+            .unwrap();
+        assert_eq!(delivered, 2);
+        assert_eq!(sent, ["a", "b", "c"]);
+        assert_eq!(apps(&spool.load().unwrap()), ["c"]);
+
+        assert_eq!(spool.replay(|_| async { Ok(()) }).await.unwrap(), 1);
+        assert!(spool.is_empty());
+        assert!(!path.exists());
+    }
+}
@@ -1,6 +1,12 @@
 use chrono::Utc;
 use dots_family_common::types::{Activity, ActivityType};
+use dots_family_daemon::{
+    config::{DaemonConfig, DatabaseConfig},
+    profile_manager::ProfileManager,
+};
+use dots_family_db::{queries::activities::ActivityQueries, Database};
 use dots_family_monitor::daemon_client::DaemonClient;
+use dots_family_proto::{error::DaemonError, payload};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -74,31 +80,44 @@ async fn test_daemon_client_graceful_degradation() {
 }
 
 #[tokio::test]
-async fn test_profile_id_retrieval() {
-    let client = DaemonClient::new().await;
-
-    // Test profile ID retrieval (will fail if daemon not running, which is expected)
-    let profile_result = client.get_active_profile_id().await;
-
-    // This test verifies the method exists and returns appropriate error when daemon unavailable
-    match profile_result {
-        Ok(profile_id) => {
-            // If daemon is running, we should get a valid UUID
-            assert!(profile_id != Uuid::nil(), "Profile ID should not be nil if daemon is running");
-        }
-        Err(e) => {
-            // If daemon is not running, we should get an appropriate error
-            let error_msg = e.to_string();
-            assert!(
-                error_msg.contains("No daemon connection")
-                    || error_msg.contains("Failed to connect")
-                    || error_msg.contains("DBus")
-                    || error_msg.contains("daemon"),
-                "Error should indicate daemon connection issue: {}",
-                error_msg
-            );
-        }
-    }
+async fn test_unregistered_activity_report_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("test.db").to_str().unwrap().to_string();
+    let config = DaemonConfig {
+        database: DatabaseConfig { path: db_path.clone(), encrypt: false, ..Default::default() },
+        dry_run: Some(true),
+        ..Default::default()
+    };
+    let db = Database::new(dots_family_db::DatabaseConfig { path: db_path, encryption_key: None })
+        .await
+        .unwrap();
+    db.run_migrations().await.unwrap();
+
+    let manager = ProfileManager::new(&config, db.clone()).await.unwrap();
+    let profile_id = manager
+        .create_profile_with_username("Alice", "8-12", Some("alice".to_string()))
+        .await
+        .unwrap();
+
+    // A monitor that never registered cannot file activity under a profile it names
+    let activity = Activity {
+        id: Uuid::new_v4(),
+        profile_id: Uuid::parse_str(&profile_id).unwrap(),
+        timestamp: Utc::now(),
+        activity_type: ActivityType::ApplicationUsage,
+        application: Some("firefox".to_string()),
+        window_title: None,
+        duration_seconds: 60,
+    };
+    let report = payload::encode(&activity).unwrap();
+    let rejected = manager.report_activity(&report, Some(":1.99"), false).await;
+    assert!(matches!(
+        rejected.unwrap_err().downcast::<DaemonError>(),
+        Ok(DaemonError::NotAuthorized(_))
+    ));
+
+    let stored = ActivityQueries::list_for_profile(&db, &profile_id, 10).await.unwrap();
+    assert!(stored.is_empty());
 }
 
 #[tokio::test]
@@ -109,6 +128,7 @@ async fn test_activity_types_serialization() {
         ActivityType::WebBrowsing { url: "https://example.com".to_string() },
         ActivityType::TerminalCommand { command: "ls -la".to_string() },
         ActivityType::PolicyViolation { reason: "Screen time exceeded".to_string() },
+        ActivityType::Unfocused,
     ];
 
     for activity_type in activity_types {
@@ -140,6 +160,7 @@ async fn test_activity_types_serialization() {
         let roundtrip_activity = deserialized.unwrap();
         match (&activity_type, &roundtrip_activity.activity_type) {
             (ActivityType::ApplicationUsage, ActivityType::ApplicationUsage) => (),
+            (ActivityType::Unfocused, ActivityType::Unfocused) => (),
             (ActivityType::WebBrowsing { url: url1 }, ActivityType::WebBrowsing { url: url2 }) => {
                 assert_eq!(url1, url2, "URL should survive round-trip serialization");
             },
@@ -1,131 +1,448 @@
-use zbus::proxy;
+//! The `org.dots.FamilyDaemon` interface, declared once and expanded into the
+//! client proxy, the server trait the daemon implements and the adapter that
+//! serves it, so the two sides cannot drift apart.
 
-#[proxy(
-    interface = "org.dots.FamilyDaemon",
-    default_service = "org.dots.FamilyDaemon",
-    default_path = "/org/dots/FamilyDaemon"
-)]
-pub trait FamilyDaemon {
-    async fn get_active_profile(&self) -> zbus::Result<String>;
+use std::future::Future;
 
-    async fn check_application_allowed(&self, app_id: &str) -> zbus::Result<bool>;
+use futures::{future, Stream, StreamExt};
+use zbus::{
+    interface, message::Header, object_server::Interface, proxy, Connection, SignalContext,
+};
 
-    async fn get_remaining_time(&self) -> zbus::Result<u32>;
+use crate::{
+    error::{DaemonError, Error, Result},
+    events::{Event, EventFilter},
+    payload,
+};
 
-    async fn report_activity(&self, activity_json: &str) -> zbus::Result<String>;
+pub const OBJECT_PATH: &str = "/org/dots/FamilyDaemon";
 
-    async fn send_heartbeat(&self, monitor_id: &str) -> zbus::Result<String>;
+/// What a server method gets to know about the call besides its arguments
+pub struct MethodCall<'a> {
+    pub connection: &'a Connection,
+    pub header: &'a Header<'a>,
+    pub signal_context: &'a SignalContext<'a>,
+}
 
-    async fn ping(&self) -> zbus::Result<String>;
+macro_rules! family_daemon_interface {
+    (
+        methods {
+            $( fn $method:ident($($arg:ident: $arg_ty:ty),* $(,)?) -> $ret:ty; )*
+        }
+        signals {
+            $( fn $signal:ident($($sig_arg:ident: $sig_ty:ty),* $(,)?); )*
+        }
+    ) => {
+        #[proxy(
+            interface = "org.dots.FamilyDaemon",
+            default_service = "org.dots.FamilyDaemon",
+            default_path = "/org/dots/FamilyDaemon"
+        )]
+        pub trait FamilyDaemon {
+            $( async fn $method(&self, $($arg: $arg_ty),*) -> Result<$ret>; )*
 
-    async fn authenticate_parent(&self, password: &str) -> zbus::Result<String>;
+            $(
+                #[zbus(signal)]
+                async fn $signal(&self, $($sig_arg: $sig_ty),*) -> zbus::Result<()>;
+            )*
+        }
 
-    async fn validate_session(&self, token: &str) -> zbus::Result<bool>;
+        /// Methods of `org.dots.FamilyDaemon` as the daemon implements them
+        pub trait FamilyDaemonServer: Send + Sync + 'static {
+            $(
+                fn $method(
+                    &self,
+                    call: &MethodCall<'_>,
+                    $($arg: $arg_ty),*
+                ) -> impl Future<Output = std::result::Result<$ret, DaemonError>> + Send;
+            )*
+        }
 
-    async fn revoke_session(&self, token: &str) -> zbus::Result<bool>;
+        /// Serves a [`FamilyDaemonServer`] on the bus; its associated functions
+        /// emit the interface's signals
+        pub struct FamilyDaemonInterface<S>(pub S);
 
-    async fn list_profiles(&self) -> zbus::Result<String>;
+        #[interface(name = "org.dots.FamilyDaemon")]
+        impl<S: FamilyDaemonServer> FamilyDaemonInterface<S> {
+            $(
+                #[allow(clippy::too_many_arguments)]
+                async fn $method(
+                    &self,
+                    #[zbus(connection)] connection: &Connection,
+                    #[zbus(header)] header: Header<'_>,
+                    #[zbus(signal_context)] signal_context: SignalContext<'_>,
+                    $($arg: $arg_ty),*
+                ) -> std::result::Result<$ret, DaemonError> {
+                    let call = MethodCall {
+                        connection,
+                        header: &header,
+                        signal_context: &signal_context,
+                    };
+                    self.0.$method(&call, $($arg),*).await
+                }
+            )*
 
-    async fn create_profile(
-        &self,
-        name: &str,
-        age_group: &str,
-        username: &str,
-    ) -> zbus::Result<String>;
+            $(
+                #[zbus(signal)]
+                pub async fn $signal(
+                    signal_context: &SignalContext<'_>,
+                    $($sig_arg: $sig_ty),*
+                ) -> zbus::Result<()>;
+            )*
+        }
 
-    async fn set_active_profile(&self, profile_id: &str) -> zbus::Result<()>;
+        /// Stands in for the daemon when only the shape of the interface matters
+        struct Unserved;
 
-    async fn request_parent_permission(
-        &self,
-        request_type: &str,
-        details: &str,
-        token: &str,
-    ) -> zbus::Result<String>;
+        impl FamilyDaemonServer for Unserved {
+            $(
+                async fn $method(
+                    &self,
+                    _call: &MethodCall<'_>,
+                    $($arg: $arg_ty),*
+                ) -> std::result::Result<$ret, DaemonError> {
+                    let _ = ($($arg,)*);
+                    Err(DaemonError::Unavailable(format!("{} is not served", stringify!($method))))
+                }
+            )*
+        }
+    };
+}
 
-    async fn request_command_approval(
-        &self,
-        command: &str,
-        risk_level: &str,
-        reasons: &str,
-    ) -> zbus::Result<String>;
+family_daemon_interface! {
+    methods {
+        fn get_active_profile() -> String;
+        fn check_application_allowed(app_id: &str) -> bool;
+        fn get_remaining_time() -> u32;
+        fn report_activity(activity_json: &str) -> String;
+        fn report_activity_event(event_json: &str) -> String;
+        fn ping() -> bool;
+        fn send_heartbeat(monitor_id: &str) -> String;
+        fn register_component(kind: &str) -> String;
 
-    async fn check_app_policy(&self, app_id: &str) -> zbus::Result<String>;
+        // Profile and session methods
+        fn list_profiles() -> String;
+        fn create_profile(name: &str, age_group: &str, username: &str) -> String;
+        fn authenticate_parent(password: &str) -> String;
+        fn authenticate_guardian(name: &str, password: &str) -> String;
+        fn validate_session(token: &str) -> bool;
+        fn revoke_session(token: &str) -> bool;
+        fn set_active_profile(profile_id: &str) -> ();
+        fn request_parent_permission(request_type: &str, details: &str, token: &str) -> String;
+        fn request_command_approval(command: &str, risk_level: &str, reasons: &str) -> String;
 
-    async fn process_activity_for_policy(&self, activity_json: &str) -> zbus::Result<String>;
+        // Exception methods
+        fn create_exception(
+            exception_type: &str,
+            reason: &str,
+            duration_json: &str,
+            token: &str,
+        ) -> String;
+        fn list_active_exceptions(profile_id: &str, token: &str) -> String;
+        fn revoke_exception(exception_id: &str, token: &str) -> String;
+        fn check_exception_applies(exception_type: &str, resource_id: &str) -> bool;
 
-    async fn sync_profile_to_policy(&self, profile_id: &str) -> zbus::Result<String>;
+        // Approval request methods
+        fn submit_approval_request(request_type: &str, message: &str, details_json: &str) -> String;
+        fn list_pending_requests(token: &str) -> String;
+        fn approve_request(request_id: &str, response_message: &str, token: &str) -> String;
+        fn deny_request(request_id: &str, response_message: &str, token: &str) -> String;
 
-    // Report generation methods
-    async fn get_daily_report(&self, profile_id: &str, date: &str) -> zbus::Result<String>;
+        // Auto-approval rule methods
+        fn list_auto_approval_rules(token: &str) -> String;
+        fn add_auto_approval_rule(rule_json: &str, token: &str) -> String;
+        fn set_auto_approval_rule_enabled(rule_id: &str, enabled: bool, token: &str) -> String;
+        fn remove_auto_approval_rule(rule_id: &str, token: &str) -> String;
 
-    async fn get_weekly_report(&self, profile_id: &str, week_start: &str) -> zbus::Result<String>;
+        // Time bank methods
+        fn grant_time_credit(profile_id: &str, minutes: u32, reason: &str, token: &str) -> String;
+        fn get_time_bank(profile_id: &str, token: &str) -> String;
+        fn get_time_credit_balance() -> u32;
 
-    async fn export_reports(
-        &self,
-        profile_id: &str,
-        format: &str,
-        start_date: &str,
-        end_date: &str,
-    ) -> zbus::Result<String>;
-
-    // Time window configuration methods
-    async fn add_time_window(
-        &self,
-        profile_id: &str,
-        window_type: &str,
-        start: &str,
-        end: &str,
-        token: &str,
-    ) -> zbus::Result<String>;
+        // Monitoring and policy methods
+        fn get_monitoring_snapshot() -> String;
+        fn get_ebpf_status() -> (u32, bool, String);
+        fn check_app_policy(app_id: &str) -> String;
+        fn process_activity_for_policy(activity_json: &str) -> String;
+        fn sync_profile_to_policy(profile_id: &str) -> String;
+        fn recent_events(filter_json: &str, token: &str) -> String;
+        fn subscribe_events(filter_json: &str, token: &str) -> ();
 
-    async fn remove_time_window(
-        &self,
-        profile_id: &str,
-        window_type: &str,
-        start: &str,
-        end: &str,
-        token: &str,
-    ) -> zbus::Result<String>;
+        // Report generation methods
+        fn get_daily_report(profile_id: &str, date: &str) -> String;
+        fn get_weekly_report(profile_id: &str, week_start: &str) -> String;
+        fn export_reports(
+            profile_id: &str,
+            format: &str,
+            start_date: &str,
+            end_date: &str,
+        ) -> String;
 
-    async fn list_time_windows(&self, profile_id: &str, token: &str) -> zbus::Result<String>;
+        // Enforcement methods
+        fn check_time_window() -> String;
+        fn get_next_time_window() -> String;
+        fn lock_session(username: &str) -> String;
+        fn clear_tamper_alert(token: &str) -> String;
+        fn verify_audit_log(token: &str) -> String;
+        fn request_finishing_up() -> String;
 
-    async fn clear_time_windows(
-        &self,
-        profile_id: &str,
-        window_type: &str,
-        token: &str,
-    ) -> zbus::Result<String>;
+        // Guardian account methods
+        fn add_guardian(
+            name: &str,
+            password: &str,
+            role: &str,
+            profile_ids: Vec<String>,
+            token: &str,
+        ) -> String;
+        fn remove_guardian(name: &str, token: &str) -> String;
+        fn list_guardians(token: &str) -> String;
 
-    // Approval request methods
-    async fn list_pending_requests(&self, token: &str) -> zbus::Result<String>;
+        // Time window configuration methods
+        fn add_time_window(
+            profile_id: &str,
+            window_type: &str,
+            start: &str,
+            end: &str,
+            token: &str,
+        ) -> String;
+        fn remove_time_window(
+            profile_id: &str,
+            window_type: &str,
+            start: &str,
+            end: &str,
+            token: &str,
+        ) -> String;
+        fn list_time_windows(profile_id: &str, token: &str) -> String;
+        fn clear_time_windows(profile_id: &str, window_type: &str, token: &str) -> String;
+    }
+    signals {
+        fn policy_updated(profile_id: &str);
+        fn approval_request_created(request_id: &str, request_type: &str);
+        fn approval_request_resolved(request_id: &str, status: &str, message: &str);
+        fn time_limit_warning(minutes_remaining: u32);
+        fn time_window_ending(minutes_remaining: u32);
+        fn enforcement_stage_changed(stage: &str, seconds_remaining: i64);
+        fn tamper_detected(reason: &str);
+        fn family_event(kind: &str, profile_id: &str, event_json: &str);
+    }
+}
 
-    async fn approve_request(
+impl FamilyDaemonProxy<'_> {
+    /// Events published from now on that match `filter` and that the guardian
+    /// signed in with `token` may see. The daemon sends them to this
+    /// connection only, until it leaves the bus or the session ends.
+    pub async fn receive_events(
         &self,
-        request_id: &str,
-        response_message: &str,
+        filter: EventFilter,
         token: &str,
-    ) -> zbus::Result<String>;
+    ) -> Result<impl Stream<Item = Event> + Unpin> {
+        let filter_json =
+            payload::encode(&filter).map_err(|e| Error::InvalidArgument(e.to_string()))?;
+        // Listen first so nothing sent right after subscribing is lost
+        let events = self.family_events(filter).await?;
+        self.subscribe_events(&filter_json, token).await?;
+        Ok(events)
+    }
 
-    async fn deny_request(
+    /// `FamilyEvent` signals that match `filter`. A single kind and the profile
+    /// are matched by the bus, so other events are not even delivered.
+    async fn family_events(
         &self,
-        request_id: &str,
-        response_message: &str,
-        token: &str,
-    ) -> zbus::Result<String>;
+        filter: EventFilter,
+    ) -> zbus::Result<impl Stream<Item = Event> + Unpin> {
+        let profile_id = filter.profile_id.map(|id| id.to_string());
+        let mut args = Vec::new();
+        if let [kind] = filter.kinds.as_slice() {
+            args.push((0, kind.as_str()));
+        }
+        if let Some(profile_id) = &profile_id {
+            args.push((1, profile_id.as_str()));
+        }
 
-    #[zbus(signal)]
-    async fn policy_updated(&self, profile_id: &str) -> zbus::Result<()>;
+        let signals = self.receive_family_event_with_args(&args).await?;
+        Ok(signals.filter_map(move |signal| {
+            let event = signal
+                .args()
+                .ok()
+                .and_then(|args| payload::decode::<Event>(args.event_json()).ok())
+                .filter(|event| filter.matches(event));
+            future::ready(event)
+        }))
+    }
+}
 
-    #[zbus(signal)]
-    async fn approval_request_created(
-        &self,
-        request_id: &str,
-        request_type: &str,
-    ) -> zbus::Result<()>;
+/// Introspection XML of the interface, as exported to `interfaces/` for
+/// clients that are not written against this crate
+pub fn introspection_xml() -> String {
+    let mut xml = String::from(
+        "<!DOCTYPE node PUBLIC \"-//freedesktop//DTD D-BUS Object Introspection 1.0//EN\"\n \
+         \"http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd\">\n<node>\n",
+    );
+    FamilyDaemonInterface(Unserved).introspect_to_writer(&mut xml, 1);
+    xml.push_str("</node>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORTED_XML: &str = include_str!("../interfaces/org.dots.FamilyDaemon.xml");
+
+    #[test]
+    fn test_exported_introspection_is_current() {
+        let xml = introspection_xml();
+        if std::env::var_os("DOTS_UPDATE_INTERFACE_EXPORTS").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/interfaces/org.dots.FamilyDaemon.xml");
+            std::fs::write(path, &xml).unwrap();
+            return;
+        }
+        assert_eq!(
+            xml, EXPORTED_XML,
+            "org.dots.FamilyDaemon changed; rerun with DOTS_UPDATE_INTERFACE_EXPORTS=1 to re-export"
+        );
+    }
+
+    macro_rules! assert_served {
+        ($proxy:expr, $($method:ident($($arg:expr),*)),* $(,)?) => {
+            $(
+                match $proxy.$method($($arg),*).await {
+                    Err(Error::Unavailable(msg)) => {
+                        assert_eq!(msg, format!("{} is not served", stringify!($method)))
+                    }
+                    other => panic!("{} did not reach the server: {:?}", stringify!($method), other),
+                }
+            )*
+        };
+    }
+
+    #[tokio::test]
+    async fn test_proxy_calls_reach_server() {
+        let (client_stream, server_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+        let server = zbus::connection::Builder::unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(OBJECT_PATH, FamilyDaemonInterface(Unserved))
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = tokio::try_join!(server, client).unwrap();
+
+        let proxy = FamilyDaemonProxy::builder(&client)
+            .destination("org.dots.FamilyDaemon")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        // Every method with placeholder arguments; a signature mismatch would be
+        // rejected by the server before it got to answer
+        assert_served!(
+            proxy,
+            get_active_profile(),
+            check_application_allowed(""),
+            get_remaining_time(),
+            report_activity(""),
+            report_activity_event(""),
+            ping(),
+            send_heartbeat(""),
+            register_component(""),
+            list_profiles(),
+            create_profile("", "", ""),
+            authenticate_parent(""),
+            authenticate_guardian("", ""),
+            validate_session(""),
+            revoke_session(""),
+            set_active_profile(""),
+            request_parent_permission("", "", ""),
+            request_command_approval("", "", ""),
+            create_exception("", "", "", ""),
+            list_active_exceptions("", ""),
+            revoke_exception("", ""),
+            check_exception_applies("", ""),
+            submit_approval_request("", "", ""),
+            list_pending_requests(""),
+            approve_request("", "", ""),
+            deny_request("", "", ""),
+            list_auto_approval_rules(""),
+            add_auto_approval_rule("", ""),
+            set_auto_approval_rule_enabled("", true, ""),
+            remove_auto_approval_rule("", ""),
+            grant_time_credit("", 0, "", ""),
+            get_time_bank("", ""),
+            get_time_credit_balance(),
+            get_monitoring_snapshot(),
+            get_ebpf_status(),
+            check_app_policy(""),
+            process_activity_for_policy(""),
+            sync_profile_to_policy(""),
+            recent_events("", ""),
+            subscribe_events("", ""),
+            get_daily_report("", ""),
+            get_weekly_report("", ""),
+            export_reports("", "", "", ""),
+            check_time_window(),
+            get_next_time_window(),
+            lock_session(""),
+            clear_tamper_alert(""),
+            verify_audit_log(""),
+            request_finishing_up(),
+            add_guardian("", "", "", Vec::new(), ""),
+            remove_guardian("", ""),
+            list_guardians(""),
+            add_time_window("", "", "", "", ""),
+            remove_time_window("", "", "", "", ""),
+            list_time_windows("", ""),
+            clear_time_windows("", "", ""),
+        );
+
+        drop(server);
+    }
+
+    #[tokio::test]
+    async fn test_receive_events_filters_published_events() {
+        let (client_stream, server_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+        let server = zbus::connection::Builder::unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(OBJECT_PATH, FamilyDaemonInterface(Unserved))
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = tokio::try_join!(server, client).unwrap();
+
+        let proxy = FamilyDaemonProxy::builder(&client)
+            .destination("org.dots.FamilyDaemon")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let profile_id = uuid::Uuid::new_v4();
+        let filter = EventFilter { kinds: vec!["app_focused".to_string()], profile_id: None };
+        let mut events = proxy.family_events(filter).await.unwrap();
 
-    #[zbus(signal)]
-    async fn time_limit_warning(&self, minutes_remaining: u32) -> zbus::Result<()>;
+        let timestamp = chrono::Utc::now();
+        let published = [
+            Event::ExceptionRevoked { exception_id: "exc-1".to_string(), timestamp },
+            Event::AppFocused { profile_id, app_id: "firefox".to_string(), timestamp },
+        ];
+        let context = SignalContext::new(&server, OBJECT_PATH).unwrap();
+        for event in &published {
+            FamilyDaemonInterface::<Unserved>::family_event(
+                &context,
+                event.kind(),
+                &event.profile_id().map(|id| id.to_string()).unwrap_or_default(),
+                &payload::encode(event).unwrap(),
+            )
+            .await
+            .unwrap();
+        }
 
-    #[zbus(signal)]
-    async fn tamper_detected(&self, reason: &str) -> zbus::Result<()>;
+        assert_eq!(events.next().await, Some(published[1].clone()));
+    }
 }
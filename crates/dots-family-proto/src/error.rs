@@ -0,0 +1,112 @@
+use std::fmt;
+
+use zbus::DBusError;
+
+/// Error replies of `org.dots.FamilyDaemon`, sent on the bus as
+/// `org.dots.FamilyDaemon.Error.<Variant>` with the message as description
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "org.dots.FamilyDaemon.Error", impl_display = false)]
+pub enum DaemonError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    /// The caller or its session may not make this call
+    NotAuthorized(String),
+    /// The profile, request, rule or other record does not exist
+    NotFound(String),
+    /// An argument could not be parsed or is out of range
+    InvalidArgument(String),
+    /// Too many calls in a short time; try again later
+    RateLimited(String),
+    /// A daemon component the call needs is not running
+    Unavailable(String),
+    /// The call was valid but could not be carried out
+    Failed(String),
+}
+
+// Only the description, so messages read the same in daemon logs as before
+impl fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZBus(e) => write!(f, "{}", e),
+            Self::NotAuthorized(msg)
+            | Self::NotFound(msg)
+            | Self::InvalidArgument(msg)
+            | Self::RateLimited(msg)
+            | Self::Unavailable(msg)
+            | Self::Failed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Error of a call through `FamilyDaemonProxy`, so clients can match on what went wrong
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    NotAuthorized(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    RateLimited(String),
+    #[error("{0}")]
+    Unavailable(String),
+    #[error("{0}")]
+    Failed(String),
+    /// The call did not reach the daemon, or it replied with an unknown error
+    #[error("D-Bus error: {0}")]
+    Dbus(zbus::Error),
+}
+
+impl From<DaemonError> for Error {
+    fn from(error: DaemonError) -> Self {
+        match error {
+            DaemonError::ZBus(e) => Self::Dbus(e),
+            DaemonError::NotAuthorized(msg) => Self::NotAuthorized(msg),
+            DaemonError::NotFound(msg) => Self::NotFound(msg),
+            DaemonError::InvalidArgument(msg) => Self::InvalidArgument(msg),
+            DaemonError::RateLimited(msg) => Self::RateLimited(msg),
+            DaemonError::Unavailable(msg) => Self::Unavailable(msg),
+            DaemonError::Failed(msg) => Self::Failed(msg),
+        }
+    }
+}
+
+impl From<zbus::Error> for Error {
+    fn from(error: zbus::Error) -> Self {
+        DaemonError::from(error).into()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use zbus::{names::ErrorName, DBusError as _};
+
+    use super::*;
+
+    #[test]
+    fn test_error_replies_round_trip() {
+        let error = DaemonError::NotFound("Approval request not found".to_string());
+        assert_eq!(error.name().as_str(), "org.dots.FamilyDaemon.Error.NotFound");
+        assert_eq!(error.to_string(), "Approval request not found");
+
+        // What a proxy sees when the daemon replies with that error
+        let reply = zbus::Error::MethodError(
+            ErrorName::from_static_str_unchecked("org.dots.FamilyDaemon.Error.NotFound").into(),
+            Some("Approval request not found".to_string()),
+            zbus::message::Message::method("/org/dots/FamilyDaemon", "Ping")
+                .unwrap()
+                .build(&())
+                .unwrap(),
+        );
+        match Error::from(reply) {
+            Error::NotFound(msg) => assert_eq!(msg, "Approval request not found"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let unknown = zbus::Error::Failure("connection closed".to_string());
+        assert!(matches!(Error::from(unknown), Error::Dbus(_)));
+    }
+}
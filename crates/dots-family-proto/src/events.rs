@@ -1,21 +1,37 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use uuid::Uuid;
 
 /// Activity events from eBPF monitoring and window tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ActivityEvent {
     /// Window gained focus (from window manager monitoring)
     WindowFocused { pid: u32, app_id: String, window_title: String, timestamp: SystemTime },
     /// Process started (from eBPF process monitoring)
-    ProcessStarted { pid: u32, executable: String, args: Vec<String>, timestamp: SystemTime },
+    ProcessStarted {
+        pid: u32,
+        executable: String,
+        /// Full argv, including argv[0]
+        args: Vec<String>,
+        /// Real uid of the process
+        uid: u32,
+        /// Parent pids, nearest first
+        #[serde(default)]
+        parent_pids: Vec<u32>,
+        timestamp: SystemTime,
+    },
+    /// Process exited (from eBPF process monitoring)
+    ProcessExited { pid: u32, uid: u32, timestamp: SystemTime },
     /// Network connection established (from eBPF network monitoring)
     NetworkConnection { pid: u32, local_addr: String, remote_addr: String, timestamp: SystemTime },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Live events the daemon publishes for dashboards and other tools, as sent
+/// in the `FamilyEvent` signal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     PolicyUpdated {
@@ -43,6 +59,89 @@ pub enum Event {
         reason: String,
         timestamp: DateTime<Utc>,
     },
+    /// A window of the active profile's session gained focus
+    AppFocused {
+        profile_id: Uuid,
+        app_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    ApprovalRequestCreated {
+        profile_id: Option<Uuid>,
+        request_id: String,
+        request_type: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Approved, denied or expired
+    ApprovalRequestResolved {
+        request_id: String,
+        status: String,
+        timestamp: DateTime<Utc>,
+    },
+    ExceptionCreated {
+        profile_id: Option<Uuid>,
+        exception_id: String,
+        exception_type: String,
+        timestamp: DateTime<Utc>,
+    },
+    ExceptionRevoked {
+        exception_id: String,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl Event {
+    /// Kind of event, as in its `type` field and the `FamilyEvent` signal
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PolicyUpdated { .. } => "policy_updated",
+            Self::TimeLimitWarning { .. } => "time_limit_warning",
+            Self::TimeLimitReached { .. } => "time_limit_reached",
+            Self::ApplicationBlocked { .. } => "application_blocked",
+            Self::WebsiteBlocked { .. } => "website_blocked",
+            Self::AppFocused { .. } => "app_focused",
+            Self::ApprovalRequestCreated { .. } => "approval_request_created",
+            Self::ApprovalRequestResolved { .. } => "approval_request_resolved",
+            Self::ExceptionCreated { .. } => "exception_created",
+            Self::ExceptionRevoked { .. } => "exception_revoked",
+        }
+    }
+
+    /// Profile the event is about, if it is about one
+    pub fn profile_id(&self) -> Option<Uuid> {
+        match self {
+            Self::PolicyUpdated { profile_id, .. }
+            | Self::TimeLimitWarning { profile_id, .. }
+            | Self::TimeLimitReached { profile_id, .. }
+            | Self::ApplicationBlocked { profile_id, .. }
+            | Self::WebsiteBlocked { profile_id, .. }
+            | Self::AppFocused { profile_id, .. } => Some(*profile_id),
+            Self::ApprovalRequestCreated { profile_id, .. }
+            | Self::ExceptionCreated { profile_id, .. } => *profile_id,
+            Self::ApprovalRequestResolved { .. } | Self::ExceptionRevoked { .. } => None,
+        }
+    }
+}
+
+/// Which events a subscriber wants; empty fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EventFilter {
+    /// Event kinds, as returned by [`Event::kind`]
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    /// Only events about this profile
+    #[serde(default)]
+    pub profile_id: Option<Uuid>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        let kind_matches = self.kinds.is_empty() || self.kinds.iter().any(|k| k == event.kind());
+        let profile_matches = match self.profile_id {
+            Some(profile_id) => event.profile_id() == Some(profile_id),
+            None => true,
+        };
+        kind_matches && profile_matches
+    }
 }
 
 #[cfg(test)]
@@ -216,6 +315,8 @@ mod tests {
             pid: 5678,
             executable: "/usr/bin/discord".to_string(),
             args: vec!["discord".to_string(), "--no-sandbox".to_string()],
+            uid: 1000,
+            parent_pids: vec![1200, 1],
             timestamp: SystemTime::now(),
         };
 
@@ -225,10 +326,12 @@ mod tests {
 
         let deserialized: ActivityEvent = serde_json::from_str(&json).unwrap();
         match deserialized {
-            ActivityEvent::ProcessStarted { pid, executable, args, .. } => {
+            ActivityEvent::ProcessStarted { pid, executable, args, uid, parent_pids, .. } => {
                 assert_eq!(pid, 5678);
                 assert!(executable.contains("discord"));
                 assert_eq!(args.len(), 2);
+                assert_eq!(uid, 1000);
+                assert_eq!(parent_pids, vec![1200, 1]);
             }
             _ => panic!("Wrong event type"),
         }
@@ -258,4 +361,59 @@ mod tests {
             _ => panic!("Wrong event type"),
         }
     }
+
+    #[test]
+    fn test_event_kind_matches_type_tag() {
+        let profile_id = Uuid::new_v4();
+        let timestamp = Utc::now();
+
+        let events = vec![
+            Event::PolicyUpdated { profile_id, timestamp },
+            Event::TimeLimitReached { profile_id, timestamp },
+            Event::AppFocused { profile_id, app_id: "firefox".to_string(), timestamp },
+            Event::ApprovalRequestCreated {
+                profile_id: None,
+                request_id: "req-1".to_string(),
+                request_type: "app".to_string(),
+                timestamp,
+            },
+            Event::ApprovalRequestResolved {
+                request_id: "req-1".to_string(),
+                status: "approved".to_string(),
+                timestamp,
+            },
+            Event::ExceptionRevoked { exception_id: "exc-1".to_string(), timestamp },
+        ];
+
+        for event in events {
+            let json = serde_json::to_value(&event).unwrap();
+            assert_eq!(json["type"], event.kind());
+        }
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let profile_id = Uuid::new_v4();
+        let timestamp = Utc::now();
+        let focused = Event::AppFocused { profile_id, app_id: "firefox".to_string(), timestamp };
+        let resolved = Event::ApprovalRequestResolved {
+            request_id: "req-1".to_string(),
+            status: "denied".to_string(),
+            timestamp,
+        };
+
+        assert!(EventFilter::default().matches(&focused));
+        assert!(EventFilter::default().matches(&resolved));
+
+        let by_kind = EventFilter { kinds: vec!["app_focused".to_string()], profile_id: None };
+        assert!(by_kind.matches(&focused));
+        assert!(!by_kind.matches(&resolved));
+
+        let by_profile = EventFilter { kinds: Vec::new(), profile_id: Some(profile_id) };
+        assert!(by_profile.matches(&focused));
+        assert!(!by_profile.matches(&resolved));
+
+        let other_profile = EventFilter { kinds: Vec::new(), profile_id: Some(Uuid::new_v4()) };
+        assert!(!other_profile.matches(&focused));
+    }
 }
@@ -1,5 +1,7 @@
 pub mod daemon;
+pub mod error;
 pub mod events;
 pub mod monitor;
+pub mod payload;
 
 pub use events::*;
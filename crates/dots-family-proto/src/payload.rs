@@ -0,0 +1,626 @@
+//! Versioned JSON payloads carried in the string arguments and replies of
+//! `org.dots.FamilyDaemon`.
+//!
+//! Every payload is a JSON object with a `schema_version` field next to its
+//! own fields. Payloads without one come from clients built before the field
+//! existed and are read as version 0, as are bare values such clients sent in
+//! place of an object; [`Payload::upgrade`] brings them (and any other older
+//! version) up to [`SCHEMA_VERSION`] before they are parsed.
+//! Payloads from a newer schema are refused so the sender learns that the
+//! daemon is older than it is. Unknown fields are ignored, so a newer daemon
+//! can add optional fields without bumping the version.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use dots_family_common::{
+    types::{Activity, Exception, ExceptionDuration, Profile, TimeWindow},
+    AutoApprovalRule, Guardian, RuleAction, RuleCondition, TimeBankSummary, TimeCreditEntry,
+};
+use schemars::{
+    schema::{InstanceType, RootSchema, Schema, SchemaObject},
+    schema_for, JsonSchema,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::events::{ActivityEvent, Event, EventFilter};
+
+/// Version of the payload schemas this build reads and writes
+pub const SCHEMA_VERSION: u32 = 1;
+
+const VERSION_FIELD: &str = "schema_version";
+
+/// A JSON payload with a stable, exported schema
+pub trait Payload: Serialize + DeserializeOwned + JsonSchema {
+    /// Name the schema is exported under
+    const NAME: &'static str;
+
+    /// Rewrite a payload of an older schema version into the current shape
+    fn upgrade(value: Value, _from_version: u32) -> Value {
+        value
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadError {
+    #[error("malformed payload: {0}")]
+    Malformed(#[from] serde_json::Error),
+    #[error("payload is not a JSON object")]
+    NotAnObject,
+    #[error("payload schema version {0} is newer than the supported version {SCHEMA_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// Serialize a payload with the current schema version
+pub fn encode<T: Payload>(payload: &T) -> Result<String, PayloadError> {
+    let mut value = serde_json::to_value(payload)?;
+    let object = value.as_object_mut().ok_or(PayloadError::NotAnObject)?;
+    object.insert(VERSION_FIELD.to_string(), SCHEMA_VERSION.into());
+    Ok(value.to_string())
+}
+
+/// Parse a payload of this or any older schema version
+pub fn decode<T: Payload>(json: &str) -> Result<T, PayloadError> {
+    let mut value: Value = serde_json::from_str(json)?;
+    let version = match value.as_object_mut().and_then(|object| object.remove(VERSION_FIELD)) {
+        Some(version) => serde_json::from_value(version)?,
+        None => 0,
+    };
+
+    if version > SCHEMA_VERSION {
+        return Err(PayloadError::UnsupportedVersion(version));
+    }
+    if version < SCHEMA_VERSION {
+        value = T::upgrade(value, version);
+    }
+    if !value.is_object() {
+        return Err(PayloadError::NotAnObject);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// JSON Schema of a payload as it is sent, `schema_version` included
+pub fn json_schema<T: Payload>() -> RootSchema {
+    let mut root = schema_for!(T);
+    let version = SchemaObject {
+        instance_type: Some(InstanceType::Integer.into()),
+        const_value: Some(SCHEMA_VERSION.into()),
+        ..Default::default()
+    };
+    let object = root.schema.object();
+    object.properties.insert(VERSION_FIELD.to_string(), Schema::Object(version));
+    object.required.insert(VERSION_FIELD.to_string());
+    root
+}
+
+/// Every payload schema, as exported to `schemas/`
+pub fn exported_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        (Activity::NAME, json_schema::<Activity>()),
+        (ActivityEvent::NAME, json_schema::<ActivityEvent>()),
+        (ActivityReceipt::NAME, json_schema::<ActivityReceipt>()),
+        (PolicyVerdict::NAME, json_schema::<PolicyVerdict>()),
+        (Profile::NAME, json_schema::<Profile>()),
+        (ProfileList::NAME, json_schema::<ProfileList>()),
+        (MonitoringSnapshot::NAME, json_schema::<MonitoringSnapshot>()),
+        (Event::NAME, json_schema::<Event>()),
+        (EventFilter::NAME, json_schema::<EventFilter>()),
+        (EventList::NAME, json_schema::<EventList>()),
+        (ExceptionList::NAME, json_schema::<ExceptionList>()),
+        (ExceptionTerm::NAME, json_schema::<ExceptionTerm>()),
+        (ApprovalDetails::NAME, json_schema::<ApprovalDetails>()),
+        (ApprovalSubmission::NAME, json_schema::<ApprovalSubmission>()),
+        (ApprovalRequestList::NAME, json_schema::<ApprovalRequestList>()),
+        (AutoApprovalRuleList::NAME, json_schema::<AutoApprovalRuleList>()),
+        (NewAutoApprovalRule::NAME, json_schema::<NewAutoApprovalRule>()),
+        (TimeBank::NAME, json_schema::<TimeBank>()),
+        (ActivityReport::NAME, json_schema::<ActivityReport>()),
+        (WeeklyReport::NAME, json_schema::<WeeklyReport>()),
+        (AuditReport::NAME, json_schema::<AuditReport>()),
+        (GuardianList::NAME, json_schema::<GuardianList>()),
+        (TimeWindowList::NAME, json_schema::<TimeWindowList>()),
+        (TimeWindowAccess::NAME, json_schema::<TimeWindowAccess>()),
+        (NextTimeWindow::NAME, json_schema::<NextTimeWindow>()),
+        (FinishingUpGrant::NAME, json_schema::<FinishingUpGrant>()),
+    ]
+}
+
+/// `ReportActivity` argument
+impl Payload for Activity {
+    const NAME: &'static str = "activity";
+}
+
+/// `ReportActivityEvent` argument
+impl Payload for ActivityEvent {
+    const NAME: &'static str = "activity-event";
+}
+
+/// `ReportActivity` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ActivityReceipt {
+    /// "success", "policy_blocked" or "policy_error"
+    pub status: String,
+    pub blocked: bool,
+    /// Action the policy engine took; absent when it did not decide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Why the policy engine failed, for "policy_error"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Payload for ActivityReceipt {
+    const NAME: &'static str = "activity-receipt";
+}
+
+/// `CheckAppPolicy` and `ProcessActivityForPolicy` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PolicyVerdict {
+    pub action: String,
+    pub reason: String,
+    pub blocked: bool,
+}
+
+impl Payload for PolicyVerdict {
+    const NAME: &'static str = "policy-verdict";
+}
+
+/// `GetActiveProfile` reply
+impl Payload for Profile {
+    const NAME: &'static str = "profile";
+}
+
+/// `ListProfiles` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+}
+
+impl Payload for ProfileList {
+    const NAME: &'static str = "profile-list";
+}
+
+/// `GetMonitoringSnapshot` reply; each monitor reports its own fields
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MonitoringSnapshot {
+    /// Unix time the snapshot was taken
+    pub timestamp: i64,
+    pub process_monitoring: Value,
+    pub network_monitoring: Value,
+    pub filesystem_monitoring: Value,
+    pub memory_monitoring: Value,
+    pub disk_io_monitoring: Value,
+}
+
+impl Payload for MonitoringSnapshot {
+    const NAME: &'static str = "monitoring-snapshot";
+}
+
+/// `FamilyEvent` signal argument
+impl Payload for Event {
+    const NAME: &'static str = "event";
+}
+
+/// `RecentEvents` argument
+impl Payload for EventFilter {
+    const NAME: &'static str = "event-filter";
+}
+
+/// `RecentEvents` reply, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventList {
+    pub events: Vec<Event>,
+}
+
+impl Payload for EventList {
+    const NAME: &'static str = "event-list";
+}
+
+/// `ListActiveExceptions` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExceptionList {
+    pub exceptions: Vec<Exception>,
+}
+
+impl Payload for ExceptionList {
+    const NAME: &'static str = "exception-list";
+}
+
+/// `CreateException` duration argument
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExceptionTerm {
+    pub duration: ExceptionDuration,
+}
+
+impl Payload for ExceptionTerm {
+    const NAME: &'static str = "exception-term";
+
+    /// Version 0 was the bare duration
+    fn upgrade(value: Value, _from_version: u32) -> Value {
+        match value {
+            Value::Object(ref object) if object.contains_key("duration") => value,
+            duration => serde_json::json!({ "duration": duration }),
+        }
+    }
+}
+
+/// `SubmitApprovalRequest` details; which fields a request needs depends on
+/// its type
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalDetails {
+    /// `app` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// `website` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// `screen_time` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_minutes: Option<u32>,
+    /// `time_extension` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_end_time: Option<DateTime<Utc>>,
+    /// `command` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// `custom` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `time_credit` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<u32>,
+}
+
+impl Payload for ApprovalDetails {
+    const NAME: &'static str = "approval-details";
+}
+
+/// `SubmitApprovalRequest` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalSubmission {
+    pub request_id: String,
+    /// "pending", "auto_approved" or "denied"
+    pub request_status: String,
+    /// Explanation from the auto-approval rule that resolved the request
+    #[serde(default)]
+    pub explanation: Option<String>,
+    /// Exception granted by an auto-approval, if any
+    #[serde(default)]
+    pub exception_id: Option<String>,
+}
+
+impl Payload for ApprovalSubmission {
+    const NAME: &'static str = "approval-submission";
+}
+
+/// An approval request as listed to parents
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub profile_id: String,
+    pub request_type: String,
+    pub requested_at: DateTime<Utc>,
+    /// "pending", "approved", "denied", "auto_approved" or "expired"
+    pub status: String,
+    pub details: ApprovalDetails,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub response_reason: Option<String>,
+}
+
+/// `ListPendingRequests` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalRequestList {
+    pub requests: Vec<ApprovalRequest>,
+}
+
+impl Payload for ApprovalRequestList {
+    const NAME: &'static str = "approval-request-list";
+}
+
+/// `ListAutoApprovalRules` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutoApprovalRuleList {
+    pub rules: Vec<AutoApprovalRule>,
+}
+
+impl Payload for AutoApprovalRuleList {
+    const NAME: &'static str = "auto-approval-rule-list";
+}
+
+/// `AddAutoApprovalRule` argument
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NewAutoApprovalRule {
+    pub name: String,
+    /// Profile the rule applies to; every profile when absent
+    #[serde(default)]
+    pub profile_id: Option<Uuid>,
+    pub conditions: Vec<RuleCondition>,
+    pub action: RuleAction,
+}
+
+impl Payload for NewAutoApprovalRule {
+    const NAME: &'static str = "new-auto-approval-rule";
+}
+
+/// `GetTimeBank` reply: spendable balance and full ledger history
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimeBank {
+    pub profile_id: String,
+    pub balance_minutes: u32,
+    pub history: Vec<TimeCreditEntry>,
+}
+
+impl Payload for TimeBank {
+    const NAME: &'static str = "time-bank";
+}
+
+/// `GetDailyReport` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ActivityReport {
+    pub date: NaiveDate,
+    pub screen_time_minutes: u32,
+    pub top_activity: String,
+    pub top_category: String,
+    pub violations: u32,
+    pub blocked_attempts: u32,
+    pub apps_used: Vec<AppUsage>,
+    #[serde(default)]
+    pub time_bank: TimeBankSummary,
+}
+
+impl Payload for ActivityReport {
+    const NAME: &'static str = "activity-report";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AppUsage {
+    pub app_id: String,
+    pub app_name: String,
+    pub category: String,
+    pub duration_minutes: u32,
+    pub percentage: f32,
+}
+
+/// `GetWeeklyReport` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WeeklyReport {
+    pub week_start: NaiveDate,
+    pub total_screen_time_minutes: u32,
+    pub average_daily_minutes: u32,
+    pub most_active_day: String,
+    pub top_categories: Vec<CategoryUsage>,
+    pub policy_violations: u32,
+    pub educational_percentage: f32,
+    #[serde(default)]
+    pub time_bank: TimeBankSummary,
+}
+
+impl Payload for WeeklyReport {
+    const NAME: &'static str = "weekly-report";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryUsage {
+    pub category: String,
+    pub duration_minutes: u32,
+    pub percentage: f32,
+}
+
+/// `VerifyAuditLog` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditReport {
+    pub entries_checked: usize,
+    /// Entries written before the log was chained
+    pub legacy_entries: usize,
+    pub seals_checked: usize,
+    pub head_entry_id: Option<i64>,
+    /// Newest entry covered by a valid seal
+    pub last_sealed_entry_id: Option<i64>,
+    /// Breaks ordered by entry, earliest first
+    pub breaks: Vec<AuditBreak>,
+}
+
+impl Payload for AuditReport {
+    const NAME: &'static str = "audit-report";
+}
+
+/// Where and why the audit chain no longer verifies
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditBreak {
+    /// First audit entry affected by the break
+    pub entry_id: i64,
+    /// Seal that exposed the break, if it was found through a seal
+    pub seal_id: Option<i64>,
+    pub reason: String,
+}
+
+/// `ListGuardians` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuardianList {
+    pub guardians: Vec<Guardian>,
+}
+
+impl Payload for GuardianList {
+    const NAME: &'static str = "guardian-list";
+}
+
+/// `ListTimeWindows` reply
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeWindowList {
+    pub profile_id: String,
+    pub profile_name: String,
+    pub weekday: Vec<TimeWindow>,
+    pub weekend: Vec<TimeWindow>,
+    pub holiday: Vec<TimeWindow>,
+}
+
+impl Payload for TimeWindowList {
+    const NAME: &'static str = "time-window-list";
+}
+
+/// `CheckTimeWindow` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimeWindowAccess {
+    pub allowed: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Payload for TimeWindowAccess {
+    const NAME: &'static str = "time-window-access";
+}
+
+/// `GetNextTimeWindow` reply; `start` and `end` are `HH:MM` when a window
+/// is available
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NextTimeWindow {
+    pub available: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// Why no window is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Payload for NextTimeWindow {
+    const NAME: &'static str = "next-time-window";
+}
+
+/// `RequestFinishingUp` reply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FinishingUpGrant {
+    pub extra_minutes: u32,
+}
+
+impl Payload for FinishingUpGrant {
+    const NAME: &'static str = "finishing-up-grant";
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::Utc;
+    use dots_family_common::types::ActivityType;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn activity() -> Activity {
+        Activity {
+            id: Uuid::new_v4(),
+            profile_id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            activity_type: ActivityType::ApplicationUsage,
+            application: Some("firefox".to_string()),
+            window_title: None,
+            duration_seconds: 60,
+        }
+    }
+
+    #[test]
+    fn test_payloads_round_trip_with_version() {
+        let activity = activity();
+        let json = encode(&activity).unwrap();
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[VERSION_FIELD], SCHEMA_VERSION);
+
+        let decoded: Activity = decode(&json).unwrap();
+        assert_eq!(decoded.id, activity.id);
+    }
+
+    #[test]
+    fn test_unversioned_payloads_from_older_clients_are_read() {
+        // What a monitor built before schema versions sends
+        let json = serde_json::to_string(&activity()).unwrap();
+        assert!(!json.contains(VERSION_FIELD));
+        assert!(decode::<Activity>(&json).is_ok());
+
+        let event = r#"{"type":"process_started","pid":42,"executable":"/usr/bin/ls","args":["ls"],
+            "uid":1000,"timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#;
+        match decode::<ActivityEvent>(event).unwrap() {
+            ActivityEvent::ProcessStarted { pid, uid, .. } => assert_eq!((pid, uid), (42, 1000)),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unversioned_replies_from_older_daemons_are_read() {
+        let next: NextTimeWindow =
+            decode(r#"{"available":false,"reason":"No upcoming windows configured"}"#).unwrap();
+        assert!(!next.available && next.start.is_none());
+
+        let grant: FinishingUpGrant = decode(r#"{"status":"success","extra_minutes":10}"#).unwrap();
+        assert_eq!(grant.extra_minutes, 10);
+
+        let submission: ApprovalSubmission = decode(
+            r#"{"status":"success","request_id":"r1","request_status":"pending",
+                "explanation":null,"exception_id":null}"#,
+        )
+        .unwrap();
+        assert_eq!((submission.request_id.as_str(), submission.explanation), ("r1", None));
+    }
+
+    #[test]
+    fn test_process_started_without_uid_is_refused() {
+        // A missing uid must not be read as root
+        let event = r#"{"type":"process_started","pid":42,"executable":"/usr/bin/ls","args":["ls"],
+            "timestamp":{"secs_since_epoch":0,"nanos_since_epoch":0}}"#;
+        assert!(decode::<ActivityEvent>(event).is_err());
+    }
+
+    #[test]
+    fn test_bare_durations_from_older_clients_are_read() {
+        let term: ExceptionTerm = decode(r#""Manual""#).unwrap();
+        assert_eq!(term.duration, ExceptionDuration::Manual);
+
+        let term: ExceptionTerm = decode(r#"{"Duration":[3600,0]}"#).unwrap();
+        assert_eq!(term.duration, ExceptionDuration::Duration(chrono::Duration::hours(1)));
+
+        let json = encode(&ExceptionTerm { duration: ExceptionDuration::UntilEndOfDay }).unwrap();
+        let term: ExceptionTerm = decode(&json).unwrap();
+        assert_eq!(term.duration, ExceptionDuration::UntilEndOfDay);
+    }
+
+    #[test]
+    fn test_newer_payloads_are_refused() {
+        let mut value = serde_json::to_value(activity()).unwrap();
+        value[VERSION_FIELD] = (SCHEMA_VERSION + 1).into();
+
+        let result = decode::<Activity>(&value.to_string());
+        assert!(
+            matches!(result, Err(PayloadError::UnsupportedVersion(v)) if v == SCHEMA_VERSION + 1)
+        );
+        assert!(matches!(decode::<Activity>("[]"), Err(PayloadError::NotAnObject)));
+    }
+
+    #[test]
+    fn test_exported_schemas_are_current() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        let update = std::env::var_os("DOTS_UPDATE_INTERFACE_EXPORTS").is_some();
+
+        for (name, schema) in exported_schemas() {
+            let path = dir.join(format!("{}.v{}.json", name, SCHEMA_VERSION));
+            let json = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            if update {
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(&path, &json).unwrap();
+                continue;
+            }
+            let exported = std::fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(
+                json,
+                exported,
+                "{} changed; rerun with DOTS_UPDATE_INTERFACE_EXPORTS=1 to re-export",
+                path.display()
+            );
+        }
+    }
+}
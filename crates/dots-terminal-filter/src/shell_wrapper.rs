@@ -1,7 +1,10 @@
 use anyhow::{Context, Result};
 use dots_family_common::types::{Activity, ActivityType};
-use dots_family_proto::daemon::FamilyDaemonProxy;
+use dots_family_proto::daemon::{ApprovalRequestResolvedStream, FamilyDaemonProxy};
+use dots_family_proto::{error::Error as DaemonError, payload};
+use futures::StreamExt;
 use std::process::{Command, Stdio};
+use std::time::Duration;
 use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tracing::{debug, error, info, warn};
 use zbus::Connection;
@@ -38,6 +41,11 @@ impl ShellWrapper {
             Ok(conn) => match FamilyDaemonProxy::new(&conn).await {
                 Ok(proxy) => {
                     debug!("Connected to daemon via DBus: {}", interface);
+                    // The daemon records our activities for this session's profile
+                    match proxy.register_component("terminal-filter").await {
+                        Ok(profile_id) => debug!("Registered for profile {}", profile_id),
+                        Err(e) => warn!("Failed to register with daemon: {}", e),
+                    }
                     Some(proxy)
                 }
                 Err(e) => {
@@ -256,6 +264,15 @@ impl ShellWrapper {
             let risk_level = format!("{:?}", assessment.risk_level);
             let reasons = assessment.reasons.join(", ");
 
+            // Subscribe before submitting so a quick parent response is not missed
+            let resolutions = match proxy.receive_approval_request_resolved().await {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    warn!("Failed to subscribe to approval resolutions: {}", e);
+                    None
+                }
+            };
+
             match proxy.request_command_approval(command, &risk_level, &reasons).await {
                 Ok(response) => match serde_json::from_str::<serde_json::Value>(&response) {
                     Ok(json) => {
@@ -266,12 +283,12 @@ impl ShellWrapper {
                                 println!("   Message: {}", message);
                             }
                             self.log_command_activity(command, "approval_requested").await;
-                            return false;
-                        } else if json.get("error").is_some() {
-                            println!(
-                                "   ✗ Approval request failed: {}",
-                                json.get("error").unwrap_or(&serde_json::Value::Null)
-                            );
+                            return match resolutions {
+                                Some(stream) => {
+                                    self.wait_for_approval(command, approval_id, stream).await
+                                }
+                                None => false,
+                            };
                         }
                     }
                     Err(e) => {
@@ -279,10 +296,11 @@ impl ShellWrapper {
                         println!("   ✗ Approval system error");
                     }
                 },
-                Err(e) => {
+                Err(DaemonError::Dbus(e)) => {
                     warn!("Failed to request command approval: {}", e);
                     println!("   ✗ Unable to connect to approval system");
                 }
+                Err(e) => println!("   ✗ Approval request failed: {}", e),
             }
         } else {
             println!("   ✗ Approval system not available (daemon not connected)");
@@ -293,6 +311,58 @@ impl ShellWrapper {
         false
     }
 
+    /// Wait for the parent's decision on a submitted approval request
+    async fn wait_for_approval(
+        &self,
+        command: &str,
+        approval_id: &str,
+        mut resolutions: ApprovalRequestResolvedStream<'static>,
+    ) -> bool {
+        let timeout_secs = self.config.shell.approval_timeout;
+        println!("   Waiting up to {} seconds for a parent to respond...", timeout_secs);
+
+        let outcome = tokio::time::timeout(Duration::from_secs(timeout_secs), async {
+            while let Some(signal) = resolutions.next().await {
+                match signal.args() {
+                    Ok(args) if args.request_id == approval_id => {
+                        return Some((args.status.to_string(), args.message.to_string()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to parse approval resolution: {}", e),
+                }
+            }
+            None
+        })
+        .await;
+
+        match outcome {
+            Ok(Some((status, message))) => {
+                let approved = status == "approved" || status == "auto_approved";
+                match status.as_str() {
+                    "approved" | "auto_approved" => println!("   ✓ Request approved"),
+                    "expired" => println!("   ✗ Request expired without a response"),
+                    _ => println!("   ✗ Request denied"),
+                }
+                if !message.is_empty() {
+                    println!("   Message: {}", message);
+                }
+                self.log_command_activity(command, if approved { "approved" } else { "denied" })
+                    .await;
+                approved
+            }
+            Ok(None) => {
+                warn!("Approval resolution stream closed before a response arrived");
+                println!("   ✗ Lost connection to approval system");
+                false
+            }
+            Err(_) => {
+                println!("   ✗ No response within {} seconds", timeout_secs);
+                println!("   The request stays open until a parent responds or it expires.");
+                false
+            }
+        }
+    }
+
     async fn check_profile_restrictions(&self, command: &str) -> Result<bool> {
         if let Some(ref proxy) = self.daemon_proxy {
             let cmd_parts: Vec<&str> = command.split_whitespace().collect();
@@ -323,6 +393,7 @@ impl ShellWrapper {
         if let Some(ref proxy) = self.daemon_proxy {
             let activity = Activity {
                 id: uuid::Uuid::new_v4(),
+                // Stamped by the daemon from our registration
                 profile_id: uuid::Uuid::nil(),
                 timestamp: chrono::Utc::now(),
                 activity_type: ActivityType::TerminalCommand { command: command.to_string() },
@@ -331,7 +402,7 @@ impl ShellWrapper {
                 duration_seconds: 0,
             };
 
-            let activity_json = serde_json::to_string(&activity).unwrap_or_default();
+            let activity_json = payload::encode(&activity).unwrap_or_default();
             if let Err(e) = proxy.report_activity(&activity_json).await {
                 warn!("Failed to log command activity: {}", e);
             }
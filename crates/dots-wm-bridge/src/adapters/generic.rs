@@ -1,6 +1,6 @@
-use crate::types::{WMCapabilities, WindowInfo};
+use crate::types::{WMCapabilities, WMEventStream, WindowInfo, WindowTarget};
 use crate::WindowManagerAdapter;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use tracing::debug;
 
@@ -30,9 +30,8 @@ impl WindowManagerAdapter for GenericAdapter {
         Ok(vec![])
     }
 
-    async fn subscribe_to_events(&self) -> Result<()> {
-        debug!("Generic adapter does not support event subscription");
-        Ok(())
+    async fn subscribe_to_events(&self) -> Result<WMEventStream> {
+        bail!("Generic adapter does not support event subscription")
     }
 
     fn get_capabilities(&self) -> WMCapabilities {
@@ -42,6 +41,22 @@ impl WindowManagerAdapter for GenericAdapter {
     fn get_name(&self) -> &'static str {
         "Generic"
     }
+
+    async fn close_windows(&self, target: &WindowTarget) -> Result<usize> {
+        bail!("Generic adapter cannot close {}", target)
+    }
+
+    async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize> {
+        bail!("Generic adapter cannot minimize {}", target)
+    }
+
+    async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize> {
+        bail!("Generic adapter cannot change fullscreen state of {}", target)
+    }
+
+    async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize> {
+        bail!("Generic adapter cannot move {} to workspace {}", target, workspace)
+    }
 }
 
 #[cfg(test)]
@@ -72,5 +87,8 @@ mod tests {
         let result = adapter.get_all_windows().await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
+
+        assert!(adapter.close_windows(&WindowTarget::All).await.is_err());
+        assert!(adapter.subscribe_to_events().await.is_err());
     }
 }
@@ -1,20 +1,273 @@
-use crate::types::{WMCapabilities, WindowInfo, WindowState};
+use crate::detection::session_socket_for;
+use crate::ipc::{hyprland_dispatch, hyprland_events, hyprland_request};
+use crate::types::{
+    CompositorType, WMCapabilities, WMEvent, WMEventStream, WindowGeometry, WindowInfo,
+    WindowState, WindowTarget,
+};
 use crate::WindowManagerAdapter;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::process::Command;
+use futures::{stream, StreamExt};
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
-#[derive(Default)]
-pub struct HyprlandAdapter;
+pub struct HyprlandAdapter {
+    instance: Option<PathBuf>,
+}
+
+impl Default for HyprlandAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl HyprlandAdapter {
+    /// Adapter for the Hyprland instance running the current session
     pub fn new() -> Self {
-        Self
+        Self { instance: session_socket_for(CompositorType::Hyprland) }
+    }
+
+    /// Adapter for the Hyprland instance whose sockets are in `instance`
+    pub fn with_socket(instance: PathBuf) -> Self {
+        Self { instance: Some(instance) }
     }
 
     pub fn is_available() -> bool {
-        std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() && which::which("hyprctl").is_ok()
+        session_socket_for(CompositorType::Hyprland)
+            .is_some_and(|instance| instance.join(".socket.sock").exists())
+    }
+
+    fn instance(&self) -> Result<&Path> {
+        self.instance.as_deref().context("No Hyprland instance found")
+    }
+
+    /// Clients matching the target, as reported by `j/clients`
+    async fn clients(&self, target: &WindowTarget) -> Result<Vec<serde_json::Value>> {
+        let reply = hyprland_request(self.instance()?, "j/clients").await?;
+        let clients: Vec<serde_json::Value> = serde_json::from_str(&reply)?;
+
+        Ok(clients
+            .into_iter()
+            .filter(|client| target.matches(client["pid"].as_u64(), client["class"].as_str()))
+            .collect())
+    }
+
+    /// Run a dispatcher for each client, built from the client address
+    async fn dispatch_clients(
+        &self,
+        clients: &[serde_json::Value],
+        dispatcher: impl Fn(&str) -> String,
+    ) -> Result<usize> {
+        let addresses: Vec<&str> =
+            clients.iter().filter_map(|client| client["address"].as_str()).collect();
+
+        for address in &addresses {
+            hyprland_dispatch(self.instance()?, &dispatcher(address)).await?;
+        }
+        Ok(addresses.len())
+    }
+}
+
+/// Older Hyprland reports `fullscreen` as a bool, newer versions as a mode number
+fn is_fullscreen(client: &serde_json::Value) -> bool {
+    match &client["fullscreen"] {
+        serde_json::Value::Bool(fullscreen) => *fullscreen,
+        serde_json::Value::Number(mode) => mode.as_u64().unwrap_or(0) != 0,
+        _ => false,
+    }
+}
+
+/// Dispatcher that takes the client out of its current fullscreen mode.
+///
+/// `fullscreen` toggles, so it has to name the mode the client is in: mode 1
+/// is maximized, anything else full fullscreen. Older versions report the mode
+/// separately as `fullscreenMode`.
+fn leave_fullscreen_dispatcher(client: &serde_json::Value) -> Option<&'static str> {
+    if !is_fullscreen(client) {
+        return None;
+    }
+
+    let mode = match &client["fullscreen"] {
+        serde_json::Value::Number(mode) => mode.as_u64(),
+        _ => client["fullscreenMode"].as_u64(),
+    };
+    Some(if mode == Some(1) { "fullscreen 1" } else { "fullscreen 0" })
+}
+
+fn client_info(client: &serde_json::Value) -> WindowInfo {
+    let state = if is_fullscreen(client) {
+        WindowState::Fullscreen
+    } else if client["floating"].as_bool() == Some(true) {
+        WindowState::Floating
+    } else {
+        WindowState::Tiled
+    };
+
+    WindowInfo {
+        app_id: client.get("class").and_then(|v| v.as_str()).map(String::from),
+        title: client.get("title").and_then(|v| v.as_str()).map(String::from),
+        pid: client.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
+        workspace: client["workspace"]["name"].as_str().map(String::from),
+        geometry: client_geometry(client),
+        state,
+    }
+}
+
+fn client_geometry(client: &serde_json::Value) -> Option<WindowGeometry> {
+    let at = client["at"].as_array()?;
+    let size = client["size"].as_array()?;
+    Some(WindowGeometry {
+        x: at.first()?.as_i64()? as i32,
+        y: at.get(1)?.as_i64()? as i32,
+        width: size.first()?.as_u64()? as u32,
+        height: size.get(1)?.as_u64()? as u32,
+    })
+}
+
+/// Window addresses are hex, with or without a `0x` prefix
+fn parse_address(address: &str) -> Option<u64> {
+    u64::from_str_radix(address.trim().trim_start_matches("0x"), 16).ok()
+}
+
+fn event_window(app_id: &str, title: &str, workspace: Option<&str>) -> WindowInfo {
+    WindowInfo {
+        app_id: Some(app_id.to_string()).filter(|a| !a.is_empty()),
+        title: Some(title.to_string()),
+        pid: None,
+        workspace: workspace.map(String::from),
+        geometry: None,
+        state: WindowState::default(),
+    }
+}
+
+/// Client at `address` in a `j/clients` reply
+fn client_at(clients: &[serde_json::Value], address: u64) -> Option<&serde_json::Value> {
+    clients
+        .iter()
+        .find(|client| client["address"].as_str().and_then(parse_address) == Some(address))
+}
+
+/// What a `.socket2` line means once parsed; windows named by address still
+/// need their details looked up in `j/clients`
+#[derive(Debug, PartialEq)]
+enum Update {
+    Events(Vec<WMEvent>),
+    /// The window at this address took focus
+    Focused(u64),
+    /// The window at this address opened, as far as the event line describes it
+    Opened(u64, WindowInfo),
+}
+
+impl Update {
+    fn address(&self) -> Option<u64> {
+        match self {
+            Update::Events(_) => None,
+            Update::Focused(address) | Update::Opened(address, _) => Some(*address),
+        }
+    }
+}
+
+/// Focus state followed across `.socket2` lines; Hyprland names the focused
+/// window by class and title in one event and by address in the next
+#[derive(Default)]
+struct HyprlandEvents {
+    focused: Option<WindowInfo>,
+    focused_address: Option<u64>,
+}
+
+impl HyprlandEvents {
+    fn apply(&mut self, line: &str) -> Update {
+        let Some((event, data)) = line.split_once(">>") else {
+            return Update::Events(Vec::new());
+        };
+
+        match event {
+            "activewindow" => {
+                let (class, title) = data.split_once(',').unwrap_or((data, ""));
+                if class.is_empty() && title.is_empty() {
+                    self.focused = None;
+                    self.focused_address = None;
+                    return Update::Events(vec![WMEvent::WindowFocused(None)]);
+                }
+
+                // Reported once `activewindowv2` names the window's address
+                self.focused = Some(event_window(class, title, None));
+                Update::Events(Vec::new())
+            }
+            "activewindowv2" => match parse_address(data) {
+                Some(address) => Update::Focused(address),
+                None => Update::Events(Vec::new()),
+            },
+            "windowtitlev2" => {
+                let (address, title) = data.split_once(',').unwrap_or((data, ""));
+                let events = match self.focused.as_mut() {
+                    Some(focused)
+                        if parse_address(address).is_some()
+                            && parse_address(address) == self.focused_address
+                            && focused.title.as_deref() != Some(title) =>
+                    {
+                        focused.title = Some(title.to_string());
+                        vec![WMEvent::WindowFocused(Some(focused.clone()))]
+                    }
+                    _ => Vec::new(),
+                };
+                Update::Events(events)
+            }
+            "openwindow" => {
+                let mut fields = data.splitn(4, ',');
+                let (address, workspace, class, title) =
+                    (fields.next(), fields.next(), fields.next(), fields.next());
+                match (address.and_then(parse_address), class, title) {
+                    (Some(address), Some(class), Some(title)) => {
+                        Update::Opened(address, event_window(class, title, workspace))
+                    }
+                    _ => Update::Events(Vec::new()),
+                }
+            }
+            "closewindow" => {
+                Update::Events(parse_address(data).map(WMEvent::WindowClosed).into_iter().collect())
+            }
+            "workspace" => Update::Events(vec![WMEvent::WorkspaceChanged(data.to_string())]),
+            _ => Update::Events(Vec::new()),
+        }
+    }
+
+    /// Events for an update, with the window filled in from its client when
+    /// `j/clients` had it and from the event line otherwise
+    fn resolve(&mut self, update: Update, client: Option<&serde_json::Value>) -> Vec<WMEvent> {
+        match update {
+            Update::Events(events) => events,
+            Update::Focused(address) => {
+                self.focused_address = Some(address);
+                if let Some(client) = client {
+                    self.focused = Some(client_info(client));
+                }
+                match &self.focused {
+                    Some(focused) => vec![WMEvent::WindowFocused(Some(focused.clone()))],
+                    None => Vec::new(),
+                }
+            }
+            Update::Opened(_, window) => {
+                vec![WMEvent::WindowOpened(client.map(client_info).unwrap_or(window))]
+            }
+        }
+    }
+}
+
+/// Client at `address`, looked up over the command socket
+async fn lookup_client(instance: &Path, address: u64) -> Option<serde_json::Value> {
+    let clients: Result<Vec<serde_json::Value>> = async {
+        let reply = hyprland_request(instance, "j/clients").await?;
+        Ok(serde_json::from_str(&reply)?)
+    }
+    .await;
+
+    match clients {
+        Ok(clients) => client_at(&clients, address).cloned(),
+        Err(e) => {
+            debug!("Failed to look up Hyprland window {:x}: {}", address, e);
+            None
+        }
     }
 }
 
@@ -23,34 +276,15 @@ impl WindowManagerAdapter for HyprlandAdapter {
     async fn get_focused_window(&self) -> Result<Option<WindowInfo>> {
         debug!("Getting focused window from Hyprland");
 
-        let output = Command::new("hyprctl").arg("activewindow").arg("-j").output()?;
-
-        if !output.status.success() {
-            debug!("hyprctl activewindow failed");
-            return Ok(None);
-        }
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        if json_str.trim().is_empty() {
+        // With nothing focused Hyprland replies `{}`
+        let reply = hyprland_request(self.instance()?, "j/activewindow").await?;
+        let client: serde_json::Value = serde_json::from_str(&reply)?;
+        if client.get("address").is_none() {
             debug!("No active window");
             return Ok(None);
         }
 
-        let value: serde_json::Value = serde_json::from_str(&json_str)?;
-
-        let window_info = WindowInfo {
-            app_id: value.get("class").and_then(|v| v.as_str()).map(String::from),
-            title: value.get("title").and_then(|v| v.as_str()).map(String::from),
-            pid: value.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
-            workspace: value
-                .get("workspace")
-                .and_then(|w| w.get("name"))
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            geometry: None,
-            state: WindowState::default(),
-        };
-
+        let window_info = client_info(&client);
         debug!("Found focused window: {:?}", window_info);
         Ok(Some(window_info))
     }
@@ -58,70 +292,295 @@ impl WindowManagerAdapter for HyprlandAdapter {
     async fn get_all_windows(&self) -> Result<Vec<WindowInfo>> {
         debug!("Getting all windows from Hyprland");
 
-        let output = Command::new("hyprctl").arg("clients").arg("-j").output()?;
-
-        if !output.status.success() {
-            debug!("hyprctl clients failed");
-            return Ok(vec![]);
-        }
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        let windows_json: serde_json::Value = serde_json::from_str(&json_str)?;
-
-        let mut windows = Vec::new();
-
-        if let Some(windows_array) = windows_json.as_array() {
-            for window in windows_array {
-                let window_info = WindowInfo {
-                    app_id: window.get("class").and_then(|v| v.as_str()).map(String::from),
-                    title: window.get("title").and_then(|v| v.as_str()).map(String::from),
-                    pid: window.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
-                    workspace: window
-                        .get("workspace")
-                        .and_then(|w| w.get("name"))
-                        .and_then(|v| v.as_str())
-                        .map(String::from),
-                    geometry: None,
-                    state: WindowState::default(),
-                };
-                windows.push(window_info);
-            }
-        }
+        let windows: Vec<WindowInfo> =
+            self.clients(&WindowTarget::All).await?.iter().map(client_info).collect();
 
         debug!("Found {} windows", windows.len());
         Ok(windows)
     }
 
-    async fn subscribe_to_events(&self) -> Result<()> {
-        debug!("Hyprland event subscription not yet implemented");
-        Ok(())
+    async fn subscribe_to_events(&self) -> Result<WMEventStream> {
+        let instance = self.instance()?.to_path_buf();
+        let events = Box::pin(hyprland_events(&instance).await?);
+
+        let updates =
+            stream::unfold((events, HyprlandEvents::default()), move |(mut events, mut state)| {
+                let instance = instance.clone();
+                async move {
+                    let update = state.apply(&events.next().await?);
+                    let client = match update.address() {
+                        Some(address) => lookup_client(&instance, address).await,
+                        None => None,
+                    };
+                    let resolved = state.resolve(update, client.as_ref());
+                    Some((stream::iter(resolved), (events, state)))
+                }
+            });
+        Ok(Box::pin(updates.flatten()))
     }
 
     fn get_capabilities(&self) -> WMCapabilities {
         WMCapabilities {
             can_get_focused_window: true,
             can_get_all_windows: true,
-            can_subscribe_to_events: false,
-            can_control_windows: true,
+            can_subscribe_to_events: true,
+            can_close_windows: true,
+            can_minimize_windows: true,
+            can_exit_fullscreen: true,
+            can_move_to_workspace: true,
             supports_workspaces: true,
-            supports_window_geometry: false,
+            supports_window_geometry: true,
         }
     }
 
     fn get_name(&self) -> &'static str {
         "Hyprland"
     }
+
+    async fn close_windows(&self, target: &WindowTarget) -> Result<usize> {
+        debug!("Closing Hyprland windows: {}", target);
+        let clients = self.clients(target).await?;
+        self.dispatch_clients(&clients, |address| format!("closewindow address:{}", address)).await
+    }
+
+    async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize> {
+        // Hyprland has no minimized state; a hidden special workspace stands in for it
+        debug!("Minimizing Hyprland windows: {}", target);
+        let clients = self.clients(target).await?;
+        self.dispatch_clients(&clients, |address| {
+            format!("movetoworkspacesilent special:minimized,address:{}", address)
+        })
+        .await
+    }
+
+    async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize> {
+        debug!("Leaving fullscreen for Hyprland windows: {}", target);
+        let instance = self.instance()?;
+        let clients: Vec<_> =
+            self.clients(target).await?.into_iter().filter(is_fullscreen).collect();
+
+        // The fullscreen dispatcher toggles the focused window only, so check
+        // the window is still fullscreen once focused before toggling it
+        let mut left = 0;
+        for address in clients.iter().filter_map(|client| client["address"].as_str()) {
+            hyprland_dispatch(instance, &format!("focuswindow address:{}", address)).await?;
+
+            let reply = hyprland_request(instance, "j/activewindow").await?;
+            let active: serde_json::Value = serde_json::from_str(&reply)?;
+            if active["address"].as_str() != Some(address) {
+                debug!("Window {} did not take focus, leaving it fullscreen", address);
+                continue;
+            }
+            if let Some(dispatcher) = leave_fullscreen_dispatcher(&active) {
+                hyprland_dispatch(instance, dispatcher).await?;
+                left += 1;
+            }
+        }
+        Ok(left)
+    }
+
+    async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize> {
+        debug!("Moving Hyprland windows to workspace {}: {}", workspace, target);
+        let workspace = match workspace.parse::<i32>() {
+            Ok(id) => id.to_string(),
+            Err(_) => format!("name:{}", workspace),
+        };
+
+        let clients = self.clients(target).await?;
+        self.dispatch_clients(&clients, |address| {
+            format!("movetoworkspacesilent {},address:{}", workspace, address)
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn recorded_clients() -> Vec<serde_json::Value> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/hyprland-clients.json");
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
     #[test]
     fn test_hyprland_availability() {
-        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
-            assert!(HyprlandAdapter::is_available());
+        if let Some(instance) = session_socket_for(CompositorType::Hyprland) {
+            assert_eq!(HyprlandAdapter::is_available(), instance.join(".socket.sock").exists());
+        }
+    }
+
+    #[test]
+    fn test_leave_fullscreen_names_the_current_mode() {
+        let clients = recorded_clients();
+
+        assert_eq!(leave_fullscreen_dispatcher(&clients[0]), None);
+        assert_eq!(leave_fullscreen_dispatcher(&clients[1]), Some("fullscreen 0"));
+        assert_eq!(leave_fullscreen_dispatcher(&clients[2]), None);
+
+        let maximized = serde_json::json!({ "fullscreen": 1 });
+        assert_eq!(leave_fullscreen_dispatcher(&maximized), Some("fullscreen 1"));
+        let old_maximized = serde_json::json!({ "fullscreen": true, "fullscreenMode": 1 });
+        assert_eq!(leave_fullscreen_dispatcher(&old_maximized), Some("fullscreen 1"));
+    }
+
+    #[test]
+    fn test_is_fullscreen_handles_both_formats() {
+        assert!(is_fullscreen(&serde_json::json!({ "fullscreen": true })));
+        assert!(is_fullscreen(&serde_json::json!({ "fullscreen": 2 })));
+        assert!(!is_fullscreen(&serde_json::json!({ "fullscreen": 0 })));
+        assert!(!is_fullscreen(&serde_json::json!({})));
+    }
+
+    #[test]
+    fn test_client_info_from_recorded_clients() {
+        let clients = recorded_clients();
+        let windows: Vec<WindowInfo> = clients.iter().map(client_info).collect();
+
+        assert_eq!(
+            windows[0],
+            WindowInfo {
+                app_id: Some("firefox".to_string()),
+                title: Some("Homework - Mozilla Firefox".to_string()),
+                pid: Some(2411),
+                workspace: Some("1".to_string()),
+                geometry: Some(WindowGeometry { x: 10, y: 40, width: 1260, height: 1390 }),
+                state: WindowState::Tiled,
+            }
+        );
+
+        assert_eq!(windows[1].workspace.as_deref(), Some("games"));
+        assert_eq!(windows[1].state, WindowState::Fullscreen);
+
+        // Recorded from an older Hyprland that reports fullscreen as a bool
+        assert_eq!(windows[2].pid, Some(3301));
+        assert_eq!(windows[2].state, WindowState::Floating);
+    }
+
+    #[test]
+    fn test_hyprland_events_follow_focus_and_titles() {
+        let clients = recorded_clients();
+        let mut state = HyprlandEvents::default();
+
+        assert_eq!(
+            state.apply("activewindow>>firefox,Homework - Mozilla Firefox"),
+            Update::Events(Vec::new())
+        );
+        let update = state.apply("activewindowv2>>55d0c1a2b3c0");
+        assert_eq!(update, Update::Focused(0x55d0c1a2b3c0));
+
+        let client = client_at(&clients, 0x55d0c1a2b3c0);
+        let homework = client_info(client.unwrap());
+        assert_eq!(homework.pid, Some(2411));
+        assert_eq!(
+            state.resolve(update, client),
+            vec![WMEvent::WindowFocused(Some(homework.clone()))]
+        );
+
+        let news = WindowInfo { title: Some("News".to_string()), ..homework };
+        assert_eq!(
+            state.apply("windowtitlev2>>55d0c1a2b3c0,News"),
+            Update::Events(vec![WMEvent::WindowFocused(Some(news))])
+        );
+        assert_eq!(state.apply("windowtitlev2>>1234,Background"), Update::Events(Vec::new()));
+
+        assert_eq!(
+            state.apply("activewindow>>,"),
+            Update::Events(vec![WMEvent::WindowFocused(None)])
+        );
+        assert_eq!(state.apply("activewindowv2>>"), Update::Events(Vec::new()));
+    }
+
+    #[test]
+    fn test_hyprland_focus_falls_back_to_the_event_line() {
+        let mut state = HyprlandEvents::default();
+
+        state.apply("activewindow>>firefox,Home, sweet home");
+        let update = state.apply("activewindowv2>>0xdead");
+        assert_eq!(
+            state.resolve(update, None),
+            vec![WMEvent::WindowFocused(Some(event_window("firefox", "Home, sweet home", None)))]
+        );
+    }
+
+    #[test]
+    fn test_hyprland_window_and_workspace_events() {
+        let clients = recorded_clients();
+        let mut state = HyprlandEvents::default();
+
+        let update =
+            state.apply("openwindow>>55d0c1a30a80,1,org.pulseaudio.pavucontrol,Volume Control");
+        match state.resolve(update, client_at(&clients, 0x55d0c1a30a80)).as_slice() {
+            [WMEvent::WindowOpened(window)] => {
+                assert_eq!(window.pid, Some(3301));
+                assert_eq!(window.state, WindowState::Floating);
+            }
+            other => panic!("unexpected events: {:?}", other),
         }
+
+        let update = state.apply("openwindow>>55d0c1a2b3c0,2,foot,make, then test");
+        match state.resolve(update, None).as_slice() {
+            [WMEvent::WindowOpened(window)] => {
+                assert_eq!(window.app_id.as_deref(), Some("foot"));
+                assert_eq!(window.title.as_deref(), Some("make, then test"));
+                assert_eq!(window.workspace.as_deref(), Some("2"));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+        assert_eq!(
+            state.apply("closewindow>>0x1f"),
+            Update::Events(vec![WMEvent::WindowClosed(0x1f)])
+        );
+        assert_eq!(
+            state.apply("workspace>>web"),
+            Update::Events(vec![WMEvent::WorkspaceChanged("web".into())])
+        );
+        assert_eq!(state.apply("monitoradded>>DP-1"), Update::Events(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_hyprland_event_stream_reports_recorded_clients() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixListener;
+
+        let instance = tempfile::tempdir().unwrap();
+        let requests = UnixListener::bind(instance.path().join(".socket.sock")).unwrap();
+        let events = UnixListener::bind(instance.path().join(".socket2.sock")).unwrap();
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/hyprland-clients.json");
+        let reply = std::fs::read_to_string(path).unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = requests.accept().await {
+                let mut request = [0u8; 64];
+                let read = stream.read(&mut request).await.unwrap();
+                assert_eq!(&request[..read], b"j/clients");
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        tokio::spawn(async move {
+            let (mut stream, _) = events.accept().await.unwrap();
+            stream
+                .write_all(
+                    b"activewindow>>Minecraft,Minecraft 1.20.4\nactivewindowv2>>55d0c1a2f710\n",
+                )
+                .await
+                .unwrap();
+        });
+
+        let adapter = HyprlandAdapter::with_socket(instance.path().to_path_buf());
+        let mut stream = adapter.subscribe_to_events().await.unwrap();
+        assert_eq!(
+            stream.next().await,
+            Some(WMEvent::WindowFocused(Some(WindowInfo {
+                app_id: Some("Minecraft".to_string()),
+                title: Some("Minecraft 1.20.4".to_string()),
+                pid: Some(5120),
+                workspace: Some("games".to_string()),
+                geometry: Some(WindowGeometry { x: 0, y: 0, width: 2560, height: 1440 }),
+                state: WindowState::Fullscreen,
+            })))
+        );
+        assert_eq!(stream.next().await, None);
     }
 
     #[tokio::test]
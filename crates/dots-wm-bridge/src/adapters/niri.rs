@@ -1,94 +1,387 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
-use std::process::Command;
+use futures::{stream, StreamExt};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
-use crate::types::{WMCapabilities, WindowInfo, WindowState};
+use crate::detection::session_socket_for;
+use crate::ipc::{niri_event_stream, niri_request};
+use crate::types::{
+    CompositorType, WMCapabilities, WMEvent, WMEventStream, WindowGeometry, WindowInfo,
+    WindowState, WindowTarget,
+};
 use crate::WindowManagerAdapter;
 
-#[derive(Default)]
-pub struct NiriAdapter;
+pub struct NiriAdapter {
+    socket: Option<PathBuf>,
+}
+
+impl Default for NiriAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl NiriAdapter {
+    /// Adapter for the Niri instance running the current session
     pub fn new() -> Self {
-        Self
+        Self { socket: session_socket_for(CompositorType::Niri) }
+    }
+
+    /// Adapter for the Niri instance listening on `socket`
+    pub fn with_socket(socket: PathBuf) -> Self {
+        Self { socket: Some(socket) }
     }
 
     pub fn is_available() -> bool {
-        std::env::var("NIRI_SOCKET").is_ok() && which::which("niri").is_ok()
+        session_socket_for(CompositorType::Niri).is_some_and(|socket| socket.exists())
     }
-}
 
-#[async_trait]
-impl WindowManagerAdapter for NiriAdapter {
-    async fn get_focused_window(&self) -> Result<Option<WindowInfo>> {
-        let output =
-            Command::new("niri").arg("msg").arg("--json").arg("focused-window").output()?;
+    fn socket(&self) -> Result<&Path> {
+        self.socket.as_deref().context("No Niri socket found")
+    }
 
-        if !output.status.success() {
-            debug!("Niri command failed: {:?}", output.stderr);
-            return Ok(None);
+    /// IDs of the windows matching the target
+    async fn window_ids(&self, target: &WindowTarget) -> Result<Vec<u64>> {
+        let reply = niri_request(self.socket()?, &json!("Windows")).await?;
+
+        Ok(reply["Windows"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|window| target.matches(window["pid"].as_u64(), window["app_id"].as_str()))
+            .filter_map(|window| window["id"].as_u64())
+            .collect())
+    }
+
+    async fn act_on_windows(
+        &self,
+        target: &WindowTarget,
+        action: impl Fn(u64) -> serde_json::Value,
+    ) -> Result<usize> {
+        let ids = self.window_ids(target).await?;
+        for id in &ids {
+            niri_request(self.socket()?, &json!({ "Action": action(*id) })).await?;
         }
+        Ok(ids.len())
+    }
+
+    async fn workspace_names(&self) -> Result<HashMap<u64, String>> {
+        let reply = niri_request(self.socket()?, &json!("Workspaces")).await?;
+        Ok(workspace_names(&reply["Workspaces"]))
+    }
+}
+
+/// Workspace names by id; unnamed workspaces go by their index on the output
+fn workspace_names(workspaces: &serde_json::Value) -> HashMap<u64, String> {
+    workspaces
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|workspace| {
+            let name = match workspace["name"].as_str() {
+                Some(name) => name.to_string(),
+                None => workspace["idx"].as_u64()?.to_string(),
+            };
+            Some((workspace["id"].as_u64()?, name))
+        })
+        .collect()
+}
 
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&json_str) {
-            Ok(Some(WindowInfo {
-                app_id: value.get("app_id").and_then(|v| v.as_str()).map(String::from),
-                title: value.get("title").and_then(|v| v.as_str()).map(String::from),
-                pid: None,
-                workspace: None,
-                geometry: None,
-                state: WindowState::default(),
-            }))
+fn window_info(window: &serde_json::Value, workspaces: &HashMap<u64, String>) -> WindowInfo {
+    WindowInfo {
+        app_id: window.get("app_id").and_then(|v| v.as_str()).map(String::from),
+        title: window.get("title").and_then(|v| v.as_str()).map(String::from),
+        pid: window.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
+        workspace: window["workspace_id"].as_u64().and_then(|id| workspaces.get(&id)).cloned(),
+        geometry: window_geometry(&window["layout"]),
+        state: if window["is_floating"].as_bool() == Some(true) {
+            WindowState::Floating
         } else {
-            Ok(None)
-        }
+            WindowState::Tiled
+        },
     }
+}
 
-    async fn get_all_windows(&self) -> Result<Vec<WindowInfo>> {
-        let output = Command::new("niri").arg("msg").arg("--json").arg("windows").output()?;
+/// Niri only places tiles that are in view; scrolled-out windows have a size
+/// but no position, and older versions report no layout at all
+fn window_geometry(layout: &serde_json::Value) -> Option<WindowGeometry> {
+    let tile = layout["tile_pos_in_workspace_view"].as_array()?;
+    let offset = layout["window_offset_in_tile"].as_array();
+    let size = layout["window_size"].as_array()?;
 
-        if !output.status.success() {
-            return Ok(Vec::new());
-        }
+    let coordinate = |i: usize| {
+        tile.get(i)?
+            .as_f64()
+            .map(|pos| pos + offset.and_then(|o| o.get(i)).and_then(|v| v.as_f64()).unwrap_or(0.0))
+    };
+    Some(WindowGeometry {
+        x: coordinate(0)?.round() as i32,
+        y: coordinate(1)?.round() as i32,
+        width: size.first()?.as_u64()? as u32,
+        height: size.get(1)?.as_u64()? as u32,
+    })
+}
 
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        if let Ok(windows) = serde_json::from_str::<serde_json::Value>(&json_str) {
-            if let Some(windows_array) = windows.as_array() {
-                let mut result = Vec::new();
-                for window in windows_array {
-                    result.push(WindowInfo {
-                        app_id: window.get("app_id").and_then(|v| v.as_str()).map(String::from),
-                        title: window.get("title").and_then(|v| v.as_str()).map(String::from),
-                        pid: None,
-                        workspace: None,
-                        geometry: None,
-                        state: WindowState::default(),
-                    });
+/// Window and workspace state rebuilt from Niri's event stream, whose focus
+/// events only carry window ids
+#[derive(Default)]
+struct NiriEvents {
+    windows: HashMap<u64, WindowInfo>,
+    workspaces: HashMap<u64, String>,
+    focused: Option<u64>,
+}
+
+impl NiriEvents {
+    fn apply(&mut self, event: &serde_json::Value) -> Vec<WMEvent> {
+        let mut events = Vec::new();
+
+        if let Some(changed) = event.get("WindowsChanged") {
+            let windows = changed["windows"].as_array().cloned().unwrap_or_default();
+            self.windows = windows
+                .iter()
+                .filter_map(|window| {
+                    Some((window["id"].as_u64()?, window_info(window, &self.workspaces)))
+                })
+                .collect();
+            self.focused = windows
+                .iter()
+                .find(|window| window["is_focused"].as_bool() == Some(true))
+                .and_then(|window| window["id"].as_u64());
+            events.push(WMEvent::WindowFocused(self.focused_window()));
+        } else if let Some(changed) = event.get("WindowOpenedOrChanged") {
+            let window = &changed["window"];
+            let Some(id) = window["id"].as_u64() else {
+                return events;
+            };
+            let info = window_info(window, &self.workspaces);
+            let previous = self.windows.insert(id, info.clone());
+            if previous.is_none() {
+                events.push(WMEvent::WindowOpened(info.clone()));
+            }
+            if window["is_focused"].as_bool() == Some(true)
+                && (self.focused != Some(id) || previous.as_ref() != Some(&info))
+            {
+                self.focused = Some(id);
+                events.push(WMEvent::WindowFocused(Some(info)));
+            }
+        } else if let Some(closed) = event.get("WindowClosed") {
+            if let Some(id) = closed["id"].as_u64() {
+                self.windows.remove(&id);
+                events.push(WMEvent::WindowClosed(id));
+                if self.focused == Some(id) {
+                    self.focused = None;
+                    events.push(WMEvent::WindowFocused(None));
+                }
+            }
+        } else if let Some(changed) = event.get("WindowFocusChanged") {
+            self.focused = changed["id"].as_u64();
+            events.push(WMEvent::WindowFocused(self.focused_window()));
+        } else if let Some(changed) = event.get("WorkspacesChanged") {
+            self.workspaces = workspace_names(&changed["workspaces"]);
+        } else if let Some(activated) = event.get("WorkspaceActivated") {
+            if activated["focused"].as_bool() == Some(true) {
+                if let Some(name) = activated["id"].as_u64().and_then(|id| self.workspaces.get(&id))
+                {
+                    events.push(WMEvent::WorkspaceChanged(name.clone()));
                 }
-                return Ok(result);
             }
         }
 
-        Ok(Vec::new())
+        events
     }
 
-    async fn subscribe_to_events(&self) -> Result<()> {
-        Ok(())
+    fn focused_window(&self) -> Option<WindowInfo> {
+        self.focused.and_then(|id| self.windows.get(&id).cloned())
+    }
+}
+
+#[async_trait]
+impl WindowManagerAdapter for NiriAdapter {
+    async fn get_focused_window(&self) -> Result<Option<WindowInfo>> {
+        let reply = niri_request(self.socket()?, &json!("FocusedWindow")).await?;
+        let window = &reply["FocusedWindow"];
+        if window.is_null() {
+            debug!("No focused window");
+            return Ok(None);
+        }
+
+        let workspaces = self.workspace_names().await?;
+        Ok(Some(window_info(window, &workspaces)))
+    }
+
+    async fn get_all_windows(&self) -> Result<Vec<WindowInfo>> {
+        let reply = niri_request(self.socket()?, &json!("Windows")).await?;
+        let workspaces = self.workspace_names().await?;
+
+        Ok(reply["Windows"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|window| window_info(window, &workspaces))
+            .collect())
+    }
+
+    async fn subscribe_to_events(&self) -> Result<WMEventStream> {
+        let events = niri_event_stream(self.socket()?).await?;
+        let mut state = NiriEvents::default();
+        Ok(Box::pin(events.flat_map(move |event| stream::iter(state.apply(&event)))))
     }
 
     fn get_capabilities(&self) -> WMCapabilities {
         WMCapabilities {
             can_get_focused_window: true,
             can_get_all_windows: true,
-            can_subscribe_to_events: false,
-            can_control_windows: false,
-            supports_workspaces: false,
-            supports_window_geometry: false,
+            can_subscribe_to_events: true,
+            can_close_windows: true,
+            // Niri has no minimized state, and only a fullscreen toggle
+            // without reporting which windows are fullscreen
+            can_minimize_windows: false,
+            can_exit_fullscreen: false,
+            can_move_to_workspace: true,
+            supports_workspaces: true,
+            supports_window_geometry: true,
         }
     }
 
     fn get_name(&self) -> &'static str {
         "Niri"
     }
+
+    async fn close_windows(&self, target: &WindowTarget) -> Result<usize> {
+        debug!("Closing Niri windows: {}", target);
+        self.act_on_windows(target, |id| json!({ "CloseWindow": { "id": id } })).await
+    }
+
+    async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize> {
+        bail!("Niri has no minimized state, cannot minimize {}", target)
+    }
+
+    async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize> {
+        // Niri only offers a fullscreen toggle and does not report the current state
+        bail!("Niri cannot leave fullscreen without toggling it, refusing for {}", target)
+    }
+
+    async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize> {
+        debug!("Moving Niri windows to workspace {}: {}", workspace, target);
+
+        let reference = match workspace.parse::<u8>() {
+            Ok(index) => json!({ "Index": index }),
+            Err(_) => json!({ "Name": workspace }),
+        };
+
+        self.act_on_windows(target, |id| {
+            json!({
+                "MoveWindowToWorkspace": {
+                    "window_id": id,
+                    "reference": reference,
+                    "focus": false,
+                }
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn focused(title: &str) -> WMEvent {
+        WMEvent::WindowFocused(Some(WindowInfo {
+            app_id: Some("firefox".to_string()),
+            title: Some(title.to_string()),
+            pid: None,
+            workspace: None,
+            geometry: None,
+            state: WindowState::Tiled,
+        }))
+    }
+
+    /// Recorded `niri msg --json` output
+    fn fixture(name: &str) -> serde_json::Value {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_niri_window_info_from_recorded_windows() {
+        let workspaces = workspace_names(&fixture("niri-workspaces.json"));
+        let windows: Vec<WindowInfo> = fixture("niri-windows.json")
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|window| window_info(window, &workspaces))
+            .collect();
+
+        assert_eq!(
+            windows[0],
+            WindowInfo {
+                app_id: Some("firefox".to_string()),
+                title: Some("Homework - Mozilla Firefox".to_string()),
+                pid: Some(2411),
+                workspace: Some("web".to_string()),
+                geometry: Some(WindowGeometry { x: 16, y: 16, width: 1264, height: 1408 }),
+                state: WindowState::Tiled,
+            }
+        );
+
+        assert_eq!(windows[1].state, WindowState::Floating);
+        assert_eq!(
+            windows[1].geometry,
+            Some(WindowGeometry { x: 1903, y: 1102, width: 476, height: 268 })
+        );
+
+        // Unnamed workspace, scrolled out of view
+        assert_eq!(windows[2].pid, Some(3090));
+        assert_eq!(windows[2].workspace.as_deref(), Some("2"));
+        assert_eq!(windows[2].geometry, None);
+    }
+
+    #[test]
+    fn test_niri_events_track_focus_by_window_id() {
+        let mut state = NiriEvents::default();
+
+        let initial = json!({ "WindowsChanged": { "windows": [
+            { "id": 1, "app_id": "firefox", "title": "Home", "is_focused": true },
+            { "id": 2, "app_id": "foot", "title": "shell", "is_focused": false }
+        ]}});
+        assert_eq!(state.apply(&initial), vec![focused("Home")]);
+
+        let retitled = json!({ "WindowOpenedOrChanged": { "window":
+            { "id": 1, "app_id": "firefox", "title": "News", "is_focused": true }
+        }});
+        assert_eq!(state.apply(&retitled), vec![focused("News")]);
+
+        let to_terminal = json!({ "WindowFocusChanged": { "id": 2 } });
+        match state.apply(&to_terminal).as_slice() {
+            [WMEvent::WindowFocused(Some(window))] => {
+                assert_eq!(window.app_id.as_deref(), Some("foot"))
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        let closed = json!({ "WindowClosed": { "id": 2 } });
+        assert_eq!(
+            state.apply(&closed),
+            vec![WMEvent::WindowClosed(2), WMEvent::WindowFocused(None)]
+        );
+    }
+
+    #[test]
+    fn test_niri_workspace_activation_uses_name_or_index() {
+        let mut state = NiriEvents::default();
+        state.apply(&json!({ "WorkspacesChanged": { "workspaces": [
+            { "id": 7, "idx": 1, "name": "web" },
+            { "id": 8, "idx": 2, "name": null }
+        ]}}));
+
+        let activated = json!({ "WorkspaceActivated": { "id": 8, "focused": true } });
+        assert_eq!(state.apply(&activated), vec![WMEvent::WorkspaceChanged("2".to_string())]);
+
+        let unfocused = json!({ "WorkspaceActivated": { "id": 7, "focused": false } });
+        assert!(state.apply(&unfocused).is_empty());
+    }
 }
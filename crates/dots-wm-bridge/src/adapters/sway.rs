@@ -1,20 +1,72 @@
-use crate::types::{WMCapabilities, WindowInfo, WindowState};
+use crate::detection::session_socket_for;
+use crate::ipc::{
+    sway_message, sway_run_command, sway_subscribe, SWAY_EVENT_WINDOW, SWAY_EVENT_WORKSPACE,
+    SWAY_GET_TREE,
+};
+use crate::types::{
+    CompositorType, WMCapabilities, WMEvent, WMEventStream, WindowGeometry, WindowInfo,
+    WindowState, WindowTarget,
+};
 use crate::WindowManagerAdapter;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::process::Command;
+use futures::{stream, StreamExt};
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
-#[derive(Default)]
-pub struct SwayAdapter;
+pub struct SwayAdapter {
+    socket: Option<PathBuf>,
+}
+
+impl Default for SwayAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl SwayAdapter {
+    /// Adapter for the Sway instance running the current session
     pub fn new() -> Self {
-        Self
+        Self { socket: session_socket_for(CompositorType::Sway) }
+    }
+
+    /// Adapter for the Sway instance listening on `socket`
+    pub fn with_socket(socket: PathBuf) -> Self {
+        Self { socket: Some(socket) }
     }
 
     pub fn is_available() -> bool {
-        std::env::var("SWAYSOCK").is_ok() && which::which("swaymsg").is_ok()
+        session_socket_for(CompositorType::Sway).is_some_and(|socket| socket.exists())
+    }
+
+    fn socket(&self) -> Result<&Path> {
+        self.socket.as_deref().context("No Sway socket found")
+    }
+
+    /// Run `command` once for every window matching the target that passes `filter`
+    async fn command_windows(
+        &self,
+        target: &WindowTarget,
+        filter: impl Fn(&serde_json::Value) -> bool,
+        command: &str,
+    ) -> Result<usize> {
+        let tree = sway_message(self.socket()?, SWAY_GET_TREE, "").await?;
+
+        let mut nodes = Vec::new();
+        collect_window_nodes(&tree, None, &mut nodes);
+
+        let con_ids: Vec<u64> = nodes
+            .into_iter()
+            .map(|(node, _)| node)
+            .filter(|node| target.matches(node["pid"].as_u64(), node["app_id"].as_str()))
+            .filter(|node| filter(node))
+            .filter_map(|node| node["id"].as_u64())
+            .collect();
+
+        for con_id in &con_ids {
+            sway_run_command(self.socket()?, &format!("[con_id={}] {}", con_id, command)).await?;
+        }
+        Ok(con_ids.len())
     }
 }
 
@@ -23,123 +75,178 @@ impl WindowManagerAdapter for SwayAdapter {
     async fn get_focused_window(&self) -> Result<Option<WindowInfo>> {
         debug!("Getting focused window from Sway");
 
-        let output = Command::new("swaymsg").arg("-t").arg("get_tree").output()?;
+        let tree = sway_message(self.socket()?, SWAY_GET_TREE, "").await?;
+        let mut nodes = Vec::new();
+        collect_window_nodes(&tree, None, &mut nodes);
 
-        if !output.status.success() {
-            debug!("swaymsg get_tree failed");
-            return Ok(None);
-        }
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        let value: serde_json::Value = serde_json::from_str(&json_str)?;
-
-        if let Some(focused) = find_focused_node(&value) {
-            let window_info = WindowInfo {
-                app_id: focused.get("app_id").and_then(|v| v.as_str()).map(String::from),
-                title: focused.get("name").and_then(|v| v.as_str()).map(String::from),
-                pid: focused.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
-                workspace: focused.get("workspace").and_then(|v| v.as_str()).map(String::from),
-                geometry: None,
-                state: WindowState::default(),
-            };
+        let focused = nodes
+            .into_iter()
+            .find(|(node, _)| node["focused"].as_bool() == Some(true))
+            .map(|(node, workspace)| node_window_info(node, workspace));
 
-            debug!("Found focused window: {:?}", window_info);
-            Ok(Some(window_info))
-        } else {
-            debug!("No focused window found");
-            Ok(None)
-        }
+        debug!("Found focused window: {:?}", focused);
+        Ok(focused)
     }
 
     async fn get_all_windows(&self) -> Result<Vec<WindowInfo>> {
         debug!("Getting all windows from Sway");
 
-        let output = Command::new("swaymsg").arg("-t").arg("get_tree").output()?;
-
-        if !output.status.success() {
-            debug!("swaymsg get_tree failed");
-            return Ok(vec![]);
-        }
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        let value: serde_json::Value = serde_json::from_str(&json_str)?;
+        let tree = sway_message(self.socket()?, SWAY_GET_TREE, "").await?;
+        let mut nodes = Vec::new();
+        collect_window_nodes(&tree, None, &mut nodes);
 
-        let mut windows = Vec::new();
-        collect_all_windows(&value, &mut windows);
+        let windows: Vec<WindowInfo> =
+            nodes.into_iter().map(|(node, workspace)| node_window_info(node, workspace)).collect();
 
         debug!("Found {} windows", windows.len());
         Ok(windows)
     }
 
-    async fn subscribe_to_events(&self) -> Result<()> {
-        debug!("Sway event subscription not yet implemented");
-        Ok(())
+    async fn subscribe_to_events(&self) -> Result<WMEventStream> {
+        let events = sway_subscribe(self.socket()?, &["window", "workspace"]).await?;
+        Ok(Box::pin(events.flat_map(|(event_type, payload)| {
+            stream::iter(parse_sway_event(event_type, &payload))
+        })))
     }
 
     fn get_capabilities(&self) -> WMCapabilities {
         WMCapabilities {
             can_get_focused_window: true,
             can_get_all_windows: true,
-            can_subscribe_to_events: false,
-            can_control_windows: true,
+            can_subscribe_to_events: true,
+            can_close_windows: true,
+            can_minimize_windows: true,
+            can_exit_fullscreen: true,
+            can_move_to_workspace: true,
             supports_workspaces: true,
-            supports_window_geometry: false,
+            supports_window_geometry: true,
         }
     }
 
     fn get_name(&self) -> &'static str {
         "Sway"
     }
+
+    async fn close_windows(&self, target: &WindowTarget) -> Result<usize> {
+        debug!("Closing Sway windows: {}", target);
+        self.command_windows(target, |_| true, "kill").await
+    }
+
+    async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize> {
+        // Sway has no minimized state; the scratchpad is the closest equivalent
+        debug!("Moving Sway windows to the scratchpad: {}", target);
+        self.command_windows(target, |_| true, "move scratchpad").await
+    }
+
+    async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize> {
+        debug!("Leaving fullscreen for Sway windows: {}", target);
+        self.command_windows(
+            target,
+            |node| node["fullscreen_mode"].as_u64().unwrap_or(0) != 0,
+            "fullscreen disable",
+        )
+        .await
+    }
+
+    async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize> {
+        debug!("Moving Sway windows to workspace {}: {}", workspace, target);
+        let command = format!("move container to workspace \"{}\"", workspace.replace('"', "\\\""));
+        self.command_windows(target, |_| true, &command).await
+    }
 }
 
-fn find_focused_node(node: &serde_json::Value) -> Option<&serde_json::Value> {
-    if let Some(focused) = node.get("focused") {
-        if focused.as_bool() == Some(true) {
-            return Some(node);
-        }
+/// Window info for a tree node; Xwayland windows have no app ID, only a class
+fn node_window_info(node: &serde_json::Value, workspace: Option<&str>) -> WindowInfo {
+    let state = if node["fullscreen_mode"].as_u64().unwrap_or(0) != 0 {
+        WindowState::Fullscreen
+    } else if node["type"].as_str() == Some("floating_con") {
+        WindowState::Floating
+    } else {
+        WindowState::Tiled
+    };
+
+    WindowInfo {
+        app_id: node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())
+            .map(String::from),
+        title: node.get("name").and_then(|v| v.as_str()).map(String::from),
+        pid: node.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
+        workspace: workspace.map(String::from),
+        geometry: rect_geometry(&node["rect"]),
+        state,
     }
+}
 
-    if let Some(nodes) = node.get("nodes").and_then(|n| n.as_array()) {
-        for child in nodes {
-            if let Some(found) = find_focused_node(child) {
-                return Some(found);
+fn rect_geometry(rect: &serde_json::Value) -> Option<WindowGeometry> {
+    Some(WindowGeometry {
+        x: rect["x"].as_i64()? as i32,
+        y: rect["y"].as_i64()? as i32,
+        width: rect["width"].as_u64()? as u32,
+        height: rect["height"].as_u64()? as u32,
+    })
+}
+
+/// Translate one `window` or `workspace` event from a Sway subscription
+fn parse_sway_event(event_type: u32, payload: &serde_json::Value) -> Vec<WMEvent> {
+    let change = payload["change"].as_str().unwrap_or_default();
+
+    match event_type {
+        SWAY_EVENT_WINDOW => {
+            // Event containers do not say which workspace they are on
+            let container = &payload["container"];
+            match change {
+                "focus" => vec![WMEvent::WindowFocused(Some(node_window_info(container, None)))],
+                // A retitled focused window is a new thing to account time to
+                "title" if container["focused"].as_bool() == Some(true) => {
+                    vec![WMEvent::WindowFocused(Some(node_window_info(container, None)))]
+                }
+                "new" => vec![WMEvent::WindowOpened(node_window_info(container, None))],
+                "close" => {
+                    container["id"].as_u64().map(WMEvent::WindowClosed).into_iter().collect()
+                }
+                _ => Vec::new(),
             }
         }
-    }
+        SWAY_EVENT_WORKSPACE if change == "focus" => {
+            let current = &payload["current"];
+            let Some(name) = current["name"].as_str() else {
+                return Vec::new();
+            };
 
-    if let Some(floating) = node.get("floating_nodes").and_then(|n| n.as_array()) {
-        for child in floating {
-            if let Some(found) = find_focused_node(child) {
-                return Some(found);
+            let mut events = vec![WMEvent::WorkspaceChanged(name.to_string())];
+            // Switching to an empty workspace focuses no window at all
+            let mut windows = Vec::new();
+            collect_window_nodes(current, Some(name), &mut windows);
+            if windows.is_empty() {
+                events.push(WMEvent::WindowFocused(None));
             }
+            events
         }
+        _ => Vec::new(),
     }
-
-    None
 }
 
-fn collect_all_windows(node: &serde_json::Value, windows: &mut Vec<WindowInfo>) {
-    if let Some(app_id) = node.get("app_id").and_then(|v| v.as_str()) {
-        let window_info = WindowInfo {
-            app_id: Some(app_id.to_string()),
-            title: node.get("name").and_then(|v| v.as_str()).map(String::from),
-            pid: node.get("pid").and_then(|v| v.as_u64()).map(|p| p as u32),
-            workspace: node.get("workspace").and_then(|v| v.as_str()).map(String::from),
-            geometry: None,
-            state: WindowState::default(),
-        };
-        windows.push(window_info);
-    }
-
-    if let Some(nodes) = node.get("nodes").and_then(|n| n.as_array()) {
-        for child in nodes {
-            collect_all_windows(child, windows);
-        }
+/// Collect the leaf nodes of the tree that are application windows, each with
+/// the name of the workspace it sits on
+fn collect_window_nodes<'a>(
+    node: &'a serde_json::Value,
+    workspace: Option<&'a str>,
+    windows: &mut Vec<(&'a serde_json::Value, Option<&'a str>)>,
+) {
+    if node.get("pid").and_then(|v| v.as_u64()).is_some() {
+        windows.push((node, workspace));
     }
 
-    if let Some(floating) = node.get("floating_nodes").and_then(|n| n.as_array()) {
-        for child in floating {
-            collect_all_windows(child, windows);
+    let workspace = match node["type"].as_str() {
+        Some("workspace") => node["name"].as_str(),
+        _ => workspace,
+    };
+    for key in ["nodes", "floating_nodes"] {
+        if let Some(children) = node.get(key).and_then(|n| n.as_array()) {
+            for child in children {
+                collect_window_nodes(child, workspace, windows);
+            }
         }
     }
 }
@@ -150,11 +257,113 @@ mod tests {
 
     #[test]
     fn test_sway_availability() {
-        if std::env::var("SWAYSOCK").is_ok() {
-            assert!(SwayAdapter::is_available());
+        if let Ok(socket) = std::env::var("SWAYSOCK") {
+            assert_eq!(SwayAdapter::is_available(), Path::new(&socket).exists());
         }
     }
 
+    #[test]
+    fn test_collect_window_nodes() {
+        let tree = serde_json::json!({
+            "id": 1,
+            "nodes": [{
+                "id": 2,
+                "nodes": [{ "id": 3, "pid": 100, "app_id": "firefox" }],
+                "floating_nodes": [{ "id": 4, "pid": 200, "app_id": null }]
+            }]
+        });
+
+        let mut nodes = Vec::new();
+        collect_window_nodes(&tree, None, &mut nodes);
+
+        let ids: Vec<u64> = nodes.iter().filter_map(|(n, _)| n["id"].as_u64()).collect();
+        assert_eq!(ids, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_sway_window_info_from_recorded_tree() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sway-tree.json");
+        let tree: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        let mut nodes = Vec::new();
+        collect_window_nodes(&tree, None, &mut nodes);
+        let windows: Vec<WindowInfo> =
+            nodes.into_iter().map(|(node, workspace)| node_window_info(node, workspace)).collect();
+        assert_eq!(windows.len(), 4);
+
+        assert_eq!(
+            windows[0],
+            WindowInfo {
+                app_id: Some("firefox".to_string()),
+                title: Some("Homework - Mozilla Firefox".to_string()),
+                pid: Some(2411),
+                workspace: Some("1".to_string()),
+                geometry: Some(WindowGeometry { x: 0, y: 0, width: 1280, height: 1440 }),
+                state: WindowState::Tiled,
+            }
+        );
+
+        // Xwayland window identified by its class
+        assert_eq!(windows[1].app_id.as_deref(), Some("Minecraft"));
+        assert_eq!(windows[1].state, WindowState::Fullscreen);
+
+        assert_eq!(windows[2].app_id.as_deref(), Some("pavucontrol"));
+        assert_eq!(windows[2].state, WindowState::Floating);
+        assert_eq!(
+            windows[2].geometry,
+            Some(WindowGeometry { x: 930, y: 470, width: 700, height: 500 })
+        );
+
+        assert_eq!(windows[3].pid, Some(4002));
+        assert_eq!(windows[3].workspace.as_deref(), Some("chat"));
+    }
+
+    #[test]
+    fn test_parse_sway_window_events() {
+        let focus = serde_json::json!({
+            "change": "focus",
+            "container": { "id": 5, "pid": 100, "app_id": "firefox", "name": "Home", "focused": true }
+        });
+        match parse_sway_event(SWAY_EVENT_WINDOW, &focus).as_slice() {
+            [WMEvent::WindowFocused(Some(window))] => {
+                assert_eq!(window.app_id.as_deref(), Some("firefox"));
+                assert_eq!(window.pid, Some(100));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        let background_title = serde_json::json!({
+            "change": "title",
+            "container": { "id": 6, "app_id": "foot", "name": "make", "focused": false }
+        });
+        assert!(parse_sway_event(SWAY_EVENT_WINDOW, &background_title).is_empty());
+
+        let close = serde_json::json!({ "change": "close", "container": { "id": 5 } });
+        assert_eq!(parse_sway_event(SWAY_EVENT_WINDOW, &close), vec![WMEvent::WindowClosed(5)]);
+    }
+
+    #[test]
+    fn test_parse_sway_workspace_focus() {
+        let empty = serde_json::json!({
+            "change": "focus",
+            "current": { "name": "3", "nodes": [], "floating_nodes": [] }
+        });
+        assert_eq!(
+            parse_sway_event(SWAY_EVENT_WORKSPACE, &empty),
+            vec![WMEvent::WorkspaceChanged("3".to_string()), WMEvent::WindowFocused(None)]
+        );
+
+        let occupied = serde_json::json!({
+            "change": "focus",
+            "current": { "name": "web", "nodes": [{ "id": 3, "pid": 100, "app_id": "firefox" }] }
+        });
+        assert_eq!(
+            parse_sway_event(SWAY_EVENT_WORKSPACE, &occupied),
+            vec![WMEvent::WorkspaceChanged("web".to_string())]
+        );
+    }
+
     #[tokio::test]
     async fn test_sway_adapter() {
         if !SwayAdapter::is_available() {
@@ -1,33 +1,190 @@
+//! Finding a running compositor's IPC socket.
+//!
+//! Inside a session the compositor exports its socket through the environment.
+//! The daemon runs as root outside any session, so for a given user it looks
+//! for the sockets in that user's runtime directory instead.
+
+use std::{
+    env, fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
 use crate::types::CompositorType;
-use std::env;
+
+/// IPC endpoint of a running compositor
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositorSocket {
+    pub compositor: CompositorType,
+    /// The socket for Niri and Sway; for Hyprland the instance directory
+    /// holding its command and event sockets
+    pub path: PathBuf,
+}
 
 pub fn detect_compositor() -> CompositorType {
-    if env::var("NIRI_SOCKET").is_ok() {
-        return CompositorType::Niri;
+    session_socket().map(|socket| socket.compositor).unwrap_or(CompositorType::Unknown)
+}
+
+/// Compositor of the current session: what the environment names, otherwise
+/// whatever is listening in the caller's runtime directory
+pub fn session_socket() -> Option<CompositorSocket> {
+    env_socket().or_else(|| find_compositor_sockets(&runtime_dir()).into_iter().next())
+}
+
+/// The current session's socket for one compositor, if it runs one
+pub fn session_socket_for(compositor: CompositorType) -> Option<PathBuf> {
+    env_socket()
+        .into_iter()
+        .chain(find_compositor_sockets(&runtime_dir()))
+        .find(|socket| socket.compositor == compositor)
+        .map(|socket| socket.path)
+}
+
+/// Compositor running the session of `uid`, found under `/run/user/<uid>`
+pub fn user_socket(uid: u32) -> Option<CompositorSocket> {
+    find_compositor_sockets(&Path::new("/run/user").join(uid.to_string())).into_iter().next()
+}
+
+fn env_socket() -> Option<CompositorSocket> {
+    if let Ok(path) = env::var("NIRI_SOCKET") {
+        return Some(CompositorSocket { compositor: CompositorType::Niri, path: path.into() });
     }
 
-    if env::var("SWAYSOCK").is_ok() {
-        return CompositorType::Sway;
+    if let Ok(path) = env::var("SWAYSOCK") {
+        return Some(CompositorSocket { compositor: CompositorType::Sway, path: path.into() });
     }
 
-    if env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
-        return CompositorType::Hyprland;
+    if let Ok(signature) = env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+        // Hyprland moved its sockets from /tmp into XDG_RUNTIME_DIR in 0.40
+        let path = env::var("XDG_RUNTIME_DIR")
+            .map(|dir| PathBuf::from(dir).join("hypr").join(&signature))
+            .ok()
+            .filter(|path| path.exists())
+            .unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(&signature));
+        return Some(CompositorSocket { compositor: CompositorType::Hyprland, path });
     }
 
-    CompositorType::Unknown
+    None
 }
 
-pub fn compositor_command_exists(compositor: CompositorType) -> bool {
-    match compositor {
-        CompositorType::Niri => which::which("niri").is_ok(),
-        CompositorType::Sway => which::which("swaymsg").is_ok(),
-        CompositorType::Hyprland => which::which("hyprctl").is_ok(),
-        CompositorType::Unknown => false,
+/// `XDG_RUNTIME_DIR`, or `/run/user/<uid>` of this process when it is unset
+fn runtime_dir() -> PathBuf {
+    if let Ok(dir) = env::var("XDG_RUNTIME_DIR") {
+        return dir.into();
+    }
+
+    let uid = fs::metadata("/proc/self").map(|metadata| metadata.uid()).unwrap_or(0);
+    Path::new("/run/user").join(uid.to_string())
+}
+
+/// Live compositor sockets in a runtime directory, most recently created first.
+///
+/// Niri names its socket `niri.<display>.<pid>.sock`, Sway
+/// `sway-ipc.<uid>.<pid>.sock`, and Hyprland keeps `hypr/<signature>/` with a
+/// `hyprland.lock` holding its pid. Sockets left behind by a compositor that
+/// is no longer running are skipped.
+pub fn find_compositor_sockets(runtime_dir: &Path) -> Vec<CompositorSocket> {
+    let mut found: Vec<(CompositorSocket, SystemTime)> = Vec::new();
+
+    for entry in fs::read_dir(runtime_dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let compositor = if name.starts_with("niri.") && name.ends_with(".sock") {
+            CompositorType::Niri
+        } else if name.starts_with("sway-ipc.") && name.ends_with(".sock") {
+            CompositorType::Sway
+        } else {
+            continue;
+        };
+
+        let pid = name.trim_end_matches(".sock").rsplit('.').next().and_then(|p| p.parse().ok());
+        if pid.is_some_and(|pid| !process_alive(pid)) {
+            continue;
+        }
+        found.push((CompositorSocket { compositor, path: entry.path() }, modified(&entry.path())));
+    }
+
+    for entry in fs::read_dir(runtime_dir.join("hypr")).into_iter().flatten().flatten() {
+        let instance = entry.path();
+        if !instance.join(".socket.sock").exists() {
+            continue;
+        }
+
+        let pid = fs::read_to_string(instance.join("hyprland.lock"))
+            .ok()
+            .and_then(|lock| lock.lines().next()?.trim().parse().ok());
+        if pid.is_some_and(|pid| !process_alive(pid)) {
+            continue;
+        }
+        let created = modified(&instance.join(".socket.sock"));
+        found.push((
+            CompositorSocket { compositor: CompositorType::Hyprland, path: instance },
+            created,
+        ));
     }
+
+    found.sort_by_key(|(_, created)| std::cmp::Reverse(*created));
+    found.into_iter().map(|(socket, _)| socket).collect()
+}
+
+fn process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
 }
 
-pub fn get_compositor_info() -> (CompositorType, bool) {
-    let detected = detect_compositor();
-    let has_command = compositor_command_exists(detected);
-    (detected, has_command)
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Above the largest pid the kernel hands out
+    const DEAD_PID: u32 = 4_194_304;
+
+    #[test]
+    fn test_find_compositor_sockets_skips_dead_compositors() {
+        let dir = tempfile::tempdir().unwrap();
+        let alive = std::process::id();
+
+        fs::write(dir.path().join(format!("niri.wayland-1.{}.sock", DEAD_PID)), "").unwrap();
+        fs::write(dir.path().join(format!("sway-ipc.1000.{}.sock", alive)), "").unwrap();
+        fs::write(dir.path().join("wayland-1"), "").unwrap();
+
+        let sockets = find_compositor_sockets(dir.path());
+        assert_eq!(
+            sockets,
+            vec![CompositorSocket {
+                compositor: CompositorType::Sway,
+                path: dir.path().join(format!("sway-ipc.1000.{}.sock", alive)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_hyprland_instance_by_lock_file() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let live = dir.path().join("hypr").join("live_1700000000_1234");
+        fs::create_dir_all(&live).unwrap();
+        fs::write(live.join(".socket.sock"), "").unwrap();
+        fs::write(live.join("hyprland.lock"), format!("{}\nwayland-1\n", std::process::id()))
+            .unwrap();
+
+        let stale = dir.path().join("hypr").join("stale_1600000000_5678");
+        fs::create_dir_all(&stale).unwrap();
+        fs::write(stale.join(".socket.sock"), "").unwrap();
+        fs::write(stale.join("hyprland.lock"), format!("{}\nwayland-0\n", DEAD_PID)).unwrap();
+
+        let sockets = find_compositor_sockets(dir.path());
+        assert_eq!(
+            sockets,
+            vec![CompositorSocket { compositor: CompositorType::Hyprland, path: live }]
+        );
+    }
+
+    #[test]
+    fn test_missing_runtime_dir_has_no_sockets() {
+        assert!(find_compositor_sockets(Path::new("/nonexistent/run/user/1000")).is_empty());
+    }
 }
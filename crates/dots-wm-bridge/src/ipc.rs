@@ -0,0 +1,267 @@
+//! Direct clients for each compositor's IPC socket.
+//!
+//! Window queries and control talk to the compositor over its socket instead
+//! of spawning `niri msg`, `swaymsg` or `hyprctl`, so they keep working when
+//! the CLI tools are missing from the daemon's `PATH`. The event streams keep a
+//! connection open and yield whatever the compositor pushes until it closes.
+//! Sockets are located by [`crate::detection`].
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use futures::{stream, Stream};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+};
+use tracing::debug;
+
+/// Sway/i3 `RUN_COMMAND` message type
+pub const SWAY_RUN_COMMAND: u32 = 0;
+/// Sway/i3 `SUBSCRIBE` message type
+pub const SWAY_SUBSCRIBE: u32 = 2;
+/// Sway/i3 `GET_TREE` message type
+pub const SWAY_GET_TREE: u32 = 4;
+/// Event message types have the high bit set
+pub const SWAY_EVENT_WORKSPACE: u32 = 0x8000_0000;
+pub const SWAY_EVENT_WINDOW: u32 = 0x8000_0003;
+
+const SWAY_MAGIC: &[u8; 6] = b"i3-ipc";
+const SWAY_HEADER_LEN: usize = SWAY_MAGIC.len() + 8;
+
+/// Send one request to Niri and return the `Ok` part of its reply.
+pub async fn niri_request(socket: &Path, request: &serde_json::Value) -> Result<serde_json::Value> {
+    let mut stream = UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Failed to connect to Niri socket {}", socket.display()))?;
+
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    stream.write_all(&line).await?;
+    stream.shutdown().await?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).await?;
+    parse_niri_reply(&reply)
+}
+
+fn parse_niri_reply(reply: &str) -> Result<serde_json::Value> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(reply).context("Failed to parse Niri reply")?;
+
+    if let Some(error) = value.get("Err") {
+        return Err(anyhow!("Niri request failed: {}", error.as_str().unwrap_or_default()));
+    }
+    value.get_mut("Ok").map(serde_json::Value::take).ok_or_else(|| anyhow!("Malformed Niri reply"))
+}
+
+/// Ask Niri for its event stream and yield each event as it arrives
+pub async fn niri_event_stream(socket: &Path) -> Result<impl Stream<Item = serde_json::Value>> {
+    let mut stream = UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Failed to connect to Niri socket {}", socket.display()))?;
+
+    stream.write_all(b"\"EventStream\"\n").await?;
+    stream.shutdown().await?;
+
+    let mut reader = BufReader::new(stream);
+    let mut reply = String::new();
+    reader.read_line(&mut reply).await?;
+    parse_niri_reply(&reply)?;
+
+    Ok(json_lines(reader))
+}
+
+/// Each line of the reader parsed as JSON, until it closes or sends garbage
+fn json_lines(reader: BufReader<UnixStream>) -> impl Stream<Item = serde_json::Value> {
+    stream::unfold(reader.lines(), |mut lines| async move {
+        match lines.next_line().await {
+            Ok(Some(line)) => match serde_json::from_str(&line) {
+                Ok(value) => Some((value, lines)),
+                Err(e) => {
+                    debug!("Unparseable compositor event, closing stream: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                debug!("Compositor event stream failed: {}", e);
+                None
+            }
+        }
+    })
+}
+
+/// Send one message to Sway and return its JSON reply.
+pub async fn sway_message(
+    socket: &Path,
+    message_type: u32,
+    payload: &str,
+) -> Result<serde_json::Value> {
+    let mut stream = sway_connect(socket).await?;
+    stream.write_all(&encode_sway_message(message_type, payload)).await?;
+
+    let (_, reply) = read_sway_message(&mut stream).await?;
+    Ok(reply)
+}
+
+/// Subscribe to Sway events (`"window"`, `"workspace"`, ...) and yield each
+/// as its event type and JSON body
+pub async fn sway_subscribe(
+    socket: &Path,
+    events: &[&str],
+) -> Result<impl Stream<Item = (u32, serde_json::Value)>> {
+    let mut stream = sway_connect(socket).await?;
+    let payload = serde_json::to_string(events)?;
+    stream.write_all(&encode_sway_message(SWAY_SUBSCRIBE, &payload)).await?;
+
+    let (_, reply) = read_sway_message(&mut stream).await?;
+    if reply.get("success").and_then(|v| v.as_bool()) != Some(true) {
+        return Err(anyhow!("Sway refused the subscription to {}", payload));
+    }
+
+    Ok(stream::unfold(stream, |mut stream| async move {
+        match read_sway_message(&mut stream).await {
+            Ok(event) => Some((event, stream)),
+            Err(e) => {
+                debug!("Sway event stream ended: {}", e);
+                None
+            }
+        }
+    }))
+}
+
+async fn sway_connect(socket: &Path) -> Result<UnixStream> {
+    UnixStream::connect(socket)
+        .await
+        .with_context(|| format!("Failed to connect to Sway socket {}", socket.display()))
+}
+
+async fn read_sway_message(stream: &mut UnixStream) -> Result<(u32, serde_json::Value)> {
+    let mut header = [0u8; SWAY_HEADER_LEN];
+    stream.read_exact(&mut header).await?;
+    let (length, message_type) = decode_sway_header(&header)?;
+
+    let mut body = vec![0u8; length];
+    stream.read_exact(&mut body).await?;
+    let body = serde_json::from_slice(&body).context("Failed to parse Sway reply")?;
+    Ok((message_type, body))
+}
+
+/// Run a Sway command and fail if any part of it was rejected.
+pub async fn sway_run_command(socket: &Path, command: &str) -> Result<()> {
+    let reply = sway_message(socket, SWAY_RUN_COMMAND, command).await?;
+
+    let errors: Vec<&str> = reply
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|result| result.get("success").and_then(|v| v.as_bool()) != Some(true))
+        .map(|result| result.get("error").and_then(|v| v.as_str()).unwrap_or("unknown error"))
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Sway command '{}' failed: {}", command, errors.join(", ")))
+    }
+}
+
+fn encode_sway_message(message_type: u32, payload: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SWAY_HEADER_LEN + payload.len());
+    message.extend_from_slice(SWAY_MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&message_type.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
+/// Body length and message type of a Sway message
+fn decode_sway_header(header: &[u8; SWAY_HEADER_LEN]) -> Result<(usize, u32)> {
+    if &header[..SWAY_MAGIC.len()] != SWAY_MAGIC {
+        return Err(anyhow!("Invalid Sway IPC reply header"));
+    }
+
+    let mut length = [0u8; 4];
+    length.copy_from_slice(&header[SWAY_MAGIC.len()..SWAY_MAGIC.len() + 4]);
+    let mut message_type = [0u8; 4];
+    message_type.copy_from_slice(&header[SWAY_MAGIC.len() + 4..]);
+    Ok((u32::from_ne_bytes(length) as usize, u32::from_ne_bytes(message_type)))
+}
+
+/// Send one request to the command socket of the Hyprland instance in
+/// `instance` and return the raw reply.
+pub async fn hyprland_request(instance: &Path, request: &str) -> Result<String> {
+    let path = instance.join(".socket.sock");
+    let mut stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to Hyprland socket {}", path.display()))?;
+
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await?;
+    Ok(reply)
+}
+
+/// Run a Hyprland dispatcher and fail unless it replies `ok`.
+pub async fn hyprland_dispatch(instance: &Path, dispatcher: &str) -> Result<()> {
+    let reply = hyprland_request(instance, &format!("dispatch {}", dispatcher)).await?;
+
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(anyhow!("Hyprland dispatch '{}' failed: {}", dispatcher, reply.trim()))
+    }
+}
+
+/// Yield each `EVENT>>DATA` line Hyprland writes to its event socket
+pub async fn hyprland_events(instance: &Path) -> Result<impl Stream<Item = String>> {
+    let path = instance.join(".socket2.sock");
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("Failed to connect to Hyprland socket {}", path.display()))?;
+
+    Ok(stream::unfold(BufReader::new(stream).lines(), |mut lines| async move {
+        match lines.next_line().await {
+            Ok(Some(line)) => Some((line, lines)),
+            Ok(None) => None,
+            Err(e) => {
+                debug!("Hyprland event stream failed: {}", e);
+                None
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sway_message_round_trip() {
+        let message = encode_sway_message(SWAY_RUN_COMMAND, "[con_id=4] kill");
+
+        let header: [u8; SWAY_HEADER_LEN] = message[..SWAY_HEADER_LEN].try_into().unwrap();
+        assert_eq!(
+            decode_sway_header(&header).unwrap(),
+            ("[con_id=4] kill".len(), SWAY_RUN_COMMAND)
+        );
+        assert_eq!(&message[SWAY_HEADER_LEN..], b"[con_id=4] kill");
+    }
+
+    #[test]
+    fn test_sway_header_rejects_bad_magic() {
+        let header = [0u8; SWAY_HEADER_LEN];
+        assert!(decode_sway_header(&header).is_err());
+    }
+
+    #[test]
+    fn test_parse_niri_reply() {
+        let ok = parse_niri_reply(r#"{"Ok":{"Windows":[]}}"#).unwrap();
+        assert_eq!(ok, serde_json::json!({"Windows": []}));
+
+        let err = parse_niri_reply(r#"{"Err":"no such window"}"#).unwrap_err();
+        assert!(err.to_string().contains("no such window"));
+    }
+}
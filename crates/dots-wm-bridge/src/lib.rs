@@ -1,5 +1,6 @@
 pub mod adapters;
 pub mod detection;
+pub mod ipc;
 pub mod types;
 
 pub use adapters::*;
@@ -12,9 +13,16 @@ use anyhow::Result;
 pub trait WindowManagerAdapter {
     async fn get_focused_window(&self) -> Result<Option<WindowInfo>>;
     async fn get_all_windows(&self) -> Result<Vec<WindowInfo>>;
-    async fn subscribe_to_events(&self) -> Result<()>;
+    /// Focus, window and workspace changes as the compositor reports them
+    async fn subscribe_to_events(&self) -> Result<WMEventStream>;
     fn get_capabilities(&self) -> WMCapabilities;
     fn get_name(&self) -> &'static str;
+
+    // Window control; each returns how many windows were affected
+    async fn close_windows(&self, target: &WindowTarget) -> Result<usize>;
+    async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize>;
+    async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize>;
+    async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize>;
 }
 
 pub struct WindowManagerBridge {
@@ -23,9 +31,21 @@ pub struct WindowManagerBridge {
 }
 
 impl WindowManagerBridge {
+    /// Bridge to the compositor running the current session
     pub fn new() -> Result<Self> {
-        let compositor_type = detect_compositor();
-        let adapter = create_adapter(compositor_type)?;
+        Self::with_socket(session_socket())
+    }
+
+    /// Bridge to the compositor running `uid`'s session, for callers outside
+    /// it such as the daemon
+    pub fn for_user(uid: u32) -> Result<Self> {
+        Self::with_socket(user_socket(uid))
+    }
+
+    fn with_socket(socket: Option<CompositorSocket>) -> Result<Self> {
+        let compositor_type =
+            socket.as_ref().map(|socket| socket.compositor).unwrap_or(CompositorType::Unknown);
+        let adapter = create_adapter(socket)?;
 
         Ok(Self { adapter, compositor_type })
     }
@@ -38,6 +58,26 @@ impl WindowManagerBridge {
         self.adapter.get_all_windows().await
     }
 
+    pub async fn subscribe_to_events(&self) -> Result<WMEventStream> {
+        self.adapter.subscribe_to_events().await
+    }
+
+    pub async fn close_windows(&self, target: &WindowTarget) -> Result<usize> {
+        self.adapter.close_windows(target).await
+    }
+
+    pub async fn minimize_windows(&self, target: &WindowTarget) -> Result<usize> {
+        self.adapter.minimize_windows(target).await
+    }
+
+    pub async fn exit_fullscreen(&self, target: &WindowTarget) -> Result<usize> {
+        self.adapter.exit_fullscreen(target).await
+    }
+
+    pub async fn move_to_workspace(&self, target: &WindowTarget, workspace: &str) -> Result<usize> {
+        self.adapter.move_to_workspace(target, workspace).await
+    }
+
     pub fn get_compositor_type(&self) -> CompositorType {
         self.compositor_type
     }
@@ -52,14 +92,18 @@ impl WindowManagerBridge {
 }
 
 fn create_adapter(
-    compositor_type: CompositorType,
+    socket: Option<CompositorSocket>,
 ) -> Result<Box<dyn WindowManagerAdapter + Send + Sync>> {
     use adapters::*;
 
-    match compositor_type {
-        CompositorType::Niri => Ok(Box::new(NiriAdapter::new())),
-        CompositorType::Sway => Ok(Box::new(SwayAdapter::new())),
-        CompositorType::Hyprland => Ok(Box::new(HyprlandAdapter::new())),
+    let Some(CompositorSocket { compositor, path }) = socket else {
+        return Ok(Box::new(GenericAdapter::new()));
+    };
+
+    match compositor {
+        CompositorType::Niri => Ok(Box::new(NiriAdapter::with_socket(path))),
+        CompositorType::Sway => Ok(Box::new(SwayAdapter::with_socket(path))),
+        CompositorType::Hyprland => Ok(Box::new(HyprlandAdapter::with_socket(path))),
         CompositorType::Unknown => Ok(Box::new(GenericAdapter::new())),
     }
 }
@@ -1,3 +1,6 @@
+use std::pin::Pin;
+
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -29,6 +32,38 @@ pub enum WindowState {
     Tiled,
 }
 
+/// Which windows a control operation applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowTarget {
+    /// Every window owned by this process
+    Pid(u32),
+    /// Every window with this app ID (Hyprland: window class)
+    AppId(String),
+    /// Every open window
+    All,
+}
+
+impl WindowTarget {
+    /// Whether a window with the given pid and app ID is targeted
+    pub fn matches(&self, pid: Option<u64>, app_id: Option<&str>) -> bool {
+        match self {
+            WindowTarget::Pid(target) => pid == Some(u64::from(*target)),
+            WindowTarget::AppId(target) => app_id == Some(target.as_str()),
+            WindowTarget::All => true,
+        }
+    }
+}
+
+impl std::fmt::Display for WindowTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowTarget::Pid(pid) => write!(f, "pid {}", pid),
+            WindowTarget::AppId(app_id) => write!(f, "app {}", app_id),
+            WindowTarget::All => write!(f, "all windows"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CompositorType {
     Niri,
@@ -53,7 +88,10 @@ pub struct WMCapabilities {
     pub can_get_focused_window: bool,
     pub can_get_all_windows: bool,
     pub can_subscribe_to_events: bool,
-    pub can_control_windows: bool,
+    pub can_close_windows: bool,
+    pub can_minimize_windows: bool,
+    pub can_exit_fullscreen: bool,
+    pub can_move_to_workspace: bool,
     pub supports_workspaces: bool,
     pub supports_window_geometry: bool,
 }
@@ -64,7 +102,10 @@ impl WMCapabilities {
             can_get_focused_window: true,
             can_get_all_windows: true,
             can_subscribe_to_events: true,
-            can_control_windows: true,
+            can_close_windows: true,
+            can_minimize_windows: true,
+            can_exit_fullscreen: true,
+            can_move_to_workspace: true,
             supports_workspaces: true,
             supports_window_geometry: true,
         }
@@ -75,7 +116,10 @@ impl WMCapabilities {
             can_get_focused_window: true,
             can_get_all_windows: false,
             can_subscribe_to_events: false,
-            can_control_windows: false,
+            can_close_windows: false,
+            can_minimize_windows: false,
+            can_exit_fullscreen: false,
+            can_move_to_workspace: false,
             supports_workspaces: false,
             supports_window_geometry: false,
         }
@@ -86,17 +130,25 @@ impl WMCapabilities {
             can_get_focused_window: false,
             can_get_all_windows: false,
             can_subscribe_to_events: false,
-            can_control_windows: false,
+            can_close_windows: false,
+            can_minimize_windows: false,
+            can_exit_fullscreen: false,
+            can_move_to_workspace: false,
             supports_workspaces: false,
             supports_window_geometry: false,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum WMEvent {
     WindowOpened(WindowInfo),
-    WindowClosed(u32),
+    /// Compositor's id of the window (Hyprland: its address)
+    WindowClosed(u64),
+    /// Focus moved, or the focused window changed its title; `None` when nothing has focus
     WindowFocused(Option<WindowInfo>),
     WorkspaceChanged(String),
 }
+
+/// Events from a compositor subscription; ends when the compositor connection closes
+pub type WMEventStream = Pin<Box<dyn Stream<Item = WMEvent> + Send>>;
@@ -55,83 +55,13 @@ in {
               <allow send_interface="org.dots.FamilyDaemon"/>
             </policy>
 
-            <!-- Users in the dots-family group can use most methods -->
-            <policy group="dots-family">
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="get_active_profile"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="check_application_allowed"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="get_remaining_time"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="list_profiles"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="validate_session"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="request_parent_permission"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="request_command_approval"/>
-              
-              <!-- Receive signals from daemon -->
-              <allow receive_sender="org.dots.FamilyDaemon"
-                     receive_interface="org.dots.FamilyDaemon"
-                     receive_member="policy_updated"/>
-              <allow receive_sender="org.dots.FamilyDaemon"
-                     receive_interface="org.dots.FamilyDaemon"
-                     receive_member="time_limit_warning"/>
-              <allow receive_sender="org.dots.FamilyDaemon"
-                     receive_interface="org.dots.FamilyDaemon"
-                     receive_member="tamper_detected"/>
-            </policy>
-
-            <!-- Parent users (in dots-parents group) have administrative privileges -->
-            <policy group="dots-parents">
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="authenticate_parent"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="create_profile"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="set_active_profile"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="revoke_session"/>
-              
-              <!-- Also inherit regular user permissions -->
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="get_active_profile"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="check_application_allowed"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="get_remaining_time"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="list_profiles"/>
-              <allow send_destination="org.dots.FamilyDaemon"
-                     send_interface="org.dots.FamilyDaemon"
-                     send_member="validate_session"/>
-              
-              <!-- Receive all signals -->
-              <allow receive_sender="org.dots.FamilyDaemon"
-                     receive_interface="org.dots.FamilyDaemon"/>
-            </policy>
-
-            <!-- Default deny policy -->
+            <!-- Anyone may call the daemon and receive its signals. Every
+                 method checks its caller in the daemon (by uid, group,
+                 polkit or session token), so the bus does not filter by
+                 member name. -->
             <policy context="default">
-              <deny send_destination="org.dots.FamilyDaemon"/>
-              <deny receive_sender="org.dots.FamilyDaemon"/>
+              <allow send_destination="org.dots.FamilyDaemon"/>
+              <allow receive_sender="org.dots.FamilyDaemon"/>
             </policy>
           </busconfig>
         '';
@@ -112,6 +112,61 @@ in {
             <allow_active>auth_admin</allow_active>
           </defaults>
         </action>
+        
+        <action id="org.dots.family.manage-profiles">
+          <description>Manage family profiles</description>
+          <description xml:lang="en">Create profiles, switch the active profile and edit time windows</description>
+          <message>Authentication is required to manage family profiles</message>
+          <defaults>
+            <allow_any>no</allow_any>
+            <allow_inactive>no</allow_inactive>
+            <allow_active>auth_admin</allow_active>
+          </defaults>
+        </action>
+        
+        <action id="org.dots.family.view-activity">
+          <description>View family activity</description>
+          <description xml:lang="en">View activity reports, exceptions and pending requests</description>
+          <message>Authentication is required to view family activity</message>
+          <defaults>
+            <allow_any>no</allow_any>
+            <allow_inactive>no</allow_inactive>
+            <allow_active>auth_admin</allow_active>
+          </defaults>
+        </action>
+        
+        <action id="org.dots.family.approve-requests">
+          <description>Approve family requests</description>
+          <description xml:lang="en">Approve or deny requests, grant exceptions and time credit</description>
+          <message>Authentication is required to approve family requests</message>
+          <defaults>
+            <allow_any>no</allow_any>
+            <allow_inactive>no</allow_inactive>
+            <allow_active>auth_admin</allow_active>
+          </defaults>
+        </action>
+        
+        <action id="org.dots.family.manage-rules">
+          <description>Manage auto-approval rules</description>
+          <description xml:lang="en">Add, enable and remove auto-approval rules</description>
+          <message>Authentication is required to manage auto-approval rules</message>
+          <defaults>
+            <allow_any>no</allow_any>
+            <allow_inactive>no</allow_inactive>
+            <allow_active>auth_admin</allow_active>
+          </defaults>
+        </action>
+        
+        <action id="org.dots.family.manage-system">
+          <description>Manage family mode system settings</description>
+          <description xml:lang="en">Manage guardians, tamper alerts and the audit log</description>
+          <message>Authentication is required to manage family mode</message>
+          <defaults>
+            <allow_any>no</allow_any>
+            <allow_inactive>no</allow_inactive>
+            <allow_active>auth_admin</allow_active>
+          </defaults>
+        </action>
       </policyconfig>
     '';
 
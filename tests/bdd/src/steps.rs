@@ -92,7 +92,7 @@ fn create_config(world: &TimeWindowWorld, username: Option<&str>) -> TimeWindowC
         weekend_windows,
         holiday_windows,
         grace_period_minutes: world.grace_period_minutes.unwrap_or(2),
-        warning_minutes: 5,
+        ..TimeWindowConfig::default()
     }
 }
 